@@ -0,0 +1,65 @@
+use bevy_lookup_curve::{Knot, KnotInterpolation, LookupCurve};
+use bevy_math::Vec2;
+use criterion::BenchmarkId;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn generate_knots(n: usize) -> Vec<Knot> {
+    let step = 1. / (n - 1) as f32;
+    (0..n)
+        .map(|i| Knot {
+            position: Vec2::new(step * i as f32, (i % 2) as f32),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Sorted `Vec` of knots, like the storage used before small curves were stored inline
+fn vec_baseline(knots: &[Knot]) -> Vec<Knot> {
+    let mut knots = knots.to_vec();
+    knots.sort_by(|a, b| a.position.x.partial_cmp(&b.position.x).unwrap());
+    knots
+}
+
+pub fn construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Construction");
+    for n in [2, 3, 4, 6, 100] {
+        let knots = generate_knots(n);
+        group.bench_with_input(BenchmarkId::new("Vec baseline", n), &knots, |b, knots| {
+            b.iter(|| vec_baseline(black_box(knots)))
+        });
+        group.bench_with_input(BenchmarkId::new("from iterator", n), &knots, |b, knots| {
+            b.iter(|| LookupCurve::from_iter(black_box(knots).iter().copied()))
+        });
+        group.bench_with_input(BenchmarkId::new("new", n), &knots, |b, knots| {
+            b.iter(|| LookupCurve::new(black_box(knots).clone()))
+        });
+    }
+
+    let [a, b, c] = generate_knots(3).try_into().unwrap();
+    group.bench_function(BenchmarkId::new("from array", 3), |bench| {
+        bench.iter(|| LookupCurve::from(black_box([a, b, c])))
+    });
+    group.finish();
+}
+
+pub fn clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Clone");
+    for n in [3, 100] {
+        let knots = generate_knots(n);
+        let baseline = vec_baseline(&knots);
+        let curve = LookupCurve::new(knots);
+        group.bench_with_input(
+            BenchmarkId::new("Vec baseline", n),
+            &baseline,
+            |b, knots| b.iter(|| black_box(knots).clone()),
+        );
+        group.bench_with_input(BenchmarkId::new("LookupCurve", n), &curve, |b, curve| {
+            b.iter(|| black_box(curve).clone())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, construction, clone);
+criterion_main!(benches);
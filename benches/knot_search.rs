@@ -1,4 +1,4 @@
-use bevy_lookup_curve::knot_search::KnotSearch;
+use bevy_lookup_curve::sample::KnotSearch;
 use bevy_lookup_curve::Knot;
 use bevy_math::Vec2;
 use criterion::BenchmarkId;
@@ -0,0 +1,97 @@
+use bevy_lookup_curve::{Knot, KnotInterpolation, LookupCurve, TangentSide};
+use bevy_math::Vec2;
+use criterion::BenchmarkId;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn generate_curve(n: usize, weighted: bool) -> LookupCurve {
+    let step = 1. / (n - 1) as f32;
+    LookupCurve::new(
+        (0..n)
+            .map(|i| {
+                let knot = Knot {
+                    position: Vec2::new(step * i as f32, (i % 2) as f32),
+                    interpolation: KnotInterpolation::Cubic,
+                    ..Default::default()
+                };
+                if weighted {
+                    knot.with_tangent_weight(TangentSide::Right, Some(0.5))
+                } else {
+                    knot
+                }
+            })
+            .collect(),
+    )
+}
+
+pub fn sampling(c: &mut Criterion) {
+    let samples: Vec<f32> = (1..999).map(|i| i as f32 / 1000.).collect();
+    for weighted in [false, true] {
+        let name = if weighted {
+            "Sampling (weighted cubic)"
+        } else {
+            "Sampling (cubic)"
+        };
+        let mut group = c.benchmark_group(name);
+        for n in [3, 10, 100] {
+            let curve = generate_curve(n, weighted);
+            let precomputed = curve.precompute();
+            let baked = curve.bake(1024);
+            group.bench_with_input(BenchmarkId::new("lookup", n), &n, |b, _| {
+                b.iter(|| {
+                    samples
+                        .iter()
+                        .map(|x| curve.lookup(black_box(*x)))
+                        .sum::<f32>()
+                })
+            });
+            group.bench_with_input(BenchmarkId::new("precomputed", n), &n, |b, _| {
+                b.iter(|| {
+                    samples
+                        .iter()
+                        .map(|x| precomputed.lookup(black_box(*x)))
+                        .sum::<f32>()
+                })
+            });
+            group.bench_with_input(BenchmarkId::new("baked", n), &n, |b, _| {
+                b.iter(|| {
+                    samples
+                        .iter()
+                        .map(|x| baked.sample(black_box(*x)))
+                        .sum::<f32>()
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+pub fn fill_buffer(c: &mut Criterion) {
+    const BUFFER: usize = 512;
+    let mut out = [0.0; BUFFER];
+    let dx = 1. / BUFFER as f32;
+    for weighted in [false, true] {
+        let name = if weighted {
+            "Buffer fill (weighted cubic)"
+        } else {
+            "Buffer fill (cubic)"
+        };
+        let mut group = c.benchmark_group(name);
+        for n in [3, 10, 100] {
+            let curve = generate_curve(n, weighted);
+            group.bench_with_input(BenchmarkId::new("lookup per sample", n), &n, |b, _| {
+                b.iter(|| {
+                    out.iter_mut().enumerate().for_each(|(i, y)| {
+                        *y = curve.lookup(black_box(i as f32 * dx));
+                    })
+                })
+            });
+            group.bench_with_input(BenchmarkId::new("fill_buffer", n), &n, |b, _| {
+                b.iter(|| curve.fill_buffer(black_box(0.), black_box(dx), &mut out))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, sampling, fill_buffer);
+criterion_main!(benches);
@@ -0,0 +1,135 @@
+//! Drives the cutoff of a low-pass filter with a lookup curve, filling a buffer of cutoff values for each block of
+//! audio samples with `LookupCurve::fill_buffer`.
+
+use bevy::audio::{AddAudioSource, Source};
+use bevy::prelude::*;
+
+use bevy_lookup_curve::{Knot, KnotInterpolation, LookupCurve};
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Samples per block, the cutoff is filled for a whole block at once
+const BLOCK: usize = 512;
+
+/// A sawtooth wave through a low-pass filter, looping the cutoff curve
+#[derive(Asset, TypePath)]
+struct FilterSweep {
+    frequency: f32,
+    /// Cutoff in Hz over time in seconds
+    cutoff: LookupCurve,
+}
+
+struct FilterSweepDecoder {
+    cutoff: LookupCurve,
+    /// Time of the last knot of the cutoff curve, where it loops
+    duration: f32,
+    phase: f32,
+    phase_step: f32,
+    filtered: f32,
+    /// Time of the first sample of the block in seconds
+    block_start: f32,
+    /// Cutoff of each sample of the block
+    block: [f32; BLOCK],
+    index: usize,
+}
+
+impl FilterSweepDecoder {
+    fn fill_block(&mut self) {
+        self.cutoff
+            .fill_buffer(self.block_start, 1.0 / SAMPLE_RATE as f32, &mut self.block);
+        self.index = 0;
+    }
+}
+
+impl Iterator for FilterSweepDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.index == BLOCK {
+            self.block_start =
+                (self.block_start + BLOCK as f32 / SAMPLE_RATE as f32) % self.duration;
+            self.fill_block();
+        }
+        let cutoff = self.block[self.index];
+        self.index += 1;
+
+        let saw = self.phase * 2.0 - 1.0;
+        self.phase = (self.phase + self.phase_step).fract();
+        // One pole low-pass filter
+        let alpha = 1.0 - (-std::f32::consts::TAU * cutoff / SAMPLE_RATE as f32).exp();
+        self.filtered += alpha * (saw - self.filtered);
+        Some(self.filtered * 0.2)
+    }
+}
+
+impl Source for FilterSweepDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        None
+    }
+}
+
+impl Decodable for FilterSweep {
+    type DecoderItem = f32;
+    type Decoder = FilterSweepDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let mut decoder = FilterSweepDecoder {
+            cutoff: self.cutoff.clone(),
+            duration: self
+                .cutoff
+                .knots()
+                .last()
+                .map_or(1.0, |knot| knot.position.x),
+            phase: 0.0,
+            phase_step: self.frequency / SAMPLE_RATE as f32,
+            filtered: 0.0,
+            block_start: 0.0,
+            block: [0.0; BLOCK],
+            index: 0,
+        };
+        decoder.fill_block();
+        decoder
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_audio_source::<FilterSweep>()
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut sweeps: ResMut<Assets<FilterSweep>>) {
+    // Opens up slowly, closes quickly, and rests before looping after 4 seconds
+    let knot = |x: f32, y: f32| Knot {
+        position: Vec2::new(x, y),
+        interpolation: KnotInterpolation::Cubic,
+        ..default()
+    };
+    let cutoff = LookupCurve::new(vec![
+        knot(0.0, 200.0),
+        knot(2.5, 4000.0),
+        knot(3.0, 400.0),
+        knot(4.0, 200.0),
+    ]);
+
+    commands.spawn(AudioSourceBundle {
+        source: sweeps.add(FilterSweep {
+            frequency: 110.0,
+            cutoff,
+        }),
+        ..default()
+    });
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::{AssetInspectorPlugin, ResourceInspectorPlugin};
 
-use bevy_lookup_curve::{editor::LookupCurveEditor, LookupCurve, LookupCurvePlugin};
+use bevy_lookup_curve::prelude::*;
 
 fn main() {
     App::new()
@@ -27,13 +27,10 @@ struct LookupCurveDevState {
 fn setup(mut commands: Commands, assets: Res<AssetServer>) {
     let handle = assets.load("example.curve.ron");
 
-    commands.spawn(LookupCurveEditor {
-        sample: Some(0.0),
-        ..LookupCurveEditor::with_save_path(
-            handle.clone(),
-            "./assets/example.curve.ron".to_string(),
-        )
-    });
+    let mut editor =
+        LookupCurveEditor::with_save_path(handle.clone(), "./assets/example.curve.ron".to_string());
+    editor.sample = Some(0.0);
+    commands.spawn(editor);
 
     commands.insert_resource(LookupCurveDevState {
         curve_handle: handle,
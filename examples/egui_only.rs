@@ -1,4 +1,4 @@
-use bevy_lookup_curve::{editor::LookupCurveEguiEditor, LookupCurve};
+use bevy_lookup_curve::prelude::*;
 use eframe::egui;
 
 /// This example has no dependencies on bevy except bevy_math
@@ -18,6 +18,7 @@ fn main() -> eframe::Result {
             Ok(Box::new(MyApp {
                 lookup_curve,
                 editor: LookupCurveEguiEditor::with_save_path(path.to_string()),
+                dirty: false,
             }))
         }),
     )
@@ -26,11 +27,22 @@ fn main() -> eframe::Result {
 struct MyApp {
     lookup_curve: LookupCurve,
     editor: LookupCurveEguiEditor,
+    /// Whether the curve has unsaved changes, as of the last frame
+    dirty: bool,
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default()
-            .show(ctx, |ui| self.editor.ui(ui, &mut self.lookup_curve, None));
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+            ui.label(if self.dirty {
+                "Unsaved changes"
+            } else {
+                "Saved"
+            });
+        });
+        // The editor is a widget filling the panel, it can be placed in any egui layout
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.dirty = self.editor.ui(ui, &mut self.lookup_curve, None).dirty;
+        });
     }
 }
@@ -0,0 +1,735 @@
+//! A gallery of small demos, each showing a feature of the crate with curves built in code. Pick a demo from the list
+//! on the left. The demos only use the public API, so the gallery also checks that the API is enough for real use.
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use rand::Rng;
+
+use bevy_lookup_curve::prelude::*;
+use bevy_lookup_curve::sample::BakedLookupCurve;
+use bevy_lookup_curve::stats::CurveStats;
+use bevy_lookup_curve::SmoothPolicy;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .init_resource::<Gallery>()
+        .add_systems(Startup, setup)
+        .add_systems(Update, gallery_ui)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+/// A self-contained demo of the gallery
+trait Demo: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Shown above the demo
+    fn description(&self) -> &'static str;
+    /// Shows and advances the demo by `dt`
+    fn ui(&mut self, ui: &mut egui::Ui, dt: Duration);
+}
+
+#[derive(Resource)]
+struct Gallery {
+    demos: Vec<Box<dyn Demo>>,
+    selected: usize,
+}
+
+impl Default for Gallery {
+    fn default() -> Self {
+        Self {
+            demos: vec![
+                Box::new(BasicSampling::new()),
+                Box::new(EmbeddedEditor::new()),
+                Box::new(ExtrapolationModes::new()),
+                Box::new(BakedVsAnalytic::new()),
+                Box::new(TweenDemo::new()),
+                Box::new(MinMaxParticles::new()),
+                Box::new(InverseLookup::new()),
+                Box::new(Validation::new()),
+            ],
+            selected: 0,
+        }
+    }
+}
+
+fn gallery_ui(mut contexts: EguiContexts, mut gallery: ResMut<Gallery>, time: Res<Time>) {
+    let Gallery { demos, selected } = &mut *gallery;
+    let ctx = contexts.ctx_mut();
+
+    egui::SidePanel::left("demos").show(ctx, |ui| {
+        ui.heading("Demos");
+        for (i, demo) in demos.iter().enumerate() {
+            if ui.selectable_label(*selected == i, demo.name()).clicked() {
+                *selected = i;
+            }
+        }
+    });
+
+    let demo = &mut demos[*selected];
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.heading(demo.name());
+        ui.label(demo.description());
+        ui.separator();
+        demo.ui(ui, time.delta());
+    });
+    // The demos animate
+    ctx.request_repaint();
+}
+
+fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+    Knot {
+        position: Vec2::new(x, y),
+        interpolation,
+        ..default()
+    }
+}
+
+fn cubic(points: &[(f32, f32)]) -> LookupCurve {
+    LookupCurve::new(
+        points
+            .iter()
+            .map(|&(x, y)| knot(x, y, KnotInterpolation::Cubic))
+            .collect(),
+    )
+}
+
+/// Number of points a curve is drawn with
+const PLOT_POINTS: usize = 200;
+
+/// Area of the screen showing curve space from `min` to `max`
+struct Plot {
+    rect: egui::Rect,
+    min: Vec2,
+    max: Vec2,
+    /// Position under the pointer in curve space
+    hover: Option<Vec2>,
+}
+
+impl Plot {
+    /// Allocates a plot of `height` spanning the available width
+    fn new(ui: &mut egui::Ui, height: f32, min: Vec2, max: Vec2) -> (Self, egui::Painter) {
+        let (response, painter) = ui.allocate_painter(
+            egui::vec2(ui.available_width(), height),
+            egui::Sense::hover(),
+        );
+        painter.rect_filled(response.rect, 2.0, ui.visuals().extreme_bg_color);
+        let mut plot = Self {
+            rect: response.rect,
+            min,
+            max,
+            hover: None,
+        };
+        plot.hover = response.hover_pos().map(|pos| plot.to_curve(pos));
+        (plot, painter)
+    }
+
+    /// Fits the domain and value range of `curves`, with some space around them
+    fn bounds(curves: &[&LookupCurve]) -> (Vec2, Vec2) {
+        let mut min = Vec2::splat(f32::INFINITY);
+        let mut max = Vec2::splat(f32::NEG_INFINITY);
+        for curve in curves {
+            if let (Some((x0, x1)), Some((y0, y1))) = (curve.domain(), curve.value_range()) {
+                min = min.min(Vec2::new(x0, y0));
+                max = max.max(Vec2::new(x1, y1));
+            }
+        }
+        let margin = ((max - min) * 0.1).max(Vec2::splat(0.05));
+        (min - margin, max + margin)
+    }
+
+    fn to_screen(&self, position: Vec2) -> egui::Pos2 {
+        let t = (position - self.min) / (self.max - self.min);
+        egui::pos2(
+            self.rect.left() + t.x * self.rect.width(),
+            self.rect.bottom() - t.y * self.rect.height(),
+        )
+    }
+
+    fn to_curve(&self, pos: egui::Pos2) -> Vec2 {
+        let t = Vec2::new(
+            (pos.x - self.rect.left()) / self.rect.width(),
+            (self.rect.bottom() - pos.y) / self.rect.height(),
+        );
+        self.min + t * (self.max - self.min)
+    }
+
+    fn curve(&self, painter: &egui::Painter, curve: &dyn SampleCurve, color: egui::Color32) {
+        let points = (0..=PLOT_POINTS)
+            .map(|i| {
+                let x = self.min.x + (self.max.x - self.min.x) * i as f32 / PLOT_POINTS as f32;
+                self.to_screen(Vec2::new(x, curve.sample(x)))
+            })
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(2.0, color)));
+    }
+
+    fn point(&self, painter: &egui::Painter, position: Vec2, color: egui::Color32) {
+        painter.circle_filled(self.to_screen(position), 4.0, color);
+    }
+
+    fn vline(&self, painter: &egui::Painter, x: f32, color: egui::Color32) {
+        let top = self.to_screen(Vec2::new(x, self.max.y));
+        painter.vline(top.x, self.rect.y_range(), egui::Stroke::new(1.0, color));
+    }
+
+    fn hline(&self, painter: &egui::Painter, y: f32, color: egui::Color32) {
+        let left = self.to_screen(Vec2::new(self.min.x, y));
+        painter.hline(self.rect.x_range(), left.y, egui::Stroke::new(1.0, color));
+    }
+}
+
+struct BasicSampling {
+    curve: LookupCurve,
+    cache: LookupCache,
+    t: f32,
+}
+
+impl BasicSampling {
+    fn new() -> Self {
+        Self {
+            curve: cubic(&[(0.0, 0.0), (2.0, 3.0), (5.0, 1.0), (8.0, 4.0)]),
+            cache: LookupCache::new(),
+            t: 0.3,
+        }
+    }
+}
+
+impl Demo for BasicSampling {
+    fn name(&self) -> &'static str {
+        "Basic sampling"
+    }
+
+    fn description(&self) -> &'static str {
+        "Sample a curve at x with `lookup`, or at t from 0 to 1 over its knots with `sample_normalized`. \
+         `lookup_cached` remembers the segment of the last lookup, which speeds up sampling nearby x."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _dt: Duration) {
+        ui.add(egui::Slider::new(&mut self.t, 0.0..=1.0).text("t"));
+        let x = self.curve.normalized_x(self.t).unwrap_or_default();
+        let y = self.curve.lookup_cached(x, &mut self.cache);
+        ui.label(format!("x = {x:.3}"));
+        ui.label(format!("lookup(x) = {:.3}", self.curve.lookup(x)));
+        ui.label(format!("lookup_cached(x) = {y:.3}"));
+        ui.label(format!(
+            "sample_normalized(t) = {:.3}",
+            self.curve.sample_normalized(self.t)
+        ));
+        ui.label(format!(
+            "slope_at(x) = {:.3}",
+            self.curve.slope_at(x, TangentSide::Right)
+        ));
+
+        let (min, max) = Plot::bounds(&[&self.curve]);
+        let (plot, painter) = Plot::new(ui, 300.0, min, max);
+        plot.curve(&painter, &self.curve, egui::Color32::WHITE);
+        for knot in self.curve.knots() {
+            plot.point(&painter, knot.position, egui::Color32::GRAY);
+        }
+        plot.point(&painter, Vec2::new(x, y), egui::Color32::RED);
+    }
+}
+
+struct EmbeddedEditor {
+    curve: LookupCurve,
+    editor: LookupCurveEguiEditor,
+    sample: f32,
+    playing: bool,
+}
+
+impl EmbeddedEditor {
+    fn new() -> Self {
+        let curve = cubic(&[(0.0, 0.0), (0.4, 0.8), (1.0, 1.0)]).with_name("Embedded");
+        Self {
+            editor: LookupCurveEguiEditor::fitted_to_curve(&curve),
+            curve,
+            sample: 0.0,
+            playing: true,
+        }
+    }
+}
+
+impl Demo for EmbeddedEditor {
+    fn name(&self) -> &'static str {
+        "Editor in a custom panel"
+    }
+
+    fn description(&self) -> &'static str {
+        "`LookupCurveEguiEditor::ui` draws the editor into any egui ui, here next to controls of the app. \
+         Drag knots and tangents, right click for more options."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, dt: Duration) {
+        if self.playing {
+            self.sample = (self.sample + dt.as_secs_f32() * 0.5) % 1.0;
+        }
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                ui.set_width(220.0);
+                ui.checkbox(&mut self.playing, "Move sample");
+                ui.add(egui::Slider::new(&mut self.sample, 0.0..=1.0).text("sample"));
+                ui.label(format!("y = {:.3}", self.curve.lookup(self.sample)));
+                ui.separator();
+                let stats = CurveStats::compute(&self.curve);
+                ui.label(format!("{} knots", stats.knot_count));
+                if let Some((min, max)) = stats.range {
+                    ui.label(format!("y from {min:.3} to {max:.3}"));
+                }
+                ui.label(format!("{:?}", stats.monotonicity));
+                if ui.button("Reset").clicked() {
+                    *self = Self::new();
+                }
+            });
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                let size = egui::vec2(ui.available_width(), 400.0);
+                ui.allocate_ui(size, |ui| {
+                    self.editor.ui(ui, &mut self.curve, Some(self.sample));
+                });
+            });
+        });
+    }
+}
+
+struct ExtrapolationModes {
+    curves: Vec<LookupCurve>,
+}
+
+impl ExtrapolationModes {
+    fn new() -> Self {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Cubic),
+            knot(0.5, 1.0, KnotInterpolation::Linear),
+            knot(1.0, 0.5, KnotInterpolation::Linear),
+        ]);
+        Self {
+            curves: Extrapolation::ALL
+                .into_iter()
+                .map(|mode| curve.clone().with_extrapolation(mode, mode))
+                .collect(),
+        }
+    }
+}
+
+impl Demo for ExtrapolationModes {
+    fn name(&self) -> &'static str {
+        "Extrapolation"
+    }
+
+    fn description(&self) -> &'static str {
+        "How a curve continues before its first and after its last knot, set with `extrapolate_before` and \
+         `extrapolate_after`. The gray lines mark the knot range."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _dt: Duration) {
+        let (min, max) = (Vec2::new(-1.5, -1.0), Vec2::new(2.5, 2.0));
+        ui.columns(2, |columns| {
+            for (i, curve) in self.curves.iter().enumerate() {
+                let ui = &mut columns[i % 2];
+                ui.label(format!("{:?}", curve.extrapolate_before));
+                let (plot, painter) = Plot::new(ui, 180.0, min, max);
+                plot.vline(&painter, 0.0, egui::Color32::GRAY);
+                plot.vline(&painter, 1.0, egui::Color32::GRAY);
+                plot.curve(&painter, curve, egui::Color32::WHITE);
+            }
+        });
+    }
+}
+
+struct BakedVsAnalytic {
+    curve: LookupCurve,
+    resolution: usize,
+    baked: BakedLookupCurve,
+    bake_error: f32,
+}
+
+impl BakedVsAnalytic {
+    fn new() -> Self {
+        let curve = cubic(&[(0.0, 0.0), (0.2, 1.0), (0.35, -0.5), (0.6, 0.8), (1.0, 0.0)]);
+        let resolution = 8;
+        Self {
+            baked: curve.bake(resolution),
+            bake_error: curve.bake_error(resolution),
+            curve,
+            resolution,
+        }
+    }
+}
+
+impl Demo for BakedVsAnalytic {
+    fn name(&self) -> &'static str {
+        "Baked vs analytic"
+    }
+
+    fn description(&self) -> &'static str {
+        "`bake` samples the curve at evenly spaced x, for faster lookups at the cost of accuracy. \
+         Hover the plot to compare the baked curve (orange) to the exact curve (white)."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _dt: Duration) {
+        if ui
+            .add(egui::Slider::new(&mut self.resolution, 2..=128).text("resolution"))
+            .changed()
+        {
+            self.baked = self.curve.bake(self.resolution);
+            self.bake_error = self.curve.bake_error(self.resolution);
+        }
+        ui.label(format!(
+            "Max error {:.4}, {} bytes",
+            self.bake_error,
+            self.baked.memory()
+        ));
+        if let Some(resolution) = self.curve.min_bake_resolution(0.01) {
+            ui.label(format!(
+                "Resolution for an error of at most 0.01: {resolution}"
+            ));
+        }
+
+        let (min, max) = Plot::bounds(&[&self.curve]);
+        let (plot, painter) = Plot::new(ui, 300.0, min, max);
+        plot.curve(&painter, &self.curve, egui::Color32::WHITE);
+        plot.curve(&painter, &self.baked, egui::Color32::from_rgb(255, 160, 0));
+        match plot.hover {
+            Some(hover) => {
+                let (exact, baked) = (self.curve.lookup(hover.x), self.baked.sample(hover.x));
+                plot.vline(&painter, hover.x, egui::Color32::GRAY);
+                ui.label(format!(
+                    "At x = {:.3}: exact {exact:.4}, baked {baked:.4}, error {:.4}",
+                    hover.x,
+                    (exact - baked).abs()
+                ));
+            }
+            None => {
+                ui.label("Hover the plot to compare the curves at a point");
+            }
+        }
+    }
+}
+
+struct TweenDemo {
+    tween: CurveTween<f32>,
+    paused: bool,
+}
+
+impl TweenDemo {
+    fn new() -> Self {
+        let curve = cubic(&[(0.0, 0.0), (0.7, 1.1), (1.0, 1.0)]);
+        Self {
+            tween: CurveTween::new(0.0, 1.0, Duration::from_secs(2), curve)
+                .with_repeat(TweenRepeat::PingPong),
+            paused: false,
+        }
+    }
+}
+
+impl Demo for TweenDemo {
+    fn name(&self) -> &'static str {
+        "Tween"
+    }
+
+    fn description(&self) -> &'static str {
+        "`CurveTween` eases a value from one end to the other with a curve, here with a slight overshoot. \
+         With the `tween_bevy` feature, `LookupCurveTween` components do this for curve assets."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, dt: Duration) {
+        if !self.paused {
+            self.tween.tick(dt);
+        }
+        ui.horizontal(|ui| {
+            for (repeat, label) in [
+                (TweenRepeat::Once, "Once"),
+                (TweenRepeat::Loop, "Loop"),
+                (TweenRepeat::PingPong, "Ping-pong"),
+            ] {
+                if ui
+                    .radio(self.tween.timer.repeat() == repeat, label)
+                    .clicked()
+                {
+                    self.tween = self.tween.clone().with_repeat(repeat);
+                }
+            }
+            ui.checkbox(&mut self.paused, "Paused");
+            if ui.button("Restart").clicked() {
+                self.tween.reset();
+            }
+            if ui.button("Reverse").clicked() {
+                self.tween.reverse();
+            }
+        });
+
+        let progress = self.tween.timer.progress();
+        let value = self.tween.value();
+        ui.label(format!("progress {progress:.2}, value {value:.3}"));
+
+        let (track, painter) = Plot::new(ui, 60.0, Vec2::new(-0.2, 0.0), Vec2::new(1.2, 1.0));
+        track.hline(&painter, 0.5, egui::Color32::GRAY);
+        painter.circle_filled(
+            track.to_screen(Vec2::new(value, 0.5)),
+            12.0,
+            egui::Color32::from_rgb(80, 160, 255),
+        );
+
+        let curve = &self.tween.curve;
+        let (min, max) = Plot::bounds(&[curve]);
+        let (plot, painter) = Plot::new(ui, 240.0, min, max);
+        plot.curve(&painter, curve, egui::Color32::WHITE);
+        let x = curve.normalized_x(progress).unwrap_or_default();
+        plot.point(&painter, Vec2::new(x, curve.lookup(x)), egui::Color32::RED);
+    }
+}
+
+/// Lifetime of the particles in seconds
+const PARTICLE_LIFETIME: f32 = 2.0;
+
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    /// Seconds since the particle was spawned
+    age: f32,
+    /// Where the size of the particle is between the lower and upper curve
+    blend: f32,
+}
+
+struct MinMaxParticles {
+    lower: LookupCurve,
+    upper: LookupCurve,
+    particles: Vec<Particle>,
+    spawn_timer: f32,
+}
+
+impl MinMaxParticles {
+    fn new() -> Self {
+        Self {
+            lower: cubic(&[(0.0, 2.0), (0.3, 6.0), (1.0, 0.0)]),
+            upper: cubic(&[(0.0, 6.0), (0.5, 16.0), (1.0, 4.0)]),
+            particles: Vec::new(),
+            spawn_timer: 0.0,
+        }
+    }
+}
+
+impl Demo for MinMaxParticles {
+    fn name(&self) -> &'static str {
+        "Min/max particles"
+    }
+
+    fn description(&self) -> &'static str {
+        "Each particle gets a random size between two curves over its lifetime, like the min/max curves of \
+         particle systems. Both curves are sampled with the normalized age of the particle."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        let mut rng = rand::thread_rng();
+        self.spawn_timer += dt;
+        while self.spawn_timer > 0.02 {
+            self.spawn_timer -= 0.02;
+            self.particles.push(Particle {
+                position: Vec2::ZERO,
+                velocity: Vec2::new(rng.gen_range(-0.15..0.15), rng.gen_range(0.3..0.5)),
+                age: 0.0,
+                blend: rng.gen(),
+            });
+        }
+        for particle in &mut self.particles {
+            particle.age += dt;
+            particle.position += particle.velocity * dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < PARTICLE_LIFETIME);
+
+        let (min, max) = Plot::bounds(&[&self.lower, &self.upper]);
+        let (plot, painter) = Plot::new(ui, 160.0, min, max);
+        plot.curve(&painter, &self.lower, egui::Color32::LIGHT_BLUE);
+        plot.curve(&painter, &self.upper, egui::Color32::LIGHT_RED);
+
+        let (area, painter) = Plot::new(ui, 360.0, Vec2::new(-0.6, -0.05), Vec2::new(0.6, 1.0));
+        for particle in &self.particles {
+            let t = particle.age / PARTICLE_LIFETIME;
+            let size = self
+                .lower
+                .lookup(t)
+                .lerp(self.upper.lookup(t), particle.blend);
+            let color = egui::Color32::from_rgb(255, 200, 80).gamma_multiply(1.0 - t);
+            painter.circle_filled(area.to_screen(particle.position), size.max(0.0), color);
+        }
+    }
+}
+
+struct InverseLookup {
+    curve: LookupCurve,
+    inverse: LookupCurve,
+    wavy: LookupCurve,
+    y: f32,
+}
+
+impl InverseLookup {
+    fn new() -> Self {
+        let curve = cubic(&[(0.0, 0.0), (0.3, 0.6), (1.0, 1.0)]);
+        Self {
+            inverse: curve
+                .inverted(1e-4)
+                .expect("The curve only increases, so it has an inverse"),
+            curve,
+            wavy: cubic(&[(0.0, 0.0), (0.3, 0.8), (0.6, 0.2), (1.0, 1.0)]),
+            y: 0.5,
+        }
+    }
+}
+
+impl Demo for InverseLookup {
+    fn name(&self) -> &'static str {
+        "Inverse lookup"
+    }
+
+    fn description(&self) -> &'static str {
+        "`inverted` fits a curve mapping y back to x, for curves where y only increases or only decreases. \
+         `crossings` finds every x where any curve reaches a y."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _dt: Duration) {
+        ui.add(egui::Slider::new(&mut self.y, 0.0..=1.0).text("y"));
+        let x = self.inverse.lookup(self.y);
+        ui.label(format!(
+            "Inverse: x = {x:.4}, which the curve maps back to {:.4}",
+            self.curve.lookup(x)
+        ));
+        let (min, max) = Plot::bounds(&[&self.curve]);
+        let (plot, painter) = Plot::new(ui, 200.0, min, max);
+        plot.hline(&painter, self.y, egui::Color32::GRAY);
+        plot.curve(&painter, &self.curve, egui::Color32::WHITE);
+        plot.point(&painter, Vec2::new(x, self.y), egui::Color32::RED);
+
+        ui.separator();
+        match self.wavy.check_invertible() {
+            Ok(()) => ui.label("The wavy curve has an inverse"),
+            Err(e) => ui.label(format!("The wavy curve has no inverse: {e}")),
+        };
+        let crossings = self.wavy.crossings(self.y);
+        ui.label(format!("Crossings of the wavy curve: {crossings:.3?}"));
+        let (min, max) = Plot::bounds(&[&self.wavy]);
+        let (plot, painter) = Plot::new(ui, 200.0, min, max);
+        plot.hline(&painter, self.y, egui::Color32::GRAY);
+        plot.curve(&painter, &self.wavy, egui::Color32::WHITE);
+        for x in crossings {
+            plot.point(&painter, Vec2::new(x, self.y), egui::Color32::RED);
+        }
+    }
+}
+
+/// Slope difference above which a knot counts as a kink
+const SLOPE_TOLERANCE: f32 = 1e-3;
+
+struct Validation {
+    curve: LookupCurve,
+}
+
+impl Validation {
+    fn new() -> Self {
+        let mut kink = knot(0.5, 0.5, KnotInterpolation::Cubic);
+        kink.left_tangent = Tangent {
+            slope: 2.0,
+            mode: TangentMode::Free,
+            weight: None,
+        };
+        kink.right_tangent = Tangent {
+            slope: -1.0,
+            mode: TangentMode::Free,
+            weight: Some(0.9),
+        };
+        let mut end = knot(0.8, 0.7, KnotInterpolation::Linear);
+        end.left_tangent.weight = Some(0.6);
+        Self {
+            curve: LookupCurve::new(vec![knot(0.0, 0.1, KnotInterpolation::Cubic), kink, end])
+                .with_anchors(vec![Vec2::new(0.25, 0.5), Vec2::new(0.8, 0.7)]),
+        }
+    }
+}
+
+impl Demo for Validation {
+    fn name(&self) -> &'static str {
+        "Validation"
+    }
+
+    fn description(&self) -> &'static str {
+        "Curves can be checked against rules, and most rules can be repaired. Asset processing applies the same \
+         rules to curve assets with the `asset_processing` feature."
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, _dt: Duration) {
+        let curve = &mut self.curve;
+        let rule = |ui: &mut egui::Ui, ok: bool, text: String| {
+            let (mark, color) = if ok {
+                ("✔", egui::Color32::LIGHT_GREEN)
+            } else {
+                ("✖", egui::Color32::LIGHT_RED)
+            };
+            ui.colored_label(color, format!("{mark} {text}"));
+        };
+
+        let violations = curve.validate_anchors();
+        ui.horizontal(|ui| {
+            rule(
+                ui,
+                violations.is_empty(),
+                format!("{} anchors missed", violations.len()),
+            );
+            if ui.button("Enforce anchors").clicked() {
+                curve.enforce_anchors();
+            }
+        });
+        let not_monotonic = curve.non_monotonic_x_segments();
+        ui.horizontal(|ui| {
+            rule(
+                ui,
+                not_monotonic.is_empty(),
+                format!("Segments folding back on x: {not_monotonic:?}"),
+            );
+            if ui.button("Enforce monotonic x").clicked() {
+                curve.enforce_monotonic_x();
+            }
+        });
+        let kinks = curve.c1_discontinuities(SLOPE_TOLERANCE);
+        ui.horizontal(|ui| {
+            rule(ui, kinks.is_empty(), format!("{} kinks", kinks.len()));
+            if ui.button("Smooth knots").clicked() {
+                curve.smooth_all_knots(SLOPE_TOLERANCE, SmoothPolicy::Average);
+            }
+        });
+        let not_finite = curve.knots().iter().filter(|k| !k.is_finite()).count();
+        rule(
+            ui,
+            not_finite == 0,
+            format!("{not_finite} knots that are not finite"),
+        );
+        match curve.check_time_warp() {
+            Ok(()) => rule(ui, true, "Valid time warp".to_string()),
+            Err(e) => rule(ui, false, format!("Not a time warp: {e}")),
+        }
+        if ui.button("Reset").clicked() {
+            *curve = Self::new().curve;
+        }
+
+        let (min, max) = Plot::bounds(&[curve]);
+        let (plot, painter) = Plot::new(ui, 300.0, min, max);
+        plot.curve(&painter, &*curve, egui::Color32::WHITE);
+        for knot in curve.knots() {
+            plot.point(&painter, knot.position, egui::Color32::GRAY);
+        }
+        for &anchor in &curve.anchors {
+            let missed = violations.iter().any(|v| v.anchor == anchor);
+            let color = if missed {
+                egui::Color32::LIGHT_RED
+            } else {
+                egui::Color32::LIGHT_GREEN
+            };
+            plot.point(&painter, anchor, color);
+        }
+    }
+}
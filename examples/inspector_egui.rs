@@ -1,7 +1,10 @@
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
 use bevy_inspector_egui::quick::{AssetInspectorPlugin, ResourceInspectorPlugin};
 
-use bevy_lookup_curve::{LookupCurve, LookupCurvePlugin};
+use bevy_lookup_curve::integrations::curve_asset_list_ui;
+use bevy_lookup_curve::prelude::*;
 
 fn main() {
     App::new()
@@ -11,6 +14,7 @@ fn main() {
         .register_type::<LookupCurveDevState>()
         .add_plugins(ResourceInspectorPlugin::<LookupCurveDevState>::default())
         .add_systems(Startup, setup)
+        .add_systems(Update, curve_asset_list)
         .run();
 }
 
@@ -28,3 +32,17 @@ fn setup(mut commands: Commands, assets: Res<AssetServer>) {
         curve_noasset: LookupCurve::default().with_name("Not asset"),
     });
 }
+
+fn curve_asset_list(world: &mut World) {
+    let Ok(egui_context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .get_single(world)
+    else {
+        return;
+    };
+    let mut egui_context = egui_context.clone();
+
+    egui::Window::new("Curve assets").show(egui_context.get_mut(), |ui| {
+        curve_asset_list_ui(world, ui);
+    });
+}
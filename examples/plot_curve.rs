@@ -0,0 +1,20 @@
+use bevy_lookup_curve::{
+    plot::{render_curve_report, PlotOptions},
+    LookupCurve,
+};
+
+/// Renders a curve to a PNG, for example:
+/// `cargo run --example plot_curve --features plot -- assets/example.curve.ron example.png`
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(input), Some(output)) = (args.next(), args.next()) else {
+        eprintln!("Usage: plot_curve <input.curve.ron> <output.png>");
+        std::process::exit(1);
+    };
+
+    let curve = LookupCurve::load_from_file(&input).expect("Failed to load curve");
+    render_curve_report(&curve, PlotOptions::default())
+        .save(&output)
+        .expect("Failed to save image");
+    println!("Saved {output}");
+}
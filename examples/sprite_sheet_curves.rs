@@ -0,0 +1,148 @@
+//! A pickup item driven by a `CurveSheet`: its scale pulses, its alpha flickers and its hue shifts, each by a curve
+//! on one shared looping timeline. Press Space to collect the item, which plays a one-shot sheet and respawns the
+//! item once it completes. Press E to open editors for the curves of the sheet, following the running timeline.
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use bevy_lookup_curve::prelude::*;
+
+/// Hue of the item without shift, in degrees
+const BASE_HUE: f32 = 45.0;
+
+#[derive(Component)]
+struct Pickup;
+
+#[derive(Component)]
+struct Collecting;
+
+#[derive(Resource)]
+struct PickupCurves {
+    idle: CurveSheet,
+    collect: CurveSheet,
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(LookupCurvePlugin)
+        .add_curve_sheet_applier("scale", |transform: &mut Transform, scale| {
+            transform.scale = Vec3::splat(scale);
+        })
+        .add_curve_sheet_applier("alpha", |sprite: &mut Sprite, alpha| {
+            sprite.color.set_alpha(alpha);
+        })
+        .add_curve_sheet_applier("hue_shift", |sprite: &mut Sprite, shift| {
+            let color = Hsla::from(sprite.color).with_hue((BASE_HUE + shift).rem_euclid(360.0));
+            sprite.color = color.into();
+        })
+        .add_systems(Startup, setup)
+        .add_systems(Update, (collect, respawn, open_editors))
+        .run();
+}
+
+fn knots(points: &[(f32, f32)]) -> LookupCurve {
+    LookupCurve::new(
+        points
+            .iter()
+            .map(|&(x, y)| Knot {
+                position: Vec2::new(x, y),
+                interpolation: KnotInterpolation::Cubic,
+                ..default()
+            })
+            .collect(),
+    )
+}
+
+fn setup(mut commands: Commands, mut curves: ResMut<Assets<LookupCurve>>) {
+    commands.spawn(Camera2dBundle::default());
+
+    let idle = CurveSheet::new(Duration::from_millis(1200))
+        .with_repeat(TweenRepeat::Loop)
+        .with_curve(
+            "scale",
+            curves.add(knots(&[(0.0, 1.0), (0.5, 1.25), (1.0, 1.0)]).with_name("Idle scale")),
+        )
+        .with_curve(
+            "alpha",
+            curves.add(
+                knots(&[(0.0, 1.0), (0.3, 0.6), (0.6, 1.0), (1.0, 1.0)]).with_name("Idle alpha"),
+            ),
+        )
+        .with_curve(
+            "hue_shift",
+            curves.add(knots(&[(0.0, -15.0), (0.5, 15.0), (1.0, -15.0)]).with_name("Idle hue")),
+        );
+    let collect = CurveSheet::new(Duration::from_millis(500))
+        .with_curve(
+            "scale",
+            curves.add(knots(&[(0.0, 1.0), (0.3, 1.8), (1.0, 0.0)]).with_name("Collect scale")),
+        )
+        .with_curve(
+            "alpha",
+            curves.add(knots(&[(0.0, 1.0), (1.0, 0.0)]).with_name("Collect alpha")),
+        );
+
+    spawn_pickup(&mut commands, idle.clone());
+    commands.insert_resource(PickupCurves { idle, collect });
+}
+
+fn spawn_pickup(commands: &mut Commands, sheet: CurveSheet) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Hsla::hsl(BASE_HUE, 0.9, 0.6).into(),
+                custom_size: Some(Vec2::splat(80.0)),
+                ..default()
+            },
+            ..default()
+        },
+        sheet,
+        Pickup,
+    ));
+}
+
+/// Replaces the looping idle sheet with the one-shot collect sheet
+fn collect(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    pickups: Query<Entity, (With<Pickup>, Without<Collecting>)>,
+    pickup_curves: Res<PickupCurves>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+    for entity in &pickups {
+        commands
+            .entity(entity)
+            .insert((pickup_curves.collect.clone(), Collecting));
+    }
+}
+
+/// Respawns the item when the collect sheet completes
+fn respawn(
+    mut commands: Commands,
+    mut completed: EventReader<CurveSheetCompleted>,
+    collecting: Query<(), With<Collecting>>,
+    pickup_curves: Res<PickupCurves>,
+) {
+    for CurveSheetCompleted { sheet } in completed.read() {
+        if collecting.contains(*sheet) {
+            commands.entity(*sheet).despawn();
+            spawn_pickup(&mut commands, pickup_curves.idle.clone());
+        }
+    }
+}
+
+fn open_editors(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    sheets: Query<(Entity, &CurveSheet), Without<Collecting>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyE) {
+        return;
+    }
+    for (entity, sheet) in &sheets {
+        commands.spawn_curve_sheet_editors(entity, sheet);
+    }
+}
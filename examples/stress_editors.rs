@@ -0,0 +1,56 @@
+//! Opens many editors and keeps switching the egui scale factor, like moving the window between monitors
+//! with different DPI. Useful for checking that the editors do not hitch when the style changes.
+use bevy::prelude::*;
+use bevy_egui::EguiSettings;
+
+use bevy_lookup_curve::prelude::*;
+
+const EDITOR_COUNT: usize = 10;
+const KNOTS_PER_CURVE: usize = 32;
+/// Seconds between switching the scale factor
+const SCALE_TOGGLE_INTERVAL: f32 = 2.0;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(LookupCurvePlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, toggle_scale_factor)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut curves: ResMut<Assets<LookupCurve>>) {
+    for i in 0..EDITOR_COUNT {
+        let frequency = (i + 1) as f32 * 3.0;
+        let knots = (0..KNOTS_PER_CURVE)
+            .map(|k| {
+                let x = k as f32 / (KNOTS_PER_CURVE - 1) as f32;
+                Knot {
+                    position: Vec2::new(x, (x * frequency).sin() * 0.5 + 0.5),
+                    interpolation: KnotInterpolation::Cubic,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let handle = curves.add(LookupCurve::new(knots).with_name(format!("Curve {i}")));
+        let mut editor = LookupCurveEditor::new(handle);
+        editor.sample = Some(0.5);
+        commands.spawn(editor);
+    }
+}
+
+fn toggle_scale_factor(
+    mut settings: ResMut<EguiSettings>,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+) {
+    *elapsed += time.delta_seconds();
+    if *elapsed >= SCALE_TOGGLE_INTERVAL {
+        *elapsed = 0.0;
+        settings.scale_factor = if settings.scale_factor == 1.0 {
+            1.5
+        } else {
+            1.0
+        };
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use bevy_lookup_curve::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(LookupCurvePlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, apply_tweens)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut curves: ResMut<Assets<LookupCurve>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Overshoots past the target before settling
+    let overshoot = curves.add(LookupCurve::new(vec![
+        Knot {
+            position: Vec2::ZERO,
+            interpolation: KnotInterpolation::Cubic,
+            ..default()
+        },
+        Knot {
+            position: Vec2::new(0.7, 1.2),
+            interpolation: KnotInterpolation::Cubic,
+            ..default()
+        },
+        Knot {
+            position: Vec2::ONE,
+            interpolation: KnotInterpolation::Cubic,
+            ..default()
+        },
+    ]));
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("bevy_icon.png"),
+            transform: Transform::from_scale(Vec3::splat(0.5)),
+            ..default()
+        },
+        LookupCurveTween::new(
+            Vec3::new(-300., 0., 0.),
+            Vec3::new(300., 0., 0.),
+            Duration::from_secs(2),
+            overshoot,
+        )
+        .with_repeat(TweenRepeat::PingPong),
+        LookupCurveTween::new(
+            Quat::IDENTITY,
+            Quat::from_rotation_z(std::f32::consts::PI),
+            Duration::from_secs(2),
+            asset_server.load("example.curve.ron"),
+        )
+        .with_repeat(TweenRepeat::PingPong),
+    ));
+}
+
+fn apply_tweens(
+    mut tweens: Query<(
+        &mut Transform,
+        &LookupCurveTween<Vec3>,
+        &LookupCurveTween<Quat>,
+    )>,
+) {
+    for (mut transform, translation, rotation) in &mut tweens {
+        transform.translation = *translation.value();
+        transform.rotation = *rotation.value();
+    }
+}
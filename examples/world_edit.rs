@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use bevy_lookup_curve::prelude::*;
+
+/// Width of the terrain along x in meters, spanned by the curve from 0 to 1
+const WIDTH: f32 = 20.0;
+/// Height of the terrain in meters at y = 1
+const HEIGHT: f32 = 5.0;
+const COLUMNS: usize = 40;
+
+/// A column of terrain, at x in curve space
+#[derive(Component)]
+struct TerrainColumn(f32);
+
+#[derive(Resource)]
+struct Terrain(Handle<LookupCurve>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(LookupCurvePlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, follow_terrain_profile)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut curves: ResMut<Assets<LookupCurve>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let profile = curves.add(LookupCurve::new(vec![
+        Knot {
+            position: Vec2::new(0.0, 0.2),
+            interpolation: KnotInterpolation::Cubic,
+            ..default()
+        },
+        Knot {
+            position: Vec2::new(0.35, 0.8),
+            interpolation: KnotInterpolation::Cubic,
+            ..default()
+        },
+        Knot {
+            position: Vec2::new(0.6, 0.4),
+            interpolation: KnotInterpolation::Cubic,
+            ..default()
+        },
+        Knot {
+            position: Vec2::new(1.0, 0.6),
+            interpolation: KnotInterpolation::Cubic,
+            ..default()
+        },
+    ]));
+
+    // The profile is edited in place, on the front faces of the columns
+    let depth = 4.0;
+    let plane = CurvePlane::new(
+        Vec3::new(-WIDTH / 2.0, 0.0, depth / 2.0),
+        Vec3::X,
+        WIDTH,
+        Vec3::Y,
+        HEIGHT,
+    );
+    commands.spawn(WorldCurveEditor::new(profile.clone(), plane).with_knot_radius(0.3));
+    commands.insert_resource(Terrain(profile));
+
+    let column_width = WIDTH / COLUMNS as f32;
+    let mesh = meshes.add(Cuboid::new(column_width, 1.0, depth));
+    let material = materials.add(Color::srgb(0.35, 0.55, 0.3));
+    for i in 0..COLUMNS {
+        let x = (i as f32 + 0.5) / COLUMNS as f32;
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(plane.to_world(Vec2::new(x, 0.0)).x, 0.0, 0.0),
+                ..default()
+            },
+            TerrainColumn(x),
+        ));
+    }
+
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+    // Looking at the profile from an oblique angle
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(-8.0, 9.0, 18.0)
+            .looking_at(Vec3::new(0.0, 2.0, 0.0), Vec3::Y),
+        ..default()
+    });
+}
+
+fn follow_terrain_profile(
+    terrain: Res<Terrain>,
+    curves: Res<Assets<LookupCurve>>,
+    mut columns: Query<(&mut Transform, &TerrainColumn)>,
+) {
+    let Some(curve) = curves.get(&terrain.0) else {
+        return;
+    };
+    for (mut transform, column) in &mut columns {
+        let height = (curve.lookup(column.0) * HEIGHT).max(0.01);
+        transform.scale.y = height;
+        transform.translation.y = height / 2.0;
+    }
+}
@@ -1,7 +1,14 @@
 use bevy_app::{App, Plugin};
 use bevy_asset::{io::Reader, AssetApp, AssetLoader, AsyncReadExt, LoadContext};
 
-use crate::{LookupCurve, LookupCurveLoadError};
+use crate::{CurveFormat, LookupCurve, LookupCurveLoadError};
+
+#[cfg(feature = "asset_processing")]
+use crate::processing::{CurveProcessError, CurveProcessSettings};
+#[cfg(feature = "asset_processing")]
+use bevy_asset::{
+    io::Writer, processor::LoadAndSave, saver::AssetSaver, saver::SavedAsset, AsyncWriteExt,
+};
 
 pub(crate) struct AssetPlugin;
 
@@ -9,9 +16,38 @@ impl Plugin for AssetPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<LookupCurve>();
         app.register_asset_loader(LookupCurveAssetLoader);
+        #[cfg(feature = "yaml")]
+        app.register_asset_loader(LookupCurveYamlAssetLoader);
+        #[cfg(feature = "toml")]
+        app.register_asset_loader(LookupCurveTomlAssetLoader);
+        #[cfg(feature = "asset_processing")]
+        {
+            app.register_asset_processor::<LookupCurveProcessor>(LookupCurveAssetSaver.into());
+            for extension in CurveFormat::Ron.extensions() {
+                app.set_default_asset_processor::<LookupCurveProcessor>(extension);
+            }
+        }
+        #[cfg(all(feature = "asset_processing", feature = "yaml"))]
+        {
+            app.register_asset_processor::<LookupCurveYamlProcessor>(LookupCurveAssetSaver.into());
+            for extension in CurveFormat::Yaml.extensions() {
+                app.set_default_asset_processor::<LookupCurveYamlProcessor>(extension);
+            }
+        }
+        #[cfg(all(feature = "asset_processing", feature = "toml"))]
+        {
+            app.register_asset_processor::<LookupCurveTomlProcessor>(LookupCurveAssetSaver.into());
+            for extension in CurveFormat::Toml.extensions() {
+                app.set_default_asset_processor::<LookupCurveTomlProcessor>(extension);
+            }
+        }
     }
 }
 
+/// Loads `.curve.ron` and `.lookupcurve.ron` assets, added by [LookupCurvePlugin](crate::LookupCurvePlugin).
+///
+/// Knots are sorted and validated like in [parse_curve_ron](crate::parse_curve_ron), errors name the knot and the
+/// asset path. With the `file_watcher` feature of bevy, edits of the file on disk reload the asset.
 #[derive(Default)]
 pub struct LookupCurveAssetLoader;
 
@@ -24,15 +60,122 @@ impl AssetLoader for LookupCurveAssetLoader {
         &'a self,
         reader: &'a mut Reader<'_>,
         _settings: &'a (),
-        _load_context: &'a mut LoadContext<'_>,
+        load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let lookup_curve = ron::de::from_bytes::<LookupCurve>(&bytes)?;
+        let path = load_context.path().to_string_lossy();
+        // Processed curves might be binary encoded
+        #[cfg(feature = "asset_processing")]
+        let lookup_curve = LookupCurve::from_asset_bytes(&bytes, &path)?;
+        #[cfg(not(feature = "asset_processing"))]
+        let lookup_curve = LookupCurve::from_text_bytes(&bytes, &path, CurveFormat::Ron)?;
         Ok(lookup_curve)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["curve.ron"]
+        CurveFormat::Ron.extensions()
+    }
+}
+
+/// Loads `.curve.yaml` and `.curve.yml` assets, validated like `.curve.ron` assets
+#[cfg(feature = "yaml")]
+#[derive(Default)]
+pub struct LookupCurveYamlAssetLoader;
+
+#[cfg(feature = "yaml")]
+impl AssetLoader for LookupCurveYamlAssetLoader {
+    type Asset = LookupCurve;
+    type Settings = ();
+    type Error = LookupCurveLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        load_text(reader, load_context, CurveFormat::Yaml).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        CurveFormat::Yaml.extensions()
+    }
+}
+
+/// Loads `.curve.toml` assets, validated like `.curve.ron` assets
+#[cfg(feature = "toml")]
+#[derive(Default)]
+pub struct LookupCurveTomlAssetLoader;
+
+#[cfg(feature = "toml")]
+impl AssetLoader for LookupCurveTomlAssetLoader {
+    type Asset = LookupCurve;
+    type Settings = ();
+    type Error = LookupCurveLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        load_text(reader, load_context, CurveFormat::Toml).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        CurveFormat::Toml.extensions()
+    }
+}
+
+#[cfg(any(feature = "yaml", feature = "toml"))]
+async fn load_text(
+    reader: &mut Reader<'_>,
+    load_context: &mut LoadContext<'_>,
+    format: CurveFormat,
+) -> Result<LookupCurve, LookupCurveLoadError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    let path = load_context.path().to_string_lossy();
+    Ok(LookupCurve::from_text_bytes(&bytes, &path, format)?)
+}
+
+/// Processes `.curve.ron` and `.lookupcurve.ron` assets with [LookupCurveAssetSaver], see [CurveProcessSettings]
+#[cfg(feature = "asset_processing")]
+pub type LookupCurveProcessor = LoadAndSave<LookupCurveAssetLoader, LookupCurveAssetSaver>;
+
+/// Processes `.curve.yaml` and `.curve.yml` assets like [LookupCurveProcessor]. Processed curves are saved as RON
+/// or in the binary encoding, and loaded with [LookupCurveAssetLoader].
+#[cfg(all(feature = "asset_processing", feature = "yaml"))]
+pub type LookupCurveYamlProcessor = LoadAndSave<LookupCurveYamlAssetLoader, LookupCurveAssetSaver>;
+
+/// Processes `.curve.toml` assets like [LookupCurveProcessor]. Processed curves are saved as RON or in the binary
+/// encoding, and loaded with [LookupCurveAssetLoader].
+#[cfg(all(feature = "asset_processing", feature = "toml"))]
+pub type LookupCurveTomlProcessor = LoadAndSave<LookupCurveTomlAssetLoader, LookupCurveAssetSaver>;
+
+/// Saves processed lookup curves, applying the rules and encoding of [CurveProcessSettings]
+#[cfg(feature = "asset_processing")]
+#[derive(Default)]
+pub struct LookupCurveAssetSaver;
+
+#[cfg(feature = "asset_processing")]
+impl AssetSaver for LookupCurveAssetSaver {
+    type Asset = LookupCurve;
+    type Settings = CurveProcessSettings;
+    type OutputLoader = LookupCurveAssetLoader;
+    type Error = CurveProcessError;
+
+    async fn save<'a>(
+        &'a self,
+        writer: &'a mut Writer,
+        asset: SavedAsset<'a, LookupCurve>,
+        settings: &'a CurveProcessSettings,
+    ) -> Result<(), CurveProcessError> {
+        let mut curve = asset.get().clone();
+        curve.apply_process_rules(settings)?;
+        let bytes = curve.encode_processed(settings.encoding)?;
+        writer.write_all(&bytes).await?;
+        Ok(())
     }
 }
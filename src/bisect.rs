@@ -0,0 +1,287 @@
+//! Finding the edit that made a curve worse, by bisecting snapshots of its history, see [CurveBisect]
+
+use std::fmt;
+
+use crate::LookupCurve;
+
+/// State of a curve after an edit, kept in the history passed to [CurveBisect::new]
+#[derive(Clone, Debug)]
+pub struct CurveSnapshot {
+    /// What the edit did, shown when it is found
+    pub description: String,
+    pub curve: LookupCurve,
+}
+
+/// Error of [CurveBisect::new]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BisectError {
+    /// The good or bad snapshot is not in the history
+    OutOfRange { index: usize, len: usize },
+    /// The good snapshot has to be older than the bad one
+    GoodNotBeforeBad { good: usize, bad: usize },
+}
+
+impl fmt::Display for BisectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BisectError::OutOfRange { index, len } => {
+                write!(f, "Snapshot {index} is not in a history of {len}")
+            }
+            BisectError::GoodNotBeforeBad { good, bad } => {
+                write!(
+                    f,
+                    "Good snapshot {good} is not older than bad snapshot {bad}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BisectError {}
+
+/// What to do next in a [CurveBisect]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BisectStep {
+    /// Apply the snapshot at `index` to the live curve, try it, and mark it with [CurveBisect::mark_good] or
+    /// [CurveBisect::mark_bad]
+    Test { index: usize },
+    /// The edit that created the snapshot at `culprit` made the curve bad, see [CurveBisect::culprit]
+    Found { culprit: usize },
+}
+
+/// Bisects a history of snapshots of a curve, ordered from oldest to newest, to find the edit that made it bad.
+///
+/// Starts from a snapshot known to be good and a newer one known to be bad. Each step tests the snapshot in the
+/// middle of the remaining range, until the good and the bad snapshot are next to each other. The edit that created
+/// the bad snapshot is the culprit.
+///
+/// Independent of any UI: apply the snapshot of each [BisectStep::Test] to the live curve, and restore the curve
+/// returned by [CurveBisect::cancel] or [CurveBisect::finish] when done.
+#[derive(Clone, Debug)]
+pub struct CurveBisect {
+    history: Vec<CurveSnapshot>,
+    /// The live curve before bisecting
+    original: LookupCurve,
+    /// Newest snapshot known to be good
+    good: usize,
+    /// Oldest snapshot known to be bad
+    bad: usize,
+}
+
+impl CurveBisect {
+    /// Starts bisecting `history` between the snapshots at `good` and `bad`. `original` is the live curve, which
+    /// is restored when done.
+    pub fn new(
+        history: Vec<CurveSnapshot>,
+        original: LookupCurve,
+        good: usize,
+        bad: usize,
+    ) -> Result<Self, BisectError> {
+        let len = history.len();
+        if let Some(&index) = [good, bad].iter().find(|&&index| index >= len) {
+            return Err(BisectError::OutOfRange { index, len });
+        }
+        if good >= bad {
+            return Err(BisectError::GoodNotBeforeBad { good, bad });
+        }
+        Ok(Self {
+            history,
+            original,
+            good,
+            bad,
+        })
+    }
+
+    /// The next snapshot to test, or the culprit once found
+    pub fn step(&self) -> BisectStep {
+        if self.bad - self.good == 1 {
+            BisectStep::Found { culprit: self.bad }
+        } else {
+            // Rounds towards the good snapshot, both halves differ in size by at most one
+            BisectStep::Test {
+                index: self.good + (self.bad - self.good) / 2,
+            }
+        }
+    }
+
+    /// The snapshot to apply to the live curve for the current [BisectStep::Test], `None` once found
+    pub fn candidate(&self) -> Option<&CurveSnapshot> {
+        match self.step() {
+            BisectStep::Test { index } => Some(&self.history[index]),
+            BisectStep::Found { .. } => None,
+        }
+    }
+
+    /// Marks the tested snapshot as good, the culprit is newer
+    pub fn mark_good(&mut self) -> BisectStep {
+        if let BisectStep::Test { index } = self.step() {
+            self.good = index;
+        }
+        self.step()
+    }
+
+    /// Marks the tested snapshot as bad, the culprit is this one or older
+    pub fn mark_bad(&mut self) -> BisectStep {
+        if let BisectStep::Test { index } = self.step() {
+            self.bad = index;
+        }
+        self.step()
+    }
+
+    /// Range of snapshots the culprit is in, from the newest good to the oldest bad snapshot
+    pub fn range(&self) -> (usize, usize) {
+        (self.good, self.bad)
+    }
+
+    /// Number of snapshots left to test, at most
+    pub fn remaining_steps(&self) -> u32 {
+        (self.bad - self.good).next_power_of_two().trailing_zeros()
+    }
+
+    /// Once found, the snapshots before and after the culprit edit. The description of the edit is the one of the
+    /// second snapshot.
+    pub fn culprit(&self) -> Option<(&CurveSnapshot, &CurveSnapshot)> {
+        match self.step() {
+            BisectStep::Found { culprit } => {
+                Some((&self.history[culprit - 1], &self.history[culprit]))
+            }
+            BisectStep::Test { .. } => None,
+        }
+    }
+
+    /// Stops bisecting, returning the live curve as it was before bisecting
+    pub fn cancel(self) -> LookupCurve {
+        self.original
+    }
+
+    /// Stops bisecting once found, returning the culprit and the live curve as it was before bisecting
+    pub fn finish(self) -> (Option<usize>, LookupCurve) {
+        let culprit = match self.step() {
+            BisectStep::Found { culprit } => Some(culprit),
+            BisectStep::Test { .. } => None,
+        };
+        (culprit, self.original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy_math::Vec2;
+
+    /// Snapshots after moving the only knot up, one step per edit
+    fn history(len: usize) -> Vec<CurveSnapshot> {
+        (0..len)
+            .map(|i| CurveSnapshot {
+                description: format!("Move knot to {i}"),
+                curve: LookupCurve::new(vec![Knot {
+                    position: Vec2::new(0.0, i as f32),
+                    ..Default::default()
+                }]),
+            })
+            .collect()
+    }
+
+    /// Bisects, marking snapshots at or after `culprit` bad. Returns the found culprit and the tested snapshots.
+    fn run(len: usize, culprit: usize) -> (usize, Vec<usize>) {
+        let mut bisect =
+            CurveBisect::new(history(len), LookupCurve::default(), 0, len - 1).unwrap();
+        let mut tested = Vec::new();
+        let mut step = bisect.step();
+        while let BisectStep::Test { index } = step {
+            assert!(tested.len() < 64, "bisect does not terminate");
+            let (good, bad) = bisect.range();
+            assert!(good < index && index < bad);
+            tested.push(index);
+            step = if index >= culprit {
+                bisect.mark_bad()
+            } else {
+                bisect.mark_good()
+            };
+        }
+        let BisectStep::Found { culprit } = step else {
+            unreachable!()
+        };
+        (culprit, tested)
+    }
+
+    #[test]
+    fn finds_every_culprit_in_even_and_odd_ranges() {
+        for len in 2..=17usize {
+            let max_steps = (len - 1).next_power_of_two().trailing_zeros() as usize;
+            for culprit in 1..len {
+                let (found, tested) = run(len, culprit);
+                assert_eq!(found, culprit, "len {len}");
+                assert!(tested.len() <= max_steps, "len {len}, culprit {culprit}");
+            }
+        }
+    }
+
+    #[test]
+    fn midpoints_round_towards_the_good_snapshot() {
+        // Odd range of 4 edits
+        let mut bisect = CurveBisect::new(history(5), LookupCurve::default(), 0, 4).unwrap();
+        assert_eq!(bisect.step(), BisectStep::Test { index: 2 });
+        assert_eq!(bisect.remaining_steps(), 2);
+        assert_eq!(bisect.mark_good(), BisectStep::Test { index: 3 });
+        assert_eq!(bisect.mark_bad(), BisectStep::Found { culprit: 3 });
+        assert_eq!(bisect.remaining_steps(), 0);
+
+        // Even range of 3 edits
+        let mut bisect = CurveBisect::new(history(4), LookupCurve::default(), 0, 3).unwrap();
+        assert_eq!(bisect.step(), BisectStep::Test { index: 1 });
+        assert_eq!(bisect.candidate().unwrap().curve.knots()[0].position.y, 1.0);
+        assert_eq!(bisect.mark_bad(), BisectStep::Found { culprit: 1 });
+        assert!(bisect.candidate().is_none());
+
+        // Marking after the culprit is found changes nothing
+        assert_eq!(bisect.mark_good(), BisectStep::Found { culprit: 1 });
+        let (before, after) = bisect.culprit().unwrap();
+        assert_eq!(before.description, "Move knot to 0");
+        assert_eq!(after.description, "Move knot to 1");
+    }
+
+    #[test]
+    fn adjacent_snapshots_need_no_tests() {
+        let bisect = CurveBisect::new(history(3), LookupCurve::default(), 1, 2).unwrap();
+        assert_eq!(bisect.step(), BisectStep::Found { culprit: 2 });
+    }
+
+    #[test]
+    fn canceling_restores_the_original_curve() {
+        let original = LookupCurve::new(vec![Knot {
+            position: Vec2::new(1.0, 7.0),
+            ..Default::default()
+        }])
+        .with_name("tuned");
+        let mut bisect = CurveBisect::new(history(10), original.clone(), 0, 9).unwrap();
+        bisect.mark_good();
+        bisect.mark_bad();
+        let restored = bisect.cancel();
+        assert!(restored.shape_eq(&original));
+        assert_eq!(restored.name, original.name);
+        assert_eq!(restored.generation(), original.generation());
+        assert_eq!(restored.knots()[0].id, original.knots()[0].id);
+
+        let bisect = CurveBisect::new(history(2), original.clone(), 0, 1).unwrap();
+        let (culprit, restored) = bisect.finish();
+        assert_eq!(culprit, Some(1));
+        assert_eq!(restored.generation(), original.generation());
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        let new =
+            |good, bad| CurveBisect::new(history(4), LookupCurve::default(), good, bad).map(|_| ());
+        assert_eq!(new(0, 4), Err(BisectError::OutOfRange { index: 4, len: 4 }));
+        assert_eq!(
+            new(2, 2),
+            Err(BisectError::GoodNotBeforeBad { good: 2, bad: 2 })
+        );
+        assert_eq!(
+            new(3, 1),
+            Err(BisectError::GoodNotBeforeBad { good: 3, bad: 1 })
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use bevy_math::Vec2;
+
+use crate::{Knot, LookupCurve, TangentMode, TangentSide};
+
+/// Max difference between the sampled value and the y of an anchor for the anchor to be satisfied
+const ANCHOR_EPSILON: f32 = 1e-5;
+/// Max distance in x, relative to the width of the curve, for [LookupCurve::enforce_anchors] to move a knot
+/// onto an anchor instead of inserting a new knot
+const ANCHOR_SNAP_DISTANCE: f32 = 0.05;
+
+/// An anchor the curve does not pass through, see [LookupCurve::validate_anchors]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnchorViolation {
+    /// Index of the anchor in [LookupCurve::anchors]
+    pub index: usize,
+    pub anchor: Vec2,
+    /// Value of the curve at the x of the anchor
+    pub value: f32,
+}
+
+impl AnchorViolation {
+    /// Difference between the value of the curve and the anchor
+    pub fn error(&self) -> f32 {
+        self.value - self.anchor.y
+    }
+}
+
+impl LookupCurve {
+    /// Consumes the curve and returns it with the anchors set
+    pub fn with_anchors(mut self, anchors: Vec<Vec2>) -> Self {
+        self.anchors = anchors;
+        self.bump_generation();
+        self
+    }
+
+    /// Returns the [LookupCurve::anchors] the curve does not pass through.
+    ///
+    /// An anchor is satisfied by a knot exactly at the anchor, or if the value of the curve at the x of the anchor
+    /// is within `1e-5` of its y.
+    pub fn validate_anchors(&self) -> Vec<AnchorViolation> {
+        self.anchors
+            .iter()
+            .enumerate()
+            .filter(|(_, anchor)| !self.satisfies_anchor(**anchor))
+            .map(|(index, anchor)| AnchorViolation {
+                index,
+                anchor: *anchor,
+                value: self.lookup(anchor.x),
+            })
+            .collect()
+    }
+
+    /// Modifies the curve to pass through all [LookupCurve::anchors].
+    ///
+    /// For each violated anchor the nearest knot is moved onto it, if that knot is within 5% of the width of the
+    /// curve and not on another anchor. Otherwise a knot is inserted at the anchor, with tangents following the curve.
+    pub fn enforce_anchors(&mut self) {
+        for a in 0..self.anchors.len() {
+            let anchor = self.anchors[a];
+            if self.satisfies_anchor(anchor) {
+                continue;
+            }
+
+            let snap_distance = match (self.knots.first(), self.knots.last()) {
+                (Some(first), Some(last)) => {
+                    (last.position.x - first.position.x) * ANCHOR_SNAP_DISTANCE
+                }
+                _ => 0.0,
+            };
+            let nearest = self
+                .knots
+                .iter()
+                .enumerate()
+                .filter(|(_, knot)| !self.anchors.contains(&knot.position))
+                .map(|(i, knot)| (i, (knot.position.x - anchor.x).abs()))
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .filter(|(_, distance)| *distance <= snap_distance);
+
+            match nearest {
+                Some((i, _)) => {
+                    let knot = self.knots[i];
+                    self.modify_knot(
+                        i,
+                        Knot {
+                            position: anchor,
+                            ..knot
+                        },
+                    );
+                }
+                None => {
+                    let i = self.knots.partition_point(|k| k.position.x < anchor.x);
+                    let interpolation = match i {
+                        0 => Knot::default().interpolation,
+                        i => self.knots[i - 1].interpolation,
+                    };
+                    let (left, right) = (
+                        self.slope_at(anchor.x, TangentSide::Left),
+                        self.slope_at(anchor.x, TangentSide::Right),
+                    );
+                    let mut knot = Knot {
+                        position: anchor,
+                        interpolation,
+                        ..Default::default()
+                    };
+                    knot.left_tangent.slope = left;
+                    knot.right_tangent.slope = right;
+                    if left != right {
+                        knot.left_tangent.mode = TangentMode::Free;
+                        knot.right_tangent.mode = TangentMode::Free;
+                    }
+                    self.add_knot(knot);
+                }
+            }
+        }
+    }
+
+    fn satisfies_anchor(&self, anchor: Vec2) -> bool {
+        self.knots.iter().any(|knot| knot.position == anchor)
+            || (self.lookup(anchor.x) - anchor.y).abs() <= ANCHOR_EPSILON
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KnotInterpolation;
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+    }
+
+    /// Normalized transfer function from (0, 0) to (1, 1)
+    fn transfer_curve() -> LookupCurve {
+        LookupCurve::new(vec![knot(0.0, 0.0), knot(0.5, 0.7), knot(1.0, 1.0)])
+            .with_anchors(vec![Vec2::ZERO, Vec2::ONE])
+    }
+
+    #[test]
+    fn satisfied_anchors_are_not_violations() {
+        let mut curve = transfer_curve();
+        // Anchor on the curve between knots
+        curve.anchors.push(Vec2::new(0.25, curve.lookup(0.25)));
+        assert!(curve.validate_anchors().is_empty());
+    }
+
+    #[test]
+    fn detects_drifted_knots() {
+        let mut curve = transfer_curve();
+        curve.modify_knot(2, knot(1.0, 0.98));
+        curve.anchors.push(Vec2::new(0.25, 5.0));
+
+        let violations = curve.validate_anchors();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].index, 1);
+        assert_eq!(violations[0].anchor, Vec2::ONE);
+        assert!((violations[0].error() + 0.02).abs() < 1e-6);
+        assert_eq!(violations[1].index, 2);
+        assert_eq!(violations[1].value, curve.lookup(0.25));
+    }
+
+    #[test]
+    fn enforcing_snaps_nearby_knots() {
+        let mut curve = transfer_curve();
+        curve.modify_knot(0, knot(0.01, 0.02));
+        curve.modify_knot(2, knot(1.0, 0.98));
+
+        curve.enforce_anchors();
+        assert!(curve.validate_anchors().is_empty());
+        assert_eq!(curve.knots().len(), 3);
+        assert_eq!(curve.knots()[0].position, Vec2::ZERO);
+        assert_eq!(curve.knots()[2].position, Vec2::ONE);
+    }
+
+    #[test]
+    fn enforcing_inserts_knots_away_from_existing_knots() {
+        let mut curve = transfer_curve();
+        let slope = curve.slope_at(0.25, TangentSide::Left);
+        curve.anchors.push(Vec2::new(0.25, 0.5));
+        curve.anchors.push(Vec2::new(2.0, 3.0));
+
+        curve.enforce_anchors();
+        assert!(curve.validate_anchors().is_empty());
+        assert_eq!(curve.knots().len(), 5);
+        let inserted = curve.knots()[1];
+        assert_eq!(inserted.position, Vec2::new(0.25, 0.5));
+        assert!(matches!(inserted.interpolation, KnotInterpolation::Cubic));
+        assert_eq!(inserted.left_tangent.slope, slope);
+        assert_eq!(inserted.right_tangent.slope, slope);
+        // Knots on other anchors are never moved
+        assert_eq!(curve.knots()[3].position, Vec2::ONE);
+        assert_eq!(curve.knots()[4].position, Vec2::new(2.0, 3.0));
+    }
+
+    #[test]
+    fn enforcing_an_empty_curve_inserts_knots() {
+        let mut curve = LookupCurve::default().with_anchors(vec![Vec2::ONE, Vec2::ZERO]);
+        curve.enforce_anchors();
+        assert!(curve.validate_anchors().is_empty());
+        let positions: Vec<_> = curve.knots().iter().map(|k| k.position).collect();
+        assert_eq!(positions, [Vec2::ZERO, Vec2::ONE]);
+    }
+
+    #[test]
+    fn inverting_swaps_anchors() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0),
+            Knot {
+                interpolation: KnotInterpolation::Linear,
+                ..knot(1.5, 0.5)
+            },
+            knot(2.0, 1.0),
+        ])
+        .with_anchors(vec![Vec2::ZERO, Vec2::new(2.0, 1.0)]);
+        let inverted = curve.inverted(1e-4).unwrap();
+        assert_eq!(inverted.anchors, [Vec2::ZERO, Vec2::new(1.0, 2.0)]);
+        assert!(inverted.validate_anchors().is_empty());
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn anchors_round_trip() {
+        let with_anchors = transfer_curve();
+        let ron = ron::ser::to_string(&with_anchors).unwrap();
+        let loaded: LookupCurve = ron::de::from_str(&ron).unwrap();
+        assert_eq!(loaded.anchors, with_anchors.anchors);
+
+        let without_anchors = LookupCurve::new(vec![knot(0.0, 0.0)]);
+        let ron = ron::ser::to_string(&without_anchors).unwrap();
+        assert!(!ron.contains("anchors"));
+        let loaded: LookupCurve = ron::de::from_str(&ron).unwrap();
+        assert!(loaded.anchors.is_empty());
+    }
+}
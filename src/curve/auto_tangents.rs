@@ -0,0 +1,201 @@
+use std::ops::RangeInclusive;
+
+use crate::{Knot, LookupCurve, TangentMode};
+
+/// Catmull-Rom slope of the knot at `i`, the slope between its neighbors. The first and last knot use the slope to
+/// their only neighbor, and knots without a neighbor at another x get a slope of 0.
+fn auto_slope(knots: &[Knot], i: usize) -> f32 {
+    let prev = knots[i.saturating_sub(1)].position;
+    let next = knots[(i + 1).min(knots.len() - 1)].position;
+    let slope = (next.y - prev.y) / (next.x - prev.x);
+    if slope.is_finite() {
+        slope
+    } else {
+        0.0
+    }
+}
+
+/// Sets the slopes of the [TangentMode::Auto] tangents of the knot at `i`. Returns `true` if a slope changed.
+pub(crate) fn update_auto_slopes(knots: &mut [Knot], i: usize) -> bool {
+    let knot = &knots[i];
+    if knot.left_tangent.mode != TangentMode::Auto && knot.right_tangent.mode != TangentMode::Auto {
+        return false;
+    }
+    let slope = auto_slope(knots, i);
+    let mut changed = false;
+    let knot = &mut knots[i];
+    for tangent in [&mut knot.left_tangent, &mut knot.right_tangent] {
+        if tangent.mode == TangentMode::Auto && tangent.slope != slope {
+            tangent.slope = slope;
+            changed = true;
+        }
+    }
+    changed
+}
+
+impl LookupCurve {
+    /// Sets the slopes of all [TangentMode::Auto] tangents from the neighbors of their knots.
+    ///
+    /// Every method adding, moving or removing knots does this, call it after changing knots in other ways, like
+    /// through reflection. Returns `true` if any slope changed.
+    pub fn update_auto_tangents(&mut self) -> bool {
+        let changed = self.refresh_auto_tangents();
+        if changed {
+            self.bump_generation();
+            self.check_consistency();
+        }
+        changed
+    }
+
+    /// [LookupCurve::update_auto_tangents] without changing the generation, for methods that already changed it.
+    /// Knots with changed slopes are stamped as modified.
+    pub(crate) fn refresh_auto_tangents(&mut self) -> bool {
+        self.refresh_auto_tangent_knots().is_some()
+    }
+
+    /// [LookupCurve::refresh_auto_tangents], returning the range of knots with changed slopes
+    pub(crate) fn refresh_auto_tangent_knots(&mut self) -> Option<RangeInclusive<usize>> {
+        let time = self.edit_time();
+        let mut changed: Option<RangeInclusive<usize>> = None;
+        for i in 0..self.knots.len() {
+            if update_auto_slopes(&mut self.knots, i) {
+                self.stamp_modified(i, time);
+                changed = Some(changed.map_or(i..=i, |range| *range.start()..=i));
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+
+    use crate::{Knot, KnotInterpolation, LookupCurve, TangentMode, TangentSide};
+
+    fn auto(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+        .with_tangent_mode(TangentSide::Left, TangentMode::Auto)
+        .with_tangent_mode(TangentSide::Right, TangentMode::Auto)
+    }
+
+    fn slopes(curve: &LookupCurve) -> Vec<(f32, f32)> {
+        curve
+            .knots()
+            .iter()
+            .map(|k| (k.left_tangent.slope, k.right_tangent.slope))
+            .collect()
+    }
+
+    #[test]
+    fn auto_slopes_follow_the_neighbors() {
+        let mut curve = LookupCurve::new(vec![
+            auto(0.0, 0.0),
+            auto(1.0, 1.0),
+            auto(2.0, 3.0),
+            auto(4.0, 3.0),
+        ]);
+        // Ends use the slope to their only neighbor
+        assert_eq!(
+            slopes(&curve),
+            [(1.0, 1.0), (1.5, 1.5), (2.0 / 3.0, 2.0 / 3.0), (0.0, 0.0)]
+        );
+        // The curve passes through its knots and is C1, also on knots
+        for knot in curve.knots() {
+            assert_eq!(curve.lookup(knot.position.x), knot.position.y);
+            let x = knot.position.x;
+            let (left, right) = (
+                curve.slope_at(x, TangentSide::Left),
+                curve.slope_at(x, TangentSide::Right),
+            );
+            if x > 0.0 && x < 4.0 {
+                assert!((left - right).abs() < 1e-4, "{x}: {left} != {right}");
+            }
+        }
+
+        let generation = curve.generation();
+        let moved = Knot {
+            position: Vec2::new(1.0, -1.0),
+            ..curve.knots()[1]
+        };
+        curve.modify_knot(1, moved);
+        assert_eq!(curve.generation(), generation + 1);
+        assert_eq!(
+            slopes(&curve),
+            [(-1.0, -1.0), (1.5, 1.5), (4.0 / 3.0, 4.0 / 3.0), (0.0, 0.0)]
+        );
+
+        curve.delete_knot(1);
+        assert_eq!(slopes(&curve)[0], (1.5, 1.5));
+        curve.add_knot(auto(3.0, 0.0));
+        assert_eq!(
+            slopes(&curve),
+            [(1.5, 1.5), (0.0, 0.0), (0.0, 0.0), (3.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn only_auto_sides_are_updated() {
+        let knot = auto(1.0, 1.0)
+            .with_tangent_mode(TangentSide::Right, TangentMode::Free)
+            .with_tangent_slope(TangentSide::Right, 5.0);
+        let mut curve = LookupCurve::new(vec![auto(0.0, 0.0), knot, auto(2.0, 0.0)]);
+        assert_eq!(slopes(&curve)[1], (0.0, 5.0));
+
+        // Setting the slope of an auto tangent makes it aligned
+        let edited = curve.knots()[0].with_tangent_slope(TangentSide::Left, 2.0);
+        assert_eq!(edited.left_tangent.mode, TangentMode::Aligned);
+        assert_eq!(edited.right_tangent.mode, TangentMode::Aligned);
+        curve.modify_knot(0, edited);
+        assert_eq!(slopes(&curve)[0], (2.0, 2.0));
+
+        // Single knots and knots sharing x with all neighbors are flat
+        assert_eq!(
+            slopes(&LookupCurve::new(vec![auto(1.0, 2.0)])),
+            [(0.0, 0.0)]
+        );
+        let stacked = LookupCurve::new(vec![auto(1.0, 0.0), auto(1.0, 2.0)]);
+        assert_eq!(slopes(&stacked), [(0.0, 0.0), (0.0, 0.0)]);
+    }
+
+    #[test]
+    fn auto_tangents_keep_strict_curves_monotonic() {
+        let mut curve = LookupCurve::new(vec![
+            auto(0.0, 0.0).with_tangent_weight(TangentSide::Right, Some(0.9)),
+            auto(0.1, 1.0).with_tangent_weight(TangentSide::Left, Some(0.9)),
+            auto(1.0, 0.0),
+        ])
+        .with_strict_monotonic_x();
+        curve.modify_knot(
+            1,
+            Knot {
+                position: Vec2::new(0.2, 2.0),
+                ..curve.knots()[1]
+            },
+        );
+        assert_eq!(slopes(&curve)[1], (0.0, 0.0));
+        let xs: Vec<f32> = (0..=200).map(|i| i as f32 / 200.0).collect();
+        for pair in xs.windows(2) {
+            let (a, b) = (curve.lookup(pair[0]), curve.lookup(pair[1]));
+            assert!(a.is_finite() && b.is_finite());
+            // No jumps from a segment doubling back on x
+            assert!((a - b).abs() < 0.2, "{}: {a} -> {b}", pair[0]);
+        }
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn loaded_auto_tangents_are_updated() {
+        let curve = LookupCurve::new(vec![auto(0.0, 0.0), auto(1.0, 2.0)]);
+        let stale = ron::to_string(&curve)
+            .unwrap()
+            .replacen("slope:2.0", "slope:10.0", 1);
+        assert!(stale.contains("slope:10.0"));
+        let loaded: LookupCurve = ron::from_str(&stale).unwrap();
+        assert_eq!(slopes(&loaded), [(2.0, 2.0), (2.0, 2.0)]);
+    }
+}
@@ -0,0 +1,177 @@
+use bevy_math::Vec2;
+
+use crate::LookupCurve;
+
+/// How [LookupCurve::merge_coincident_knots] chooses y of the kept knot
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum MergeY {
+    /// Keep y of the first knot, which is the value sampling already returns at its x
+    #[default]
+    KeepFirst,
+    /// Move the kept knot to the average y of the merged knots
+    Average,
+}
+
+impl LookupCurve {
+    /// Returns groups of knots that coincide within `tolerance` on each axis, as indices in increasing order. Every
+    /// group has at least two knots.
+    ///
+    /// A group holds the knots within `tolerance` of its first knot, which is the first knot not in an earlier group.
+    /// So knots further apart than `tolerance` are never grouped through knots between them.
+    pub fn coincident_knots(&self, tolerance: Vec2) -> Vec<Vec<usize>> {
+        let mut grouped = vec![false; self.knots.len()];
+        let mut groups = Vec::new();
+        for (i, first) in self.knots.iter().enumerate() {
+            if grouped[i] {
+                continue;
+            }
+            let mut group = vec![i];
+            for (j, knot) in self.knots.iter().enumerate().skip(i + 1) {
+                let delta = (knot.position - first.position).abs();
+                if delta.x > tolerance.x {
+                    break;
+                }
+                if !grouped[j] && delta.y <= tolerance.y {
+                    grouped[j] = true;
+                    group.push(j);
+                }
+            }
+            if group.len() > 1 {
+                groups.push(group);
+            }
+        }
+        groups
+    }
+
+    /// Merges each group of [LookupCurve::coincident_knots] into its first knot, and returns the ids of the removed
+    /// knots.
+    ///
+    /// The first knot of a group is the one sampling uses at its x. It keeps its id, x, tangents and interpolation,
+    /// and y follows `merge_y`.
+    pub fn merge_coincident_knots(&mut self, tolerance: Vec2, merge_y: MergeY) -> Vec<usize> {
+        let groups = self.coincident_knots(tolerance);
+        self.merge_knots(&groups, merge_y)
+    }
+
+    /// Merges groups of knot indices into the first knot of each group, see [LookupCurve::merge_coincident_knots]
+    pub(crate) fn merge_knots(&mut self, groups: &[Vec<usize>], merge_y: MergeY) -> Vec<usize> {
+        if groups.iter().all(|group| group.len() < 2) {
+            return Vec::new();
+        }
+        let time = self.edit_time();
+        let mut removed = vec![false; self.knots.len()];
+        for group in groups {
+            let Some((&first, rest)) = group.split_first() else {
+                continue;
+            };
+            if merge_y == MergeY::Average {
+                let sum: f32 = group.iter().map(|&i| self.knots[i].position.y).sum();
+                let y = sum / group.len() as f32;
+                if y != self.knots[first].position.y {
+                    self.knots[first].position.y = y;
+                    self.stamp_modified(first, time);
+                }
+            }
+            for &i in rest {
+                removed[i] = true;
+            }
+        }
+
+        let mut removed_ids = Vec::new();
+        let mut i = 0;
+        self.knots.retain(|knot| {
+            let keep = !removed[i];
+            i += 1;
+            if !keep {
+                removed_ids.push(knot.id);
+            }
+            keep
+        });
+        self.bump_generation();
+        self.bump_order_epoch();
+        if self.strict_monotonic_x {
+            self.enforce_monotonic_x();
+        }
+        self.refresh_auto_tangents();
+        self.check_consistency();
+        removed_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, KnotInterpolation};
+
+    fn test_curve() -> LookupCurve {
+        let knot = |x: f32, y: f32| Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        };
+        LookupCurve::new(vec![
+            knot(0.0, 0.0),
+            knot(1.0, 1.0),
+            knot(1.001, 1.002),
+            // A step, not coincident with the knots before
+            knot(1.002, 3.0),
+            knot(1.003, 0.999),
+            knot(2.0, 0.0),
+            knot(2.0, 0.0),
+        ])
+    }
+
+    #[test]
+    fn coincident_knots_are_grouped() {
+        let curve = test_curve();
+        let tolerance = Vec2::new(0.01, 0.01);
+        assert_eq!(
+            curve.coincident_knots(tolerance),
+            [vec![1, 2, 4], vec![5, 6]]
+        );
+        // Zooming in separates the knots
+        assert_eq!(
+            curve.coincident_knots(Vec2::new(0.0015, 0.01)),
+            [vec![1, 2], vec![5, 6]]
+        );
+        assert_eq!(curve.coincident_knots(Vec2::ZERO), [vec![5, 6]]);
+        assert!(LookupCurve::default()
+            .coincident_knots(tolerance)
+            .is_empty());
+    }
+
+    #[test]
+    fn merged_knots_keep_the_first() {
+        for merge_y in [MergeY::KeepFirst, MergeY::Average] {
+            let mut curve = test_curve();
+            let ids: Vec<_> = curve.knots().iter().map(|k| k.id).collect();
+            let generation = curve.generation();
+
+            let removed = curve.merge_coincident_knots(Vec2::splat(0.01), merge_y);
+            assert_eq!(removed, [ids[2], ids[4], ids[6]]);
+            let remaining: Vec<_> = curve.knots().iter().map(|k| k.id).collect();
+            assert_eq!(remaining, [ids[0], ids[1], ids[3], ids[5]]);
+            assert_eq!(curve.generation(), generation + 1);
+
+            let kept = curve.knots()[1];
+            let expected_y = match merge_y {
+                MergeY::KeepFirst => 1.0,
+                MergeY::Average => (1.0 + 1.002 + 0.999) / 3.0,
+            };
+            assert_eq!(kept.position, Vec2::new(1.0, expected_y));
+            // Sampling at the merged x gives the kept knot
+            for knot in curve.knots() {
+                let y = curve.lookup(knot.position.x);
+                assert!((y - knot.position.y).abs() < 1e-6, "{y} {knot:?}");
+            }
+
+            // Nothing left to merge
+            let generation = curve.generation();
+            assert!(curve
+                .merge_coincident_knots(Vec2::splat(0.01), merge_y)
+                .is_empty());
+            assert_eq!(curve.generation(), generation);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::LookupCurve;
+
+/// Sharing curves between threads relies on the curve staying free of interior mutability
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<LookupCurve>();
+};
+
+impl LookupCurve {
+    /// Panics in debug builds if the invariants other methods rely on do not hold: the knots are ordered by x
+    /// and their ids are unique.
+    ///
+    /// With the `consistency_checks` feature this is called at the end of every mutating method.
+    #[cfg(feature = "consistency_checks")]
+    pub fn debug_assert_consistent(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        if let Some(i) = self
+            .knots
+            .windows(2)
+            .position(|pair| pair[0].position.x > pair[1].position.x)
+        {
+            panic!(
+                "knots {i} and {} of {} are not ordered by x",
+                i + 1,
+                self.name_or_default()
+            );
+        }
+        let mut ids: Vec<usize> = self.knots.iter().map(|knot| knot.id).collect();
+        ids.sort_unstable();
+        if let Some(pair) = ids.windows(2).find(|pair| pair[0] == pair[1]) {
+            panic!(
+                "knot id {} is used twice in {}",
+                pair[0],
+                self.name_or_default()
+            );
+        }
+    }
+
+    /// Called at the end of mutating methods
+    #[inline]
+    pub(crate) fn check_consistency(&self) {
+        #[cfg(feature = "consistency_checks")]
+        self.debug_assert_consistent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use bevy_math::Vec2;
+
+    use crate::{Knot, KnotInterpolation, LookupCache, LookupCurve};
+
+    #[test]
+    fn readers_never_see_a_partial_mutation() {
+        let curve = Arc::new(RwLock::new(LookupCurve::new(
+            (0..8)
+                .map(|i| Knot {
+                    position: Vec2::new(i as f32, (i % 3) as f32),
+                    interpolation: KnotInterpolation::Cubic,
+                    ..Default::default()
+                })
+                .collect(),
+        )));
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let curve = curve.clone();
+                scope.spawn(move || {
+                    // Reused across mutations, it is invalidated by the generation of the curve
+                    let mut cache = LookupCache::new();
+                    for n in 0..2000 {
+                        let curve = curve.read().unwrap();
+                        // Snapshots taken while the lock is held are identical to the shared curve
+                        let snapshot = curve.clone();
+                        let x = (n % 100) as f32 * 0.08;
+                        assert_eq!(curve.lookup_cached(x, &mut cache), snapshot.lookup(x));
+                        assert!(curve
+                            .knots()
+                            .windows(2)
+                            .all(|pair| pair[0].position.x <= pair[1].position.x));
+                        #[cfg(feature = "consistency_checks")]
+                        curve.debug_assert_consistent();
+                    }
+                });
+            }
+
+            let curve = curve.clone();
+            scope.spawn(move || {
+                for n in 0..500 {
+                    let mut curve = curve.write().unwrap();
+                    let i = n % curve.knots().len();
+                    let knot = curve.knots()[i];
+                    // Moves knots past their neighbors, which reorders the knots
+                    let x = (knot.position.x + 2.5) % 8.0;
+                    curve.modify_knot(
+                        i,
+                        Knot {
+                            position: Vec2::new(x, knot.position.y),
+                            ..knot
+                        },
+                    );
+                    if n % 7 == 0 {
+                        let i = curve.add_knot(Knot {
+                            position: Vec2::new(x * 0.5, 1.0),
+                            ..Default::default()
+                        });
+                        curve.delete_knot(i);
+                    }
+                }
+            });
+        });
+        assert_eq!(curve.read().unwrap().knots().len(), 8);
+    }
+
+    #[cfg(feature = "consistency_checks")]
+    #[test]
+    #[should_panic(expected = "not ordered by x")]
+    fn unordered_knots_are_reported() {
+        let mut curve = LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::ONE,
+                ..Default::default()
+            },
+        ]);
+        curve.knots.swap(0, 1);
+        curve.debug_assert_consistent();
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{CubicSegment, KnotInterpolation, LookupCurve};
+
+/// Iterations of bisection used to find crossings in cubic segments
+const BISECTION_ITERS: usize = 40;
+
+impl LookupCurve {
+    /// Returns the x values where the curve reaches `y`, in increasing order.
+    ///
+    /// Where the curve stays at `y` over a range of x, only the start of the range is returned. Where
+    /// [KnotInterpolation::Constant] and [KnotInterpolation::ConstantNext] segments jump past `y`, the x of the jump
+    /// is returned. The curve outside of the knot range is not considered.
+    pub fn crossings(&self, y: f32) -> Vec<f32> {
+        let mut xs = Vec::new();
+        for pair in self.knots.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if a.position.y == y {
+                xs.push(a.position.x);
+            }
+            let between = |y_a: f32, y_b: f32| y_a.min(y_b) < y && y < y_a.max(y_b);
+            match a.interpolation {
+                KnotInterpolation::Constant => {
+                    if between(a.position.y, b.position.y) {
+                        xs.push(b.position.x);
+                    }
+                }
+                KnotInterpolation::ConstantNext => {
+                    if between(a.position.y, b.position.y) {
+                        xs.push(a.position.x);
+                    }
+                }
+                KnotInterpolation::Linear | KnotInterpolation::Power { .. } => {
+                    if between(a.position.y, b.position.y) {
+                        let mut s = (y - a.position.y) / (b.position.y - a.position.y);
+                        if let KnotInterpolation::Power { exponent } = a.interpolation {
+                            s = s.powf(exponent.recip());
+                        }
+                        xs.push(a.position.x + s * (b.position.x - a.position.x));
+                    }
+                }
+                KnotInterpolation::Cubic => {
+                    let segment = CubicSegment::from_bezier_points(a.compute_bezier_to(b));
+                    cubic_crossings(&segment, y, &mut xs);
+                }
+            }
+        }
+        if let Some(last) = self.knots.last() {
+            if last.position.y == y {
+                xs.push(last.position.x);
+            }
+        }
+        xs.sort_by(f32::total_cmp);
+        xs.dedup();
+        xs
+    }
+}
+
+/// Pushes x of the points strictly within `segment` where it reaches `y`
+fn cubic_crossings(segment: &CubicSegment, y: f32, xs: &mut Vec<f32>) {
+    let f = |t: f32| segment.position(t).y - y;
+    // y is monotonic between its extrema, so each interval contains at most one crossing
+    let mut bounds = vec![0.0];
+    bounds.extend(segment.extrema_y().into_iter().flatten());
+    bounds.push(1.0);
+    for pair in bounds.windows(2) {
+        let (mut t0, mut t1) = (pair[0], pair[1]);
+        if t1 < 1.0 && f(t1) == 0.0 {
+            // Touching y at an extremum
+            xs.push(segment.position(t1).x);
+            continue;
+        }
+        let (f0, f1) = (f(t0), f(t1));
+        if f0 == 0.0 || f0.signum() == f1.signum() {
+            continue;
+        }
+        for _ in 0..BISECTION_ITERS {
+            let t = (t0 + t1) / 2.0;
+            if f(t).signum() == f0.signum() {
+                t0 = t;
+            } else {
+                t1 = t;
+            }
+        }
+        xs.push(segment.position((t0 + t1) / 2.0).x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn linear_and_constant_crossings() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Constant),
+            knot(2.0, -1.0, KnotInterpolation::Linear),
+            knot(3.0, 0.5, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(curve.crossings(0.5), [0.5, 2.0, 3.0]);
+        // The jump of the constant segment
+        assert_eq!(curve.crossings(0.0), [0.0, 2.0, 2.0 + 2.0 / 3.0]);
+        assert_eq!(curve.crossings(1.0), [1.0]);
+        assert!(curve.crossings(2.0).is_empty());
+    }
+
+    #[test]
+    fn power_and_constant_next_crossings() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Power { exponent: 2.0 }),
+            knot(1.0, 1.0, KnotInterpolation::ConstantNext),
+            knot(2.0, 3.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(curve.crossings(0.25), [0.5]);
+        // The jump right after the knot
+        assert_eq!(curve.crossings(2.0), [1.0]);
+        assert_eq!(curve.crossings(3.0), [2.0]);
+    }
+
+    #[test]
+    fn flat_segments_cross_once() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 1.0, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+            knot(2.0, 0.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(curve.crossings(1.0), [0.0, 1.0]);
+    }
+
+    #[test]
+    fn cubic_crossings_match_lookup() {
+        let mut a = knot(0.0, 0.0, KnotInterpolation::Cubic);
+        a.right_tangent.slope = 6.0;
+        let mut b = knot(1.0, 0.0, KnotInterpolation::Cubic);
+        b.left_tangent.slope = 6.0;
+        let mut curve = LookupCurve::new(vec![a, b, knot(2.0, 2.0, KnotInterpolation::Linear)]);
+        for weighted in [false, true] {
+            if weighted {
+                let knot = curve.knots()[0];
+                curve.modify_knot(
+                    0,
+                    knot.with_tangent_weight(crate::TangentSide::Right, Some(0.5)),
+                );
+            }
+            // Rises, falls below 0 and rises again
+            let xs = curve.crossings(0.1);
+            assert_eq!(xs.len(), 3, "{xs:?}");
+            for x in xs {
+                assert!((curve.lookup(x) - 0.1).abs() < 1e-4, "{x}");
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{hermite_coefficients, CubicSegment, KnotInterpolation, LookupCurve};
+
+/// Self-contained description of how a [LookupCurve] is evaluated, see [LookupCurve::describe].
+///
+/// Evaluating the description as documented gives the same results as [LookupCurve::lookup], bit for bit:
+/// - If there are no segments, or `x` is at or before the start of the domain, the result is `value_before`.
+/// - If `x` is at or after the end of the domain, the result is `value_after`.
+/// - Otherwise the first segment with `x_start < x <= x_end` is evaluated, see [SegmentEvaluation].
+///
+/// All math is in `f32`, in the order given by the formulas, without fused multiply-add.
+/// Lookup curves have no transform or quantization applied when sampling, so there are no settings for these.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurveDescription {
+    /// x of the first and last knot, `None` if the curve has no knots
+    pub domain: Option<(f32, f32)>,
+    /// Value at and before the start of the domain, the y of the first knot. 0 if the curve has no knots.
+    pub value_before: f32,
+    /// Value at and after the end of the domain, the y of the last knot. 0 if the curve has no knots.
+    pub value_after: f32,
+    /// Max number of Newton iterations for [SegmentEvaluation::Parametric] segments
+    pub max_iters: u8,
+    /// Max error in x allowed before stopping Newton iteration for [SegmentEvaluation::Parametric] segments
+    pub max_error: f32,
+    /// Segments between each pair of knots, in order of x. Segments might have zero width.
+    pub segments: Vec<SegmentDescription>,
+}
+
+/// A segment between two knots of a [CurveDescription]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SegmentDescription {
+    pub x_start: f32,
+    pub x_end: f32,
+    pub evaluation: SegmentEvaluation,
+}
+
+/// How a segment is evaluated at `x`.
+///
+/// Unless noted otherwise `s = (x - x_start) / (x_end - x_start)`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum SegmentEvaluation {
+    /// [KnotInterpolation::Constant] and [KnotInterpolation::ConstantNext]: `y = value`
+    Constant { value: f32 },
+    /// [KnotInterpolation::Linear]: `y = intercept + slope * s`
+    Linear { intercept: f32, slope: f32 },
+    /// [KnotInterpolation::Power]: `y = intercept + slope * s.powf(exponent)`
+    Power {
+        intercept: f32,
+        slope: f32,
+        exponent: f32,
+    },
+    /// Unweighted [KnotInterpolation::Cubic]: `y = c[0] + s * (c[1] + s * (c[2] + s * c[3]))`
+    Cubic { coefficients: [f32; 4] },
+    /// Weighted [KnotInterpolation::Cubic], a parametric cubic where `p(t) = c[0] + c[1] * t + c[2] * t.powi(2) + c[3] * t.powi(3)`
+    /// for both `x` and `y`.
+    ///
+    /// `x` is first clamped between `p_x(0.0)` and `p_x(1.0)`. `t` is found with Newton's method, starting with
+    /// `t = x.clamp(0.0, 1.0)` and `low = 0.0`, `high = 1.0`. For each of at most `max_iters` iterations:
+    /// `error = p_x(t) - x`, stop if `error.abs() <= max_error`. Otherwise set `high = t` if
+    /// `(error > 0.0) == (p_x(1.0) >= p_x(0.0))`, else `low = t`. Then with
+    /// `slope = x[1] + x[2] * 2.0 * t + x[3] * 3.0 * t.powi(2)` and
+    /// `next = (t - error / slope).clamp(0.0, 1.0)`, set `t = next` if `slope.abs() > f32::EPSILON` and
+    /// `low <= next <= high`, else `t = 0.5 * (low + high)`. The result is `p_y` of the last `t` where `error` was
+    /// computed, or of the initial `t` if `max_iters` is 0.
+    Parametric { x: [f32; 4], y: [f32; 4] },
+}
+
+impl LookupCurve {
+    /// Describes exactly how the curve is evaluated, for reproducing [LookupCurve::lookup] outside of this crate,
+    /// see [CurveDescription].
+    pub fn describe(&self) -> CurveDescription {
+        let segments = self
+            .knots
+            .windows(2)
+            .map(|pair| {
+                let (a, b) = (&pair[0], &pair[1]);
+                let evaluation = match a.interpolation {
+                    KnotInterpolation::Constant => SegmentEvaluation::Constant {
+                        value: a.position.y,
+                    },
+                    // The segment is only evaluated after its start
+                    KnotInterpolation::ConstantNext => SegmentEvaluation::Constant {
+                        value: b.position.y,
+                    },
+                    KnotInterpolation::Linear => SegmentEvaluation::Linear {
+                        intercept: a.position.y,
+                        slope: b.position.y - a.position.y,
+                    },
+                    KnotInterpolation::Power { exponent } => SegmentEvaluation::Power {
+                        intercept: a.position.y,
+                        slope: b.position.y - a.position.y,
+                        exponent,
+                    },
+                    KnotInterpolation::Cubic
+                        if a.right_tangent.weight.is_some() || b.left_tangent.weight.is_some() =>
+                    {
+                        let coeff = CubicSegment::from_bezier_points(a.compute_bezier_to(b)).coeff;
+                        SegmentEvaluation::Parametric {
+                            x: coeff.map(|c| c.x),
+                            y: coeff.map(|c| c.y),
+                        }
+                    }
+                    KnotInterpolation::Cubic => SegmentEvaluation::Cubic {
+                        coefficients: hermite_coefficients(a, b),
+                    },
+                };
+                SegmentDescription {
+                    x_start: a.position.x,
+                    x_end: b.position.x,
+                    evaluation,
+                }
+            })
+            .collect();
+
+        CurveDescription {
+            domain: self
+                .knots
+                .first()
+                .zip(self.knots.last())
+                .map(|(first, last)| (first.position.x, last.position.x)),
+            value_before: self.knots.first().map_or(0.0, |knot| knot.position.y),
+            value_after: self.knots.last().map_or(0.0, |knot| knot.position.y),
+            max_iters: self.max_iters,
+            max_error: self.max_error,
+            segments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, Tangent};
+    use bevy_math::Vec2;
+
+    /// Evaluates `description` using only what is documented on [CurveDescription]
+    fn evaluate(description: &CurveDescription, x: f32) -> f32 {
+        let Some((start, end)) = description.domain else {
+            return description.value_before;
+        };
+        if description.segments.is_empty() || x <= start {
+            return description.value_before;
+        }
+        if x >= end {
+            return description.value_after;
+        }
+        let segment = description
+            .segments
+            .iter()
+            .find(|segment| segment.x_start < x && x <= segment.x_end)
+            .unwrap();
+        let s = (x - segment.x_start) / (segment.x_end - segment.x_start);
+        match segment.evaluation {
+            SegmentEvaluation::Constant { value } => value,
+            SegmentEvaluation::Linear { intercept, slope } => intercept + slope * s,
+            SegmentEvaluation::Power {
+                intercept,
+                slope,
+                exponent,
+            } => intercept + slope * s.powf(exponent),
+            SegmentEvaluation::Cubic { coefficients: c } => {
+                c[0] + s * (c[1] + s * (c[2] + s * c[3]))
+            }
+            SegmentEvaluation::Parametric { x: cx, y: cy } => {
+                let p = |c: [f32; 4], t: f32| c[0] + c[1] * t + c[2] * t.powi(2) + c[3] * t.powi(3);
+                let (x_start, x_end) = (p(cx, 0.0), p(cx, 1.0));
+                let x = x.clamp(x_start.min(x_end), x_start.max(x_end));
+                let (mut low, mut high) = (0.0, 1.0);
+                let mut t = x.clamp(0.0, 1.0);
+                let mut t_result = t;
+                for _ in 0..description.max_iters {
+                    t_result = t;
+                    let error = p(cx, t) - x;
+                    if error.abs() <= description.max_error {
+                        break;
+                    }
+                    if (error > 0.0) == (x_end >= x_start) {
+                        high = t;
+                    } else {
+                        low = t;
+                    }
+                    let slope = cx[1] + cx[2] * 2.0 * t + cx[3] * 3.0 * t.powi(2);
+                    let next = (t - error / slope).clamp(0.0, 1.0);
+                    t = if slope.abs() > f32::EPSILON && (low..=high).contains(&next) {
+                        next
+                    } else {
+                        0.5 * (low + high)
+                    };
+                }
+                p(cy, t_result)
+            }
+        }
+    }
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation, slope: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+        .with_tangent_slope(crate::TangentSide::Left, slope)
+    }
+
+    fn fixtures() -> Vec<LookupCurve> {
+        let weighted = Tangent {
+            slope: 1.5,
+            weight: Some(0.6),
+            ..Default::default()
+        };
+        vec![
+            LookupCurve::default(),
+            LookupCurve::new(vec![knot(0.3, 0.7, KnotInterpolation::Linear, 0.0)]),
+            LookupCurve::new(vec![
+                knot(0.0, 0.0, KnotInterpolation::Linear, 0.0),
+                knot(0.25, 0.8, KnotInterpolation::Constant, 0.0),
+                knot(0.5, 0.3, KnotInterpolation::Cubic, 2.0),
+                knot(0.5, 0.9, KnotInterpolation::Cubic, -1.0),
+                knot(1.0, 0.1, KnotInterpolation::Linear, 0.5),
+            ]),
+            LookupCurve::new(vec![
+                Knot {
+                    right_tangent: weighted,
+                    ..knot(0.0, 0.0, KnotInterpolation::Cubic, 0.0)
+                },
+                Knot {
+                    left_tangent: weighted,
+                    ..knot(1.0, 1.0, KnotInterpolation::Cubic, 0.0)
+                },
+                knot(4.0, -2.0, KnotInterpolation::Linear, 0.0),
+            ]),
+            LookupCurve::new(vec![
+                knot(-3.0, 10.0, KnotInterpolation::Cubic, 0.0),
+                Knot {
+                    left_tangent: weighted,
+                    ..knot(7.0, -5.0, KnotInterpolation::Cubic, 3.0)
+                },
+                knot(20.0, 1.0, KnotInterpolation::Cubic, -0.2),
+            ])
+            .with_max_iters(5),
+        ]
+    }
+
+    #[test]
+    fn reference_evaluator_matches_lookup() {
+        for curve in fixtures() {
+            let description = curve.describe();
+            let (start, end) = description.domain.unwrap_or((0.0, 1.0));
+            let mut xs: Vec<f32> = (-10..=1010)
+                .map(|i| start + (end - start) * i as f32 / 1000.0)
+                .collect();
+            xs.extend(curve.knots().iter().map(|knot| knot.position.x));
+            for x in xs {
+                assert_eq!(
+                    evaluate(&description, x).to_bits(),
+                    curve.lookup(x).to_bits(),
+                    "x = {x}, {description:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn describes_segments_in_order() {
+        let description = fixtures()[2].describe();
+        assert_eq!(description.domain, Some((0.0, 1.0)));
+        assert_eq!(description.value_before, 0.0);
+        assert_eq!(description.value_after, 0.1);
+        let kinds: Vec<_> = description
+            .segments
+            .iter()
+            .map(|segment| std::mem::discriminant(&segment.evaluation))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                SegmentEvaluation::Linear {
+                    intercept: 0.0,
+                    slope: 0.0
+                },
+                SegmentEvaluation::Constant { value: 0.0 },
+                SegmentEvaluation::Cubic {
+                    coefficients: [0.0; 4]
+                },
+                SegmentEvaluation::Cubic {
+                    coefficients: [0.0; 4]
+                },
+            ]
+            .iter()
+            .map(std::mem::discriminant)
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            description.segments[1].evaluation,
+            SegmentEvaluation::Constant { value: 0.8 }
+        );
+        assert_eq!(
+            description.segments[2].x_start,
+            description.segments[2].x_end
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use bevy_math::Vec2;
+
+use crate::{unique_knot_id, Knot};
+
+/// How ids are assigned to newly created knots, see [Knot::id]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum IdStrategy {
+    /// Next id from a global counter, depends on what happened earlier in the session
+    #[default]
+    Sequential,
+    /// Id derived from the (quantized) position of the knot, stable across sessions.
+    ///
+    /// If another knot already has the id, the next free id is used.
+    PositionHash,
+}
+
+/// Ids derived from positions have the highest bit set, so they never collide with sequential ids.
+const POSITION_HASH_BIT: usize = 1 << (usize::BITS - 1);
+
+impl IdStrategy {
+    /// Returns an id for a new knot at `position`, that is not used by any of the `existing` knots.
+    pub fn assign(&self, position: Vec2, existing: &[Knot]) -> usize {
+        match self {
+            IdStrategy::Sequential => unique_knot_id(),
+            IdStrategy::PositionHash => {
+                let mut id = position_hash(position) | POSITION_HASH_BIT;
+                while existing.iter().any(|knot| knot.id == id) {
+                    id = id.wrapping_add(1) | POSITION_HASH_BIT;
+                }
+                id
+            }
+        }
+    }
+}
+
+/// Deterministic (FNV-1a) hash of the bits of a position, identical on all platforms for the same input
+fn position_hash(position: Vec2) -> usize {
+    // Treat -0.0 as 0.0, so that equal positions always hash equally
+    let bits = [position.x + 0.0, position.y + 0.0].map(f32::to_bits);
+    fnv1a(bits.iter().flat_map(|b| b.to_le_bytes())) as usize
+}
+
+/// FNV-1a hash of `bytes`, identical on all platforms for the same input
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.into_iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Rounds `value` to the nearest multiple of `step`. Returns `value` unchanged if `step` is not positive.
+///
+/// Steps with a few decimals, like `0.05`, and reciprocals of integers, like `1.0 / 60.0`, are counted in a scaled
+/// integer domain, so that the result is the `f32` nearest to the exact multiple (`0.3` rather than `0.30000001`),
+/// even though the step itself is not exact in `f32`.
+pub fn quantize(value: f32, step: f32) -> f32 {
+    if step <= 0.0 || !step.is_finite() {
+        return value;
+    }
+    let (numerator, denominator) = step_fraction(step);
+    let multiple = (value as f64 * denominator / numerator).round();
+    // Adding zero turns -0.0 into 0.0, which would otherwise be written to RON as is
+    (multiple * numerator / denominator) as f32 + 0.0
+}
+
+/// Largest numerator of a decimal step, larger numerators are too close to the precision of `f32` to tell apart
+const MAX_STEP_NUMERATOR: f64 = 1e5;
+/// Relative difference to an integer up to which a scaled step counts as the integer, above the precision of `f32`
+const STEP_TOLERANCE: f64 = 1e-7;
+
+/// `step` as a fraction of integers, `(5, 100)` for `0.05` and `(1, 60)` for `1.0 / 60.0`, or `(step, 1)` if it is
+/// neither a short decimal nor a reciprocal
+fn step_fraction(step: f32) -> (f64, f64) {
+    let step = step as f64;
+    let near_integer = |value: f64| (value - value.round()).abs() <= value * STEP_TOLERANCE;
+    let mut denominator = 1.0;
+    while step * denominator <= MAX_STEP_NUMERATOR {
+        let numerator = step * denominator;
+        if near_integer(numerator) && numerator >= 0.5 {
+            return (numerator.round(), denominator);
+        }
+        denominator *= 10.0;
+    }
+    let reciprocal = 1.0 / step;
+    if reciprocal >= 1.0 && near_integer(reciprocal) {
+        return (1.0, reciprocal.round());
+    }
+    (step, 1.0)
+}
+
+/// Rounds both coordinates of `position` using [quantize]
+pub fn quantize_position(position: Vec2, step: f32) -> Vec2 {
+    Vec2::new(quantize(position.x, step), quantize(position.y, step))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantizes_to_nearest_step() {
+        assert_eq!(quantize(0.3 + f32::EPSILON, 0.001), 0.3);
+        assert_eq!(quantize(0.2999996, 0.001), 0.3);
+        assert_eq!(quantize(-1.23456, 0.01), -1.23);
+        assert_eq!(quantize(7.4, 0.5), 7.5);
+        assert!(quantize(-0.0001, 0.001).is_sign_positive());
+        assert_eq!(quantize(0.123456, 0.0), 0.123456);
+        assert_eq!(quantize(0.123456, -1.0), 0.123456);
+    }
+
+    #[test]
+    fn quantizes_exactly_in_scaled_integers() {
+        assert_eq!(step_fraction(0.1), (1.0, 10.0));
+        assert_eq!(step_fraction(0.05), (5.0, 100.0));
+        assert_eq!(step_fraction(2.5), (25.0, 10.0));
+        assert_eq!(step_fraction(1.0 / 60.0), (1.0, 60.0));
+        assert_eq!(step_fraction(1.0 / 3.0), (1.0, 3.0));
+        assert_eq!(step_fraction(1e-3), (1.0, 1000.0));
+
+        // Multiples of steps that are not exact in f32 are the nearest f32 to the exact value
+        let sum = (0..7).fold(0.0f32, |sum, _| sum + 0.1);
+        assert_ne!(sum, 0.7);
+        assert_eq!(quantize(sum, 0.1), 0.7);
+        assert_eq!(quantize(0.3, 0.1), 0.3);
+        assert_eq!(quantize(0.51, 1.0 / 60.0), 31.0 / 60.0);
+        assert_eq!(quantize(0.5 + 1e-4, 1.0 / 60.0), 0.5);
+        assert_eq!(quantize(1.0 / 60.0 * 90.0, 1.0 / 60.0), 1.5);
+        // Quantized values stay as they are
+        for i in -200..200 {
+            let value = quantize(i as f32 * 0.0173, 1.0 / 60.0);
+            assert_eq!(quantize(value, 1.0 / 60.0), value);
+        }
+        // Steps that are neither short decimals nor reciprocals are used as they are
+        assert_eq!(quantize(4.0, std::f32::consts::PI), std::f32::consts::PI);
+        assert_eq!(quantize(f32::INFINITY, 0.1), f32::INFINITY);
+    }
+
+    #[test]
+    fn position_hash_is_stable() {
+        let a = IdStrategy::PositionHash.assign(Vec2::new(0.3, 0.5), &[]);
+        let b = IdStrategy::PositionHash.assign(Vec2::new(0.3, 0.5), &[]);
+        assert_eq!(a, b);
+        assert_ne!(a, IdStrategy::PositionHash.assign(Vec2::new(0.5, 0.3), &[]));
+        assert_eq!(
+            IdStrategy::PositionHash.assign(Vec2::new(-0.0, 0.0), &[]),
+            IdStrategy::PositionHash.assign(Vec2::new(0.0, 0.0), &[])
+        );
+        assert!(a & POSITION_HASH_BIT != 0);
+    }
+
+    #[test]
+    fn position_hash_avoids_collisions() {
+        let position = Vec2::new(0.3, 0.5);
+        let mut knots = vec![];
+        for _ in 0..3 {
+            let id = IdStrategy::PositionHash.assign(position, &knots);
+            assert!(knots.iter().all(|k: &Knot| k.id != id));
+            knots.push(Knot {
+                position,
+                id,
+                ..Default::default()
+            });
+        }
+        assert_eq!(knots[1].id, knots[0].id + 1);
+    }
+
+    #[test]
+    fn sequential_is_unique() {
+        let a = IdStrategy::Sequential.assign(Vec2::ZERO, &[]);
+        let b = IdStrategy::Sequential.assign(Vec2::ZERO, &[]);
+        assert_ne!(a, b);
+    }
+
+    #[cfg(all(feature = "editor_egui", feature = "ron"))]
+    #[test]
+    fn sessions_with_same_edits_produce_identical_ron() {
+        use crate::editor::LookupCurveEguiEditor;
+        use crate::{KnotInterpolation, LookupCurve};
+
+        // Performs the same logical edits, with pointer noise depending on `noise`
+        let session = |noise: f32| {
+            let mut editor = LookupCurveEguiEditor::default();
+            editor.round_new_positions_to = Some(0.001);
+            editor.id_strategy = IdStrategy::PositionHash;
+            let mut curve = LookupCurve::new(vec![]);
+            for position in [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(0.3, 0.7),
+            ] {
+                let knot = editor.new_knot(
+                    &curve,
+                    position + Vec2::splat(noise),
+                    KnotInterpolation::Cubic,
+                );
+                curve.add_knot(knot);
+            }
+            // Dragging a knot, with the position rounded once the drag completes
+            let id = curve.knots()[1].id;
+            let knot = curve.knots()[1];
+            curve.modify_knot(
+                1,
+                Knot {
+                    position: knot.position + Vec2::new(0.0, 0.1 + noise),
+                    ..knot
+                },
+            );
+            assert!(editor.round_knot_positions(&mut curve, &[id]));
+            curve
+        };
+
+        let a = session(0.00000012);
+        // Sequential ids used in between must not affect the ids of the second session
+        let _ = IdStrategy::Sequential.assign(Vec2::ZERO, &[]);
+        let b = session(-0.00000031);
+
+        let ids = |curve: &LookupCurve| curve.knots().iter().map(|k| k.id).collect::<Vec<_>>();
+        assert_eq!(ids(&a), ids(&b));
+        assert_eq!(a.knots()[1].position, Vec2::new(0.3, 0.8));
+
+        let ron_a = ron::ser::to_string_pretty(&a, Default::default()).unwrap();
+        let ron_b = ron::ser::to_string_pretty(&b, Default::default()).unwrap();
+        assert_eq!(ron_a, ron_b);
+        assert!(!ron_a.contains("0.30000001"));
+    }
+}
@@ -0,0 +1,450 @@
+use std::ops::RangeInclusive;
+
+use crate::sample::hermite_coefficients;
+use crate::stats::CurveStats;
+use crate::{CubicSegment, Extrapolation, Knot, KnotInterpolation, LookupCurve};
+
+/// Max bisection steps of [LookupCurve::percentile_over], more than enough to reach f32 precision
+const PERCENTILE_ITERS: usize = 100;
+
+impl LookupCurve {
+    /// Integral of the curve over `range`, the signed area between the curve and y = 0.
+    ///
+    /// Exact apart from rounding, including weighted cubic segments, whose area is integrated over the bezier
+    /// parameter. Segments that fold back on the x axis, see [CurveStats::corrections], are integrated as drawn, which
+    /// might not exactly match [LookupCurve::lookup]. Outside of the knot range the curve is extrapolated like in
+    /// [LookupCurve::lookup]. A reversed range is empty.
+    pub fn integrate(&self, range: RangeInclusive<f32>) -> f32 {
+        let (start, end) = bounds(&range);
+        let line = |y: f32, slope: f32, d: f32| d * (y + 0.5 * slope * d);
+        let inside = |x: f32| self.integral_from_first(x);
+        self.cumulative(end, &inside, &line) - self.cumulative(start, &inside, &line)
+    }
+
+    /// Mean y of the curve over `range`, its integral divided by the width, see [LookupCurve::integrate].
+    ///
+    /// The expected value of the curve for x uniformly distributed over `range`. Ranges without width give the y
+    /// at their start.
+    pub fn mean_over(&self, range: RangeInclusive<f32>) -> f32 {
+        let (start, end) = bounds(&range);
+        if end <= start {
+            return self.lookup(start);
+        }
+        self.integrate(range) / (end - start)
+    }
+
+    /// Width of the parts of `range` where the curve is above `y`.
+    ///
+    /// Found from the [LookupCurve::crossings] of `y`, so exact up to the precision of the crossings. Outside of the
+    /// knot range the curve is extrapolated like in [LookupCurve::lookup]. A reversed range is empty.
+    pub fn measure_above(&self, range: RangeInclusive<f32>, y: f32) -> f32 {
+        let (start, end) = bounds(&range);
+        let line = |boundary_y: f32, slope: f32, d: f32| {
+            // Length of the t between 0 and d where the line is above y, negative for negative d
+            let (low, high) = (d.min(0.0), d.max(0.0));
+            let above = if slope == 0.0 {
+                if boundary_y > y {
+                    high - low
+                } else {
+                    0.0
+                }
+            } else {
+                let root = (y - boundary_y) / slope;
+                let (from, to) = if slope > 0.0 {
+                    (root.max(low), high)
+                } else {
+                    (low, root.min(high))
+                };
+                (to - from).max(0.0)
+            };
+            above * d.signum()
+        };
+        let crossings = self.crossings(y);
+        let inside = |x: f32| self.measure_above_from_first(x, y, &crossings);
+        self.cumulative(end, &inside, &line) - self.cumulative(start, &inside, &line)
+    }
+
+    /// The `p`-th percentile of the y of the curve, for x uniformly distributed over `range`. The curve is at or
+    /// below the percentile for at least a fraction `p` of the range.
+    ///
+    /// `p` is clamped between 0 and 1, where 0 gives the smallest and 1 the largest y over the range. Found by
+    /// bisecting over y with [LookupCurve::measure_above], so precise up to f32 rounding. Ranges without width give
+    /// the y at their start.
+    pub fn percentile_over(&self, range: RangeInclusive<f32>, p: f32) -> f32 {
+        let (start, end) = bounds(&range);
+        let width = end - start;
+        if width.is_nan() || width <= 0.0 {
+            return self.lookup(start);
+        }
+        let p = p.clamp(0.0, 1.0);
+        // Fraction of the range at or below y reaches p, and is not 0 for the smallest y
+        let reached = |y: f32| {
+            let below = 1.0 - self.measure_above(start..=end, y) / width;
+            below >= p && below > 0.0
+        };
+
+        // Every y of the curve over the range is within these bounds: the knot range covers repeated and mirrored
+        // extrapolation, and linear extrapolation is largest at the ends of the range
+        let (mut low, mut high) = CurveStats::compute(self).range.unwrap_or((0.0, 0.0));
+        for y in [self.lookup(start), self.lookup(end)] {
+            (low, high) = (low.min(y), high.max(y));
+        }
+        if reached(low) {
+            return low;
+        }
+        for _ in 0..PERCENTILE_ITERS {
+            let mid = low + 0.5 * (high - low);
+            if mid <= low || mid >= high {
+                break;
+            }
+            if reached(mid) {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        high
+    }
+
+    /// Fractions of `range` where the y of the curve falls into each of `buckets` buckets of equal size, from
+    /// [LookupCurve::percentile_over] 0 to 1. For plotting the distribution of the curve for x uniformly distributed
+    /// over `range`.
+    ///
+    /// Each bucket holds the y above its lower edge up to and including its upper edge, and the first bucket also
+    /// holds the smallest y. The fractions add up to 1. Curves that are constant over the range have all of it in
+    /// the first bucket.
+    pub fn value_histogram(&self, range: RangeInclusive<f32>, buckets: usize) -> Vec<f32> {
+        let (start, end) = bounds(&range);
+        let mut histogram = vec![0.0; buckets];
+        let width = end - start;
+        let Some(first) = histogram.first_mut() else {
+            return histogram;
+        };
+        *first = 1.0;
+        if width.is_nan() || width <= 0.0 {
+            return histogram;
+        }
+        let (min, max) = (
+            self.percentile_over(start..=end, 0.0),
+            self.percentile_over(start..=end, 1.0),
+        );
+        if max.is_nan() || max <= min {
+            return histogram;
+        }
+        // Fraction of the range above each inner edge
+        let above: Vec<f32> = (1..buckets)
+            .map(|i| {
+                let edge = min + (max - min) * i as f32 / buckets as f32;
+                self.measure_above(start..=end, edge) / width
+            })
+            .collect();
+        for (i, fraction) in histogram.iter_mut().enumerate() {
+            let lower = if i == 0 { 1.0 } else { above[i - 1] };
+            let upper = above.get(i).copied().unwrap_or(0.0);
+            *fraction = (lower - upper).max(0.0);
+        }
+        histogram
+    }
+
+    /// A quantity accumulated from the first knot to `x`, like an integral, extended to all x following the
+    /// extrapolation of the curve.
+    ///
+    /// `inside` accumulates from the first knot to x within the knot range. `line(y, slope, d)` accumulates over a
+    /// line starting at y, from 0 to the signed distance `d`, and is negative for negative `d`.
+    fn cumulative(
+        &self,
+        x: f32,
+        inside: &dyn Fn(f32) -> f32,
+        line: &dyn Fn(f32, f32, f32) -> f32,
+    ) -> f32 {
+        let (Some(first), Some(last)) = (self.knots.first(), self.knots.last()) else {
+            // 0 everywhere
+            return line(0.0, 0.0, x);
+        };
+        let (first, last) = (first.position, last.position);
+        let (mode, slope) = if x < first.x {
+            (self.extrapolate_before, self.boundary_slopes().0)
+        } else if x > last.x {
+            (self.extrapolate_after, self.boundary_slopes().1)
+        } else {
+            return inside(x);
+        };
+        let width = last.x - first.x;
+        let d = x - first.x;
+        let along = |slope: f32| {
+            if x < first.x {
+                line(first.y, slope, d)
+            } else {
+                inside(last.x) + line(last.y, slope, x - last.x)
+            }
+        };
+        match mode {
+            Extrapolation::Clamp => along(0.0),
+            Extrapolation::Linear => along(slope),
+            _ if width <= 0.0 || !x.is_finite() => along(0.0),
+            Extrapolation::Repeat => {
+                let phase = d.rem_euclid(width);
+                let periods = ((d - phase) / width).round();
+                periods * inside(last.x) + inside(first.x + phase)
+            }
+            Extrapolation::PingPong => {
+                let phase = d.rem_euclid(2.0 * width);
+                let periods = ((d - phase) / (2.0 * width)).round();
+                let total = inside(last.x);
+                if phase <= width {
+                    2.0 * periods * total + inside(first.x + phase)
+                } else {
+                    // Runs back from the last knot
+                    2.0 * periods * total + 2.0 * total - inside(first.x + 2.0 * width - phase)
+                }
+            }
+        }
+    }
+
+    /// Integral from the first knot to `x` within the knot range
+    fn integral_from_first(&self, x: f32) -> f32 {
+        let mut sum = 0.0;
+        for pair in self.knots.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if a.position.x >= x {
+                break;
+            }
+            sum += self.segment_integral(a, b, x.min(b.position.x));
+        }
+        sum
+    }
+
+    /// Integral of the segment from `a` to `b`, from `a` to `x`
+    fn segment_integral(&self, a: &Knot, b: &Knot, x: f32) -> f32 {
+        let dx = x - a.position.x;
+        if dx <= 0.0 {
+            return 0.0;
+        }
+        let width = b.position.x - a.position.x;
+        match a.interpolation {
+            KnotInterpolation::Constant => a.position.y * dx,
+            KnotInterpolation::ConstantNext => b.position.y * dx,
+            KnotInterpolation::Linear => {
+                let y = a.position.y + (b.position.y - a.position.y) * (dx / width);
+                0.5 * dx * (a.position.y + y)
+            }
+            KnotInterpolation::Power { exponent } => {
+                let s = dx / width;
+                a.position.y * dx
+                    + (b.position.y - a.position.y) * width * s.powf(exponent + 1.0)
+                        / (exponent + 1.0)
+            }
+            KnotInterpolation::Cubic
+                if a.right_tangent.weight.is_some() || b.left_tangent.weight.is_some() =>
+            {
+                let segment = CubicSegment::from_bezier_points(a.compute_bezier_to(b));
+                let t = if x >= b.position.x {
+                    1.0
+                } else {
+                    segment.find_t_given_x(x, self.max_error, self.max_iters)
+                };
+                // y dx/dt is a polynomial of degree 5, which 3 point Gauss-Legendre integrates exactly
+                let area = |t: f32| segment.position(t).y * segment.velocity(t).x;
+                let (node, half) = ((3.0f32 / 5.0).sqrt() * 0.5 * t, 0.5 * t);
+                half * (5.0 * area(half - node) + 8.0 * area(half) + 5.0 * area(half + node)) / 9.0
+            }
+            KnotInterpolation::Cubic => {
+                let s = dx / width;
+                let [c0, c1, c2, c3] = hermite_coefficients(a, b);
+                width * s * (c0 + s * (c1 / 2.0 + s * (c2 / 3.0 + s * c3 / 4.0)))
+            }
+        }
+    }
+
+    /// Width between the first knot and `x` within the knot range where the curve is above `y`, given the
+    /// `crossings` of `y`
+    fn measure_above_from_first(&self, x: f32, y: f32, crossings: &[f32]) -> f32 {
+        let first = self.knots[0].position.x;
+        // The curve is on one side of y between crossings and knots. Knots are included, as the end of a range of x
+        // where the curve is at y is not a crossing.
+        let mut breaks: Vec<f32> = self
+            .knots
+            .iter()
+            .map(|knot| knot.position.x)
+            .chain(crossings.iter().copied())
+            .filter(|&b| b < x)
+            .chain([x])
+            .collect();
+        breaks.sort_by(f32::total_cmp);
+        breaks.dedup();
+        breaks
+            .windows(2)
+            .filter(|pair| pair[0] >= first && self.lookup(0.5 * (pair[0] + pair[1])) > y)
+            .map(|pair| pair[1] - pair[0])
+            .sum()
+    }
+}
+
+/// Start and end of `range`, with the end moved to the start for reversed ranges
+fn bounds(range: &RangeInclusive<f32>) -> (f32, f32) {
+    let start = *range.start();
+    (start, range.end().max(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::{Extrapolation, Knot, KnotInterpolation, LookupCurve, TangentSide};
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    fn ramp() -> LookupCurve {
+        LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ])
+    }
+
+    /// Weighted and unweighted cubic segments with overshoot, and a constant segment
+    fn bezier() -> LookupCurve {
+        LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Cubic)
+                .with_tangent_slope(TangentSide::Right, 4.0)
+                .with_tangent_weight(TangentSide::Right, Some(0.6)),
+            knot(0.4, 1.0, KnotInterpolation::Cubic)
+                .with_tangent_slope(TangentSide::Left, -1.0)
+                .with_tangent_weight(TangentSide::Left, Some(0.2)),
+            knot(0.7, 0.2, KnotInterpolation::Constant),
+            knot(0.8, 0.5, KnotInterpolation::Cubic).with_tangent_slope(TangentSide::Right, 2.0),
+            knot(1.0, 0.3, KnotInterpolation::Cubic),
+        ])
+    }
+
+    fn close(a: f32, b: f32, tolerance: f32) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    #[test]
+    fn linear_ramp() {
+        let curve = ramp();
+        assert_eq!(curve.integrate(0.0..=1.0), 0.5);
+        assert_eq!(curve.mean_over(0.0..=1.0), 0.5);
+        assert_eq!(curve.mean_over(0.0..=0.5), 0.25);
+        assert_eq!(curve.measure_above(0.0..=1.0, 0.25), 0.75);
+        assert!(close(curve.percentile_over(0.0..=1.0, 0.5), 0.5, 1e-6));
+        assert!(close(curve.percentile_over(0.0..=1.0, 0.9), 0.9, 1e-6));
+        assert!(close(curve.percentile_over(0.0..=1.0, 0.0), 0.0, 1e-6));
+        assert_eq!(curve.percentile_over(0.0..=1.0, 1.0), 1.0);
+        for fraction in curve.value_histogram(0.0..=1.0, 4) {
+            assert!(close(fraction, 0.25, 1e-6));
+        }
+    }
+
+    #[test]
+    fn constant_curves_and_empty_ranges() {
+        let flat = LookupCurve::new(vec![knot(0.0, 2.0, KnotInterpolation::Linear)]);
+        assert_eq!(flat.integrate(-1.0..=2.0), 6.0);
+        assert_eq!(flat.mean_over(-1.0..=2.0), 2.0);
+        assert_eq!(flat.measure_above(-1.0..=2.0, 2.0), 0.0);
+        assert_eq!(flat.measure_above(-1.0..=2.0, 1.0), 3.0);
+        assert_eq!(flat.percentile_over(-1.0..=2.0, 0.3), 2.0);
+        assert_eq!(flat.value_histogram(-1.0..=2.0, 3), [1.0, 0.0, 0.0]);
+        assert!(flat.value_histogram(-1.0..=2.0, 0).is_empty());
+
+        let empty = LookupCurve::default();
+        assert_eq!(empty.integrate(-1.0..=1.0), 0.0);
+        assert_eq!(empty.percentile_over(-1.0..=1.0, 0.5), 0.0);
+
+        let curve = ramp();
+        assert_eq!(curve.mean_over(0.5..=0.5), 0.5);
+        assert_eq!(curve.integrate(1.0..=0.0), 0.0);
+        assert_eq!(curve.percentile_over(0.3..=0.3, 0.9), 0.3);
+        assert_eq!(curve.value_histogram(0.3..=0.3, 2), [1.0, 0.0]);
+    }
+
+    #[test]
+    fn power_and_constant_next_segments() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Power { exponent: 2.0 }),
+            knot(1.0, 1.0, KnotInterpolation::ConstantNext),
+            knot(2.0, 3.0, KnotInterpolation::Linear),
+        ]);
+        assert!(close(curve.integrate(0.0..=1.0), 1.0 / 3.0, 1e-6));
+        assert!(close(curve.integrate(0.0..=0.5), 1.0 / 24.0, 1e-6));
+        assert_eq!(curve.integrate(1.0..=2.0), 3.0);
+        assert!(close(curve.measure_above(0.0..=2.0, 0.25), 1.5, 1e-5));
+    }
+
+    #[test]
+    fn extrapolation_is_included() {
+        // Clamped at 0 before and 1 after
+        let curve = ramp();
+        assert_eq!(curve.integrate(-1.0..=2.0), 1.5);
+        assert_eq!(curve.measure_above(-1.0..=2.0, 0.5), 1.5);
+
+        let linear = ramp().with_extrapolation(Extrapolation::Linear, Extrapolation::Linear);
+        assert_eq!(linear.integrate(-1.0..=2.0), 1.5);
+        assert_eq!(linear.integrate(-1.0..=0.0), -0.5);
+        assert_eq!(linear.measure_above(-1.0..=2.0, -0.5), 2.5);
+        assert!(close(linear.percentile_over(-1.0..=2.0, 0.5), 0.5, 1e-6));
+        assert_eq!(linear.percentile_over(-1.0..=2.0, 1.0), 2.0);
+
+        let repeat = ramp().with_extrapolation(Extrapolation::Repeat, Extrapolation::PingPong);
+        assert!(close(repeat.integrate(-3.0..=4.0), 3.5, 1e-5));
+        assert!(close(repeat.integrate(-0.75..=0.0), 0.75 * 0.625, 1e-6));
+        assert!(close(repeat.integrate(1.0..=1.5), 0.5 * 0.75, 1e-6));
+        assert!(close(repeat.measure_above(-2.0..=3.0, 0.75), 1.25, 1e-5));
+        assert!(close(repeat.mean_over(-10.5..=20.5), 0.5, 1e-5));
+    }
+
+    #[test]
+    fn matches_monte_carlo() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for curve in [
+            bezier(),
+            bezier().with_extrapolation(Extrapolation::Linear, Extrapolation::PingPong),
+        ] {
+            for range in [0.0..=1.0, 0.1..=0.75, -0.5..=1.7] {
+                let mut ys: Vec<f32> = (0..200_000)
+                    .map(|_| curve.lookup(rng.gen_range(range.clone())))
+                    .collect();
+                ys.sort_by(f32::total_cmp);
+                let mean = ys.iter().map(|&y| y as f64).sum::<f64>() / ys.len() as f64;
+                assert!(
+                    close(curve.mean_over(range.clone()), mean as f32, 5e-3),
+                    "{range:?}: {} != {mean}",
+                    curve.mean_over(range.clone())
+                );
+                for p in [0.1, 0.5, 0.9] {
+                    let sampled = ys[(p * ys.len() as f32) as usize];
+                    let percentile = curve.percentile_over(range.clone(), p);
+                    assert!(
+                        close(percentile, sampled, 1e-2),
+                        "{range:?} {p}: {percentile} != {sampled}"
+                    );
+                }
+
+                let histogram = curve.value_histogram(range.clone(), 8);
+                assert!(close(histogram.iter().sum(), 1.0, 1e-5));
+                let (min, max) = (ys[0], ys[ys.len() - 1]);
+                for (i, fraction) in histogram.iter().enumerate() {
+                    let lower = min + (max - min) * i as f32 / 8.0;
+                    let upper = min + (max - min) * (i + 1) as f32 / 8.0;
+                    let sampled = ys
+                        .iter()
+                        .filter(|&&y| (y > lower || i == 0) && y <= upper)
+                        .count() as f32
+                        / ys.len() as f32;
+                    assert!(
+                        close(*fraction, sampled, 1e-2),
+                        "{range:?} bucket {i}: {fraction} != {sampled}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::LookupCurve;
+
+/// Number of recent edits a curve keeps for [LookupCurve::edit_effect_since]
+pub const EDIT_LOG_LEN: usize = 8;
+
+/// Segment range of edits that may have changed anything
+const ALL: (u32, u32) = (0, u32::MAX);
+
+/// What changed in a curve since a generation, returned by [LookupCurve::edit_effect_since].
+///
+/// Segments are indexed by their first knot, the segment `i` goes from the knot `i` to the knot `i + 1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditEffect {
+    /// Knots were added, removed or moved past each other, so data kept by knot or segment index has to be rebuilt.
+    ///
+    /// Also set for edits that are not tracked in detail.
+    pub reordered: bool,
+    /// Segments that might have changed. Empty if the curve did not change, and all segments if the change is not
+    /// known in more detail.
+    pub dirty_segments: Range<usize>,
+}
+
+impl EditEffect {
+    /// Returns `true` if the curve did not change
+    pub fn is_empty(&self) -> bool {
+        !self.reordered && self.dirty_segments.is_empty()
+    }
+}
+
+/// The segments changed by the last [EDIT_LOG_LEN] generations of a curve
+#[derive(Debug, Default)]
+pub(crate) struct EditLog {
+    /// Generation after each edit with the segments it changed, in a ring starting at `next`
+    entries: [(u64, (u32, u32)); EDIT_LOG_LEN],
+    next: usize,
+    /// Set for clones until their first edit, see [LookupCurve::generation]
+    cloned: bool,
+}
+
+impl Clone for EditLog {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries,
+            next: self.next,
+            cloned: true,
+        }
+    }
+}
+
+impl EditLog {
+    /// Returns whether the curve is a clone that was not edited yet, and clears it
+    #[inline]
+    pub(crate) fn take_cloned(&mut self) -> bool {
+        std::mem::take(&mut self.cloned)
+    }
+
+    /// Records that the edit creating `generation` may have changed anything
+    #[inline]
+    pub(crate) fn push(&mut self, generation: u64) {
+        self.entries[self.next] = (generation, ALL);
+        self.next = (self.next + 1) % EDIT_LOG_LEN;
+    }
+
+    /// Narrows the latest edit, creating `generation`, to the segments in `segments`
+    fn narrow(&mut self, generation: u64, segments: Range<usize>) {
+        let last = &mut self.entries[(self.next + EDIT_LOG_LEN - 1) % EDIT_LOG_LEN];
+        if last.0 == generation {
+            if let (Ok(start), Ok(end)) = (segments.start.try_into(), segments.end.try_into()) {
+                last.1 = (start, end);
+            }
+        }
+    }
+}
+
+impl LookupCurve {
+    /// Returns what changed in the curve since it had `generation`, see [LookupCurve::generation].
+    ///
+    /// Moving a knot without passing another knot or changing the range of the knots, or changing its tangents,
+    /// only changes the segments next to it and to knots with [TangentMode::Auto](crate::TangentMode::Auto) tangents
+    /// following it. Other edits, and generations older than the last [EDIT_LOG_LEN] edits, return
+    /// [EditEffect::reordered] with all segments. Data derived from the curve can use this to only rebuild the parts
+    /// of the changed segments, like [PrecomputedLookupCurve::update](crate::sample::PrecomputedLookupCurve::update).
+    pub fn edit_effect_since(&self, generation: u64) -> EditEffect {
+        let segments = self.knots.len().saturating_sub(1);
+        let all = EditEffect {
+            reordered: true,
+            dirty_segments: 0..segments,
+        };
+        let edits = self.generation.wrapping_sub(generation);
+        if edits as usize > EDIT_LOG_LEN {
+            return all;
+        }
+        let mut dirty: Option<(u32, u32)> = None;
+        let mut found = 0;
+        for &(entry, range) in &self.edit_log.entries {
+            if entry.wrapping_sub(generation).wrapping_sub(1) >= edits {
+                continue;
+            }
+            if range == ALL {
+                return all;
+            }
+            found += 1;
+            dirty =
+                Some(dirty.map_or(range, |(start, end)| (start.min(range.0), end.max(range.1))));
+        }
+        if found != edits {
+            return all;
+        }
+        let (start, end) = dirty.unwrap_or((0, 0));
+        EditEffect {
+            reordered: false,
+            dirty_segments: (start as usize).min(segments)..(end as usize).min(segments),
+        }
+    }
+
+    /// Narrows the latest edit to the segments next to the knots in `knots`
+    pub(crate) fn record_changed_knots(&mut self, knots: RangeInclusive<usize>) {
+        let segments = self.knots.len().saturating_sub(1);
+        let dirty = knots.start().saturating_sub(1)..(knots.end() + 1).min(segments);
+        self.edit_log.narrow(self.generation, dirty);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+
+    use super::*;
+    use crate::{Knot, KnotInterpolation, TangentMode, TangentSide};
+
+    fn curve(knots: usize) -> LookupCurve {
+        LookupCurve::new(
+            (0..knots)
+                .map(|i| Knot {
+                    position: Vec2::new(i as f32, (i % 3) as f32),
+                    interpolation: KnotInterpolation::Cubic,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn moved(curve: &LookupCurve, i: usize, by: Vec2) -> Knot {
+        Knot {
+            position: curve.knots()[i].position + by,
+            ..curve.knots()[i]
+        }
+    }
+
+    #[test]
+    fn moving_a_knot_dirties_its_segments() {
+        let mut curve = curve(10);
+        let start = curve.generation();
+        assert!(curve.edit_effect_since(start).is_empty());
+
+        curve.modify_knot(4, moved(&curve, 4, Vec2::new(0.2, 1.0)));
+        let effect = curve.edit_effect_since(start);
+        assert_eq!(effect.dirty_segments, 3..5);
+        assert!(!effect.reordered);
+        let after_first = curve.generation();
+
+        curve.modify_knot(
+            7,
+            curve.knots()[7].with_tangent_slope(TangentSide::Left, 2.0),
+        );
+        assert_eq!(curve.edit_effect_since(after_first).dirty_segments, 6..8);
+        assert_eq!(curve.edit_effect_since(start).dirty_segments, 3..8);
+
+        // Moving the last knot on x changes the range of the knots
+        curve.modify_knot(9, moved(&curve, 9, Vec2::X));
+        assert!(curve.edit_effect_since(start).reordered);
+        // Ends are clamped, y of the last knot only changes the last segment
+        let generation = curve.generation();
+        curve.modify_knot(9, moved(&curve, 9, Vec2::Y));
+        assert_eq!(curve.edit_effect_since(generation).dirty_segments, 8..9);
+    }
+
+    #[test]
+    fn other_edits_dirty_everything() {
+        let mut curve = curve(10);
+        let start = curve.generation();
+        curve.modify_knot(4, moved(&curve, 4, Vec2::new(2.5, 0.0)));
+        let all = EditEffect {
+            reordered: true,
+            dirty_segments: 0..9,
+        };
+        assert_eq!(curve.edit_effect_since(start), all);
+
+        let generation = curve.generation();
+        curve.delete_knot(2);
+        assert!(curve.edit_effect_since(generation).reordered);
+
+        // Edits older than the log
+        let generation = curve.generation();
+        for _ in 0..=EDIT_LOG_LEN {
+            curve.modify_knot(4, moved(&curve, 4, Vec2::Y));
+        }
+        assert!(curve.edit_effect_since(generation).reordered);
+        // Other curves
+        assert!(
+            curve
+                .edit_effect_since(LookupCurve::default().generation())
+                .reordered
+        );
+    }
+
+    #[test]
+    fn auto_tangents_widen_the_dirty_segments() {
+        let mut curve = curve(10);
+        for i in 0..10 {
+            let knot = curve.knots()[i].with_tangent_mode(TangentSide::Left, TangentMode::Auto);
+            curve.modify_knot(
+                i,
+                knot.with_tangent_mode(TangentSide::Right, TangentMode::Auto),
+            );
+        }
+        let start = curve.generation();
+        curve.modify_knot(4, moved(&curve, 4, Vec2::Y));
+        assert_eq!(curve.edit_effect_since(start).dirty_segments, 2..6);
+    }
+}
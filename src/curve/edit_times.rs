@@ -0,0 +1,268 @@
+use std::ops::RangeInclusive;
+
+use crate::{Knot, LookupCurve};
+
+/// Clock used for [Knot::created_at] and [Knot::modified_at], see [LookupCurve::track_edit_times]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum EditClock {
+    /// Counter of the curve, one more than the latest edit time of any of its knots.
+    ///
+    /// Deterministic and independent of the wall clock. Deleting the most recently edited knot lets the
+    /// next edit reuse its time.
+    #[default]
+    Counter,
+    /// Seconds since the unix epoch. Not available on `wasm32-unknown-unknown`, where reading the time panics.
+    UnixTime,
+}
+
+impl EditClock {
+    #[cfg(feature = "serialize")]
+    pub(crate) fn is_default(&self) -> bool {
+        *self == EditClock::default()
+    }
+}
+
+/// Summary of the edit times of the knots of a [LookupCurve], see [LookupCurve::edit_stats]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditStats {
+    /// Number of knots with a [Knot::created_at] time
+    pub tracked: usize,
+    /// Number of knots without edit times, e.g. created before [LookupCurve::track_edit_times] was set
+    pub untracked: usize,
+    /// Id and creation time of the knot created first
+    pub oldest: Option<(usize, u64)>,
+    /// Id and creation time of the knot created last
+    pub newest: Option<(usize, u64)>,
+    /// Sorted modification times of all knots that have one
+    modified: Vec<u64>,
+}
+
+impl EditStats {
+    /// Earliest modification time of any knot
+    pub fn first_modified(&self) -> Option<u64> {
+        self.modified.first().copied()
+    }
+
+    /// Latest modification time of any knot
+    pub fn last_modified(&self) -> Option<u64> {
+        self.modified.last().copied()
+    }
+
+    /// Number of knots last modified within `window`
+    pub fn modified_within(&self, window: RangeInclusive<u64>) -> usize {
+        let start = self.modified.partition_point(|t| t < window.start());
+        let end = self.modified.partition_point(|t| t <= window.end());
+        end.saturating_sub(start)
+    }
+}
+
+impl LookupCurve {
+    /// Consumes the curve and returns it with [LookupCurve::track_edit_times] set, using `clock`
+    pub fn with_edit_times(mut self, clock: EditClock) -> Self {
+        self.track_edit_times = true;
+        self.edit_clock = clock;
+        self
+    }
+
+    /// Summarizes the edit times of the knots of the curve
+    pub fn edit_stats(&self) -> EditStats {
+        let mut stats = EditStats::default();
+        for knot in self.knots.iter() {
+            let Some(created_at) = knot.created_at else {
+                stats.untracked += 1;
+                continue;
+            };
+            stats.tracked += 1;
+            if stats.oldest.is_none_or(|(_, t)| created_at < t) {
+                stats.oldest = Some((knot.id, created_at));
+            }
+            if stats.newest.is_none_or(|(_, t)| created_at >= t) {
+                stats.newest = Some((knot.id, created_at));
+            }
+        }
+        stats.modified = self.knots.iter().filter_map(|k| k.modified_at).collect();
+        stats.modified.sort_unstable();
+        stats
+    }
+
+    /// Time of an edit happening now, `None` if the curve does not track edit times
+    pub(crate) fn edit_time(&self) -> Option<u64> {
+        if !self.track_edit_times {
+            return None;
+        }
+        Some(match self.edit_clock {
+            EditClock::Counter => self
+                .knots
+                .iter()
+                .flat_map(|k| [k.created_at, k.modified_at])
+                .flatten()
+                .max()
+                .map_or(1, |t| t + 1),
+            EditClock::UnixTime => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        })
+    }
+
+    /// Sets [Knot::modified_at] of the knot at `i` to `time`, if the curve tracks edit times
+    pub(crate) fn stamp_modified(&mut self, i: usize, time: Option<u64>) {
+        if time.is_some() {
+            self.knots[i].modified_at = time;
+        }
+    }
+}
+
+impl Knot {
+    /// Returns the knot with both edit times set to `time`, as a newly created knot
+    pub(crate) fn stamped_created(mut self, time: Option<u64>) -> Self {
+        if time.is_some() {
+            self.created_at = time;
+            self.modified_at = time;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KnotInterpolation, TangentSide};
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+    }
+
+    fn times(curve: &LookupCurve) -> Vec<(Option<u64>, Option<u64>)> {
+        curve
+            .knots()
+            .iter()
+            .map(|k| (k.created_at, k.modified_at))
+            .collect()
+    }
+
+    #[test]
+    fn mutations_stamp_knots() {
+        let mut curve = LookupCurve::default().with_edit_times(EditClock::Counter);
+        curve.add_knot(knot(0.0, 0.0));
+        curve.add_knot(knot(1.0, 1.0));
+        assert_eq!(times(&curve), [(Some(1), Some(1)), (Some(2), Some(2))]);
+
+        // The creation time is kept, even if the new value does not have one
+        let moved = Knot {
+            created_at: None,
+            ..curve.knots()[0]
+        };
+        let i = curve.modify_knot(0, moved.with_tangent_slope(TangentSide::Right, 1.0));
+        assert_eq!(times(&curve)[i], (Some(1), Some(3)));
+
+        curve.add_knot(knot(0.5, 0.5));
+        curve.delete_knot(2);
+        assert_eq!(times(&curve), [(Some(1), Some(3)), (Some(4), Some(4))]);
+
+        // Repairs stamp the knots they change
+        curve.strict_monotonic_x = true;
+        let knots = curve.knots();
+        let (a, b) = (knots[0], knots[1]);
+        curve.modify_knot(0, a.with_tangent_weight(TangentSide::Right, Some(0.9)));
+        assert_eq!(times(&curve), [(Some(1), Some(5)), (Some(4), Some(4))]);
+        curve.modify_knot(1, b.with_tangent_weight(TangentSide::Left, Some(0.9)));
+        // The second knot gives way to the first one, which is unchanged
+        assert_eq!(times(&curve), [(Some(1), Some(5)), (Some(4), Some(6))]);
+    }
+
+    #[test]
+    fn untracked_curves_are_not_stamped() {
+        let mut curve = LookupCurve::default();
+        curve.add_knot(knot(0.0, 0.0));
+        let stamped = Knot {
+            created_at: Some(7),
+            modified_at: Some(8),
+            ..knot(1.0, 1.0)
+        };
+        curve.add_knot(stamped);
+        curve.modify_knot(0, knot(0.5, 0.5));
+        assert_eq!(times(&curve), [(None, None), (Some(7), Some(8))]);
+    }
+
+    #[test]
+    fn unix_time_clock() {
+        let mut curve = LookupCurve::default().with_edit_times(EditClock::UnixTime);
+        curve.add_knot(knot(0.0, 0.0));
+        // Some time after this was written
+        assert!(curve.knots()[0].created_at.unwrap() > 1_700_000_000);
+    }
+
+    #[test]
+    fn splice_keeps_source_times() {
+        let mut source = LookupCurve::default().with_edit_times(EditClock::Counter);
+        source.add_knot(knot(0.0, 0.0));
+        source.add_knot(knot(1.0, 1.0));
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(4.0, 1.0)])
+            .with_edit_times(EditClock::Counter);
+        curve.splice(1.0..=2.0, &source, crate::StitchMode::Absolute);
+        let times = times(&curve);
+        assert_eq!(times.len(), 6);
+        assert_eq!(times[0], (None, None));
+        // Knots added at the edges of the range are new
+        assert_eq!(times[1], (Some(3), Some(3)));
+        assert_eq!(times[2..4], [(Some(1), Some(1)), (Some(2), Some(2))]);
+        assert_eq!(times[4], (Some(3), Some(3)));
+    }
+
+    #[test]
+    fn stats() {
+        let empty = LookupCurve::default().edit_stats();
+        assert_eq!(empty, EditStats::default());
+        assert_eq!(empty.last_modified(), None);
+
+        let mut curve = LookupCurve::new(vec![knot(-1.0, 0.0)]).with_edit_times(EditClock::Counter);
+        let first = curve.knots()[0].id;
+        for x in 0..4 {
+            curve.add_knot(knot(x as f32, 0.0));
+        }
+        let i = curve
+            .knots()
+            .iter()
+            .position(|k| k.created_at == Some(2))
+            .unwrap();
+        curve.modify_knot(i, knot(1.5, 1.0));
+
+        let stats = curve.edit_stats();
+        assert_eq!((stats.tracked, stats.untracked), (4, 1));
+        assert_eq!(stats.oldest.map(|(_, t)| t), Some(1));
+        assert_eq!(stats.newest.map(|(_, t)| t), Some(4));
+        assert_ne!(stats.oldest.unwrap().0, first);
+        assert_eq!(stats.first_modified(), Some(1));
+        assert_eq!(stats.last_modified(), Some(5));
+        assert_eq!(stats.modified_within(4..=5), 2);
+        assert_eq!(stats.modified_within(0..=u64::MAX), 4);
+        assert_eq!(stats.modified_within(6..=9), 0);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn serialized_only_when_present() {
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0)]);
+        let plain = ron::to_string(&curve).unwrap();
+        assert!(!plain.contains("created_at"));
+        assert!(!plain.contains("track_edit_times"));
+
+        curve.track_edit_times = true;
+        curve.add_knot(knot(1.0, 1.0));
+        let tracked = ron::to_string(&curve).unwrap();
+        assert_eq!(tracked.matches("created_at").count(), 1);
+        assert!(tracked.contains("track_edit_times"));
+        assert!(!tracked.contains("edit_clock"));
+
+        let loaded: LookupCurve = ron::from_str(&tracked).unwrap();
+        assert!(loaded.track_edit_times);
+        assert_eq!(times(&loaded), [(None, None), (Some(1), Some(1))]);
+    }
+}
@@ -0,0 +1,385 @@
+use bevy_math::Vec2;
+
+use crate::{KnotInterpolation, LookupCurve, TangentSide};
+
+/// How a [LookupCurve] continues before its first or after its last knot, see [LookupCurve::extrapolate_before] and
+/// [LookupCurve::extrapolate_after]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum Extrapolation {
+    /// The y of the first or last knot
+    #[default]
+    Clamp,
+    /// A line through the first or last knot, with the slope of the curve at the knot. That is the slope of the
+    /// tangent for cubic segments, the slope of the segment for linear segments, and 0 for constant segments.
+    Linear,
+    /// The curve repeats, x is wrapped into the knot range
+    Repeat,
+    /// The curve repeats mirrored every other time, x moves back and forth over the knot range
+    PingPong,
+}
+
+impl Extrapolation {
+    pub const ALL: [Extrapolation; 4] = [
+        Extrapolation::Clamp,
+        Extrapolation::Linear,
+        Extrapolation::Repeat,
+        Extrapolation::PingPong,
+    ];
+
+    #[cfg(feature = "serialize")]
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Extrapolation::default()
+    }
+}
+
+/// Where an x outside of the knot range ends up, see [Extrapolator::outside]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Outside {
+    /// y and slope given directly by the extrapolation
+    Value { y: f32, slope: f32 },
+    /// x mapped into the knot range, where the curve is sampled instead. The curve runs backwards at mirrored x,
+    /// which negates its odd derivatives.
+    Mapped { x: f32, mirrored: bool },
+}
+
+/// Snapshot of the extrapolation of a curve, for mapping x outside of the knot range
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Extrapolator {
+    before: Extrapolation,
+    after: Extrapolation,
+    first: Vec2,
+    last: Vec2,
+    slope_before: f32,
+    slope_after: f32,
+}
+
+impl Extrapolator {
+    /// Where `x` ends up, or `None` within the knot range including its ends.
+    ///
+    /// [Extrapolation::Repeat] and [Extrapolation::PingPong] clamp for curves without width and for infinite x.
+    /// NaN is never outside.
+    #[inline]
+    pub(crate) fn outside(&self, x: f32) -> Option<Outside> {
+        let (mode, boundary, slope) = if x < self.first.x {
+            (self.before, self.first, self.slope_before)
+        } else if x > self.last.x {
+            (self.after, self.last, self.slope_after)
+        } else {
+            return None;
+        };
+        let width = self.last.x - self.first.x;
+        let clamped = Outside::Value {
+            y: boundary.y,
+            slope: 0.0,
+        };
+        Some(match mode {
+            Extrapolation::Clamp => clamped,
+            Extrapolation::Linear if slope == 0.0 => clamped,
+            Extrapolation::Linear => Outside::Value {
+                y: boundary.y + slope * (x - boundary.x),
+                slope,
+            },
+            _ if width <= 0.0 || !x.is_finite() => clamped,
+            Extrapolation::Repeat => Outside::Mapped {
+                x: self.first.x + (x - self.first.x).rem_euclid(width),
+                mirrored: false,
+            },
+            Extrapolation::PingPong => {
+                let phase = (x - self.first.x).rem_euclid(2.0 * width);
+                if phase > width {
+                    Outside::Mapped {
+                        x: self.first.x + 2.0 * width - phase,
+                        mirrored: true,
+                    }
+                } else {
+                    Outside::Mapped {
+                        x: self.first.x + phase,
+                        mirrored: false,
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl LookupCurve {
+    /// Consumes the curve and returns it with the extrapolation before the first and after the last knot set
+    pub fn with_extrapolation(mut self, before: Extrapolation, after: Extrapolation) -> Self {
+        self.extrapolate_before = before;
+        self.extrapolate_after = after;
+        self.bump_generation();
+        self
+    }
+
+    /// Snapshot of the extrapolation settings with the positions and slopes of the boundary knots. Empty curves
+    /// use the origin, and single knots extrapolate as constants with any setting.
+    pub(crate) fn extrapolator(&self) -> Extrapolator {
+        let (first, last) = match (self.knots.first(), self.knots.last()) {
+            (Some(first), Some(last)) => (first.position, last.position),
+            _ => (Vec2::ZERO, Vec2::ZERO),
+        };
+        let (slope_before, slope_after) = self.boundary_slopes();
+        Extrapolator {
+            before: self.extrapolate_before,
+            after: self.extrapolate_after,
+            first,
+            last,
+            slope_before,
+            slope_after,
+        }
+    }
+
+    /// Slopes of [Extrapolation::Linear] before the first and after the last knot, 0 for fewer than 2 knots
+    pub(crate) fn boundary_slopes(&self) -> (f32, f32) {
+        let n = self.knots.len();
+        if n < 2 {
+            return (0.0, 0.0);
+        }
+        (
+            self.boundary_slope(0, TangentSide::Right),
+            self.boundary_slope(n - 2, TangentSide::Left),
+        )
+    }
+
+    /// Slope of the segment starting at knot `i` at its start or end, decided by `side` like the tangent used there
+    fn boundary_slope(&self, i: usize, side: TangentSide) -> f32 {
+        let (a, b) = (&self.knots[i], &self.knots[i + 1]);
+        let slope = match a.interpolation {
+            KnotInterpolation::Constant | KnotInterpolation::ConstantNext => 0.0,
+            KnotInterpolation::Linear => {
+                (b.position.y - a.position.y) / (b.position.x - a.position.x)
+            }
+            KnotInterpolation::Power { .. } if b.position.y == a.position.y => 0.0,
+            KnotInterpolation::Power { .. } => {
+                let s = match side {
+                    TangentSide::Left => 1.0,
+                    TangentSide::Right => 0.0,
+                };
+                // Infinite at the start of segments with exponents below 1, which is not extrapolated
+                a.interpolation.fraction_slope(s).unwrap_or(0.0) * (b.position.y - a.position.y)
+                    / (b.position.x - a.position.x)
+            }
+            KnotInterpolation::Cubic => match side {
+                TangentSide::Left => b.left_tangent.slope,
+                TangentSide::Right => a.right_tangent.slope,
+            },
+        };
+        if slope.is_finite() {
+            slope
+        } else {
+            0.0
+        }
+    }
+
+    /// Slope just past the first knot, or the last knot if `after`, continuing on the extrapolated side. `side` is
+    /// the side of the knot the slope is taken at, as seen from within the knot range.
+    pub(crate) fn slope_past_boundary(&self, after: bool, side: TangentSide) -> f32 {
+        let (first, last) = (
+            self.knots[0].position.x,
+            self.knots[self.knots.len() - 1].position.x,
+        );
+        let mode = if after {
+            self.extrapolate_after
+        } else {
+            self.extrapolate_before
+        };
+        match mode {
+            Extrapolation::Clamp => 0.0,
+            Extrapolation::Linear if after => self.boundary_slopes().1,
+            Extrapolation::Linear => self.boundary_slopes().0,
+            _ if last <= first => 0.0,
+            // Continues at the other end of the curve
+            Extrapolation::Repeat => self.slope_at(if after { first } else { last }, side),
+            // Runs back over the boundary segment
+            Extrapolation::PingPong => {
+                let (x, side) = if after {
+                    (last, TangentSide::Left)
+                } else {
+                    (first, TangentSide::Right)
+                };
+                -self.slope_at(x, side)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+
+    use crate::{Extrapolation, Knot, KnotInterpolation, LookupCurve, TangentSide};
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    /// Cubic from (0, 0) with slope 2, linear from (1, 1) to (3, 0)
+    fn curve(before: Extrapolation, after: Extrapolation) -> LookupCurve {
+        LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Cubic).with_tangent_slope(TangentSide::Right, 2.0),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+            knot(3.0, 0.0, KnotInterpolation::Linear),
+        ])
+        .with_extrapolation(before, after)
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn clamp_is_the_default() {
+        let curve = curve(Extrapolation::Clamp, Extrapolation::Clamp);
+        assert_eq!(
+            LookupCurve::default().extrapolate_before,
+            Extrapolation::Clamp
+        );
+        assert_eq!(
+            LookupCurve::default().extrapolate_after,
+            Extrapolation::Clamp
+        );
+        assert_eq!(curve.lookup(-5.0), 0.0);
+        assert_eq!(curve.lookup(10.0), 0.0);
+        assert_eq!(curve.slope_at(-5.0, TangentSide::Left), 0.0);
+        assert_eq!(curve.slope_at(3.0, TangentSide::Right), 0.0);
+    }
+
+    #[test]
+    fn linear_follows_the_boundary_slopes() {
+        let curve = curve(Extrapolation::Linear, Extrapolation::Linear);
+        // Tangent of the cubic segment before, slope of the linear segment after
+        assert_eq!(curve.lookup(-1.5), -3.0);
+        assert_eq!(curve.lookup(5.0), -1.0);
+        assert_eq!(curve.slope_at(-1.0, TangentSide::Right), 2.0);
+        assert_eq!(curve.slope_at(0.0, TangentSide::Left), 2.0);
+        assert_eq!(curve.slope_at(3.0, TangentSide::Right), -0.5);
+        assert_eq!(curve.lookup(f32::INFINITY), f32::NEG_INFINITY);
+
+        // Constant segments extrapolate as constants
+        let constant = LookupCurve::new(vec![
+            knot(0.0, 1.0, KnotInterpolation::Constant),
+            knot(1.0, 2.0, KnotInterpolation::Constant),
+        ])
+        .with_extrapolation(Extrapolation::Linear, Extrapolation::Linear);
+        assert_eq!(constant.lookup(-1.0), 1.0);
+        assert_eq!(constant.lookup(f32::NEG_INFINITY), 1.0);
+        assert_eq!(constant.lookup(2.0), 2.0);
+    }
+
+    #[test]
+    fn repeat_wraps_x() {
+        let curve = curve(Extrapolation::Repeat, Extrapolation::Repeat);
+        for x in [0.25, 0.5, 1.0, 2.0, 2.9] {
+            let y = curve.lookup(x);
+            for period in [-2.0, -1.0, 1.0, 3.0] {
+                let wrapped = x + 3.0 * period;
+                assert!(close(curve.lookup(wrapped), y), "{wrapped}");
+                assert!(close(
+                    curve.slope_at(wrapped, TangentSide::Left),
+                    curve.slope_at(x, TangentSide::Left)
+                ));
+            }
+        }
+        // Just past the last knot the curve starts over
+        assert_eq!(curve.slope_at(3.0, TangentSide::Right), 2.0);
+        assert_eq!(curve.slope_at(0.0, TangentSide::Left), -0.5);
+        // Infinite x clamps
+        assert_eq!(curve.lookup(f32::INFINITY), 0.0);
+    }
+
+    #[test]
+    fn ping_pong_mirrors_every_other_period() {
+        let curve = curve(Extrapolation::PingPong, Extrapolation::PingPong);
+        for x in [0.25, 0.5, 1.0, 2.0, 2.9] {
+            let y = curve.lookup(x);
+            let slope = curve.slope_at(x, TangentSide::Left);
+            // Mirrored at the last and the first knot
+            for mirrored in [6.0 - x, -x] {
+                assert!(close(curve.lookup(mirrored), y), "{mirrored}");
+                assert!(close(curve.slope_at(mirrored, TangentSide::Right), -slope));
+            }
+            assert!(close(curve.lookup(x + 6.0), y));
+            assert!(close(curve.lookup(x - 6.0), y));
+        }
+        assert_eq!(curve.slope_at(3.0, TangentSide::Right), 0.5);
+        assert_eq!(curve.slope_at(0.0, TangentSide::Left), -2.0);
+    }
+
+    #[test]
+    fn modes_apply_to_their_side() {
+        let curve = curve(Extrapolation::Linear, Extrapolation::Clamp);
+        assert_eq!(curve.lookup(-1.0), -2.0);
+        assert_eq!(curve.lookup(4.0), 0.0);
+        let curve = curve.with_extrapolation(Extrapolation::Clamp, Extrapolation::Repeat);
+        assert_eq!(curve.lookup(-1.0), 0.0);
+        assert_eq!(curve.lookup(4.0), curve.lookup(1.0));
+    }
+
+    #[test]
+    fn small_curves_stay_defined() {
+        for mode in Extrapolation::ALL {
+            let empty = LookupCurve::default().with_extrapolation(mode, mode);
+            assert_eq!(empty.lookup(-1.0), 0.0);
+            assert_eq!(empty.lookup(1.0), 0.0);
+            assert_eq!(empty.slope_at(1.0, TangentSide::Right), 0.0);
+
+            let single = LookupCurve::new(vec![knot(1.0, 2.0, KnotInterpolation::Linear)])
+                .with_extrapolation(mode, mode);
+            for x in [-10.0, 1.0, 5.0, f32::INFINITY] {
+                assert_eq!(single.lookup(x), 2.0);
+                assert_eq!(single.slope_at(x, TangentSide::Left), 0.0);
+                assert_eq!(single.slope_at(x, TangentSide::Right), 0.0);
+            }
+
+            // Knots at the same x have no width to repeat
+            let stacked = LookupCurve::new(vec![
+                knot(1.0, 2.0, KnotInterpolation::Linear),
+                knot(1.0, 3.0, KnotInterpolation::Linear),
+            ])
+            .with_extrapolation(mode, mode);
+            assert_eq!(stacked.lookup(0.0), 2.0);
+            assert_eq!(stacked.lookup(2.0), 3.0);
+            assert_eq!(stacked.slope_at(1.0, TangentSide::Right), 0.0);
+        }
+    }
+
+    #[test]
+    fn samplers_agree_with_lookup() {
+        for before in Extrapolation::ALL {
+            for after in Extrapolation::ALL {
+                let curve = curve(before, after);
+                let precomputed = curve.precompute();
+                let baked = curve.bake(1000);
+                let mut filled = [0.0; 121];
+                curve.fill_buffer(-6.0, 0.1, &mut filled);
+                for (i, filled) in filled.iter().enumerate() {
+                    let x = -6.0 + 0.1 * i as f32;
+                    let y = curve.lookup(x);
+                    assert!(close(precomputed.lookup(x), y), "{before:?} {after:?} {x}");
+                    assert!(close(*filled, y), "{before:?} {after:?} {x}");
+                    assert!(
+                        (baked.sample(x) - y).abs() < 1e-2,
+                        "{before:?} {after:?} {x}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn extrapolation_round_trips() {
+        let plain = ron::to_string(&curve(Extrapolation::Clamp, Extrapolation::Clamp)).unwrap();
+        assert!(!plain.contains("extrapolate"));
+        let curve = curve(Extrapolation::PingPong, Extrapolation::Linear);
+        let loaded: LookupCurve = ron::from_str(&ron::to_string(&curve).unwrap()).unwrap();
+        assert_eq!(loaded.extrapolate_before, Extrapolation::PingPong);
+        assert_eq!(loaded.extrapolate_after, Extrapolation::Linear);
+    }
+}
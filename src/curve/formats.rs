@@ -0,0 +1,371 @@
+use crate::{
+    parse_curve_ron, serialize_curve_ron, CurveParseError, LookupCurve, LookupCurveSaveError,
+    RonStyle,
+};
+
+/// Text format of a curve file. RON is always available, YAML and TOML need the `yaml` and `toml` features.
+///
+/// All formats describe the same fields, are validated like [parse_curve_ron], and are processed the same way.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CurveFormat {
+    /// `.curve.ron` or `.lookupcurve.ron`
+    #[default]
+    Ron,
+    /// `.curve.yaml` or `.curve.yml`
+    #[cfg(feature = "yaml")]
+    Yaml,
+    /// `.curve.toml`
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl CurveFormat {
+    /// The format of a file by the extension of its path, or `None` for unknown extensions and formats of disabled
+    /// features
+    pub fn from_path(path: &str) -> Option<Self> {
+        let (_, extension) = path.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "ron" => Some(CurveFormat::Ron),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(CurveFormat::Yaml),
+            #[cfg(feature = "toml")]
+            "toml" => Some(CurveFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Asset extensions of curve files in this format, without the leading dot
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            CurveFormat::Ron => &["curve.ron", "lookupcurve.ron"],
+            #[cfg(feature = "yaml")]
+            CurveFormat::Yaml => &["curve.yaml", "curve.yml"],
+            #[cfg(feature = "toml")]
+            CurveFormat::Toml => &["curve.toml"],
+        }
+    }
+
+    /// All formats of enabled features
+    pub fn all() -> &'static [CurveFormat] {
+        &[
+            CurveFormat::Ron,
+            #[cfg(feature = "yaml")]
+            CurveFormat::Yaml,
+            #[cfg(feature = "toml")]
+            CurveFormat::Toml,
+        ]
+    }
+}
+
+/// Parses a lookup curve in `format`, without the asset system. Knots are sorted and validated like in
+/// [parse_curve_ron].
+pub fn parse_curve(bytes: &[u8], format: CurveFormat) -> Result<LookupCurve, CurveParseError> {
+    let parsed = match format {
+        CurveFormat::Ron => parse_curve_ron(bytes),
+        #[cfg(feature = "yaml")]
+        CurveFormat::Yaml => {
+            serde_yaml::from_slice::<LookupCurve>(bytes).map_err(|code| {
+                let (offset, line, column) = match code.location() {
+                    Some(location) => (location.index(), location.line(), location.column()),
+                    // Errors at the end of the input have no location
+                    None => {
+                        let (line, column) = line_column(bytes, bytes.len());
+                        (bytes.len(), line, column)
+                    }
+                };
+                CurveParseError::Yaml {
+                    offset,
+                    line,
+                    column,
+                    code,
+                }
+            })
+        }
+        #[cfg(feature = "toml")]
+        CurveFormat::Toml => {
+            let parsed = std::str::from_utf8(bytes)
+                .map_err(|e| {
+                    let code = <toml::de::Error as serde::de::Error>::custom(e);
+                    (e.valid_up_to(), code)
+                })
+                .and_then(|s| {
+                    toml::from_str::<LookupCurve>(s).map_err(|code| {
+                        let offset = code.span().map_or(bytes.len(), |span| span.start);
+                        (offset, code)
+                    })
+                });
+            parsed.map_err(|(offset, code)| {
+                let (line, column) = line_column(bytes, offset);
+                CurveParseError::Toml {
+                    offset,
+                    line,
+                    column,
+                    code,
+                }
+            })
+        }
+    };
+    #[cfg(feature = "trace")]
+    if let Err(error) = &parsed {
+        tracing::debug!(?format, %error, "rejected lookup curve");
+    }
+    parsed
+}
+
+/// Serializes a lookup curve in `format`, which [parse_curve] parses back to the same curve. RON is written with
+/// [RonStyle::Pretty].
+pub fn serialize_curve(
+    curve: &LookupCurve,
+    format: CurveFormat,
+) -> Result<Vec<u8>, LookupCurveSaveError> {
+    match format {
+        CurveFormat::Ron => Ok(serialize_curve_ron(curve, RonStyle::Pretty)?),
+        #[cfg(feature = "yaml")]
+        CurveFormat::Yaml => Ok(serde_yaml::to_string(curve)?.into_bytes()),
+        #[cfg(feature = "toml")]
+        CurveFormat::Toml => {
+            let mut value = toml::Value::try_from(curve)?;
+            shorten_floats(&mut value);
+            Ok(toml::to_string(&value)?.into_bytes())
+        }
+    }
+}
+
+/// TOML stores all floats as f64, write the shortest representation of the f32 instead, which parses to the same f32
+#[cfg(feature = "toml")]
+fn shorten_floats(value: &mut toml::Value) {
+    match value {
+        toml::Value::Float(f) => {
+            *f = (*f as f32).to_string().parse().unwrap_or(*f);
+        }
+        toml::Value::Array(array) => array.iter_mut().for_each(shorten_floats),
+        toml::Value::Table(table) => table
+            .iter_mut()
+            .for_each(|(_, value)| shorten_floats(value)),
+        _ => {}
+    }
+}
+
+/// Line and column of `offset` in `bytes`, both counted from 1 and columns in bytes, like RON
+#[cfg_attr(not(any(feature = "yaml", feature = "toml")), allow(dead_code))]
+fn line_column(bytes: &[u8], offset: usize) -> (usize, usize) {
+    let before = &bytes[..offset.min(bytes.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    (line, before.len() - line_start + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, KnotInterpolation, TangentSide};
+    use bevy_math::Vec2;
+
+    const RON: &[u8] = include_bytes!("../../assets/example.curve.ron");
+    #[cfg(feature = "yaml")]
+    const YAML: &[u8] = include_bytes!("../../assets/example.curve.yaml");
+    #[cfg(feature = "toml")]
+    const TOML: &[u8] = include_bytes!("../../assets/example.curve.toml");
+
+    #[test]
+    fn formats_by_path() {
+        assert_eq!(
+            CurveFormat::from_path("assets/example.curve.ron"),
+            Some(CurveFormat::Ron)
+        );
+        assert_eq!(CurveFormat::from_path("example.curve.png"), None);
+        assert_eq!(CurveFormat::from_path("example"), None);
+        #[cfg(feature = "yaml")]
+        for path in ["a.curve.yaml", "a.curve.yml", "A.CURVE.YAML"] {
+            assert_eq!(CurveFormat::from_path(path), Some(CurveFormat::Yaml));
+        }
+        #[cfg(feature = "toml")]
+        assert_eq!(
+            CurveFormat::from_path("a.curve.toml"),
+            Some(CurveFormat::Toml)
+        );
+        for &format in CurveFormat::all() {
+            for extension in format.extensions() {
+                assert_eq!(CurveFormat::from_path(extension), Some(format));
+            }
+        }
+    }
+
+    #[test]
+    fn equivalent_fixtures_load_the_same_curve() {
+        let ron = parse_curve(RON, CurveFormat::Ron).unwrap();
+        #[allow(unused_mut)]
+        let mut fixtures: Vec<(CurveFormat, &[u8])> = Vec::new();
+        #[cfg(feature = "yaml")]
+        fixtures.push((CurveFormat::Yaml, YAML));
+        #[cfg(feature = "toml")]
+        fixtures.push((CurveFormat::Toml, TOML));
+        for (format, bytes) in fixtures {
+            let curve = parse_curve(bytes, format).unwrap();
+            assert!(curve.shape_eq(&ron), "{format:?}");
+            assert_eq!(curve.name, ron.name);
+            assert_eq!(curve.knots().len(), ron.knots().len());
+        }
+    }
+
+    #[test]
+    fn saving_round_trips_each_format() {
+        let curve = parse_curve(RON, CurveFormat::Ron)
+            .unwrap()
+            .with_y_unit(crate::UnitDisplay {
+                unit: "ms".to_string(),
+                scale: 1000.0,
+                precision: 1,
+            });
+        for &format in CurveFormat::all() {
+            let bytes = serialize_curve(&curve, format).unwrap();
+            let parsed = parse_curve(&bytes, format).unwrap();
+            assert!(parsed.shape_eq(&curve), "{format:?}");
+            assert_eq!(parsed.y_unit, curve.y_unit);
+            // Saving is canonical, saving a loaded curve again writes the same bytes
+            assert_eq!(serialize_curve(&parsed, format).unwrap(), bytes);
+        }
+    }
+
+    #[cfg(any(feature = "yaml", feature = "toml"))]
+    #[test]
+    fn malformed_fixtures_report_their_position() {
+        #[cfg(feature = "yaml")]
+        {
+            let bytes = b"knots:\n  - position: [0.0, 0.0]\n    interpolation: Cubicc\n";
+            let Err(CurveParseError::Yaml {
+                offset,
+                line,
+                column,
+                ..
+            }) = parse_curve(bytes, CurveFormat::Yaml)
+            else {
+                panic!("expected a YAML error");
+            };
+            assert_eq!((line, column), (3, 20));
+            assert!(bytes[offset..].starts_with(b"Cubicc"));
+        }
+        #[cfg(feature = "toml")]
+        {
+            let bytes = b"[[knots]]\nposition = [0.0, 0.0]\ninterpolation = \"Cubicc\"\n";
+            let Err(CurveParseError::Toml {
+                offset,
+                line,
+                column,
+                ..
+            }) = parse_curve(bytes, CurveFormat::Toml)
+            else {
+                panic!("expected a TOML error");
+            };
+            assert_eq!((line, column), (3, 17));
+            assert!(bytes[offset..].starts_with(b"\"Cubicc\""));
+
+            let Err(CurveParseError::Toml { line, .. }) =
+                parse_curve(b"name = \"a\"\n\xff", CurveFormat::Toml)
+            else {
+                panic!("expected a TOML error");
+            };
+            assert_eq!(line, 2);
+        }
+    }
+
+    #[test]
+    fn round_trips_sample_the_same() {
+        let knot = |x: f32, y: f32, interpolation| {
+            Knot {
+                position: Vec2::new(x, y),
+                interpolation,
+                ..Default::default()
+            }
+            .with_tangent_slope(TangentSide::Left, 0.7)
+            .with_tangent_slope(TangentSide::Right, -2.5)
+        };
+        let curve = LookupCurve::new(vec![
+            knot(-0.25, 0.1, KnotInterpolation::Constant),
+            knot(0.1, 1.0 / 3.0, KnotInterpolation::Cubic)
+                .with_tangent_weight(TangentSide::Right, Some(0.6)),
+            knot(0.45, -0.7, KnotInterpolation::Cubic),
+            knot(0.8, 0.9, KnotInterpolation::Linear),
+            knot(0.8, 0.2, KnotInterpolation::Cubic),
+            knot(1.3, 0.35, KnotInterpolation::Linear),
+        ]);
+        for &format in CurveFormat::all() {
+            let bytes = serialize_curve(&curve, format).unwrap();
+            let parsed = parse_curve(&bytes, format).unwrap();
+            for i in -100..=400 {
+                let x = i as f32 / 250.0;
+                assert_eq!(
+                    parsed.lookup(x).to_bits(),
+                    curve.lookup(x).to_bits(),
+                    "{format:?} at {x}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unordered_knots_are_sorted_and_invalid_knots_rejected() {
+        let ron_knot = |x: &str| {
+            format!(
+                "(position: ({x}, 0.0), interpolation: Linear, \
+                left_tangent: (slope: 0.0, mode: Free), right_tangent: (slope: 0.0, mode: Free))"
+            )
+        };
+        #[allow(unused_mut)]
+        let mut documents = vec![(
+            CurveFormat::Ron,
+            format!(
+                "(knots: [{}, {}, {}])",
+                ron_knot("1.0"),
+                ron_knot("0.0"),
+                ron_knot("0.5")
+            ),
+            format!("(knots: [{}, {}])", ron_knot("0.0"), ron_knot("NaN")),
+        )];
+        #[cfg(feature = "yaml")]
+        {
+            let knot = |x: &str| {
+                format!(
+                    "  - position: [{x}, 0.0]\n    interpolation: Linear\n    \
+                    left_tangent: {{slope: 0.0, mode: Free}}\n    right_tangent: {{slope: 0.0, mode: Free}}\n"
+                )
+            };
+            documents.push((
+                CurveFormat::Yaml,
+                format!("knots:\n{}{}{}", knot("1.0"), knot("0.0"), knot("0.5")),
+                format!("knots:\n{}{}", knot("0.0"), knot(".nan")),
+            ));
+        }
+        #[cfg(feature = "toml")]
+        {
+            let knot = |x: &str| {
+                format!(
+                    "[[knots]]\nposition = [{x}, 0.0]\ninterpolation = \"Linear\"\n\
+                    left_tangent = {{ slope = 0.0, mode = \"Free\" }}\n\
+                    right_tangent = {{ slope = 0.0, mode = \"Free\" }}\n"
+                )
+            };
+            documents.push((
+                CurveFormat::Toml,
+                format!("{}{}{}", knot("1.0"), knot("0.0"), knot("0.5")),
+                format!("{}{}", knot("0.0"), knot("nan")),
+            ));
+        }
+        for (format, unordered, nan) in documents {
+            let curve = parse_curve(unordered.as_bytes(), format).unwrap();
+            let xs: Vec<f32> = curve.knots().iter().map(|k| k.position.x).collect();
+            assert_eq!(xs, [0.0, 0.5, 1.0], "{format:?}");
+
+            let error = parse_curve(nan.as_bytes(), format).unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains("knot 1 of lookup curve has a position that is not finite"),
+                "{format:?}: {error}"
+            );
+        }
+    }
+}
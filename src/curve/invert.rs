@@ -0,0 +1,475 @@
+use std::fmt;
+
+use bevy_math::Vec2;
+
+use crate::{
+    unweighted_cubic_interp, CubicSegment, Knot, KnotInterpolation, LookupCurve, QuantizeRule,
+    TangentMode, TangentSide,
+};
+
+/// Max number of times a cubic segment is halved while fitting its inverse
+const MAX_FIT_DEPTH: u32 = 16;
+/// Number of parts a fitted segment is split into when comparing it to the exact inverse
+const FIT_CHECKS: usize = 8;
+/// Iterations of bisection when solving for x on the original curve
+const SOLVE_ITERS: usize = 48;
+
+/// Error returned by [LookupCurve::inverted] when y is not strictly monotonic, so the curve has no inverse.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NotMonotonic {
+    /// y changes direction within the segment starting at the knot with this index,
+    /// or the segment goes in the other direction than the previous segments
+    Reverses { segment: usize },
+    /// y does not change over the segment starting at the knot with this index.
+    ///
+    /// [KnotInterpolation::Constant] and [KnotInterpolation::ConstantNext] segments are always flat, even if the
+    /// curve jumps at their end or start.
+    Flat { segment: usize },
+}
+
+impl fmt::Display for NotMonotonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotMonotonic::Reverses { segment } => {
+                write!(f, "Curve changes direction at segment {segment}")
+            }
+            NotMonotonic::Flat { segment } => write!(f, "Curve is flat at segment {segment}"),
+        }
+    }
+}
+
+impl std::error::Error for NotMonotonic {}
+
+impl LookupCurve {
+    /// Checks that y is strictly increasing or strictly decreasing over the whole curve, see [LookupCurve::inverted].
+    ///
+    /// Uses the exact extrema of cubic segments, so overshoot between knots is detected.
+    pub fn check_invertible(&self) -> Result<(), NotMonotonic> {
+        self.strict_direction().map(|_| ())
+    }
+
+    /// Returns the inverse of the curve, with x and y swapped.
+    ///
+    /// Linear segments are inverted exactly, as are [KnotInterpolation::Power] segments of increasing curves, with
+    /// the reciprocal exponent. The inverse of a cubic segment is not a cubic, so it is fitted with
+    /// unweighted cubic segments, halving them until they are within `tolerance` of the exact inverse on the x axis
+    /// of the original curve. Where the curve is flat the inverse is vertical, so close to these points the
+    /// subdivision stops after a fixed depth, and the fit might not reach `tolerance`.
+    ///
+    /// Curves with [KnotInterpolation::Constant] or [KnotInterpolation::ConstantNext] segments have no inverse, and return [NotMonotonic::Flat].
+    ///
+    /// The inverse keeps the name and lookup settings of the curve, and swaps its display units and the coordinates of its anchors.
+    /// It is clamped on both sides, see [Extrapolation](crate::Extrapolation), as most extrapolations have no inverse.
+    /// On curves with [LookupCurve::track_edit_times] all knots of the inverse are stamped as new knots.
+    pub fn inverted(&self, tolerance: f32) -> Result<LookupCurve, NotMonotonic> {
+        let increasing = self.strict_direction()?;
+        let tolerance = tolerance.abs();
+
+        // Knots of the inverse are in order of y of the original knots
+        let ordered: Vec<&Knot> = if increasing {
+            self.knots.iter().collect()
+        } else {
+            self.knots.iter().rev().collect()
+        };
+
+        let mut knots: Vec<Knot> = ordered
+            .first()
+            .map(|knot| Knot {
+                position: swapped(knot.position),
+                interpolation: KnotInterpolation::Linear,
+                ..Default::default()
+            })
+            .into_iter()
+            .collect();
+        for pair in ordered.windows(2) {
+            let (a, b) = if increasing {
+                (pair[0], pair[1])
+            } else {
+                (pair[1], pair[0])
+            };
+            let end = swapped(pair[1].position);
+            match a.interpolation {
+                KnotInterpolation::Power { exponent } if increasing => {
+                    let interpolation = KnotInterpolation::Power {
+                        exponent: exponent.recip(),
+                    };
+                    push_segment(&mut knots, end, interpolation, 0.0, 0.0);
+                }
+                // Of decreasing curves the inverse runs from the end of the segment, where it is not a power
+                KnotInterpolation::Cubic | KnotInterpolation::Power { .. }
+                    if a.position.x < b.position.x =>
+                {
+                    let start = swapped(pair[0].position);
+                    self.fit_inverse(a, b, start, end, tolerance, 0, &mut knots);
+                }
+                _ => push_segment(&mut knots, end, KnotInterpolation::Linear, 0.0, 0.0),
+            }
+        }
+
+        // The inverse is fitted from scratch, so all of its knots are new
+        let time = self.edit_time();
+        for knot in &mut knots {
+            *knot = knot.stamped_created(time);
+            if knot.left_tangent.slope != knot.right_tangent.slope {
+                knot.left_tangent.mode = TangentMode::Free;
+                knot.right_tangent.mode = TangentMode::Free;
+            }
+        }
+
+        Ok(LookupCurve {
+            knots: knots.into(),
+            max_iters: self.max_iters,
+            max_error: self.max_error,
+            name: self.name.clone(),
+            x_unit: self.y_unit.clone(),
+            y_unit: self.x_unit.clone(),
+            anchors: self.anchors.iter().map(|a| Vec2::new(a.y, a.x)).collect(),
+            strict_monotonic_x: self.strict_monotonic_x,
+            track_edit_times: self.track_edit_times,
+            edit_clock: self.edit_clock,
+            time_warp: self.time_warp,
+            // The axes are swapped, and the fitted knots are kept exact
+            quantize_storage: self.quantize_storage.map(|rule| QuantizeRule {
+                x_step: rule.y_step,
+                y_step: rule.x_step,
+                ..rule
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Returns `true` if y is strictly increasing, `false` if strictly decreasing
+    fn strict_direction(&self) -> Result<bool, NotMonotonic> {
+        let mut increasing = None;
+        for (segment, pair) in self.knots.windows(2).enumerate() {
+            let (a, b) = (&pair[0], &pair[1]);
+            if matches!(
+                a.interpolation,
+                KnotInterpolation::Constant | KnotInterpolation::ConstantNext
+            ) {
+                return Err(NotMonotonic::Flat { segment });
+            }
+            let ys = segment_ys(a, b);
+
+            let mut segment_increasing = None;
+            for dy in ys.windows(2).map(|w| w[1] - w[0]).filter(|&dy| dy != 0.0) {
+                match segment_increasing {
+                    None => segment_increasing = Some(dy > 0.0),
+                    Some(up) if up != (dy > 0.0) => return Err(NotMonotonic::Reverses { segment }),
+                    Some(_) => {}
+                }
+            }
+            let Some(segment_increasing) = segment_increasing else {
+                return Err(NotMonotonic::Flat { segment });
+            };
+            match increasing {
+                None => increasing = Some(segment_increasing),
+                Some(up) if up != segment_increasing => {
+                    return Err(NotMonotonic::Reverses { segment })
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(increasing.unwrap_or(true))
+    }
+
+    /// Fits the inverse of the cubic or power segment from `a` to `b` between the points `start` and `end` of the inverse,
+    /// and appends it to `knots`. The last knot in `knots` is expected to be at `start`.
+    #[allow(clippy::too_many_arguments)]
+    fn fit_inverse(
+        &self,
+        a: &Knot,
+        b: &Knot,
+        start: Vec2,
+        end: Vec2,
+        tolerance: f32,
+        depth: u32,
+        knots: &mut Vec<Knot>,
+    ) {
+        let width = end.x - start.x;
+        if width <= 0.0 {
+            push_segment(knots, end, KnotInterpolation::Linear, 0.0, 0.0);
+            return;
+        }
+        let secant = (end.y - start.y) / width;
+        let inverse_slope = |x: f32| {
+            let side = if x >= b.position.x {
+                TangentSide::Left
+            } else {
+                TangentSide::Right
+            };
+            limit_slope(1.0 / self.slope_at(x, side), secant)
+        };
+        let (slope_start, slope_end) = (inverse_slope(start.y), inverse_slope(end.y));
+
+        let fitted_start = Knot {
+            position: start,
+            ..Default::default()
+        }
+        .with_tangent_slope(TangentSide::Right, slope_start);
+        let fitted_end = Knot {
+            position: end,
+            ..Default::default()
+        }
+        .with_tangent_slope(TangentSide::Left, slope_end);
+        let error = (1..FIT_CHECKS)
+            .map(|i| start.x + width * i as f32 / FIT_CHECKS as f32)
+            .map(|y| {
+                (unweighted_cubic_interp(&fitted_start, &fitted_end, y) - self.solve_x(a, b, y))
+                    .abs()
+            })
+            .fold(0.0, f32::max);
+
+        if error <= tolerance || depth >= MAX_FIT_DEPTH {
+            push_segment(knots, end, KnotInterpolation::Cubic, slope_start, slope_end);
+            return;
+        }
+
+        let mid_y = start.x + width * 0.5;
+        let mid = Vec2::new(mid_y, self.solve_x(a, b, mid_y));
+        self.fit_inverse(a, b, start, mid, tolerance, depth + 1, knots);
+        self.fit_inverse(a, b, mid, end, tolerance, depth + 1, knots);
+    }
+
+    /// Finds x between `a` and `b` where the curve has the value `y`, using bisection.
+    ///
+    /// The curve is expected to be strictly monotonic between the knots.
+    pub(super) fn solve_x(&self, a: &Knot, b: &Knot, y: f32) -> f32 {
+        let increasing = b.position.y > a.position.y;
+        let (mut low, mut high) = (a.position.x, b.position.x);
+        for _ in 0..SOLVE_ITERS {
+            let mid = 0.5 * (low + high);
+            if mid <= low || mid >= high {
+                break;
+            }
+            if (self.lookup(mid) < y) == increasing {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        0.5 * (low + high)
+    }
+}
+
+/// y values of the segment from `a` to `b` where it might change direction, in order of x: the knots and the exact
+/// extrema of cubic segments. Constant segments step between the y of `a` and the y of `b`, and power segments are
+/// monotonic like linear segments.
+pub(super) fn segment_ys(a: &Knot, b: &Knot) -> Vec<f32> {
+    match a.interpolation {
+        KnotInterpolation::Constant
+        | KnotInterpolation::ConstantNext
+        | KnotInterpolation::Linear
+        | KnotInterpolation::Power { .. } => {
+            vec![a.position.y, b.position.y]
+        }
+        KnotInterpolation::Cubic => {
+            let cubic = CubicSegment::from_bezier_points(a.compute_bezier_to(b));
+            std::iter::once(a.position.y)
+                .chain(
+                    cubic
+                        .extrema_y()
+                        .into_iter()
+                        .flatten()
+                        .map(|t| cubic.position(t).y),
+                )
+                .chain(std::iter::once(b.position.y))
+                .collect()
+        }
+    }
+}
+
+#[inline]
+fn swapped(position: Vec2) -> Vec2 {
+    Vec2::new(position.y, position.x)
+}
+
+/// Limits the slope of a fitted segment to the direction of the segment, and to at most three times its secant,
+/// which keeps the segment monotonic. Infinite slopes are limited as well.
+fn limit_slope(slope: f32, secant: f32) -> f32 {
+    if slope.is_nan() {
+        return 0.0;
+    }
+    if slope.is_infinite() {
+        return 3.0 * secant;
+    }
+    if slope * secant <= 0.0 {
+        return 0.0;
+    }
+    secant.signum() * slope.abs().min(3.0 * secant.abs())
+}
+
+/// Appends a knot at `end`, connected to the last knot in `knots` with `interpolation`
+fn push_segment(
+    knots: &mut Vec<Knot>,
+    end: Vec2,
+    interpolation: KnotInterpolation,
+    slope_start: f32,
+    slope_end: f32,
+) {
+    if let Some(last) = knots.last_mut() {
+        last.interpolation = interpolation;
+        last.right_tangent.slope = slope_start;
+    }
+    let mut knot = Knot {
+        position: end,
+        interpolation: KnotInterpolation::Linear,
+        ..Default::default()
+    };
+    knot.left_tangent.slope = slope_end;
+    knots.push(knot);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    fn cubic(x: f32, y: f32, slope: f32) -> Knot {
+        knot(x, y, KnotInterpolation::Cubic).with_tangent_slope(TangentSide::Left, slope)
+    }
+
+    fn assert_round_trip(curve: &LookupCurve, tolerance: f32) {
+        let inverse = curve.inverted(tolerance).unwrap();
+        let (start, end) = (
+            curve.knots()[0].position.x,
+            curve.knots().last().unwrap().position.x,
+        );
+        for i in 0..=200 {
+            let x = start + (end - start) * i as f32 / 200.0;
+            let round_trip = inverse.lookup(curve.lookup(x));
+            assert!(
+                (round_trip - x).abs() <= tolerance * 2.0,
+                "x = {x}, round trip = {round_trip}"
+            );
+        }
+    }
+
+    #[test]
+    fn inverts_monotone_bezier_curve() {
+        let mut weighted = cubic(1.0, 2.0, 1.5);
+        weighted.left_tangent.weight = Some(0.4);
+        let curve = LookupCurve::new(vec![cubic(0.0, 0.0, 0.5), weighted, cubic(3.0, 4.0, 0.8)]);
+        assert_round_trip(&curve, 1e-3);
+    }
+
+    #[test]
+    fn inverts_decreasing_curve() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 10.0, KnotInterpolation::Linear),
+            cubic(2.0, 4.0, -2.0),
+            cubic(4.0, 1.0, -1.0),
+        ]);
+        let inverse = curve.inverted(1e-3).unwrap();
+        let xs: Vec<f32> = inverse.knots().iter().map(|k| k.position.x).collect();
+        assert!(xs.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            inverse.knots().first().unwrap().position,
+            Vec2::new(1.0, 4.0)
+        );
+        assert_eq!(
+            inverse.knots().last().unwrap().position,
+            Vec2::new(10.0, 0.0)
+        );
+        assert_round_trip(&curve, 1e-3);
+    }
+
+    #[test]
+    fn inverts_linear_segments_exactly() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 4.0, KnotInterpolation::Linear),
+            knot(3.0, 5.0, KnotInterpolation::Linear),
+        ]);
+        let inverse = curve.inverted(1e-3).unwrap();
+        let positions: Vec<Vec2> = inverse.knots().iter().map(|k| k.position).collect();
+        assert_eq!(
+            positions,
+            vec![Vec2::ZERO, Vec2::new(4.0, 1.0), Vec2::new(5.0, 3.0)]
+        );
+        assert_eq!(inverse.lookup(2.0), 0.5);
+    }
+
+    #[test]
+    fn inverts_power_segments() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Power { exponent: 4.0 }),
+            knot(1.0, 2.0, KnotInterpolation::Power { exponent: 0.5 }),
+            knot(2.0, 3.0, KnotInterpolation::Linear),
+        ]);
+        let inverse = curve.inverted(1e-3).unwrap();
+        let interpolations: Vec<_> = inverse.knots().iter().map(|k| k.interpolation).collect();
+        assert_eq!(
+            interpolations[..2],
+            [
+                KnotInterpolation::Power { exponent: 0.25 },
+                KnotInterpolation::Power { exponent: 2.0 }
+            ]
+        );
+        assert_round_trip(&curve, 1e-3);
+
+        // The inverse of a decreasing power segment is fitted
+        let decreasing = LookupCurve::new(vec![
+            knot(0.0, 2.0, KnotInterpolation::Power { exponent: 2.0 }),
+            knot(1.0, 0.0, KnotInterpolation::Linear),
+        ]);
+        assert_round_trip(&decreasing, 1e-3);
+
+        let step = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::ConstantNext),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(
+            step.check_invertible(),
+            Err(NotMonotonic::Flat { segment: 0 })
+        );
+    }
+
+    #[test]
+    fn rejects_non_monotonic_curves() {
+        let overshoot = LookupCurve::new(vec![cubic(0.0, 0.0, 4.0), cubic(1.0, 1.0, 0.0)]);
+        assert_eq!(
+            overshoot.inverted(1e-3).unwrap_err(),
+            NotMonotonic::Reverses { segment: 0 }
+        );
+
+        let up_and_down = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+            knot(2.0, 0.5, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(
+            up_and_down.check_invertible(),
+            Err(NotMonotonic::Reverses { segment: 1 })
+        );
+
+        let flat = LookupCurve::new(vec![
+            knot(0.0, 1.0, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(
+            flat.check_invertible(),
+            Err(NotMonotonic::Flat { segment: 0 })
+        );
+    }
+
+    #[test]
+    fn constant_segments_are_flat() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Constant),
+            knot(2.0, 2.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(
+            curve.inverted(1e-3).unwrap_err(),
+            NotMonotonic::Flat { segment: 1 }
+        );
+    }
+}
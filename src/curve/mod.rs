@@ -0,0 +1,1426 @@
+//! The [LookupCurve] with its [Knot]s, and operations creating and editing curves
+
+use bevy_math::Vec2;
+use smallvec::SmallVec;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+mod anchors;
+pub use anchors::AnchorViolation;
+
+mod auto_tangents;
+
+mod coincident;
+pub use coincident::MergeY;
+
+mod consistency;
+
+mod crossings;
+
+mod describe;
+pub use describe::{CurveDescription, SegmentDescription, SegmentEvaluation};
+
+mod determinism;
+pub use determinism::{quantize, quantize_position, IdStrategy};
+
+mod distribution;
+
+mod edit_effect;
+pub(crate) use edit_effect::EditLog;
+pub use edit_effect::{EditEffect, EDIT_LOG_LEN};
+
+mod edit_times;
+pub use edit_times::{EditClock, EditStats};
+
+mod extrapolation;
+pub use extrapolation::Extrapolation;
+pub(crate) use extrapolation::{Extrapolator, Outside};
+
+mod invert;
+pub use invert::NotMonotonic;
+
+mod monotonic_x;
+
+mod order;
+
+mod presets;
+
+mod quantize_rule;
+pub use quantize_rule::QuantizeRule;
+
+mod randomize;
+pub use randomize::{RandomizeParams, YJitterMode};
+
+#[cfg(feature = "ron")]
+mod formats;
+#[cfg(feature = "ron")]
+pub use formats::{parse_curve, serialize_curve, CurveFormat};
+
+#[cfg(feature = "ron")]
+mod ron_format;
+#[cfg(feature = "ron")]
+pub use ron_format::{parse_curve_ron, serialize_curve_ron, CurveParseError, RonStyle};
+
+mod reference;
+#[cfg(feature = "editor_egui")]
+pub(crate) use reference::sample_adaptive;
+pub use reference::FitError;
+
+mod replace;
+pub use replace::PreserveOptions;
+
+mod shape_hash;
+pub use shape_hash::SHAPE_HASH_VERSION;
+
+mod smoothness;
+pub use smoothness::SmoothPolicy;
+
+mod splice;
+pub use splice::StitchMode;
+
+mod stretch;
+pub use stretch::{StretchMode, MIN_STRETCH_GAP};
+
+mod subdivide;
+mod transform;
+
+mod time_warp;
+pub use time_warp::{TimeWarpCurve, TimeWarpError, TIME_WARP_EPSILON};
+
+mod units;
+pub use units::{UnitDisplay, UnitParseError};
+
+#[cfg(feature = "ron")]
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum LookupCurveLoadError {
+    /// An [IO](std::io) Error
+    #[error("Could not load lookup curve: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file could not be parsed, see [parse_curve]
+    #[error(transparent)]
+    Parse(#[from] CurveParseError),
+    /// A [postcard] Error, while loading a processed curve
+    #[cfg(feature = "asset_processing")]
+    #[error("Could not decode processed lookup curve: {0}")]
+    Binary(#[from] postcard::Error),
+    /// The processed curve was encoded by an unsupported version of this crate
+    #[cfg(feature = "asset_processing")]
+    #[error("Processed lookup curve has an unsupported encoding version")]
+    UnsupportedBinaryVersion,
+}
+
+#[cfg(feature = "ron")]
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum LookupCurveSaveError {
+    /// An [IO](std::io) Error
+    #[error("Could not save lookup curve: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not serialize lookup curve to RON: {0}")]
+    RonError(#[from] ron::error::Error),
+    /// A [YAML](serde_yaml) Error
+    #[cfg(feature = "yaml")]
+    #[error("Could not serialize lookup curve to YAML: {0}")]
+    YamlError(#[from] serde_yaml::Error),
+    /// A [TOML](toml) Error
+    #[cfg(feature = "toml")]
+    #[error("Could not serialize lookup curve to TOML: {0}")]
+    TomlError(#[from] toml::ser::Error),
+}
+
+/// How a tangent behaves when a knot or its tangents are moved
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum TangentMode {
+    /// The tangent can be freely moved without affecting the other tangent
+    Free,
+    /// When moving the tangent, the other tangent will be updated for a smooth curve.
+    ///
+    /// Both tangents need [TangentMode::Aligned] for this to apply.
+    Aligned,
+    /// The slope is computed from the neighboring knots, like in a Catmull-Rom spline, and updated whenever knots are
+    /// added, moved or removed. The first and last knot use the slope to their only neighbor.
+    ///
+    /// Both tangents of a knot with [TangentMode::Auto] get the same slope, so the curve is smooth at the knot. The
+    /// weight of the tangent is kept, and limited like any other weight with [LookupCurve::strict_monotonic_x].
+    /// Setting the slope of an auto tangent, see [Knot::with_tangent_slope], makes it [TangentMode::Aligned].
+    Auto,
+}
+
+/// Tangents are used to control cubic interpolation for [Knot]s in a [LookupCurve]
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct Tangent {
+    pub slope: f32,
+    pub mode: TangentMode,
+    pub weight: Option<f32>,
+}
+
+impl Tangent {
+    fn default_left() -> Self {
+        Self {
+            slope: 0.0,
+            ..Default::default()
+        }
+    }
+
+    fn default_right() -> Self {
+        Self {
+            slope: 0.0,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Tangent {
+    fn default() -> Self {
+        Self {
+            slope: 0.0,
+            mode: TangentMode::Aligned,
+            weight: None,
+        }
+    }
+}
+
+/// Interpolation used between a [Knot] the next knot
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum KnotInterpolation {
+    /// Holds the y of the knot until the next knot, stepping to the next y at the next knot
+    Constant,
+    Linear,
+    Cubic,
+    // Binary curves store the index of the variant, new variants go last
+    /// Steps to the y of the next knot right after the knot, and holds it until the next knot. Sampled at the x of
+    /// the knot the curve has the y of the knot.
+    ///
+    /// Useful for discrete values aligned to the end of an interval, like animation frames.
+    ConstantNext,
+    /// Straight line between the knots, eased by raising the fraction of the segment to `exponent`: the y at the
+    /// fraction `s` of the segment is `y0 + (y1 - y0) * s^exponent`. Exponents above 1 start slowly and end quickly,
+    /// exponents below 1 start quickly and end slowly, and 1 is [KnotInterpolation::Linear].
+    ///
+    /// The exponent must be positive and finite. With exponents below 1 the segment starts vertically.
+    Power {
+        exponent: f32,
+    },
+}
+
+impl KnotInterpolation {
+    /// For segments moving from the y of the knot to the y of the next knot as `y0 + (y1 - y0) * f(s)`, where `s` is
+    /// the fraction of the segment in `0..=1`, returns the derivative of `f` at `s`. Infinite at the start of
+    /// [KnotInterpolation::Power] segments with exponents below 1, `None` for [KnotInterpolation::Cubic].
+    #[inline]
+    pub(crate) fn fraction_slope(self, s: f32) -> Option<f32> {
+        match self {
+            KnotInterpolation::Constant | KnotInterpolation::ConstantNext => Some(0.0),
+            KnotInterpolation::Linear => Some(1.0),
+            KnotInterpolation::Power { exponent } => Some(exponent * s.powf(exponent - 1.0)),
+            KnotInterpolation::Cubic => None,
+        }
+    }
+
+    /// Whether the parameters of the interpolation are valid, the exponent of [KnotInterpolation::Power] must be
+    /// positive and finite
+    pub fn is_valid(self) -> bool {
+        match self {
+            KnotInterpolation::Power { exponent } => exponent > 0.0 && exponent.is_finite(),
+            _ => true,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+/// A knot in a [LookupCurve].
+pub struct Knot {
+    /// The position of this knot in curve space
+    pub position: Vec2,
+
+    /// Interpolation used between this and the next knot
+    pub interpolation: KnotInterpolation,
+
+    /// Left tangent defining in slope and weight
+    pub left_tangent: Tangent,
+    /// Right tangent defining out slope and weight
+    pub right_tangent: Tangent,
+
+    /// Identifier used by editor operations because index might change during modification, see
+    /// [LookupCurve::knot_index_of]
+    ///
+    /// There should not be any need to change this as it will be set internally. Ids are unique within a curve:
+    /// knots with id 0 or the id of another knot of the curve get a new id when the curve is constructed or
+    /// deserialized, and when they are added. A knot modified to have such an id keeps its own.
+    /// Ids are not stored in RON files, see [IdStrategy] for how ids of new knots are chosen.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(skip_serializing, default = "unique_knot_id")
+    )]
+    #[cfg_attr(
+        feature = "bevy_reflect",
+        reflect(skip_serializing, default = "unique_knot_id")
+    )]
+    pub id: usize,
+
+    /// Time this knot was created, on curves with [LookupCurve::track_edit_times]. See [EditClock] for the unit.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub created_at: Option<u64>,
+    /// Time this knot was last modified, on curves with [LookupCurve::track_edit_times]
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub modified_at: Option<u64>,
+}
+
+pub(crate) fn unique_knot_id() -> usize {
+    static KNOT_ID_COUNTER: AtomicUsize = AtomicUsize::new(1);
+    KNOT_ID_COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Number of knots up to which ids are checked for duplicates without allocating
+const PAIRWISE_ID_CHECK_LEN: usize = 32;
+
+/// Gives knots with id 0, or with the id of an earlier knot, a new unique id. Returns `true` if any id changed.
+fn assign_unique_ids(knots: &mut [Knot]) -> bool {
+    // Knots created one after another have increasing ids, and small curves are compared pairwise, as the full
+    // check allocates
+    let unique = knots.windows(2).all(|pair| pair[0].id < pair[1].id)
+        || (knots.len() <= PAIRWISE_ID_CHECK_LEN
+            && (0..knots.len()).all(|i| knots[i + 1..].iter().all(|knot| knot.id != knots[i].id)));
+    if unique && knots.iter().all(|knot| knot.id != 0) {
+        return false;
+    }
+    let mut used = HashSet::with_capacity(knots.len());
+    let duplicates: Vec<usize> = (0..knots.len())
+        .filter(|&i| knots[i].id == 0 || !used.insert(knots[i].id))
+        .collect();
+    for &i in &duplicates {
+        // Explicitly chosen ids of later knots might be next in the counter
+        knots[i].id = std::iter::repeat_with(unique_knot_id)
+            .find(|id| used.insert(*id))
+            .unwrap();
+    }
+    !duplicates.is_empty()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TangentSide {
+    Left,
+    Right,
+}
+
+impl Knot {
+    /// Returns a new knot copied from self, with the tangent slope decided by `side` set to `slope`. This might also affect the other tangent depending on [`TangentMode`].
+    ///
+    /// Tangents with [TangentMode::Auto] that get the slope become [TangentMode::Aligned].
+    pub fn with_tangent_slope(&self, side: TangentSide, slope: f32) -> Self {
+        let mut knot = *self;
+
+        let aligned = matches!(
+            (self.left_tangent.mode, self.right_tangent.mode),
+            (
+                TangentMode::Aligned | TangentMode::Auto,
+                TangentMode::Aligned | TangentMode::Auto
+            )
+        );
+
+        for (tangent_side, tangent) in [
+            (TangentSide::Left, &mut knot.left_tangent),
+            (TangentSide::Right, &mut knot.right_tangent),
+        ] {
+            if tangent_side == side || aligned {
+                tangent.slope = slope;
+                if tangent.mode == TangentMode::Auto {
+                    // Keep the slope instead of computing it again
+                    tangent.mode = TangentMode::Aligned;
+                }
+            }
+        }
+
+        knot
+    }
+
+    /// Returns a new knot copied from self, with the tangent mode decided by `side` set to `mode`.
+    pub fn with_tangent_mode(&self, side: TangentSide, mode: TangentMode) -> Self {
+        let mut knot = *self;
+        match side {
+            TangentSide::Left => knot.left_tangent.mode = mode,
+            TangentSide::Right => knot.right_tangent.mode = mode,
+        }
+        knot
+    }
+
+    /// Returns a new knot copied from self, with the tangent weight decided by `side` set to `weight`. Weights will be clamped between 0 and 1.
+    pub fn with_tangent_weight(&self, side: TangentSide, weight: Option<f32>) -> Self {
+        let mut knot = *self;
+        let weight = weight.map(|w| w.clamp(0.0, 1.0));
+        match side {
+            TangentSide::Left => knot.left_tangent.weight = weight,
+            TangentSide::Right => knot.right_tangent.weight = weight,
+        }
+        knot
+    }
+
+    /// Returns `true` if the position, the tangent slopes and the tangent weights of the knot are finite
+    pub fn is_finite(&self) -> bool {
+        let tangent_finite =
+            |t: &Tangent| t.slope.is_finite() && t.weight.is_none_or(f32::is_finite);
+        self.position.is_finite()
+            && tangent_finite(&self.left_tangent)
+            && tangent_finite(&self.right_tangent)
+    }
+
+    #[inline]
+    pub(crate) fn compute_bezier_to(&self, knot_b: &Knot) -> [Vec2; 4] {
+        let slope_a = self.right_tangent.slope;
+        let weight_a = self.right_tangent.weight.unwrap_or(1. / 3.);
+        let slope_b = knot_b.left_tangent.slope;
+        let weight_b = knot_b.left_tangent.weight.unwrap_or(1. / 3.);
+        let dx = knot_b.position.x - self.position.x;
+        [
+            self.position,
+            Vec2::new(
+                self.position.x + weight_a * dx,
+                self.position.y + weight_a * slope_a * dx,
+            ),
+            Vec2::new(
+                knot_b.position.x - weight_b * dx,
+                knot_b.position.y - weight_b * slope_b * dx,
+            ),
+            knot_b.position,
+        ]
+    }
+}
+
+impl Default for Knot {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            interpolation: KnotInterpolation::Linear,
+            id: unique_knot_id(),
+            right_tangent: Tangent::default_right(),
+            left_tangent: Tangent::default_left(),
+            created_at: None,
+            modified_at: None,
+        }
+    }
+}
+
+const fn max_iters_default() -> u8 {
+    20
+}
+const fn max_error_default() -> f32 {
+    1e-5
+}
+
+/// Number of knots a [LookupCurve] stores without allocating
+pub(crate) const INLINE_KNOTS: usize = 4;
+
+/// Two-dimensional spline that only allows a single y-value per x-value
+///
+/// Curves with up to 4 knots store them inline, so creating and cloning small curves does not allocate.
+///
+/// Curves have no lazily built caches or interior mutability. Every mutating method takes `&mut self` and leaves the
+/// curve consistent, so a `&LookupCurve` can be read from any number of systems or threads without locks. Data
+/// derived from the knots is kept outside of the curve, like [LookupCache](crate::LookupCache) or
+/// [DerivedStore](crate::derived::DerivedStore), and is invalidated by [LookupCurve::generation], or partly with
+/// [LookupCurve::edit_effect_since].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "bevy_asset", derive(bevy_asset::Asset))]
+pub struct LookupCurve {
+    /// Deserialized knots are sorted by x like in [LookupCurve::new]
+    #[cfg_attr(feature = "serialize", serde(deserialize_with = "deserialize_knots"))]
+    pub(crate) knots: SmallVec<[Knot; INLINE_KNOTS]>,
+
+    /// Max number of iterations used for Newton-Rhapson iteration in weighted cubic segments
+    #[cfg_attr(
+        feature = "serialize",
+        serde(skip_serializing, default = "max_iters_default")
+    )]
+    #[cfg_attr(
+        feature = "bevy_reflect",
+        reflect(skip_serializing, default = "max_iters_default")
+    )]
+    pub max_iters: u8,
+    /// Max error allowed before breaking Newton-Rhapson iteration in weighted cubic segments
+    #[cfg_attr(
+        feature = "serialize",
+        serde(skip_serializing, default = "max_error_default")
+    )]
+    #[cfg_attr(
+        feature = "bevy_reflect",
+        reflect(skip_serializing, default = "max_error_default")
+    )]
+    pub max_error: f32,
+
+    pub name: Option<String>,
+
+    /// How x values are displayed in the editor
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub x_unit: Option<UnitDisplay>,
+    /// How y values are displayed in the editor
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub y_unit: Option<UnitDisplay>,
+
+    /// Points the curve must pass through, see [LookupCurve::validate_anchors] and [LookupCurve::enforce_anchors]
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub anchors: Vec<Vec2>,
+
+    /// Keep the tangents of every cubic segment from overlapping on the x axis, so that the stored tangents are
+    /// evaluated as is. Adding and modifying knots limits their tangent weights, see [LookupCurve::enforce_monotonic_x].
+    ///
+    /// Setting this directly does not correct existing segments, use [LookupCurve::with_strict_monotonic_x].
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "is_false")
+    )]
+    pub strict_monotonic_x: bool,
+
+    /// How the curve continues before the first knot, see [Extrapolation]
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Extrapolation::is_default")
+    )]
+    pub extrapolate_before: Extrapolation,
+    /// How the curve continues after the last knot, see [Extrapolation]
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Extrapolation::is_default")
+    )]
+    pub extrapolate_after: Extrapolation,
+
+    /// Stamp [Knot::created_at] and [Knot::modified_at] when knots are added or modified, using
+    /// [LookupCurve::edit_clock].
+    ///
+    /// Repairs like [LookupCurve::enforce_monotonic_x] stamp the knots they change. Knots inserted by
+    /// [LookupCurve::splice] keep the times of the source knots.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "is_false")
+    )]
+    pub track_edit_times: bool,
+    /// Clock used with [LookupCurve::track_edit_times]
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "EditClock::is_default")
+    )]
+    pub edit_clock: EditClock,
+
+    /// Marks the curve as remapping normalized time, see [TimeWarpCurve]. Edits in the editor are repaired with
+    /// [LookupCurve::enforce_time_warp], keeping the end knots at `(0, 0)` and `(1, 1)` and y from decreasing.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "is_false")
+    )]
+    pub time_warp: bool,
+
+    /// Rounding of the stored knot values, see [QuantizeRule]. Enforced when knots are added, by transforms like
+    /// [LookupCurve::scaled] and [LookupCurve::splice], and by the editor after every edit, once drags complete.
+    /// Other edits, like [LookupCurve::modify_knot], keep exact values until [LookupCurve::requantize].
+    ///
+    /// Setting this directly does not round existing knots, use [LookupCurve::with_quantize_storage].
+    #[cfg_attr(
+        feature = "serialize",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub quantize_storage: Option<QuantizeRule>,
+
+    /// Changed by every mutating operation, see [LookupCurve::generation]
+    #[cfg_attr(feature = "serialize", serde(skip, default = "next_generation"))]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore, default = "next_generation"))]
+    pub(crate) generation: u64,
+    /// Changed when the order of the knot ids changes, see [LookupCurve::order_epoch]
+    #[cfg_attr(feature = "serialize", serde(skip, default = "next_generation"))]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore, default = "next_generation"))]
+    pub(crate) order_epoch: u64,
+    /// Segments changed by the last generations, see [LookupCurve::edit_effect_since]
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub(crate) edit_log: EditLog,
+}
+
+#[cfg(feature = "serialize")]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Deserializes knots like [LookupCurve::new] constructs a curve, but fails instead of panicking on positions that are
+/// not finite. Slopes of [TangentMode::Auto] tangents are computed again. Tangent weights must be within `0..=1`, like set by [Knot::with_tangent_weight], and interpolations
+/// [valid](KnotInterpolation::is_valid). Tangents that are not finite are kept for [processing](crate::processing) to reject or repair.
+#[cfg(feature = "serialize")]
+fn deserialize_knots<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SmallVec<[Knot; INLINE_KNOTS]>, D::Error> {
+    let mut knots =
+        <SmallVec<[Knot; INLINE_KNOTS]> as serde::Deserialize>::deserialize(deserializer)?;
+    for (index, knot) in knots.iter().enumerate() {
+        if !knot.position.is_finite() {
+            #[cfg(feature = "trace")]
+            tracing::debug!(index, position = %knot.position, "rejected knot with a position that is not finite");
+            return Err(serde::de::Error::custom(format_args!(
+                "knot {index} of lookup curve has a position that is not finite: {}",
+                knot.position
+            )));
+        }
+        if !knot.interpolation.is_valid() {
+            #[cfg(feature = "trace")]
+            tracing::debug!(index, interpolation = ?knot.interpolation, "rejected knot with an invalid interpolation");
+            return Err(serde::de::Error::custom(format_args!(
+                "knot {index} of lookup curve has an invalid interpolation: {:?}",
+                knot.interpolation
+            )));
+        }
+        for (side, tangent) in [("left", &knot.left_tangent), ("right", &knot.right_tangent)] {
+            if let Some(weight) = tangent
+                .weight
+                .filter(|w| w.is_finite() && !(0.0..=1.0).contains(w))
+            {
+                #[cfg(feature = "trace")]
+                tracing::debug!(
+                    index,
+                    side,
+                    weight,
+                    "rejected knot with a tangent weight outside of 0..=1"
+                );
+                return Err(serde::de::Error::custom(format_args!(
+                    "knot {index} of lookup curve has a {side} tangent weight of {weight}, outside of 0..=1"
+                )));
+            }
+        }
+    }
+    assign_unique_ids(&mut knots);
+    sort_knots(&mut knots);
+    for i in 0..knots.len() {
+        auto_tangents::update_auto_slopes(&mut knots, i);
+    }
+    Ok(knots)
+}
+
+/// Sorts knots by x, keeping the order of knots with the same x. Panics if an x is NaN.
+fn sort_knots(knots: &mut [Knot]) {
+    let by_x = |a: &Knot, b: &Knot| {
+        a.position
+            .x
+            .partial_cmp(&b.position.x)
+            .expect("NaN is not allowed")
+    };
+    // Checked first, as sorting may allocate a buffer
+    if knots
+        .windows(2)
+        .any(|pair| by_x(&pair[0], &pair[1]).is_gt())
+    {
+        knots.sort_by(by_x);
+    }
+}
+
+/// Initial generation for a new curve. Each curve gets its own range of generations in the upper bits,
+/// so that generations of different curves do not collide.
+pub(crate) fn next_generation() -> u64 {
+    static CURVE_COUNTER: AtomicU64 = AtomicU64::new(1);
+    CURVE_COUNTER.fetch_add(1, Ordering::Relaxed) << 32
+}
+
+impl Default for LookupCurve {
+    fn default() -> Self {
+        Self {
+            knots: SmallVec::new(),
+            max_iters: max_iters_default(),
+            max_error: max_error_default(),
+            name: None,
+            x_unit: None,
+            y_unit: None,
+            anchors: Vec::new(),
+            strict_monotonic_x: false,
+            extrapolate_before: Extrapolation::Clamp,
+            extrapolate_after: Extrapolation::Clamp,
+            track_edit_times: false,
+            edit_clock: EditClock::Counter,
+            time_warp: false,
+            quantize_storage: None,
+            generation: next_generation(),
+            order_epoch: next_generation(),
+            edit_log: EditLog::default(),
+        }
+    }
+}
+
+impl From<Vec<Knot>> for LookupCurve {
+    fn from(knots: Vec<Knot>) -> Self {
+        Self::new(knots)
+    }
+}
+
+impl<const N: usize> From<[Knot; N]> for LookupCurve {
+    fn from(knots: [Knot; N]) -> Self {
+        Self::from_smallvec(knots.into_iter().collect())
+    }
+}
+
+impl FromIterator<Knot> for LookupCurve {
+    fn from_iter<I: IntoIterator<Item = Knot>>(knots: I) -> Self {
+        Self::from_smallvec(knots.into_iter().collect())
+    }
+}
+
+impl LookupCurve {
+    /// Constructs a curve from `knots` in any order. Knots with id 0 or the id of an earlier knot get a new id.
+    ///
+    /// Up to 4 knots are moved inline and the `Vec` is freed, use [LookupCurve::from] with an array
+    /// to create small curves without allocating.
+    pub fn new(knots: Vec<Knot>) -> Self {
+        Self::from_smallvec(SmallVec::from_vec(knots))
+    }
+
+    pub(crate) fn from_smallvec(mut knots: SmallVec<[Knot; INLINE_KNOTS]>) -> Self {
+        assign_unique_ids(&mut knots);
+        sort_knots(&mut knots);
+        let mut curve = Self {
+            knots,
+            ..Default::default()
+        };
+        curve.refresh_auto_tangents();
+        curve
+    }
+
+    /// Consumes the curve and returns it with max_iters set to the new value
+    pub fn with_max_iters(mut self, max_iters: u8) -> Self {
+        self.max_iters = max_iters;
+        self.bump_generation();
+        self
+    }
+
+    /// Consumes the curve and returns it with max_errors set to the new value
+    pub fn with_max_error(mut self, max_error: f32) -> Self {
+        self.max_error = max_error;
+        self.bump_generation();
+        self
+    }
+
+    /// Returns the current generation of the curve.
+    ///
+    /// The generation is incremented by one for every mutating operation on the curve,
+    /// and can be used to know when data derived from the curve needs to be recomputed.
+    /// A generation never repeats within the lifetime of a curve, and different curves start at
+    /// different generations. Clones keep the generation of the original until they are changed: the first change of
+    /// a clone continues in a new range of generations, so that copies of a curve that were changed independently
+    /// never have the same generation. [LookupCurve::edit_effect_since] reports all segments as changed since the
+    /// generations before that first change.
+    ///
+    /// Note that directly changing the public fields, or the knots through reflection, does not change the generation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    #[inline]
+    pub(crate) fn bump_generation(&mut self) {
+        if self.edit_log.take_cloned() {
+            self.generation = next_generation();
+        }
+        self.generation = self.generation.wrapping_add(1);
+        self.edit_log.push(self.generation);
+    }
+
+    /// Consumes the curve and returns it with name set
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self.bump_generation();
+        self
+    }
+
+    /// Consumes the curve and returns it with the display unit for x set
+    pub fn with_x_unit(mut self, unit: UnitDisplay) -> Self {
+        self.x_unit = Some(unit);
+        self.bump_generation();
+        self
+    }
+
+    /// Consumes the curve and returns it with the display unit for y set
+    pub fn with_y_unit(mut self, unit: UnitDisplay) -> Self {
+        self.y_unit = Some(unit);
+        self.bump_generation();
+        self
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn name_or_default(&self) -> &str {
+        self.name.as_deref().unwrap_or("Unnamed lookup curve")
+    }
+
+    #[cfg(feature = "ron")]
+    /// Loads a lookup curve from a file, in the [CurveFormat] of the extension of `path` or RON for unknown
+    /// extensions
+    pub fn load_from_file(path: &str) -> Result<Self, LookupCurveLoadError> {
+        let bytes = std::fs::read(path)?;
+        let format = CurveFormat::from_path(path).unwrap_or_default();
+        let lookup_curve = Self::from_text_bytes(&bytes, path, format)?;
+        Ok(lookup_curve)
+    }
+
+    /// Deserializes a lookup curve with [parse_curve]. `path` is only used for diagnostics.
+    #[cfg(feature = "ron")]
+    #[cfg_attr(not(feature = "trace"), allow(unused_variables))]
+    pub(crate) fn from_text_bytes(
+        bytes: &[u8],
+        path: &str,
+        format: CurveFormat,
+    ) -> Result<Self, CurveParseError> {
+        #[cfg(feature = "trace")]
+        let span = tracing::info_span!(
+            "lookup_curve_load",
+            path,
+            knot_count = tracing::field::Empty
+        )
+        .entered();
+
+        let lookup_curve = parse_curve(bytes, format)?;
+
+        #[cfg(feature = "trace")]
+        span.record("knot_count", lookup_curve.knots.len());
+
+        Ok(lookup_curve)
+    }
+
+    #[cfg(feature = "ron")]
+    /// Serializes the lookup curve and saves it as a file, in the [CurveFormat] of the extension of `path` or RON
+    /// for unknown extensions
+    pub fn save_to_file(&self, path: &str) -> Result<(), LookupCurveSaveError> {
+        self.save_to_file_as(path, CurveFormat::from_path(path).unwrap_or_default())
+    }
+
+    #[cfg(feature = "ron")]
+    /// Serializes the lookup curve and saves it as a file in `format`, regardless of the extension of `path`
+    pub fn save_to_file_as(
+        &self,
+        path: &str,
+        format: CurveFormat,
+    ) -> Result<(), LookupCurveSaveError> {
+        let bytes = serialize_curve(self, format)?;
+        std::fs::write(path, bytes)?;
+
+        Ok(())
+    }
+
+    /// Returns the knots in the curve as a slice, in ascending order of x.
+    ///
+    /// Knots with the same x keep their relative order: knots passed to [LookupCurve::new] or deserialized keep the
+    /// order they were given in, added knots are placed after knots with the same x, and knots moved onto the x of
+    /// other knots are placed before them. The order only changes when knots are added, deleted or moved past each
+    /// other, see [LookupCurve::order_epoch].
+    pub fn knots(&self) -> &[Knot] {
+        self.knots.as_slice()
+    }
+
+    #[inline]
+    /// Given a knot index, returns the previous knot in the curve, or `None` if there is no previous knot.
+    pub fn prev_knot(&self, i: usize) -> Option<&Knot> {
+        if i > 0 {
+            Some(&self.knots[i - 1])
+        } else {
+            None
+        }
+    }
+
+    /// Given a knot index, returns the next knot in the curve, or `None` if there is no next knot.
+    #[inline]
+    pub fn next_knot(&self, i: usize) -> Option<&Knot> {
+        if i < self.knots.len() - 1 {
+            Some(&self.knots[i + 1])
+        } else {
+            None
+        }
+    }
+
+    /// Adds a knot to the curve. Returns the index of the added knot.
+    ///
+    /// The knot is inserted after any knots with the same x. If its id is 0 or already used by another knot of the
+    /// curve, it gets a new unique id.
+    ///
+    /// On curves with [LookupCurve::strict_monotonic_x] the tangent weights of the knot are limited, and on curves with
+    /// [LookupCurve::quantize_storage] its values are rounded.
+    ///
+    /// On curves with [LookupCurve::track_edit_times] both edit times of the knot are set to the current time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position of the knot is not finite, see [LookupCurve::try_add_knot].
+    pub fn add_knot(&mut self, knot: Knot) -> usize {
+        self.try_add_knot(knot)
+            .expect("Knot position must be finite")
+    }
+
+    /// Like [LookupCurve::add_knot], but returns `None` without changing the curve if the position of the knot is
+    /// not finite
+    pub fn try_add_knot(&mut self, mut knot: Knot) -> Option<usize> {
+        if !knot.position.is_finite() {
+            return None;
+        }
+        if knot.id == 0 || self.knots.iter().any(|k| k.id == knot.id) {
+            knot.id = unique_knot_id();
+        }
+        if let Some(rule) = self.quantize_storage {
+            knot = rule.knot(knot);
+        }
+        self.bump_generation();
+        self.bump_order_epoch();
+        let knot = knot.stamped_created(self.edit_time());
+
+        let i = if self.knots.is_empty() || knot.position.x >= self.knots.last().unwrap().position.x
+        {
+            self.knots.push(knot);
+            self.knots.len() - 1
+        } else {
+            let i = self
+                .knots
+                .partition_point(|k| k.position.x <= knot.position.x);
+            self.knots.insert(i, knot);
+            i
+        };
+        if self.strict_monotonic_x {
+            self.limit_tangent_weights(i);
+        }
+        self.refresh_auto_tangents();
+        self.check_consistency();
+        Some(i)
+    }
+
+    /// Modifies an existing knot in the lookup curve. Returns the new (possibly unchanged) index of the knot.
+    ///
+    /// On curves with [LookupCurve::strict_monotonic_x] the tangent weights of the knot are limited.
+    ///
+    /// On curves with [LookupCurve::track_edit_times] the knot keeps its creation time and its modification time is
+    /// set to the current time.
+    ///
+    /// If the id of `new_value` is 0 or the id of another knot, the knot keeps its id. See
+    /// [LookupCurve::modify_knot_by_id] for modifying a knot by its id.
+    ///
+    /// The index of a knot only changes when it is moved past another knot on the x axis, the other knots keep their
+    /// order. Use the id of the knot to find it again after modifying other knots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds or the new position is not finite.
+    pub fn modify_knot(&mut self, i: usize, mut new_value: Knot) -> usize {
+        assert!(
+            new_value.position.is_finite(),
+            "Knot position must be finite"
+        );
+        if let Some(time) = self.edit_time() {
+            new_value.created_at = self.knots[i].created_at;
+            new_value.modified_at = Some(time);
+        }
+        let old_x = self.knots[i].position.x;
+        let new_i = self.replace_knot(i, new_value);
+        if self.strict_monotonic_x {
+            // Only changes the tangents of the segments next to the knot
+            self.limit_tangent_weights(new_i);
+            if new_i != i {
+                // The previous neighbors of the knot are now next to each other
+                self.enforce_monotonic_x();
+            }
+        }
+        let auto = self.refresh_auto_tangent_knots();
+        // Moving an end knot on x changes the range of the knots
+        let end_knot = i == 0 || i + 1 == self.knots.len();
+        if new_i == i && !(end_knot && old_x != new_value.position.x) {
+            let (start, end) =
+                auto.map_or((i, i), |auto| (i.min(*auto.start()), i.max(*auto.end())));
+            self.record_changed_knots(start..=end);
+        }
+        self.check_consistency();
+        new_i
+    }
+
+    fn replace_knot(&mut self, i: usize, mut new_value: Knot) -> usize {
+        let old_value = self.knots[i];
+        self.bump_generation();
+
+        if new_value.id == 0
+            || self
+                .knots
+                .iter()
+                .enumerate()
+                .any(|(j, knot)| j != i && knot.id == new_value.id)
+        {
+            new_value.id = old_value.id;
+        }
+
+        if old_value.id != new_value.id {
+            self.bump_order_epoch();
+        }
+
+        if old_value.position.x == new_value.position.x {
+            // The knot has not been moved on the x axis, simply overwrite it
+            self.knots[i] = new_value;
+            return i;
+        }
+
+        // binary seach for new idx
+        let new_i = self
+            .knots
+            .partition_point(|knot| knot.position.x < new_value.position.x);
+        if new_i == i {
+            // knot stays in the same spot even though position was changed, overwrite it
+            self.knots[i] = new_value;
+            return i;
+        }
+
+        self.knots.remove(i);
+
+        let insert_i = if i < new_i { new_i - 1 } else { new_i };
+        self.knots.insert(insert_i, new_value);
+        if insert_i != i {
+            self.bump_order_epoch();
+        }
+
+        insert_i
+    }
+
+    /// Deletes a knot given index. Knots after it move down by one index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn delete_knot(&mut self, i: usize) {
+        self.knots.remove(i);
+        self.bump_generation();
+        self.bump_order_epoch();
+        if self.strict_monotonic_x {
+            // The neighbors of the knot are now next to each other
+            self.enforce_monotonic_x();
+        }
+        self.refresh_auto_tangents();
+        self.check_consistency();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LookupCache;
+
+    fn test_curve() -> LookupCurve {
+        LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::new(0.5, 0.2),
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::ONE,
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn mutations_bump_generation_once() {
+        let mut curve = test_curve();
+        let mut generation = curve.generation();
+        let mut assert_bumped_once = |curve: &LookupCurve| {
+            assert_eq!(curve.generation(), generation + 1);
+            generation = curve.generation();
+        };
+
+        curve.add_knot(Knot {
+            position: Vec2::new(0.25, 0.0),
+            ..Default::default()
+        });
+        assert_bumped_once(&curve);
+        curve.add_knot(Knot {
+            position: Vec2::new(2.0, 0.0),
+            ..Default::default()
+        });
+        assert_bumped_once(&curve);
+
+        // Not moved on x
+        let knot = curve.knots()[1];
+        curve.modify_knot(1, knot.with_tangent_slope(TangentSide::Left, 1.0));
+        assert_bumped_once(&curve);
+        // Moved on x but keeping index
+        curve.modify_knot(
+            1,
+            Knot {
+                position: Vec2::new(0.3, 0.0),
+                ..knot
+            },
+        );
+        assert_bumped_once(&curve);
+        // Moved past other knots
+        curve.modify_knot(
+            1,
+            Knot {
+                position: Vec2::new(1.5, 0.0),
+                ..knot
+            },
+        );
+        assert_bumped_once(&curve);
+
+        curve.delete_knot(0);
+        assert_bumped_once(&curve);
+
+        curve.splice(0.1..=0.2, &test_curve(), StitchMode::Smooth);
+        assert_bumped_once(&curve);
+
+        let curve = curve.with_max_iters(5);
+        assert_bumped_once(&curve);
+        let curve = curve.with_max_error(0.1);
+        assert_bumped_once(&curve);
+        let curve = curve.with_name("name");
+        assert_bumped_once(&curve);
+        let curve = curve.with_x_unit(UnitDisplay::new("s"));
+        assert_bumped_once(&curve);
+        let curve = curve.with_y_unit(UnitDisplay::new("m"));
+        assert_bumped_once(&curve);
+    }
+
+    fn cubic(x: f32, y: f32, weight: Option<f32>) -> Knot {
+        let tangent = Tangent {
+            slope: 1.0,
+            weight,
+            ..Default::default()
+        };
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            left_tangent: tangent,
+            right_tangent: tangent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn knots_are_finite_with_finite_tangents() {
+        let knot = cubic(0.0, 1.0, Some(0.5));
+        assert!(knot.is_finite());
+        assert!(!knot
+            .with_tangent_slope(TangentSide::Left, f32::INFINITY)
+            .is_finite());
+        let mut unweighted = knot.with_tangent_weight(TangentSide::Right, None);
+        assert!(unweighted.is_finite());
+        unweighted.right_tangent.weight = Some(f32::NAN);
+        assert!(!unweighted.is_finite());
+        unweighted.position.y = f32::NEG_INFINITY;
+        assert!(!unweighted.is_finite());
+    }
+
+    #[test]
+    fn add_knot_inserts_sorted() {
+        let mut curve = test_curve();
+        let before = Knot {
+            position: Vec2::new(-1.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(curve.add_knot(before), 0);
+        let after = Knot {
+            position: Vec2::new(2.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(curve.add_knot(after), 4);
+        let xs: Vec<f32> = curve.knots().iter().map(|k| k.position.x).collect();
+        assert_eq!(xs, [-1.0, 0.0, 0.5, 1.0, 2.0]);
+        assert_eq!(curve.knots()[0].id, before.id);
+        assert_eq!(curve.knots()[4].id, after.id);
+    }
+
+    #[test]
+    fn add_knot_between_bezier_knots_limits_its_weights() {
+        let mut curve =
+            LookupCurve::new(vec![cubic(0.0, 0.0, Some(0.9)), cubic(1.0, 1.0, Some(0.9))]);
+        curve.strict_monotonic_x = true;
+        let neighbors = [curve.knots()[0], curve.knots()[1]];
+
+        let i = curve.add_knot(cubic(0.5, 0.5, Some(0.9)));
+        assert_eq!(i, 1);
+        // The neighbors keep their tangents, the tangents of the new knot give way
+        for (knot, neighbor) in [(0, neighbors[0]), (2, neighbors[1])] {
+            let knot = curve.knots()[knot];
+            assert_eq!(knot.id, neighbor.id);
+            assert_eq!(knot.left_tangent.weight, neighbor.left_tangent.weight);
+            assert_eq!(knot.right_tangent.weight, neighbor.right_tangent.weight);
+        }
+        let added = curve.knots()[1];
+        for (weight, side) in [
+            (added.left_tangent.weight, TangentSide::Left),
+            (added.right_tangent.weight, TangentSide::Right),
+        ] {
+            let limit = curve.tangent_weight_limit(1, side).unwrap();
+            assert!(weight.unwrap() <= limit + 1e-6);
+        }
+        // The segments on both sides are still functions of x
+        let ys: Vec<f32> = (0..=100).map(|i| curve.lookup(i as f32 / 100.0)).collect();
+        assert!(ys.iter().all(|y| y.is_finite()));
+        assert!(ys.windows(2).all(|pair| pair[0] <= pair[1] + 1e-4));
+        assert_eq!(curve.lookup(0.5), 0.5);
+    }
+
+    #[test]
+    fn add_knot_with_equal_x_or_id() {
+        let mut curve = test_curve();
+        let existing = curve.knots()[1];
+        let duplicate = Knot {
+            position: Vec2::new(0.5, 0.9),
+            ..existing
+        };
+        assert_eq!(curve.add_knot(duplicate), 2);
+        assert_eq!(curve.knots()[1].id, existing.id);
+        assert_eq!(curve.knots()[2].position.y, 0.9);
+        assert_ne!(curve.knots()[2].id, existing.id);
+
+        let last = curve.knots()[3];
+        let unset = Knot {
+            position: last.position,
+            id: 0,
+            ..Default::default()
+        };
+        assert_eq!(curve.add_knot(unset), 4);
+        assert_ne!(curve.knots()[4].id, 0);
+    }
+
+    #[test]
+    fn knots_that_are_not_finite_are_rejected() {
+        let mut curve = test_curve();
+        let generation = curve.generation();
+        let nan = Knot {
+            position: Vec2::new(f32::NAN, 0.0),
+            ..Default::default()
+        };
+        let infinite = Knot {
+            position: Vec2::new(0.5, f32::INFINITY),
+            ..Default::default()
+        };
+        assert_eq!(curve.try_add_knot(nan), None);
+        assert_eq!(curve.try_add_knot(infinite), None);
+        assert_eq!(curve.knots().len(), 3);
+        assert_eq!(curve.generation(), generation);
+        let mut modified = curve.clone();
+        assert!(std::panic::catch_unwind(move || modified.modify_knot(0, infinite)).is_err());
+        assert!(std::panic::catch_unwind(move || curve.add_knot(nan)).is_err());
+    }
+
+    #[test]
+    fn read_only_does_not_bump_generation() {
+        let curve = test_curve();
+        let generation = curve.generation();
+
+        let _ = curve.knots();
+        let _ = curve.prev_knot(1);
+        let _ = curve.next_knot(1);
+        let _ = curve.lookup(0.3);
+        let _ = curve.lookup_cached(0.3, &mut LookupCache::new());
+        let _ = curve.sample_normalized(0.3);
+        let clone = curve.clone();
+
+        assert_eq!(curve.generation(), generation);
+        assert_eq!(clone.generation(), generation);
+    }
+
+    #[test]
+    fn generations_differ_between_curves() {
+        assert_ne!(test_curve().generation(), test_curve().generation());
+    }
+
+    #[test]
+    fn clones_edited_independently_differ() {
+        let mut curve = test_curve();
+        let generation = curve.generation();
+        let mut a = curve.clone();
+        let mut b = curve.clone();
+        let mut knot = a.knots()[1];
+        knot.position.y += 0.5;
+        a.modify_knot(1, knot);
+        knot.position.y -= 1.0;
+        b.modify_knot(1, knot);
+        curve.add_knot(Knot {
+            position: Vec2::new(0.7, 0.5),
+            ..Default::default()
+        });
+
+        let generations = [curve.generation(), a.generation(), b.generation()];
+        assert_eq!(generations[0], generation + 1);
+        assert!(!generations[1..].contains(&(generation + 1)));
+        assert_ne!(generations[1], generations[2]);
+        assert!(a.edit_effect_since(generation).reordered);
+
+        // Later edits of a clone continue in its own range
+        let a_generation = a.generation();
+        a.modify_knot(1, knot);
+        assert_eq!(a.generation(), a_generation + 1);
+        assert_eq!(a.edit_effect_since(a_generation).dirty_segments, 0..2);
+    }
+
+    #[cfg(all(feature = "trace", feature = "ron", feature = "editor_egui"))]
+    #[test]
+    fn traces_load_and_editor_apply() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{span, Subscriber};
+        use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+        type SpanRecord = (&'static str, Vec<&'static str>);
+
+        #[derive(Clone, Default)]
+        struct SpanCollector(Arc<Mutex<Vec<SpanRecord>>>);
+
+        impl<S: Subscriber> Layer<S> for SpanCollector {
+            fn on_new_span(&self, attrs: &span::Attributes<'_>, _: &span::Id, _: Context<'_, S>) {
+                let metadata = attrs.metadata();
+                let fields = metadata.fields().iter().map(|f| f.name()).collect();
+                self.0.lock().unwrap().push((metadata.name(), fields));
+            }
+        }
+
+        let collector = SpanCollector::default();
+        let subscriber = tracing_subscriber::registry().with(collector.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let path = std::env::temp_dir().join("bevy_lookup_curve_trace_test.curve.ron");
+            let path = path.to_str().unwrap();
+            LookupCurve::new(vec![
+                Knot::default(),
+                Knot {
+                    position: Vec2::ONE,
+                    ..Default::default()
+                },
+            ])
+            .save_to_file(path)
+            .unwrap();
+            let mut curve = LookupCurve::load_from_file(path).unwrap();
+            assert_eq!(curve.knots().len(), 2);
+            curve.fill_buffer(0.0, 0.25, &mut [0.0; 4]);
+
+            let mut editor = crate::editor::LookupCurveEguiEditor::fitted_to_curve(&curve);
+            let ctx = egui::Context::default();
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| editor.ui(ui, &mut curve, None));
+            });
+        });
+
+        let spans = collector.0.lock().unwrap();
+        let has_span = |name: &str, expected_fields: &[&str]| {
+            spans.iter().any(|(span_name, fields)| {
+                *span_name == name && expected_fields.iter().all(|f| fields.contains(f))
+            })
+        };
+        assert!(has_span("lookup_curve_load", &["path", "knot_count"]));
+        assert!(has_span("lookup_curve_editor_apply", &["edits"]));
+        assert!(has_span("lookup_curve_fill_buffer", &["samples"]));
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn traces_cache_updates() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{field::Field, span::Record, Id, Subscriber};
+        use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+        /// Values of the `rebuilt` field recorded on `lookup_curve_cache_update` spans
+        #[derive(Clone, Default)]
+        struct RebuiltCollector(Arc<Mutex<Vec<u64>>>);
+
+        impl<S: Subscriber> Layer<S> for RebuiltCollector {
+            fn on_record(&self, _: &Id, values: &Record<'_>, _: Context<'_, S>) {
+                struct Rebuilt<'a>(&'a Mutex<Vec<u64>>);
+                impl tracing::field::Visit for Rebuilt<'_> {
+                    fn record_u64(&mut self, field: &Field, value: u64) {
+                        if field.name() == "rebuilt" {
+                            self.0.lock().unwrap().push(value);
+                        }
+                    }
+                    fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+                }
+                values.record(&mut Rebuilt(&self.0));
+            }
+        }
+
+        let mut curve = LookupCurve::new(
+            (0..4)
+                .map(|i| Knot {
+                    position: Vec2::new(i as f32, 0.0),
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let mut precomputed = curve.precompute();
+        let mut baked = curve.bake(31);
+        let id = curve.knots()[3].id;
+        curve.modify_knot_by_id(
+            id,
+            Knot {
+                position: Vec2::new(3.0, 1.0),
+                ..curve.knots()[3]
+            },
+        );
+
+        let collector = RebuiltCollector::default();
+        let subscriber = tracing_subscriber::registry().with(collector.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            assert_eq!(precomputed.update(&curve), 1);
+            assert_eq!(baked.update(&curve), 11);
+        });
+        assert_eq!(*collector.0.lock().unwrap(), [1, 11]);
+    }
+
+    #[cfg(all(feature = "trace", feature = "ron"))]
+    #[test]
+    fn traces_rejected_knots() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{field::Field, Event, Subscriber};
+        use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+        /// Values of the `index` field of debug events
+        #[derive(Clone, Default)]
+        struct IndexCollector(Arc<Mutex<Vec<u64>>>);
+
+        impl<S: Subscriber> Layer<S> for IndexCollector {
+            fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+                struct Index(Option<u64>);
+                impl tracing::field::Visit for Index {
+                    fn record_u64(&mut self, field: &Field, value: u64) {
+                        if field.name() == "index" {
+                            self.0 = Some(value);
+                        }
+                    }
+                    fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+                }
+                let mut index = Index(None);
+                event.record(&mut index);
+                if let Some(index) = index.0 {
+                    self.0.lock().unwrap().push(index);
+                }
+            }
+        }
+
+        let collector = IndexCollector::default();
+        let subscriber = tracing_subscriber::registry().with(collector.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let ron = "(knots: [\
+                (position: (0.0, 0.0), interpolation: Linear, \
+                left_tangent: (slope: 0.0, mode: Free), right_tangent: (slope: 0.0, mode: Free)), \
+                (position: (1.0, 1.0), interpolation: Linear, \
+                left_tangent: (slope: 0.0, mode: Free, weight: Some(2.0)), \
+                right_tangent: (slope: 0.0, mode: Free))])";
+            assert!(parse_curve_ron(ron.as_bytes()).is_err());
+        });
+        assert_eq!(*collector.0.lock().unwrap(), [1]);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{Knot, LookupCurve, Tangent};
+
+/// Weight used for unweighted tangents
+pub(super) const UNWEIGHTED: f32 = 1. / 3.;
+/// Allowed rounding error of the sum of the weights of a segment
+const WEIGHT_EPSILON: f32 = 1e-6;
+
+/// Returns the weights of the right tangent of `knot_a` and the left tangent of `knot_b`, corrected so that x of the
+/// bezier control points of the segment between them never decreases.
+///
+/// Both weights are clamped between 0 and 1, and if their sum exceeds 1 the weighted tangents are shortened.
+/// Unweighted tangents are kept. Returns the stored weights if the segment is already x-monotonic.
+pub(crate) fn x_monotonic_weights(knot_a: &Knot, knot_b: &Knot) -> (Option<f32>, Option<f32>) {
+    let clamp = |weight: Option<f32>| weight.map(|w| w.clamp(0.0, 1.0));
+    let (a, b) = (
+        clamp(knot_a.right_tangent.weight),
+        clamp(knot_b.left_tangent.weight),
+    );
+    let sum = a.unwrap_or(UNWEIGHTED) + b.unwrap_or(UNWEIGHTED);
+    if sum <= 1.0 + WEIGHT_EPSILON {
+        return (a, b);
+    }
+    match (a, b) {
+        (Some(a), Some(b)) => (Some(a / sum), Some(b / sum)),
+        (Some(_), None) => (Some(1.0 - UNWEIGHTED), None),
+        (None, Some(_)) => (None, Some(1.0 - UNWEIGHTED)),
+        // Two unweighted tangents never overlap
+        (None, None) => (None, None),
+    }
+}
+
+fn is_x_monotonic(knot_a: &Knot, knot_b: &Knot) -> bool {
+    x_monotonic_weights(knot_a, knot_b) == (knot_a.right_tangent.weight, knot_b.left_tangent.weight)
+}
+
+/// Limits `tangent` to `limit`, making it weighted if an unweighted tangent would exceed the limit
+fn limit_weight(tangent: &mut Tangent, limit: f32) {
+    let limit = limit.clamp(0.0, 1.0);
+    tangent.weight = match tangent.weight {
+        Some(weight) => Some(weight.clamp(0.0, limit)),
+        None if UNWEIGHTED > limit => Some(limit),
+        None => None,
+    };
+}
+
+impl LookupCurve {
+    /// Consumes the curve and returns it with [LookupCurve::strict_monotonic_x] set, correcting all segments
+    pub fn with_strict_monotonic_x(mut self) -> Self {
+        self.strict_monotonic_x = true;
+        self.enforce_monotonic_x();
+        self.bump_generation();
+        self
+    }
+
+    /// Corrects the tangent weights of all segments, so that x of their bezier control points never decreases.
+    ///
+    /// Weights are clamped between 0 and 1, and where the weights of a segment sum to more than 1 its weighted
+    /// tangents are shortened. Returns `true` if any weight changed.
+    pub fn enforce_monotonic_x(&mut self) -> bool {
+        let time = self.edit_time();
+        let mut changed = false;
+        for i in 1..self.knots.len() {
+            let (a, b) = x_monotonic_weights(&self.knots[i - 1], &self.knots[i]);
+            if (a, b)
+                != (
+                    self.knots[i - 1].right_tangent.weight,
+                    self.knots[i].left_tangent.weight,
+                )
+            {
+                self.knots[i - 1].right_tangent.weight = a;
+                self.knots[i].left_tangent.weight = b;
+                self.stamp_modified(i - 1, time);
+                self.stamp_modified(i, time);
+                changed = true;
+            }
+        }
+        if changed {
+            self.bump_generation();
+            self.check_consistency();
+        }
+        changed
+    }
+
+    /// Returns `true` if x of the bezier control points of every segment never decreases, see
+    /// [LookupCurve::enforce_monotonic_x]
+    pub fn is_monotonic_x(&self) -> bool {
+        self.knots
+            .windows(2)
+            .all(|pair| is_x_monotonic(&pair[0], &pair[1]))
+    }
+
+    /// Indices of the first knots of the segments that are not x-monotonic, see [LookupCurve::is_monotonic_x]
+    pub fn non_monotonic_x_segments(&self) -> Vec<usize> {
+        self.knots
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| !is_x_monotonic(&pair[0], &pair[1]))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Limits the tangent weights of the knot at `i` to keep the segments on both sides x-monotonic.
+    ///
+    /// Called after the knot was added or modified on curves with [LookupCurve::strict_monotonic_x]. The tangents
+    /// of the knot give way to the tangents of its neighbors, so a dragged tangent handle stops at the limit.
+    pub(crate) fn limit_tangent_weights(&mut self, i: usize) {
+        let limit = |weight: Option<f32>| 1.0 - weight.unwrap_or(UNWEIGHTED);
+        let clamp = |tangent: &mut Tangent| {
+            let clamped = tangent.weight.map(|w| w.clamp(0.0, 1.0));
+            std::mem::replace(&mut tangent.weight, clamped) != clamped
+        };
+        let time = self.edit_time();
+        if i > 0 {
+            if clamp(&mut self.knots[i - 1].right_tangent) {
+                self.stamp_modified(i - 1, time);
+            }
+            let limit = limit(self.knots[i - 1].right_tangent.weight);
+            limit_weight(&mut self.knots[i].left_tangent, limit);
+            debug_assert!(is_x_monotonic(&self.knots[i - 1], &self.knots[i]));
+        }
+        if i + 1 < self.knots.len() {
+            if clamp(&mut self.knots[i + 1].left_tangent) {
+                self.stamp_modified(i + 1, time);
+            }
+            let limit = limit(self.knots[i + 1].left_tangent.weight);
+            limit_weight(&mut self.knots[i].right_tangent, limit);
+            debug_assert!(is_x_monotonic(&self.knots[i], &self.knots[i + 1]));
+        }
+    }
+
+    /// Largest weight the `side` tangent of the knot at `i` can have on a curve with
+    /// [LookupCurve::strict_monotonic_x], `None` if the curve is not strict or the tangent has no segment
+    pub fn tangent_weight_limit(&self, i: usize, side: crate::TangentSide) -> Option<f32> {
+        if !self.strict_monotonic_x {
+            return None;
+        }
+        let other = match side {
+            crate::TangentSide::Left => self.prev_knot(i)?.right_tangent.weight,
+            crate::TangentSide::Right => self.next_knot(i)?.left_tangent.weight,
+        };
+        Some((1.0 - other.unwrap_or(UNWEIGHTED)).clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KnotInterpolation, TangentSide};
+    use bevy_math::Vec2;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+    }
+
+    fn weighted(x: f32, left: Option<f32>, right: Option<f32>) -> Knot {
+        let mut knot = knot(x, 0.0);
+        knot.left_tangent.weight = left;
+        knot.right_tangent.weight = right;
+        knot
+    }
+
+    /// Bezier control points of every segment, stored tangents are evaluated as is
+    fn control_points(curve: &LookupCurve) -> Vec<[Vec2; 4]> {
+        curve
+            .knots()
+            .windows(2)
+            .map(|pair| pair[0].compute_bezier_to(&pair[1]))
+            .collect()
+    }
+
+    #[test]
+    fn correction_shortens_overlapping_tangents() {
+        let a = weighted(0.0, None, Some(0.9));
+        let b = weighted(1.0, Some(0.6), None);
+        let (wa, wb) = x_monotonic_weights(&a, &b);
+        assert!((wa.unwrap() - 0.6).abs() < 1e-6);
+        assert!((wb.unwrap() - 0.4).abs() < 1e-6);
+
+        let b = weighted(1.0, None, None);
+        let (wa, wb) = x_monotonic_weights(&a, &b);
+        assert!((wa.unwrap() - 2. / 3.).abs() < 1e-6);
+        assert_eq!(wb, None);
+
+        let b = weighted(1.0, Some(0.1), None);
+        assert_eq!(x_monotonic_weights(&a, &b), (Some(0.9), Some(0.1)));
+        assert_eq!(
+            x_monotonic_weights(&knot(0.0, 0.0), &knot(1.0, 0.0)),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn strict_curves_limit_mutated_tangents() {
+        let mut curve = LookupCurve::new(vec![
+            weighted(0.0, None, Some(0.8)),
+            weighted(1.0, None, None),
+            weighted(2.0, None, None),
+        ])
+        .with_strict_monotonic_x();
+        // The weighted tangent was shortened to make room for the unweighted one
+        let [a, b, _] = curve.knots().try_into().unwrap();
+        assert!((a.right_tangent.weight.unwrap() - 2. / 3.).abs() < 1e-6);
+        assert_eq!(b.left_tangent.weight, None);
+
+        // A mutated tangent stops at the limit given by its neighbor
+        curve.modify_knot(1, b.with_tangent_weight(TangentSide::Left, Some(0.5)));
+        let limit = curve.tangent_weight_limit(1, TangentSide::Left).unwrap();
+        assert!((limit - 1. / 3.).abs() < 1e-6);
+        assert_eq!(curve.knots()[1].left_tangent.weight, Some(limit));
+
+        curve.add_knot(weighted(1.5, Some(1.0), Some(1.0)));
+        assert!(curve.is_monotonic_x());
+        // The added knot gives way to its unweighted neighbors
+        let added = curve.knots()[2];
+        assert!((added.left_tangent.weight.unwrap() - 2. / 3.).abs() < 1e-6);
+        assert!((added.right_tangent.weight.unwrap() - 2. / 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn non_monotonic_segments_are_listed() {
+        let mut curve = LookupCurve::new(vec![
+            weighted(0.0, None, None),
+            weighted(1.0, None, Some(0.9)),
+            weighted(2.0, Some(0.6), None),
+        ]);
+        assert_eq!(curve.non_monotonic_x_segments(), [1]);
+        assert!(!curve.is_monotonic_x());
+        curve.enforce_monotonic_x();
+        assert!(curve.non_monotonic_x_segments().is_empty());
+        assert!(curve.is_monotonic_x());
+    }
+
+    #[test]
+    fn non_strict_curves_store_tangents_as_is() {
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]);
+        let a = curve.knots()[0];
+        curve.modify_knot(0, a.with_tangent_weight(TangentSide::Right, Some(1.0)));
+        curve.add_knot(weighted(0.5, Some(0.9), None));
+        assert_eq!(curve.knots()[0].right_tangent.weight, Some(1.0));
+        assert_eq!(curve.knots()[1].left_tangent.weight, Some(0.9));
+        assert!(!curve.is_monotonic_x());
+        assert_eq!(curve.tangent_weight_limit(0, TangentSide::Right), None);
+    }
+
+    /// Random edits with random tangent weights, including weights outside of `0..=1`.
+    ///
+    /// Checks that curves without [LookupCurve::strict_monotonic_x] store the weights as is.
+    fn random_edits(rng: &mut StdRng, curve: &mut LookupCurve, edits: usize) {
+        let weight = |rng: &mut StdRng| rng.gen_bool(0.7).then(|| rng.gen_range(-0.5..1.5));
+        for _ in 0..edits {
+            let len = curve.knots().len();
+            let mut knot = if len > 0 && rng.gen_bool(0.6) {
+                curve.knots()[rng.gen_range(0..len)]
+            } else {
+                knot(rng.gen_range(-1.0..2.0), rng.gen_range(-1.0..2.0))
+            };
+            knot.left_tangent.weight = weight(rng);
+            knot.right_tangent.weight = weight(rng);
+            if rng.gen_bool(0.3) {
+                knot.position.x = rng.gen_range(-1.0..2.0);
+            }
+            let i = match curve.knots().iter().position(|k| k.id == knot.id) {
+                Some(i) if rng.gen_bool(0.2) => {
+                    curve.delete_knot(i);
+                    continue;
+                }
+                Some(i) => curve.modify_knot(i, knot),
+                None => curve.add_knot(knot),
+            };
+            if !curve.strict_monotonic_x {
+                let stored = curve.knots()[i];
+                assert_eq!(stored.left_tangent.weight, knot.left_tangent.weight);
+                assert_eq!(stored.right_tangent.weight, knot.right_tangent.weight);
+            }
+        }
+    }
+
+    #[test]
+    fn strict_mutations_never_need_correction() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..200 {
+            let mut curve = LookupCurve::default().with_strict_monotonic_x();
+            random_edits(&mut rng, &mut curve, 30);
+            for pair in curve.knots().windows(2) {
+                assert_eq!(
+                    x_monotonic_weights(&pair[0], &pair[1]),
+                    (pair[0].right_tangent.weight, pair[1].left_tangent.weight)
+                );
+            }
+            for [c0, c1, c2, c3] in control_points(&curve) {
+                assert!(c0.x <= c1.x && c1.x <= c2.x + 1e-5 && c2.x <= c3.x);
+            }
+        }
+    }
+
+    #[test]
+    fn non_strict_mutations_are_unchanged() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut found_overlap = false;
+        for _ in 0..50 {
+            let mut curve = LookupCurve::default();
+            random_edits(&mut rng, &mut curve, 30);
+            found_overlap |= !curve.is_monotonic_x();
+        }
+        // Otherwise the strict test would not be meaningful
+        assert!(found_overlap);
+    }
+}
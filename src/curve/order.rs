@@ -0,0 +1,374 @@
+use super::{assign_unique_ids, sort_knots, Knot, LookupCurve};
+
+impl LookupCurve {
+    /// Returns the current order epoch of the curve.
+    ///
+    /// The epoch changes whenever the sequence of knot ids in [LookupCurve::knots] changes, which is when knots are
+    /// added or deleted, when a knot is moved past another knot on the x axis, or when a knot gets another id. Edits
+    /// that keep every knot at its index, like moving a knot between its neighbors or changing its tangents, do not
+    /// change the epoch, while they do change the [generation](LookupCurve::generation).
+    ///
+    /// Data keyed by knot index stays valid while the epoch is unchanged, use [LookupCurve::knot_index_of] to find the
+    /// knots again after it changed. Like the generation, an epoch never repeats within the lifetime of a curve and
+    /// different curves start at different epochs. Clones keep the epoch of the original.
+    ///
+    /// Directly changing the knots through reflection does not change the epoch, see
+    /// [LookupCurve::repair_knot_order].
+    pub fn order_epoch(&self) -> u64 {
+        self.order_epoch
+    }
+
+    #[inline]
+    pub(crate) fn bump_order_epoch(&mut self) {
+        self.order_epoch = self.order_epoch.wrapping_add(1);
+    }
+
+    /// Returns the index of the knot with `id` in [LookupCurve::knots], `None` if the curve has no such knot
+    pub fn knot_index_of(&self, id: usize) -> Option<usize> {
+        self.knots.iter().position(|knot| knot.id == id)
+    }
+
+    /// Returns the knot with `id`, `None` if the curve has no such knot
+    pub fn knot_by_id(&self, id: usize) -> Option<&Knot> {
+        self.knots.iter().find(|knot| knot.id == id)
+    }
+
+    /// Modifies the knot with `id` like [LookupCurve::modify_knot], keeping its id. Returns the new index of the knot,
+    /// or `None` without changing the curve if it has no such knot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new position is not finite.
+    pub fn modify_knot_by_id(&mut self, id: usize, new_value: Knot) -> Option<usize> {
+        let i = self.knot_index_of(id)?;
+        Some(self.modify_knot(i, Knot { id, ..new_value }))
+    }
+
+    /// Deletes the knot with `id` like [LookupCurve::delete_knot]. Returns the deleted knot, or `None` without
+    /// changing the curve if it has no such knot.
+    pub fn delete_knot_by_id(&mut self, id: usize) -> Option<Knot> {
+        let i = self.knot_index_of(id)?;
+        let knot = self.knots[i];
+        self.delete_knot(i);
+        Some(knot)
+    }
+
+    /// Sorts the knots by x again, after they were changed without the methods of the curve, for example through
+    /// reflection in an inspector. Knots with the same x keep their order, knots with id 0 or the id of an earlier
+    /// knot get a new id. Returns `true` if the order or an id changed, which changes the generation and the order
+    /// epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the x of a knot is NaN.
+    pub fn repair_knot_order(&mut self) -> bool {
+        let ids: Vec<usize> = self.knots.iter().map(|knot| knot.id).collect();
+        let reassigned = assign_unique_ids(&mut self.knots);
+        sort_knots(&mut self.knots);
+        if !reassigned && self.knots.iter().map(|knot| knot.id).eq(ids) {
+            return false;
+        }
+        self.bump_generation();
+        self.bump_order_epoch();
+        self.refresh_auto_tangents();
+        self.check_consistency();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+
+    use crate::{Knot, KnotInterpolation, LookupCurve, MergeY, SmoothPolicy, StitchMode};
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+    }
+
+    fn test_curve() -> LookupCurve {
+        LookupCurve::new(vec![
+            knot(0.0, 0.0),
+            knot(1.0, 1.0),
+            knot(2.0, 0.0),
+            knot(3.0, 1.0),
+        ])
+    }
+
+    /// Asserts that `edit` changes the order epoch exactly when it changes the sequence of knot ids
+    fn assert_epoch(
+        curve: &mut LookupCurve,
+        expect_change: bool,
+        edit: impl FnOnce(&mut LookupCurve),
+    ) {
+        let ids: Vec<usize> = curve.knots().iter().map(|knot| knot.id).collect();
+        let epoch = curve.order_epoch();
+        edit(curve);
+        let reordered = !curve.knots().iter().map(|knot| knot.id).eq(ids);
+        assert_eq!(reordered, expect_change, "order of the knots");
+        if expect_change {
+            assert_ne!(curve.order_epoch(), epoch);
+        } else {
+            assert_eq!(curve.order_epoch(), epoch);
+        }
+    }
+
+    #[test]
+    fn epoch_changes_only_with_the_order() {
+        let mut curve = test_curve();
+        assert_eq!(curve.clone().order_epoch(), curve.order_epoch());
+        assert_ne!(test_curve().order_epoch(), curve.order_epoch());
+
+        // In place edits
+        assert_epoch(&mut curve, false, |curve| {
+            let knot = curve.knots()[1];
+            curve.modify_knot(
+                1,
+                Knot {
+                    position: Vec2::new(1.0, 2.0),
+                    ..knot
+                },
+            );
+        });
+        assert_epoch(&mut curve, false, |curve| {
+            let knot = curve.knots()[1];
+            curve.modify_knot(1, knot.with_tangent_slope(crate::TangentSide::Left, 2.0));
+        });
+        assert_epoch(&mut curve, false, |curve| {
+            let knot = curve.knots()[1];
+            curve.modify_knot(
+                1,
+                Knot {
+                    position: Vec2::new(1.5, 2.0),
+                    ..knot
+                },
+            );
+        });
+        assert_epoch(&mut curve, false, |curve| {
+            curve.smooth_knot(curve.knots()[1].id, SmoothPolicy::Average);
+        });
+        assert_epoch(&mut curve, false, |curve| {
+            curve.enforce_monotonic_x();
+        });
+        assert_epoch(&mut curve, false, |curve| {
+            let last = curve.knots()[3].id;
+            curve.stretch_from_knot(last, 6.0, crate::StretchMode::Proportional { pivot: None });
+        });
+        assert_epoch(&mut curve, false, |curve| {
+            let existing = curve.knots()[1].position.x;
+            curve.insert_knot_at(existing);
+        });
+        assert_epoch(&mut curve, false, |curve| {
+            curve.merge_coincident_knots(Vec2::splat(1e-3), MergeY::KeepFirst);
+        });
+
+        // Reorders
+        assert_epoch(&mut curve, true, |curve| {
+            let knot = curve.knots()[0];
+            curve.modify_knot(
+                0,
+                Knot {
+                    position: Vec2::new(5.0, 0.0),
+                    ..knot
+                },
+            );
+        });
+        // Moved onto the x of its neighbor, it is placed before it
+        assert_epoch(&mut curve, true, |curve| {
+            let [previous, knot] = [curve.knots()[2], curve.knots()[3]];
+            let i = curve.modify_knot(
+                3,
+                Knot {
+                    position: Vec2::new(previous.position.x, 0.0),
+                    ..knot
+                },
+            );
+            assert_eq!(i, 2);
+        });
+        assert_epoch(&mut curve, true, |curve| {
+            let knot = curve.knots()[1];
+            curve.modify_knot(
+                1,
+                Knot {
+                    id: crate::unique_knot_id(),
+                    ..knot
+                },
+            );
+        });
+        assert_epoch(&mut curve, true, |curve| {
+            curve.add_knot(knot(0.5, 0.0));
+        });
+        assert_epoch(&mut curve, true, |curve| {
+            curve.insert_knot_at(1.75);
+        });
+        assert_epoch(&mut curve, true, |curve| curve.delete_knot(0));
+        assert_epoch(&mut curve, true, |curve| {
+            curve.splice(1.0..=2.0, &test_curve(), StitchMode::Smooth);
+        });
+        let x = curve.knots()[1].position.x;
+        curve.add_knot(knot(x, 0.0));
+        assert_epoch(&mut curve, true, |curve| {
+            curve.merge_coincident_knots(Vec2::splat(1e-3), MergeY::KeepFirst);
+        });
+    }
+
+    #[test]
+    fn knots_with_equal_x_keep_their_order() {
+        let mut curve = LookupCurve::new(vec![knot(1.0, 0.0), knot(0.0, 0.0), knot(1.0, 1.0)]);
+        let ys: Vec<f32> = curve.knots().iter().map(|k| k.position.y).collect();
+        assert_eq!(ys, [0.0, 0.0, 1.0]);
+
+        // Added after the knots with the same x
+        let added = knot(1.0, 2.0);
+        assert_eq!(curve.add_knot(added), 3);
+        assert_eq!(curve.knot_index_of(added.id), Some(3));
+        assert_eq!(curve.knot_index_of(usize::MAX), None);
+    }
+
+    #[test]
+    fn repairs_order_after_direct_changes() {
+        let mut curve = test_curve();
+        assert_epoch(&mut curve, false, |curve| {
+            assert!(!curve.repair_knot_order());
+        });
+
+        let generation = curve.generation();
+        let moved = curve.knots()[0].id;
+        // As if changed through reflection
+        curve.knots[0].position.x = 2.5;
+        assert_epoch(&mut curve, true, |curve| {
+            assert!(curve.repair_knot_order());
+        });
+        assert_eq!(curve.knot_index_of(moved), Some(2));
+        assert_eq!(curve.generation(), generation + 1);
+
+        // Duplicate ids get new ids
+        let first = curve.knots()[0].id;
+        curve.knots[1].id = first;
+        assert_epoch(&mut curve, true, |curve| {
+            assert!(curve.repair_knot_order());
+        });
+        assert_eq!(curve.knots()[0].id, first);
+        assert_ne!(curve.knots()[1].id, first);
+    }
+
+    #[test]
+    fn ids_stay_unique() {
+        let shared = Knot {
+            id: 7,
+            ..knot(0.0, 0.0)
+        };
+        let mut curve = LookupCurve::new(vec![
+            shared,
+            Knot {
+                position: Vec2::new(1.0, 1.0),
+                ..shared
+            },
+            Knot {
+                id: 0,
+                ..knot(2.0, 0.0)
+            },
+        ]);
+        let ids: Vec<usize> = curve.knots().iter().map(|knot| knot.id).collect();
+        assert_eq!(ids[0], 7);
+        assert!(!ids[1..].contains(&7) && !ids.contains(&0) && ids[1] != ids[2]);
+
+        // Given the id of another knot, or no id, a modified knot keeps its own
+        let second = curve.knots()[1];
+        curve.modify_knot(1, Knot { id: 7, ..second });
+        curve.modify_knot(1, Knot { id: 0, ..second });
+        assert_eq!(curve.knots()[1].id, second.id);
+
+        assert_eq!(curve.modify_knot_by_id(second.id, knot(3.0, 1.0)), Some(2));
+        assert_eq!(curve.knots()[2].id, second.id);
+        assert_eq!(
+            curve.knot_by_id(second.id).map(|knot| knot.position),
+            Some(Vec2::new(3.0, 1.0))
+        );
+        assert_eq!(curve.modify_knot_by_id(usize::MAX, knot(0.0, 0.0)), None);
+        assert!(curve.knot_by_id(usize::MAX).is_none());
+        assert_eq!(
+            curve.delete_knot_by_id(7).map(|knot| knot.position),
+            Some(Vec2::ZERO)
+        );
+        assert!(curve.delete_knot_by_id(7).is_none());
+        assert_eq!(curve.knots().len(), 2);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn deserialized_ids_are_unique() {
+        let curve = test_curve();
+        let bytes = crate::serialize_curve_ron(&curve, crate::RonStyle::default()).unwrap();
+        let ron = String::from_utf8(bytes).unwrap();
+        // Ids are not written, but are read if present
+        let ron = ron.replace("position:", "id: 5, position:");
+        assert_eq!(ron.matches("id: 5").count(), 4);
+
+        let parsed = crate::parse_curve_ron(ron.as_bytes()).unwrap();
+        let mut ids: Vec<usize> = parsed.knots().iter().map(|knot| knot.id).collect();
+        assert_eq!(ids[0], 5);
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn transformed_curves_get_their_own_epoch() {
+        let curve = test_curve();
+        assert_ne!(curve.mirrored(1.5).order_epoch(), curve.order_epoch());
+    }
+
+    #[cfg(feature = "ipc")]
+    #[test]
+    fn deltas_change_the_epoch_with_the_order() {
+        use crate::ipc::CurveDelta;
+
+        let mut curve = test_curve();
+        assert_epoch(&mut curve, false, |curve| {
+            CurveDelta::SetKnot {
+                index: 1,
+                knot: knot(1.0, 3.0),
+            }
+            .apply(curve)
+            .unwrap();
+        });
+        assert_epoch(&mut curve, true, |curve| {
+            CurveDelta::SetKnot {
+                index: 1,
+                knot: knot(2.5, 3.0),
+            }
+            .apply(curve)
+            .unwrap();
+        });
+        assert_epoch(&mut curve, true, |curve| {
+            CurveDelta::AddKnot(knot(4.0, 0.0)).apply(curve).unwrap();
+        });
+        assert_epoch(&mut curve, true, |curve| {
+            CurveDelta::DeleteKnot { index: 0 }.apply(curve).unwrap();
+        });
+    }
+
+    #[cfg(feature = "asset_processing")]
+    #[test]
+    fn repairing_non_finite_knots_changes_the_epoch() {
+        use crate::processing::{CurveProcessSettings, RuleAction};
+
+        let settings = CurveProcessSettings {
+            finite_knots: RuleAction::Repair,
+            ..Default::default()
+        };
+        let mut curve = test_curve();
+        assert_epoch(&mut curve, false, |curve| {
+            curve.apply_process_rules(&settings).unwrap();
+        });
+        curve.knots[1].left_tangent.slope = f32::INFINITY;
+        assert_epoch(&mut curve, true, |curve| {
+            curve.apply_process_rules(&settings).unwrap();
+        });
+    }
+}
@@ -0,0 +1,107 @@
+use bevy_math::Vec2;
+
+use crate::{Knot, KnotInterpolation, LookupCurve};
+
+impl LookupCurve {
+    /// Constructs a straight line from `from` to `to`. Outside of the knots y is clamped, like for all new curves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a position is NaN.
+    pub fn linear(from: Vec2, to: Vec2) -> Self {
+        Self::from([from, to].map(|position| Knot {
+            position,
+            interpolation: KnotInterpolation::Linear,
+            ..Default::default()
+        }))
+    }
+
+    /// Constructs an ease in and out from `(0, 0)` to `(1, 1)`, a cubic segment with flat tangents at both ends.
+    /// Its y at `t` is `3t² - 2t³`, like `smoothstep`.
+    pub fn ease_in_out() -> Self {
+        Self::from([Vec2::ZERO, Vec2::ONE].map(|position| Knot {
+            position,
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }))
+    }
+
+    /// Constructs a curve that is `y` everywhere, with knots at x 0 and 1 so that it can be edited like other
+    /// curves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is NaN.
+    pub fn constant(y: f32) -> Self {
+        Self::linear(Vec2::new(0.0, y), Vec2::new(1.0, y))
+    }
+
+    /// Constructs a curve with a linear segment between each of the `(x, y)` samples, like values imported from a
+    /// table of measurements. The samples can be in any order, samples with the same x keep their order and form a
+    /// step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sample is NaN.
+    pub fn from_samples(samples: &[(f32, f32)]) -> Self {
+        samples
+            .iter()
+            .map(|&(x, y)| Knot {
+                position: Vec2::new(x, y),
+                interpolation: KnotInterpolation::Linear,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_samples(curve: &LookupCurve, expected: impl Fn(f32) -> f32) {
+        for i in -5..=25 {
+            let x = i as f32 / 10.0;
+            let (actual, expected) = (curve.lookup(x), expected(x));
+            assert!(
+                (actual - expected).abs() < 1e-5,
+                "{x}: {actual} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn presets_sample_as_expected() {
+        let line = LookupCurve::linear(Vec2::new(0.0, 1.0), Vec2::new(2.0, -1.0));
+        assert_samples(&line, |x| 1.0 - x.clamp(0.0, 2.0));
+
+        let ease = LookupCurve::ease_in_out();
+        assert_samples(&ease, |x| {
+            let t = x.clamp(0.0, 1.0);
+            3.0 * t * t - 2.0 * t * t * t
+        });
+        assert_eq!(ease.domain(), Some((0.0, 1.0)));
+
+        let constant = LookupCurve::constant(0.25);
+        assert_samples(&constant, |_| 0.25);
+        assert_eq!(constant.domain(), Some((0.0, 1.0)));
+    }
+
+    #[test]
+    fn samples_become_linear_knots() {
+        let curve = LookupCurve::from_samples(&[(2.0, 0.0), (0.0, 1.0), (1.0, 3.0), (1.0, 2.0)]);
+        let positions: Vec<(f32, f32)> = curve
+            .knots()
+            .iter()
+            .map(|knot| (knot.position.x, knot.position.y))
+            .collect();
+        assert_eq!(positions, [(0.0, 1.0), (1.0, 3.0), (1.0, 2.0), (2.0, 0.0)]);
+        assert_samples(&curve, |x| match x {
+            x if x < 0.0 => 1.0,
+            x if x <= 1.0 => 1.0 + 2.0 * x,
+            x if x <= 2.0 => 2.0 - 2.0 * (x - 1.0),
+            _ => 0.0,
+        });
+        assert!(LookupCurve::from_samples(&[]).knots().is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+use bevy_math::Vec2;
+
+use crate::{quantize, Knot, LookupCurve, TangentMode};
+
+/// Rounding of the values stored in a curve, an authoring rule like frame aligned times or damage in steps of 0.5,
+/// rather than a display setting. See [LookupCurve::quantize_storage].
+///
+/// Values are rounded with [quantize], so that multiples of decimal steps like `0.1` and of steps like `1.0 / 60.0`
+/// are stored as the nearest `f32` to the exact multiple.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(default))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct QuantizeRule {
+    /// Step x of knots is rounded to, for example `1.0 / 60.0` for times aligned to frames. `None` keeps x as is.
+    pub x_step: Option<f32>,
+    /// Step y of knots is rounded to. `None` keeps y as is.
+    pub y_step: Option<f32>,
+    /// Also round the slopes of tangents, to multiples of [QuantizeRule::y_step] per unit of x.
+    ///
+    /// Tangent weights are kept, as are the slopes of [TangentMode::Auto] tangents, which follow the rounded positions.
+    pub tangents_too: bool,
+}
+
+impl QuantizeRule {
+    /// `position` with the steps of the rule applied
+    pub fn position(&self, position: Vec2) -> Vec2 {
+        let round =
+            |value: f32, step: Option<f32>| step.map_or(value, |step| quantize(value, step));
+        Vec2::new(
+            round(position.x, self.x_step),
+            round(position.y, self.y_step),
+        )
+    }
+
+    /// `knot` with its position, and its tangents with [QuantizeRule::tangents_too], following the rule
+    pub fn knot(&self, mut knot: Knot) -> Knot {
+        knot.position = self.position(knot.position);
+        if let (true, Some(step)) = (self.tangents_too, self.y_step) {
+            for tangent in [&mut knot.left_tangent, &mut knot.right_tangent] {
+                if tangent.mode != TangentMode::Auto {
+                    tangent.slope = quantize(tangent.slope, step);
+                }
+            }
+        }
+        knot
+    }
+}
+
+impl LookupCurve {
+    /// Consumes the curve and returns it with [LookupCurve::quantize_storage] set to `rule`, rounding its knots
+    pub fn with_quantize_storage(mut self, rule: QuantizeRule) -> Self {
+        self.quantize_storage = Some(rule);
+        self.requantize();
+        self
+    }
+
+    /// Applies [LookupCurve::quantize_storage] to all knots, after changing the rule or after editing knots with
+    /// methods that keep exact values, like [LookupCurve::modify_knot]. Returns the ids of the knots that changed, in
+    /// the order of the knots.
+    ///
+    /// Rounding never moves a knot past another knot, so all knots keep their index.
+    pub fn requantize(&mut self) -> Vec<usize> {
+        let Some(rule) = self.quantize_storage else {
+            return Vec::new();
+        };
+        let time = self.edit_time();
+        let mut changed = Vec::new();
+        for i in 0..self.knots.len() {
+            let knot = self.knots[i];
+            let quantized = rule.knot(knot);
+            if quantized.position != knot.position
+                || quantized.left_tangent.slope != knot.left_tangent.slope
+                || quantized.right_tangent.slope != knot.right_tangent.slope
+            {
+                self.knots[i] = quantized;
+                self.stamp_modified(i, time);
+                changed.push(knot.id);
+            }
+        }
+        if !changed.is_empty() {
+            self.bump_generation();
+            self.refresh_auto_tangents();
+            self.check_consistency();
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KnotInterpolation, StitchMode};
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+    }
+
+    /// Frames of a 60 Hz timeline on x, damage steps of 0.5 on y
+    const RULE: QuantizeRule = QuantizeRule {
+        x_step: Some(1.0 / 60.0),
+        y_step: Some(0.5),
+        tangents_too: false,
+    };
+
+    fn on_grid(curve: &LookupCurve) -> bool {
+        curve
+            .knots()
+            .iter()
+            .all(|knot| RULE.position(knot.position) == knot.position)
+    }
+
+    #[test]
+    fn rounds_positions_and_tangents() {
+        let mut knot = knot(0.505, 1.3);
+        for (tangent, slope) in [
+            (&mut knot.left_tangent, 0.8),
+            (&mut knot.right_tangent, 1.1),
+        ] {
+            tangent.slope = slope;
+            tangent.mode = TangentMode::Free;
+        }
+        let rounded = RULE.knot(knot);
+        assert_eq!(rounded.position, Vec2::new(0.5, 1.5));
+        assert_eq!(rounded.left_tangent.slope, 0.8);
+
+        let with_tangents = QuantizeRule {
+            tangents_too: true,
+            ..RULE
+        }
+        .knot(knot);
+        assert_eq!(with_tangents.left_tangent.slope, 1.0);
+        assert_eq!(with_tangents.right_tangent.slope, 1.0);
+
+        // Axes without a step are kept
+        let only_y = QuantizeRule {
+            y_step: Some(0.1),
+            ..Default::default()
+        };
+        assert_eq!(
+            only_y.position(Vec2::new(0.123, 0.3 + 1e-7)),
+            Vec2::new(0.123, 0.3)
+        );
+    }
+
+    #[test]
+    fn requantize_reports_moved_knots() {
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(0.51, 1.0), knot(1.0, 2.2)]);
+        let ids: Vec<usize> = curve.knots().iter().map(|knot| knot.id).collect();
+        assert_eq!(
+            curve.requantize(),
+            [0usize; 0],
+            "curves without a rule are kept"
+        );
+
+        curve.quantize_storage = Some(RULE);
+        let generation = curve.generation();
+        assert_eq!(curve.requantize(), [ids[1], ids[2]]);
+        assert_eq!(curve.generation(), generation + 1);
+        assert!(on_grid(&curve));
+        assert_eq!(curve.knots()[2].position, Vec2::new(1.0, 2.0));
+        // Rounded values stay as they are
+        assert!(curve.requantize().is_empty());
+        assert_eq!(curve.generation(), generation + 1);
+    }
+
+    #[test]
+    fn adds_and_transforms_follow_the_rule() {
+        let mut curve =
+            LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]).with_quantize_storage(RULE);
+        let i = curve.add_knot(knot(0.3 + 1e-3, 0.74));
+        assert_eq!(curve.knots()[i].position, Vec2::new(18.0 / 60.0, 0.5));
+
+        // Direct modifications keep exact values until requantized
+        let knot_1 = curve.knots()[1];
+        curve.modify_knot(
+            1,
+            Knot {
+                position: Vec2::new(0.401, 0.3),
+                ..knot_1
+            },
+        );
+        assert!(!on_grid(&curve));
+        assert_eq!(curve.requantize(), [knot_1.id]);
+
+        assert!(on_grid(&curve.scaled(1.01, 1.3)));
+        assert!(on_grid(&curve.mirrored(0.123)));
+        let mut spliced = curve.clone();
+        spliced.splice(
+            0.2..=0.6,
+            &LookupCurve::new(vec![knot(0.0, 0.0), knot(0.33, 0.1), knot(1.0, 1.0)]),
+            StitchMode::Absolute,
+        );
+        assert!(on_grid(&spliced));
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn quantized_values_serialize_cleanly() {
+        let mut curve =
+            LookupCurve::new(vec![knot(0.0, 0.0)]).with_quantize_storage(QuantizeRule {
+                x_step: Some(0.1),
+                y_step: Some(0.1),
+                tangents_too: true,
+            });
+        // Seven steps of 0.1 added up in f32 are not 0.7
+        let sum = (0..7).fold(0.0f32, |sum, _| sum + 0.1);
+        assert_ne!(sum, 0.7);
+        curve.add_knot(knot(sum, 0.7000001));
+        curve.add_knot(knot(0.3 + 1e-7, 7.0 / 60.0));
+
+        let bytes = crate::serialize_curve_ron(&curve, crate::RonStyle::default()).unwrap();
+        let ron = String::from_utf8(bytes).unwrap();
+        assert!(ron.contains("0.3"), "{ron}");
+        assert!(!ron.contains("0.30000"), "{ron}");
+        assert!(!ron.contains("0.70000"), "{ron}");
+
+        let mut parsed = crate::parse_curve_ron(ron.as_bytes()).unwrap();
+        assert_eq!(parsed.quantize_storage, curve.quantize_storage);
+        assert!(parsed.requantize().is_empty());
+        let positions = |curve: &LookupCurve| {
+            curve
+                .knots()
+                .iter()
+                .map(|knot| knot.position)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&parsed), positions(&curve));
+        assert_eq!(
+            positions(&curve),
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(0.3, 0.1),
+                Vec2::new(0.7, 0.7)
+            ]
+        );
+    }
+}
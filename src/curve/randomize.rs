@@ -0,0 +1,244 @@
+use crate::{next_generation, LookupCurve};
+
+/// Smallest gap between two knots after jittering x, as a fraction of their original gap
+const MIN_GAP_FRACTION: f32 = 0.01;
+
+/// How [RandomizeParams::y_jitter] is measured
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum YJitterMode {
+    /// y of each knot moves by at most `y_jitter`
+    #[default]
+    Absolute,
+    /// y of each knot moves by at most `y_jitter` times the absolute y of the knot, so knots at 0 stay in place
+    Relative,
+}
+
+/// Bounds of the random changes made by [LookupCurve::randomized]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RandomizeParams {
+    /// Max change of y of each knot, see [Self::y_jitter_mode]
+    pub y_jitter: f32,
+    pub y_jitter_mode: YJitterMode,
+    /// Max change of x of each knot, as a fraction of the gap to the neighboring knot in the direction it moves.
+    ///
+    /// Limited so that gaps between knots never shrink below [Self::min_gap_fraction] of their original size, so
+    /// knots are never reordered.
+    pub x_jitter_fraction_of_gap: f32,
+    /// Smallest gap between two knots after jittering x, as a fraction of their original gap. At least 0.01.
+    pub min_gap_fraction: f32,
+    /// Max relative change of the tangent slopes of each knot, between 0 and 1. Both tangents of a knot are scaled
+    /// by the same factor, so aligned tangents stay aligned.
+    pub tangent_scale_jitter: f32,
+    /// Keep the positions of the first and last knot
+    pub preserve_endpoints: bool,
+}
+
+impl Default for RandomizeParams {
+    fn default() -> Self {
+        Self {
+            y_jitter: 0.0,
+            y_jitter_mode: YJitterMode::default(),
+            x_jitter_fraction_of_gap: 0.0,
+            min_gap_fraction: 0.1,
+            tangent_scale_jitter: 0.0,
+            preserve_endpoints: false,
+        }
+    }
+}
+
+impl LookupCurve {
+    /// Returns a randomized variant of the curve, for example to vary an authored curve when spawning.
+    ///
+    /// `uniform` returns random values in `0.0..1.0`, for example `|| rng.gen()` with the `rand` crate. Exactly three
+    /// values are drawn for each knot, in order of the knots, whatever the `params`. So the same sequence of values
+    /// always gives the same variant, and variants with different `params` are randomized alike.
+    ///
+    /// The variant has the same number of knots in the same order, with the same ids, interpolations and tangent
+    /// modes. Tangent weights are relative to the gaps between knots, so weighted tangents follow jittered x.
+    /// Slopes of [TangentMode::Auto](crate::TangentMode::Auto) tangents are computed for the jittered knots.
+    /// Anchors are kept, and might not be on the variant.
+    pub fn randomized(
+        &self,
+        params: &RandomizeParams,
+        mut uniform: impl FnMut() -> f32,
+    ) -> LookupCurve {
+        // Each knot moves at most this fraction of a gap towards its neighbor, so that two knots moving towards each
+        // other leave the minimum gap
+        let min_gap = params.min_gap_fraction.clamp(MIN_GAP_FRACTION, 1.0);
+        let x_fraction = params
+            .x_jitter_fraction_of_gap
+            .clamp(0.0, (1.0 - min_gap) / 2.0);
+        let slope_jitter = params.tangent_scale_jitter.clamp(0.0, 1.0);
+
+        let mut curve = self.clone();
+        let last = self.knots.len().saturating_sub(1);
+        for (i, knot) in curve.knots.iter_mut().enumerate() {
+            // Between -1 and 1
+            let mut signed = || uniform().clamp(0.0, 1.0) * 2.0 - 1.0;
+            let (dx, dy, slope_scale) = (signed(), signed(), 1.0 + signed() * slope_jitter);
+
+            let pinned = params.preserve_endpoints && (i == 0 || i == last);
+            if !pinned {
+                let gap_left = i
+                    .checked_sub(1)
+                    .map(|j| self.knots[i].position.x - self.knots[j].position.x);
+                let gap_right = self
+                    .knots
+                    .get(i + 1)
+                    .map(|next| next.position.x - self.knots[i].position.x);
+                // The first and last knot can move as far outwards as inwards
+                let gap = if dx < 0.0 {
+                    gap_left.or(gap_right)
+                } else {
+                    gap_right.or(gap_left)
+                };
+                knot.position.x += dx * x_fraction * gap.unwrap_or(0.0);
+
+                knot.position.y += dy
+                    * match params.y_jitter_mode {
+                        YJitterMode::Absolute => params.y_jitter,
+                        YJitterMode::Relative => params.y_jitter * knot.position.y.abs(),
+                    };
+            }
+            knot.left_tangent.slope *= slope_scale;
+            knot.right_tangent.slope *= slope_scale;
+        }
+        curve.refresh_auto_tangents();
+        curve.generation = next_generation();
+        curve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, KnotInterpolation, TangentSide};
+    use bevy_math::Vec2;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn test_curve() -> LookupCurve {
+        let knots = [
+            (0.0, 0.0),
+            (0.1, 1.0),
+            (0.15, -2.0),
+            (2.0, 0.5),
+            (2.01, 3.0),
+        ];
+        LookupCurve::new(
+            knots
+                .into_iter()
+                .map(|(x, y)| {
+                    Knot {
+                        position: Vec2::new(x, y),
+                        interpolation: KnotInterpolation::Cubic,
+                        ..Default::default()
+                    }
+                    .with_tangent_slope(TangentSide::Right, 1.5)
+                    .with_tangent_weight(TangentSide::Left, Some(0.4))
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Everything randomization can change
+    fn shape(curve: &LookupCurve) -> Vec<(Vec2, [f32; 2], [Option<f32>; 2])> {
+        curve
+            .knots()
+            .iter()
+            .map(|k| {
+                (
+                    k.position,
+                    [k.left_tangent.slope, k.right_tangent.slope],
+                    [k.left_tangent.weight, k.right_tangent.weight],
+                )
+            })
+            .collect()
+    }
+
+    fn extreme() -> RandomizeParams {
+        RandomizeParams {
+            y_jitter: 100.0,
+            y_jitter_mode: YJitterMode::Absolute,
+            x_jitter_fraction_of_gap: 10.0,
+            min_gap_fraction: 0.0,
+            tangent_scale_jitter: 5.0,
+            preserve_endpoints: false,
+        }
+    }
+
+    #[test]
+    fn zero_params_keep_the_curve() {
+        let curve = test_curve();
+        let mut rng = StdRng::seed_from_u64(1);
+        let variant = curve.randomized(&RandomizeParams::default(), || rng.gen());
+        assert_eq!(shape(&variant), shape(&curve));
+        assert_ne!(variant.generation(), curve.generation());
+    }
+
+    #[test]
+    fn order_is_preserved() {
+        let curve = test_curve();
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..200 {
+            let variant = curve.randomized(&extreme(), || rng.gen());
+            for (pair, original) in variant.knots().windows(2).zip(curve.knots().windows(2)) {
+                let (gap, original_gap) = (
+                    pair[1].position.x - pair[0].position.x,
+                    original[1].position.x - original[0].position.x,
+                );
+                assert!(
+                    gap >= MIN_GAP_FRACTION * original_gap * 0.999,
+                    "{gap} {original_gap}"
+                );
+            }
+            // Extreme values of the source
+            for value in [0.0, 1.0] {
+                let variant = curve.randomized(&extreme(), || value);
+                let xs: Vec<_> = variant.knots().iter().map(|k| k.position.x).collect();
+                assert!(xs.windows(2).all(|pair| pair[0] < pair[1]), "{xs:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn endpoints_can_be_pinned() {
+        let curve = test_curve();
+        let params = RandomizeParams {
+            preserve_endpoints: true,
+            ..extreme()
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        let variant = curve.randomized(&params, || rng.gen());
+        let (knots, original) = (variant.knots(), curve.knots());
+        assert_eq!(knots[0].position, original[0].position);
+        assert_eq!(knots[4].position, original[4].position);
+        assert_ne!(knots[2].position, original[2].position);
+    }
+
+    #[test]
+    fn same_source_gives_same_variant() {
+        let curve = test_curve();
+        let params = RandomizeParams {
+            y_jitter: 0.5,
+            y_jitter_mode: YJitterMode::Relative,
+            x_jitter_fraction_of_gap: 0.3,
+            tangent_scale_jitter: 0.2,
+            ..Default::default()
+        };
+        let variant = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            curve.randomized(&params, || rng.gen())
+        };
+        assert_eq!(shape(&variant(4)), shape(&variant(4)));
+        assert_ne!(shape(&variant(4)), shape(&variant(5)));
+
+        let variant = variant(4);
+        for (knot, original) in variant.knots().iter().zip(curve.knots()) {
+            assert_eq!(knot.id, original.id);
+            assert!(
+                (knot.position.y - original.position.y).abs() <= 0.5 * original.position.y.abs()
+            );
+            assert_eq!(knot.left_tangent.weight, original.left_tangent.weight);
+        }
+    }
+}
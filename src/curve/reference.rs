@@ -0,0 +1,313 @@
+use bevy_math::Vec2;
+
+use crate::{unweighted_cubic_interp, Knot, KnotInterpolation, LookupCurve, TangentSide};
+
+/// Max number of times a segment is halved while fitting a reference function
+const MAX_FIT_DEPTH: u32 = 12;
+/// Number of parts a fitted segment is split into when comparing it to the reference
+const FIT_CHECKS: usize = 8;
+/// Step of the central difference estimating the slope of the reference, relative to the fitted range
+const SLOPE_STEP: f32 = 1e-4;
+/// Number of evenly spaced segments [sample_adaptive] starts from
+const INITIAL_SAMPLES: usize = 16;
+/// Max number of times [sample_adaptive] halves a segment
+const MAX_SAMPLE_DEPTH: u32 = 6;
+
+/// Difference between a curve and a reference function, see [LookupCurve::fit_error]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FitError {
+    /// Largest absolute difference in y
+    pub max: f32,
+    /// Root mean square of the differences in y
+    pub rms: f32,
+}
+
+impl LookupCurve {
+    /// Compares the curve to `reference` at `samples` evenly spaced x between `min_x` and `max_x`, including both.
+    ///
+    /// Samples where the reference is not finite are skipped. Returns `None` if the curve has no knots, the range is
+    /// empty or not finite, or no sample could be compared.
+    pub fn fit_error(
+        &self,
+        reference: impl Fn(f32) -> f32,
+        min_x: f32,
+        max_x: f32,
+        samples: usize,
+    ) -> Option<FitError> {
+        if self.knots.is_empty() || !valid_range(min_x, max_x) {
+            return None;
+        }
+        let samples = samples.max(2);
+        let (mut max, mut sum_squared, mut count) = (0.0f32, 0.0f32, 0);
+        for i in 0..samples {
+            let x = min_x + (max_x - min_x) * i as f32 / (samples - 1) as f32;
+            let expected = reference(x);
+            if !expected.is_finite() {
+                continue;
+            }
+            let error = (self.lookup(x) - expected).abs();
+            max = max.max(error);
+            sum_squared += error * error;
+            count += 1;
+        }
+        (count > 0).then(|| FitError {
+            max,
+            rms: (sum_squared / count as f32).sqrt(),
+        })
+    }
+
+    /// Returns a curve with the name and settings of this curve, and cubic knots fitted to `reference` between
+    /// `min_x` and `max_x`.
+    ///
+    /// Segments are halved until they are within `tolerance` of the reference on the y axis. Slopes of the reference
+    /// are estimated from nearby values. Where the reference is not smooth the subdivision stops after a fixed depth,
+    /// and the fit might not reach `tolerance`.
+    ///
+    /// Returns `None` if the range is empty or not finite, or the reference is not finite within the range.
+    pub fn fitted_to(
+        &self,
+        reference: impl Fn(f32) -> f32,
+        min_x: f32,
+        max_x: f32,
+        tolerance: f32,
+    ) -> Option<LookupCurve> {
+        if !valid_range(min_x, max_x) {
+            return None;
+        }
+        let step = (max_x - min_x) * SLOPE_STEP;
+        let point = |x: f32| {
+            let (low, high) = ((x - step).max(min_x), (x + step).min(max_x));
+            let slope = (reference(high) - reference(low)) / (high - low);
+            let y = reference(x);
+            (y.is_finite() && slope.is_finite()).then_some((Vec2::new(x, y), slope))
+        };
+
+        let time = self.edit_time();
+        let (start, start_slope) = point(min_x)?;
+        let mut knots = vec![Knot {
+            position: start,
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+        .with_tangent_slope(TangentSide::Right, start_slope)];
+        let end = point(max_x)?;
+        fit_segment(
+            &point,
+            (start, start_slope),
+            end,
+            tolerance.abs(),
+            0,
+            &mut knots,
+        )?;
+        for knot in &mut knots {
+            *knot = knot.stamped_created(time);
+        }
+
+        Some(LookupCurve {
+            knots: knots.into(),
+            max_iters: self.max_iters,
+            max_error: self.max_error,
+            name: self.name.clone(),
+            x_unit: self.x_unit.clone(),
+            y_unit: self.y_unit.clone(),
+            anchors: self.anchors.clone(),
+            strict_monotonic_x: self.strict_monotonic_x,
+            extrapolate_before: self.extrapolate_before,
+            extrapolate_after: self.extrapolate_after,
+            track_edit_times: self.track_edit_times,
+            edit_clock: self.edit_clock,
+            ..Default::default()
+        })
+    }
+}
+
+fn valid_range(min_x: f32, max_x: f32) -> bool {
+    min_x.is_finite() && max_x.is_finite() && min_x < max_x
+}
+
+/// Fits the reference between `start` and `end`, given with their slopes, and appends the knots after `start`
+fn fit_segment(
+    point: &impl Fn(f32) -> Option<(Vec2, f32)>,
+    start: (Vec2, f32),
+    end: (Vec2, f32),
+    tolerance: f32,
+    depth: u32,
+    knots: &mut Vec<Knot>,
+) -> Option<()> {
+    let fitted_end = Knot {
+        position: end.0,
+        interpolation: KnotInterpolation::Cubic,
+        ..Default::default()
+    }
+    .with_tangent_slope(TangentSide::Left, end.1)
+    .with_tangent_slope(TangentSide::Right, end.1);
+    let fitted_start = Knot {
+        position: start.0,
+        ..Default::default()
+    }
+    .with_tangent_slope(TangentSide::Right, start.1);
+
+    let width = end.0.x - start.0.x;
+    let mut error = 0.0f32;
+    for i in 1..FIT_CHECKS {
+        let (expected, _) = point(start.0.x + width * i as f32 / FIT_CHECKS as f32)?;
+        error = error.max(
+            (unweighted_cubic_interp(&fitted_start, &fitted_end, expected.x) - expected.y).abs(),
+        );
+    }
+
+    if error <= tolerance || depth >= MAX_FIT_DEPTH {
+        knots.push(fitted_end);
+        return Some(());
+    }
+    let mid = point(start.0.x + width * 0.5)?;
+    fit_segment(point, start, mid, tolerance, depth + 1, knots)?;
+    fit_segment(point, mid, end, tolerance, depth + 1, knots)
+}
+
+/// Samples `f` between `min_x` and `max_x`, adding samples where the straight lines between them are further than
+/// `tolerance` from `f` on the y axis. Returns runs of finite samples, split where `f` is not finite.
+#[cfg_attr(not(feature = "editor_egui"), allow(dead_code))]
+pub(crate) fn sample_adaptive(
+    f: &dyn Fn(f32) -> f32,
+    min_x: f32,
+    max_x: f32,
+    tolerance: f32,
+) -> Vec<Vec<Vec2>> {
+    let mut runs = Vec::new();
+    if !valid_range(min_x, max_x) {
+        return runs;
+    }
+    let mut run: Vec<Vec2> = Vec::new();
+    let mut push = |p: Vec2| {
+        if p.y.is_finite() {
+            run.push(p);
+        } else if !run.is_empty() {
+            runs.push(std::mem::take(&mut run));
+        }
+    };
+
+    let sample = |x: f32| Vec2::new(x, f(x));
+    let mut prev = sample(min_x);
+    push(prev);
+    for i in 1..=INITIAL_SAMPLES {
+        let next = sample(min_x + (max_x - min_x) * i as f32 / INITIAL_SAMPLES as f32);
+        refine(&sample, prev, next, tolerance, 0, &mut push);
+        prev = next;
+    }
+    if !run.is_empty() {
+        runs.push(run);
+    }
+    runs
+}
+
+/// Pushes the samples between `a` and `b`, and then `b`
+fn refine(
+    sample: &impl Fn(f32) -> Vec2,
+    a: Vec2,
+    b: Vec2,
+    tolerance: f32,
+    depth: u32,
+    push: &mut impl FnMut(Vec2),
+) {
+    let mid = sample(0.5 * (a.x + b.x));
+    let straight = 0.5 * (a.y + b.y);
+    // Non-finite values are never close, and are refined until the max depth to find where they start
+    let close = (mid.y - straight).abs() <= tolerance;
+    if depth < MAX_SAMPLE_DEPTH && !close {
+        refine(sample, a, mid, tolerance, depth + 1, push);
+        refine(sample, mid, b, tolerance, depth + 1, push);
+    } else {
+        push(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(x: f32) -> f32 {
+        1.0 - (-3.0 * x).exp()
+    }
+
+    #[test]
+    fn fitting_a_smooth_reference_reaches_the_tolerance() {
+        for tolerance in [1e-2, 1e-3, 1e-4] {
+            let fitted = LookupCurve::default()
+                .with_name("fit")
+                .fitted_to(target, 0.0, 2.0, tolerance)
+                .unwrap();
+            assert_eq!(fitted.name.as_deref(), Some("fit"));
+            assert_eq!(fitted.knots().first().unwrap().position.x, 0.0);
+            assert_eq!(fitted.knots().last().unwrap().position.x, 2.0);
+            let error = fitted.fit_error(target, 0.0, 2.0, 1000).unwrap();
+            // The lookup solves x on the bezier, which adds a small error to the fitted hermite segments
+            assert!(error.max <= tolerance * 1.1, "{error:?} > {tolerance}");
+        }
+
+        let coarse = LookupCurve::default()
+            .fitted_to(target, 0.0, 2.0, 1e-2)
+            .unwrap();
+        let fine = LookupCurve::default()
+            .fitted_to(target, 0.0, 2.0, 1e-4)
+            .unwrap();
+        assert!(coarse.knots().len() < fine.knots().len());
+    }
+
+    #[test]
+    fn fitting_needs_a_finite_reference() {
+        let curve = LookupCurve::default();
+        assert!(curve.fitted_to(|x| 1.0 / x, 0.0, 1.0, 1e-3).is_none());
+        assert!(curve.fitted_to(target, 1.0, 1.0, 1e-3).is_none());
+        assert!(curve.fitted_to(target, 0.0, f32::INFINITY, 1e-3).is_none());
+    }
+
+    #[test]
+    fn fit_error_matches_direct_computation() {
+        let curve = LookupCurve::new(vec![
+            Knot {
+                position: Vec2::ZERO,
+                interpolation: KnotInterpolation::Linear,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::ONE,
+                interpolation: KnotInterpolation::Linear,
+                ..Default::default()
+            },
+        ]);
+        let reference = |x: f32| x * x;
+        // x - x^2 at x = 0, 0.25, 0.5, 0.75, 1
+        let errors = [0.0f32, 0.1875, 0.25, 0.1875, 0.0];
+        let rms = (errors.iter().map(|e| e * e).sum::<f32>() / 5.0).sqrt();
+
+        let error = curve.fit_error(reference, 0.0, 1.0, 5).unwrap();
+        assert!((error.max - 0.25).abs() < 1e-6);
+        assert!((error.rms - rms).abs() < 1e-6);
+
+        // Samples where the reference is not finite are skipped
+        let gaps = |x: f32| if x == 0.5 { f32::NAN } else { x * x };
+        let error = curve.fit_error(gaps, 0.0, 1.0, 5).unwrap();
+        assert!((error.max - 0.1875).abs() < 1e-6);
+        assert!(LookupCurve::default()
+            .fit_error(reference, 0.0, 1.0, 5)
+            .is_none());
+    }
+
+    #[test]
+    fn adaptive_samples_follow_the_function() {
+        let runs = sample_adaptive(&target, 0.0, 2.0, 1e-3);
+        assert_eq!(runs.len(), 1);
+        let samples = &runs[0];
+        assert_eq!(samples.first().unwrap().x, 0.0);
+        assert_eq!(samples.last().unwrap().x, 2.0);
+        // The curve is steep near 0, so more samples are taken there
+        let near_start = samples.iter().filter(|p| p.x < 0.5).count();
+        let near_end = samples.iter().filter(|p| p.x > 1.5).count();
+        assert!(near_start > near_end);
+
+        let runs = sample_adaptive(&|x: f32| (x - 1.0).ln(), 0.0, 2.0, 1e-3);
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].iter().all(|p| p.x > 1.0 && p.y.is_finite()));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{unique_knot_id, Knot, LookupCurve};
+
+/// What [LookupCurve::replace_with] keeps of the replaced curve. The default keeps nothing, so the curve becomes a
+/// copy of the source with fresh knot ids.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct PreserveOptions {
+    /// Keep [LookupCurve::name], [LookupCurve::x_unit] and [LookupCurve::y_unit]
+    pub metadata: bool,
+    /// Keep how the curve is evaluated and tracked: [LookupCurve::extrapolate_before],
+    /// [LookupCurve::extrapolate_after], [LookupCurve::max_iters], [LookupCurve::max_error],
+    /// [LookupCurve::strict_monotonic_x], [LookupCurve::time_warp], [LookupCurve::track_edit_times] and
+    /// [LookupCurve::edit_clock]
+    pub settings: bool,
+    /// Keep [LookupCurve::quantize_storage] and [LookupCurve::anchors]
+    pub rules: bool,
+    /// Give the knots of the source the ids of the knots they replace, so that selections in editors and other
+    /// references to knot ids survive the replacement.
+    ///
+    /// A source knot takes the id of the knot nearest to it on the x axis, if it is also the source knot nearest to
+    /// that knot. Other source knots get fresh ids.
+    pub match_ids: bool,
+}
+
+impl LookupCurve {
+    /// Replaces the knots and settings of the curve with those of `source`, keeping what `preserve` asks for.
+    ///
+    /// The replacement is one modification: the generation changes once, and writing it to a curve asset sends one
+    /// modified event, so data derived from the curve is only rebuilt once. The order epoch changes if the sequence of
+    /// knot ids changes, which it does unless [PreserveOptions::match_ids] matches every knot.
+    ///
+    /// Knots keep their [Knot::created_at] and [Knot::modified_at] times, like in [LookupCurve::splice]. Kept rules
+    /// are applied to the new knots: they are rounded by a kept [LookupCurve::quantize_storage], and their tangent
+    /// weights are limited by a kept [LookupCurve::strict_monotonic_x]. Kept anchors are not enforced, see
+    /// [LookupCurve::enforce_anchors].
+    pub fn replace_with(&mut self, source: &LookupCurve, preserve: PreserveOptions) {
+        let ids: Vec<usize> = self.knots.iter().map(|knot| knot.id).collect();
+        let mut replaced = source.clone();
+        for knot in &mut replaced.knots {
+            knot.id = unique_knot_id();
+        }
+        if preserve.match_ids {
+            match_ids(&self.knots, &mut replaced.knots);
+        }
+
+        if preserve.metadata {
+            replaced.name = self.name.take();
+            replaced.x_unit = self.x_unit.take();
+            replaced.y_unit = self.y_unit.take();
+        }
+        if preserve.settings {
+            replaced.extrapolate_before = self.extrapolate_before;
+            replaced.extrapolate_after = self.extrapolate_after;
+            replaced.max_iters = self.max_iters;
+            replaced.max_error = self.max_error;
+            replaced.strict_monotonic_x = self.strict_monotonic_x;
+            replaced.time_warp = self.time_warp;
+            replaced.track_edit_times = self.track_edit_times;
+            replaced.edit_clock = self.edit_clock;
+        }
+        if preserve.rules {
+            replaced.quantize_storage = self.quantize_storage;
+            replaced.anchors = std::mem::take(&mut self.anchors);
+        }
+        if replaced.strict_monotonic_x {
+            replaced.enforce_monotonic_x();
+        }
+        replaced.requantize();
+
+        // The repairs above changed the generation of the copy, the curve only changes once
+        replaced.generation = self.generation;
+        replaced.order_epoch = self.order_epoch;
+        replaced.edit_log = std::mem::take(&mut self.edit_log);
+        *self = replaced;
+        self.bump_generation();
+        if !self.knots.iter().map(|knot| knot.id).eq(ids) {
+            self.bump_order_epoch();
+        }
+        self.check_consistency();
+    }
+}
+
+/// Gives `knots` the ids of the `existing` knots they are mutually nearest to on the x axis. Both are sorted by x.
+fn match_ids(existing: &[Knot], knots: &mut [Knot]) {
+    let matches: Vec<Option<usize>> = (0..knots.len())
+        .map(|i| {
+            let j = nearest_x(existing, knots[i].position.x)?;
+            (nearest_x(knots, existing[j].position.x) == Some(i)).then_some(existing[j].id)
+        })
+        .collect();
+    for (knot, id) in knots.iter_mut().zip(matches) {
+        if let Some(id) = id {
+            knot.id = id;
+        }
+    }
+}
+
+/// Index of the knot nearest to `x`, the first of knots at the same distance
+fn nearest_x(knots: &[Knot], x: f32) -> Option<usize> {
+    let i = knots.partition_point(|knot| knot.position.x < x);
+    let distance = |i: usize| (knots[i].position.x - x).abs();
+    match (i.checked_sub(1), (i < knots.len()).then_some(i)) {
+        (Some(before), Some(after)) if distance(after) < distance(before) => Some(after),
+        (Some(before), _) => Some(before),
+        (None, after) => after,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+
+    use super::*;
+    use crate::{Extrapolation, KnotInterpolation, QuantizeRule, UnitDisplay};
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+    }
+
+    fn ids(curve: &LookupCurve) -> Vec<usize> {
+        curve.knots().iter().map(|knot| knot.id).collect()
+    }
+
+    fn destination() -> LookupCurve {
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 0.0)])
+            .with_name("destination")
+            .with_y_unit(UnitDisplay::new("%").with_scale(100.0))
+            .with_anchors(vec![Vec2::new(1.0, 1.0)])
+            .with_quantize_storage(QuantizeRule {
+                x_step: Some(0.5),
+                ..Default::default()
+            });
+        curve.extrapolate_after = Extrapolation::Linear;
+        curve
+    }
+
+    fn source() -> LookupCurve {
+        LookupCurve::new(vec![knot(0.1, 1.0), knot(1.2, 2.0), knot(1.3, 3.0)]).with_name("source")
+    }
+
+    #[test]
+    fn replaces_everything_by_default() {
+        let mut curve = destination();
+        let source = source();
+        let (generation, epoch) = (curve.generation(), curve.order_epoch());
+        curve.replace_with(&source, PreserveOptions::default());
+
+        assert_eq!(curve.generation(), generation + 1);
+        assert_ne!(curve.order_epoch(), epoch);
+        assert_eq!(curve.name.as_deref(), Some("source"));
+        assert_eq!(curve.y_unit, None);
+        assert_eq!(curve.extrapolate_after, Extrapolation::Clamp);
+        assert!(curve.anchors.is_empty());
+        assert_eq!(curve.quantize_storage, None);
+        let positions = |curve: &LookupCurve| {
+            curve
+                .knots()
+                .iter()
+                .map(|knot| knot.position)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&curve), positions(&source));
+        // The source keeps its ids to itself
+        assert!(ids(&curve).iter().all(|id| !ids(&source).contains(id)));
+        assert_eq!(curve.edit_effect_since(generation).dirty_segments, 0..2);
+    }
+
+    #[test]
+    fn keeps_what_is_preserved() {
+        let mut curve = destination();
+        curve.replace_with(
+            &source(),
+            PreserveOptions {
+                metadata: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(curve.name.as_deref(), Some("destination"));
+        assert_eq!(curve.y_unit, Some(UnitDisplay::new("%").with_scale(100.0)));
+        assert_eq!(curve.extrapolate_after, Extrapolation::Clamp);
+
+        let mut curve = destination().with_strict_monotonic_x();
+        let mut weighted = source();
+        let knot = weighted.knots()[1].with_tangent_weight(crate::TangentSide::Right, Some(1.0));
+        weighted.modify_knot(1, knot);
+        let generation = curve.generation();
+        curve.replace_with(
+            &weighted,
+            PreserveOptions {
+                settings: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(curve.name.as_deref(), Some("source"));
+        assert_eq!(curve.extrapolate_after, Extrapolation::Linear);
+        assert!(curve.strict_monotonic_x);
+        assert!(curve.is_monotonic_x());
+        assert_eq!(curve.generation(), generation + 1, "repairs are no edits");
+
+        let mut curve = destination();
+        let generation = curve.generation();
+        curve.replace_with(
+            &source(),
+            PreserveOptions {
+                rules: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(curve.anchors, [Vec2::new(1.0, 1.0)]);
+        assert_eq!(curve.quantize_storage, destination().quantize_storage);
+        let xs: Vec<f32> = curve.knots().iter().map(|knot| knot.position.x).collect();
+        assert_eq!(xs, [0.0, 1.0, 1.5]);
+        assert_eq!(curve.generation(), generation + 1);
+    }
+
+    #[test]
+    fn matches_ids_by_nearest_x() {
+        let mut curve = destination();
+        let original = ids(&curve);
+        let epoch = curve.order_epoch();
+        let preserve = PreserveOptions {
+            match_ids: true,
+            ..Default::default()
+        };
+        // Similar shapes keep all ids, and the order epoch
+        curve.replace_with(
+            &LookupCurve::new(vec![knot(0.1, 0.5), knot(0.9, 1.5), knot(2.2, 0.5)]),
+            preserve,
+        );
+        assert_eq!(ids(&curve), original);
+        assert_eq!(curve.order_epoch(), epoch);
+
+        // The knots at 1.2 and 1.3 are both nearest to the knot at 0.9, only 1.2 is also nearest to it
+        curve.replace_with(&source(), preserve);
+        let matched = ids(&curve);
+        assert_eq!(matched[..2], original[..2]);
+        assert!(!original.contains(&matched[2]));
+        assert_ne!(curve.order_epoch(), epoch);
+
+        // Fewer knots in the source
+        let mut curve = destination();
+        let last = curve.knots()[2].id;
+        curve.replace_with(&LookupCurve::new(vec![knot(1.9, 0.0)]), preserve);
+        assert_eq!(ids(&curve), [last]);
+
+        let mut empty = LookupCurve::default();
+        empty.replace_with(&source(), preserve);
+        assert_eq!(empty.knots().len(), 3);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::LookupCurve;
+
+/// Formatting of RON written by [serialize_curve_ron]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RonStyle {
+    /// Indented with two spaces and one field per line, as written by [LookupCurve::save_to_file]
+    #[default]
+    Pretty,
+    /// Without whitespace, as written for processed assets
+    Compact,
+}
+
+/// Error returned by [parse_curve_ron]
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum CurveParseError {
+    /// The bytes are not RON describing a lookup curve
+    #[error("Could not parse RON for lookup curve at byte {offset} (line {line}, column {column}): {code}")]
+    Syntax {
+        /// Offset of the error from the start of the bytes
+        offset: usize,
+        line: usize,
+        column: usize,
+        code: ron::error::Error,
+    },
+    /// The bytes are not YAML describing a lookup curve
+    #[cfg(feature = "yaml")]
+    #[error("Could not parse YAML for lookup curve at byte {offset} (line {line}, column {column}): {code}")]
+    Yaml {
+        offset: usize,
+        line: usize,
+        column: usize,
+        code: serde_yaml::Error,
+    },
+    /// The bytes are not TOML describing a lookup curve
+    #[cfg(feature = "toml")]
+    #[error(
+        "Could not parse TOML for lookup curve at byte {offset} (line {line}, column {column}): {}",
+        code.message()
+    )]
+    Toml {
+        offset: usize,
+        line: usize,
+        column: usize,
+        code: toml::de::Error,
+    },
+}
+
+impl CurveParseError {
+    fn from_spanned(bytes: &[u8], error: ron::error::SpannedError) -> Self {
+        let ron::error::Position { line, col } = error.position;
+        // RON counts lines from 1, and columns in bytes from 1
+        let line_start: usize = bytes
+            .split_inclusive(|&b| b == b'\n')
+            .take(line.saturating_sub(1))
+            .map(<[u8]>::len)
+            .sum();
+        CurveParseError::Syntax {
+            offset: (line_start + col.saturating_sub(1)).min(bytes.len()),
+            line,
+            column: col,
+            code: error.code,
+        }
+    }
+}
+
+/// Parses a lookup curve from RON, without the asset system.
+///
+/// This is what the asset loader and [LookupCurve::load_from_file] use, so curves parsed here are identical to loaded
+/// curves. Fields added in later versions of this crate are optional, so curves saved by earlier versions parse as
+/// well. Knots out of order are sorted by x like in [LookupCurve::new]. Knots with positions that are not finite, or
+/// tangent weights outside of `0..=1`, are an error. Tangents that are not finite are kept for processing to reject or
+/// repair.
+pub fn parse_curve_ron(bytes: &[u8]) -> Result<LookupCurve, CurveParseError> {
+    ron::de::from_bytes::<LookupCurve>(bytes).map_err(|e| CurveParseError::from_spanned(bytes, e))
+}
+
+/// Serializes a lookup curve to RON, which [parse_curve_ron] parses back to the same curve
+pub fn serialize_curve_ron(
+    curve: &LookupCurve,
+    style: RonStyle,
+) -> Result<Vec<u8>, ron::error::Error> {
+    let s = match style {
+        RonStyle::Pretty => {
+            let config = ron::ser::PrettyConfig::new()
+                .new_line("\n".to_string())
+                .indentor("  ".to_string());
+            ron::ser::to_string_pretty(curve, config)?
+        }
+        RonStyle::Compact => ron::ser::to_string(curve)?,
+    };
+    Ok(s.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A curve saved before units, anchors and edit times were added
+    const LEGACY: &[u8] = include_bytes!("../../assets/example.curve.ron");
+
+    #[test]
+    fn file_and_free_function_parse_the_same_curve() {
+        let parsed = parse_curve_ron(LEGACY).unwrap();
+        let loaded =
+            LookupCurve::from_text_bytes(LEGACY, "example.curve.ron", crate::CurveFormat::Ron)
+                .unwrap();
+        assert_eq!(parsed.knots().len(), 3);
+        for style in [RonStyle::Pretty, RonStyle::Compact] {
+            let bytes = serialize_curve_ron(&parsed, style).unwrap();
+            assert_eq!(bytes, serialize_curve_ron(&loaded, style).unwrap());
+            let reparsed = parse_curve_ron(&bytes).unwrap();
+            assert_eq!(serialize_curve_ron(&reparsed, style).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn malformed_ron_reports_its_position() {
+        let bytes = b"(\n  knots: [\n    (position: (0.0, 0.0), interpolation: Cubicc),\n  ],\n)";
+        let Err(CurveParseError::Syntax {
+            offset, line, code, ..
+        }) = parse_curve_ron(bytes)
+        else {
+            panic!("expected a syntax error");
+        };
+        assert_eq!(line, 3);
+        assert!(
+            bytes[offset..].starts_with(b"Cubicc") || bytes[..offset].ends_with(b"Cubicc"),
+            "{offset}: {code}"
+        );
+
+        let knot = |x: f32| {
+            format!(
+                "(position: ({x:?}, 0.0), interpolation: Linear, \
+                left_tangent: (slope: 0.0, mode: Free), right_tangent: (slope: 0.0, mode: Free))"
+            )
+        };
+        let unordered = format!("(knots: [{}, {}, {}])", knot(1.0), knot(0.0), knot(2.0));
+        let sorted = parse_curve_ron(unordered.as_bytes()).unwrap();
+        let xs: Vec<f32> = sorted.knots().iter().map(|k| k.position.x).collect();
+        assert_eq!(xs, [0.0, 1.0, 2.0]);
+
+        let nan = format!("(knots: [{}, {}])", knot(0.0), knot(f32::NAN));
+        let Err(CurveParseError::Syntax { code, .. }) = parse_curve_ron(nan.as_bytes()) else {
+            panic!("expected a NaN error");
+        };
+        assert!(code.to_string().contains("knot 1 "), "{code}");
+
+        let invalid = [
+            format!("(knots: [{}, {}])", knot(0.0), knot(f32::INFINITY)),
+            format!("(knots: [{}])", knot(0.0)).replace(
+                "(slope: 0.0, mode: Free)",
+                "(slope: 0.0, mode: Free, weight: Some(1.5))",
+            ),
+            format!("(knots: [{}])", knot(0.0)).replace("Linear", "Power(exponent: -1.0)"),
+        ];
+        for bytes in invalid {
+            assert!(matches!(
+                parse_curve_ron(bytes.as_bytes()),
+                Err(CurveParseError::Syntax { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn interpolations_with_parameters_round_trip() {
+        use crate::{Knot, KnotInterpolation};
+        use bevy_math::Vec2;
+
+        let interpolations = [
+            KnotInterpolation::Constant,
+            KnotInterpolation::ConstantNext,
+            KnotInterpolation::Power { exponent: 2.5 },
+            KnotInterpolation::Linear,
+        ];
+        let curve = LookupCurve::new(
+            interpolations
+                .iter()
+                .enumerate()
+                .map(|(i, &interpolation)| Knot {
+                    position: Vec2::new(i as f32, 0.0),
+                    interpolation,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>(),
+        );
+        let bytes = serialize_curve_ron(&curve, RonStyle::Compact).unwrap();
+        let ron = String::from_utf8(bytes).unwrap();
+        assert!(ron.contains("interpolation:ConstantNext"), "{ron}");
+        assert!(ron.contains("interpolation:Power(exponent:2.5)"), "{ron}");
+        let parsed = parse_curve_ron(ron.as_bytes()).unwrap();
+        let parsed: Vec<_> = parsed.knots().iter().map(|k| k.interpolation).collect();
+        assert_eq!(parsed, interpolations);
+
+        // Files with the interpolations without parameters load as before
+        let legacy = parse_curve_ron(LEGACY).unwrap();
+        assert!(legacy
+            .knots()
+            .iter()
+            .all(|knot| knot.interpolation == KnotInterpolation::Cubic));
+    }
+}
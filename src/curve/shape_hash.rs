@@ -0,0 +1,218 @@
+use super::determinism::fnv1a;
+use crate::{Extrapolation, KnotInterpolation, LookupCurve, Tangent};
+
+/// Version of the algorithm of [LookupCurve::shape_hash].
+///
+/// Shape hashes are stable within a minor version of this crate. This is increased whenever the algorithm changes,
+/// and stored hashes computed with a different version should be recomputed.
+pub const SHAPE_HASH_VERSION: u32 = 1;
+
+impl LookupCurve {
+    /// Hash of the data that decides the values sampled from the curve, identical on all platforms.
+    ///
+    /// Covers the knot positions, the interpolation of every segment, the tangents of cubic segments, the
+    /// extrapolation and the settings of the weighted cubic solve. Knot ids, edit times, tangent modes, the name, units, anchors and other
+    /// settings only used while editing are left out, as are tangents and interpolations that are never sampled,
+    /// like the tangents of linear segments and the interpolation of the last knot.
+    ///
+    /// `-0.0` is hashed like `0.0`, and all NaNs hash alike. See [SHAPE_HASH_VERSION] for the stability of the
+    /// hash, and [LookupCurve::shape_eq] for the matching comparison.
+    pub fn shape_hash(&self) -> u64 {
+        let mut words = Vec::new();
+        self.shape_words(|word| words.push(word));
+        fnv1a(words.iter().flat_map(|word| word.to_le_bytes()))
+    }
+
+    /// Whether both curves have the same shape, comparing exactly the data hashed by [LookupCurve::shape_hash].
+    ///
+    /// Curves with equal shapes always have equal shape hashes.
+    pub fn shape_eq(&self, other: &LookupCurve) -> bool {
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        self.shape_words(|word| a.push(word));
+        other.shape_words(|word| b.push(word));
+        a == b
+    }
+
+    /// Visits the shape relevant data of the curve, in a fixed order
+    fn shape_words(&self, mut visit: impl FnMut(u32)) {
+        visit(SHAPE_HASH_VERSION);
+        visit(self.max_iters as u32);
+        visit(canonical_bits(self.max_error));
+        visit(self.knots.len() as u32);
+        for (i, knot) in self.knots.iter().enumerate() {
+            visit(canonical_bits(knot.position.x));
+            visit(canonical_bits(knot.position.y));
+            let Some(next) = self.knots.get(i + 1) else {
+                continue;
+            };
+            match knot.interpolation {
+                KnotInterpolation::Constant => visit(0),
+                KnotInterpolation::Linear => visit(1),
+                KnotInterpolation::Cubic => {
+                    visit(2);
+                    visit_tangent(&knot.right_tangent, &mut visit);
+                    visit_tangent(&next.left_tangent, &mut visit);
+                }
+                KnotInterpolation::ConstantNext => visit(3),
+                KnotInterpolation::Power { exponent } => {
+                    visit(4);
+                    visit(canonical_bits(exponent));
+                }
+            }
+        }
+        // Only extrapolation other than clamping is visited, so hashes of clamped curves stay the same
+        let extrapolation = [self.extrapolate_before, self.extrapolate_after];
+        if extrapolation != [Extrapolation::Clamp; 2] {
+            for mode in extrapolation {
+                visit(mode as u32);
+            }
+        }
+    }
+}
+
+fn visit_tangent(tangent: &Tangent, visit: &mut impl FnMut(u32)) {
+    visit(canonical_bits(tangent.slope));
+    match tangent.weight {
+        Some(weight) => {
+            visit(1);
+            visit(canonical_bits(weight));
+        }
+        None => visit(0),
+    }
+}
+
+/// Bits of `value`, with `-0.0` turned into `0.0` and all NaNs into the same NaN
+fn canonical_bits(value: f32) -> u32 {
+    if value.is_nan() {
+        f32::NAN.to_bits()
+    } else {
+        (value + 0.0).to_bits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, TangentMode};
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    fn curve() -> LookupCurve {
+        LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Cubic),
+            knot(0.5, 1.0, KnotInterpolation::Linear),
+            knot(1.0, 0.5, KnotInterpolation::Constant),
+        ])
+    }
+
+    #[test]
+    fn metadata_does_not_change_the_hash() {
+        let original = curve();
+        let knots: Vec<Knot> = original
+            .knots()
+            .iter()
+            .map(|knot| Knot {
+                id: knot.id + 100,
+                created_at: Some(7),
+                ..knot.with_tangent_mode(crate::TangentSide::Left, TangentMode::Aligned)
+            })
+            .collect();
+        let mut changed = LookupCurve::new(knots).with_name("renamed");
+        changed.anchors.push(Vec2::ONE);
+        changed.track_edit_times = true;
+
+        assert!(original.shape_eq(&changed));
+        assert_eq!(original.shape_hash(), changed.shape_hash());
+
+        // Tangents of linear segments and the interpolation of the last knot are never sampled
+        let mut unused = original.clone();
+        let mut last = unused.knots()[2];
+        last.interpolation = KnotInterpolation::Cubic;
+        unused.modify_knot(2, last);
+        let mut linear = unused.knots()[1];
+        linear.right_tangent.slope = 3.0;
+        unused.modify_knot(1, linear);
+        assert!(original.shape_eq(&unused));
+        assert_eq!(original.shape_hash(), unused.shape_hash());
+    }
+
+    #[test]
+    fn shape_changes_change_the_hash() {
+        let original = curve();
+        let hash = original.shape_hash();
+
+        let mut moved = original.clone();
+        let mut knot = moved.knots()[1];
+        knot.position.y += 0.001;
+        moved.modify_knot(1, knot);
+
+        let mut interpolation = original.clone();
+        let mut knot = interpolation.knots()[1];
+        knot.interpolation = KnotInterpolation::Constant;
+        interpolation.modify_knot(1, knot);
+
+        let mut tangent = original.clone();
+        let mut knot = tangent.knots()[0];
+        knot.right_tangent.weight = Some(0.5);
+        tangent.modify_knot(0, knot);
+
+        let extrapolated = original
+            .clone()
+            .with_extrapolation(Extrapolation::Clamp, Extrapolation::Repeat);
+
+        let mut eased = original.clone();
+        let mut knot = eased.knots()[1];
+        knot.interpolation = KnotInterpolation::Power { exponent: 2.0 };
+        eased.modify_knot(1, knot);
+        let mut more_eased = eased.clone();
+        knot.interpolation = KnotInterpolation::Power { exponent: 2.5 };
+        more_eased.modify_knot(1, knot);
+        assert_ne!(eased.shape_hash(), more_eased.shape_hash());
+
+        for changed in [moved, interpolation, tangent, extrapolated, eased] {
+            assert!(!original.shape_eq(&changed));
+            assert_ne!(hash, changed.shape_hash());
+        }
+    }
+
+    #[test]
+    fn signed_zeros_and_nans_hash_alike() {
+        let positive = LookupCurve::new(vec![knot(0.0, 0.0, KnotInterpolation::Linear)]);
+        let negative = LookupCurve::new(vec![knot(-0.0, -0.0, KnotInterpolation::Linear)]);
+        assert!(positive.shape_eq(&negative));
+        assert_eq!(positive.shape_hash(), negative.shape_hash());
+
+        let nan = LookupCurve::new(vec![knot(0.0, f32::NAN, KnotInterpolation::Linear)]);
+        let other_nan = LookupCurve::new(vec![knot(
+            0.0,
+            f32::from_bits(f32::NAN.to_bits() | 1),
+            KnotInterpolation::Linear,
+        )]);
+        assert!(nan.shape_eq(&other_nan));
+        assert_eq!(nan.shape_hash(), other_nan.shape_hash());
+        assert!(!nan.shape_eq(&positive));
+    }
+
+    /// Changing these values changes stored hashes, and needs a new [SHAPE_HASH_VERSION]
+    #[test]
+    fn hashes_are_stable() {
+        let fixtures = [
+            (LookupCurve::new(vec![]), 0x1a29a58c097c24d1),
+            (
+                LookupCurve::new(vec![knot(1.0, 2.0, KnotInterpolation::Linear)]),
+                0x2c6285c61963ce5d,
+            ),
+            (curve(), 0x4cb1195ca8859031),
+        ];
+        for (curve, expected) in fixtures {
+            assert_eq!(curve.shape_hash(), expected, "{curve:?}");
+        }
+    }
+}
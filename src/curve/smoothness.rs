@@ -0,0 +1,319 @@
+use bevy_math::Vec2;
+
+use super::monotonic_x::x_monotonic_weights;
+use crate::{Knot, KnotInterpolation, LookupCurve};
+
+/// Which slope [LookupCurve::smooth_knot] keeps at a kinked knot, when tangents control both sides of the knot
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum SmoothPolicy {
+    /// Set both sides to the average of the incoming and outgoing slope
+    #[default]
+    Average,
+    /// Keep the incoming slope, and set the outgoing slope to it
+    MatchIncoming,
+    /// Keep the outgoing slope, and set the incoming slope to it
+    MatchOutgoing,
+}
+
+/// Slope of the curve on one side of a knot
+#[derive(Copy, Clone, Debug)]
+struct SideSlope {
+    slope: f32,
+    /// The slope is set by the tangent of the knot on this side
+    adjustable: bool,
+}
+
+/// Slope of a segment where it leaves `from`, towards the control point `to` or the control points after it
+fn direction_slope(from: Vec2, to: &[Vec2]) -> f32 {
+    match to.iter().map(|&p| p - from).find(|d| *d != Vec2::ZERO) {
+        Some(d) if d.x != 0.0 => d.y / d.x,
+        _ => 0.0,
+    }
+}
+
+/// Slopes of a segment at `knot_a` and at `knot_b`, or `None` if the segment has no width
+fn segment_end_slopes(knot_a: &Knot, knot_b: &Knot) -> Option<(SideSlope, SideSlope)> {
+    let dx = knot_b.position.x - knot_a.position.x;
+    if dx <= 0.0 {
+        return None;
+    }
+    let fixed = |slope| SideSlope {
+        slope,
+        adjustable: false,
+    };
+    let slopes = match knot_a.interpolation {
+        KnotInterpolation::Constant | KnotInterpolation::ConstantNext => (fixed(0.0), fixed(0.0)),
+        KnotInterpolation::Linear => {
+            let slope = (knot_b.position.y - knot_a.position.y) / dx;
+            (fixed(slope), fixed(slope))
+        }
+        KnotInterpolation::Power { .. } => {
+            let dy = knot_b.position.y - knot_a.position.y;
+            let slope = |s| {
+                if dy == 0.0 {
+                    0.0
+                } else {
+                    knot_a.interpolation.fraction_slope(s).unwrap_or(0.0) * dy / dx
+                }
+            };
+            (fixed(slope(0.0)), fixed(slope(1.0)))
+        }
+        KnotInterpolation::Cubic => {
+            // Sampling evaluates the segment with x-monotonic weights
+            let (weight_a, weight_b) = x_monotonic_weights(knot_a, knot_b);
+            let mut corrected = (*knot_a, *knot_b);
+            corrected.0.right_tangent.weight = weight_a;
+            corrected.1.left_tangent.weight = weight_b;
+            let [p0, p1, p2, p3] = corrected.0.compute_bezier_to(&corrected.1);
+            // A tangent of zero length does not set the slope, the segment leaves towards the next control point
+            let side = |weight: Option<f32>, slope, from, to: &[Vec2]| {
+                if weight.is_none_or(|w| w > 0.0) {
+                    SideSlope {
+                        slope,
+                        adjustable: true,
+                    }
+                } else {
+                    fixed(direction_slope(from, to))
+                }
+            };
+            (
+                side(weight_a, knot_a.right_tangent.slope, p0, &[p1, p2, p3]),
+                side(weight_b, knot_b.left_tangent.slope, p3, &[p2, p1, p0]),
+            )
+        }
+    };
+    Some(slopes)
+}
+
+impl LookupCurve {
+    /// Returns the knots where the slope of the curve jumps by more than `slope_tolerance`, as
+    /// `(knot id, incoming slope, outgoing slope)`.
+    ///
+    /// Slopes are those of the segments on both sides as sampled, with tangent weights corrected as in
+    /// [LookupCurve::enforce_monotonic_x]:
+    /// - Cubic segments have the slope of the tangent at the knot, or the direction to the next control point if
+    ///   the tangent has zero length.
+    /// - Linear segments have the slope of the line on both ends.
+    /// - Power segments have the slope of the eased line at each end, which is infinite at the start for exponents
+    ///   below 1.
+    /// - Constant segments have slope 0. The curve steps at the knot ending a [KnotInterpolation::Constant] segment,
+    ///   and right after the knot starting a [KnotInterpolation::ConstantNext] segment, unless both knots have the
+    ///   same y. Steps are discontinuities in value rather than slope, so such knots are not reported.
+    ///
+    /// The first and last knot, and knots sharing x with a neighbor, have only one side and are not reported.
+    pub fn c1_discontinuities(&self, slope_tolerance: f32) -> Vec<(usize, f32, f32)> {
+        (0..self.knots.len())
+            .filter_map(|i| {
+                let (incoming, outgoing) = self.knot_slopes(i)?;
+                ((incoming.slope - outgoing.slope).abs() > slope_tolerance).then_some((
+                    self.knots[i].id,
+                    incoming.slope,
+                    outgoing.slope,
+                ))
+            })
+            .collect()
+    }
+
+    /// Makes the incoming and outgoing slope at the knot with `id` equal, by setting the slopes of its tangents.
+    /// Returns `true` if the knot was changed.
+    ///
+    /// Only slopes set by tangents change, see [LookupCurve::c1_discontinuities]. Next to a linear or constant
+    /// segment the tangent takes the slope of that segment, whatever `policy` says. Knots with neither side set by
+    /// tangents can not be smoothed.
+    pub fn smooth_knot(&mut self, id: usize, policy: SmoothPolicy) -> bool {
+        let Some(i) = self.knots.iter().position(|knot| knot.id == id) else {
+            return false;
+        };
+        let Some(knot) = self.smoothed_knot(i, policy) else {
+            return false;
+        };
+        self.modify_knot(i, knot);
+        true
+    }
+
+    /// Smooths every knot reported by [LookupCurve::c1_discontinuities], see [LookupCurve::smooth_knot]. Returns
+    /// the ids of the changed knots.
+    pub fn smooth_all_knots(&mut self, slope_tolerance: f32, policy: SmoothPolicy) -> Vec<usize> {
+        let time = self.edit_time();
+        let mut smoothed = Vec::new();
+        for (id, _, _) in self.c1_discontinuities(slope_tolerance) {
+            let i = self.knots.iter().position(|knot| knot.id == id).unwrap();
+            if let Some(knot) = self.smoothed_knot(i, policy) {
+                self.knots[i] = knot;
+                self.stamp_modified(i, time);
+                if self.strict_monotonic_x {
+                    self.limit_tangent_weights(i);
+                }
+                smoothed.push(id);
+            }
+        }
+        if !smoothed.is_empty() {
+            self.bump_generation();
+            self.refresh_auto_tangents();
+            self.check_consistency();
+        }
+        smoothed
+    }
+
+    /// Incoming and outgoing slope at the knot at `i`, `None` if the knot does not have both
+    fn knot_slopes(&self, i: usize) -> Option<(SideSlope, SideSlope)> {
+        let knot = &self.knots[i];
+        let prev = self.prev_knot(i)?;
+        let (_, incoming) = segment_end_slopes(prev, knot)?;
+        if prev.interpolation == KnotInterpolation::Constant && prev.position.y != knot.position.y {
+            return None;
+        }
+        let next = self.next_knot(i)?;
+        if knot.interpolation == KnotInterpolation::ConstantNext
+            && next.position.y != knot.position.y
+        {
+            return None;
+        }
+        let (outgoing, _) = segment_end_slopes(knot, next)?;
+        Some((incoming, outgoing))
+    }
+
+    /// The knot at `i` with equal incoming and outgoing slope, `None` if it already has or can not be smoothed
+    fn smoothed_knot(&self, i: usize, policy: SmoothPolicy) -> Option<Knot> {
+        let (incoming, outgoing) = self.knot_slopes(i)?;
+        if incoming.slope == outgoing.slope {
+            return None;
+        }
+        let slope = match (incoming.adjustable, outgoing.adjustable, policy) {
+            (false, false, _) => return None,
+            (false, true, _) | (true, true, SmoothPolicy::MatchIncoming) => incoming.slope,
+            (true, false, _) | (true, true, SmoothPolicy::MatchOutgoing) => outgoing.slope,
+            (true, true, SmoothPolicy::Average) => (incoming.slope + outgoing.slope) / 2.0,
+        };
+        let mut knot = self.knots[i];
+        if incoming.adjustable {
+            knot.left_tangent.slope = slope;
+        }
+        if outgoing.adjustable {
+            knot.right_tangent.slope = slope;
+        }
+        Some(knot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tangent, TangentMode};
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation, left: f32, right: f32) -> Knot {
+        let tangent = |slope| Tangent {
+            slope,
+            mode: TangentMode::Free,
+            weight: None,
+        };
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            left_tangent: tangent(left),
+            right_tangent: tangent(right),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn slopes_across_mixed_joins() {
+        use KnotInterpolation::*;
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, Linear, 0.0, 0.0),
+            // Linear in with slope 1, cubic out
+            knot(1.0, 1.0, Cubic, 0.0, 3.0),
+            // Cubic in, linear out with slope -1
+            knot(2.0, 2.0, Linear, 0.5, 0.0),
+            // Linear in, constant out
+            knot(3.0, 1.0, Constant, 0.0, 0.0),
+            // Step after a constant segment
+            knot(4.0, 2.0, Constant, 0.0, 0.0),
+            // Continuous after a constant segment, cubic out
+            knot(5.0, 2.0, Cubic, 0.0, 0.0),
+            knot(6.0, 0.0, Cubic, 0.0, 0.0),
+        ]);
+        let ids: Vec<_> = curve.knots().iter().map(|k| k.id).collect();
+        assert_eq!(
+            curve.c1_discontinuities(1e-4),
+            [(ids[1], 1.0, 3.0), (ids[2], 0.5, -1.0), (ids[3], -1.0, 0.0)]
+        );
+
+        // The reported slopes are those of the sampled curve
+        for (id, incoming, outgoing) in curve.c1_discontinuities(1e-4) {
+            let x = curve
+                .knots()
+                .iter()
+                .find(|k| k.id == id)
+                .unwrap()
+                .position
+                .x;
+            let left = curve.slope_at(x, crate::TangentSide::Left);
+            let right = curve.slope_at(x, crate::TangentSide::Right);
+            assert!((left - incoming).abs() < 1e-3, "{x}: {left} {incoming}");
+            assert!((right - outgoing).abs() < 1e-3, "{x}: {right} {outgoing}");
+        }
+    }
+
+    #[test]
+    fn zero_length_tangents_use_the_next_control_point() {
+        let mut a = knot(0.0, 0.0, KnotInterpolation::Cubic, 0.0, 5.0);
+        a.right_tangent.weight = Some(0.0);
+        let b = knot(1.0, 1.0, KnotInterpolation::Cubic, 0.0, 0.0);
+        let (start, end) = segment_end_slopes(&a, &b).unwrap();
+        // Towards the control point of b, at x = 2/3 and y = 1
+        assert!((start.slope - 1.5).abs() < 1e-6);
+        assert!(!start.adjustable);
+        assert_eq!(end.slope, 0.0);
+        assert!(end.adjustable);
+    }
+
+    #[test]
+    fn smoothed_knots_are_not_reported() {
+        use KnotInterpolation::*;
+        for policy in [
+            SmoothPolicy::Average,
+            SmoothPolicy::MatchIncoming,
+            SmoothPolicy::MatchOutgoing,
+        ] {
+            let mut curve = LookupCurve::new(vec![
+                knot(0.0, 0.0, Cubic, 0.0, 0.0),
+                knot(1.0, 1.0, Cubic, 1.0, 2.0),
+                knot(2.0, 1.0, Linear, 0.0, 0.0),
+                knot(3.0, 0.0, Cubic, 0.0, 1.0),
+                knot(4.0, 1.0, Constant, 0.0, 0.0),
+                knot(5.0, 1.0, Linear, 0.0, 0.0),
+                knot(6.0, 0.0, Cubic, 0.0, 0.0),
+            ]);
+            let ids: Vec<_> = curve.knots().iter().map(|k| k.id).collect();
+
+            let mut single = curve.clone();
+            assert!(single.smooth_knot(ids[1], policy));
+            let expected = match policy {
+                SmoothPolicy::Average => 1.5,
+                SmoothPolicy::MatchIncoming => 1.0,
+                SmoothPolicy::MatchOutgoing => 2.0,
+            };
+            let smoothed = single.knots()[1];
+            assert_eq!(smoothed.left_tangent.slope, expected);
+            assert_eq!(smoothed.right_tangent.slope, expected);
+            assert!(!single.smooth_knot(ids[1], policy));
+
+            let generation = curve.generation();
+            let smoothed = curve.smooth_all_knots(1e-4, policy);
+            assert_eq!(curve.generation(), generation + 1);
+            // Slopes already match at knot 4, and tangents set neither side of knot 5
+            assert_eq!(smoothed, [ids[1], ids[2], ids[3]]);
+            let remaining: Vec<_> = curve
+                .c1_discontinuities(1e-4)
+                .into_iter()
+                .map(|(id, _, _)| id)
+                .collect();
+            assert_eq!(remaining, [ids[5]]);
+            // The tangent next to the linear segment takes its slope
+            assert_eq!(curve.knots()[2].left_tangent.slope, -1.0);
+            assert_eq!(curve.knots()[3].right_tangent.slope, -1.0);
+        }
+    }
+}
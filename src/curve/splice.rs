@@ -0,0 +1,346 @@
+use std::ops::RangeInclusive;
+
+use bevy_math::Vec2;
+
+use crate::{
+    unique_knot_id, Knot, KnotInterpolation, LookupCurve, Tangent, TangentMode, TangentSide,
+};
+
+/// How [LookupCurve::splice] fits the source curve into the target range
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum StitchMode {
+    /// Keep the y values of the source as they are. This might create steps at the edges of the range.
+    Absolute,
+    /// Scale the source on the y axis, so that its first and last knots match the value of the curve at the edges of the range.
+    ///
+    /// If the first and last knots of the source have the same y value, the source is instead offset by a linear ramp between the edges.
+    MatchEndpoints,
+    /// Same as [StitchMode::MatchEndpoints], but the tangents at the edges are also blended for a smooth transition.
+    Smooth,
+}
+
+impl LookupCurve {
+    /// Replaces the part of the curve within `range` with `source`, remapped to fit the range.
+    ///
+    /// Knots within the range (including its edges) are removed, and the knots of `source` are inserted with fresh ids.
+    /// The source is remapped on the x axis from the x of its first and last knot, to `range`. `stitch` decides
+    /// how the source is fitted on the y axis, see [StitchMode].
+    ///
+    /// The curve outside of the range is kept, though weighted cubic segments crossing the edges of the range might
+    /// change slightly since they are split at the edges. A range outside of the knot range extends the curve.
+    ///
+    /// Knots from the source keep their [Knot::created_at] and [Knot::modified_at] times, even where their tangents are
+    /// adjusted to stitch them in. Knots added at the edges of the range are stamped as new knots.
+    ///
+    /// This is a no-op if the range is empty or has zero width, or if `source` has no knots.
+    pub fn splice(&mut self, range: RangeInclusive<f32>, source: &LookupCurve, stitch: StitchMode) {
+        let (start, end) = (*range.start(), *range.end());
+        let (Some(source_first), Some(source_last)) = (source.knots.first(), source.knots.last())
+        else {
+            return;
+        };
+        if start >= end || start.is_nan() || end.is_nan() {
+            return;
+        }
+
+        // Sample the curve at the edges before modifying it
+        let (y_start, y_end) = (self.lookup(start), self.lookup(end));
+        let outer_slope_start = self.slope_at(start, TangentSide::Left);
+        let outer_slope_end = self.slope_at(end, TangentSide::Right);
+        let end_interpolation = match self.knots.partition_point(|k| k.position.x <= end) {
+            0 => KnotInterpolation::Linear,
+            i => self.knots[i - 1].interpolation,
+        };
+
+        let (source_start, source_end) = (source_first.position.x, source_last.position.x);
+        let scale_x = if source_end > source_start {
+            (end - start) / (source_end - source_start)
+        } else {
+            0.0
+        };
+        let (source_y_start, source_y_end) = (source_first.position.y, source_last.position.y);
+        let scale_y = match stitch {
+            StitchMode::Absolute => 1.0,
+            _ if source_y_end != source_y_start => {
+                (y_end - y_start) / (source_y_end - source_y_start)
+            }
+            _ => 1.0,
+        };
+        // Used instead of scaling if the source starts and ends at the same y
+        let ramp_slope = match stitch {
+            StitchMode::Absolute => 0.0,
+            _ if source_y_end != source_y_start => 0.0,
+            _ => (y_end - y_start) / (end - start),
+        };
+
+        let map_slope = |slope: f32| {
+            if scale_x == 0.0 {
+                0.0
+            } else {
+                slope * scale_y / scale_x + ramp_slope
+            }
+        };
+        let map_tangent = |tangent: Tangent| Tangent {
+            slope: map_slope(tangent.slope),
+            ..tangent
+        };
+
+        let mut inner: Vec<Knot> = source
+            .knots
+            .iter()
+            .map(|knot| {
+                let x = start + (knot.position.x - source_start) * scale_x;
+                let y = match stitch {
+                    StitchMode::Absolute => knot.position.y,
+                    _ => {
+                        y_start
+                            + (knot.position.y - source_y_start) * scale_y
+                            + (x - start) * ramp_slope
+                    }
+                };
+                Knot {
+                    position: Vec2::new(x, y),
+                    left_tangent: map_tangent(knot.left_tangent),
+                    right_tangent: map_tangent(knot.right_tangent),
+                    id: unique_knot_id(),
+                    ..*knot
+                }
+            })
+            .collect();
+
+        let boundary_tangent = |slope: f32| Tangent {
+            slope,
+            mode: TangentMode::Free,
+            weight: None,
+        };
+        let start_knot = Knot {
+            position: Vec2::new(start, y_start),
+            interpolation: KnotInterpolation::Linear,
+            left_tangent: boundary_tangent(outer_slope_start),
+            right_tangent: boundary_tangent(outer_slope_start),
+            id: unique_knot_id(),
+            created_at: None,
+            modified_at: None,
+        };
+        let end_knot = Knot {
+            position: Vec2::new(end, y_end),
+            interpolation: end_interpolation,
+            left_tangent: boundary_tangent(outer_slope_end),
+            right_tangent: boundary_tangent(outer_slope_end),
+            id: unique_knot_id(),
+            created_at: None,
+            modified_at: None,
+        };
+
+        if matches!(stitch, StitchMode::Absolute) {
+            inner.insert(0, start_knot);
+        } else {
+            // The first source knot is already at the start of the range with a matching value
+            let first = &mut inner[0];
+            first.left_tangent = boundary_tangent(outer_slope_start);
+            first.right_tangent.mode = TangentMode::Free;
+            if matches!(stitch, StitchMode::Smooth) {
+                let slope = (outer_slope_start + first.right_tangent.slope) / 2.0;
+                first.left_tangent.slope = slope;
+                first.right_tangent.slope = slope;
+            }
+        }
+
+        if matches!(stitch, StitchMode::Absolute) || scale_x == 0.0 {
+            inner.push(end_knot);
+        } else {
+            let last = inner.last_mut().unwrap();
+            last.interpolation = end_interpolation;
+            last.right_tangent = boundary_tangent(outer_slope_end);
+            last.left_tangent.mode = TangentMode::Free;
+            if matches!(stitch, StitchMode::Smooth) {
+                let slope = (outer_slope_end + last.left_tangent.slope) / 2.0;
+                last.left_tangent.slope = slope;
+                last.right_tangent.slope = slope;
+            }
+        }
+
+        self.knots
+            .retain(|knot| knot.position.x < start || knot.position.x > end);
+        let i = self.knots.partition_point(|knot| knot.position.x < start);
+        self.knots.insert_many(i, inner);
+        // Knots from the source keep their edit times, only the knots added at the edges are new
+        let time = self.edit_time();
+        for knot in self.knots.iter_mut() {
+            if knot.id == start_knot.id || knot.id == end_knot.id {
+                *knot = knot.stamped_created(time);
+            }
+        }
+        self.bump_generation();
+        // The source knots get new ids
+        self.bump_order_epoch();
+        if self.strict_monotonic_x {
+            self.enforce_monotonic_x();
+        }
+        self.refresh_auto_tangents();
+        self.requantize();
+        self.check_consistency();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    fn linear(points: &[(f32, f32)]) -> LookupCurve {
+        LookupCurve::new(
+            points
+                .iter()
+                .map(|&(x, y)| knot(x, y, KnotInterpolation::Linear))
+                .collect(),
+        )
+    }
+
+    fn assert_continuous(curve: &LookupCurve, x: f32) {
+        let eps = 1e-4;
+        assert!(
+            (curve.lookup(x - eps) - curve.lookup(x + eps)).abs() < 1e-3,
+            "discontinuity at {x}"
+        );
+    }
+
+    #[test]
+    fn match_endpoints_scales_source() {
+        let mut curve = linear(&[(0.0, 0.0), (0.5, 0.5), (1.0, 1.0)]);
+        let source = linear(&[(0.0, 0.0), (1.0, 0.5), (2.0, 2.0)]);
+        curve.splice(0.3..=0.6, &source, StitchMode::MatchEndpoints);
+
+        assert!(!curve.knots().iter().any(|k| k.position.x == 0.5));
+        assert_eq!(curve.lookup(0.3), 0.3);
+        assert!((curve.lookup(0.6) - 0.6).abs() < 1e-6);
+        // Source middle knot at 1/4 of the source range in y
+        assert!((curve.lookup(0.45) - (0.3 + 0.25 * 0.3)).abs() < 1e-6);
+        assert_continuous(&curve, 0.3);
+        assert_continuous(&curve, 0.6);
+        assert_eq!(curve.lookup(0.9), 0.9);
+    }
+
+    #[test]
+    fn match_endpoints_ramps_flat_source() {
+        let mut curve = linear(&[(0.0, 0.0), (1.0, 1.0)]);
+        let source = linear(&[(0.0, 0.0), (0.5, 1.0), (1.0, 0.0)]);
+        curve.splice(0.2..=0.8, &source, StitchMode::MatchEndpoints);
+
+        assert!((curve.lookup(0.5) - 1.5).abs() < 1e-6);
+        assert_continuous(&curve, 0.2);
+        assert_continuous(&curve, 0.8);
+    }
+
+    #[test]
+    fn absolute_keeps_values() {
+        let mut curve = linear(&[(0.0, 0.0), (1.0, 1.0)]);
+        let source = linear(&[(0.0, 5.0), (1.0, 5.0)]);
+        curve.splice(0.3..=0.6, &source, StitchMode::Absolute);
+
+        assert_eq!(curve.lookup(0.45), 5.0);
+        assert!((curve.lookup(0.29) - 0.29).abs() < 1e-6);
+        assert!((curve.lookup(0.61) - 0.61).abs() < 1e-6);
+    }
+
+    #[test]
+    fn smooth_blends_tangents() {
+        let mut curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Cubic),
+            knot(1.0, 1.0, KnotInterpolation::Cubic),
+        ]);
+        let mut source_start = knot(0.0, 0.0, KnotInterpolation::Cubic);
+        source_start.right_tangent.slope = 2.0;
+        let source = LookupCurve::new(vec![source_start, knot(1.0, 1.0, KnotInterpolation::Cubic)]);
+        curve.splice(0.25..=0.75, &source, StitchMode::Smooth);
+
+        for x in [0.25, 0.75] {
+            let left = curve.slope_at(x, TangentSide::Left);
+            let right = curve.slope_at(x, TangentSide::Right);
+            assert!((left - right).abs() < 1e-4, "{left} != {right} at {x}");
+            assert_continuous(&curve, x);
+        }
+    }
+
+    #[test]
+    fn cubic_outside_range_is_preserved() {
+        let mut start = knot(0.0, 0.0, KnotInterpolation::Cubic);
+        start.right_tangent.slope = 3.0;
+        let original = LookupCurve::new(vec![start, knot(1.0, 1.0, KnotInterpolation::Cubic)]);
+        let mut curve = original.clone();
+        curve.splice(
+            0.4..=0.6,
+            &linear(&[(0.0, 0.0), (1.0, 1.0)]),
+            StitchMode::MatchEndpoints,
+        );
+
+        for x in [0.1, 0.2, 0.3, 0.7, 0.8, 0.9] {
+            assert!((curve.lookup(x) - original.lookup(x)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let mut curve = linear(&[(0.0, 0.0), (0.5, 0.5), (1.0, 1.0)]);
+        let source = linear(&[(0.0, 0.0), (0.5, 1.0), (1.0, 0.0)]);
+        curve.splice(0.2..=0.8, &source, StitchMode::Absolute);
+        curve.splice(0.3..=0.4, &source, StitchMode::Smooth);
+
+        let ids: HashSet<usize> = curve.knots().iter().map(|k| k.id).collect();
+        assert_eq!(ids.len(), curve.knots().len());
+        assert!(source.knots().iter().all(|k| !ids.contains(&k.id)));
+    }
+
+    #[test]
+    fn extends_outside_domain() {
+        let mut curve = linear(&[(0.0, 0.0), (1.0, 1.0)]);
+        curve.splice(
+            2.0..=3.0,
+            &linear(&[(0.0, 0.0), (1.0, 1.0)]),
+            StitchMode::Absolute,
+        );
+
+        assert_eq!(curve.lookup(1.5), 1.0);
+        assert_eq!(curve.lookup(2.5), 0.5);
+        assert_eq!(curve.knots().last().unwrap().position.x, 3.0);
+    }
+
+    #[test]
+    fn degenerate_input_is_noop() {
+        let mut curve = linear(&[(0.0, 0.0), (1.0, 1.0)]);
+        let generation = curve.generation();
+        let source = linear(&[(0.0, 0.0), (1.0, 1.0)]);
+
+        curve.splice(0.5..=0.5, &source, StitchMode::Absolute);
+        curve.splice(0.6..=0.4, &source, StitchMode::Absolute);
+        curve.splice(0.2..=0.4, &LookupCurve::default(), StitchMode::Absolute);
+
+        assert_eq!(curve.generation(), generation);
+        assert_eq!(curve.knots().len(), 2);
+    }
+
+    #[test]
+    fn slope_matches_numerical_derivative() {
+        let mut start = knot(0.0, 0.0, KnotInterpolation::Cubic);
+        start.right_tangent.slope = 3.0;
+        let mut end = knot(2.0, 1.0, KnotInterpolation::Cubic);
+        end.left_tangent.slope = -1.0;
+        let curve = LookupCurve::new(vec![start, end]);
+
+        assert!((curve.slope_at(0.0, TangentSide::Right) - 3.0).abs() < 1e-5);
+        assert!((curve.slope_at(2.0, TangentSide::Left) + 1.0).abs() < 1e-5);
+        assert_eq!(curve.slope_at(0.0, TangentSide::Left), 0.0);
+        let h = 1e-3;
+        let numerical = (curve.lookup(0.7 + h) - curve.lookup(0.7 - h)) / (2.0 * h);
+        assert!((curve.slope_at(0.7, TangentSide::Right) - numerical).abs() < 1e-2);
+    }
+}
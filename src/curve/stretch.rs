@@ -0,0 +1,256 @@
+use crate::{Knot, LookupCurve};
+
+/// Smallest gap [LookupCurve::stretch_from_knot] leaves between knots when compressing
+pub const MIN_STRETCH_GAP: f32 = 1e-4;
+
+/// How [LookupCurve::stretch_from_knot] moves the other knots
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum StretchMode {
+    /// Only the moved knot changes, like [LookupCurve::modify_knot]
+    #[default]
+    Absolute,
+    /// The knots between the pivot and the moved knot are rescaled on the x axis, keeping their relative spacing.
+    Proportional {
+        /// Id of the knot that stays in place, `None` for the first knot
+        pivot: Option<usize>,
+    },
+}
+
+impl LookupCurve {
+    /// Moves the knot with `moved_knot_id` to `new_x`, moving the other knots as decided by `mode`. Returns `true`
+    /// if the curve was changed.
+    ///
+    /// With [StretchMode::Proportional] every knot between the pivot and the moved knot is moved, so that the
+    /// distances to the pivot are scaled by the same factor. The slopes of the tangents within that range are
+    /// scaled so that the segments keep their shape. Knots outside of the range are not moved. `new_x` is limited so
+    /// that knots keep their order and are at least [MIN_STRETCH_GAP] apart, unless they were closer before.
+    ///
+    /// If the moved knot is the pivot, or the pivot does not exist, the knot is moved like with
+    /// [StretchMode::Absolute].
+    pub fn stretch_from_knot(
+        &mut self,
+        moved_knot_id: usize,
+        new_x: f32,
+        mode: StretchMode,
+    ) -> bool {
+        let Some(i) = self.knots.iter().position(|knot| knot.id == moved_knot_id) else {
+            return false;
+        };
+        let pivot = match mode {
+            StretchMode::Absolute => None,
+            StretchMode::Proportional { pivot: None } => Some(0),
+            StretchMode::Proportional { pivot: Some(id) } => {
+                self.knots.iter().position(|knot| knot.id == id)
+            }
+        };
+        let Some(p) = pivot.filter(|&p| p != i) else {
+            let knot = self.knots[i];
+            if knot.position.x == new_x {
+                return false;
+            }
+            self.modify_knot(
+                i,
+                Knot {
+                    position: bevy_math::Vec2::new(new_x, knot.position.y),
+                    ..knot
+                },
+            );
+            return true;
+        };
+
+        let pivot_x = self.knots[p].position.x;
+        let old_width = self.knots[i].position.x - pivot_x;
+        if old_width == 0.0 || !new_x.is_finite() {
+            return false;
+        }
+        let scale = self
+            .stretch_scale_limits(p, i)
+            .clamp((new_x - pivot_x) / old_width);
+        if scale == 1.0 {
+            return false;
+        }
+
+        self.bump_generation();
+        let time = self.edit_time();
+        let (low, high) = (p.min(i), p.max(i));
+        for j in low..=high {
+            let knot = &mut self.knots[j];
+            if j != p {
+                knot.position.x = pivot_x + (knot.position.x - pivot_x) * scale;
+            }
+            // Only the tangents within the stretched range belong to stretched segments
+            if j > low {
+                knot.left_tangent.slope /= scale;
+            }
+            if j < high {
+                knot.right_tangent.slope /= scale;
+            }
+            self.stamp_modified(j, time);
+        }
+        self.refresh_auto_tangents();
+        self.check_consistency();
+        true
+    }
+
+    /// Range of scales for stretching the knots between the pivot `p` and the moved knot `i`, that keeps the order
+    /// of the knots and the min gap
+    fn stretch_scale_limits(&self, p: usize, i: usize) -> ScaleLimits {
+        let (low, high) = (p.min(i), p.max(i));
+        let width = (self.knots[i].position.x - self.knots[p].position.x).abs();
+        let min_gap = self.knots[low..=high]
+            .windows(2)
+            .map(|pair| pair[1].position.x - pair[0].position.x)
+            .filter(|&gap| gap > 0.0)
+            .fold(width, f32::min);
+        let min = (MIN_STRETCH_GAP / min_gap).min(1.0);
+
+        // The moved knot stays before its next neighbor outside of the range
+        let outer = if i > p {
+            self.knots.get(i + 1)
+        } else {
+            i.checked_sub(1).map(|j| &self.knots[j])
+        };
+        let max = match outer {
+            Some(outer) => {
+                let room = (outer.position.x - self.knots[p].position.x).abs() - MIN_STRETCH_GAP;
+                (room / width).max(1.0)
+            }
+            None => f32::INFINITY,
+        };
+        ScaleLimits { min, max }
+    }
+}
+
+struct ScaleLimits {
+    min: f32,
+    max: f32,
+}
+
+impl ScaleLimits {
+    fn clamp(&self, scale: f32) -> f32 {
+        scale.max(self.min).min(self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KnotInterpolation, Tangent, TangentMode};
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32) -> Knot {
+        let tangent = |slope| Tangent {
+            slope,
+            mode: TangentMode::Free,
+            weight: None,
+        };
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            left_tangent: tangent(1.0),
+            right_tangent: tangent(2.0),
+            ..Default::default()
+        }
+    }
+
+    fn curve() -> LookupCurve {
+        LookupCurve::new(vec![
+            knot(0.0, 0.0),
+            knot(1.0, 1.0),
+            knot(3.0, 0.5),
+            knot(4.0, 2.0),
+        ])
+    }
+
+    fn xs(curve: &LookupCurve) -> Vec<f32> {
+        curve.knots().iter().map(|knot| knot.position.x).collect()
+    }
+
+    #[test]
+    fn proportional_stretch_keeps_relative_spacing() {
+        let mut stretched = curve();
+        let original = stretched.clone();
+        let ids: Vec<usize> = original.knots().iter().map(|knot| knot.id).collect();
+        assert!(stretched.stretch_from_knot(
+            ids[3],
+            8.0,
+            StretchMode::Proportional { pivot: None }
+        ));
+        assert_eq!(xs(&stretched), [0.0, 2.0, 6.0, 8.0]);
+        assert_eq!(
+            stretched.knots().iter().map(|k| k.id).collect::<Vec<_>>(),
+            ids
+        );
+
+        // The first knot keeps its incoming slope, the last its outgoing slope
+        let slopes: Vec<(f32, f32)> = stretched
+            .knots()
+            .iter()
+            .map(|k| (k.left_tangent.slope, k.right_tangent.slope))
+            .collect();
+        assert_eq!(slopes, [(1.0, 1.0), (0.5, 1.0), (0.5, 1.0), (0.5, 2.0)]);
+
+        // The shape is stretched along with the knots
+        for x in [0.3, 1.7, 2.5, 3.9] {
+            assert!((stretched.lookup(x * 2.0) - original.lookup(x)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn knots_outside_the_pivot_range_are_not_moved() {
+        let mut curve = curve();
+        let (pivot, moved) = (curve.knots()[1].id, curve.knots()[2].id);
+        curve.stretch_from_knot(moved, 2.0, StretchMode::Proportional { pivot: Some(pivot) });
+        assert_eq!(xs(&curve), [0.0, 1.0, 2.0, 4.0]);
+        // The segment after the moved knot changed width, so its slope is kept
+        assert_eq!(curve.knots()[2].left_tangent.slope, 2.0);
+        assert_eq!(curve.knots()[2].right_tangent.slope, 2.0);
+        assert_eq!(curve.knots()[0].right_tangent.slope, 2.0);
+    }
+
+    #[test]
+    fn compressing_keeps_the_order_and_a_min_gap() {
+        let mut curve = curve();
+        let last = curve.knots()[3].id;
+        curve.stretch_from_knot(last, 2.0, StretchMode::Proportional { pivot: None });
+        assert_eq!(xs(&curve), [0.0, 0.5, 1.5, 2.0]);
+
+        curve.stretch_from_knot(last, -5.0, StretchMode::Proportional { pivot: None });
+        let xs = xs(&curve);
+        for pair in xs.windows(2) {
+            assert!(pair[1] - pair[0] >= MIN_STRETCH_GAP * 0.99, "{xs:?}");
+        }
+
+        // Moving the first knot with the last as pivot can not pass the knot it moves toward
+        let mut curve = self::curve();
+        let (first, last) = (curve.knots()[0].id, curve.knots()[3].id);
+        curve.stretch_from_knot(first, 10.0, StretchMode::Proportional { pivot: Some(last) });
+        let xs = self::xs(&curve);
+        assert_eq!(xs[3], 4.0);
+        assert!(xs.windows(2).all(|pair| pair[0] < pair[1]), "{xs:?}");
+    }
+
+    #[test]
+    fn growing_stops_before_the_next_knot() {
+        let mut curve = curve();
+        let (moved, next) = (curve.knots()[2].id, curve.knots()[3].id);
+        curve.stretch_from_knot(moved, 10.0, StretchMode::Proportional { pivot: None });
+        assert_eq!(curve.knots()[3].id, next);
+        assert!(curve.knots()[2].position.x < 4.0);
+        assert!(curve.knots()[2].position.x >= 4.0 - MIN_STRETCH_GAP * 1.01);
+    }
+
+    #[test]
+    fn absolute_moves_only_the_knot() {
+        let mut curve = curve();
+        let last = curve.knots()[3].id;
+        assert!(curve.stretch_from_knot(last, 8.0, StretchMode::Absolute));
+        assert_eq!(xs(&curve), [0.0, 1.0, 3.0, 8.0]);
+        // Stretching from the pivot itself is the same
+        let first = curve.knots()[0].id;
+        curve.stretch_from_knot(first, -1.0, StretchMode::Proportional { pivot: None });
+        assert_eq!(xs(&curve), [-1.0, 1.0, 3.0, 8.0]);
+        assert!(!curve.stretch_from_knot(usize::MAX, 1.0, StretchMode::Absolute));
+    }
+}
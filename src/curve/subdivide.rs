@@ -0,0 +1,242 @@
+use bevy_math::Vec2;
+
+use crate::{
+    unique_knot_id, CubicSegment, Knot, KnotInterpolation, LookupCurve, Tangent, TangentMode,
+};
+
+/// Slope of the line from `from` to `to`, `None` if it is vertical or the points are equal
+fn slope_between(from: Vec2, to: Vec2) -> Option<f32> {
+    let delta = to - from;
+    (delta.x != 0.0).then(|| delta.y / delta.x)
+}
+
+impl LookupCurve {
+    /// Inserts a knot on the curve at `x`, without changing the shape of the curve. Returns the index of the knot.
+    ///
+    /// Cubic segments are split in two, adjusting the tangent weights of the neighboring knots where the segment is
+    /// weighted. [TangentMode::Auto] tangents of the neighboring knots become [TangentMode::Aligned], keeping their
+    /// slopes. Of a [KnotInterpolation::Power] segment only the part before `x` is still a power, the part after it
+    /// becomes a cubic segment with the slopes of the power at its ends, which is exact for exponents up to 3 and
+    /// close for others. Returns the index of the existing knot if there is one at `x`, and `None` if `x` is outside of the
+    /// knot range.
+    pub fn insert_knot_at(&mut self, x: f32) -> Option<usize> {
+        self.insert_knot_with_id(x, unique_knot_id())
+    }
+
+    /// [LookupCurve::insert_knot_at], with the id of the inserted knot
+    pub(crate) fn insert_knot_with_id(&mut self, x: f32, id: usize) -> Option<usize> {
+        let (first, last) = (self.knots.first()?, self.knots.last()?);
+        if !(first.position.x..=last.position.x).contains(&x) {
+            return None;
+        }
+        let i = self.knots.partition_point(|k| k.position.x < x);
+        if self.knots[i].position.x == x {
+            return Some(i);
+        }
+        let time = self.edit_time();
+        for j in [i - 1, i] {
+            let knot = &mut self.knots[j];
+            let mut frozen = false;
+            for tangent in [&mut knot.left_tangent, &mut knot.right_tangent] {
+                if tangent.mode == TangentMode::Auto {
+                    tangent.mode = TangentMode::Aligned;
+                    frozen = true;
+                }
+            }
+            if frozen {
+                self.stamp_modified(j, time);
+            }
+        }
+        let (a, b) = (self.knots[i - 1], self.knots[i]);
+        let mut knot = Knot {
+            position: Vec2::new(x, self.lookup(x)),
+            interpolation: a.interpolation,
+            id,
+            ..Default::default()
+        };
+
+        if matches!(a.interpolation, KnotInterpolation::Cubic) {
+            let weighted = a.right_tangent.weight.is_some() || b.left_tangent.weight.is_some();
+            let [p0, p1, p2, p3] = a.compute_bezier_to(&b);
+            let t = if weighted {
+                CubicSegment::from_bezier_points([p0, p1, p2, p3]).find_t_given_x(
+                    x,
+                    self.max_error,
+                    self.max_iters,
+                )
+            } else {
+                // x is linear in t for unweighted segments
+                (x - p0.x) / (p3.x - p0.x)
+            };
+
+            // de Casteljau's algorithm
+            let (p01, p12, p23) = (p0.lerp(p1, t), p1.lerp(p2, t), p2.lerp(p3, t));
+            let (p012, p123) = (p01.lerp(p12, t), p12.lerp(p23, t));
+            let p = p012.lerp(p123, t);
+
+            // Keep x exact, t is only found to within max_error in weighted segments
+            knot.position.y = p.y;
+            let slope = slope_between(p012, p123).unwrap_or(0.0);
+            knot.left_tangent.slope = slope;
+            knot.right_tangent.slope = slope;
+            if weighted {
+                // The slopes of the neighbors are kept, as p01 and p23 are on their tangents
+                let (left_dx, right_dx) = (p.x - p0.x, p3.x - p.x);
+                self.knots[i - 1].right_tangent.weight = Some((p01.x - p0.x) / left_dx);
+                self.knots[i].left_tangent.weight = Some((p3.x - p23.x) / right_dx);
+                self.stamp_modified(i - 1, time);
+                self.stamp_modified(i, time);
+                knot.left_tangent.weight = Some((p.x - p012.x) / left_dx);
+                knot.right_tangent.weight = Some((p123.x - p.x) / right_dx);
+            }
+        }
+        if let KnotInterpolation::Power { .. } = a.interpolation {
+            let (dx, dy) = (b.position.x - a.position.x, b.position.y - a.position.y);
+            let slope = |s: f32| {
+                if dy == 0.0 {
+                    0.0
+                } else {
+                    a.interpolation.fraction_slope(s).unwrap_or(0.0) * dy / dx
+                }
+            };
+            let s = (x - a.position.x) / dx;
+            knot.interpolation = KnotInterpolation::Cubic;
+            knot.left_tangent.slope = slope(s);
+            knot.right_tangent.slope = slope(s);
+            // Not used by the power segment before
+            self.knots[i].left_tangent = Tangent {
+                slope: slope(1.0),
+                mode: TangentMode::Free,
+                weight: None,
+            };
+            self.stamp_modified(i, time);
+        }
+        Some(self.add_knot(knot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TangentSide;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    fn assert_same_shape(before: &LookupCurve, after: &LookupCurve) {
+        for i in 0..=200 {
+            let x = i as f32 / 100.0;
+            let (y_before, y_after) = (before.lookup(x), after.lookup(x));
+            assert!(
+                (y_before - y_after).abs() < 1e-4,
+                "{x}: {y_before} != {y_after}"
+            );
+        }
+    }
+
+    fn test_curve() -> LookupCurve {
+        let mut a = knot(0.0, 0.0, KnotInterpolation::Cubic);
+        a.right_tangent.slope = 3.0;
+        let mut b = knot(1.0, 1.0, KnotInterpolation::Constant);
+        b.left_tangent.slope = -1.0;
+        LookupCurve::new(vec![
+            a,
+            b,
+            knot(1.5, 0.5, KnotInterpolation::Linear),
+            knot(2.0, 2.0, KnotInterpolation::Linear),
+        ])
+    }
+
+    #[test]
+    fn inserting_keeps_shape() {
+        for weights in [None, Some((Some(0.6), None)), Some((Some(0.2), Some(0.7)))] {
+            let mut curve = test_curve();
+            if let Some((a, b)) = weights {
+                let knots = curve.knots();
+                let (knot_a, knot_b) = (knots[0], knots[1]);
+                curve.modify_knot(0, knot_a.with_tangent_weight(TangentSide::Right, a));
+                curve.modify_knot(1, knot_b.with_tangent_weight(TangentSide::Left, b));
+            }
+            let before = curve.clone();
+            for x in [0.3, 0.8, 1.2, 1.7] {
+                let i = curve.insert_knot_at(x).unwrap();
+                assert_eq!(curve.knots()[i].position.x, x);
+            }
+            assert_eq!(curve.knots().len(), 8);
+            assert_same_shape(&before, &curve);
+            // Unweighted segments stay unweighted
+            if weights.is_none() {
+                assert!(curve
+                    .knots()
+                    .iter()
+                    .all(|k| k.left_tangent.weight.is_none() && k.right_tangent.weight.is_none()));
+            }
+        }
+    }
+
+    #[test]
+    fn power_segments_keep_their_shape() {
+        for exponent in [0.5, 2.0, 3.0] {
+            let mut curve = LookupCurve::new(vec![
+                knot(0.0, 1.0, KnotInterpolation::Power { exponent }),
+                knot(2.0, -1.0, KnotInterpolation::Linear),
+            ]);
+            let before = curve.clone();
+            let i = curve.insert_knot_at(0.5).unwrap();
+            assert_eq!(
+                curve.knots()[0].interpolation,
+                KnotInterpolation::Power { exponent }
+            );
+            assert_eq!(curve.knots()[i].interpolation, KnotInterpolation::Cubic);
+            if exponent >= 2.0 {
+                assert_same_shape(&before, &curve);
+            } else {
+                let worst = (0..=200)
+                    .map(|j| j as f32 / 100.0)
+                    .map(|x| (before.lookup(x) - curve.lookup(x)).abs())
+                    .fold(0.0, f32::max);
+                // Within 1% of the change of y over the segment
+                assert!(worst < 0.02, "{worst}");
+            }
+        }
+    }
+
+    #[test]
+    fn auto_neighbors_keep_their_slopes() {
+        let auto = |x, y| {
+            knot(x, y, KnotInterpolation::Cubic)
+                .with_tangent_mode(TangentSide::Left, TangentMode::Auto)
+                .with_tangent_mode(TangentSide::Right, TangentMode::Auto)
+        };
+        let mut curve = LookupCurve::new(vec![
+            auto(0.0, 0.0),
+            auto(0.5, 1.0),
+            auto(1.5, -1.0),
+            auto(2.0, 0.0),
+        ]);
+        let before = curve.clone();
+        let i = curve.insert_knot_at(1.0).unwrap();
+        assert_same_shape(&before, &curve);
+        for knot in [curve.knots()[i - 1], curve.knots()[i + 1]] {
+            assert_eq!(knot.left_tangent.mode, TangentMode::Aligned);
+            assert_eq!(knot.right_tangent.mode, TangentMode::Aligned);
+        }
+        // Knots further away are not affected by the inserted knot
+        assert_eq!(curve.knots()[0].right_tangent.mode, TangentMode::Auto);
+    }
+
+    #[test]
+    fn existing_knots_and_outside_of_range() {
+        let mut curve = test_curve();
+        assert_eq!(curve.insert_knot_at(1.0), Some(1));
+        assert_eq!(curve.insert_knot_at(-0.1), None);
+        assert_eq!(curve.insert_knot_at(2.1), None);
+        assert_eq!(LookupCurve::default().insert_knot_at(0.0), None);
+        assert_eq!(curve.knots().len(), 4);
+    }
+}
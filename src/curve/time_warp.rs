@@ -0,0 +1,418 @@
+use std::fmt;
+
+use bevy_math::Vec2;
+
+use super::invert::segment_ys;
+use super::monotonic_x::UNWEIGHTED;
+use crate::{Knot, KnotInterpolation, LookupCurve, TangentMode};
+
+/// Allowed distance of the end knots of a [TimeWarpCurve] from `(0, 0)` and `(1, 1)`, and allowed decrease of y
+/// from rounding
+pub const TIME_WARP_EPSILON: f32 = 1e-5;
+
+/// Error returned when a [LookupCurve] is not a valid [TimeWarpCurve]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimeWarpError {
+    /// The curve has less than two knots
+    TooFewKnots,
+    /// The first knot is not at `(0, 0)`
+    Start { position: Vec2 },
+    /// The last knot is not at `(1, 1)`
+    End { position: Vec2 },
+    /// y decreases within the segment starting at the knot with this index
+    Decreasing { segment: usize },
+}
+
+impl fmt::Display for TimeWarpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeWarpError::TooFewKnots => write!(f, "Time warp needs at least two knots"),
+            TimeWarpError::Start { position } => {
+                write!(f, "Time warp starts at {position} instead of (0, 0)")
+            }
+            TimeWarpError::End { position } => {
+                write!(f, "Time warp ends at {position} instead of (1, 1)")
+            }
+            TimeWarpError::Decreasing { segment } => {
+                write!(f, "Time warp goes back in time at segment {segment}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimeWarpError {}
+
+/// A [LookupCurve] remapping normalized time, for easing or retiming animations.
+///
+/// The curve goes from `(0, 0)` to `(1, 1)` and y never decreases, so time never runs backwards. Flat segments are
+/// allowed, and hold time still. Created with [TimeWarpCurve::try_from], or [TimeWarpCurve::enforce] to repair a
+/// curve instead. The wrapped curve has [LookupCurve::time_warp] set, so that the editor keeps it valid.
+#[derive(Clone, Debug)]
+pub struct TimeWarpCurve(LookupCurve);
+
+impl TimeWarpCurve {
+    /// Repairs `curve` with [LookupCurve::enforce_time_warp] and wraps it
+    pub fn enforce(mut curve: LookupCurve) -> Self {
+        curve.enforce_time_warp();
+        curve.time_warp = true;
+        Self(curve)
+    }
+
+    /// The wrapped curve
+    pub fn curve(&self) -> &LookupCurve {
+        &self.0
+    }
+
+    /// Returns the wrapped curve
+    pub fn into_curve(self) -> LookupCurve {
+        self.0
+    }
+
+    /// Returns the warped time at time `t`. Both are clamped to `0..=1`.
+    pub fn warp(&self, t: f32) -> f32 {
+        self.0.lookup(t.clamp(0.0, 1.0)).clamp(0.0, 1.0)
+    }
+
+    /// Returns the earliest time at which [TimeWarpCurve::warp] reaches the warped time `t`. Both are clamped to
+    /// `0..=1`.
+    ///
+    /// Where the warp is flat, this is the start of the flat part. Solved with bisection, like the exact inverse in
+    /// [LookupCurve::inverted].
+    pub fn inverse_warp(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        let knots = self.0.knots();
+        // Knots are in order of y, up to TIME_WARP_EPSILON
+        let i = knots.partition_point(|knot| knot.position.y < t);
+        let x = match (i.checked_sub(1).map(|i| &knots[i]), knots.get(i)) {
+            (_, None) => 1.0,
+            (None, Some(b)) => b.position.x,
+            (Some(a), Some(b))
+                if a.interpolation == KnotInterpolation::Constant
+                    || a.position.x == b.position.x =>
+            {
+                b.position.x
+            }
+            (Some(a), Some(_)) if a.interpolation == KnotInterpolation::ConstantNext => {
+                a.position.x
+            }
+            (Some(a), Some(b)) => self.0.solve_x(a, b, t),
+        };
+        x.clamp(0.0, 1.0)
+    }
+}
+
+impl TryFrom<LookupCurve> for TimeWarpCurve {
+    type Error = TimeWarpError;
+
+    /// Wraps `curve` if [LookupCurve::check_time_warp] passes
+    fn try_from(mut curve: LookupCurve) -> Result<Self, Self::Error> {
+        curve.check_time_warp()?;
+        curve.time_warp = true;
+        Ok(Self(curve))
+    }
+}
+
+impl From<TimeWarpCurve> for LookupCurve {
+    fn from(warp: TimeWarpCurve) -> Self {
+        warp.0
+    }
+}
+
+impl LookupCurve {
+    /// Checks that the curve is a valid [TimeWarpCurve]: it goes from `(0, 0)` to `(1, 1)` within
+    /// [TIME_WARP_EPSILON], and y never decreases.
+    ///
+    /// Uses the exact extrema of cubic segments, so overshoot between knots is detected.
+    pub fn check_time_warp(&self) -> Result<(), TimeWarpError> {
+        let [first, .., last] = &self.knots[..] else {
+            return Err(TimeWarpError::TooFewKnots);
+        };
+        if first.position.distance(Vec2::ZERO) > TIME_WARP_EPSILON {
+            return Err(TimeWarpError::Start {
+                position: first.position,
+            });
+        }
+        if last.position.distance(Vec2::ONE) > TIME_WARP_EPSILON {
+            return Err(TimeWarpError::End {
+                position: last.position,
+            });
+        }
+        match self
+            .knots
+            .windows(2)
+            .position(|pair| decreases(&pair[0], &pair[1]))
+        {
+            Some(segment) => Err(TimeWarpError::Decreasing { segment }),
+            None => Ok(()),
+        }
+    }
+
+    /// Repairs the curve so that [LookupCurve::check_time_warp] passes.
+    ///
+    /// Knots are clamped to `0..=1` on both axes, and y of every knot is raised to at least y of the previous knot.
+    /// The first knot is moved to `(0, 0)` and the last to `(1, 1)`, and knots are added to curves with less than
+    /// two knots. The tangents of cubic segments that still overshoot are flattened until they no longer do, which
+    /// makes them [TangentMode::Free] where the two tangents of a knot get different slopes.
+    ///
+    /// Returns `true` if the curve changed.
+    pub fn enforce_time_warp(&mut self) -> bool {
+        let mut changed = false;
+        if self.knots.len() < 2 {
+            if let Some(knot) = self.knots.first_mut() {
+                knot.position = Vec2::ZERO;
+            } else {
+                self.add_knot(Knot::default());
+            }
+            self.add_knot(Knot {
+                position: Vec2::ONE,
+                ..Default::default()
+            });
+            changed = true;
+        }
+
+        let time = self.edit_time();
+        let last = self.knots.len() - 1;
+        let mut min_y = 0.0;
+        for i in 0..=last {
+            let position = self.knots[i].position;
+            let mut repaired = position.clamp(Vec2::ZERO, Vec2::ONE);
+            repaired.y = repaired.y.max(min_y);
+            if i == 0 {
+                repaired = Vec2::ZERO;
+            } else if i == last {
+                repaired = Vec2::ONE;
+            }
+            min_y = repaired.y;
+            if repaired != position {
+                self.knots[i].position = repaired;
+                self.stamp_modified(i, time);
+                changed = true;
+            }
+        }
+        changed |= self.refresh_auto_tangents();
+
+        for i in 1..=last {
+            let (a, b) = (&self.knots[i - 1], &self.knots[i]);
+            let dx = b.position.x - a.position.x;
+            if a.interpolation != KnotInterpolation::Cubic || dx <= 0.0 || !decreases(a, b) {
+                continue;
+            }
+            // With the y of the bezier control points in order, the segment never decreases
+            let secant = (b.position.y - a.position.y) / dx;
+            let limit = |slope: f32, weight: Option<f32>| {
+                let max = (secant / (2.0 * weight.unwrap_or(UNWEIGHTED))).max(0.0);
+                slope.clamp(0.0, max)
+            };
+            let right = limit(a.right_tangent.slope, a.right_tangent.weight);
+            let left = limit(b.left_tangent.slope, b.left_tangent.weight);
+            self.knots[i - 1].right_tangent.slope = right;
+            self.knots[i].left_tangent.slope = left;
+            for j in [i - 1, i] {
+                keep_slopes(&mut self.knots[j]);
+                self.stamp_modified(j, time);
+            }
+            changed = true;
+        }
+
+        if changed {
+            self.bump_generation();
+            self.check_consistency();
+        }
+        changed
+    }
+}
+
+/// Returns `true` if y decreases within the segment from `a` to `b`
+fn decreases(a: &Knot, b: &Knot) -> bool {
+    segment_ys(a, b)
+        .windows(2)
+        .any(|w| w[1] - w[0] < -TIME_WARP_EPSILON)
+}
+
+/// Changes the tangent modes of a knot with changed slopes, so that they are not computed again or aligned
+fn keep_slopes(knot: &mut Knot) {
+    let split = knot.left_tangent.slope != knot.right_tangent.slope;
+    for tangent in [&mut knot.left_tangent, &mut knot.right_tangent] {
+        if split {
+            tangent.mode = TangentMode::Free;
+        } else if tangent.mode == TangentMode::Auto {
+            tangent.mode = TangentMode::Aligned;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TangentSide;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    fn cubic(x: f32, y: f32, slope: f32) -> Knot {
+        knot(x, y, KnotInterpolation::Cubic).with_tangent_slope(TangentSide::Left, slope)
+    }
+
+    fn fixtures() -> Vec<LookupCurve> {
+        vec![
+            LookupCurve::new(vec![
+                knot(0.0, 0.0, KnotInterpolation::Linear),
+                knot(1.0, 1.0, KnotInterpolation::Linear),
+            ]),
+            // Ease in and out
+            LookupCurve::new(vec![cubic(0.0, 0.0, 0.0), cubic(1.0, 1.0, 0.0)]),
+            // Holds still in the middle
+            LookupCurve::new(vec![
+                cubic(0.0, 0.0, 1.0),
+                knot(0.4, 0.5, KnotInterpolation::Linear),
+                cubic(0.6, 0.5, 0.0),
+                cubic(1.0, 1.0, 2.0),
+            ]),
+            // Jumps ahead
+            LookupCurve::new(vec![
+                knot(0.0, 0.0, KnotInterpolation::Linear),
+                knot(0.5, 0.25, KnotInterpolation::Constant),
+                knot(0.75, 0.8, KnotInterpolation::Linear),
+                knot(1.0, 1.0, KnotInterpolation::Linear),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn validates_time_warps() {
+        for curve in fixtures() {
+            assert_eq!(curve.check_time_warp(), Ok(()));
+            assert!(TimeWarpCurve::try_from(curve).unwrap().curve().time_warp);
+        }
+
+        let decreasing = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(0.5, 0.6, KnotInterpolation::Linear),
+            knot(0.6, 0.59, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(
+            decreasing.check_time_warp(),
+            Err(TimeWarpError::Decreasing { segment: 1 })
+        );
+        // Knots in order, but the cubic segment overshoots between them
+        let overshoot = LookupCurve::new(vec![cubic(0.0, 0.0, 0.0), cubic(1.0, 1.0, 8.0)]);
+        assert_eq!(
+            TimeWarpCurve::try_from(overshoot).unwrap_err(),
+            TimeWarpError::Decreasing { segment: 0 }
+        );
+
+        let start = LookupCurve::new(vec![
+            knot(0.0, 0.1, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ]);
+        assert!(matches!(
+            start.check_time_warp(),
+            Err(TimeWarpError::Start { .. })
+        ));
+        let end = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(2.0, 1.0, KnotInterpolation::Linear),
+        ]);
+        assert!(matches!(
+            end.check_time_warp(),
+            Err(TimeWarpError::End { .. })
+        ));
+        let almost = LookupCurve::new(vec![
+            knot(0.0, 1e-6, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(almost.check_time_warp(), Ok(()));
+        assert_eq!(
+            LookupCurve::new(vec![knot(0.0, 0.0, KnotInterpolation::Linear)]).check_time_warp(),
+            Err(TimeWarpError::TooFewKnots)
+        );
+    }
+
+    #[test]
+    fn enforce_repairs_curves() {
+        for mut curve in fixtures() {
+            let generation = curve.generation();
+            assert!(!curve.enforce_time_warp());
+            assert_eq!(curve.generation(), generation);
+        }
+
+        let broken = [
+            LookupCurve::default(),
+            LookupCurve::new(vec![knot(0.3, 0.7, KnotInterpolation::Cubic)]),
+            LookupCurve::new(vec![
+                knot(-0.5, 0.2, KnotInterpolation::Linear),
+                knot(0.3, 0.6, KnotInterpolation::Cubic),
+                cubic(0.5, 0.2, -3.0),
+                cubic(0.9, 1.4, 6.0),
+                knot(1.5, 1.2, KnotInterpolation::Linear),
+            ]),
+            LookupCurve::new(vec![
+                cubic(0.0, 0.0, 0.0),
+                cubic(0.5, 0.5, 9.0).with_tangent_weight(TangentSide::Right, Some(0.9)),
+                cubic(1.0, 1.0, 0.0),
+            ]),
+        ];
+        for mut curve in broken {
+            assert!(curve.enforce_time_warp());
+            assert_eq!(curve.check_time_warp(), Ok(()));
+            let warp = TimeWarpCurve::try_from(curve).unwrap();
+            let mut previous = 0.0;
+            for i in 0..=200 {
+                let y = warp.warp(i as f32 / 200.0);
+                assert!(y >= previous - TIME_WARP_EPSILON, "{y} < {previous}");
+                previous = y;
+            }
+        }
+
+        // Knots below the previous ones are raised to them
+        let dip = TimeWarpCurve::enforce(LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(0.5, 0.6, KnotInterpolation::Linear),
+            knot(0.7, 0.3, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ]));
+        assert_eq!(dip.curve().knots()[2].position, Vec2::new(0.7, 0.6));
+        assert!(dip.curve().time_warp);
+    }
+
+    #[test]
+    fn inverse_warp_round_trips() {
+        let fixtures = fixtures();
+        for (i, curve) in fixtures.iter().enumerate() {
+            let warp = TimeWarpCurve::try_from(curve.clone()).unwrap();
+            for j in 0..=100 {
+                let t = j as f32 / 100.0;
+                // Strictly increasing warps
+                if i < 2 {
+                    assert!(
+                        (warp.inverse_warp(warp.warp(t)) - t).abs() < 1e-3,
+                        "{i}: {t}"
+                    );
+                }
+                // Warps without jumps
+                if i < 3 {
+                    assert!(
+                        (warp.warp(warp.inverse_warp(t)) - t).abs() < 1e-5,
+                        "{i}: {t}"
+                    );
+                }
+            }
+        }
+
+        let hold = TimeWarpCurve::try_from(fixtures[2].clone()).unwrap();
+        assert!((hold.inverse_warp(0.5) - 0.4).abs() < 1e-5);
+        assert_eq!(hold.inverse_warp(-1.0), 0.0);
+        assert_eq!(hold.inverse_warp(2.0), 1.0);
+        assert_eq!(hold.warp(f32::NAN), 0.0);
+
+        let jump = TimeWarpCurve::try_from(fixtures[3].clone()).unwrap();
+        assert_eq!(jump.inverse_warp(0.5), 0.75);
+        assert!((jump.inverse_warp(0.9) - 0.875).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,264 @@
+use bevy_math::Vec2;
+
+use crate::{next_generation, unique_knot_id, Knot, LookupCurve};
+
+impl LookupCurve {
+    /// Returns the curve with x and y of its knots and anchors multiplied by `x` and `y`, keeping the shape of the
+    /// curve. A negative `x` mirrors the curve, see [LookupCurve::mirrored].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is zero or not finite, as the knots would collapse.
+    pub fn scaled(&self, x: f32, y: f32) -> LookupCurve {
+        self.transformed(Vec2::new(x, y), Vec2::ZERO)
+    }
+
+    /// Returns the curve mirrored across the vertical line at `axis_x`, so that the mirrored curve at `x` has the
+    /// value of the curve at `2 * axis_x - x`.
+    ///
+    /// [KnotInterpolation::Constant](crate::KnotInterpolation::Constant) segments still hold the value of the knot at
+    /// their left end, which was the right end before mirroring. So the mirror of constant segments is not exact.
+    pub fn mirrored(&self, axis_x: f32) -> LookupCurve {
+        self.transformed(Vec2::new(-1.0, 1.0), Vec2::new(2.0 * axis_x, 0.0))
+    }
+
+    /// Returns the curve with its knots and anchors moved by `x` and `y`
+    pub fn translated(&self, x: f32, y: f32) -> LookupCurve {
+        self.transformed(Vec2::ONE, Vec2::new(x, y))
+    }
+
+    /// Returns the curve reversed on the x axis over the range of its knots, [mirrored](LookupCurve::mirrored) across
+    /// the middle of the range, so that the knots cover the same range.
+    pub fn reversed(&self) -> LookupCurve {
+        match self.domain() {
+            Some((min, max)) => self.mirrored((min + max) / 2.0),
+            None => self.mirrored(0.0),
+        }
+    }
+
+    /// Returns the curve followed by `other`, with the knots of `other` moved on the x axis to start `gap` after the
+    /// last knot of the curve. y is kept as is, so the curves are joined by a step if `other` starts at another y
+    /// than the curve ends at and the gap is zero, or otherwise by the segment from the last knot of the curve.
+    ///
+    /// The knots of `other` get new ids and keep their edit times, like in [LookupCurve::splice]. The settings of the
+    /// curve are kept, with [LookupCurve::extrapolate_after] taken from `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `gap` is negative or not finite.
+    pub fn appended(&self, other: &LookupCurve, gap: f32) -> LookupCurve {
+        assert!(
+            gap >= 0.0 && gap.is_finite(),
+            "gap must be finite and not negative, got {gap}"
+        );
+        let mut curve = self.clone();
+        let offset = match (self.knots.last(), other.knots.first()) {
+            (Some(last), Some(first)) => last.position.x + gap - first.position.x,
+            _ => 0.0,
+        };
+        curve.knots.extend(other.knots.iter().map(|knot| Knot {
+            position: knot.position + Vec2::new(offset, 0.0),
+            id: unique_knot_id(),
+            ..*knot
+        }));
+        if !other.knots.is_empty() {
+            curve.extrapolate_after = other.extrapolate_after;
+        }
+        if curve.strict_monotonic_x {
+            curve.enforce_monotonic_x();
+        }
+        curve.refresh_auto_tangents();
+        curve.requantize();
+        curve.generation = next_generation();
+        curve.order_epoch = next_generation();
+        curve.check_consistency();
+        curve
+    }
+
+    /// Maps positions `p` to `p * scale + offset`
+    fn transformed(&self, scale: Vec2, offset: Vec2) -> LookupCurve {
+        assert!(
+            scale.x != 0.0 && scale.x.is_finite(),
+            "x scale must be finite and non-zero, got {}",
+            scale.x
+        );
+        let mut curve = self.clone();
+        let slope_scale = scale.y / scale.x;
+        for knot in curve.knots.iter_mut() {
+            knot.position = knot.position * scale + offset;
+            knot.left_tangent.slope *= slope_scale;
+            knot.right_tangent.slope *= slope_scale;
+        }
+        for anchor in curve.anchors.iter_mut() {
+            *anchor = *anchor * scale + offset;
+        }
+
+        if scale.x < 0.0 {
+            // Restore the order of the knots. The interpolation of a segment is stored in its left knot, which was
+            // the right knot of the segment before. The unused interpolation of the last knot stays with the last knot.
+            let len = curve.knots.len();
+            let interpolations: Vec<_> = curve.knots.iter().map(|k| k.interpolation).collect();
+            curve.knots.reverse();
+            for (i, knot) in curve.knots.iter_mut().enumerate() {
+                std::mem::swap(&mut knot.left_tangent, &mut knot.right_tangent);
+                knot.interpolation = if i + 1 < len {
+                    interpolations[len - 2 - i]
+                } else {
+                    interpolations[len - 1]
+                };
+            }
+            std::mem::swap(&mut curve.extrapolate_before, &mut curve.extrapolate_after);
+        }
+        curve.requantize();
+        curve.generation = next_generation();
+        curve.order_epoch = next_generation();
+        curve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Extrapolation, Knot, KnotInterpolation, TangentSide};
+
+    fn test_curve() -> LookupCurve {
+        let knot = |x: f32, y: f32, interpolation| Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        };
+        let mut curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Cubic)
+                .with_tangent_slope(TangentSide::Right, 3.0)
+                .with_tangent_weight(TangentSide::Right, Some(0.6)),
+            knot(1.0, 1.0, KnotInterpolation::Linear)
+                .with_tangent_slope(TangentSide::Left, -1.0)
+                .with_tangent_weight(TangentSide::Left, Some(0.2)),
+            knot(1.5, 0.5, KnotInterpolation::Cubic),
+            knot(3.0, 2.0, KnotInterpolation::Linear),
+        ]);
+        curve.anchors.push(Vec2::new(1.0, 1.0));
+        curve
+    }
+
+    #[test]
+    fn scaled_keeps_shape() {
+        let curve = test_curve();
+        for (sx, sy) in [(2.0, 3.0), (0.5, -1.0), (-2.0, 0.5)] {
+            let scaled = curve.scaled(sx, sy);
+            for i in 0..=30 {
+                let x = i as f32 / 10.0;
+                let expected = curve.lookup(x) * sy;
+                let actual = scaled.lookup(x * sx);
+                assert!(
+                    (expected - actual).abs() < 1e-3,
+                    "{sx} {sy} {x}: {expected} != {actual}"
+                );
+            }
+            assert_eq!(scaled.anchors[0], Vec2::new(sx, sy));
+            assert_ne!(scaled.generation(), curve.generation());
+        }
+    }
+
+    #[test]
+    fn mirrored_twice_is_the_same() {
+        let curve = test_curve().with_extrapolation(Extrapolation::Linear, Extrapolation::Repeat);
+        let mirrored = curve.mirrored(2.0);
+        let xs: Vec<_> = mirrored.knots().iter().map(|k| k.position.x).collect();
+        assert_eq!(xs, [1.0, 2.5, 3.0, 4.0]);
+        assert_eq!(mirrored.extrapolate_before, Extrapolation::Repeat);
+        assert_eq!(mirrored.extrapolate_after, Extrapolation::Linear);
+        for i in -10..=40 {
+            let x = i as f32 / 10.0;
+            let (expected, actual) = (curve.lookup(x), mirrored.lookup(4.0 - x));
+            assert!(
+                (expected - actual).abs() < 1e-3,
+                "{x}: {expected} != {actual}"
+            );
+        }
+
+        let back = mirrored.mirrored(2.0);
+        for (a, b) in back.knots().iter().zip(curve.knots()) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.position, b.position);
+            assert_eq!(a.interpolation, b.interpolation);
+            assert_eq!(a.left_tangent.weight, b.left_tangent.weight);
+            assert_eq!(a.right_tangent.slope, b.right_tangent.slope);
+        }
+    }
+
+    #[test]
+    fn translated_and_reversed_sample_as_expected() {
+        let curve = test_curve();
+        let translated = curve.translated(0.5, -2.0);
+        let reversed = curve.reversed();
+        assert_eq!(reversed.domain(), curve.domain());
+        for i in -10..=40 {
+            let x = i as f32 / 10.0;
+            let expected = curve.lookup(x);
+            for (name, actual) in [
+                ("translated", translated.lookup(x + 0.5) + 2.0),
+                ("reversed", reversed.lookup(3.0 - x)),
+            ] {
+                assert!(
+                    (expected - actual).abs() < 1e-3,
+                    "{name} {x}: {expected} != {actual}"
+                );
+            }
+        }
+        assert_eq!(translated.anchors[0], Vec2::new(1.5, -1.0));
+        assert_eq!(
+            LookupCurve::linear(Vec2::ZERO, Vec2::ONE)
+                .reversed()
+                .lookup(0.25),
+            0.75
+        );
+        assert!(LookupCurve::default().reversed().knots().is_empty());
+    }
+
+    #[test]
+    fn appended_curves_follow_each_other() {
+        let rise = LookupCurve::linear(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0))
+            .with_extrapolation(Extrapolation::Linear, Extrapolation::Clamp);
+        let fall = LookupCurve::ease_in_out()
+            .scaled(1.0, -1.0)
+            .translated(0.0, 1.0)
+            .with_extrapolation(Extrapolation::Clamp, Extrapolation::Linear);
+        let curve = rise.appended(&fall, 0.5);
+        let xs: Vec<f32> = curve.knots().iter().map(|k| k.position.x).collect();
+        assert_eq!(xs, [0.0, 1.0, 1.5, 2.5]);
+        assert_eq!(curve.extrapolate_before, Extrapolation::Linear);
+        assert_eq!(curve.extrapolate_after, Extrapolation::Linear);
+        for i in -5..=30 {
+            let x = i as f32 / 10.0;
+            let expected = match x {
+                x if x <= 1.0 => x,
+                x if x <= 1.5 => 1.0,
+                x => {
+                    let t = (x - 1.5).min(1.0);
+                    1.0 - (3.0 * t * t - 2.0 * t * t * t)
+                }
+            };
+            let actual = curve.lookup(x);
+            assert!(
+                (expected - actual).abs() < 1e-5,
+                "{x}: {expected} != {actual}"
+            );
+        }
+
+        // Appending a curve to itself keeps the ids unique
+        let twice = rise.appended(&rise, 0.0);
+        let mut ids: Vec<usize> = twice.knots().iter().map(|knot| knot.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 4);
+        assert_eq!(twice.lookup(1.5), 0.5);
+        assert_eq!(LookupCurve::default().appended(&rise, 1.0).lookup(0.5), 0.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_x_scale_panics() {
+        test_curve().scaled(0.0, 1.0);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+/// How values on one axis of a [LookupCurve](crate::LookupCurve) are displayed in the editor.
+///
+/// This is authoring metadata only, lookups always use the stored values.
+/// Displayed values are the stored values multiplied by `scale`, for example storing seconds
+/// but displaying milliseconds using a `scale` of 1000.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct UnitDisplay {
+    /// Unit shown after displayed values, e.g. `"ms"`
+    pub unit: String,
+    /// Multiplier from stored value to displayed value
+    pub scale: f32,
+    /// Number of decimals shown
+    pub precision: usize,
+}
+
+impl Default for UnitDisplay {
+    fn default() -> Self {
+        Self {
+            unit: String::new(),
+            scale: 1.0,
+            precision: 3,
+        }
+    }
+}
+
+/// Error returned by [UnitDisplay::parse]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnitParseError {
+    /// The text was not a valid number, optionally followed by the unit
+    InvalidNumber(String),
+    /// The number was NaN or infinite
+    NotFinite,
+    /// The scale of the [UnitDisplay] is zero or not finite, so displayed values can not be converted back
+    InvalidScale,
+}
+
+impl fmt::Display for UnitParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitParseError::InvalidNumber(text) => write!(f, "Invalid number: {text:?}"),
+            UnitParseError::NotFinite => write!(f, "Number must be finite"),
+            UnitParseError::InvalidScale => write!(f, "Unit scale must be finite and non-zero"),
+        }
+    }
+}
+
+impl std::error::Error for UnitParseError {}
+
+impl UnitDisplay {
+    pub fn new<S: Into<String>>(unit: S) -> Self {
+        Self {
+            unit: unit.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Consumes the unit display and returns it with scale set
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Consumes the unit display and returns it with precision set
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Converts a stored value to its displayed value
+    #[inline]
+    pub fn to_display(&self, value: f32) -> f32 {
+        value * self.scale
+    }
+
+    /// Converts a displayed value to its stored value
+    #[inline]
+    pub fn from_display(&self, value: f32) -> f32 {
+        value / self.scale
+    }
+
+    /// Formats a stored value for display, with the unit appended
+    pub fn format(&self, value: f32) -> String {
+        let precision = self.precision;
+        let value = self.to_display(value);
+        if self.unit.is_empty() {
+            format!("{value:.precision$}")
+        } else {
+            format!("{value:.precision$} {}", self.unit)
+        }
+    }
+
+    /// Parses displayed text back into a stored value. The unit is optional in the text.
+    pub fn parse(&self, text: &str) -> Result<f32, UnitParseError> {
+        if self.scale == 0.0 || !self.scale.is_finite() {
+            return Err(UnitParseError::InvalidScale);
+        }
+
+        let trimmed = text.trim();
+        let number = if self.unit.is_empty() {
+            trimmed
+        } else {
+            trimmed.strip_suffix(self.unit.as_str()).unwrap_or(trimmed)
+        };
+
+        let value = number
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| UnitParseError::InvalidNumber(text.to_string()))?;
+        if !value.is_finite() {
+            return Err(UnitParseError::NotFinite);
+        }
+
+        Ok(self.from_display(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_with_precision_and_unit() {
+        let ms = UnitDisplay::new("ms").with_scale(1000.0).with_precision(1);
+        assert_eq!(ms.format(0.25), "250.0 ms");
+        assert_eq!(UnitDisplay::default().format(0.123456), "0.123");
+        assert_eq!(UnitDisplay::new("%").with_precision(0).format(12.6), "13 %");
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        let ms = UnitDisplay::new("ms").with_scale(1000.0).with_precision(3);
+        for value in [0.0, 0.25, -1.5, 12.125] {
+            assert_eq!(ms.parse(&ms.format(value)).unwrap(), value);
+        }
+        assert_eq!(ms.parse("500").unwrap(), 0.5);
+        assert_eq!(ms.parse(" 500ms ").unwrap(), 0.5);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let ms = UnitDisplay::new("ms").with_scale(1000.0);
+        assert!(matches!(
+            ms.parse(""),
+            Err(UnitParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            ms.parse("abc"),
+            Err(UnitParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            ms.parse("5 kg"),
+            Err(UnitParseError::InvalidNumber(_))
+        ));
+        assert_eq!(ms.parse("inf"), Err(UnitParseError::NotFinite));
+        assert_eq!(ms.parse("NaN ms"), Err(UnitParseError::NotFinite));
+        assert_eq!(
+            UnitDisplay::new("s").with_scale(0.0).parse("1"),
+            Err(UnitParseError::InvalidScale)
+        );
+    }
+}
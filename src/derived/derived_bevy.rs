@@ -0,0 +1,235 @@
+use std::marker::PhantomData;
+use std::time::Instant;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{AssetId, Assets};
+use bevy_ecs::prelude::{IntoSystemConfigs, Res, ResMut, Resource};
+use bevy_tasks::{block_on, AsyncComputeTaskPool, Task};
+
+use super::{Derived, DerivedCurveData, DerivedStore, PendingRebuild, RebakePolicy};
+use crate::link::update_linked_curves;
+use crate::LookupCurve;
+
+/// Keeps data of type `T` for every curve asset in [CurveDerivedData], rebuilding it after the curve changes as
+/// allowed by the [RebakePolicy]. Add one plugin per type of data.
+pub struct CurveDerivedDataPlugin<T> {
+    pub policy: RebakePolicy,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> CurveDerivedDataPlugin<T> {
+    pub fn new(policy: RebakePolicy) -> Self {
+        Self {
+            policy,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for CurveDerivedDataPlugin<T> {
+    fn default() -> Self {
+        Self::new(RebakePolicy::default())
+    }
+}
+
+impl<T: DerivedCurveData> Plugin for CurveDerivedDataPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CurveDerivedData::<T>::new(self.policy))
+            .add_systems(
+                PostUpdate,
+                rebake_derived_data::<T>.after(update_linked_curves),
+            );
+    }
+}
+
+/// Data of type `T` derived from the curve assets, see [CurveDerivedDataPlugin]
+#[derive(Resource)]
+pub struct CurveDerivedData<T: DerivedCurveData> {
+    store: DerivedStore<AssetId<LookupCurve>, T>,
+    /// Rebuilds in flight with [RebakePolicy::Async], with the generation of the curve they build from
+    tasks: Vec<(AssetId<LookupCurve>, u64, Task<T>)>,
+}
+
+impl<T: DerivedCurveData> CurveDerivedData<T> {
+    pub fn new(policy: RebakePolicy) -> Self {
+        Self {
+            store: DerivedStore::new(policy),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// The data of `curve`, `None` until it was first built
+    pub fn get(&self, curve: impl Into<AssetId<LookupCurve>>) -> Option<Derived<'_, T>> {
+        self.store.get(&curve.into())
+    }
+
+    pub fn policy(&self) -> RebakePolicy {
+        self.store.policy
+    }
+
+    /// Changes the policy. Rebuilds in flight are completed.
+    pub fn set_policy(&mut self, policy: RebakePolicy) {
+        self.store.policy = policy;
+    }
+
+    /// Number of times data was built, including rebuilds still in flight
+    pub fn rebuilds(&self) -> u64 {
+        self.store.rebuilds()
+    }
+}
+
+/// Rebuilds stale data in [CurveDerivedData], and stores the data of completed background rebuilds.
+///
+/// Runs in [PostUpdate] after [update_linked_curves], so that derived curves are up to date.
+pub fn rebake_derived_data<T: DerivedCurveData>(
+    mut data: ResMut<CurveDerivedData<T>>,
+    curves: Res<Assets<LookupCurve>>,
+) {
+    let CurveDerivedData { store, tasks } = &mut *data;
+    // Completed rebuilds first, so that their data is served from this frame on
+    tasks.retain_mut(|(id, generation, task)| {
+        if !task.is_finished() {
+            return true;
+        }
+        store.complete(id, block_on(task), *generation);
+        false
+    });
+
+    let pending = store.update(curves.iter(), Instant::now());
+    if pending.is_empty() {
+        return;
+    }
+    let pool = AsyncComputeTaskPool::get();
+    for PendingRebuild { key, curve } in pending {
+        let generation = curve.generation();
+        tasks.push((
+            key,
+            generation,
+            pool.spawn(async move { T::derive(&curve) }),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy::prelude::{AssetApp, AssetPlugin, Handle, MinimalPlugins};
+    use bevy_math::Vec2;
+    use std::time::Duration;
+
+    /// Number of knots of the curve
+    #[derive(Debug, PartialEq)]
+    struct KnotCount(usize);
+
+    impl DerivedCurveData for KnotCount {
+        fn derive(curve: &LookupCurve) -> Self {
+            KnotCount(curve.knots().len())
+        }
+    }
+
+    fn test_app(policy: RebakePolicy) -> (App, Handle<LookupCurve>) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<LookupCurve>()
+            .add_plugins(CurveDerivedDataPlugin::<KnotCount>::new(policy));
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<LookupCurve>>()
+            .add(LookupCurve::default());
+        (app, handle)
+    }
+
+    fn add_knot(app: &mut App, handle: &Handle<LookupCurve>) {
+        let mut curves = app.world_mut().resource_mut::<Assets<LookupCurve>>();
+        let curve = curves.get_mut(handle).unwrap();
+        let x = curve.knots().len() as f32;
+        curve.add_knot(Knot {
+            position: Vec2::new(x, x),
+            ..Default::default()
+        });
+    }
+
+    fn derived(app: &App, handle: &Handle<LookupCurve>) -> Option<(usize, u64, bool)> {
+        let derived = app.world().resource::<CurveDerivedData<KnotCount>>();
+        derived
+            .get(handle)
+            .map(|derived| (derived.data.0, derived.generation, derived.stale))
+    }
+
+    fn generation(app: &App, handle: &Handle<LookupCurve>) -> u64 {
+        let curves = app.world().resource::<Assets<LookupCurve>>();
+        curves.get(handle).unwrap().generation()
+    }
+
+    #[test]
+    fn dragged_curves_are_rebuilt_a_bounded_number_of_times() {
+        let (mut app, handle) = test_app(RebakePolicy::Budgeted {
+            frame_budget: Duration::from_secs(1),
+            interval: Duration::from_secs(3600),
+        });
+        app.update();
+        assert_eq!(
+            derived(&app, &handle),
+            Some((0, generation(&app, &handle), false))
+        );
+
+        for _ in 0..20 {
+            add_knot(&mut app, &handle);
+            app.update();
+            let (knots, _, stale) = derived(&app, &handle).unwrap();
+            assert_eq!(knots, 0);
+            assert!(stale);
+        }
+        let data = app.world().resource::<CurveDerivedData<KnotCount>>();
+        assert_eq!(data.rebuilds(), 1);
+
+        app.update();
+        assert_eq!(
+            derived(&app, &handle),
+            Some((20, generation(&app, &handle), false))
+        );
+        assert_eq!(
+            app.world()
+                .resource::<CurveDerivedData<KnotCount>>()
+                .rebuilds(),
+            2
+        );
+    }
+
+    #[test]
+    fn async_rebuilds_replace_stale_data_once_complete() {
+        let (mut app, handle) = test_app(RebakePolicy::Async {
+            interval: Duration::ZERO,
+        });
+        let mut served = Vec::new();
+        for _ in 0..10 {
+            add_knot(&mut app, &handle);
+            app.update();
+            served.extend(derived(&app, &handle));
+        }
+
+        // Wait for the last rebuild to complete
+        let latest = generation(&app, &handle);
+        for _ in 0..1000 {
+            app.update();
+            served.extend(derived(&app, &handle));
+            if served
+                .last()
+                .is_some_and(|&(_, generation, _)| generation == latest)
+            {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(served.last(), Some(&(10, latest, false)));
+
+        // Once newer data is served, older data never is again
+        assert!(
+            served.windows(2).all(|pair| pair[0].1 <= pair[1].1),
+            "{served:?}"
+        );
+        app.update();
+        assert_eq!(derived(&app, &handle), Some((10, latest, false)));
+    }
+}
@@ -0,0 +1,331 @@
+//! Data derived from curves, like lookup tables or textures, rebuilt when the curve changes without rebuilding in
+//! every frame of a drag. See [DerivedStore], and `CurveDerivedDataPlugin` for curve assets.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "fixed")]
+use crate::fixed::FixedLookupCurve;
+use crate::stats::CurveStats;
+use crate::LookupCurve;
+
+#[cfg(feature = "bevy_asset")]
+mod derived_bevy;
+#[cfg(feature = "bevy_asset")]
+pub use derived_bevy::*;
+
+/// Data built from a curve, kept by a [DerivedStore]
+pub trait DerivedCurveData: Send + Sync + 'static {
+    fn derive(curve: &LookupCurve) -> Self;
+}
+
+impl DerivedCurveData for CurveStats {
+    fn derive(curve: &LookupCurve) -> Self {
+        CurveStats::compute(curve)
+    }
+}
+
+#[cfg(feature = "fixed")]
+impl DerivedCurveData for FixedLookupCurve {
+    fn derive(curve: &LookupCurve) -> Self {
+        curve.to_fixed_curve()
+    }
+}
+
+/// When a [DerivedStore] rebuilds data of changed curves.
+///
+/// While a curve keeps changing, like during a drag, the previous data is served until the next rebuild and is
+/// reported as stale. Once the curve stops changing its data is rebuilt right away.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum RebakePolicy {
+    /// Rebuild in every update the curve changed in
+    Immediate,
+    /// Rebuild each curve at most once per `interval` while it keeps changing. Stops starting rebuilds in an update
+    /// once they took `frame_budget`, continuing with the remaining curves in the next update.
+    Budgeted {
+        frame_budget: Duration,
+        interval: Duration,
+    },
+    /// Rebuild on a background thread, at most once per `interval` while the curve keeps changing. Each curve has
+    /// at most one rebuild in flight, its result is used once it completes.
+    Async { interval: Duration },
+}
+
+impl Default for RebakePolicy {
+    fn default() -> Self {
+        RebakePolicy::Budgeted {
+            frame_budget: Duration::from_millis(2),
+            interval: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RebakePolicy {
+    fn interval(&self) -> Duration {
+        match *self {
+            RebakePolicy::Immediate => Duration::ZERO,
+            RebakePolicy::Budgeted { interval, .. } | RebakePolicy::Async { interval } => interval,
+        }
+    }
+}
+
+/// Data of a curve returned by [DerivedStore::get]
+#[derive(Debug)]
+pub struct Derived<'a, T> {
+    pub data: &'a T,
+    /// [LookupCurve::generation] of the curve the data was built from
+    pub generation: u64,
+    /// Whether the curve changed since the data was built
+    pub stale: bool,
+}
+
+#[derive(Debug)]
+struct Entry<T> {
+    data: Option<(T, u64)>,
+    /// Generation of the curve in the last update
+    current: u64,
+    last_rebuild: Option<Instant>,
+    in_flight: bool,
+}
+
+impl<T> Entry<T> {
+    fn is_stale(&self) -> bool {
+        self.data
+            .as_ref()
+            .is_none_or(|(_, generation)| *generation != self.current)
+    }
+}
+
+/// A rebuild started by [DerivedStore::update] with [RebakePolicy::Async], to be passed to
+/// [DerivedStore::complete] with the built data
+#[derive(Debug)]
+pub struct PendingRebuild<K> {
+    pub key: K,
+    pub curve: LookupCurve,
+}
+
+/// Data derived from curves by key, rebuilt according to a [RebakePolicy]
+#[derive(Debug)]
+pub struct DerivedStore<K, T> {
+    pub policy: RebakePolicy,
+    entries: HashMap<K, Entry<T>>,
+    rebuilds: u64,
+}
+
+impl<K, T> Default for DerivedStore<K, T> {
+    fn default() -> Self {
+        Self::new(RebakePolicy::default())
+    }
+}
+
+impl<K, T> DerivedStore<K, T> {
+    pub fn new(policy: RebakePolicy) -> Self {
+        Self {
+            policy,
+            entries: HashMap::new(),
+            rebuilds: 0,
+        }
+    }
+
+    /// Number of times data was built, including rebuilds still in flight
+    pub fn rebuilds(&self) -> u64 {
+        self.rebuilds
+    }
+}
+
+impl<K: Hash + Eq + Clone, T: DerivedCurveData> DerivedStore<K, T> {
+    /// The data of the curve with `key`, `None` if it was not built yet
+    pub fn get(&self, key: &K) -> Option<Derived<'_, T>> {
+        let entry = self.entries.get(key)?;
+        let (data, generation) = entry.data.as_ref()?;
+        Some(Derived {
+            data,
+            generation: *generation,
+            stale: *generation != entry.current,
+        })
+    }
+
+    /// Rebuilds stale data of `curves` as allowed by the policy. Call once per update with all curves to keep
+    /// data for, the data of other curves is dropped. With [RebakePolicy::Async] nothing is built, instead the
+    /// returned rebuilds should be built and passed to [DerivedStore::complete].
+    pub fn update<'a>(
+        &mut self,
+        curves: impl IntoIterator<Item = (K, &'a LookupCurve)>,
+        now: Instant,
+    ) -> Vec<PendingRebuild<K>> {
+        let interval = self.policy.interval();
+        let mut seen = HashSet::new();
+        let mut due = Vec::new();
+        for (key, curve) in curves {
+            seen.insert(key.clone());
+            let entry = self.entries.entry(key.clone()).or_insert(Entry {
+                data: None,
+                current: curve.generation(),
+                last_rebuild: None,
+                in_flight: false,
+            });
+            let changing = entry.current != curve.generation();
+            entry.current = curve.generation();
+            let allowed = !changing
+                || entry
+                    .last_rebuild
+                    .is_none_or(|last| now.saturating_duration_since(last) >= interval);
+            if entry.is_stale() && !entry.in_flight && allowed {
+                due.push((entry.last_rebuild, key, curve));
+            }
+        }
+        self.entries.retain(|key, _| seen.contains(key));
+        // Curves waiting the longest first, so that a tight budget does not starve any curve
+        due.sort_by_key(|(last_rebuild, _, _)| *last_rebuild);
+
+        let started = Instant::now();
+        let mut pending = Vec::new();
+        for (n, (_, key, curve)) in due.into_iter().enumerate() {
+            // The first rebuild is always done, so that a budget smaller than one rebuild still makes progress
+            if let RebakePolicy::Budgeted { frame_budget, .. } = self.policy {
+                if n > 0 && started.elapsed() >= frame_budget {
+                    break;
+                }
+            }
+            let entry = self.entries.get_mut(&key).unwrap();
+            entry.last_rebuild = Some(now);
+            self.rebuilds += 1;
+            if let RebakePolicy::Async { .. } = self.policy {
+                entry.in_flight = true;
+                pending.push(PendingRebuild {
+                    key,
+                    curve: curve.clone(),
+                });
+            } else {
+                entry.data = Some((T::derive(curve), curve.generation()));
+            }
+        }
+        pending
+    }
+
+    /// Stores data built for a [PendingRebuild], from the curve with `generation`. The data is dropped if the stored
+    /// data is already up to date, or if the curve is no longer passed to [DerivedStore::update].
+    pub fn complete(&mut self, key: &K, data: T, generation: u64) {
+        let Some(entry) = self.entries.get_mut(key) else {
+            return;
+        };
+        entry.in_flight = false;
+        // Generations are not ordered when a curve is replaced, for example by undo, so only fresh data is kept
+        if entry.is_stale() || generation == entry.current {
+            entry.data = Some((data, generation));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy_math::Vec2;
+
+    /// Number of knots of the curve
+    #[derive(Debug, PartialEq)]
+    struct KnotCount(usize);
+
+    impl DerivedCurveData for KnotCount {
+        fn derive(curve: &LookupCurve) -> Self {
+            KnotCount(curve.knots().len())
+        }
+    }
+
+    fn add_knot(curve: &mut LookupCurve) {
+        let x = curve.knots().len() as f32;
+        curve.add_knot(Knot {
+            position: Vec2::new(x, x),
+            ..Default::default()
+        });
+    }
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn changing_curves_are_rebuilt_once_per_interval() {
+        let mut store = DerivedStore::<u32, KnotCount>::new(RebakePolicy::Budgeted {
+            frame_budget: HOUR,
+            interval: HOUR,
+        });
+        let mut curve = LookupCurve::default();
+        let now = Instant::now();
+        store.update([(0, &curve)], now);
+        assert_eq!(store.rebuilds(), 1);
+
+        for _ in 0..10 {
+            add_knot(&mut curve);
+            store.update([(0, &curve)], now);
+            let derived = store.get(&0).unwrap();
+            assert!(derived.stale);
+            assert_eq!(derived.data, &KnotCount(0));
+        }
+        assert_eq!(store.rebuilds(), 1);
+
+        // Rebuilt as soon as the curve stops changing, or once the interval passed
+        store.update([(0, &curve)], now);
+        let derived = store.get(&0).unwrap();
+        assert!(!derived.stale);
+        assert_eq!(derived.data, &KnotCount(10));
+        assert_eq!(derived.generation, curve.generation());
+        add_knot(&mut curve);
+        store.update([(0, &curve)], now + HOUR);
+        assert!(!store.get(&0).unwrap().stale);
+        assert_eq!(store.rebuilds(), 3);
+    }
+
+    #[test]
+    fn budget_spreads_rebuilds_over_updates() {
+        let mut store = DerivedStore::<u32, KnotCount>::new(RebakePolicy::Budgeted {
+            frame_budget: Duration::ZERO,
+            interval: Duration::ZERO,
+        });
+        let curves: Vec<LookupCurve> = (0..3).map(|_| LookupCurve::default()).collect();
+        let now = Instant::now();
+        for built in 1..=3 {
+            store.update(curves.iter().enumerate().map(|(i, c)| (i as u32, c)), now);
+            assert_eq!(store.rebuilds(), built);
+        }
+        assert!((0..3).all(|i| !store.get(&i).unwrap().stale));
+
+        // Curves dropped from the update lose their data
+        store.update([(1, &curves[1])], now);
+        assert!(store.get(&0).is_none());
+        assert!(store.get(&1).is_some());
+    }
+
+    #[test]
+    fn async_rebuilds_serve_the_previous_data_until_complete() {
+        let mut store = DerivedStore::<u32, KnotCount>::new(RebakePolicy::Async {
+            interval: Duration::ZERO,
+        });
+        let mut curve = LookupCurve::default();
+        let now = Instant::now();
+        let first = store.update([(0, &curve)], now);
+        assert_eq!(first.len(), 1);
+        assert!(store.get(&0).is_none());
+
+        // One rebuild in flight at a time
+        add_knot(&mut curve);
+        assert!(store.update([(0, &curve)], now).is_empty());
+        let old_generation = first[0].curve.generation();
+        store.complete(&0, KnotCount::derive(&first[0].curve), old_generation);
+        let derived = store.get(&0).unwrap();
+        assert!(derived.stale);
+        assert_eq!(derived.data, &KnotCount(0));
+
+        let second = store.update([(0, &curve)], now);
+        assert_eq!(second.len(), 1);
+        store.complete(&0, KnotCount::derive(&curve), curve.generation());
+        assert!(!store.get(&0).unwrap().stale);
+
+        // Data of an older curve never replaces up to date data
+        store.complete(&0, KnotCount(0), old_generation);
+        assert_eq!(store.get(&0).unwrap().data, &KnotCount(1));
+        store.complete(&7, KnotCount(0), 0);
+        assert!(store.get(&7).is_none());
+    }
+}
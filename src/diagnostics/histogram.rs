@@ -0,0 +1,303 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Default number of buckets of a [SampleHistogram]
+pub const DEFAULT_BUCKETS: usize = 64;
+
+/// Fixed-resolution histogram of sampled x values over the domain of a curve.
+///
+/// Recording only takes `&self` and increments an atomic counter, so it can be called from parallel systems
+/// without locking. Samples before or after the domain are counted separately, see [HistogramSnapshot::below]
+/// and [HistogramSnapshot::above].
+#[derive(Debug)]
+pub struct SampleHistogram {
+    domain: (f32, f32),
+    buckets: Box<[AtomicU64]>,
+    below: AtomicU64,
+    above: AtomicU64,
+    /// The samples were cleared by [SampleHistogram::sync_domain] since the last [SampleHistogram::clear]
+    domain_reset: bool,
+}
+
+/// What [SampleHistogram::sync_domain] did with the recorded samples
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DomainChange {
+    Unchanged,
+    /// Moved the recorded samples to the buckets of the new domain
+    Rebucketed,
+    /// Cleared the recorded samples, as samples outside the old domain could be inside the new domain
+    Reset,
+}
+
+/// Counts of a [SampleHistogram] at one point in time
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistogramSnapshot {
+    pub domain: (f32, f32),
+    /// Samples within the domain, by equally wide buckets
+    pub buckets: Vec<u64>,
+    /// Samples before the start of the domain
+    pub below: u64,
+    /// Samples after the end of the domain
+    pub above: u64,
+    /// Samples were discarded after the domain changed, see [DomainChange::Reset]
+    pub domain_reset: bool,
+}
+
+impl HistogramSnapshot {
+    /// Total number of samples, including samples outside of the domain
+    pub fn total(&self) -> u64 {
+        self.buckets.iter().sum::<u64>() + self.below + self.above
+    }
+
+    /// Highest count of any bucket, including the counts outside of the domain. Useful for normalizing.
+    pub fn max(&self) -> u64 {
+        self.buckets
+            .iter()
+            .copied()
+            .chain([self.below, self.above])
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Range of x covered by the bucket at `index`
+    pub fn bucket_range(&self, index: usize) -> (f32, f32) {
+        let (start, end) = self.domain;
+        let width = (end - start) / self.buckets.len() as f32;
+        (
+            start + width * index as f32,
+            start + width * (index + 1) as f32,
+        )
+    }
+}
+
+impl SampleHistogram {
+    /// Constructs an empty histogram over `domain` with `buckets` buckets, at least one
+    pub fn new(domain: (f32, f32), buckets: usize) -> Self {
+        Self {
+            domain,
+            buckets: (0..buckets.max(1)).map(|_| AtomicU64::new(0)).collect(),
+            below: AtomicU64::new(0),
+            above: AtomicU64::new(0),
+            domain_reset: false,
+        }
+    }
+
+    pub fn domain(&self) -> (f32, f32) {
+        self.domain
+    }
+
+    /// Number of buckets within the domain
+    pub fn resolution(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Counts a sample at `x`. NaN is ignored.
+    #[inline]
+    pub fn record(&self, x: f32) {
+        let counter = match self.bucket_index(x) {
+            Bucket::Below => &self.below,
+            Bucket::Above => &self.above,
+            Bucket::Inside(i) => &self.buckets[i],
+            Bucket::None => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn bucket_index(&self, x: f32) -> Bucket {
+        let (start, end) = self.domain;
+        if x.is_nan() {
+            Bucket::None
+        } else if x < start {
+            Bucket::Below
+        } else if x > end {
+            Bucket::Above
+        } else if end <= start {
+            Bucket::Inside(0)
+        } else {
+            let n = self.buckets.len();
+            let i = ((x - start) / (end - start) * n as f32) as usize;
+            // x at the end of the domain is in the last bucket
+            Bucket::Inside(i.min(n - 1))
+        }
+    }
+
+    /// Returns the current counts
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            domain: self.domain,
+            buckets: self
+                .buckets
+                .iter()
+                .map(|count| count.load(Ordering::Relaxed))
+                .collect(),
+            below: self.below.load(Ordering::Relaxed),
+            above: self.above.load(Ordering::Relaxed),
+            domain_reset: self.domain_reset,
+        }
+    }
+
+    /// Returns the current counts and resets them. Samples recorded concurrently are either in the returned
+    /// snapshot or remain in the histogram, none are lost.
+    pub fn drain(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            domain: self.domain,
+            buckets: self
+                .buckets
+                .iter()
+                .map(|count| count.swap(0, Ordering::Relaxed))
+                .collect(),
+            below: self.below.swap(0, Ordering::Relaxed),
+            above: self.above.swap(0, Ordering::Relaxed),
+            domain_reset: self.domain_reset,
+        }
+    }
+
+    /// Resets the counts, and the notice that samples were discarded after a domain change
+    pub fn clear(&mut self) {
+        for count in self
+            .buckets
+            .iter_mut()
+            .chain([&mut self.below, &mut self.above])
+        {
+            *count.get_mut() = 0;
+        }
+        self.domain_reset = false;
+    }
+
+    /// Changes the domain of the histogram, for example after the knots of the curve were moved.
+    ///
+    /// Samples within the old domain are moved to the bucket containing the center of their old bucket.
+    /// If there are samples outside of the old domain, and the domain grew on that side, their position within
+    /// the new domain is unknown, so all samples are cleared instead.
+    pub fn sync_domain(&mut self, domain: (f32, f32)) -> DomainChange {
+        if domain == self.domain {
+            return DomainChange::Unchanged;
+        }
+        let old = self.snapshot();
+        let (old_start, old_end) = self.domain;
+        let grew_below = domain.0 < old_start && old.below > 0;
+        let grew_above = domain.1 > old_end && old.above > 0;
+
+        let mut rebucketed = SampleHistogram::new(domain, self.buckets.len());
+        if grew_below || grew_above {
+            rebucketed.domain_reset = true;
+            *self = rebucketed;
+            return DomainChange::Reset;
+        }
+        rebucketed.domain_reset = self.domain_reset;
+        for (i, count) in old.buckets.iter().enumerate() {
+            let (start, end) = old.bucket_range(i);
+            let counter = match rebucketed.bucket_index((start + end) / 2.0) {
+                Bucket::Below => &mut rebucketed.below,
+                Bucket::Above => &mut rebucketed.above,
+                Bucket::Inside(i) => &mut rebucketed.buckets[i],
+                Bucket::None => continue,
+            };
+            *counter.get_mut() += count;
+        }
+        *rebucketed.below.get_mut() += old.below;
+        *rebucketed.above.get_mut() += old.above;
+        *self = rebucketed;
+        DomainChange::Rebucketed
+    }
+}
+
+enum Bucket {
+    Below,
+    Above,
+    Inside(usize),
+    None,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_cover_domain() {
+        let histogram = SampleHistogram::new((0.0, 1.0), 4);
+        for x in [0.0, 0.1, 0.25, 0.6, 0.99, 1.0, -0.1, 1.5, 2.0, f32::NAN] {
+            histogram.record(x);
+        }
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets, [2, 1, 1, 2]);
+        assert_eq!((snapshot.below, snapshot.above), (1, 2));
+        assert_eq!(snapshot.total(), 9);
+        assert_eq!(snapshot.max(), 2);
+        assert_eq!(snapshot.bucket_range(1), (0.25, 0.5));
+    }
+
+    #[test]
+    fn zero_width_domain_uses_single_bucket() {
+        let histogram = SampleHistogram::new((2.0, 2.0), 8);
+        for x in [1.0, 2.0, 2.0, 3.0] {
+            histogram.record(x);
+        }
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets[0], 2);
+        assert_eq!(snapshot.total(), 4);
+    }
+
+    #[test]
+    fn shrinking_domain_rebuckets() {
+        let mut histogram = SampleHistogram::new((0.0, 4.0), 4);
+        for x in [-1.0, 0.5, 1.5, 1.5, 2.5, 3.5, 5.0] {
+            histogram.record(x);
+        }
+        assert_eq!(histogram.sync_domain((0.0, 4.0)), DomainChange::Unchanged);
+        assert_eq!(histogram.sync_domain((1.0, 3.0)), DomainChange::Rebucketed);
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.domain, (1.0, 3.0));
+        assert_eq!(snapshot.buckets, [0, 2, 0, 1]);
+        assert_eq!((snapshot.below, snapshot.above), (2, 2));
+        assert_eq!(snapshot.total(), 7);
+    }
+
+    #[test]
+    fn growing_domain_resets_if_samples_were_outside() {
+        let mut histogram = SampleHistogram::new((0.0, 1.0), 4);
+        histogram.record(0.5);
+        assert_eq!(histogram.sync_domain((-1.0, 2.0)), DomainChange::Rebucketed);
+        assert_eq!(histogram.snapshot().total(), 1);
+        assert!(!histogram.snapshot().domain_reset);
+
+        histogram.record(2.5);
+        assert_eq!(histogram.sync_domain((-1.0, 3.0)), DomainChange::Reset);
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.total(), 0);
+        assert_eq!(snapshot.domain, (-1.0, 3.0));
+        assert_eq!(snapshot.buckets.len(), 4);
+        assert!(snapshot.domain_reset);
+
+        histogram.record(0.0);
+        histogram.sync_domain((-1.0, 2.0));
+        assert!(histogram.snapshot().domain_reset);
+        histogram.clear();
+        assert!(!histogram.snapshot().domain_reset);
+    }
+
+    #[test]
+    fn concurrent_recording_counts_every_sample() {
+        const THREADS: usize = 8;
+        const SAMPLES: usize = 10_000;
+        let histogram = SampleHistogram::new((0.0, 1.0), DEFAULT_BUCKETS);
+        let mut drained = 0;
+        std::thread::scope(|scope| {
+            for thread in 0..THREADS {
+                let histogram = &histogram;
+                scope.spawn(move || {
+                    for i in 0..SAMPLES {
+                        // Also outside of the domain
+                        histogram.record((i + thread) as f32 / SAMPLES as f32 * 1.2 - 0.1);
+                    }
+                });
+            }
+            // Draining while recording must not lose samples
+            for _ in 0..100 {
+                drained += histogram.drain().total();
+            }
+        });
+        drained += histogram.drain().total();
+        assert_eq!(drained, (THREADS * SAMPLES) as u64);
+        assert_eq!(histogram.snapshot().total(), 0);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+
+use bevy_app::{App, Last, Plugin};
+use bevy_asset::{AssetId, Assets};
+use bevy_ecs::prelude::{Res, ResMut, Resource};
+
+use crate::LookupCurve;
+
+mod histogram;
+pub use histogram::{DomainChange, HistogramSnapshot, SampleHistogram, DEFAULT_BUCKETS};
+
+pub(crate) struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurveSampleHistogram>()
+            .add_systems(Last, collect_curve_samples);
+    }
+}
+
+/// Histograms of where curve assets are sampled at runtime, shown as a heat overlay in the editor.
+///
+/// Game systems call [CurveSampleHistogram::record] through `Res<CurveSampleHistogram>`, so recording does not
+/// prevent systems from running in parallel. Samples of curves that already have a histogram are counted
+/// with an atomic increment, samples of new curves are queued until [collect_curve_samples] runs in [Last].
+///
+/// Histograms follow changes to the domain of their curve, see [SampleHistogram::sync_domain].
+#[derive(Resource)]
+pub struct CurveSampleHistogram {
+    /// Number of buckets of new histograms
+    pub resolution: usize,
+    histograms: HashMap<AssetId<LookupCurve>, SampleHistogram>,
+    pending_sender: Sender<(AssetId<LookupCurve>, f32)>,
+    pending: Mutex<Receiver<(AssetId<LookupCurve>, f32)>>,
+}
+
+impl Default for CurveSampleHistogram {
+    fn default() -> Self {
+        let (pending_sender, pending) = channel();
+        Self {
+            resolution: DEFAULT_BUCKETS,
+            histograms: HashMap::new(),
+            pending_sender,
+            pending: Mutex::new(pending),
+        }
+    }
+}
+
+impl CurveSampleHistogram {
+    /// Counts a sample of `curve` at `x`
+    pub fn record(&self, curve: impl Into<AssetId<LookupCurve>>, x: f32) {
+        let id = curve.into();
+        match self.histograms.get(&id) {
+            Some(histogram) => histogram.record(x),
+            None => {
+                // The receiver is owned by self, so sending can not fail
+                let _ = self.pending_sender.send((id, x));
+            }
+        }
+    }
+
+    /// Returns the counts for `curve`, `None` if it has not been sampled
+    pub fn snapshot(&self, curve: impl Into<AssetId<LookupCurve>>) -> Option<HistogramSnapshot> {
+        self.histograms
+            .get(&curve.into())
+            .map(SampleHistogram::snapshot)
+    }
+
+    /// Returns the histogram for `curve`, `None` if it has not been sampled
+    pub fn get(&self, curve: impl Into<AssetId<LookupCurve>>) -> Option<&SampleHistogram> {
+        self.histograms.get(&curve.into())
+    }
+
+    /// Resets the counts for `curve`
+    pub fn clear(&mut self, curve: impl Into<AssetId<LookupCurve>>) {
+        if let Some(histogram) = self.histograms.get_mut(&curve.into()) {
+            histogram.clear();
+        }
+    }
+
+    /// Follows domain changes of the curves, removes histograms of removed curves, and counts queued samples
+    pub fn collect(&mut self, curves: &Assets<LookupCurve>) {
+        self.histograms
+            .retain(|id, histogram| match curves.get(*id) {
+                Some(curve) => {
+                    histogram.sync_domain(curve_domain(curve));
+                    true
+                }
+                None => false,
+            });
+
+        let pending = self.pending.get_mut().unwrap_or_else(|e| e.into_inner());
+        for (id, x) in pending.try_iter() {
+            let Some(curve) = curves.get(id) else {
+                continue;
+            };
+            self.histograms
+                .entry(id)
+                .or_insert_with(|| SampleHistogram::new(curve_domain(curve), self.resolution))
+                .record(x);
+        }
+    }
+}
+
+/// x of the first and last knot
+fn curve_domain(curve: &LookupCurve) -> (f32, f32) {
+    match (curve.knots().first(), curve.knots().last()) {
+        (Some(first), Some(last)) => (first.position.x, last.position.x),
+        _ => (0.0, 0.0),
+    }
+}
+
+/// Runs [CurveSampleHistogram::collect], added to [Last] by the `LookupCurvePlugin`
+pub fn collect_curve_samples(
+    mut histogram: ResMut<CurveSampleHistogram>,
+    curves: Res<Assets<LookupCurve>>,
+) {
+    histogram.collect(&curves);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy::prelude::{AssetApp, AssetPlugin, Handle, MinimalPlugins, Update};
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_math::Vec2;
+
+    fn test_app() -> (App, Handle<LookupCurve>) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), DiagnosticsPlugin))
+            .init_asset::<LookupCurve>();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<LookupCurve>>()
+            .add(LookupCurve::new(vec![
+                Knot::default(),
+                Knot {
+                    position: Vec2::new(1.0, 1.0),
+                    ..Default::default()
+                },
+            ]));
+        (app, handle)
+    }
+
+    #[test]
+    fn records_from_systems() {
+        let (mut app, handle) = test_app();
+        let id = handle.id();
+        app.add_systems(Update, move |histogram: Res<CurveSampleHistogram>| {
+            for x in [-1.0, 0.1, 0.5, 2.0] {
+                histogram.record(id, x);
+            }
+        });
+
+        app.update();
+        let snapshot = app
+            .world()
+            .resource::<CurveSampleHistogram>()
+            .snapshot(&handle)
+            .unwrap();
+        assert_eq!(snapshot.total(), 4);
+        assert_eq!((snapshot.below, snapshot.above), (1, 1));
+
+        app.update();
+        let snapshot = app
+            .world()
+            .resource::<CurveSampleHistogram>()
+            .snapshot(&handle)
+            .unwrap();
+        assert_eq!(snapshot.total(), 8);
+    }
+
+    #[test]
+    fn follows_domain_changes_and_removed_curves() {
+        let (mut app, handle) = test_app();
+        let id = handle.id();
+        app.world_mut()
+            .run_system_once(move |histogram: Res<CurveSampleHistogram>| histogram.record(id, 0.9));
+        app.update();
+
+        {
+            let mut curves = app.world_mut().resource_mut::<Assets<LookupCurve>>();
+            let curve = curves.get_mut(&handle).unwrap();
+            let knot = curve.knots()[1];
+            curve.modify_knot(
+                1,
+                Knot {
+                    position: Vec2::new(2.0, 1.0),
+                    ..knot
+                },
+            );
+        }
+        app.update();
+        let snapshot = app
+            .world()
+            .resource::<CurveSampleHistogram>()
+            .snapshot(&handle)
+            .unwrap();
+        assert_eq!(snapshot.domain, (0.0, 2.0));
+        assert_eq!(snapshot.total(), 1);
+
+        drop(handle);
+        app.update();
+        app.update();
+        assert!(app
+            .world()
+            .resource::<CurveSampleHistogram>()
+            .snapshot(id)
+            .is_none());
+    }
+}
@@ -0,0 +1,1625 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use bevy_math::Vec2;
+
+use super::clipboard::{KnotClipboard, PastePlacement};
+use super::edit_scope::{clamp_to_scope, in_scope};
+use super::guides::GuideAxis;
+use super::multi_edit::KnotEdit;
+use super::LookupCurveEguiEditor;
+#[cfg(feature = "ron")]
+use crate::LookupCurveSaveError;
+use crate::{
+    Extrapolation, Knot, KnotInterpolation, LookupCurve, MergeY, NotMonotonic, SmoothPolicy,
+    StretchMode, TangentMode, TangentSide,
+};
+
+/// Tolerance used by [EditorAction::InvertCurve], relative to the width of the curve
+const INVERT_TOLERANCE: f32 = 1e-3;
+
+/// An edit of a curve, or of the state of a [LookupCurveEguiEditor].
+///
+/// All pointer interaction with the plot and the controls of the editor is performed as actions, so everything
+/// the editor can do can also be scripted, see [LookupCurveEguiEditor::apply_action] and
+/// [LookupCurveEguiEditor::pending_actions]. The playback controls of [LookupCurveEguiEditor::scrub_ui] act on the
+/// supplied [ScrubPlayback](crate::scrub::ScrubPlayback) instead, and are not actions.
+///
+/// Knots are referred to by [Knot::id], as the index of a knot changes when it is moved past another knot.
+#[derive(Clone, Debug)]
+pub enum EditorAction {
+    /// Adds a knot at `position`. x is clamped to the edit scope, and the position is rounded following
+    /// [LookupCurveEguiEditor::round_new_positions_to].
+    AddKnot {
+        position: Vec2,
+        interpolation: KnotInterpolation,
+    },
+    /// Moves a knot to `to`, with x clamped to the edit scope.
+    ///
+    /// The position is not rounded, so that dragged knots follow the pointer smoothly.
+    MoveKnot {
+        id: usize,
+        to: Vec2,
+    },
+    /// Moves a knot to `to`, stretching the knots between `pivot` and it proportionally on the x axis. `pivot` is a
+    /// knot id, `None` for the first knot. See [LookupCurve::stretch_from_knot].
+    ///
+    /// x is clamped to the edit scope, and all stretched knots must be within the scope.
+    StretchKnot {
+        id: usize,
+        to: Vec2,
+        pivot: Option<usize>,
+    },
+    /// Moves the knots with `ids` by `delta`. `delta.x` is limited so that all knots stay within the edit scope.
+    ///
+    /// Knots moved past knots that are not moved are reordered, keeping the curve sorted by x. If any of the knots
+    /// can not be edited, none of them are moved.
+    MoveKnots {
+        ids: Vec<usize>,
+        delta: Vec2,
+    },
+    DeleteKnot {
+        id: usize,
+    },
+    /// Deletes the knots with `ids` and deselects them. If any of the knots can not be edited, none of them are
+    /// deleted.
+    DeleteKnots {
+        ids: Vec<usize>,
+    },
+    SetInterpolation {
+        id: usize,
+        interpolation: KnotInterpolation,
+    },
+    /// Sets the slope of a tangent, which also sets the other tangent if both are [TangentMode::Aligned] or
+    /// [TangentMode::Auto]. Auto tangents become aligned, see [Knot::with_tangent_slope](crate::Knot::with_tangent_slope).
+    SetTangentSlope {
+        id: usize,
+        side: TangentSide,
+        slope: f32,
+    },
+    /// Sets the weight of a tangent, `None` for an unweighted tangent. Weights are clamped between 0 and 1.
+    SetTangentWeight {
+        id: usize,
+        side: TangentSide,
+        weight: Option<f32>,
+    },
+    SetTangentMode {
+        id: usize,
+        side: TangentSide,
+        mode: TangentMode,
+    },
+    /// Replaces the curve by its inverse and fits the view to it, see [LookupCurve::inverted]
+    InvertCurve,
+    /// See [LookupCurve::enforce_anchors]
+    EnforceAnchors,
+    /// Sets [LookupCurve::extrapolate_before] and [LookupCurve::extrapolate_after]
+    SetExtrapolation {
+        before: Extrapolation,
+        after: Extrapolation,
+    },
+    /// Sets [LookupCurveEguiEditor::offset] and [LookupCurveEguiEditor::scale]. Views without a positive, finite
+    /// size are ignored.
+    SetView {
+        offset: Vec2,
+        scale: Vec2,
+    },
+    /// Zooms the view by `factor` on each axis, keeping `center` in curve space at the same place on screen. Factors
+    /// below 1 zoom in. Factors that are not positive and finite are ignored.
+    ZoomView {
+        center: Vec2,
+        factor: Vec2,
+    },
+    /// See [LookupCurveEguiEditor::fit_to_curve]
+    FitToCurve,
+    /// Sets [LookupCurveEguiEditor::edit_scope]. The bounds are swapped if the range is reversed.
+    SetEditScope(Option<RangeInclusive<f32>>),
+    /// Saves the curve to [LookupCurveEguiEditor::ron_path]
+    #[cfg(feature = "ron")]
+    Save,
+    /// Sets [LookupCurveEguiEditor::show_knot_age]
+    ShowKnotAge(bool),
+    /// Inserts a knot on the curve at `x` without changing its shape, see [LookupCurve::insert_knot_at]. x is
+    /// clamped to the edit scope, and the position is not rounded.
+    InsertKnotAt {
+        x: f32,
+    },
+    /// Adds a guide to [LookupCurveEguiEditor::guides]
+    AddGuide {
+        axis: GuideAxis,
+        value: f32,
+    },
+    MoveGuide {
+        id: usize,
+        value: f32,
+    },
+    RemoveGuide {
+        id: usize,
+    },
+    /// Sets [LookupCurveEguiEditor::snap_to_guides]
+    SnapToGuides(bool),
+    /// Sets [LookupCurveEguiEditor::snap_to_grid]
+    SnapToGrid(bool),
+    /// Applies `edit` to each of the knots with `ids`. If any of the knots can not be edited, none of them are.
+    EditKnots {
+        ids: Vec<usize>,
+        edit: KnotEdit,
+    },
+    /// Sets [LookupCurveEguiEditor::selected_knots]
+    SetSelection(Vec<usize>),
+    /// Selects the knots within the edit scope inside the rectangle with corners `from` and `to`. With `extend`
+    /// they are added to the selection, otherwise they replace it.
+    SelectInRect {
+        from: Vec2,
+        to: Vec2,
+        extend: bool,
+    },
+    /// Adds the knots of `clipboard` at `placement`, with new ids, and selects them. All pasted knots must be within
+    /// the edit scope.
+    PasteKnots {
+        clipboard: KnotClipboard,
+        placement: PastePlacement,
+    },
+    /// Merges knots that coincide within `tolerance`, see [LookupCurve::merge_coincident_knots]. The editor uses the
+    /// hit radius of knots at the current zoom. Removed knots are deselected.
+    MergeCoincidentKnots {
+        tolerance: Vec2,
+        merge_y: MergeY,
+    },
+    /// Sets [LookupCurveEguiEditor::show_smoothness]
+    ShowSmoothness(bool),
+    /// Equalizes the slopes on both sides of a knot, see [LookupCurve::smooth_knot]
+    SmoothKnot {
+        id: usize,
+        policy: SmoothPolicy,
+    },
+    /// Smooths every knot where the slope jumps by more than `slope_tolerance`, see
+    /// [LookupCurve::smooth_all_knots]. The editor uses [LookupCurveEguiEditor::smoothness_tolerance].
+    SmoothAllKnots {
+        slope_tolerance: f32,
+        policy: SmoothPolicy,
+    },
+    /// Sets [LookupCurveEguiEditor::fit_error_in_view]
+    FitErrorInView(bool),
+    /// Replaces the knots by knots fitted to [LookupCurveEguiEditor::reference_fn] within `tolerance`, over the
+    /// domain of the curve, or the view if the curve has no width. See [LookupCurve::fitted_to].
+    FitToReference {
+        tolerance: f32,
+    },
+    /// Undoes the last edit, see [LookupCurveEguiEditor::history]
+    Undo,
+    /// Redoes the last undone edit, see [LookupCurveEguiEditor::history]
+    Redo,
+    /// Requests removing the link deriving the curve, see [LookupCurveEguiEditor::take_detach_link]
+    DetachLink,
+    /// Sets [LookupCurveEguiEditor::show_sample_heat]
+    #[cfg(feature = "diagnostics")]
+    ShowSampleHeat(bool),
+    /// Requests clearing the recorded samples, see [LookupCurveEguiEditor::take_clear_sample_heat]
+    #[cfg(feature = "diagnostics")]
+    ClearSampleHeat,
+}
+
+impl EditorAction {
+    /// Returns `true` for actions that modify the curve
+    pub fn edits_curve(&self) -> bool {
+        matches!(
+            self,
+            EditorAction::AddKnot { .. }
+                | EditorAction::MoveKnot { .. }
+                | EditorAction::StretchKnot { .. }
+                | EditorAction::MoveKnots { .. }
+                | EditorAction::DeleteKnot { .. }
+                | EditorAction::DeleteKnots { .. }
+                | EditorAction::SetInterpolation { .. }
+                | EditorAction::SetTangentSlope { .. }
+                | EditorAction::SetTangentWeight { .. }
+                | EditorAction::SetTangentMode { .. }
+                | EditorAction::InvertCurve
+                | EditorAction::EnforceAnchors
+                | EditorAction::SetExtrapolation { .. }
+                | EditorAction::InsertKnotAt { .. }
+                | EditorAction::EditKnots { .. }
+                | EditorAction::PasteKnots { .. }
+                | EditorAction::MergeCoincidentKnots { .. }
+                | EditorAction::SmoothKnot { .. }
+                | EditorAction::SmoothAllKnots { .. }
+                | EditorAction::FitToReference { .. }
+                | EditorAction::Undo
+                | EditorAction::Redo
+        )
+    }
+}
+
+/// What an [EditorAction] changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionOutcome {
+    /// Nothing changed, for example a knot was moved to where it already is
+    Unchanged,
+    CurveChanged,
+    /// A knot with this id was added to the curve
+    KnotAdded(usize),
+    /// Only the state of the editor changed, like the view or the edit scope
+    EditorChanged,
+    #[cfg(feature = "ron")]
+    Saved,
+}
+
+impl ActionOutcome {
+    /// Returns `true` if the curve was modified
+    pub fn curve_changed(&self) -> bool {
+        matches!(
+            self,
+            ActionOutcome::CurveChanged | ActionOutcome::KnotAdded(_)
+        )
+    }
+}
+
+/// Error returned by [LookupCurveEguiEditor::apply_action], the curve and editor are left unchanged
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ActionError {
+    /// The curve has no knot with this id
+    UnknownKnot(usize),
+    /// The knot with this id is outside of [LookupCurveEguiEditor::edit_scope], so it can not be edited
+    OutOfScope(usize),
+    /// The curve can not be inverted
+    NotInvertible(NotMonotonic),
+    /// The editor has no guide with this id
+    UnknownGuide(usize),
+    /// The edit would move the knot with this id to a position that is not finite
+    NotFinite(usize),
+    /// The curve is generated from another curve, see [LookupCurveEguiEditor::set_derived]
+    DerivedCurve,
+    /// [EditorAction::PasteKnots] would place knots outside of [LookupCurveEguiEditor::edit_scope]
+    PasteOutOfScope,
+    /// [EditorAction::FitToReference] without [LookupCurveEguiEditor::reference_fn]
+    NoReference,
+    /// The reference function is not finite everywhere in the fitted range
+    ReferenceNotFinite,
+    /// [EditorAction::Save] without [LookupCurveEguiEditor::ron_path]
+    #[cfg(feature = "ron")]
+    NoSavePath,
+    #[cfg(feature = "ron")]
+    Save(LookupCurveSaveError),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::UnknownKnot(id) => write!(f, "No knot with id {id}"),
+            ActionError::OutOfScope(id) => write!(f, "Knot {id} is outside of the edit scope"),
+            ActionError::NotInvertible(e) => write!(f, "Curve can not be inverted: {e}"),
+            ActionError::UnknownGuide(id) => write!(f, "No guide with id {id}"),
+            ActionError::DerivedCurve => {
+                write!(
+                    f,
+                    "Curve is generated from another curve, detach the link to edit it"
+                )
+            }
+            ActionError::NotFinite(id) => {
+                write!(f, "Knot {id} would be moved to a non-finite position")
+            }
+            ActionError::PasteOutOfScope => {
+                write!(f, "Pasted knots would be outside of the edit scope")
+            }
+            ActionError::NoReference => write!(f, "No reference function to fit the curve to"),
+            ActionError::ReferenceNotFinite => {
+                write!(
+                    f,
+                    "Reference function is not finite everywhere in the domain"
+                )
+            }
+            #[cfg(feature = "ron")]
+            ActionError::NoSavePath => write!(f, "No path to save the curve to"),
+            #[cfg(feature = "ron")]
+            ActionError::Save(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ActionError::NotInvertible(e) => Some(e),
+            #[cfg(feature = "ron")]
+            ActionError::Save(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl LookupCurveEguiEditor {
+    /// Applies `action` to `curve` and the editor, the same way as the interaction it corresponds to.
+    ///
+    /// Knots outside of [Self::edit_scope] can not be edited, like in the UI. On curves with
+    /// [LookupCurve::time_warp] every edit is followed by [LookupCurve::enforce_time_warp], and on curves with
+    /// [LookupCurve::quantize_storage] by [LookupCurve::requantize], except while dragging in the UI. Edits of the
+    /// curve are recorded in [Self::history].
+    pub fn apply_action(
+        &mut self,
+        action: EditorAction,
+        curve: &mut LookupCurve,
+    ) -> Result<ActionOutcome, ActionError> {
+        if self.derived.is_some() && action.edits_curve() {
+            return Err(ActionError::DerivedCurve);
+        }
+        let record =
+            action.edits_curve() && !matches!(action, EditorAction::Undo | EditorAction::Redo);
+        let before = (record && self.history.needs_snapshot()).then(|| curve.clone());
+        let outcome = self.apply(action, curve)?;
+        // Edits of time warps are repaired right away, which also keeps the end knots in place
+        if curve.time_warp && outcome.curve_changed() {
+            curve.enforce_time_warp();
+        }
+        if outcome.curve_changed() && !self.defer_quantize {
+            curve.requantize();
+        }
+        if record && outcome.curve_changed() {
+            self.history.record(before);
+        }
+        Ok(outcome)
+    }
+
+    fn apply(
+        &mut self,
+        action: EditorAction,
+        curve: &mut LookupCurve,
+    ) -> Result<ActionOutcome, ActionError> {
+        match action {
+            EditorAction::AddKnot {
+                position,
+                interpolation,
+            } => {
+                let x = clamp_to_scope(self.edit_scope.as_ref(), position.x);
+                let knot = self.new_knot(curve, Vec2::new(x, position.y), interpolation);
+                let i = curve.add_knot(knot);
+                Ok(ActionOutcome::KnotAdded(curve.knots()[i].id))
+            }
+            EditorAction::MoveKnot { id, to } => {
+                let (i, knot) = self.editable_knot(curve, id)?;
+                let to = Vec2::new(clamp_to_scope(self.edit_scope.as_ref(), to.x), to.y);
+                if to == knot.position {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                curve.modify_knot(
+                    i,
+                    Knot {
+                        position: to,
+                        ..knot
+                    },
+                );
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::StretchKnot { id, to, pivot } => {
+                let (i, _) = self.editable_knot(curve, id)?;
+                let p = match pivot {
+                    Some(pivot) => self.editable_knot(curve, pivot)?.0,
+                    None => 0,
+                };
+                if let Some(knot) = curve.knots()[i.min(p)..=i.max(p)]
+                    .iter()
+                    .find(|knot| !in_scope(self.edit_scope.as_ref(), knot.position.x))
+                {
+                    return Err(ActionError::OutOfScope(knot.id));
+                }
+                let x = clamp_to_scope(self.edit_scope.as_ref(), to.x);
+                let stretched = curve.stretch_from_knot(id, x, StretchMode::Proportional { pivot });
+                // Stretching keeps the order of the knots
+                let knot = curve.knots()[i];
+                if to.y == knot.position.y {
+                    return Ok(if stretched {
+                        ActionOutcome::CurveChanged
+                    } else {
+                        ActionOutcome::Unchanged
+                    });
+                }
+                let position = Vec2::new(knot.position.x, to.y);
+                curve.modify_knot(i, Knot { position, ..knot });
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::MoveKnots { ids, delta } => {
+                let knots = ids
+                    .iter()
+                    .map(|&id| Ok(self.editable_knot(curve, id)?.1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut delta = delta;
+                if let Some(scope) = &self.edit_scope {
+                    let xs = knots.iter().map(|knot| knot.position.x);
+                    let min_x = xs.clone().fold(f32::INFINITY, f32::min);
+                    let max_x = xs.fold(f32::NEG_INFINITY, f32::max);
+                    delta.x = delta.x.min(scope.end() - max_x).max(scope.start() - min_x);
+                }
+                if knots.is_empty() || delta == Vec2::ZERO {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                if let Some(knot) = knots
+                    .iter()
+                    .find(|knot| !(knot.position + delta).is_finite())
+                {
+                    return Err(ActionError::NotFinite(knot.id));
+                }
+                // Move the knots in the direction of the move first, so that the moved knots keep their order
+                let mut knots = knots;
+                knots.sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+                if delta.x > 0.0 {
+                    knots.reverse();
+                }
+                for knot in knots {
+                    let position = knot.position + delta;
+                    curve.modify_knot_by_id(knot.id, Knot { position, ..knot });
+                }
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::DeleteKnot { id } => {
+                let (i, _) = self.editable_knot(curve, id)?;
+                curve.delete_knot(i);
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::DeleteKnots { ids } => {
+                for &id in &ids {
+                    self.editable_knot(curve, id)?;
+                }
+                if ids.is_empty() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                for &id in &ids {
+                    // Ids listed twice are already deleted
+                    curve.delete_knot_by_id(id);
+                }
+                self.selected_knots.retain(|id| !ids.contains(id));
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::SetInterpolation { id, interpolation } => {
+                self.edit_knot(curve, id, |knot| Knot {
+                    interpolation,
+                    ..knot
+                })
+            }
+            EditorAction::SetTangentSlope { id, side, slope } => {
+                self.edit_knot(curve, id, |knot| knot.with_tangent_slope(side, slope))
+            }
+            EditorAction::SetTangentWeight { id, side, weight } => {
+                self.edit_knot(curve, id, |knot| knot.with_tangent_weight(side, weight))
+            }
+            EditorAction::SetTangentMode { id, side, mode } => {
+                self.edit_knot(curve, id, |knot| knot.with_tangent_mode(side, mode))
+            }
+            EditorAction::InvertCurve => {
+                let width = match (curve.knots().first(), curve.knots().last()) {
+                    (Some(first), Some(last)) => last.position.x - first.position.x,
+                    _ => 0.0,
+                };
+                let tolerance = INVERT_TOLERANCE * width.max(f32::EPSILON);
+                *curve = curve
+                    .inverted(tolerance)
+                    .map_err(ActionError::NotInvertible)?;
+                self.fit_to_curve(curve);
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::EnforceAnchors => {
+                if curve.validate_anchors().is_empty() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                curve.enforce_anchors();
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::SetExtrapolation { before, after } => {
+                if (curve.extrapolate_before, curve.extrapolate_after) == (before, after) {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                curve.extrapolate_before = before;
+                curve.extrapolate_after = after;
+                curve.bump_generation();
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::SetView { offset, scale } => {
+                if !offset.is_finite() || !scale.is_finite() || scale.cmple(Vec2::ZERO).any() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                self.offset = offset;
+                self.scale = scale;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::ZoomView { center, factor } => {
+                if !factor.is_finite() || factor.cmple(Vec2::ZERO).any() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                self.apply(
+                    EditorAction::SetView {
+                        offset: center - (center - self.offset) * factor,
+                        scale: self.scale * factor,
+                    },
+                    curve,
+                )
+            }
+            EditorAction::FitToCurve => {
+                self.fit_to_curve(curve);
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::SetEditScope(scope) => {
+                self.edit_scope = scope.map(|scope| {
+                    let (start, end) = (*scope.start(), *scope.end());
+                    start.min(end)..=start.max(end)
+                });
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::ShowKnotAge(show) => {
+                self.show_knot_age = show;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::InsertKnotAt { x } => {
+                let x = clamp_to_scope(self.edit_scope.as_ref(), x);
+                let id = self
+                    .id_strategy
+                    .assign(Vec2::new(x, curve.lookup(x)), curve.knots());
+                match curve.insert_knot_with_id(x, id) {
+                    Some(i) if curve.knots()[i].id == id => Ok(ActionOutcome::KnotAdded(id)),
+                    // Outside of the knot range, or there already is a knot at x
+                    _ => Ok(ActionOutcome::Unchanged),
+                }
+            }
+            EditorAction::AddGuide { axis, value } => {
+                self.guides.add(axis, value);
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::MoveGuide { id, value } => {
+                if !self.guides.move_to(id, value) {
+                    return Err(ActionError::UnknownGuide(id));
+                }
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::RemoveGuide { id } => {
+                self.guides
+                    .remove(id)
+                    .ok_or(ActionError::UnknownGuide(id))?;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::SnapToGrid(snap) => {
+                self.snap_to_grid = snap;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::SnapToGuides(snap) => {
+                self.snap_to_guides = snap;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::EditKnots { ids, edit } => {
+                // Edit all knots before modifying the curve, so that it is left unchanged on errors
+                let edited = ids
+                    .iter()
+                    .map(|&id| {
+                        let (_, knot) = self.editable_knot(curve, id)?;
+                        let edited = edit.apply(knot);
+                        if !edited.position.is_finite() {
+                            return Err(ActionError::NotFinite(id));
+                        }
+                        Ok(edited)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                for knot in edited {
+                    let (i, _) = self.editable_knot(curve, knot.id)?;
+                    curve.modify_knot(i, knot);
+                }
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::SetSelection(ids) => {
+                self.selected_knots = ids;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::SelectInRect { from, to, extend } => {
+                let (min, max) = (from.min(to), from.max(to));
+                let inside = curve.knots().iter().filter(|knot| {
+                    knot.position.cmpge(min).all()
+                        && knot.position.cmple(max).all()
+                        && in_scope(self.edit_scope.as_ref(), knot.position.x)
+                });
+                if !extend {
+                    self.selected_knots.clear();
+                }
+                for knot in inside {
+                    if !self.selected_knots.contains(&knot.id) {
+                        self.selected_knots.push(knot.id);
+                    }
+                }
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::PasteKnots {
+                clipboard,
+                placement,
+            } => {
+                let domain = curve
+                    .knots()
+                    .first()
+                    .zip(curve.knots().last())
+                    .map(|(first, last)| (first.position.x, last.position.x));
+                let placed = clipboard.place(placement, domain);
+                if placed.is_empty() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                if let Some(knot) = placed.iter().find(|knot| !knot.position.is_finite()) {
+                    return Err(ActionError::NotFinite(knot.id));
+                }
+                if placed
+                    .iter()
+                    .any(|knot| !in_scope(self.edit_scope.as_ref(), knot.position.x))
+                {
+                    return Err(ActionError::PasteOutOfScope);
+                }
+                self.selected_knots = placed
+                    .into_iter()
+                    .map(|knot| {
+                        let id = self.id_strategy.assign(knot.position, curve.knots());
+                        curve.add_knot(Knot {
+                            id,
+                            created_at: None,
+                            modified_at: None,
+                            ..knot
+                        });
+                        id
+                    })
+                    .collect();
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::MergeCoincidentKnots { tolerance, merge_y } => {
+                let groups = curve.coincident_knots(tolerance);
+                if groups.is_empty() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                for &i in groups.iter().flatten() {
+                    let knot = &curve.knots()[i];
+                    if !in_scope(self.edit_scope.as_ref(), knot.position.x) {
+                        return Err(ActionError::OutOfScope(knot.id));
+                    }
+                }
+                let removed = curve.merge_knots(&groups, merge_y);
+                self.selected_knots.retain(|id| !removed.contains(id));
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::ShowSmoothness(show) => {
+                self.show_smoothness = show;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::SmoothKnot { id, policy } => {
+                self.editable_knot(curve, id)?;
+                if !curve.smooth_knot(id, policy) {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::SmoothAllKnots {
+                slope_tolerance,
+                policy,
+            } => {
+                for (id, _, _) in curve.c1_discontinuities(slope_tolerance) {
+                    self.editable_knot(curve, id)?;
+                }
+                if curve.smooth_all_knots(slope_tolerance, policy).is_empty() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::FitErrorInView(in_view) => {
+                self.fit_error_in_view = in_view;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            EditorAction::FitToReference { tolerance } => {
+                let reference = self.reference_fn.as_ref().ok_or(ActionError::NoReference)?;
+                if let Some(knot) = curve
+                    .knots()
+                    .iter()
+                    .find(|knot| !in_scope(self.edit_scope.as_ref(), knot.position.x))
+                {
+                    return Err(ActionError::OutOfScope(knot.id));
+                }
+                let (min_x, max_x) = self.fit_domain(curve);
+                *curve = curve
+                    .fitted_to(reference, min_x, max_x, tolerance)
+                    .ok_or(ActionError::ReferenceNotFinite)?;
+                Ok(ActionOutcome::CurveChanged)
+            }
+            EditorAction::Undo => Ok(if self.history.undo(curve) {
+                ActionOutcome::CurveChanged
+            } else {
+                ActionOutcome::Unchanged
+            }),
+            EditorAction::Redo => Ok(if self.history.redo(curve) {
+                ActionOutcome::CurveChanged
+            } else {
+                ActionOutcome::Unchanged
+            }),
+            EditorAction::DetachLink => {
+                if self.derived.is_none() {
+                    return Ok(ActionOutcome::Unchanged);
+                }
+                self.detach_link = true;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            #[cfg(feature = "ron")]
+            EditorAction::Save => {
+                let path = self.ron_path.as_deref().ok_or(ActionError::NoSavePath)?;
+                self.save(curve, path).map_err(ActionError::Save)?;
+                self.mark_saved(curve);
+                self.saved_in_update = true;
+                Ok(ActionOutcome::Saved)
+            }
+            #[cfg(feature = "diagnostics")]
+            EditorAction::ShowSampleHeat(show) => {
+                self.show_sample_heat = show;
+                Ok(ActionOutcome::EditorChanged)
+            }
+            #[cfg(feature = "diagnostics")]
+            EditorAction::ClearSampleHeat => {
+                self.clear_sample_heat = true;
+                Ok(ActionOutcome::EditorChanged)
+            }
+        }
+    }
+
+    /// Applies `actions` in order, showing errors in the editor. Returns `true` if the curve was changed.
+    pub(crate) fn apply_actions(
+        &mut self,
+        curve: &mut LookupCurve,
+        actions: impl IntoIterator<Item = EditorAction>,
+    ) -> bool {
+        let mut changed = false;
+        for action in actions {
+            match self.apply_action(action, curve) {
+                Ok(outcome) => changed |= outcome.curve_changed(),
+                Err(e) => self.show_error(e.to_string()),
+            }
+        }
+        changed
+    }
+
+    /// Returns the index of the knot with `id`, if it can be edited
+    fn editable_knot(&self, curve: &LookupCurve, id: usize) -> Result<(usize, Knot), ActionError> {
+        let (i, knot) = curve
+            .knots()
+            .iter()
+            .enumerate()
+            .find(|(_, knot)| knot.id == id)
+            .ok_or(ActionError::UnknownKnot(id))?;
+        if !in_scope(self.edit_scope.as_ref(), knot.position.x) {
+            return Err(ActionError::OutOfScope(id));
+        }
+        Ok((i, *knot))
+    }
+
+    fn edit_knot(
+        &self,
+        curve: &mut LookupCurve,
+        id: usize,
+        modify: impl FnOnce(Knot) -> Knot,
+    ) -> Result<ActionOutcome, ActionError> {
+        let (i, knot) = self.editable_knot(curve, id)?;
+        curve.modify_knot(i, modify(knot));
+        Ok(ActionOutcome::CurveChanged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::RelativeValue;
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Linear,
+            ..Default::default()
+        }
+    }
+
+    fn ids(curve: &LookupCurve) -> Vec<usize> {
+        curve.knots().iter().map(|knot| knot.id).collect()
+    }
+
+    #[test]
+    fn curves_are_built_from_empty_and_emptied_through_actions() {
+        use crate::editor::editor_egui::new_knot_interpolation;
+
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::default();
+        let add = |editor: &mut LookupCurveEguiEditor, curve: &mut LookupCurve, x, y| {
+            let position = Vec2::new(x, y);
+            let interpolation = new_knot_interpolation(curve, x);
+            match editor.apply_action(
+                EditorAction::AddKnot {
+                    position,
+                    interpolation,
+                },
+                curve,
+            ) {
+                Ok(ActionOutcome::KnotAdded(id)) => id,
+                outcome => panic!("expected an added knot, got {outcome:?}"),
+            }
+        };
+
+        // The first knot, then extending it to the left and filling in between
+        let first = add(&mut editor, &mut curve, 1.0, 1.0);
+        editor
+            .apply_action(EditorAction::FitToCurve, &mut curve)
+            .unwrap();
+        assert!(editor.scale.cmpgt(Vec2::ZERO).all());
+        let edit = KnotEdit::Interpolation(KnotInterpolation::Cubic);
+        editor
+            .apply_action(
+                EditorAction::EditKnots {
+                    ids: vec![first],
+                    edit,
+                },
+                &mut curve,
+            )
+            .unwrap();
+        let second = add(&mut editor, &mut curve, 0.0, 0.0);
+        let third = add(&mut editor, &mut curve, 0.5, 0.8);
+        assert_eq!(ids(&curve), [second, third, first]);
+        // The extension continues the cubic segment of the first knot
+        assert!(curve
+            .knots()
+            .iter()
+            .all(|knot| knot.interpolation == KnotInterpolation::Cubic));
+        editor
+            .apply_action(EditorAction::FitToCurve, &mut curve)
+            .unwrap();
+        assert_eq!(
+            (editor.offset, editor.scale),
+            (Vec2::splat(-0.2), Vec2::splat(1.4))
+        );
+
+        for id in [first, second, third] {
+            let outcome = editor.apply_action(EditorAction::DeleteKnot { id }, &mut curve);
+            assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        }
+        assert!(curve.knots().is_empty());
+        assert_eq!(curve.lookup(0.5), 0.0);
+        editor
+            .apply_action(EditorAction::FitToCurve, &mut curve)
+            .unwrap();
+        assert_eq!(
+            (editor.offset, editor.scale),
+            (Vec2::splat(-0.2), Vec2::splat(1.4))
+        );
+        add(&mut editor, &mut curve, 0.3, 0.3);
+        assert_eq!(curve.lookup(10.0), 0.3);
+
+        // Views without a size are ignored
+        for scale in [Vec2::ZERO, Vec2::new(1.0, -1.0), Vec2::splat(f32::INFINITY)] {
+            let outcome = editor.apply_action(
+                EditorAction::SetView {
+                    offset: Vec2::ZERO,
+                    scale,
+                },
+                &mut curve,
+            );
+            assert_eq!(outcome.unwrap(), ActionOutcome::Unchanged);
+        }
+        assert_eq!(
+            (editor.offset, editor.scale),
+            (Vec2::splat(-0.2), Vec2::splat(1.4))
+        );
+    }
+
+    #[test]
+    fn zooms_around_the_center() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::default();
+        editor.scale = Vec2::new(1000.0, 1.0);
+        let center = Vec2::new(250.0, 0.5);
+        let outcome = editor.apply_action(
+            EditorAction::ZoomView {
+                center,
+                factor: Vec2::new(0.5, 1.0),
+            },
+            &mut curve,
+        );
+        assert_eq!(outcome.unwrap(), ActionOutcome::EditorChanged);
+        // Only x is zoomed, and the center stays in place
+        assert_eq!(editor.scale, Vec2::new(500.0, 1.0));
+        assert_eq!(editor.offset, Vec2::new(125.0, 0.0));
+        assert!(!EditorAction::ZoomView {
+            center,
+            factor: Vec2::ONE
+        }
+        .edits_curve());
+
+        for factor in [Vec2::ZERO, Vec2::new(2.0, -1.0), Vec2::splat(f32::NAN)] {
+            let outcome =
+                editor.apply_action(EditorAction::ZoomView { center, factor }, &mut curve);
+            assert_eq!(outcome.unwrap(), ActionOutcome::Unchanged);
+        }
+        assert_eq!(editor.scale, Vec2::new(500.0, 1.0));
+    }
+
+    #[test]
+    fn knots_are_referred_to_by_id() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 0.0)]);
+        let [a, b, c] = ids(&curve).try_into().unwrap();
+
+        // Moving past the other knots changes the index, not the id
+        let outcome = editor.apply_action(
+            EditorAction::MoveKnot {
+                id: a,
+                to: Vec2::new(3.0, 0.5),
+            },
+            &mut curve,
+        );
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        assert_eq!(ids(&curve), [b, c, a]);
+
+        let outcome = editor.apply_action(
+            EditorAction::MoveKnot {
+                id: a,
+                to: Vec2::new(3.0, 0.5),
+            },
+            &mut curve,
+        );
+        assert_eq!(outcome.unwrap(), ActionOutcome::Unchanged);
+
+        editor
+            .apply_action(EditorAction::DeleteKnot { id: b }, &mut curve)
+            .unwrap();
+        assert_eq!(ids(&curve), [c, a]);
+        assert!(matches!(
+            editor.apply_action(EditorAction::DeleteKnot { id: b }, &mut curve),
+            Err(ActionError::UnknownKnot(id)) if id == b
+        ));
+    }
+
+    #[test]
+    fn actions_respect_edit_scope() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 0.0)]);
+        let [a, b, _] = ids(&curve).try_into().unwrap();
+        editor
+            .apply_action(EditorAction::SetEditScope(Some(1.5..=0.5)), &mut curve)
+            .unwrap();
+        assert_eq!(editor.edit_scope, Some(0.5..=1.5));
+
+        let generation = curve.generation();
+        assert!(matches!(
+            editor.apply_action(
+                EditorAction::SetInterpolation {
+                    id: a,
+                    interpolation: KnotInterpolation::Cubic
+                },
+                &mut curve
+            ),
+            Err(ActionError::OutOfScope(id)) if id == a
+        ));
+        assert_eq!(curve.generation(), generation);
+
+        editor
+            .apply_action(
+                EditorAction::MoveKnot {
+                    id: b,
+                    to: Vec2::new(5.0, 2.0),
+                },
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(curve.knots()[1].position, Vec2::new(1.5, 2.0));
+
+        let outcome = editor.apply_action(
+            EditorAction::AddKnot {
+                position: Vec2::new(-1.0, 0.0),
+                interpolation: KnotInterpolation::Constant,
+            },
+            &mut curve,
+        );
+        let Ok(ActionOutcome::KnotAdded(id)) = outcome else {
+            panic!("expected a knot to be added, got {outcome:?}");
+        };
+        let added = curve.knots().iter().find(|knot| knot.id == id).unwrap();
+        assert_eq!(added.position, Vec2::new(0.5, 0.0));
+    }
+
+    #[test]
+    fn guides_and_inserted_knots() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(2.0, 1.0)]);
+        editor
+            .apply_action(
+                EditorAction::AddGuide {
+                    axis: GuideAxis::Vertical,
+                    value: 0.5,
+                },
+                &mut curve,
+            )
+            .unwrap();
+        let id = editor.guides.iter().next().unwrap().id;
+        editor
+            .apply_action(EditorAction::MoveGuide { id, value: 1.5 }, &mut curve)
+            .unwrap();
+        assert_eq!(editor.guides.get(id).unwrap().value, 1.5);
+
+        let outcome = editor.apply_action(EditorAction::InsertKnotAt { x: 1.5 }, &mut curve);
+        let Ok(ActionOutcome::KnotAdded(added)) = outcome else {
+            panic!("expected a knot to be added, got {outcome:?}");
+        };
+        assert_eq!(curve.knots()[1].id, added);
+        assert_eq!(curve.knots()[1].position, Vec2::new(1.5, 0.75));
+        let outcome = editor.apply_action(EditorAction::InsertKnotAt { x: 1.5 }, &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::Unchanged);
+
+        editor
+            .apply_action(EditorAction::RemoveGuide { id }, &mut curve)
+            .unwrap();
+        assert!(matches!(
+            editor.apply_action(EditorAction::RemoveGuide { id }, &mut curve),
+            Err(ActionError::UnknownGuide(unknown)) if unknown == id
+        ));
+    }
+
+    #[test]
+    fn knots_are_edited_together_or_not_at_all() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 2.0)]);
+        let [a, b, c] = ids(&curve).try_into().unwrap();
+        let ys = |curve: &LookupCurve| {
+            curve
+                .knots()
+                .iter()
+                .map(|k| k.position.y)
+                .collect::<Vec<_>>()
+        };
+
+        let edit = |ids: &[usize], edit| EditorAction::EditKnots {
+            ids: ids.to_vec(),
+            edit,
+        };
+        editor
+            .apply_action(
+                edit(&[a, c], KnotEdit::Y(RelativeValue::Add(0.5))),
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(ys(&curve), [0.5, 1.0, 2.5]);
+        editor
+            .apply_action(
+                edit(&[b, c], KnotEdit::Interpolation(KnotInterpolation::Cubic)),
+                &mut curve,
+            )
+            .unwrap();
+        assert!(matches!(
+            curve.knots()[2].interpolation,
+            KnotInterpolation::Cubic
+        ));
+
+        // Rejected by the last knot
+        let generation = curve.generation();
+        editor
+            .apply_action(EditorAction::SetEditScope(Some(0.0..=1.5)), &mut curve)
+            .unwrap();
+        assert!(matches!(
+            editor.apply_action(edit(&[a, b, c], KnotEdit::Y(RelativeValue::Set(0.0))), &mut curve),
+            Err(ActionError::OutOfScope(id)) if id == c
+        ));
+        assert!(matches!(
+            editor.apply_action(
+                edit(&[b, a], KnotEdit::Y(RelativeValue::Multiply(f32::INFINITY))),
+                &mut curve
+            ),
+            Err(ActionError::NotFinite(id)) if id == b
+        ));
+        assert_eq!(ys(&curve), [0.5, 1.0, 2.5]);
+        assert_eq!(curve.generation(), generation);
+    }
+
+    #[test]
+    fn derived_curves_can_not_be_edited() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]);
+        let [a, _] = ids(&curve).try_into().unwrap();
+        editor.set_derived(Some(crate::link::LinkRelation::Scaled { x: 2.0, y: 1.0 }));
+
+        assert!(matches!(
+            editor.apply_action(EditorAction::DeleteKnot { id: a }, &mut curve),
+            Err(ActionError::DerivedCurve)
+        ));
+        assert_eq!(curve.knots().len(), 2);
+        // The view can still be changed
+        editor
+            .apply_action(EditorAction::FitToCurve, &mut curve)
+            .unwrap();
+
+        assert!(!editor.take_detach_link());
+        editor
+            .apply_action(EditorAction::DetachLink, &mut curve)
+            .unwrap();
+        assert!(editor.take_detach_link());
+        assert!(!editor.take_detach_link());
+    }
+
+    #[test]
+    fn merging_coincident_knots_deselects_removed_knots() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![
+            knot(0.0, 0.0),
+            knot(1.0, 1.0),
+            knot(1.0, 1.0001),
+            knot(2.0, 0.0),
+        ]);
+        let [_, b, c, d] = ids(&curve).try_into().unwrap();
+        editor.selected_knots = vec![c, d];
+        let merge = EditorAction::MergeCoincidentKnots {
+            tolerance: Vec2::splat(0.01),
+            merge_y: MergeY::KeepFirst,
+        };
+
+        editor.edit_scope = Some(1.5..=2.0);
+        assert!(matches!(
+            editor.apply_action(merge.clone(), &mut curve),
+            Err(ActionError::OutOfScope(id)) if id == b
+        ));
+        assert_eq!(curve.knots().len(), 4);
+
+        editor.edit_scope = None;
+        let outcome = editor.apply_action(merge.clone(), &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        assert!(!ids(&curve).contains(&c));
+        assert_eq!(editor.selected_knots, [d]);
+        assert_eq!(curve.lookup(1.0), 1.0);
+        assert_eq!(
+            editor.apply_action(merge, &mut curve).unwrap(),
+            ActionOutcome::Unchanged
+        );
+    }
+
+    #[test]
+    fn failed_inversion_leaves_curve() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 0.0)]);
+        let result = editor.apply_action(EditorAction::InvertCurve, &mut curve);
+        assert!(matches!(
+            result,
+            Err(ActionError::NotInvertible(NotMonotonic::Reverses {
+                segment: 1
+            }))
+        ));
+        assert_eq!(curve.knots().len(), 3);
+
+        curve.delete_knot(2);
+        let outcome = editor.apply_action(EditorAction::InvertCurve, &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        assert_eq!(curve.knots()[1].position, Vec2::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn extrapolation_is_set_on_the_curve() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]);
+        let generation = curve.generation();
+        let action = EditorAction::SetExtrapolation {
+            before: Extrapolation::Clamp,
+            after: Extrapolation::Linear,
+        };
+        let outcome = editor.apply_action(action.clone(), &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        assert_eq!(curve.generation(), generation + 1);
+        assert_eq!(curve.lookup(3.0), 3.0);
+        let outcome = editor.apply_action(action, &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::Unchanged);
+    }
+
+    #[test]
+    fn smoothing_respects_the_edit_scope() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let kinked = Knot {
+            interpolation: KnotInterpolation::Cubic,
+            ..knot(1.0, 1.0)
+        };
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), kinked, knot(2.0, 1.0)]);
+        let [_, b, _] = ids(&curve).try_into().unwrap();
+        let smooth_all = EditorAction::SmoothAllKnots {
+            slope_tolerance: 1e-3,
+            policy: SmoothPolicy::Average,
+        };
+
+        editor.edit_scope = Some(1.5..=2.0);
+        assert!(matches!(
+            editor.apply_action(smooth_all.clone(), &mut curve),
+            Err(ActionError::OutOfScope(id)) if id == b
+        ));
+        assert_eq!(curve.c1_discontinuities(1e-3).len(), 1);
+
+        editor.edit_scope = None;
+        let outcome = editor.apply_action(smooth_all.clone(), &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        // The incoming linear segment sets the slope
+        assert_eq!(curve.knots()[1].right_tangent.slope, 1.0);
+        assert!(curve.c1_discontinuities(1e-3).is_empty());
+        let outcome = editor.apply_action(
+            EditorAction::SmoothKnot {
+                id: b,
+                policy: SmoothPolicy::MatchOutgoing,
+            },
+            &mut curve,
+        );
+        assert_eq!(outcome.unwrap(), ActionOutcome::Unchanged);
+    }
+
+    #[test]
+    fn fit_to_reference_replaces_the_knots_over_the_domain() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve =
+            LookupCurve::new(vec![knot(0.0, 0.0), knot(2.0, 1.0)]).with_name("approach");
+        let fit = EditorAction::FitToReference { tolerance: 1e-3 };
+        assert!(matches!(
+            editor.apply_action(fit.clone(), &mut curve),
+            Err(ActionError::NoReference)
+        ));
+
+        editor.reference_fn = Some(Box::new(|x| 1.0 - (-3.0 * x).exp()));
+        let outcome = editor.apply_action(fit, &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        assert_eq!(curve.name.as_deref(), Some("approach"));
+        assert_eq!(curve.knots().first().unwrap().position.x, 0.0);
+        assert_eq!(curve.knots().last().unwrap().position.x, 2.0);
+        let reference = editor.reference_fn.as_ref().unwrap();
+        assert!(curve.fit_error(reference, 0.0, 2.0, 100).unwrap().max < 2e-3);
+    }
+
+    #[test]
+    fn stretching_needs_every_stretched_knot_in_scope() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 0.0)]);
+        let [a, b, c] = ids(&curve).try_into().unwrap();
+        let stretch = EditorAction::StretchKnot {
+            id: c,
+            to: Vec2::new(4.0, 0.5),
+            pivot: None,
+        };
+
+        editor.edit_scope = Some(0.5..=5.0);
+        assert!(matches!(
+            editor.apply_action(stretch.clone(), &mut curve),
+            Err(ActionError::OutOfScope(id)) if id == a
+        ));
+        let outcome = editor.apply_action(
+            EditorAction::StretchKnot {
+                id: c,
+                to: Vec2::new(3.0, 0.0),
+                pivot: Some(b),
+            },
+            &mut curve,
+        );
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        assert_eq!(curve.knots()[2].position, Vec2::new(3.0, 0.0));
+
+        editor.edit_scope = None;
+        let outcome = editor.apply_action(stretch, &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        let positions: Vec<Vec2> = curve.knots().iter().map(|knot| knot.position).collect();
+        assert_eq!(
+            positions,
+            [Vec2::ZERO, Vec2::new(4.0 / 3.0, 1.0), Vec2::new(4.0, 0.5)]
+        );
+        assert_eq!(ids(&curve), [a, b, c]);
+    }
+
+    #[test]
+    fn pasted_knots_get_new_ids_and_are_selected() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let source = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 0.0)]);
+        let copied = ids(&source);
+        let clipboard = KnotClipboard::copy(&source, &copied[1..]).unwrap();
+
+        let mut curve = LookupCurve::new(vec![knot(10.0, 0.0), knot(14.0, 0.0)]);
+        let paste = EditorAction::PasteKnots {
+            clipboard,
+            placement: PastePlacement::Normalized,
+        };
+        editor.edit_scope = Some(10.0..=11.0);
+        assert!(matches!(
+            editor.apply_action(paste.clone(), &mut curve),
+            Err(ActionError::PasteOutOfScope)
+        ));
+        assert_eq!(curve.knots().len(), 2);
+
+        editor.edit_scope = None;
+        let outcome = editor.apply_action(paste, &mut curve);
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        let positions: Vec<Vec2> = curve.knots().iter().map(|knot| knot.position).collect();
+        assert_eq!(
+            positions,
+            [
+                Vec2::new(10.0, 0.0),
+                Vec2::new(12.0, 1.0),
+                Vec2::new(14.0, 0.0),
+                Vec2::new(14.0, 0.0)
+            ]
+        );
+        assert_eq!(editor.selected_knots.len(), 2);
+        for id in &editor.selected_knots {
+            assert!(!copied.contains(id));
+            assert!(curve.knots().iter().any(|knot| knot.id == *id));
+        }
+    }
+
+    #[test]
+    fn selected_knots_are_moved_and_deleted_together() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new([0.0, 1.0, 2.0, 3.0, 4.0].map(|x| knot(x, x)).to_vec());
+        let [a, b, c, d, e] = [0, 1, 2, 3, 4].map(|i| curve.knots()[i].id);
+
+        editor
+            .apply_action(
+                EditorAction::SelectInRect {
+                    from: Vec2::new(2.5, 4.0),
+                    to: Vec2::new(0.5, 0.5),
+                    extend: false,
+                },
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(editor.selected_knots, [b, c]);
+        editor
+            .apply_action(
+                EditorAction::SelectInRect {
+                    from: Vec2::new(3.5, 3.5),
+                    to: Vec2::new(4.5, 4.5),
+                    extend: true,
+                },
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(editor.selected_knots, [b, c, e]);
+
+        // b and c pass d, and keep their order
+        let outcome = editor.apply_action(
+            EditorAction::MoveKnots {
+                ids: vec![b, c],
+                delta: Vec2::new(2.5, 1.0),
+            },
+            &mut curve,
+        );
+        assert_eq!(outcome.unwrap(), ActionOutcome::CurveChanged);
+        assert_eq!(ids(&curve), [a, d, b, e, c]);
+        let positions: Vec<Vec2> = curve.knots().iter().map(|knot| knot.position).collect();
+        assert_eq!(
+            positions,
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(3.0, 3.0),
+                Vec2::new(3.5, 2.0),
+                Vec2::new(4.0, 4.0),
+                Vec2::new(4.5, 3.0),
+            ]
+        );
+
+        // Limited by the scope as a group
+        editor.edit_scope = Some(0.0..=5.5);
+        editor
+            .apply_action(
+                EditorAction::MoveKnots {
+                    ids: vec![b, c],
+                    delta: Vec2::new(2.0, 0.0),
+                },
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(ids(&curve), [a, d, e, b, c]);
+        assert_eq!(curve.knots()[4].position, Vec2::new(5.5, 3.0));
+        assert_eq!(curve.knots()[3].position, Vec2::new(4.5, 2.0));
+
+        // Knots outside of the scope are not moved or deleted, and neither are the others
+        editor.edit_scope = Some(1.0..=5.5);
+        let before = ids(&curve);
+        let moved = editor.apply_action(
+            EditorAction::MoveKnots {
+                ids: vec![a, b],
+                delta: Vec2::Y,
+            },
+            &mut curve,
+        );
+        assert!(matches!(moved, Err(ActionError::OutOfScope(id)) if id == a));
+        let deleted =
+            editor.apply_action(EditorAction::DeleteKnots { ids: vec![b, a] }, &mut curve);
+        assert!(matches!(deleted, Err(ActionError::OutOfScope(id)) if id == a));
+        assert_eq!(ids(&curve), before);
+        assert_eq!(curve.knots()[3].position, Vec2::new(4.5, 2.0));
+
+        editor
+            .apply_action(
+                EditorAction::DeleteKnots {
+                    ids: editor.selected_knots.clone(),
+                },
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(ids(&curve), [a, d]);
+        assert!(editor.selected_knots.is_empty());
+    }
+
+    #[test]
+    fn time_warps_stay_valid() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = crate::TimeWarpCurve::try_from(LookupCurve::new(vec![
+            knot(0.0, 0.0),
+            knot(0.5, 0.5),
+            knot(1.0, 1.0),
+        ]))
+        .unwrap()
+        .into_curve();
+        let [start, middle, end] = [0, 1, 2].map(|i| curve.knots()[i].id);
+
+        // The end knots are locked, and knots can not go back in time
+        for (id, to) in [(start, Vec2::new(0.2, 0.3)), (end, Vec2::new(0.8, 2.0))] {
+            editor
+                .apply_action(EditorAction::MoveKnot { id, to }, &mut curve)
+                .unwrap();
+        }
+        let to = Vec2::new(0.6, -1.0);
+        editor
+            .apply_action(EditorAction::MoveKnot { id: middle, to }, &mut curve)
+            .unwrap();
+        let positions: Vec<Vec2> = curve.knots().iter().map(|knot| knot.position).collect();
+        assert_eq!(positions, [Vec2::ZERO, Vec2::new(0.6, 0.0), Vec2::ONE]);
+
+        editor
+            .apply_action(EditorAction::DeleteKnot { id: end }, &mut curve)
+            .unwrap();
+        assert_eq!(curve.check_time_warp(), Ok(()));
+    }
+
+    #[test]
+    fn edits_follow_the_quantize_rule() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let rule = crate::QuantizeRule {
+            x_step: Some(1.0 / 60.0),
+            y_step: Some(0.5),
+            tangents_too: true,
+        };
+        let mut curve =
+            LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]).with_quantize_storage(rule);
+        let on_grid = |curve: &LookupCurve| {
+            curve.knots().iter().all(|knot| {
+                let rounded = rule.knot(*knot);
+                rounded.position == knot.position
+                    && rounded.left_tangent.slope == knot.left_tangent.slope
+                    && rounded.right_tangent.slope == knot.right_tangent.slope
+            })
+        };
+
+        let Ok(ActionOutcome::KnotAdded(added)) = editor.apply_action(
+            EditorAction::AddKnot {
+                position: Vec2::new(0.252, 0.3),
+                interpolation: KnotInterpolation::Cubic,
+            },
+            &mut curve,
+        ) else {
+            panic!("expected an added knot");
+        };
+        assert!(on_grid(&curve));
+        assert_eq!(curve.knots()[1].position, Vec2::new(0.25, 0.5));
+
+        // Numeric entry
+        let entries = [
+            EditorAction::MoveKnot {
+                id: added,
+                to: Vec2::new(0.4, 0.8),
+            },
+            EditorAction::SetTangentSlope {
+                id: added,
+                side: TangentSide::Right,
+                slope: 1.3,
+            },
+        ];
+        for action in entries {
+            editor.apply_action(action, &mut curve).unwrap();
+            assert!(on_grid(&curve));
+        }
+        assert_eq!(curve.knots()[1].right_tangent.slope, 1.5);
+
+        let clipboard = KnotClipboard::copy(&curve, &[added]).unwrap();
+        editor
+            .apply_action(
+                EditorAction::PasteKnots {
+                    clipboard,
+                    placement: PastePlacement::AtCursor { x: 0.709 },
+                },
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(curve.knots().len(), 4);
+        assert!(on_grid(&curve));
+
+        // Dragged knots follow the pointer exactly, and are rounded once the drag completes
+        editor.defer_quantize = true;
+        let to = Vec2::new(0.41, 0.9);
+        editor
+            .apply_action(EditorAction::MoveKnot { id: added, to }, &mut curve)
+            .unwrap();
+        assert_eq!(curve.knots()[1].position, to);
+        editor.defer_quantize = false;
+        let to = Vec2::new(0.42, 0.95);
+        editor
+            .apply_action(EditorAction::MoveKnot { id: added, to }, &mut curve)
+            .unwrap();
+        assert_eq!(curve.knots()[1].position, Vec2::new(25.0 / 60.0, 1.0));
+        assert!(on_grid(&curve));
+    }
+
+    #[test]
+    fn edits_are_undone_and_redone() {
+        let mut editor = LookupCurveEguiEditor::default();
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(2.0, 0.0)]);
+        let original = ids(&curve);
+        let undo = |editor: &mut LookupCurveEguiEditor, curve: &mut LookupCurve| {
+            editor.apply_action(EditorAction::Undo, curve).unwrap()
+        };
+        assert_eq!(undo(&mut editor, &mut curve), ActionOutcome::Unchanged);
+
+        editor
+            .apply_action(EditorAction::DeleteKnot { id: original[1] }, &mut curve)
+            .unwrap();
+        // A drag, moved in every frame while the pointer is held
+        editor.history.set_coalescing(true);
+        for i in 1..=10 {
+            let to = Vec2::new(2.0, i as f32 * 0.1);
+            editor
+                .apply_action(
+                    EditorAction::MoveKnot {
+                        id: original[2],
+                        to,
+                    },
+                    &mut curve,
+                )
+                .unwrap();
+        }
+        editor.history.set_coalescing(false);
+        // Not edits of the curve
+        editor
+            .apply_action(EditorAction::ShowSmoothness(true), &mut curve)
+            .unwrap();
+        assert_eq!(editor.history.undo_len(), 2);
+
+        assert_eq!(undo(&mut editor, &mut curve), ActionOutcome::CurveChanged);
+        assert_eq!(curve.knots()[1].position, Vec2::new(2.0, 0.0));
+        undo(&mut editor, &mut curve);
+        assert_eq!(ids(&curve), original);
+
+        editor.apply_action(EditorAction::Redo, &mut curve).unwrap();
+        assert_eq!(ids(&curve), [original[0], original[2]]);
+        assert_eq!(editor.history.redo_len(), 1);
+        editor
+            .apply_action(EditorAction::DeleteKnot { id: original[2] }, &mut curve)
+            .unwrap();
+        assert_eq!(editor.history.redo_len(), 0);
+    }
+
+    #[test]
+    fn knots_snap_to_the_grid_per_axis() {
+        let mut editor = LookupCurveEguiEditor::default();
+        editor.grid_snap_step = Vec2::new(0.5, 0.0);
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]);
+        editor
+            .apply_action(EditorAction::SnapToGrid(true), &mut curve)
+            .unwrap();
+        assert!(editor.snap_to_grid);
+
+        // An axis with a step of 0 is kept
+        let position = Vec2::new(0.8, 0.37);
+        assert_eq!(
+            editor.snap_position(position, false, true),
+            Vec2::new(1.0, 0.37)
+        );
+        assert_eq!(editor.snap_position(position, true, false), position);
+
+        // Typed positions keep the selection when they reorder the knots
+        let [a, b] = ids(&curve).try_into().unwrap();
+        editor.selected_knots = vec![a];
+        editor
+            .apply_action(
+                EditorAction::MoveKnot {
+                    id: a,
+                    to: Vec2::new(2.0, 0.0),
+                },
+                &mut curve,
+            )
+            .unwrap();
+        assert_eq!(ids(&curve), [b, a]);
+        assert_eq!(editor.selected_knots, [a]);
+    }
+}
@@ -0,0 +1,321 @@
+use bevy_math::Vec2;
+use egui::{Context, Id, Key, Modifiers, Ui};
+
+use super::{EditorAction, LookupCurveEguiEditor};
+use crate::{Knot, LookupCurve};
+
+/// Id of the copied knots in the egui memory, shared by all editors
+fn clipboard_id() -> Id {
+    Id::new("lookup_curve_knot_clipboard")
+}
+
+/// The knots last copied in any editor of the context
+pub(crate) fn load_clipboard(ctx: &Context) -> Option<KnotClipboard> {
+    ctx.data(|data| data.get_temp(clipboard_id()))
+}
+
+pub(crate) fn store_clipboard(ctx: &Context, clipboard: KnotClipboard) {
+    ctx.data_mut(|data| data.insert_temp(clipboard_id(), clipboard));
+}
+
+/// Where [EditorAction::PasteKnots] places the knots of a [KnotClipboard]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PastePlacement {
+    /// At the positions they were copied from
+    Absolute,
+    /// At the same relative position within the domain of the target curve as within the domain they were copied
+    /// from, scaling the spacing of the knots and their tangents with the width of the domain.
+    ///
+    /// Pasting into a curve without width, like an empty curve, places the knots like [PastePlacement::Absolute].
+    Normalized,
+    /// Moved along x so that the first knot is at `x`, keeping the spacing of the knots
+    AtCursor { x: f32 },
+}
+
+/// Knots copied from a curve, with what is needed to place them in another curve, see [PastePlacement].
+///
+/// The editor keeps the last copied knots in the egui memory, so that they can be pasted into the curves of other
+/// editors.
+#[derive(Clone, Debug)]
+pub struct KnotClipboard {
+    /// The copied knots, ordered by x
+    pub knots: Vec<Knot>,
+    /// Corner of the bounding box of the knots with the lowest x and y
+    pub min: Vec2,
+    /// Corner of the bounding box of the knots with the highest x and y
+    pub max: Vec2,
+    /// First and last x of the curve the knots were copied from
+    pub source_domain: (f32, f32),
+}
+
+impl KnotClipboard {
+    /// Copies the knots of `curve` with `ids`, `None` if there are none
+    pub fn copy(curve: &LookupCurve, ids: &[usize]) -> Option<Self> {
+        let knots: Vec<Knot> = curve
+            .knots()
+            .iter()
+            .filter(|knot| ids.contains(&knot.id))
+            .copied()
+            .collect();
+        let (first, last) = (curve.knots().first()?, curve.knots().last()?);
+        let (min, max) = knots.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), knot| (min.min(knot.position), max.max(knot.position)),
+        );
+        (!knots.is_empty()).then_some(Self {
+            knots,
+            min,
+            max,
+            source_domain: (first.position.x, last.position.x),
+        })
+    }
+
+    /// The knots placed into a curve with `target_domain`, `None` for an empty curve. The knots keep their ids, the
+    /// editor assigns new ones when pasting.
+    pub fn place(&self, placement: PastePlacement, target_domain: Option<(f32, f32)>) -> Vec<Knot> {
+        let (scale, offset) = match placement {
+            PastePlacement::Absolute => (1.0, 0.0),
+            PastePlacement::AtCursor { x } => (1.0, x - self.min.x),
+            PastePlacement::Normalized => {
+                let (source_start, source_end) = self.source_domain;
+                match target_domain {
+                    Some((start, end)) if end > start && source_end > source_start => {
+                        let scale = (end - start) / (source_end - source_start);
+                        (scale, start - source_start * scale)
+                    }
+                    // A single knot keeps its offset from the start of the domain
+                    Some((start, end)) if end > start => (1.0, start - source_start),
+                    _ => (1.0, 0.0),
+                }
+            }
+        };
+        self.knots
+            .iter()
+            .map(|knot| {
+                let mut placed = *knot;
+                placed.position.x = knot.position.x * scale + offset;
+                placed.left_tangent.slope /= scale;
+                placed.right_tangent.slope /= scale;
+                placed
+            })
+            .collect()
+    }
+}
+
+impl LookupCurveEguiEditor {
+    /// Copies the selected knots with command+C. Pastes with command+V at the x of the pointer, with
+    /// command+shift+V normalized to the domain and with command+alt+V where they were copied from.
+    ///
+    /// Call only while the pointer is over the plot, at `pointer_x`.
+    pub(crate) fn clipboard_shortcuts(
+        &self,
+        ui: &Ui,
+        curve: &LookupCurve,
+        pointer_x: f32,
+        actions: &mut Vec<EditorAction>,
+    ) {
+        // Leave the shortcuts to focused text fields
+        if ui.memory(|memory| memory.focused().is_some()) {
+            return;
+        }
+        if ui.input_mut(|input| input.consume_key(Modifiers::COMMAND, Key::C)) {
+            if let Some(clipboard) = KnotClipboard::copy(curve, &self.selected_knots) {
+                store_clipboard(ui.ctx(), clipboard);
+            }
+        }
+        let placement = ui.input_mut(|input| {
+            if input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::V) {
+                Some(PastePlacement::Normalized)
+            } else if input.consume_key(Modifiers::COMMAND | Modifiers::ALT, Key::V) {
+                Some(PastePlacement::Absolute)
+            } else if input.consume_key(Modifiers::COMMAND, Key::V) {
+                Some(PastePlacement::AtCursor { x: pointer_x })
+            } else {
+                None
+            }
+        });
+        if let (Some(placement), Some(clipboard)) = (placement, load_clipboard(ui.ctx())) {
+            actions.push(EditorAction::PasteKnots {
+                clipboard,
+                placement,
+            });
+        }
+    }
+
+    /// Entries of the context menu of the plot for copying the selected knots and pasting, with the menu at
+    /// `menu_x`
+    pub(crate) fn clipboard_menu(
+        &self,
+        ui: &mut Ui,
+        curve: &LookupCurve,
+        menu_x: f32,
+        actions: &mut Vec<EditorAction>,
+    ) {
+        let copy_button = ui.add_enabled(
+            !self.selected_knots.is_empty(),
+            egui::Button::new("Copy selected knots"),
+        );
+        if copy_button.clicked() {
+            if let Some(clipboard) = KnotClipboard::copy(curve, &self.selected_knots) {
+                store_clipboard(ui.ctx(), clipboard);
+            }
+            ui.close_menu();
+        }
+
+        let clipboard = load_clipboard(ui.ctx());
+        ui.add_enabled_ui(clipboard.is_some(), |ui| {
+            ui.menu_button("Paste knots", |ui| {
+                for (placement, label, hint) in [
+                    (
+                        PastePlacement::AtCursor { x: menu_x },
+                        "Here",
+                        "First knot at the x of the menu, keeping the spacing",
+                    ),
+                    (
+                        PastePlacement::Normalized,
+                        "Same relative position",
+                        "At the same relative position within the domain of this curve",
+                    ),
+                    (
+                        PastePlacement::Absolute,
+                        "As copied",
+                        "At the positions the knots were copied from",
+                    ),
+                ] {
+                    if ui.button(label).on_hover_text(hint).clicked() {
+                        if let Some(clipboard) = clipboard.clone() {
+                            actions.push(EditorAction::PasteKnots {
+                                clipboard,
+                                placement,
+                            });
+                        }
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KnotInterpolation, Tangent, TangentMode};
+
+    fn knot(x: f32, y: f32) -> Knot {
+        let tangent = |slope| Tangent {
+            slope,
+            mode: TangentMode::Free,
+            weight: None,
+        };
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            left_tangent: tangent(1.0),
+            right_tangent: tangent(-2.0),
+            ..Default::default()
+        }
+    }
+
+    /// Knots 1 and 2 of a curve over 0..=10
+    fn clipboard() -> KnotClipboard {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0),
+            knot(2.0, 1.0),
+            knot(4.0, 3.0),
+            knot(10.0, 0.0),
+        ]);
+        let ids: Vec<usize> = curve.knots()[1..3].iter().map(|knot| knot.id).collect();
+        KnotClipboard::copy(&curve, &ids).unwrap()
+    }
+
+    fn positions(knots: &[Knot]) -> Vec<Vec2> {
+        knots.iter().map(|knot| knot.position).collect()
+    }
+
+    #[test]
+    fn copy_records_bounds_and_domain() {
+        let clipboard = clipboard();
+        assert_eq!(
+            positions(&clipboard.knots),
+            [Vec2::new(2.0, 1.0), Vec2::new(4.0, 3.0)]
+        );
+        assert_eq!(
+            (clipboard.min, clipboard.max),
+            (Vec2::new(2.0, 1.0), Vec2::new(4.0, 3.0))
+        );
+        assert_eq!(clipboard.source_domain, (0.0, 10.0));
+        assert!(KnotClipboard::copy(&LookupCurve::default(), &[1]).is_none());
+        let curve = LookupCurve::new(vec![knot(0.0, 0.0)]);
+        assert!(KnotClipboard::copy(&curve, &[usize::MAX]).is_none());
+    }
+
+    #[test]
+    fn absolute_and_at_cursor_keep_the_spacing() {
+        let clipboard = clipboard();
+        let absolute = clipboard.place(PastePlacement::Absolute, Some((100.0, 200.0)));
+        assert_eq!(positions(&absolute), positions(&clipboard.knots));
+
+        let at_cursor =
+            clipboard.place(PastePlacement::AtCursor { x: 150.0 }, Some((100.0, 200.0)));
+        assert_eq!(
+            positions(&at_cursor),
+            [Vec2::new(150.0, 1.0), Vec2::new(152.0, 3.0)]
+        );
+        assert_eq!(at_cursor[0].left_tangent.slope, 1.0);
+        assert_eq!(at_cursor[1].right_tangent.slope, -2.0);
+    }
+
+    #[test]
+    fn normalized_keeps_the_relative_position_in_the_domain() {
+        let clipboard = clipboard();
+        let placed = clipboard.place(PastePlacement::Normalized, Some((100.0, 200.0)));
+        assert_eq!(
+            positions(&placed),
+            [Vec2::new(120.0, 1.0), Vec2::new(140.0, 3.0)]
+        );
+        // Ten times as wide, so slopes are a tenth
+        assert_eq!(placed[0].left_tangent.slope, 0.1);
+        assert_eq!(placed[1].right_tangent.slope, -0.2);
+
+        // Into a narrower domain than the selection, which is compressed to fit
+        let placed = clipboard.place(PastePlacement::Normalized, Some((0.0, 1.0)));
+        assert_eq!(
+            positions(&placed),
+            [Vec2::new(0.2, 1.0), Vec2::new(0.4, 3.0)]
+        );
+        assert_eq!(placed[0].left_tangent.slope, 10.0);
+    }
+
+    #[test]
+    fn selections_wider_than_the_target_extend_past_its_domain_at_the_cursor() {
+        let clipboard = clipboard();
+        let placed = clipboard.place(PastePlacement::AtCursor { x: 0.5 }, Some((0.0, 1.0)));
+        assert_eq!(
+            positions(&placed),
+            [Vec2::new(0.5, 1.0), Vec2::new(2.5, 3.0)]
+        );
+    }
+
+    #[test]
+    fn pasting_into_a_curve_without_width() {
+        let clipboard = clipboard();
+        // Empty and single knot curves have no domain to map into, knots stay where they were copied from
+        for target in [None, Some((5.0, 5.0))] {
+            let placed = clipboard.place(PastePlacement::Normalized, target);
+            assert_eq!(positions(&placed), positions(&clipboard.knots));
+        }
+        let placed = clipboard.place(PastePlacement::AtCursor { x: -1.0 }, None);
+        assert_eq!(
+            positions(&placed),
+            [Vec2::new(-1.0, 1.0), Vec2::new(1.0, 3.0)]
+        );
+
+        // A knot copied from a single knot curve keeps its offset from the start of the domain
+        let single = LookupCurve::new(vec![knot(3.0, 1.0)]);
+        let id = single.knots()[0].id;
+        let clipboard = KnotClipboard::copy(&single, &[id]).unwrap();
+        let placed = clipboard.place(PastePlacement::Normalized, Some((10.0, 20.0)));
+        assert_eq!(positions(&placed), [Vec2::new(10.0, 1.0)]);
+    }
+}
@@ -0,0 +1,236 @@
+use super::LookupCurveEguiEditor;
+use crate::{next_generation, LookupCurve};
+
+/// What the curve asset should be set to, returned by [SavedCompare]
+#[derive(Clone, Debug)]
+pub(crate) enum CompareWrite {
+    /// Show the saved state, the curve is the edited curve with the knots of the saved state
+    ShowSaved(Box<LookupCurve>),
+    /// Write the edited curve back
+    RestoreEdited,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+enum CompareState {
+    #[default]
+    Idle,
+    /// The key is held and the asset shows the saved state
+    ShowingSaved,
+    /// The key is still held, but the asset was reloaded while showing the saved state. Comparing starts again once
+    /// the key is released and pressed again.
+    Suspended,
+}
+
+/// Swaps a curve asset to its last saved state while a key is held, see
+/// [LookupCurveEditor::compare_key](super::LookupCurveEditor::compare_key).
+///
+/// While the saved state is shown the editor keeps editing its copy of the curve, which is written back on release.
+/// The copy keeps its generation, so the dirty flag of the editor is not changed.
+///
+/// Precedence when the saved state changes while the key is held:
+/// - After a save the saved state is the edited curve, which is shown until the key is released.
+/// - A reload of the asset from disk wins, like outside of comparing: the reloaded curve becomes the saved state,
+///   replaces the edited curve and is not swapped again until the key is pressed again.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SavedCompare {
+    saved: Option<LookupCurve>,
+    /// [LookupCurveEguiEditor::saved_generation](super::LookupCurveEguiEditor) the saved state was taken at
+    saved_generation: Option<u64>,
+    state: CompareState,
+}
+
+impl SavedCompare {
+    pub(crate) fn saved(&self) -> Option<&LookupCurve> {
+        self.saved.as_ref()
+    }
+
+    /// Whether the asset shows the saved state, and edits are kept in the editor
+    pub(crate) fn is_showing_saved(&self) -> bool {
+        self.state == CompareState::ShowingSaved
+    }
+
+    /// Whether the saved state should be refreshed, because the editor was opened or saved since the last refresh.
+    /// `saved_generation` is the generation of the curve when the editor last saved it or first showed it.
+    pub(crate) fn needs_refresh(&self, saved_generation: Option<u64>) -> bool {
+        saved_generation.is_some() && saved_generation != self.saved_generation
+    }
+
+    /// Caches `saved` as the saved state after the editor was opened or saved, for `saved_generation`. While the
+    /// saved state is shown, returns the new state to show.
+    pub(crate) fn refresh(
+        &mut self,
+        saved: LookupCurve,
+        saved_generation: Option<u64>,
+        edited: &LookupCurve,
+    ) -> Option<CompareWrite> {
+        self.saved = Some(saved);
+        self.saved_generation = saved_generation;
+        self.is_showing_saved()
+            .then(|| CompareWrite::ShowSaved(Box::new(self.showing_saved(edited))))
+    }
+
+    /// Caches the asset as the saved state after it was reloaded from disk. Stops showing the saved state, as the
+    /// reloaded curve replaces the edited curve.
+    pub(crate) fn reloaded(&mut self, asset: &LookupCurve) {
+        self.saved = Some(asset.clone());
+        if self.state == CompareState::ShowingSaved {
+            self.state = CompareState::Suspended;
+        }
+    }
+
+    /// Updates whether the compare key is `held`, returning the write that starts or stops showing the saved state
+    pub(crate) fn set_held(&mut self, held: bool, edited: &LookupCurve) -> Option<CompareWrite> {
+        match (self.state, held) {
+            (CompareState::Idle, true) => {
+                self.saved.as_ref()?;
+                self.state = CompareState::ShowingSaved;
+                Some(CompareWrite::ShowSaved(Box::new(
+                    self.showing_saved(edited),
+                )))
+            }
+            (CompareState::ShowingSaved, false) => {
+                self.state = CompareState::Idle;
+                Some(CompareWrite::RestoreEdited)
+            }
+            (CompareState::Suspended, false) => {
+                self.state = CompareState::Idle;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// The edited curve with the knots of the saved state. It gets a generation of its own, so that it is not
+    /// mistaken for the edited curve, for example by [DerivedStore](crate::derived::DerivedStore).
+    fn showing_saved(&self, edited: &LookupCurve) -> LookupCurve {
+        let mut curve = edited.clone();
+        if let Some(saved) = &self.saved {
+            curve.knots = saved.knots.clone();
+        }
+        curve.generation = next_generation();
+        curve.order_epoch = next_generation();
+        curve
+    }
+}
+
+impl LookupCurveEguiEditor {
+    /// Whether the pointer is over the editor window
+    pub(crate) fn is_hovered(&self, ctx: &egui::Context) -> bool {
+        let Some(rect) = self.window_rect else {
+            return false;
+        };
+        ctx.input(|input| input.pointer.hover_pos())
+            .is_some_and(|pos| rect.contains(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy_math::Vec2;
+
+    fn curve(ys: &[f32]) -> LookupCurve {
+        LookupCurve::new(
+            ys.iter()
+                .enumerate()
+                .map(|(x, &y)| Knot {
+                    position: Vec2::new(x as f32, y),
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn ys(curve: &LookupCurve) -> Vec<f32> {
+        curve.knots().iter().map(|knot| knot.position.y).collect()
+    }
+
+    fn shown(write: Option<CompareWrite>) -> LookupCurve {
+        match write {
+            Some(CompareWrite::ShowSaved(curve)) => *curve,
+            other => panic!("expected the saved state to be shown, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn holding_shows_the_saved_knots_and_releasing_restores() {
+        let saved = curve(&[0.0, 1.0]);
+        let mut compare = SavedCompare::default();
+        let mut edited = saved.clone().with_name("edited");
+        assert!(compare.needs_refresh(Some(saved.generation())));
+        assert!(compare
+            .refresh(saved.clone(), Some(saved.generation()), &edited)
+            .is_none());
+        assert!(!compare.needs_refresh(Some(saved.generation())));
+
+        let knot = Knot {
+            position: Vec2::new(1.0, 5.0),
+            ..edited.knots()[1]
+        };
+        edited.modify_knot(1, knot);
+        let generation = edited.generation();
+
+        let showing = shown(compare.set_held(true, &edited));
+        assert_eq!(ys(&showing), [0.0, 1.0]);
+        assert_eq!(showing.name.as_deref(), Some("edited"));
+        assert_ne!(showing.generation(), generation);
+        assert_ne!(showing.generation(), saved.generation());
+        assert!(compare.is_showing_saved());
+        assert!(compare.set_held(true, &edited).is_none());
+
+        assert!(matches!(
+            compare.set_held(false, &edited),
+            Some(CompareWrite::RestoreEdited)
+        ));
+        assert!(!compare.is_showing_saved());
+        assert_eq!(edited.generation(), generation);
+    }
+
+    #[test]
+    fn nothing_is_shown_without_a_saved_state() {
+        let mut compare = SavedCompare::default();
+        let edited = curve(&[0.0]);
+        assert!(compare.set_held(true, &edited).is_none());
+        assert!(compare.set_held(false, &edited).is_none());
+        assert!(compare.needs_refresh(Some(edited.generation())));
+        assert!(!compare.needs_refresh(None));
+    }
+
+    #[test]
+    fn saving_while_held_shows_the_new_saved_state() {
+        let mut compare = SavedCompare::default();
+        let saved = curve(&[0.0, 1.0]);
+        compare.refresh(saved.clone(), Some(saved.generation()), &saved);
+        let edited = curve(&[2.0, 3.0]);
+        shown(compare.set_held(true, &edited));
+
+        let showing = shown(compare.refresh(edited.clone(), Some(edited.generation()), &edited));
+        assert_eq!(ys(&showing), [2.0, 3.0]);
+        assert_eq!(ys(compare.saved().unwrap()), [2.0, 3.0]);
+        assert!(matches!(
+            compare.set_held(false, &edited),
+            Some(CompareWrite::RestoreEdited)
+        ));
+    }
+
+    #[test]
+    fn reloading_while_held_wins_until_the_key_is_pressed_again() {
+        let mut compare = SavedCompare::default();
+        let saved = curve(&[0.0, 1.0]);
+        compare.refresh(saved.clone(), Some(saved.generation()), &saved);
+        let edited = curve(&[2.0, 3.0]);
+        shown(compare.set_held(true, &edited));
+
+        let reloaded = curve(&[4.0, 4.0]);
+        compare.reloaded(&reloaded);
+        assert!(!compare.is_showing_saved());
+        assert_eq!(ys(compare.saved().unwrap()), [4.0, 4.0]);
+        // Neither the held key nor its release write, the asset holds the reloaded curve
+        assert!(compare.set_held(true, &reloaded).is_none());
+        assert!(compare.set_held(false, &reloaded).is_none());
+
+        let showing = shown(compare.set_held(true, &edited));
+        assert_eq!(ys(&showing), [4.0, 4.0]);
+    }
+}
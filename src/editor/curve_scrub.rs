@@ -0,0 +1,124 @@
+use bevy_ecs::prelude::{Event, EventWriter, Res, ResMut, Resource};
+use bevy_time::{Real, Time};
+
+use crate::scrub::{RecordedTrack, ScrubPlayback};
+
+/// Scrubbing through the domain of the edited curves, for example against a recorded gameplay session.
+///
+/// Insert this resource to show the scrub controls and the recorded `track` below the plot of every editor,
+/// with the scrub position shown as the sample instead of [LookupCurveEditor::sample](super::LookupCurveEditor::sample).
+/// Playback advances using wall-clock time, so it is not affected by pausing or scaling virtual time.
+///
+/// Whenever the position changes, from playback or from the UI, a [CurveScrubChanged] event is sent.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct CurveScrub {
+    pub playback: ScrubPlayback,
+    /// Recorded values shown below the plot, read-only
+    pub track: Option<RecordedTrack>,
+    /// When dragging the scrub cursor, snap to the nearest recorded sample of `track`
+    pub snap_to_samples: bool,
+    last_emitted: Option<f32>,
+}
+
+impl CurveScrub {
+    /// Constructs a [CurveScrub] for `track`, looping over the recorded range
+    pub fn with_track(track: RecordedTrack) -> Self {
+        let mut playback = ScrubPlayback::default();
+        playback.loop_range = track.domain();
+        if let Some((start, _)) = track.domain() {
+            playback.seek(start);
+        }
+        Self {
+            playback,
+            track: Some(track),
+            ..Default::default()
+        }
+    }
+}
+
+/// Sent when the position of the [CurveScrub] changes, at most once per frame
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct CurveScrubChanged {
+    pub x: f32,
+}
+
+pub(crate) fn advance_curve_scrub(time: Res<Time<Real>>, scrub: Option<ResMut<CurveScrub>>) {
+    if let Some(mut scrub) = scrub {
+        if scrub.playback.playing {
+            scrub.playback.advance(time.delta_seconds());
+        }
+    }
+}
+
+pub(crate) fn emit_curve_scrub_changed(
+    scrub: Option<ResMut<CurveScrub>>,
+    mut changed: EventWriter<CurveScrubChanged>,
+) {
+    let Some(mut scrub) = scrub else {
+        return;
+    };
+    let x = scrub.playback.x();
+    if scrub.last_emitted != Some(x) {
+        scrub.last_emitted = Some(x);
+        changed.send(CurveScrubChanged { x });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::{App, Events, MinimalPlugins, Update};
+    use bevy_ecs::prelude::IntoSystemConfigs;
+    use bevy_math::Vec2;
+
+    fn test_app(scrub: CurveScrub) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<CurveScrubChanged>()
+            .insert_resource(scrub)
+            .add_systems(
+                Update,
+                (advance_curve_scrub, emit_curve_scrub_changed).chain(),
+            );
+        app
+    }
+
+    fn drain_events(app: &mut App) -> Vec<f32> {
+        app.world_mut()
+            .resource_mut::<Events<CurveScrubChanged>>()
+            .drain()
+            .map(|event| event.x)
+            .collect()
+    }
+
+    #[test]
+    fn sends_event_when_position_changes() {
+        let track = RecordedTrack::new(vec![Vec2::new(1.0, 0.0), Vec2::new(3.0, 1.0)]);
+        let mut app = test_app(CurveScrub::with_track(track));
+        app.update();
+        assert_eq!(drain_events(&mut app), [1.0]);
+
+        app.update();
+        assert!(drain_events(&mut app).is_empty());
+
+        app.world_mut()
+            .resource_mut::<CurveScrub>()
+            .playback
+            .seek(2.5);
+        app.update();
+        assert_eq!(drain_events(&mut app), [2.5]);
+    }
+
+    #[test]
+    fn no_scrub_resource_is_ignored() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<CurveScrubChanged>()
+            .add_systems(
+                Update,
+                (advance_curve_scrub, emit_curve_scrub_changed).chain(),
+            );
+        app.update();
+        assert!(drain_events(&mut app).is_empty());
+    }
+}
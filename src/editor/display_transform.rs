@@ -0,0 +1,315 @@
+use super::RelativeValue;
+use crate::{UnitDisplay, UnitParseError};
+
+/// Lowest value shown by [DisplayTransform::Decibel], lower and non-positive values are shown at this floor
+pub const DECIBEL_FLOOR: f32 = -80.0;
+/// Lowest value shown by [DisplayTransform::Semitones], lower and non-positive values are shown at this floor
+pub const SEMITONE_FLOOR: f32 = -120.0;
+
+/// Prefix of values at the floor of a logarithmic [DisplayTransform]
+const FLOOR_INDICATOR: &str = "≤ ";
+
+/// How the editor shows y values, see
+/// [LookupCurveEguiEditor::y_display_transform](super::LookupCurveEguiEditor::y_display_transform).
+///
+/// Only the display changes, curves always store and sample linear values. Transforms are applied after the scale
+/// of the [UnitDisplay] of the curve, and replace its unit by their own suffix.
+#[derive(Clone, Debug, Default)]
+pub enum DisplayTransform {
+    /// Values as they are
+    #[default]
+    Linear,
+    /// Values multiplied by 100, so 1 is shown as 100 %
+    Percent,
+    /// Amplitude relative to `reference` in decibels, `20 * log10(value / reference)`.
+    ///
+    /// Values below [DECIBEL_FLOOR], including zero and negative values, are shown at the floor.
+    Decibel { reference: f32 },
+    /// Frequency ratio in semitones, `12 * log2(value)`, so 2 is shown as 12 semitones.
+    ///
+    /// Values below [SEMITONE_FLOOR], including zero and negative values, are shown at the floor.
+    Semitones,
+    /// Transform supplied by the app. `from_display` should be the inverse of `to_display`.
+    Custom {
+        to_display: fn(f32) -> f32,
+        from_display: fn(f32) -> f32,
+        suffix: String,
+    },
+}
+
+impl DisplayTransform {
+    /// Converts a linear value to its displayed value
+    pub fn to_display(&self, value: f32) -> f32 {
+        match self {
+            DisplayTransform::Linear => value,
+            DisplayTransform::Percent => (value as f64 * 100.0) as f32,
+            DisplayTransform::Decibel { reference } => {
+                let db = 20.0 * (value as f64 / *reference as f64).log10();
+                floored(db, DECIBEL_FLOOR)
+            }
+            DisplayTransform::Semitones => floored(12.0 * (value as f64).log2(), SEMITONE_FLOOR),
+            DisplayTransform::Custom { to_display, .. } => to_display(value),
+        }
+    }
+
+    /// Converts a displayed value back to a linear value. Values below the floor of logarithmic transforms are
+    /// converted as if they were at the floor.
+    pub fn from_display(&self, value: f32) -> f32 {
+        match self {
+            DisplayTransform::Linear => value,
+            DisplayTransform::Percent => (value as f64 / 100.0) as f32,
+            DisplayTransform::Decibel { reference } => {
+                let db = value.max(DECIBEL_FLOOR) as f64;
+                (*reference as f64 * 10f64.powf(db / 20.0)) as f32
+            }
+            DisplayTransform::Semitones => (value.max(SEMITONE_FLOOR) as f64 / 12.0).exp2() as f32,
+            DisplayTransform::Custom { from_display, .. } => from_display(value),
+        }
+    }
+
+    /// Whether `value` is shown at the floor of a logarithmic transform, rather than at its own value
+    pub fn at_floor(&self, value: f32) -> bool {
+        let floor = match self {
+            DisplayTransform::Decibel { .. } => DECIBEL_FLOOR,
+            DisplayTransform::Semitones => SEMITONE_FLOOR,
+            _ => return false,
+        };
+        self.to_display(value) <= floor
+    }
+
+    /// Suffix shown after displayed values, `None` to keep the unit of the curve
+    pub fn suffix(&self) -> Option<&str> {
+        match self {
+            DisplayTransform::Linear => None,
+            DisplayTransform::Percent => Some("%"),
+            DisplayTransform::Decibel { .. } => Some("dB"),
+            DisplayTransform::Semitones => Some("st"),
+            DisplayTransform::Custom { suffix, .. } => Some(suffix),
+        }
+    }
+
+    pub(crate) fn is_linear(&self) -> bool {
+        matches!(self, DisplayTransform::Linear)
+    }
+}
+
+/// Logarithms of zero and negative values are not finite, they are shown at the floor like tiny values
+fn floored(value: f64, floor: f32) -> f32 {
+    if value.is_nan() {
+        floor
+    } else {
+        (value as f32).max(floor)
+    }
+}
+
+/// Formatting and parsing of values on one axis of the editor, combining the [UnitDisplay] of the curve and a
+/// [DisplayTransform]. All values shown or typed in the editor go through this.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ValueFormat {
+    pub(crate) unit: UnitDisplay,
+    pub(crate) transform: DisplayTransform,
+}
+
+impl From<UnitDisplay> for ValueFormat {
+    fn from(unit: UnitDisplay) -> Self {
+        Self {
+            unit,
+            transform: DisplayTransform::Linear,
+        }
+    }
+}
+
+impl ValueFormat {
+    /// Unit shown after values
+    pub(crate) fn label(&self) -> &str {
+        self.transform.suffix().unwrap_or(&self.unit.unit)
+    }
+
+    pub(crate) fn to_display(&self, value: f32) -> f32 {
+        self.transform.to_display(self.unit.to_display(value))
+    }
+
+    /// Formats a stored value. Values at the floor of a logarithmic transform are prefixed with `≤`.
+    pub(crate) fn format(&self, value: f32) -> String {
+        if self.transform.is_linear() {
+            return self.unit.format(value);
+        }
+        let precision = self.unit.precision;
+        let scaled = self.unit.to_display(value);
+        let indicator = if self.transform.at_floor(scaled) {
+            FLOOR_INDICATOR
+        } else {
+            ""
+        };
+        let shown = self.transform.to_display(scaled);
+        match self.label() {
+            "" => format!("{indicator}{shown:.precision$}"),
+            label => format!("{indicator}{shown:.precision$} {label}"),
+        }
+    }
+
+    /// Parses displayed text back into a stored value. The suffix and the floor indicator are optional.
+    pub(crate) fn parse(&self, text: &str) -> Result<f32, UnitParseError> {
+        if self.transform.is_linear() {
+            return self.unit.parse(text);
+        }
+        let trimmed = text.trim();
+        let trimmed = trimmed
+            .strip_prefix(FLOOR_INDICATOR.trim_end())
+            .or_else(|| trimmed.strip_prefix("<="))
+            .unwrap_or(trimmed);
+        let number = trimmed.trim();
+        let number = number.strip_suffix(self.label()).unwrap_or(number);
+        let shown = UnitDisplay::default().parse(number)?;
+        if self.unit.scale == 0.0 || !self.unit.scale.is_finite() {
+            return Err(UnitParseError::InvalidScale);
+        }
+        let scaled = self.transform.from_display(shown);
+        if !scaled.is_finite() {
+            return Err(UnitParseError::NotFinite);
+        }
+        Ok(self.unit.from_display(scaled))
+    }
+
+    /// Parses a value for several knots, see [RelativeValue::parse].
+    ///
+    /// With a non-linear transform, offsets in displayed units can not be applied to stored values, so `+=` and `-=`
+    /// are rejected. `*=` multiplies the stored values.
+    pub(crate) fn parse_relative(&self, text: &str) -> Result<RelativeValue, UnitParseError> {
+        if self.transform.is_linear() {
+            return RelativeValue::parse(text, &self.unit);
+        }
+        let trimmed = text.trim();
+        if trimmed.starts_with("+=") || trimmed.starts_with("-=") {
+            Err(UnitParseError::InvalidNumber(text.to_string()))
+        } else if trimmed.starts_with("*=") {
+            RelativeValue::parse(trimmed, &UnitDisplay::default())
+        } else {
+            self.parse(trimmed).map(RelativeValue::Set)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transforms() -> Vec<DisplayTransform> {
+        vec![
+            DisplayTransform::Linear,
+            DisplayTransform::Percent,
+            DisplayTransform::Decibel { reference: 1.0 },
+            DisplayTransform::Decibel { reference: 0.5 },
+            DisplayTransform::Semitones,
+            DisplayTransform::Custom {
+                to_display: |v| v * 4.0,
+                from_display: |v| v / 4.0,
+                suffix: "u".to_string(),
+            },
+        ]
+    }
+
+    /// Positive values spread over many magnitudes, above the floors of the logarithmic transforms
+    fn values() -> impl Iterator<Item = f32> {
+        (0..400).map(|i| 1e-3 * 1.035f32.powi(i))
+    }
+
+    #[test]
+    fn transforms_round_trip() {
+        for transform in transforms() {
+            for value in values() {
+                let back = transform.from_display(transform.to_display(value));
+                assert!(
+                    (back - value).abs() <= value.abs() * 1e-5,
+                    "{transform:?}: {value} -> {back}"
+                );
+            }
+        }
+        // Linear values round trip exactly, including negative values
+        for value in [-3.5, 0.0, 1e-20, 7.25] {
+            let linear = DisplayTransform::Linear;
+            assert_eq!(linear.from_display(linear.to_display(value)), value);
+            let percent = DisplayTransform::Percent;
+            assert_eq!(percent.from_display(percent.to_display(value)), value);
+        }
+    }
+
+    #[test]
+    fn formatted_values_round_trip() {
+        for transform in transforms() {
+            let format = ValueFormat {
+                unit: UnitDisplay::default().with_precision(6),
+                transform,
+            };
+            for value in values() {
+                let back = format.parse(&format.format(value)).unwrap();
+                assert!(
+                    (back - value).abs() <= value.abs() * 1e-4 + 1e-6,
+                    "{:?}: {value} -> {} -> {back}",
+                    format.transform,
+                    format.format(value)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decibels_stop_at_the_floor() {
+        let db = DisplayTransform::Decibel { reference: 1.0 };
+        assert_eq!(db.to_display(1.0), 0.0);
+        assert!((db.to_display(0.5) - -6.0206).abs() < 1e-3);
+        for value in [0.0, -0.0, -1.0, 1e-5, f32::MIN_POSITIVE] {
+            assert_eq!(db.to_display(value), DECIBEL_FLOOR);
+            assert!(db.at_floor(value));
+        }
+        assert!(!db.at_floor(0.5));
+        assert!(!DisplayTransform::Percent.at_floor(-1.0));
+        assert!((db.from_display(-200.0) - 1e-4).abs() < 1e-9);
+
+        let format = ValueFormat {
+            unit: UnitDisplay::new("ignored").with_precision(1),
+            transform: db,
+        };
+        assert_eq!(format.format(0.0), "≤ -80.0 dB");
+        assert_eq!(format.format(1.0), "0.0 dB");
+        assert!((format.parse("≤ -80.0 dB").unwrap() - 1e-4).abs() < 1e-9);
+        assert!((format.parse("-6.0206").unwrap() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn custom_transforms_are_used_for_formatting_and_parsing() {
+        let format = ValueFormat {
+            unit: UnitDisplay::default().with_precision(2),
+            transform: DisplayTransform::Custom {
+                to_display: |v| v * v,
+                from_display: f32::sqrt,
+                suffix: "sq".to_string(),
+            },
+        };
+        assert_eq!(format.label(), "sq");
+        assert_eq!(format.format(3.0), "9.00 sq");
+        assert_eq!(format.parse("16 sq").unwrap(), 4.0);
+        assert_eq!(format.parse("-4"), Err(UnitParseError::NotFinite));
+    }
+
+    #[test]
+    fn relative_values_with_transforms() {
+        let percent = ValueFormat {
+            unit: UnitDisplay::default(),
+            transform: DisplayTransform::Percent,
+        };
+        assert_eq!(percent.parse_relative("50 %"), Ok(RelativeValue::Set(0.5)));
+        assert_eq!(
+            percent.parse_relative("*= 2"),
+            Ok(RelativeValue::Multiply(2.0))
+        );
+        assert!(percent.parse_relative("+= 10").is_err());
+
+        let linear = ValueFormat::from(UnitDisplay::new("ms").with_scale(1000.0));
+        assert_eq!(linear.label(), "ms");
+        assert_eq!(
+            linear.parse_relative("+=100 ms"),
+            Ok(RelativeValue::Add(0.1))
+        );
+    }
+}
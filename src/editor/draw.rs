@@ -0,0 +1,494 @@
+use std::{cell::RefCell, sync::Arc};
+
+use egui::{
+    epaint::{CubicBezierShape, RectShape},
+    text::Fonts,
+    Align2, Color32, FontDefinitions, FontId, Galley, Painter, Pos2, Rect, Shape, Stroke,
+};
+
+/// Shape the editor draws on the plot, turned into egui shapes by [DrawCall::into_shapes].
+///
+/// Keeps the shapes the editor needs separate from the egui shape constructors, which change between egui versions.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum DrawCall {
+    LineSegment {
+        points: [Pos2; 2],
+        stroke: Stroke,
+    },
+    /// Polyline through `points`
+    Line {
+        points: Vec<Pos2>,
+        stroke: Stroke,
+    },
+    /// Cubic bezier from its four control points
+    CubicBezier {
+        points: [Pos2; 4],
+        stroke: Stroke,
+    },
+    DashedLine {
+        points: Vec<Pos2>,
+        stroke: Stroke,
+        dash_length: f32,
+        gap_length: f32,
+    },
+    CircleFilled {
+        center: Pos2,
+        radius: f32,
+        color: Color32,
+    },
+    CircleStroke {
+        center: Pos2,
+        radius: f32,
+        stroke: Stroke,
+    },
+    Rect {
+        rect: Rect,
+        rounding: f32,
+        fill: Color32,
+        stroke: Stroke,
+    },
+    ConvexPolygon {
+        points: Vec<Pos2>,
+        fill: Color32,
+        stroke: Stroke,
+    },
+    /// Text laid out by [CanvasPainter::layout_no_wrap], with its top left corner at `pos`
+    Text {
+        pos: Pos2,
+        galley: Arc<Galley>,
+        color: Color32,
+    },
+}
+
+impl DrawCall {
+    pub(crate) fn into_shapes(self) -> Vec<Shape> {
+        match self {
+            DrawCall::LineSegment { points, stroke } => vec![Shape::line_segment(points, stroke)],
+            DrawCall::Line { points, stroke } => vec![Shape::line(points, stroke)],
+            DrawCall::CubicBezier { points, stroke } => vec![CubicBezierShape::from_points_stroke(
+                points,
+                false,
+                Color32::TRANSPARENT,
+                stroke,
+            )
+            .into()],
+            DrawCall::DashedLine {
+                points,
+                stroke,
+                dash_length,
+                gap_length,
+            } => Shape::dashed_line(&points, stroke, dash_length, gap_length),
+            DrawCall::CircleFilled {
+                center,
+                radius,
+                color,
+            } => vec![Shape::circle_filled(center, radius, color)],
+            DrawCall::CircleStroke {
+                center,
+                radius,
+                stroke,
+            } => vec![Shape::circle_stroke(center, radius, stroke)],
+            DrawCall::Rect {
+                rect,
+                rounding,
+                fill,
+                stroke,
+            } => vec![RectShape::new(rect, rounding, fill, stroke).into()],
+            DrawCall::ConvexPolygon {
+                points,
+                fill,
+                stroke,
+            } => vec![Shape::convex_polygon(points, fill, stroke)],
+            DrawCall::Text { pos, galley, color } => vec![Shape::galley(pos, galley, color)],
+        }
+    }
+}
+
+/// Target of [DrawCall]s, an egui [Painter] or a [DrawRecorder]
+pub(crate) trait CanvasPainter {
+    fn draw(&self, call: DrawCall);
+
+    /// Lays out a single line of text for [DrawCall::Text]
+    fn layout_no_wrap(&self, text: String, font_id: FontId, color: Color32) -> Arc<Galley>;
+
+    /// Draws a single line of text, placed at `pos` by `anchor`
+    fn draw_text(
+        &self,
+        pos: Pos2,
+        anchor: Align2,
+        text: impl Into<String>,
+        font_id: FontId,
+        color: Color32,
+    ) {
+        let galley = self.layout_no_wrap(text.into(), font_id, color);
+        let rect = anchor.anchor_size(pos, galley.size());
+        self.draw(DrawCall::Text {
+            pos: rect.min,
+            galley,
+            color,
+        });
+    }
+}
+
+impl CanvasPainter for Painter {
+    fn draw(&self, call: DrawCall) {
+        self.extend(call.into_shapes());
+    }
+
+    fn layout_no_wrap(&self, text: String, font_id: FontId, color: Color32) -> Arc<Galley> {
+        Painter::layout_no_wrap(self, text, font_id, color)
+    }
+}
+
+/// Keeps the calls drawn to it, to check what the editor draws without an egui context
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) struct DrawRecorder {
+    calls: RefCell<Vec<DrawCall>>,
+    fonts: Fonts,
+}
+
+impl Default for DrawRecorder {
+    fn default() -> Self {
+        Self {
+            calls: RefCell::default(),
+            fonts: Fonts::new(1.0, 2048, FontDefinitions::default()),
+        }
+    }
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl DrawRecorder {
+    pub(crate) fn into_calls(self) -> Vec<DrawCall> {
+        self.calls.into_inner()
+    }
+}
+
+impl CanvasPainter for DrawRecorder {
+    fn draw(&self, call: DrawCall) {
+        self.calls.borrow_mut().push(call);
+    }
+
+    fn layout_no_wrap(&self, text: String, font_id: FontId, color: Color32) -> Arc<Galley> {
+        self.fonts.layout_no_wrap(text, font_id, color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::LookupCurveEguiEditor;
+    use crate::{Extrapolation, Knot, KnotInterpolation, LookupCurve, UnitDisplay};
+    use bevy_math::Vec2;
+    use egui::{emath, pos2, Rect};
+
+    fn identity() -> emath::RectTransform {
+        let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 50.0));
+        emath::RectTransform::from_to(rect, rect)
+    }
+
+    #[test]
+    fn preview_points_draw_connected_circles() {
+        let recorder = DrawRecorder::default();
+        let points = [
+            (pos2(0.0, 0.0), Color32::RED),
+            (pos2(10.0, 5.0), Color32::GREEN),
+        ];
+        crate::editor::editor_egui::paint_preview_points(&recorder, points.into_iter());
+        assert_eq!(
+            recorder.into_calls(),
+            [
+                DrawCall::CircleFilled {
+                    center: pos2(0.0, 0.0),
+                    radius: 2.0,
+                    color: Color32::RED,
+                },
+                DrawCall::LineSegment {
+                    points: [pos2(0.0, 0.0), pos2(10.0, 5.0)],
+                    stroke: Stroke::new(1.5, Color32::GREEN),
+                },
+                DrawCall::CircleFilled {
+                    center: pos2(10.0, 5.0),
+                    radius: 2.0,
+                    color: Color32::GREEN,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reference_is_drawn_over_the_view() {
+        let mut editor = LookupCurveEguiEditor::default();
+        editor.editor_size = Vec2::new(100.0, 50.0);
+        let recorder = DrawRecorder::default();
+        editor.paint_reference(&recorder, &identity());
+        assert!(recorder.into_calls().is_empty());
+
+        editor.reference_fn = Some(Box::new(|x| x));
+        let recorder = DrawRecorder::default();
+        editor.paint_reference(&recorder, &identity());
+        let calls = recorder.into_calls();
+        assert_eq!(calls.len(), 1);
+        let DrawCall::DashedLine { points, .. } = &calls[0] else {
+            panic!("expected a dashed line, got {:?}", calls[0]);
+        };
+        // From the bottom left to the top right corner of the plot
+        assert_eq!(points.first(), Some(&pos2(0.0, 50.0)));
+        assert_eq!(points.last(), Some(&pos2(100.0, 0.0)));
+    }
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn views_without_a_domain_have_a_size() {
+        let curves = [
+            LookupCurve::default(),
+            LookupCurve::new(vec![knot(2.0, 3.0, KnotInterpolation::Cubic)]),
+            // Stacked on x, and flat on y
+            LookupCurve::new(vec![
+                knot(1.0, 0.0, KnotInterpolation::Linear),
+                knot(1.0, 2.0, KnotInterpolation::Linear),
+            ]),
+            LookupCurve::new(vec![
+                knot(0.0, 1.0, KnotInterpolation::Linear),
+                knot(4.0, 1.0, KnotInterpolation::Linear),
+            ]),
+        ];
+        for curve in &curves {
+            let editor = LookupCurveEguiEditor::fitted_to_curve(curve);
+            assert!(editor.scale.is_finite() && editor.scale.cmpgt(Vec2::ZERO).all());
+            assert!(editor.offset.is_finite());
+            for knot in curve.knots() {
+                let view_max = editor.offset + editor.scale;
+                assert!(knot.position.cmpgt(editor.offset).all());
+                assert!(knot.position.cmplt(view_max).all());
+            }
+        }
+        // The unit box, with the usual padding
+        let empty = LookupCurveEguiEditor::fitted_to_curve(&curves[0]);
+        assert_eq!(empty.offset, Vec2::splat(-0.2));
+        assert_eq!(empty.scale, Vec2::splat(1.4));
+        let single = LookupCurveEguiEditor::fitted_to_curve(&curves[1]);
+        assert_eq!(single.scale, Vec2::splat(1.4));
+    }
+
+    #[test]
+    fn curves_without_segments_are_drawn_to_the_edges() {
+        let mut editor = LookupCurveEguiEditor::default();
+        editor.editor_size = Vec2::new(100.0, 50.0);
+        let recorder = DrawRecorder::default();
+        editor.paint_curve(&recorder, &identity(), &LookupCurve::default());
+        assert!(recorder.into_calls().is_empty());
+
+        // A single knot is a constant curve, extended to both edges of the view
+        let curve = LookupCurve::new(vec![knot(0.5, 0.5, KnotInterpolation::Cubic)]);
+        let recorder = DrawRecorder::default();
+        editor.paint_curve(&recorder, &identity(), &curve);
+        let calls = recorder.into_calls();
+        let lines: Vec<_> = calls
+            .iter()
+            .map(|call| match call {
+                DrawCall::DashedLine { points, .. } => points.clone(),
+                call => panic!("expected a dashed line, got {call:?}"),
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                vec![pos2(0.0, 25.0), pos2(50.0, 25.0)],
+                vec![pos2(50.0, 25.0), pos2(100.0, 25.0)]
+            ]
+        );
+
+        // Knots beyond the view are not extended, and only the part of the segment in the view is drawn
+        let curve = LookupCurve::new(vec![
+            knot(-1.0, 0.0, KnotInterpolation::Cubic),
+            knot(2.0, 1.0, KnotInterpolation::Linear),
+        ]);
+        let recorder = DrawRecorder::default();
+        editor.paint_curve(&recorder, &identity(), &curve);
+        let calls = recorder.into_calls();
+        assert_eq!(calls.len(), 1);
+        let DrawCall::Line { points, .. } = &calls[0] else {
+            panic!("expected a line, got {:?}", calls[0]);
+        };
+        assert_eq!(points.first().map(|p| p.x), Some(0.0));
+        assert_eq!(points.last().map(|p| p.x), Some(100.0));
+    }
+
+    #[test]
+    fn segments_outside_of_the_view_are_skipped() {
+        let mut editor = LookupCurveEguiEditor::default();
+        editor.editor_size = Vec2::new(100.0, 50.0);
+        let curve = LookupCurve::new(vec![
+            knot(-2.0, 0.0, KnotInterpolation::Linear),
+            knot(-1.0, 1.0, KnotInterpolation::Cubic),
+            knot(0.25, 0.0, KnotInterpolation::Cubic),
+            knot(0.75, 1.0, KnotInterpolation::Cubic),
+            knot(3.0, 0.0, KnotInterpolation::Linear),
+        ]);
+        let recorder = DrawRecorder::default();
+        editor.paint_curve(&recorder, &identity(), &curve);
+        let calls = recorder.into_calls();
+        // The end of the second segment, the whole third and the start of the fourth
+        assert_eq!(calls.len(), 3);
+        assert!(matches!(calls[0], DrawCall::Line { .. }));
+        assert!(matches!(calls[1], DrawCall::CubicBezier { .. }));
+        assert!(matches!(calls[2], DrawCall::Line { .. }));
+    }
+
+    #[test]
+    fn extrapolation_is_drawn_to_the_edges() {
+        let mut editor = LookupCurveEguiEditor::default();
+        editor.editor_size = Vec2::new(100.0, 50.0);
+        let curve = LookupCurve::new(vec![
+            knot(0.25, 0.0, KnotInterpolation::Linear),
+            knot(0.5, 0.5, KnotInterpolation::Linear),
+        ]);
+        let dashed = |curve: &LookupCurve| {
+            let recorder = DrawRecorder::default();
+            editor.paint_curve(&recorder, &identity(), curve);
+            recorder
+                .into_calls()
+                .into_iter()
+                .filter_map(|call| match call {
+                    DrawCall::DashedLine { points, .. } => Some(points),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Straight lines along the slope of the boundary segments
+        let linear = curve
+            .clone()
+            .with_extrapolation(Extrapolation::Linear, Extrapolation::Linear);
+        assert_eq!(
+            dashed(&linear),
+            [
+                vec![pos2(0.0, 75.0), pos2(25.0, 50.0)],
+                vec![pos2(50.0, 25.0), pos2(100.0, -25.0)]
+            ]
+        );
+
+        // Repeats follow the curve, jumping back at every period
+        let repeat = curve.with_extrapolation(Extrapolation::Clamp, Extrapolation::Repeat);
+        let lines = dashed(&repeat);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], [pos2(0.0, 50.0), pos2(25.0, 50.0)]);
+        assert!(lines[1].len() > 2);
+        assert!(lines[1]
+            .iter()
+            .all(|p| (25.0..=50.0).contains(&p.y) && (50.0..=100.0).contains(&p.x)));
+    }
+
+    #[test]
+    fn grid_steps_follow_the_zoom() {
+        let mut editor = LookupCurveEguiEditor::default();
+        editor.editor_size = Vec2::new(400.0, 200.0);
+        let unit = UnitDisplay::default();
+        assert_eq!(editor.grid_steps(&unit, &unit), (0.2, 0.2));
+
+        editor.scale = Vec2::new(1000.0, 1e-3);
+        assert_eq!(editor.grid_steps(&unit, &unit), (200.0, 2e-4));
+
+        // Nice steps of milliseconds, for seconds stored in the curve
+        editor.scale = Vec2::new(0.5, 1.0);
+        let millis = UnitDisplay {
+            unit: "ms".to_string(),
+            scale: 1000.0,
+            precision: 0,
+        };
+        assert_eq!(editor.grid_steps(&millis, &unit).0, 0.1);
+    }
+
+    #[test]
+    fn calls_turn_into_the_matching_egui_shapes() {
+        let points = vec![pos2(0.0, 0.0), pos2(20.0, 0.0)];
+        let stroke = Stroke::new(1.0, Color32::WHITE);
+        let dashed = DrawCall::DashedLine {
+            points: points.clone(),
+            stroke,
+            dash_length: 6.0,
+            gap_length: 4.0,
+        };
+        assert_eq!(
+            dashed.into_shapes(),
+            Shape::dashed_line(&points, stroke, 6.0, 4.0)
+        );
+        let circle = DrawCall::CircleFilled {
+            center: pos2(1.0, 2.0),
+            radius: 3.0,
+            color: Color32::RED,
+        };
+        assert_eq!(
+            circle.into_shapes(),
+            [Shape::circle_filled(pos2(1.0, 2.0), 3.0, Color32::RED)]
+        );
+        let rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(4.0, 2.0));
+        let filled = DrawCall::Rect {
+            rect,
+            rounding: 0.0,
+            fill: Color32::RED,
+            stroke: Stroke::NONE,
+        };
+        assert_eq!(
+            filled.into_shapes(),
+            [Shape::rect_filled(rect, 0.0, Color32::RED)]
+        );
+
+        let recorder = DrawRecorder::default();
+        recorder.draw_text(
+            pos2(10.0, 10.0),
+            egui::Align2::CENTER_CENTER,
+            "0.5",
+            egui::FontId::default(),
+            Color32::WHITE,
+        );
+        let calls = recorder.into_calls();
+        let [DrawCall::Text { pos, galley, .. }] = &calls[..] else {
+            panic!("expected text, got {calls:?}");
+        };
+        assert_eq!(*pos, pos2(10.0, 10.0) - galley.size() / 2.0);
+        assert_eq!(
+            calls[0].clone().into_shapes(),
+            [Shape::galley(*pos, galley.clone(), Color32::WHITE)]
+        );
+    }
+
+    #[test]
+    fn grid_lines_are_labeled() {
+        let mut editor = LookupCurveEguiEditor::default();
+        editor.editor_size = Vec2::new(100.0, 100.0);
+        editor.grid_spacing = Vec2::splat(50.0);
+        let unit = UnitDisplay::default();
+        let recorder = DrawRecorder::default();
+        editor.paint_grid(
+            &recorder,
+            &identity(),
+            &unit,
+            &unit.clone().into(),
+            &egui::FontId::default(),
+        );
+        let calls = recorder.into_calls();
+        let lines = calls
+            .iter()
+            .filter(|call| matches!(call, DrawCall::LineSegment { .. }))
+            .count();
+        let labels: Vec<_> = calls
+            .iter()
+            .filter_map(|call| match call {
+                DrawCall::Text { galley, .. } => Some(galley.text()),
+                _ => None,
+            })
+            .collect();
+        assert!(lines > 0);
+        // Horizontal lines near the bottom are not labeled, to keep clear of the labels of vertical lines
+        assert!(labels.len() <= lines && !labels.is_empty(), "{labels:?}");
+        assert!(labels.contains(&"0.0"), "{labels:?}");
+    }
+}
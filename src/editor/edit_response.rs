@@ -0,0 +1,16 @@
+/// What happened to the curve shown by [LookupCurveEguiEditor::ui](super::LookupCurveEguiEditor::ui) during one
+/// update, for embedding the editor in other egui layouts
+#[derive(Clone, Debug)]
+pub struct CurveEditResponse {
+    /// The curve was modified during this update, by an edit or by undoing or redoing one. Callers keeping the
+    /// curve elsewhere, like in an asset, write it back when this is set.
+    pub changed: bool,
+    /// The curve was saved to the save path of the editor during this update
+    pub saved: bool,
+    /// The curve differs from the last saved state, see
+    /// [LookupCurveEguiEditor::is_dirty](super::LookupCurveEguiEditor::is_dirty). Callers with their own save logic
+    /// call [LookupCurveEguiEditor::mark_saved](super::LookupCurveEguiEditor::mark_saved) after saving.
+    pub dirty: bool,
+    /// Response of the area the curve is drawn in, for hover and context menus of the caller
+    pub response: egui::Response,
+}
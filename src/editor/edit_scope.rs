@@ -0,0 +1,84 @@
+use std::ops::RangeInclusive;
+
+use bevy_math::Vec2;
+
+use crate::LookupCurve;
+
+/// Returns `true` if a knot at `x` can be edited. Knots exactly on the edges of the scope are within it.
+pub(crate) fn in_scope(scope: Option<&RangeInclusive<f32>>, x: f32) -> bool {
+    match scope {
+        Some(scope) => scope.contains(&x),
+        None => true,
+    }
+}
+
+/// Clamps `x` to the scope, used for adding knots and for dragging knots within the scope
+pub(crate) fn clamp_to_scope(scope: Option<&RangeInclusive<f32>>, x: f32) -> f32 {
+    match scope {
+        Some(scope) => x.max(*scope.start()).min(*scope.end()),
+        None => x,
+    }
+}
+
+/// Min and max corner of the part of `curve` within `scope`, including the value of the curve at its edges
+pub(crate) fn scope_bounds(curve: &LookupCurve, scope: &RangeInclusive<f32>) -> (Vec2, Vec2) {
+    let (start, end) = (*scope.start(), *scope.end());
+    let (min_y, max_y) = curve
+        .knots()
+        .iter()
+        .filter(|knot| scope.contains(&knot.position.x))
+        .map(|knot| knot.position.y)
+        .chain([curve.lookup(start), curve.lookup(end)])
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), y| {
+            (min.min(y), max.max(y))
+        });
+    (Vec2::new(start, min_y), Vec2::new(end, max_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+
+    #[test]
+    fn without_scope_everything_is_editable() {
+        assert!(in_scope(None, -1e9));
+        assert_eq!(clamp_to_scope(None, 1e9), 1e9);
+    }
+
+    #[test]
+    fn knots_on_edges_are_in_scope() {
+        let scope = 3600.0..=7200.0;
+        assert!(in_scope(Some(&scope), 3600.0));
+        assert!(in_scope(Some(&scope), 7200.0));
+        assert!(in_scope(Some(&scope), 5000.0));
+        assert!(!in_scope(Some(&scope), 3599.9));
+        assert!(!in_scope(Some(&scope), 86400.0));
+    }
+
+    #[test]
+    fn drags_can_not_cross_edges() {
+        let scope = 3600.0..=7200.0;
+        // Knot on the edge dragged outwards stays on the edge, inwards moves freely
+        assert_eq!(clamp_to_scope(Some(&scope), 3500.0), 3600.0);
+        assert_eq!(clamp_to_scope(Some(&scope), 3700.0), 3700.0);
+        // Drag across the whole scope and beyond
+        assert_eq!(clamp_to_scope(Some(&scope), 90000.0), 7200.0);
+    }
+
+    #[test]
+    fn bounds_include_values_at_edges() {
+        let curve = LookupCurve::new(
+            [(0.0, 0.0), (2.0, 5.0), (4.0, -3.0), (6.0, 1.0)]
+                .into_iter()
+                .map(|(x, y)| Knot {
+                    position: Vec2::new(x, y),
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let (min, max) = scope_bounds(&curve, &(1.0..=3.0));
+        assert_eq!(min, Vec2::new(1.0, 1.0));
+        assert_eq!(max, Vec2::new(3.0, 5.0));
+    }
+}
@@ -1,19 +1,102 @@
+use std::collections::HashMap;
+
 use bevy_app::{App, Plugin, Update};
-use bevy_asset::{Assets, Handle};
-use bevy_ecs::prelude::{Component, Entity, Query, ResMut};
-use bevy_egui::{EguiContexts, EguiPlugin};
+use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::world::World;
+use bevy_ecs::{
+    prelude::{
+        not, resource_exists, Added, Commands, Component, Entity, Event, EventReader, EventWriter,
+        IntoSystemConfigs, Query, Res, ResMut, Resource,
+    },
+    system::{EntityCommands, Local, SystemParam},
+};
+use bevy_egui::{EguiContexts, EguiPlugin, EguiUserTextures};
+use bevy_log::warn;
+use bevy_time::{Real, Time};
+use bevy_window::FileDragAndDrop;
 
-use super::LookupCurveEguiEditor;
+use super::compare::{CompareWrite, SavedCompare};
+use super::curve_scrub::{
+    advance_curve_scrub, emit_curve_scrub_changed, CurveScrub, CurveScrubChanged,
+};
+use super::file_drop::{confirm_curve_drop_ui, open_dropped_curve_files, CurveDropSettings};
+use super::mutation_policy::MutationPolicy;
+#[cfg(feature = "bevy_reflect")]
+use super::target::{sync_editor_targets, LookupCurveEditorTarget};
+use super::{ActionError, ActionOutcome, EditHistory, EditorAction, LookupCurveEguiEditor};
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::CurveSampleHistogram;
+use crate::link::CurveLinks;
 use crate::LookupCurve;
 
-pub(crate) struct EditorPlugin;
+/// Logged once an editor is spawned without `EguiPlugin`, or the panic message with
+/// [LookupCurvePluginOptions::require_egui]
+///
+/// [LookupCurvePluginOptions::require_egui]: crate::LookupCurvePluginOptions::require_egui
+const MISSING_EGUI: &str =
+    "Lookup curve editors need the `EguiPlugin` of `bevy_egui` and are not shown. Add it to \
+    the app, or remove `add_egui(false)` and `require_egui(true)` from the `LookupCurvePlugin`";
+
+/// Added by [LookupCurvePlugin](crate::LookupCurvePlugin), with the options of
+/// [LookupCurvePluginOptions](crate::LookupCurvePluginOptions)
+pub(crate) struct EditorPlugin {
+    pub(crate) add_egui: bool,
+    pub(crate) require_egui: bool,
+}
+
+impl Default for EditorPlugin {
+    fn default() -> Self {
+        Self {
+            add_egui: true,
+            require_egui: false,
+        }
+    }
+}
 
 impl Plugin for EditorPlugin {
     fn build(&self, app: &mut App) {
-        if !app.is_plugin_added::<EguiPlugin>() {
+        if self.add_egui && !app.is_plugin_added::<EguiPlugin>() {
             app.add_plugins(EguiPlugin);
         }
-        app.add_systems(Update, lookup_curve_editor_ui);
+        app.add_event::<FileDragAndDrop>()
+            .add_event::<CurveScrubChanged>()
+            .add_event::<LookupCurveEdited>()
+            .init_resource::<CurveDropSettings>()
+            .init_resource::<CurveEditHistories>()
+            .add_systems(
+                Update,
+                (
+                    open_dropped_curve_files,
+                    advance_curve_scrub,
+                    lookup_curve_editor_ui,
+                    confirm_curve_drop_ui,
+                    emit_curve_scrub_changed,
+                )
+                    .chain()
+                    .run_if(resource_exists::<EguiUserTextures>),
+            )
+            .add_systems(
+                Update,
+                warn_missing_egui.run_if(not(resource_exists::<EguiUserTextures>)),
+            );
+        #[cfg(feature = "bevy_reflect")]
+        app.add_systems(Update, sync_editor_targets.before(lookup_curve_editor_ui));
+    }
+
+    fn finish(&self, app: &mut App) {
+        // All plugins are added by now, also an EguiPlugin added after this one
+        if self.require_egui && !app.is_plugin_added::<EguiPlugin>() {
+            panic!("{MISSING_EGUI}");
+        }
+    }
+}
+
+/// Warns once when editors are spawned while the editor systems can not run, leaving the editors inert
+fn warn_missing_egui(editors: Query<(), Added<LookupCurveEditor>>, mut warned: Local<bool>) {
+    if !*warned && !editors.is_empty() {
+        *warned = true;
+        warn!("{MISSING_EGUI}");
     }
 }
 
@@ -21,46 +104,378 @@ impl Plugin for EditorPlugin {
 #[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
 /// Component for convience of spawning lookup curve editor windows
 ///
-/// Holds a `curve_handle` to the loaded lookup curve asset
+/// Holds a `curve_handle` to the loaded lookup curve asset. The handle should be strong,
+/// so that the asset is kept alive for as long as the editor is open.
 pub struct LookupCurveEditor {
     pub curve_handle: Handle<LookupCurve>,
     pub egui_editor: LookupCurveEguiEditor,
+    /// Shown on the curve, replaced by the scrub position while a [CurveScrub] resource exists
     pub sample: Option<f32>,
+    /// When edits are written to the curve asset. While not yet written, edits are only shown in the editor.
+    pub mutation_policy: MutationPolicy,
+    /// While this key is held over the editor, the curve asset shows the knots of the last saved state, so that the
+    /// running app can be compared to it. Releasing the key restores the edited curve. `None` to disable.
+    ///
+    /// The saved state is read from the save path when the editor opens, or taken from the curve when it is first
+    /// shown without a save path. It is refreshed after saving and when the asset is reloaded.
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub compare_key: Option<egui::Key>,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    compare: SavedCompare,
+}
+
+/// Undo histories of the curve assets edited by [LookupCurveEditor]s, see [LookupCurveEguiEditor::history].
+///
+/// The history of a curve is kept until the curve asset is removed, so that it survives closing and respawning its
+/// editors. Editors only hold the history while they are shown, and edits applied to an editor in between are
+/// added to the history the next time it is shown.
+#[derive(Resource, Default)]
+pub struct CurveEditHistories(HashMap<AssetId<LookupCurve>, EditHistory>);
+
+impl CurveEditHistories {
+    /// The history of a curve asset, if it was edited in an editor
+    pub fn get(&self, curve: AssetId<LookupCurve>) -> Option<&EditHistory> {
+        self.0.get(&curve)
+    }
+
+    /// The history of a curve asset, if it was edited in an editor
+    pub fn get_mut(&mut self, curve: AssetId<LookupCurve>) -> Option<&mut EditHistory> {
+        self.0.get_mut(&curve)
+    }
+}
+
+/// Sent when an editor writes its edits to the curve asset, as often as its [MutationPolicy] allows
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct LookupCurveEdited {
+    pub editor: Entity,
+    pub curve: AssetId<LookupCurve>,
 }
 
 impl LookupCurveEditor {
     /// Constructs a [LookupCurveEditor] with the supplied `curve_handle`.
+    ///
+    /// In debug builds this panics if the handle is weak, as the asset could be dropped while the editor is open.
     pub fn new(curve_handle: Handle<LookupCurve>) -> Self {
+        debug_assert!(
+            curve_handle.is_strong(),
+            "LookupCurveEditor needs a strong handle to keep the curve alive"
+        );
         Self {
             curve_handle,
             egui_editor: LookupCurveEguiEditor::default(),
             sample: None,
+            mutation_policy: MutationPolicy::default(),
+            compare_key: Some(egui::Key::Backtick),
+            compare: SavedCompare::default(),
         }
     }
 
     /// Constructs a [LookupCurveEditor] with the supplied `curve_handle` and `path` as save path.
     pub fn with_save_path(curve_handle: Handle<LookupCurve>, path: String) -> Self {
         Self {
-            egui_editor: LookupCurveEguiEditor {
-                ron_path: Some(path),
-                ..Default::default()
-            },
+            egui_editor: LookupCurveEguiEditor::with_save_path(path),
             ..LookupCurveEditor::new(curve_handle)
         }
     }
+
+    /// Applies `action` to `curve`, see [LookupCurveEguiEditor::apply_action].
+    ///
+    /// Systems without access to the curve asset can push actions to the `pending_actions` of [Self::egui_editor]
+    /// instead, they are applied the next time the editor is shown.
+    pub fn apply_action(
+        &mut self,
+        action: EditorAction,
+        curve: &mut LookupCurve,
+    ) -> Result<ActionOutcome, ActionError> {
+        self.egui_editor.apply_action(action, curve)
+    }
+
+    /// Sets [Self::mutation_policy]
+    pub fn with_mutation_policy(mut self, policy: MutationPolicy) -> Self {
+        self.mutation_policy = policy;
+        self
+    }
+
+    /// Sets [Self::compare_key]
+    pub fn with_compare_key(mut self, key: Option<egui::Key>) -> Self {
+        self.compare_key = key;
+        self
+    }
+}
+
+/// Extension trait for spawning lookup curve editors with [Commands]
+pub trait LookupCurveCommandsExt {
+    /// Spawns an entity with a [LookupCurveEditor] for the curve asset
+    fn spawn_curve_editor(&mut self, curve_handle: Handle<LookupCurve>) -> EntityCommands<'_>;
+
+    /// Spawns an entity with a [LookupCurveEditor] for a curve that is not only an asset, like a field of a
+    /// component, see [LookupCurveEditorTarget]
+    #[cfg(feature = "bevy_reflect")]
+    fn spawn_curve_editor_for(&mut self, target: LookupCurveEditorTarget) -> EntityCommands<'_>;
+}
+
+impl LookupCurveCommandsExt for Commands<'_, '_> {
+    fn spawn_curve_editor(&mut self, curve_handle: Handle<LookupCurve>) -> EntityCommands<'_> {
+        self.spawn(LookupCurveEditor::new(curve_handle))
+    }
+
+    #[cfg(feature = "bevy_reflect")]
+    fn spawn_curve_editor_for(&mut self, target: LookupCurveEditorTarget) -> EntityCommands<'_> {
+        if let LookupCurveEditorTarget::Asset(handle) = &target {
+            return self.spawn((LookupCurveEditor::new(handle.clone()), target));
+        }
+        let mut entity = self.spawn_empty();
+        // The editor edits a copy of the target in an asset of its own
+        entity.add(move |entity: Entity, world: &mut World| {
+            let handle = world
+                .resource_mut::<Assets<LookupCurve>>()
+                .add(LookupCurve::default());
+            world
+                .entity_mut(entity)
+                .insert((LookupCurveEditor::new(handle), target));
+        });
+        entity
+    }
+}
+
+/// [SystemParam] for creating lookup curve assets at runtime, and optionally opening editors for them
+///
+/// The returned handles are strong, make sure to keep them around for as long as the curve is needed.
+#[derive(SystemParam)]
+pub struct Curves<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub assets: ResMut<'w, Assets<LookupCurve>>,
+}
+
+impl Curves<'_, '_> {
+    /// Adds `curve` as an asset
+    pub fn add(&mut self, curve: impl Into<LookupCurve>) -> Handle<LookupCurve> {
+        self.assets.add(curve)
+    }
+
+    /// Adds `curve` as an asset, and spawns an entity with a [LookupCurveEditor] for it
+    pub fn add_with_editor(
+        &mut self,
+        curve: impl Into<LookupCurve>,
+    ) -> (Handle<LookupCurve>, Entity) {
+        let handle = self.assets.add(curve);
+        let entity = self.commands.spawn_curve_editor(handle.clone()).id();
+        (handle, entity)
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn lookup_curve_editor_ui(
     mut editors: Query<(Entity, &mut LookupCurveEditor)>,
     mut contexts: EguiContexts,
     mut curves: ResMut<Assets<LookupCurve>>,
+    mut edited: EventWriter<LookupCurveEdited>,
+    mut asset_events: EventReader<AssetEvent<LookupCurve>>,
+    mut histories: ResMut<CurveEditHistories>,
+    time: Res<Time<Real>>,
+    mut scrub: Option<ResMut<CurveScrub>>,
+    mut links: Option<ResMut<CurveLinks>>,
+    #[cfg(feature = "diagnostics")] mut sample_heat: Option<ResMut<CurveSampleHistogram>>,
 ) {
+    // Writes of the editors only modify the assets, loads are reloads from disk
+    let mut reloaded = Vec::new();
+    for event in asset_events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } => reloaded.push(*id),
+            AssetEvent::Removed { id } => {
+                histories.0.remove(id);
+            }
+            _ => {}
+        }
+    }
+
     for (entity, mut editor) in &mut editors {
-        if let Some(curve) = curves.get_mut(&editor.curve_handle) {
-            let sample = editor.sample;
-            editor
-                .egui_editor
-                .ui_window(contexts.ctx_mut(), entity, curve, sample);
+        let editor = &mut *editor;
+        let relation = links
+            .as_deref()
+            .and_then(|links| links.derived_by(&editor.curve_handle))
+            .map(|link| link.relation);
+        editor.egui_editor.set_derived(relation);
+
+        #[cfg(feature = "diagnostics")]
+        if let Some(sample_heat) = sample_heat.as_deref() {
+            let heat = sample_heat.snapshot(&editor.curve_handle);
+            editor.egui_editor.set_sample_heat(heat);
+        }
+
+        // Edit a copy, so that the asset is only modified when the mutation policy allows it
+        if let Some(asset) = curves.get(&editor.curve_handle) {
+            if reloaded.contains(&editor.curve_handle.id()) {
+                editor.compare.reloaded(asset);
+            }
+            let id = editor.curve_handle.id();
+            let limit = editor.egui_editor.history.limit;
+            if let Some(mut history) = histories.0.remove(&id) {
+                history.limit = limit;
+                history.append(std::mem::take(&mut editor.egui_editor.history));
+                editor.egui_editor.history = history;
+            }
+            let mut pending = std::mem::take(&mut editor.egui_editor.pending);
+            // While the asset shows the saved state, the edited curve is only kept in the editor
+            pending.set_detached(editor.compare.is_showing_saved());
+            let curve = pending.working(asset);
+            match scrub.as_deref_mut() {
+                Some(CurveScrub {
+                    playback,
+                    track,
+                    snap_to_samples,
+                    ..
+                }) => {
+                    editor.egui_editor.ui_window_with_scrub(
+                        contexts.ctx_mut(),
+                        entity,
+                        curve,
+                        playback,
+                        track.as_ref(),
+                        snap_to_samples,
+                    );
+                }
+                None => {
+                    let sample = editor.sample;
+                    editor
+                        .egui_editor
+                        .ui_window(contexts.ctx_mut(), entity, curve, sample);
+                }
+            }
+
+            let mut compare_write = None;
+            let saved_generation = editor.egui_editor.saved_generation();
+            if editor.compare.needs_refresh(saved_generation) {
+                // Read from disk when the editor opens, afterwards only the editor saves
+                let saved = match (editor.compare.saved(), &editor.egui_editor.ron_path) {
+                    (None, Some(path)) => LookupCurve::load_from_file(path).ok(),
+                    _ => None,
+                };
+                let saved = saved.unwrap_or_else(|| curve.clone());
+                compare_write = editor.compare.refresh(saved, saved_generation, curve);
+            }
+            let ctx = contexts.ctx_mut();
+            let held = editor.compare_key.is_some_and(|key| {
+                ctx.input(|input| input.key_down(key))
+                    && ctx.memory(|memory| memory.focused().is_none())
+                    && (editor.compare.is_showing_saved() || editor.egui_editor.is_hovered(ctx))
+            });
+            compare_write = editor.compare.set_held(held, curve).or(compare_write);
+
+            let interacting = editor.egui_editor.is_interacting(ctx);
+            let now = time.elapsed();
+            let write = match compare_write {
+                Some(CompareWrite::ShowSaved(saved)) => {
+                    if let Some(asset) = curves.get_mut(&editor.curve_handle) {
+                        *asset = *saved;
+                    }
+                    None
+                }
+                Some(CompareWrite::RestoreEdited) => {
+                    pending.set_detached(false);
+                    pending.write_now(now)
+                }
+                None => pending.take_write(editor.mutation_policy, interacting, now),
+            };
+            if let Some(curve) = write {
+                if let Some(asset) = curves.get_mut(&editor.curve_handle) {
+                    *asset = curve.clone();
+                    edited.send(LookupCurveEdited {
+                        editor: entity,
+                        curve: editor.curve_handle.id(),
+                    });
+                }
+            }
+            editor.egui_editor.pending = pending;
+            let history =
+                std::mem::replace(&mut editor.egui_editor.history, EditHistory::new(limit));
+            histories.0.insert(id, history);
+        }
+
+        if let Some(links) = links.as_deref_mut() {
+            if editor.egui_editor.take_detach_link() {
+                links.detach(&editor.curve_handle);
+            }
         }
+
+        #[cfg(feature = "diagnostics")]
+        if let Some(sample_heat) = sample_heat.as_deref_mut() {
+            if editor.egui_editor.take_clear_sample_heat() {
+                sample_heat.clear(&editor.curve_handle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::{AssetApp, AssetPlugin, MinimalPlugins};
+    use bevy_ecs::system::{Resource, RunSystemOnce};
+
+    #[derive(Resource)]
+    struct Spawned(Handle<LookupCurve>, Entity);
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<LookupCurve>();
+        app
+    }
+
+    fn add_with_editor(mut curves: Curves, mut commands: Commands) {
+        let (handle, entity) = curves.add_with_editor(LookupCurve::default().with_name("a"));
+        commands.insert_resource(Spawned(handle, entity));
+    }
+
+    #[test]
+    fn curves_param_adds_asset_and_editor() {
+        let mut app = test_app();
+        app.world_mut().run_system_once(add_with_editor);
+        app.update();
+
+        let Spawned(handle, entity) = app.world_mut().remove_resource::<Spawned>().unwrap();
+        let curves = app.world().resource::<Assets<LookupCurve>>();
+        assert_eq!(curves.get(&handle).unwrap().name.as_deref(), Some("a"));
+        let editor = app.world().get::<LookupCurveEditor>(entity).unwrap();
+        assert_eq!(editor.curve_handle, handle);
+    }
+
+    #[test]
+    fn spawn_curve_editor_spawns_editor() {
+        let mut app = test_app();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<LookupCurve>>()
+            .add(LookupCurve::default());
+        let handle_clone = handle.clone();
+        app.world_mut()
+            .run_system_once(move |mut commands: Commands| {
+                commands.spawn_curve_editor(handle_clone.clone());
+            });
+        app.update();
+
+        let mut editors = app.world_mut().query::<&LookupCurveEditor>();
+        let editor = editors.single(app.world());
+        assert_eq!(editor.curve_handle, handle);
+    }
+
+    #[test]
+    fn editor_keeps_curve_alive() {
+        let mut app = test_app();
+        app.world_mut().run_system_once(add_with_editor);
+        app.update();
+
+        let Spawned(handle, entity) = app.world_mut().remove_resource::<Spawned>().unwrap();
+        let id = handle.id();
+        drop(handle);
+        app.update();
+        app.update();
+        assert!(app.world().resource::<Assets<LookupCurve>>().contains(id));
+
+        app.world_mut().despawn(entity);
+        app.update();
+        app.update();
+        assert!(!app.world().resource::<Assets<LookupCurve>>().contains(id));
     }
 }
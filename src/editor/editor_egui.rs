@@ -1,52 +1,299 @@
+use std::ops::RangeInclusive;
+
 use bevy_math::Vec2;
-use egui::{
-    emath, epaint::CubicBezierShape, Color32, Frame, Id, Painter, Pos2, Rect, Sense, Shape, Stroke,
-    Ui,
+use egui::{emath, Color32, Frame, Id, Pos2, Rect, Sense, Stroke, Ui};
+
+use super::action::EditorAction;
+use super::display_transform::{DisplayTransform, ValueFormat};
+use super::draw::{CanvasPainter, DrawCall};
+use super::edit_response::CurveEditResponse;
+use super::edit_scope::{in_scope, scope_bounds};
+use super::footer::{footer_entries, FooterStats, FooterStatsCache};
+use super::guides::{GuideAxis, Guides};
+use super::history::EditHistory;
+use super::multi_edit::{KnotEdit, Shared};
+use super::mutation_policy::PendingCurve;
+use super::render_cache::{label_font, style_key, RebuildBudget, TickLabelCache};
+use super::sample_trail::{SampleTrail, TrailPoint};
+use super::segment_drag::{segment_drag_deltas, SegmentDrag, SegmentDragEvent, SegmentGrab};
+use crate::curve::sample_adaptive;
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::HistogramSnapshot;
+use crate::link::LinkRelation;
+use crate::sample::KnotSearch;
+use crate::scrub::{RecordedTrack, ScrubPlayback};
+use crate::ticks::{display_step, grid_lines, step_precision};
+#[cfg(feature = "ron")]
+use crate::CurveFormat;
+use crate::{
+    quantize_position, Extrapolation, IdStrategy, Knot, KnotInterpolation, LookupCurve, MergeY,
+    SmoothPolicy, TangentMode, TangentSide, UnitDisplay,
 };
 
-use crate::{Knot, KnotInterpolation, LookupCurve, TangentMode, TangentSide};
-
 #[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
 /// Lookup curve editor implemented using `egui`.
 ///
 /// Holds the editor state.
 pub struct LookupCurveEguiEditor {
+    /// Curve space at the bottom left corner of the view
     pub offset: Vec2,
+    /// Size of the view in curve space
     pub scale: Vec2,
 
-    pub grid_step_x: f32,
-    pub grid_step_y: f32,
+    /// Smallest distance in points between grid lines along x and y. The grid steps follow the zoom, picking 1, 2 or
+    /// 5 times a power of ten of the displayed values.
+    pub grid_spacing: Vec2,
 
     pub editor_size: Vec2,
     pub hover_point: Vec2,
 
+    /// Path the curve is saved to, in [Self::save_format]
     #[cfg(feature = "ron")]
     pub ron_path: Option<String>,
+    /// Format the curve is saved in, the format it was loaded in. `None` for the format of the extension of
+    /// [Self::ron_path].
+    #[cfg(feature = "ron")]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub save_format: Option<CurveFormat>,
+
+    /// How y values are shown and typed, for example in decibels. The curve keeps storing linear values.
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub y_display_transform: DisplayTransform,
+
+    /// Which statistics to show in the footer, set to [FooterStats::empty] to hide the footer
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub footer_stats: FooterStats,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    footer_stats_cache: FooterStatsCache,
+
+    /// When dragging the curve between two knots, move the knot nearer to the pointer more than the other
+    pub segment_drag_falloff: bool,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    segment_drag: SegmentDrag,
+
+    /// When dragging the last knot, stretch the knots before it proportionally, as if the drag started with alt held.
+    /// See [StretchMode::Proportional](crate::StretchMode::Proportional).
+    pub stretch_with_last_knot: bool,
+    /// Id of the knot being dragged, if the drag started with alt held
+    stretch_drag: Option<usize>,
+    /// Corners of the selection rectangle being dragged, where the drag started and the pointer
+    box_select: Option<(Vec2, Vec2)>,
+    /// Set while dragging, when [LookupCurve::quantize_storage] is only applied once the drag completes
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub(crate) defer_quantize: bool,
+
+    /// Round positions of created knots, and of dragged knots once the drag completes, to multiples of this step
+    pub round_new_positions_to: Option<f32>,
+    /// How ids of knots created in the editor are chosen
+    pub id_strategy: IdStrategy,
+
+    /// Restricts editing to knots with x within this range. Knots outside are dimmed and can not be edited,
+    /// new knots are clamped to the range, and dragged knots can not leave it.
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub edit_scope: Option<RangeInclusive<f32>>,
+
+    /// Number of recent samples drawn as a fading trail behind the sample, set to 0 to hide the trail
+    pub sample_trail_length: usize,
+    /// Seconds until a point in the sample trail has faded out, set to 0 to not fade
+    pub sample_trail_fade: f32,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    sample_trail: SampleTrail,
+
+    /// Color knots by [Knot::modified_at], from gray for the least recently modified knot to green for the most
+    /// recently modified one. Only used for curves with [LookupCurve::track_edit_times].
+    pub show_knot_age: bool,
+
+    /// Mark knots where the slope of the curve jumps, see [LookupCurve::c1_discontinuities]. Right click a mark to
+    /// smooth the knot.
+    pub show_smoothness: bool,
+    /// Slope difference at a knot above which it is marked, with [Self::show_smoothness]
+    pub smoothness_tolerance: f32,
+
+    /// Function drawn behind the curve as a dashed line, for matching the curve to it. Never edited.
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub reference_fn: Option<Box<dyn Fn(f32) -> f32 + Send + Sync>>,
+    /// Compare the curve to [Self::reference_fn] only within the view, instead of over the domain of the curve
+    pub fit_error_in_view: bool,
+    /// Max difference in y to the reference for [EditorAction::FitToReference] from the UI
+    pub reference_fit_tolerance: f32,
+
+    /// Guide lines, created by clicking the rulers along the bottom and left edges of the plot
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub guides: Guides,
+    /// Snap dragged knots to [Self::guides] within a few points of the pointer
+    pub snap_to_guides: bool,
+    /// Snap dragged knots to multiples of [Self::grid_snap_step]. Holding command while dragging toggles snapping.
+    pub snap_to_grid: bool,
+    /// Steps in curve space along x and y for [Self::snap_to_grid]. An axis with a step of 0 is not snapped.
+    pub grid_snap_step: Vec2,
+
+    /// Ids of the selected knots. Click a knot to select it, shift or ctrl click to add it to or remove it from the
+    /// selection. Drag empty space with shift held to add the knots in a rectangle to the selection, or with ctrl to
+    /// select only them. When several knots are selected, their shared properties can be edited together, dragging
+    /// one of them moves all of them, and Delete deletes them.
+    pub selected_knots: Vec<usize>,
+    /// Text of the y field for editing the selected knots
+    multi_edit_y: String,
+
+    /// How the curve is generated from another curve, see [Self::set_derived]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub(crate) derived: Option<LinkRelation>,
+    pub(crate) detach_link: bool,
+
+    /// Actions applied to the curve the next time the editor is shown, before any interaction in that frame.
+    ///
+    /// Used to script the editor, see [Self::apply_action] to apply actions immediately.
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub pending_actions: Vec<EditorAction>,
+
+    /// Undo and redo history of the edits of the curve. Undo with command+Z or the undo button, redo with
+    /// command+shift+Z or the redo button. `LookupCurveEditor`s keep the history of each curve asset, so that it
+    /// survives respawning the editor.
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub history: EditHistory,
+
+    /// Tint the plot by how often each part of the curve is sampled, see [Self::set_sample_heat]
+    #[cfg(feature = "diagnostics")]
+    pub show_sample_heat: bool,
+    #[cfg(feature = "diagnostics")]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    sample_heat: Option<HistogramSnapshot>,
+    #[cfg(feature = "diagnostics")]
+    pub(crate) clear_sample_heat: bool,
+
+    /// [LookupCurve::generation] when the curve was last saved or first shown, see [Self::is_dirty]
+    saved_generation: Option<u64>,
+    /// Set when the curve is saved to the save path, read and cleared by [Self::ui] for [CurveEditResponse::saved]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub(crate) saved_in_update: bool,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    error: Option<EditorError>,
+    /// Screen rect of the window, as of the last [Self::ui_window]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    pub(crate) window_rect: Option<Rect>,
+    /// Copy of the edited curve asset, written according to the mutation policy of the `LookupCurveEditor`
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    #[cfg_attr(not(feature = "editor_bevy"), allow(dead_code))]
+    pub(crate) pending: PendingCurve,
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    tick_labels: TickLabelCache,
+    /// Height kept free below the plot, for controls shown after [Self::ui] in the same window
+    reserved_height: f32,
 }
 
+/// Error message shown at the top of the editor
+#[derive(Clone, Debug)]
+struct EditorError {
+    message: String,
+    /// egui time when the message was first shown
+    shown_at: Option<f64>,
+}
+
+/// Radius around knots and tangent handles that can be interacted with
+const KNOT_RADIUS: f32 = 8.0;
+/// Max distance from the curve for grabbing it between knots
+const CURVE_GRAB_DISTANCE: f32 = 6.0;
+/// Seconds an error message is shown in the editor
+const ERROR_DURATION: f64 = 5.0;
+/// Height of the recorded track below the plot when scrubbing
+const SCRUB_TRACK_HEIGHT: f32 = 60.0;
+/// Width of the rulers along the bottom and left edges of the plot, which create guides when clicked
+const RULER_SIZE: f32 = 16.0;
+/// Max distance from a guide for dragged knots to snap to it, and for grabbing it
+const GUIDE_SNAP_DISTANCE: f32 = 6.0;
+/// Color of the reference function
+const REFERENCE_COLOR: Color32 = Color32::LIGHT_BLUE;
+/// Number of samples comparing the curve to the reference function
+const FIT_ERROR_SAMPLES: usize = 256;
+/// Zoom per point scrolled, as the exponent of the zoom factor
+const ZOOM_SPEED: f32 = 0.002;
+
 impl Default for LookupCurveEguiEditor {
     fn default() -> Self {
         Self {
             offset: Vec2::ZERO,
             scale: Vec2::new(1.0, 1.0),
 
-            grid_step_x: 0.1,
-            grid_step_y: 0.1,
+            grid_spacing: Vec2::new(80.0, 40.0),
 
             editor_size: Vec2::ZERO,
             hover_point: Vec2::ZERO,
 
             #[cfg(feature = "ron")]
             ron_path: None,
+            #[cfg(feature = "ron")]
+            save_format: None,
+
+            y_display_transform: DisplayTransform::Linear,
+
+            footer_stats: FooterStats::default(),
+            footer_stats_cache: FooterStatsCache::default(),
+
+            segment_drag_falloff: false,
+            segment_drag: SegmentDrag::default(),
+            defer_quantize: false,
+
+            stretch_with_last_knot: false,
+            stretch_drag: None,
+            box_select: None,
+
+            round_new_positions_to: None,
+            id_strategy: IdStrategy::default(),
+
+            edit_scope: None,
+
+            sample_trail_length: 0,
+            sample_trail_fade: 1.0,
+            sample_trail: SampleTrail::default(),
+
+            show_knot_age: false,
+
+            show_smoothness: false,
+            smoothness_tolerance: 1e-3,
+
+            reference_fn: None,
+            fit_error_in_view: false,
+            reference_fit_tolerance: 1e-3,
+
+            guides: Guides::default(),
+            snap_to_guides: true,
+            snap_to_grid: false,
+            grid_snap_step: Vec2::splat(0.05),
+
+            selected_knots: Vec::new(),
+            multi_edit_y: String::new(),
+
+            derived: None,
+            detach_link: false,
+
+            pending_actions: Vec::new(),
+            history: EditHistory::default(),
+
+            #[cfg(feature = "diagnostics")]
+            show_sample_heat: true,
+            #[cfg(feature = "diagnostics")]
+            sample_heat: None,
+            #[cfg(feature = "diagnostics")]
+            clear_sample_heat: false,
+
+            saved_generation: None,
+            saved_in_update: false,
+            error: None,
+            window_rect: None,
+            pending: PendingCurve::default(),
+            tick_labels: TickLabelCache::default(),
+            reserved_height: 0.0,
         }
     }
 }
 
 impl LookupCurveEguiEditor {
-    /// Constructs a [LookupCurveEguiEditor] with the supplied `path` as save path.
+    /// Constructs a [LookupCurveEguiEditor] with the supplied `path` as save path, saving in the format of its
+    /// extension.
     #[cfg(feature = "ron")]
     pub fn with_save_path(path: String) -> Self {
         Self {
+            save_format: CurveFormat::from_path(&path),
             ron_path: Some(path),
             ..Default::default()
         }
@@ -60,21 +307,31 @@ impl LookupCurveEguiEditor {
     }
 
     /// Fits the editor viewport to the supplied [LookupCurve] by updating scale and offset.
+    ///
+    /// Fits the part of the curve within [Self::edit_scope] if set. Empty curves are fitted to the unit box, and
+    /// axes the knots do not span, like both axes of a single knot, get a width of 1 around the knots.
     pub fn fit_to_curve(&mut self, curve: &LookupCurve) {
+        if let Some(scope) = &self.edit_scope {
+            let (min, max) = scope_bounds(curve, scope);
+            // Avoid a zero sized view for flat curves or empty scopes
+            let diff = (max - min).max(Vec2::splat(1e-3));
+            self.offset = min - 0.2 * diff;
+            self.scale = diff * 1.4;
+            return;
+        }
+
         let knots = curve.knots();
         let (min, max) = match knots.len() {
             0 => (Vec2::ZERO, Vec2::ONE),
-            1 => {
-                let pos = knots[0].position;
-                let padding = Vec2::splat(0.5);
-                (pos - padding, pos + padding)
-            }
             _ => knots
                 .iter()
                 .fold((Vec2::INFINITY, Vec2::NEG_INFINITY), |(min, max), knot| {
                     (min.min(knot.position), max.max(knot.position))
                 }),
         };
+        // The view transform divides by the size of the view
+        let padding = Vec2::select(max.cmpgt(min), Vec2::ZERO, Vec2::splat(0.5));
+        let (min, max) = (min - padding, max + padding);
 
         let diff = max - min;
 
@@ -95,14 +352,15 @@ impl LookupCurveEguiEditor {
         emath::Vec2::new(canvas.x, -canvas.y)
     }
 
+    // The canvas has no size before the first frame, or when there is no space left for it
     fn canvas_to_curve(&self, canvas: Pos2) -> Vec2 {
         let canvas = Vec2::new(canvas.x, self.editor_size.y - canvas.y);
-        self.offset + canvas / self.editor_size * self.scale
+        self.offset + canvas / self.editor_size.max(Vec2::ONE) * self.scale
     }
 
     fn canvas_to_curve_vec(&self, canvas: emath::Vec2) -> Vec2 {
         let canvas = Vec2::new(canvas.x, -canvas.y);
-        canvas / self.editor_size * self.scale
+        canvas / self.editor_size.max(Vec2::ONE) * self.scale
     }
 
     /// Display the editor in a window
@@ -118,45 +376,215 @@ impl LookupCurveEguiEditor {
         sample: Option<f32>,
     ) -> bool {
         let mut changed = false;
-        egui::Window::new(curve.name_or_default())
+        let response = egui::Window::new(curve.name_or_default())
             .id(Id::new(id))
             .show(ctx, |ui| {
-                changed = self.ui(ui, curve, sample);
+                changed = self.ui(ui, curve, sample).changed;
             });
+        self.window_rect = response.map(|response| response.response.rect);
         changed
     }
 
-    /// Display the editor
+    /// Display the editor in a window, with [Self::scrub_ui] below the plot.
+    ///
+    /// The position of `playback` is displayed as the sample.
+    ///
+    /// Returns `true` if the curve was changed, and `true` if the scrub position was changed from the UI.
+    pub fn ui_window_with_scrub(
+        &mut self,
+        ctx: &mut egui::Context,
+        id: impl std::hash::Hash,
+        curve: &mut LookupCurve,
+        playback: &mut ScrubPlayback,
+        track: Option<&RecordedTrack>,
+        snap_to_samples: &mut bool,
+    ) -> (bool, bool) {
+        let (mut changed, mut scrubbed) = (false, false);
+        let response = egui::Window::new(curve.name_or_default())
+            .id(Id::new(id))
+            .show(ctx, |ui| {
+                self.reserved_height = SCRUB_TRACK_HEIGHT
+                    + ui.spacing().interact_size.y
+                    + 4.0 * ui.spacing().item_spacing.y;
+                changed = self.ui(ui, curve, Some(playback.x())).changed;
+                self.reserved_height = 0.0;
+                scrubbed = self.scrub_ui(ui, curve, playback, track, snap_to_samples);
+            });
+        self.window_rect = response.map(|response| response.response.rect);
+        (changed, scrubbed)
+    }
+
+    /// Display the editor in `ui`, filling the available space. Only egui and the curve are needed, so the editor can
+    /// be embedded in any egui layout, like a panel or a tab of a docking layout.
     ///
     /// If a `sample` is supplied, it will be displayed as a red dot on the curve.
     ///
-    /// Returns `true` if the curve was changed during this update
-    pub fn ui(&mut self, ui: &mut Ui, curve: &mut LookupCurve, sample: Option<f32>) -> bool {
+    /// Scrolling zooms around the pointer, only along x while command is held and only along y while shift is held.
+    /// Dragging empty space, dragging with the middle button or dragging with space held pans the view, and F fits
+    /// the view to the knots.
+    ///
+    /// Returns whether the curve was changed or saved during this update, see [CurveEditResponse]
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        curve: &mut LookupCurve,
+        sample: Option<f32>,
+    ) -> CurveEditResponse {
+        let x_unit = curve.x_unit.clone().unwrap_or_default();
+        let y_unit = ValueFormat {
+            unit: curve.y_unit.clone().unwrap_or_default(),
+            transform: self.y_display_transform.clone(),
+        };
+        let slope_unit = slope_unit(&x_unit, &y_unit.unit);
+
+        if self.saved_generation.is_none() {
+            self.saved_generation = Some(curve.generation());
+        }
+        self.saved_in_update = false;
+
+        // Edits while the pointer is held, like during a drag, are undone together. The release still belongs to
+        // the drag, as dragged knots are rounded when they are released.
+        let pointer_held =
+            ui.input(|input| input.pointer.any_down() || input.pointer.any_released());
+        self.history.set_coalescing(pointer_held);
+        // Dragged knots follow the pointer exactly, and are rounded to the rule of the curve once released
+        self.defer_quantize = ui.input(|input| input.pointer.is_decidedly_dragging());
+
+        let mut changed = false;
+        let pending = std::mem::take(&mut self.pending_actions);
+        changed |= self.apply_actions(curve, pending);
+
+        self.error_ui(ui);
+
+        // Rebuild caches after style or scale changes, staggered over frames when there are multiple editors
+        let ctx = ui.ctx().clone();
+        if self
+            .tick_labels
+            .sync_style(style_key(ui.style(), ctx.pixels_per_point()), || {
+                RebuildBudget::acquire_in(&ctx)
+            })
+        {
+            ctx.request_repaint();
+        }
+        let font_id = label_font(ui.style());
+
         ui.label(format!(
             "x = {}, y = {}",
-            self.hover_point.x, self.hover_point.y
+            x_unit.format(self.hover_point.x),
+            y_unit.format(self.hover_point.y)
         ));
 
-        let mut changed = false;
+        let mut actions = Vec::new();
 
-        #[cfg(feature = "ron")]
-        if self.ron_path.is_some() && ui.button("Save").clicked() {
-            if let Err(e) = curve.save_to_file(self.ron_path.as_ref().unwrap().as_str()) {
-                #[cfg(feature = "bevy_app")]
-                bevy_log::error!("Failed to save curve {}", e);
-                #[cfg(not(feature = "bevy_app"))]
-                println!("Failed to save curve {}", e);
-            } else {
-                #[cfg(feature = "bevy_app")]
-                bevy_log::info!("Curve saved successfully.");
-                #[cfg(not(feature = "bevy_app"))]
-                println!("Curve saved successfully.");
+        ui.horizontal(|ui| {
+            #[cfg(feature = "ron")]
+            if self.ron_path.is_some() && ui.button("Save").clicked() {
+                actions.push(EditorAction::Save);
+            }
+            let undo = ui
+                .add_enabled(self.history.undo_len() > 0, egui::Button::new("Undo"))
+                .on_hover_text("Undo the last edit (command+Z)");
+            if undo.clicked() {
+                actions.push(EditorAction::Undo);
+            }
+            let redo = ui
+                .add_enabled(self.history.redo_len() > 0, egui::Button::new("Redo"))
+                .on_hover_text("Redo the last undone edit (command+shift+Z)");
+            if redo.clicked() {
+                actions.push(EditorAction::Redo);
+            }
+            if ui
+                .button("Frame")
+                .on_hover_text("Fit the view to the knots (F)")
+                .clicked()
+            {
+                actions.push(EditorAction::FitToCurve);
+            }
+        });
+
+        if let Some(relation) = self.derived {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    Color32::LIGHT_BLUE,
+                    format!("Auto-generated ({relation}) from another curve"),
+                )
+                .on_hover_text("Edits are disabled, as they would be overwritten");
+                if ui.button("Detach link").clicked() {
+                    actions.push(EditorAction::DetachLink);
+                }
+            });
+        }
+        self.edit_scope_ui(ui, &x_unit, &mut actions);
+        // Knots might have been deleted since they were selected
+        self.selected_knots
+            .retain(|id| curve.knots().iter().any(|knot| knot.id == *id));
+        self.knot_edit_ui(ui, curve, &y_unit, &mut actions);
+        self.multi_edit_ui(ui, curve, &y_unit, &mut actions);
+        if curve.track_edit_times {
+            let mut show = self.show_knot_age;
+            if ui
+                .checkbox(&mut show, "Knot age")
+                .on_hover_text("Color knots by when they were last modified")
+                .changed()
+            {
+                actions.push(EditorAction::ShowKnotAge(show));
             }
         }
+        self.smoothness_ui(ui, curve, &mut actions);
+        self.extrapolation_ui(ui, curve, &mut actions);
+        self.reference_ui(ui, curve, &y_unit.unit, &mut actions);
+        self.grid_snap_ui(ui, &mut actions);
+        quantize_rule_ui(ui, curve);
+        if !self.guides.is_empty() {
+            let mut snap = self.snap_to_guides;
+            if ui
+                .checkbox(&mut snap, "Snap to guides")
+                .on_hover_text("Snap dragged knots to guides near the pointer")
+                .changed()
+            {
+                actions.push(EditorAction::SnapToGuides(snap));
+            }
+        }
+        #[cfg(feature = "diagnostics")]
+        self.sample_heat_ui(ui, &mut actions);
+
+        let anchor_violations = curve.validate_anchors();
+        if !anchor_violations.is_empty() {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    Color32::LIGHT_RED,
+                    format!(
+                        "{} of {} anchors not on the curve",
+                        anchor_violations.len(),
+                        curve.anchors.len()
+                    ),
+                );
+                if ui
+                    .button("Enforce anchors")
+                    .on_hover_text("Move the nearest knot onto each anchor, or insert a knot")
+                    .clicked()
+                {
+                    actions.push(EditorAction::EnforceAnchors);
+                }
+            });
+        }
 
-        Frame::canvas(ui.style()).show(ui, |ui| {
+        changed |= self.apply_actions(curve, actions.drain(..));
+        // The actions might have moved knots onto or off the anchors
+        let anchor_violations = curve.validate_anchors();
+
+        let footer_height = if self.footer_stats.is_empty() {
+            0.0
+        } else {
+            ui.text_style_height(&egui::TextStyle::Body) + 2.0 * ui.spacing().item_spacing.y
+        };
+
+        let canvas = Frame::canvas(ui.style()).show(ui, |ui| {
             let (response, painter) = ui.allocate_painter(
-                emath::Vec2::new(ui.available_width(), ui.available_height()),
+                emath::Vec2::new(
+                    ui.available_width(),
+                    (ui.available_height() - footer_height - self.reserved_height).max(0.0),
+                ),
                 Sense::click_and_drag(),
             );
 
@@ -173,165 +601,476 @@ impl LookupCurveEguiEditor {
             let height = response.rect.height();
             self.editor_size = Vec2::new(width, height);
 
+            // Holding space pans the view with the primary button, instead of editing
+            let space_panning = ui.memory(|memory| memory.focused().is_none())
+                && ui.input(|input| input.key_down(egui::Key::Space));
+
             if let Some(hover_pos) = response.hover_pos() {
                 self.hover_point = self.canvas_to_curve(to_canvas.transform_pos(hover_pos));
 
-                // Zooming
-                ui.input(|input| {
-                    let scroll_delta = input.raw_scroll_delta.y;
-                    if scroll_delta != 0.0 {
-                        self.scale *= 1.0 + -scroll_delta * 0.001;
-                        // TODO: adjust offset accordingly
-                    }
+                // Zooming around the pointer, only x while command is held and only y while shift is held
+                let (scroll, command, shift) = ui.input(|input| {
+                    (
+                        input.raw_scroll_delta,
+                        input.modifiers.command,
+                        input.modifiers.shift,
+                    )
                 });
+                // egui turns scrolling with shift held into horizontal scrolling
+                let scroll = if shift { scroll.x + scroll.y } else { scroll.y };
+                if scroll != 0.0 {
+                    let zoom = (-scroll * ZOOM_SPEED).exp();
+                    let factor = match (command, shift) {
+                        (true, false) => Vec2::new(zoom, 1.0),
+                        (false, true) => Vec2::new(1.0, zoom),
+                        _ => Vec2::splat(zoom),
+                    };
+                    actions.push(EditorAction::ZoomView {
+                        center: self.hover_point,
+                        factor,
+                    });
+                }
+
+                if response.hovered() {
+                    self.clipboard_shortcuts(ui, curve, self.hover_point.x, &mut actions);
+                    if ui.memory(|memory| memory.focused().is_none()) {
+                        let shortcut = ui.input_mut(|input| {
+                            if input.consume_key(
+                                egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                                egui::Key::Z,
+                            ) {
+                                Some(EditorAction::Redo)
+                            } else if input.consume_key(egui::Modifiers::COMMAND, egui::Key::Z) {
+                                Some(EditorAction::Undo)
+                            } else if input.consume_key(egui::Modifiers::NONE, egui::Key::F) {
+                                Some(EditorAction::FitToCurve)
+                            } else {
+                                None
+                            }
+                        });
+                        actions.extend(shortcut);
+                    }
+                    let delete = !self.selected_knots.is_empty()
+                        && ui.memory(|memory| memory.focused().is_none())
+                        && ui.input_mut(|input| {
+                            input.consume_key(egui::Modifiers::NONE, egui::Key::Delete)
+                        });
+                    if delete {
+                        actions.push(EditorAction::DeleteKnots {
+                            ids: self.selected_knots.clone(),
+                        });
+                    }
+                }
             } else {
                 self.hover_point = Vec2::ZERO;
             }
 
-            // Panning
-            if response.dragged() || response.dragged_by(egui::PointerButton::Middle) {
-                self.offset -= self.canvas_to_curve_vec(response.drag_delta());
+            // Grabbing the curve between knots. Clicking inserts a knot, dragging moves the segment vertically.
+            let (pointer_delta, primary_pressed, primary_released) = ui.input(|input| {
+                (
+                    input.pointer.delta(),
+                    input.pointer.primary_pressed(),
+                    input.pointer.primary_released(),
+                )
+            });
+            if primary_pressed && response.hovered() && !space_panning {
+                if let Some(grab) = response
+                    .hover_pos()
+                    .and_then(|pos| self.grab_segment(curve, to_canvas.transform_pos(pos)))
+                {
+                    self.segment_drag = SegmentDrag::start(grab);
+                }
+            }
+            let last_segment_grab = match &self.segment_drag {
+                SegmentDrag::Idle => None,
+                SegmentDrag::Pending { grab, .. } | SegmentDrag::Dragging { grab } => Some(*grab),
+            };
+            let mut segment_dragged = matches!(self.segment_drag, SegmentDrag::Dragging { .. });
+            match self.segment_drag.update(
+                Vec2::new(pointer_delta.x, pointer_delta.y),
+                primary_released,
+            ) {
+                SegmentDragEvent::None => {}
+                SegmentDragEvent::Click(grab) => {
+                    let interpolation = curve
+                        .knots()
+                        .iter()
+                        .find(|knot| knot.id == grab.left_id)
+                        .map_or(KnotInterpolation::Linear, |knot| knot.interpolation);
+                    actions.push(EditorAction::AddKnot {
+                        position: grab.position,
+                        interpolation,
+                    });
+                }
+                SegmentDragEvent::Drag(grab, delta) => {
+                    segment_dragged = true;
+                    let dy = self
+                        .canvas_to_curve_vec(emath::Vec2::new(delta.x, delta.y))
+                        .y;
+                    let (dy_left, dy_right) =
+                        segment_drag_deltas(dy, grab.t, self.segment_drag_falloff);
+                    for (id, dy) in [(grab.left_id, dy_left), (grab.right_id, dy_right)] {
+                        if let Some(knot) = curve.knots().iter().find(|knot| knot.id == id) {
+                            actions.push(EditorAction::MoveKnot {
+                                id,
+                                to: knot.position + Vec2::new(0.0, dy),
+                            });
+                        }
+                    }
+                }
+            }
+            // Double-clicking empty space adds a knot, the first knot of an empty curve or one past the ends
+            if let (true, Some(pointer)) = (response.double_clicked(), response.interact_pointer_pos()) {
+                let canvas_pos = to_canvas.transform_pos(pointer);
+                let on_knot = curve.knots().iter().any(|knot| {
+                    self.curve_to_canvas(knot.position).distance(canvas_pos) <= KNOT_RADIUS
+                });
+                if !on_knot && self.grab_segment(curve, canvas_pos).is_none() {
+                    let position = self.canvas_to_curve(canvas_pos);
+                    actions.push(EditorAction::AddKnot {
+                        position,
+                        interpolation: new_knot_interpolation(curve, position.x),
+                    });
+                }
+            }
+            changed |= self.apply_actions(curve, actions.drain(..));
+            // Round only once the drag completes, so the segment follows the pointer smoothly
+            if segment_dragged && !self.segment_drag.is_active() {
+                if let Some(grab) = last_segment_grab {
+                    changed |= self.round_knot_positions(curve, &[grab.left_id, grab.right_id]);
+                }
+            }
+
+            // Selecting knots in a rectangle, by dragging empty space with shift held to extend the selection, or ctrl
+            // to replace it
+            let (shift, command) =
+                ui.input(|input| (input.modifiers.shift, input.modifiers.command));
+            if response.drag_started_by(egui::PointerButton::Primary)
+                && (shift || command)
+                && !space_panning
+                && !self.segment_drag.is_active()
+            {
+                if let Some(origin) = ui.input(|input| input.pointer.press_origin()) {
+                    let from = self.canvas_to_curve(to_canvas.transform_pos(origin));
+                    self.box_select = Some((from, from));
+                }
+            }
+            if let Some((from, to)) = self.box_select {
+                let to = ui
+                    .input(|input| input.pointer.interact_pos())
+                    .map_or(to, |pointer| {
+                        self.canvas_to_curve(to_canvas.transform_pos(pointer))
+                    });
+                if response.dragged() {
+                    self.box_select = Some((from, to));
+                } else {
+                    actions.push(EditorAction::SelectInRect {
+                        from,
+                        to,
+                        extend: shift,
+                    });
+                    self.box_select = None;
+                }
+            }
+
+            // Panning, by dragging empty space, with the middle button, or with space held
+            if !self.segment_drag.is_active()
+                && self.box_select.is_none()
+                && (response.dragged() || response.dragged_by(egui::PointerButton::Middle))
+            {
+                actions.push(EditorAction::SetView {
+                    offset: self.offset - self.canvas_to_curve_vec(response.drag_delta()),
+                    scale: self.scale,
+                });
             }
 
             response.context_menu(|ui| {
                 let menu_pos = ui.min_rect().left_top(); // hacky and not entirely correct
+                let menu_point = self.canvas_to_curve(to_canvas.transform_pos(menu_pos));
                 if ui.button("Add knot").clicked() {
-                    curve.add_knot(Knot {
-                        position: self.canvas_to_curve(to_canvas.transform_pos(menu_pos)),
-                        ..Default::default()
+                    actions.push(EditorAction::AddKnot {
+                        position: menu_point,
+                        interpolation: new_knot_interpolation(curve, menu_point.x),
+                    });
+                    ui.close_menu();
+                }
+                self.clipboard_menu(ui, curve, menu_point.x, &mut actions);
+
+                let invertible = curve.check_invertible();
+                let invert_button = ui
+                    .add_enabled(invertible.is_ok(), egui::Button::new("Invert curve"))
+                    .on_disabled_hover_text(match invertible {
+                        Err(e) => format!(
+                            "{e}, only strictly increasing or decreasing curves can be inverted"
+                        ),
+                        Ok(()) => String::new(),
                     });
-                    changed = true;
+                if invert_button.clicked() {
+                    actions.push(EditorAction::InvertCurve);
                     ui.close_menu();
                 }
             });
+            changed |= self.apply_actions(curve, actions.drain(..));
 
-            self.paint_grid(&painter, &to_screen);
+            #[cfg(feature = "diagnostics")]
+            if let (true, Some(heat)) = (self.show_sample_heat, &self.sample_heat) {
+                self.paint_sample_heat(&painter, &to_screen, response.rect, heat);
+            }
 
-            // Draw the curve
-            let curve_stroke = Stroke {
-                color: Color32::GREEN,
-                width: 2.0,
-            };
+            self.paint_grid(&painter, &to_screen, &x_unit, &y_unit, &font_id);
+            self.paint_reference(&painter, &to_screen);
+            let guide_actions = self.guides_ui(ui, &painter, &to_screen, curve, &x_unit, &y_unit);
+            actions.extend(guide_actions);
+
+            self.paint_curve(&painter, &to_screen, curve);
+            if curve.knots().is_empty() && self.derived.is_none() {
+                painter.draw_text(
+                    response.rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "Double-click to add the first knot",
+                    font_id.clone(),
+                    Color32::GRAY,
+                );
+            }
 
-            // TODO: Only knots inside viewport
-            let mut prev_knot: Option<&Knot> = None;
-            for knot in curve.knots().iter() {
-                if let Some(prev_knot) = prev_knot {
-                    match prev_knot.interpolation {
-                        KnotInterpolation::Constant => {
-                            painter.add(Shape::line(
-                                vec![
-                                    to_screen
-                                        .transform_pos(self.curve_to_canvas(prev_knot.position)),
-                                    to_screen.transform_pos(self.curve_to_canvas(Vec2::new(
-                                        knot.position.x,
-                                        prev_knot.position.y,
-                                    ))),
-                                    to_screen.transform_pos(self.curve_to_canvas(knot.position)),
-                                ],
-                                curve_stroke,
-                            ));
-                        }
-                        KnotInterpolation::Linear => {
-                            painter.add(Shape::line(
-                                vec![
-                                    to_screen
-                                        .transform_pos(self.curve_to_canvas(prev_knot.position)),
-                                    to_screen.transform_pos(self.curve_to_canvas(knot.position)),
-                                ],
-                                curve_stroke,
-                            ));
-                        }
-                        KnotInterpolation::Cubic => {
-                            painter.add(CubicBezierShape::from_points_stroke(
-                                prev_knot
-                                    .compute_bezier_to(knot)
-                                    .map(|p| to_screen.transform_pos(self.curve_to_canvas(p))),
-                                false,
-                                Color32::TRANSPARENT,
-                                curve_stroke,
-                            ));
-                        }
-                    }
-                }
+            // Anchors the curve must pass through
+            for (i, anchor) in curve.anchors.iter().enumerate() {
+                let center = to_screen.transform_pos(self.curve_to_canvas(*anchor));
+                let color = if anchor_violations.iter().any(|v| v.index == i) {
+                    Color32::LIGHT_RED
+                } else {
+                    Color32::GOLD
+                };
+                const ANCHOR_SIZE: f32 = 5.0;
+                painter.draw(DrawCall::ConvexPolygon {
+                    points: [
+                        emath::vec2(0.0, -ANCHOR_SIZE),
+                        emath::vec2(ANCHOR_SIZE, 0.0),
+                        emath::vec2(0.0, ANCHOR_SIZE),
+                        emath::vec2(-ANCHOR_SIZE, 0.0),
+                    ]
+                    .map(|offset| center + offset)
+                    .to_vec(),
+                    fill: Color32::TRANSPARENT,
+                    stroke: Stroke::new(1.5, color),
+                });
+            }
 
-                prev_knot = Some(knot);
+            // Dim the parts outside of the edit scope
+            if let Some(scope) = &self.edit_scope {
+                let rect = response.rect;
+                let to_screen_x = |x: f32| {
+                    to_screen
+                        .transform_pos(self.curve_to_canvas(Vec2::new(x, 0.0)))
+                        .x
+                };
+                let (start, end) = (to_screen_x(*scope.start()), to_screen_x(*scope.end()));
+                let dim = Color32::from_black_alpha(120);
+                if start > rect.left() {
+                    let right = start.min(rect.right());
+                    painter.draw(DrawCall::Rect {
+                        rect: Rect::from_min_max(rect.left_top(), Pos2::new(right, rect.bottom())),
+                        rounding: 0.0,
+                        fill: dim,
+                        stroke: Stroke::NONE,
+                    });
+                }
+                if end < rect.right() {
+                    let left = end.max(rect.left());
+                    painter.draw(DrawCall::Rect {
+                        rect: Rect::from_min_max(Pos2::new(left, rect.top()), rect.right_bottom()),
+                        rounding: 0.0,
+                        fill: dim,
+                        stroke: Stroke::NONE,
+                    });
+                }
             }
 
+            // Knots without a modification time are drawn as the most recent ones
+            let modified_range = (self.show_knot_age && curve.track_edit_times)
+                .then(|| {
+                    let stats = curve.edit_stats();
+                    Some((stats.first_modified()?, stats.last_modified()?))
+                })
+                .flatten();
+            let knot_color = |knot: &Knot| match (modified_range, knot.modified_at) {
+                (Some((oldest, newest)), Some(modified_at)) if newest > oldest => {
+                    let t = (modified_at - oldest) as f32 / (newest - oldest) as f32;
+                    lerp_color(Color32::GRAY, Color32::LIGHT_GREEN, t)
+                }
+                _ => Color32::LIGHT_GREEN,
+            };
+
+            // Knots closer than the hit radius can not be told apart, only one knot of each stack is interactive
+            let stack_tolerance = self
+                .canvas_to_curve_vec(emath::Vec2::splat(KNOT_RADIUS))
+                .abs();
+            let stacks = curve.coincident_knots(stack_tolerance);
+            let kinks = if self.show_smoothness {
+                curve.c1_discontinuities(self.smoothness_tolerance)
+            } else {
+                Vec::new()
+            };
+
             // Handles
-            let knot_radius = 8.0;
-            let mut modified_knot = None;
-            let mut deleted_knot_index = None;
             for (i, knot) in curve.knots().iter().enumerate() {
                 let prev_knot = curve.prev_knot(i);
                 let next_knot = curve.next_knot(i);
 
                 let point_in_screen = to_screen.transform_pos(self.curve_to_canvas(knot.position));
+                if !in_scope(self.edit_scope.as_ref(), knot.position.x) {
+                    painter.draw(DrawCall::CircleFilled {
+                        center: point_in_screen,
+                        radius: 3.0,
+                        color: Color32::LIGHT_GREEN.gamma_multiply(0.3),
+                    });
+                    continue;
+                }
+                let stack = stacks.iter().find(|stack| stack.contains(&i));
+                if let Some(stack) = stack {
+                    // The selected knot of the stack, or else the first one, which sampling uses at its x
+                    let mut editable = stack.iter().copied().filter(|&j| {
+                        in_scope(self.edit_scope.as_ref(), curve.knots()[j].position.x)
+                    });
+                    let first = editable.clone().next();
+                    let active = editable
+                        .find(|&j| self.selected_knots.contains(&curve.knots()[j].id))
+                        .or(first);
+                    if active != Some(i) {
+                        painter.draw(DrawCall::CircleFilled {
+                            center: point_in_screen,
+                            radius: 3.0,
+                            color: knot_color(knot),
+                        });
+                        continue;
+                    }
+                }
                 let interact_rect =
-                    Rect::from_center_size(point_in_screen, emath::Vec2::splat(2.0 * knot_radius));
+                    Rect::from_center_size(point_in_screen, emath::Vec2::splat(2.0 * KNOT_RADIUS));
                 let interact_id = response.id.with(knot.id);
-                let interact_response = ui.interact(interact_rect, interact_id, Sense::drag());
-
-                if interact_response.dragged_by(egui::PointerButton::Primary) {
-                    modified_knot = Some((
-                        i,
-                        Knot {
-                            position: knot.position
-                                + self.canvas_to_curve_vec(interact_response.drag_delta()),
-                            ..*knot
+                // Knots let the pointer through while panning
+                let sense = if space_panning {
+                    Sense::hover()
+                } else {
+                    Sense::click_and_drag()
+                };
+                let interact_response = ui.interact(interact_rect, interact_id, sense);
+
+                let id = knot.id;
+                let selected = self.selected_knots.contains(&id);
+                let stack_popup_id = interact_id.with("stack");
+                if interact_response.clicked() {
+                    let toggle = ui.input(|input| input.modifiers.shift || input.modifiers.command);
+                    let selection = match (toggle, selected) {
+                        (true, true) => {
+                            let mut selection = self.selected_knots.clone();
+                            selection.retain(|selected| *selected != id);
+                            selection
+                        }
+                        (true, false) => [self.selected_knots.as_slice(), &[id]].concat(),
+                        (false, _) => vec![id],
+                    };
+                    actions.push(EditorAction::SetSelection(selection));
+                    if stack.is_some() && !toggle {
+                        ui.memory_mut(|memory| memory.toggle_popup(stack_popup_id));
+                    }
+                }
+                if let Some(stack) = stack {
+                    egui::popup_below_widget(
+                        ui,
+                        stack_popup_id,
+                        &interact_response,
+                        egui::PopupCloseBehavior::CloseOnClickOutside,
+                        |ui| {
+                            ui.label("Overlapping knots, choose one to drag");
+                            for &j in stack {
+                                let knot = &curve.knots()[j];
+                                let label = format!(
+                                    "#{}  x: {}  y: {}",
+                                    knot.id,
+                                    x_unit.format(knot.position.x),
+                                    y_unit.format(knot.position.y)
+                                );
+                                if ui.selectable_label(knot.id == id, label).clicked() {
+                                    actions.push(EditorAction::SetSelection(vec![knot.id]));
+                                    ui.memory_mut(|memory| memory.close_popup());
+                                }
+                            }
                         },
-                    ));
+                    );
+                }
+                if interact_response.drag_started() && ui.input(|input| input.modifiers.alt) {
+                    self.stretch_drag = Some(id);
+                }
+                let stretch = self.stretch_drag == Some(id)
+                    || (self.stretch_with_last_knot && next_knot.is_none());
+                // Dragging one of several selected knots moves all of them
+                let group = (selected && self.selected_knots.len() > 1)
+                    .then(|| self.selected_knots.clone());
+                let move_to = |to: Vec2| match &group {
+                    _ if stretch => EditorAction::StretchKnot {
+                        id,
+                        to,
+                        pivot: None,
+                    },
+                    Some(ids) => EditorAction::MoveKnots {
+                        ids: ids.clone(),
+                        delta: to - knot.position,
+                    },
+                    None => EditorAction::MoveKnot { id, to },
+                };
+                let grid = self.snap_to_grid != ui.input(|input| input.modifiers.command);
+                let mut dragged_to = None;
+                if interact_response.dragged_by(egui::PointerButton::Primary) {
+                    let to = match interact_response.interact_pointer_pos() {
+                        // Follow the pointer, so that the knot can be pulled away from a guide or grid point again
+                        Some(pointer) if grid || self.snaps_to_guides() => self.snap_position(
+                            self.canvas_to_curve(to_canvas.transform_pos(pointer)),
+                            false,
+                            grid,
+                        ),
+                        _ => {
+                            knot.position + self.canvas_to_curve_vec(interact_response.drag_delta())
+                        }
+                    };
+                    actions.push(move_to(to));
+                    dragged_to = Some(to);
+                }
+                if interact_response.drag_stopped()
+                    && (self.round_new_positions_to.is_some() || self.snaps_to_guides() || grid)
+                {
+                    let to = self.snap_position(dragged_to.unwrap_or(knot.position), true, grid);
+                    actions.push(move_to(to));
+                }
+                if interact_response.drag_stopped() && self.stretch_drag == Some(id) {
+                    self.stretch_drag = None;
                 }
 
                 interact_response.context_menu(|ui| {
-                    ui.label("Interpolation");
-                    if ui
-                        .radio(
-                            matches!(knot.interpolation, KnotInterpolation::Constant),
-                            "Constant",
-                        )
-                        .clicked()
-                    {
-                        modified_knot = Some((
-                            i,
-                            Knot {
-                                interpolation: KnotInterpolation::Constant,
-                                ..*knot
-                            },
-                        ));
-                        ui.close_menu();
-                    }
-                    if ui
-                        .radio(
-                            matches!(knot.interpolation, KnotInterpolation::Linear),
-                            "Linear",
-                        )
-                        .clicked()
-                    {
-                        modified_knot = Some((
-                            i,
-                            Knot {
-                                interpolation: KnotInterpolation::Linear,
-                                ..*knot
-                            },
-                        ));
-                        ui.close_menu();
-                    }
-                    if ui
-                        .radio(
-                            matches!(knot.interpolation, KnotInterpolation::Cubic),
-                            "Cubic",
-                        )
-                        .clicked()
-                    {
-                        modified_knot = Some((
-                            i,
-                            Knot {
-                                interpolation: KnotInterpolation::Cubic,
-                                ..*knot
-                            },
-                        ));
-                        ui.close_menu();
+                    // Of all selected knots, when the knot is one of several selected knots
+                    let shared_interpolation = group.as_ref().map(|ids| {
+                        let mut knots = curve.knots().iter().filter(|knot| ids.contains(&knot.id));
+                        let first = knots.next().map(|knot| knot.interpolation);
+                        first.filter(|&first| knots.all(|knot| knot.interpolation == first))
+                    });
+                    match &group {
+                        Some(ids) => ui.label(format!("Interpolation of {} knots", ids.len())),
+                        None => ui.label("Interpolation"),
+                    };
+                    let current = shared_interpolation.unwrap_or(Some(knot.interpolation));
+                    for interpolation in interpolation_options(current) {
+                        let label = interpolation_label(interpolation);
+                        let checked = current.is_some_and(|current| same_kind(current, interpolation));
+                        if ui.radio(checked, label).clicked() {
+                            actions.push(match &group {
+                                Some(ids) => EditorAction::EditKnots {
+                                    ids: ids.clone(),
+                                    edit: KnotEdit::Interpolation(interpolation),
+                                },
+                                None => EditorAction::SetInterpolation { id, interpolation },
+                            });
+                            ui.close_menu();
+                        }
                     }
 
                     ui.label("Position");
@@ -340,50 +1079,131 @@ impl LookupCurveEguiEditor {
                         ui.add(
                             egui::DragValue::from_get_set(|v| match v {
                                 Some(v) => {
-                                    modified_knot = Some((
-                                        i,
-                                        Knot {
-                                            position: Vec2::new(v as f32, knot.position.y),
-                                            ..*knot
-                                        },
-                                    ));
+                                    actions.push(EditorAction::MoveKnot {
+                                        id,
+                                        to: Vec2::new(v as f32, knot.position.y),
+                                    });
                                     v
                                 }
                                 _ => knot.position.x as f64,
                             })
+                            .custom_formatter(|v, _| x_unit.format(v as f32))
+                            .custom_parser(|s| x_unit.parse(s).ok().map(f64::from))
                             .speed(0.001),
                         );
                         ui.label("y:");
                         ui.add(
                             egui::DragValue::from_get_set(|v| match v {
                                 Some(v) => {
-                                    modified_knot = Some((
-                                        i,
-                                        Knot {
-                                            position: Vec2::new(knot.position.x, v as f32),
-                                            ..*knot
-                                        },
-                                    ));
+                                    actions.push(EditorAction::MoveKnot {
+                                        id,
+                                        to: Vec2::new(knot.position.x, v as f32),
+                                    });
                                     v
                                 }
                                 _ => knot.position.y as f64,
                             })
+                            .custom_formatter(|v, _| y_unit.format(v as f32))
+                            .custom_parser(|s| y_unit.parse(s).ok().map(f64::from))
                             .speed(0.001),
                         );
                     });
 
                     ui.label("Actions");
-                    if ui.button("Delete knot").clicked() {
-                        deleted_knot_index = Some(i);
+                    match &group {
+                        Some(ids) => {
+                            if ui.button(format!("Delete {} knots", ids.len())).clicked() {
+                                actions.push(EditorAction::DeleteKnots { ids: ids.clone() });
+                                ui.close_menu();
+                            }
+                        }
+                        None => {
+                            if ui.button("Delete knot").clicked() {
+                                actions.push(EditorAction::DeleteKnot { id });
+                                ui.close_menu();
+                            }
+                        }
+                    }
+                    if stack.is_some() {
+                        ui.menu_button("Merge coincident knots", |ui| {
+                            for (merge_y, label) in [
+                                (MergeY::KeepFirst, "Keep first y"),
+                                (MergeY::Average, "Average y"),
+                            ] {
+                                if ui.button(label).clicked() {
+                                    actions.push(EditorAction::MergeCoincidentKnots {
+                                        tolerance: stack_tolerance,
+                                        merge_y,
+                                    });
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    }
+                    if !self.guides.is_empty() && ui.button("Snap to nearest guide").clicked() {
+                        // Nearest on screen, the axes usually have different scales
+                        let point = self.canvas_to_curve_vec(emath::Vec2::splat(1.0)).abs();
+                        if let Some((guide, _)) = self.guides.closest(knot.position, point) {
+                            let to = match guide.axis {
+                                GuideAxis::Horizontal => Vec2::new(knot.position.x, guide.value),
+                                GuideAxis::Vertical => Vec2::new(guide.value, knot.position.y),
+                            };
+                            actions.push(EditorAction::MoveKnot { id, to });
+                        }
                         ui.close_menu();
                     }
                 });
 
-                painter.add(Shape::circle_filled(
-                    to_screen.transform_pos(self.curve_to_canvas(knot.position)),
-                    3.0,
-                    Color32::LIGHT_GREEN,
-                ));
+                painter.draw(DrawCall::CircleFilled {
+                    center: to_screen.transform_pos(self.curve_to_canvas(knot.position)),
+                    radius: 3.0,
+                    color: knot_color(knot),
+                });
+                if selected {
+                    painter.draw(DrawCall::CircleStroke {
+                        center: point_in_screen,
+                        radius: 6.0,
+                        stroke: Stroke::new(1.5, Color32::WHITE),
+                    });
+                }
+                if let Some(stack) = stack {
+                    painter.draw_text(
+                        point_in_screen + emath::vec2(KNOT_RADIUS, -KNOT_RADIUS),
+                        egui::Align2::LEFT_BOTTOM,
+                        format!("×{}", stack.len()),
+                        egui::FontId::proportional(10.0),
+                        Color32::WHITE,
+                    );
+                }
+                if let Some(&(_, incoming, outgoing)) = kinks.iter().find(|kink| kink.0 == id) {
+                    let center = point_in_screen + emath::vec2(-KNOT_RADIUS, -KNOT_RADIUS);
+                    painter.draw(DrawCall::ConvexPolygon {
+                        points: vec![
+                            center + emath::vec2(0.0, -4.0),
+                            center + emath::vec2(4.0, 3.0),
+                            center + emath::vec2(-4.0, 3.0),
+                        ],
+                        fill: Color32::from_rgb(255, 165, 0),
+                        stroke: Stroke::NONE,
+                    });
+                    let kink_rect = Rect::from_center_size(center, emath::Vec2::splat(KNOT_RADIUS));
+                    ui.interact(kink_rect, interact_id.with("kink"), Sense::click())
+                        .on_hover_text(format!(
+                            "Slope changes from {incoming:.3} to {outgoing:.3}, right click to smooth"
+                        ))
+                        .context_menu(|ui| {
+                            for (policy, label) in [
+                                (SmoothPolicy::Average, "Smooth, average slopes"),
+                                (SmoothPolicy::MatchIncoming, "Smooth, keep incoming slope"),
+                                (SmoothPolicy::MatchOutgoing, "Smooth, keep outgoing slope"),
+                            ] {
+                                if ui.button(label).clicked() {
+                                    actions.push(EditorAction::SmoothKnot { id, policy });
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                }
 
                 // tangents
                 const UNWEIGHTED_TANGENT_LEN: f32 = 60.;
@@ -404,6 +1224,11 @@ impl LookupCurveEguiEditor {
                         TangentSide::Left => (bezier[3], bezier[2]),
                         TangentSide::Right => (bezier[0], bezier[1]),
                     };
+                    // x of the control point at the weight limit of strict curves
+                    let limit_x = curve
+                        .tangent_weight_limit(i, side)
+                        .filter(|_| tangent.weight.is_some())
+                        .map(|limit| endpoint.x + (bezier[3].x - bezier[0].x) * dir * limit);
                     let point_in_canvas = if tangent.weight.is_some() {
                         self.curve_to_canvas(intermediate)
                     } else {
@@ -418,10 +1243,15 @@ impl LookupCurveEguiEditor {
 
                     let interact_rect = Rect::from_center_size(
                         point_in_screen,
-                        emath::Vec2::splat(2.0 * knot_radius),
+                        emath::Vec2::splat(2.0 * KNOT_RADIUS),
                     );
                     let interact_id = interact_id.with(side);
-                    let interact_response = ui.interact(interact_rect, interact_id, Sense::drag());
+                    let sense = if space_panning {
+                        Sense::hover()
+                    } else {
+                        Sense::drag()
+                    };
+                    let interact_response = ui.interact(interact_rect, interact_id, sense);
 
                     if interact_response.dragged_by(egui::PointerButton::Primary) {
                         let mut c = self.canvas_to_curve(
@@ -435,57 +1265,76 @@ impl LookupCurveEguiEditor {
                             let relative_c = c - endpoint;
                             c = endpoint + relative_c * (x / relative_c.x);
                         };
+                        if let Some(limit_x) = limit_x {
+                            // Stop at the limit, keeping the direction of the handle
+                            let relative_c = c - endpoint;
+                            if (c.x - limit_x) * dir > 0.0 && relative_c.x != 0.0 {
+                                c = endpoint + relative_c * ((limit_x - endpoint.x) / relative_c.x);
+                            }
+                        }
 
                         let (new_slope, new_weight) =
                             slope_weight_from_bezier(bezier[0], bezier[3], endpoint, c, dir);
 
-                        let mut knot = knot.with_tangent_slope(side, new_slope);
+                        actions.push(EditorAction::SetTangentSlope {
+                            id,
+                            side,
+                            slope: new_slope,
+                        });
                         if tangent.weight.is_some() {
-                            knot = knot.with_tangent_weight(side, Some(new_weight));
+                            actions.push(EditorAction::SetTangentWeight {
+                                id,
+                                side,
+                                weight: Some(new_weight),
+                            });
                         }
-
-                        modified_knot = Some((i, knot));
                     }
 
                     interact_response.context_menu(|ui| {
                         ui.label("Edit mode");
-                        if ui
-                            .radio(matches!(tangent.mode, TangentMode::Free), "Free")
-                            .clicked()
-                        {
-                            modified_knot =
-                                Some((i, knot.with_tangent_mode(side, TangentMode::Free)));
-                            ui.close_menu();
-                        }
-                        if ui
-                            .radio(matches!(tangent.mode, TangentMode::Aligned), "Aligned")
-                            .clicked()
-                        {
-                            modified_knot =
-                                Some((i, knot.with_tangent_mode(side, TangentMode::Aligned)));
-                            ui.close_menu();
+                        for (mode, label) in [
+                            (TangentMode::Free, "Free"),
+                            (TangentMode::Aligned, "Aligned"),
+                            (TangentMode::Auto, "Auto"),
+                        ] {
+                            if ui.radio(tangent.mode == mode, label).clicked() {
+                                actions.push(EditorAction::SetTangentMode { id, side, mode });
+                                ui.close_menu();
+                            }
                         }
 
                         ui.label("Slope:");
                         ui.add(
                             egui::DragValue::from_get_set(|v| match v {
                                 Some(v) => {
-                                    modified_knot =
-                                        Some((i, knot.with_tangent_slope(side, v as f32)));
+                                    actions.push(EditorAction::SetTangentSlope {
+                                        id,
+                                        side,
+                                        slope: v as f32,
+                                    });
                                     v
                                 }
                                 _ => tangent.slope as f64,
                             })
+                            .custom_formatter(|v, _| slope_unit.format(v as f32))
+                            .custom_parser(|s| slope_unit.parse(s).ok().map(f64::from))
                             .speed(0.001),
                         );
 
                         let mut weighted = tangent.weight.is_some();
                         if ui.checkbox(&mut weighted, "Weighted").changed() {
                             if weighted && tangent.weight.is_none() {
-                                modified_knot =
-                                    Some((i, knot.with_tangent_weight(side, Some(1. / 3.))));
+                                actions.push(EditorAction::SetTangentWeight {
+                                    id,
+                                    side,
+                                    weight: Some(1. / 3.),
+                                });
                             } else if !weighted {
-                                modified_knot = Some((i, knot.with_tangent_weight(side, None)));
+                                actions.push(EditorAction::SetTangentWeight {
+                                    id,
+                                    side,
+                                    weight: None,
+                                });
                             }
                         };
 
@@ -495,10 +1344,11 @@ impl LookupCurveEguiEditor {
                                 ui.add(
                                     egui::DragValue::from_get_set(|v| match v {
                                         Some(v) => {
-                                            modified_knot = Some((
-                                                i,
-                                                knot.with_tangent_weight(side, Some(v as f32)),
-                                            ));
+                                            actions.push(EditorAction::SetTangentWeight {
+                                                id,
+                                                side,
+                                                weight: Some(v as f32),
+                                            });
                                             v
                                         }
                                         _ => tangent.weight.unwrap() as f64,
@@ -509,21 +1359,34 @@ impl LookupCurveEguiEditor {
                         }
                     });
 
-                    painter.add(Shape::dashed_line(
-                        &[
+                    if let Some(limit_x) = limit_x {
+                        let limit_in_screen = to_screen.transform_pos(
+                            self.curve_to_canvas(Vec2::new(limit_x, intermediate.y)),
+                        );
+                        painter.draw(DrawCall::LineSegment {
+                            points: [
+                                limit_in_screen - emath::vec2(0.0, 2.0 * KNOT_RADIUS),
+                                limit_in_screen + emath::vec2(0.0, 2.0 * KNOT_RADIUS),
+                            ],
+                            stroke: Stroke::new(1.0, Color32::LIGHT_GRAY.gamma_multiply(0.4)),
+                        });
+                    }
+
+                    painter.draw(DrawCall::DashedLine {
+                        points: vec![
                             to_screen.transform_pos(self.curve_to_canvas(knot.position)),
                             point_in_screen,
                         ],
-                        Stroke::new(1.0, Color32::GRAY),
-                        4.0,
-                        2.0,
-                    ));
+                        stroke: Stroke::new(1.0, Color32::GRAY),
+                        dash_length: 4.0,
+                        gap_length: 2.0,
+                    });
 
-                    painter.add(Shape::circle_filled(
-                        point_in_screen,
-                        3.0,
-                        Color32::LIGHT_GRAY,
-                    ));
+                    painter.draw(DrawCall::CircleFilled {
+                        center: point_in_screen,
+                        radius: 3.0,
+                        color: Color32::LIGHT_GRAY,
+                    });
                 };
 
                 // right tangent
@@ -539,102 +1402,1510 @@ impl LookupCurveEguiEditor {
                 }
             }
 
-            // Apply modifications
-            if let Some((i, knot)) = modified_knot {
-                curve.modify_knot(i, knot);
-                changed = true;
+            if let Some((from, to)) = self.box_select {
+                let [from, to] =
+                    [from, to].map(|corner| to_screen.transform_pos(self.curve_to_canvas(corner)));
+                painter.draw(DrawCall::Rect {
+                    rect: Rect::from_two_pos(from, to),
+                    rounding: 0.0,
+                    fill: Color32::from_white_alpha(16),
+                    stroke: Stroke::new(1.0, Color32::WHITE),
+                });
             }
-            if let Some(i) = deleted_knot_index {
-                curve.delete_knot(i);
-                changed = true;
+
+            // Apply modifications
+            #[cfg(feature = "trace")]
+            let _span =
+                tracing::info_span!("lookup_curve_editor_apply", edits = actions.len()).entered();
+            changed |= self.apply_actions(curve, actions.drain(..));
+
+            // Where dragged knots are stored once the drag completes
+            if let (true, Some(rule)) = (self.defer_quantize, curve.quantize_storage) {
+                for knot in curve.knots() {
+                    let stored = rule.position(knot.position);
+                    if stored != knot.position {
+                        painter.draw(DrawCall::CircleStroke {
+                            center: to_screen.transform_pos(self.curve_to_canvas(stored)),
+                            radius: 0.75 * KNOT_RADIUS,
+                            stroke: Stroke::new(1.0, Color32::GOLD),
+                        });
+                    }
+                }
             }
 
             // Sample to visualize and test find_y_given_x
             if let Some(sample) = sample {
-                painter.add(Shape::circle_filled(
-                    to_screen.transform_pos(
-                        self.curve_to_canvas(Vec2::new(sample, curve.lookup(sample))),
-                    ),
-                    3.0,
-                    Color32::RED,
-                ));
+                let position = Vec2::new(sample, curve.lookup(sample));
+                if self.sample_trail_length > 0 {
+                    self.sample_trail.sync_generation(curve.generation());
+                    let now = ui.input(|input| input.time);
+                    self.sample_trail
+                        .push(position, now, self.sample_trail_length);
+                    // Keep repainting until the trail has faded out
+                    if self.paint_sample_trail(&painter, &to_screen, now) {
+                        ui.ctx().request_repaint();
+                    }
+                }
+
+                painter.draw(DrawCall::CircleFilled {
+                    center: to_screen.transform_pos(self.curve_to_canvas(position)),
+                    radius: 3.0,
+                    color: Color32::RED,
+                });
             }
+            response
         });
 
-        changed
-    }
+        // Knots released this frame, or edited without an action
+        if !self.defer_quantize && self.derived.is_none() {
+            changed |= !curve.requantize().is_empty();
+        }
 
-    fn paint_grid(&mut self, painter: &Painter, to_screen: &emath::RectTransform) {
-        // vertical lines
-        if self.grid_step_x > 0.0 {
-            let grid_offset_x = self.offset.x % self.grid_step_x;
-            let grid_x_count = (self.scale.x / self.grid_step_x).ceil() as i32 + 1;
-            for i in 0..grid_x_count {
-                let grid_local_x = (i as f32) * self.grid_step_x - grid_offset_x;
+        if !self.footer_stats.is_empty() {
+            self.footer_ui(ui, curve);
+        }
 
-                let line_from = self.offset + Vec2::new(grid_local_x, 0.0);
-                let line_to = self.offset + Vec2::new(grid_local_x, self.scale.y);
+        CurveEditResponse {
+            changed,
+            saved: std::mem::take(&mut self.saved_in_update),
+            dirty: self.is_dirty(curve),
+            response: canvas.inner,
+        }
+    }
 
-                painter.add(Shape::LineSegment {
-                    points: [
-                        to_screen.transform_pos(self.curve_to_canvas(line_from)),
-                        to_screen.transform_pos(self.curve_to_canvas(line_to)),
-                    ],
-                    stroke: Stroke {
-                        width: 1.0,
-                        color: Color32::from_rgb(42, 42, 42),
-                    }
-                    .into(),
-                });
+    /// Controls for setting and clearing [Self::edit_scope]
+    fn edit_scope_ui(&self, ui: &mut Ui, x_unit: &UnitDisplay, actions: &mut Vec<EditorAction>) {
+        let view = self.offset.x..=self.offset.x + self.scale.x;
+        ui.horizontal(|ui| {
+            let mut scoped = self.edit_scope.is_some();
+            if ui
+                .checkbox(&mut scoped, "Edit scope")
+                .on_hover_text("Only allow editing knots within a range of x")
+                .changed()
+            {
+                actions.push(EditorAction::SetEditScope(scoped.then(|| view.clone())));
+            }
 
-                painter.text(
-                    to_screen.transform_pos(Pos2::new(
-                        self.curve_to_canvas(line_from).x,
-                        self.editor_size.y - 5.,
-                    )),
-                    egui::Align2::CENTER_BOTTOM,
-                    format!("{:.1}", line_from.x),
-                    egui::FontId::default(),
-                    Color32::WHITE,
+            let Some(scope) = &self.edit_scope else {
+                return;
+            };
+            let (mut start, mut end) = (*scope.start(), *scope.end());
+            for value in [&mut start, &mut end] {
+                ui.add(
+                    egui::DragValue::new(value)
+                        .custom_formatter(|v, _| x_unit.format(v as f32))
+                        .custom_parser(|s| x_unit.parse(s).ok().map(f64::from))
+                        .speed(0.001 * self.scale.x),
                 );
             }
-        }
+            if ui.button("Set from view").clicked() {
+                (start, end) = (*view.start(), *view.end());
+            }
+            if (start, end) != (*scope.start(), *scope.end()) {
+                actions.push(EditorAction::SetEditScope(Some(start..=end)));
+            }
 
-        // horizontal lines
-        if self.grid_step_y > 0.0 {
-            let grid_offset_y = self.offset.y % self.grid_step_y;
-            let grid_y_count = (self.scale.y / self.grid_step_y).ceil() as i32 + 1;
-            for i in 0..grid_y_count {
-                let grid_local_y = (i as f32) * self.grid_step_y - grid_offset_y;
+            if ui.button("Clear").clicked() {
+                actions.push(EditorAction::SetEditScope(None));
+            }
+        });
+    }
 
-                let line_from = self.offset + Vec2::new(0.0, grid_local_y);
-                let line_to = self.offset + Vec2::new(self.scale.x, grid_local_y);
+    /// Marks the curve as generated from another curve by `relation`, `None` for curves that are not derived.
+    ///
+    /// The editor refuses edits of derived curves, and shows a button for detaching the link instead, see
+    /// [Self::take_detach_link]. Set every frame by the `LookupCurvePlugin` from the
+    /// [CurveLinks](crate::link::CurveLinks) resource.
+    pub fn set_derived(&mut self, relation: Option<LinkRelation>) {
+        self.derived = relation;
+    }
+
+    /// Returns `true` once after the "Detach link" button was clicked
+    pub fn take_detach_link(&mut self) -> bool {
+        std::mem::take(&mut self.detach_link)
+    }
+
+    /// Snapping toggle and steps of [Self::snap_to_grid]
+    fn grid_snap_ui(&mut self, ui: &mut Ui, actions: &mut Vec<EditorAction>) {
+        ui.horizontal(|ui| {
+            let mut snap = self.snap_to_grid;
+            if ui
+                .checkbox(&mut snap, "Snap to grid")
+                .on_hover_text(
+                    "Snap dragged knots to the grid. Hold command while dragging to toggle.",
+                )
+                .changed()
+            {
+                actions.push(EditorAction::SnapToGrid(snap));
+            }
+            if self.snap_to_grid {
+                for (label, step) in [
+                    ("x step:", &mut self.grid_snap_step.x),
+                    ("y step:", &mut self.grid_snap_step.y),
+                ] {
+                    ui.label(label);
+                    ui.add(
+                        egui::DragValue::new(step)
+                            .range(0.0..=f32::MAX)
+                            .speed(0.001),
+                    );
+                }
+            }
+        });
+    }
+
+    /// Fields for the exact position, tangents and interpolation of the selected knot, shown when one knot is
+    /// selected
+    fn knot_edit_ui(
+        &mut self,
+        ui: &mut Ui,
+        curve: &LookupCurve,
+        y_unit: &ValueFormat,
+        actions: &mut Vec<EditorAction>,
+    ) {
+        let [id] = self.selected_knots[..] else {
+            return;
+        };
+        let Some(knot) = curve.knots().iter().find(|knot| knot.id == id) else {
+            return;
+        };
+        let x_unit = curve.x_unit.clone().unwrap_or_default();
+        let slope_unit = slope_unit(&x_unit, &y_unit.unit);
+
+        ui.horizontal(|ui| {
+            ui.label("Knot:");
+            egui::ComboBox::from_id_source(ui.id().with("knot_interpolation"))
+                .selected_text(interpolation_label(knot.interpolation))
+                .show_ui(ui, |ui| {
+                    for option in interpolation_options(Some(knot.interpolation)) {
+                        let current = same_kind(knot.interpolation, option);
+                        if ui
+                            .selectable_label(current, interpolation_label(option))
+                            .clicked()
+                        {
+                            actions.push(EditorAction::SetInterpolation {
+                                id,
+                                interpolation: option,
+                            });
+                        }
+                    }
+                });
+            if let Some(interpolation) = exponent_ui(ui, knot.interpolation) {
+                actions.push(EditorAction::SetInterpolation { id, interpolation });
+            }
+
+            ui.label("x:");
+            ui.add(
+                egui::DragValue::from_get_set(|v| match v {
+                    Some(v) => {
+                        actions.push(EditorAction::MoveKnot {
+                            id,
+                            to: Vec2::new(v as f32, knot.position.y),
+                        });
+                        v
+                    }
+                    _ => knot.position.x as f64,
+                })
+                .custom_formatter(|v, _| x_unit.format(v as f32))
+                .custom_parser(|s| x_unit.parse(s).ok().map(f64::from))
+                .speed(0.001),
+            );
+            ui.label("y:");
+            ui.add(
+                egui::DragValue::from_get_set(|v| match v {
+                    Some(v) => {
+                        actions.push(EditorAction::MoveKnot {
+                            id,
+                            to: Vec2::new(knot.position.x, v as f32),
+                        });
+                        v
+                    }
+                    _ => knot.position.y as f64,
+                })
+                .custom_formatter(|v, _| y_unit.format(v as f32))
+                .custom_parser(|s| y_unit.parse(s).ok().map(f64::from))
+                .speed(0.001),
+            );
+
+            for (side, tangent, label) in [
+                (TangentSide::Left, &knot.left_tangent, "Left slope:"),
+                (TangentSide::Right, &knot.right_tangent, "Right slope:"),
+            ] {
+                ui.label(label);
+                ui.add(
+                    egui::DragValue::from_get_set(|v| match v {
+                        Some(v) => {
+                            actions.push(EditorAction::SetTangentSlope {
+                                id,
+                                side,
+                                slope: v as f32,
+                            });
+                            v
+                        }
+                        _ => tangent.slope as f64,
+                    })
+                    .custom_formatter(|v, _| slope_unit.format(v as f32))
+                    .custom_parser(|s| slope_unit.parse(s).ok().map(f64::from))
+                    .speed(0.001),
+                );
+                if let Some(weight) = tangent.weight {
+                    ui.label("weight:");
+                    ui.add(
+                        egui::DragValue::from_get_set(|v| match v {
+                            Some(v) => {
+                                actions.push(EditorAction::SetTangentWeight {
+                                    id,
+                                    side,
+                                    weight: Some(v as f32),
+                                });
+                                v
+                            }
+                            _ => weight as f64,
+                        })
+                        .speed(0.001),
+                    );
+                }
+            }
+
+            if ui.button("Deselect").clicked() {
+                actions.push(EditorAction::SetSelection(Vec::new()));
+            }
+        });
+    }
+
+    /// Controls for editing the shared properties of the selected knots, shown when several knots are selected
+    fn multi_edit_ui(
+        &mut self,
+        ui: &mut Ui,
+        curve: &LookupCurve,
+        y_unit: &ValueFormat,
+        actions: &mut Vec<EditorAction>,
+    ) {
+        let knots: Vec<&Knot> = curve
+            .knots()
+            .iter()
+            .filter(|knot| self.selected_knots.contains(&knot.id))
+            .collect();
+        if knots.len() < 2 {
+            return;
+        }
+        let ids: Vec<usize> = knots.iter().map(|knot| knot.id).collect();
+        let edit = |edit| EditorAction::EditKnots {
+            ids: ids.clone(),
+            edit,
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{} knots:", knots.len()));
+
+            let interpolation = Shared::of(knots.iter().map(|knot| knot.interpolation));
+            egui::ComboBox::from_id_source(ui.id().with("interpolation"))
+                .selected_text(interpolation.label(|&i| interpolation_label(i).into()))
+                .show_ui(ui, |ui| {
+                    let shared = match interpolation {
+                        Shared::Same(interpolation) => Some(interpolation),
+                        _ => None,
+                    };
+                    for option in interpolation_options(shared) {
+                        let current = shared.is_some_and(|shared| same_kind(shared, option));
+                        if ui
+                            .selectable_label(current, interpolation_label(option))
+                            .clicked()
+                        {
+                            actions.push(edit(KnotEdit::Interpolation(option)));
+                        }
+                    }
+                });
+            if let Shared::Same(shared) = interpolation {
+                if let Some(option) = exponent_ui(ui, shared) {
+                    actions.push(edit(KnotEdit::Interpolation(option)));
+                }
+            }
+
+            let mode = Shared::of(
+                knots
+                    .iter()
+                    .flat_map(|knot| [knot.left_tangent.mode, knot.right_tangent.mode]),
+            );
+            egui::ComboBox::from_id_source(ui.id().with("tangent_mode"))
+                .selected_text(mode.label(|mode| format!("{mode:?} tangents")))
+                .show_ui(ui, |ui| {
+                    for option in [TangentMode::Free, TangentMode::Aligned, TangentMode::Auto] {
+                        let current = mode == Shared::Same(option);
+                        if ui
+                            .selectable_label(current, format!("{option:?}"))
+                            .clicked()
+                        {
+                            actions.push(edit(KnotEdit::TangentMode(option)));
+                        }
+                    }
+                });
+
+            ui.label("y:");
+            let y = Shared::of(knots.iter().map(|knot| knot.position.y));
+            let response = ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.multi_edit_y)
+                        .hint_text(y.label(|y| y_unit.format(*y)))
+                        .desired_width(80.0),
+                )
+                .on_hover_text(
+                    "A value, or +=, -= or *= followed by a value, applied to each knot",
+                );
+            if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                match y_unit.parse_relative(&self.multi_edit_y) {
+                    Ok(value) => {
+                        actions.push(edit(KnotEdit::Y(value)));
+                        self.multi_edit_y.clear();
+                    }
+                    Err(e) => self.show_error(e.to_string()),
+                }
+            }
+
+            if ui.button("Deselect").clicked() {
+                actions.push(EditorAction::SetSelection(Vec::new()));
+            }
+        });
+    }
+
+    /// Playback controls and the recorded `track` below the plot, with a scrub cursor at the position of `playback`.
+    ///
+    /// Dragging along the track moves the cursor, snapping to the nearest recorded sample if `snap_to_samples` is set.
+    /// Playback is advanced by the caller, see [ScrubPlayback::advance].
+    ///
+    /// Returns `true` if the scrub position was changed from the UI
+    pub fn scrub_ui(
+        &mut self,
+        ui: &mut Ui,
+        curve: &LookupCurve,
+        playback: &mut ScrubPlayback,
+        track: Option<&RecordedTrack>,
+        snap_to_samples: &mut bool,
+    ) -> bool {
+        let x_unit = curve.x_unit.clone().unwrap_or_default();
+        let mut scrubbed = false;
+
+        ui.horizontal(|ui| {
+            let label = if playback.playing { "Pause" } else { "Play" };
+            if ui.button(label).clicked() {
+                playback.toggle();
+            }
+            ui.label("Speed:");
+            ui.add(
+                egui::DragValue::new(&mut playback.speed)
+                    .custom_formatter(|v, _| format!("{}/s", x_unit.format(v as f32)))
+                    .custom_parser(|s| x_unit.parse(s.trim_end_matches("/s")).ok().map(f64::from))
+                    .speed(0.01 * self.scale.x),
+            );
+
+            let mut looping = playback.loop_range.is_some();
+            if ui.checkbox(&mut looping, "Loop").changed() {
+                playback.loop_range = looping.then(|| {
+                    track
+                        .and_then(RecordedTrack::domain)
+                        .unwrap_or((self.offset.x, self.offset.x + self.scale.x))
+                });
+            }
+            if let Some((start, end)) = &mut playback.loop_range {
+                for value in [&mut *start, &mut *end] {
+                    ui.add(
+                        egui::DragValue::new(value)
+                            .custom_formatter(|v, _| x_unit.format(v as f32))
+                            .custom_parser(|s| x_unit.parse(s).ok().map(f64::from))
+                            .speed(0.001 * self.scale.x),
+                    );
+                }
+                (*start, *end) = (start.min(*end), start.max(*end));
+            }
+
+            if track.is_some() {
+                ui.checkbox(snap_to_samples, "Snap to samples");
+            }
+        });
+
+        Frame::canvas(ui.style()).show(ui, |ui| {
+            let (response, painter) = ui.allocate_painter(
+                emath::Vec2::new(ui.available_width(), SCRUB_TRACK_HEIGHT),
+                Sense::click_and_drag(),
+            );
+            let rect = response.rect;
+            // Same x mapping as the plot above, which has the same width
+            let to_screen_x = |x: f32| rect.left() + self.curve_to_canvas(Vec2::new(x, 0.0)).x;
+
+            if let Some((start, end)) = playback.loop_range {
+                let (left, right) = (to_screen_x(start), to_screen_x(end));
+                let (left, right) = (left.max(rect.left()), right.min(rect.right()));
+                if left < right {
+                    painter.draw(DrawCall::Rect {
+                        rect: Rect::from_x_y_ranges(left..=right, rect.y_range()),
+                        rounding: 0.0,
+                        fill: Color32::from_white_alpha(12),
+                        stroke: Stroke::NONE,
+                    });
+                }
+            }
+
+            if let Some(track) = track {
+                let (min, max) = track
+                    .points()
+                    .iter()
+                    .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), point| {
+                        (min.min(point.y), max.max(point.y))
+                    });
+                let range = (max - min).max(f32::EPSILON);
+                let inner = rect.shrink(4.0);
+                paint_preview_points(
+                    &painter,
+                    track.points().iter().map(|point| {
+                        (
+                            Pos2::new(
+                                to_screen_x(point.x),
+                                inner.bottom() - (point.y - min) / range * inner.height(),
+                            ),
+                            Color32::LIGHT_BLUE,
+                        )
+                    }),
+                );
+            }
 
-                painter.add(Shape::LineSegment {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                if response.clicked() || response.dragged() {
+                    let x = self
+                        .canvas_to_curve(Pos2::new(pointer.x - rect.left(), 0.0))
+                        .x;
+                    let x = match track {
+                        Some(track) if *snap_to_samples => track.snap(x).unwrap_or(x),
+                        _ => x,
+                    };
+                    scrubbed |= playback.seek(x);
+                }
+            }
+
+            let cursor_x = to_screen_x(playback.x());
+            if rect.x_range().contains(cursor_x) {
+                painter.draw(DrawCall::LineSegment {
                     points: [
-                        to_screen.transform_pos(self.curve_to_canvas(line_from)),
-                        to_screen.transform_pos(self.curve_to_canvas(line_to)),
+                        Pos2::new(cursor_x, rect.top()),
+                        Pos2::new(cursor_x, rect.bottom()),
                     ],
-                    stroke: Stroke {
-                        width: 1.0,
-                        color: Color32::from_rgb(42, 42, 42),
+                    stroke: Stroke::new(2.0, Color32::RED),
+                });
+            }
+        });
+
+        if playback.playing {
+            ui.ctx().request_repaint();
+        }
+        scrubbed
+    }
+
+    /// Sets the sample counts shown as heat overlay, `None` to hide the overlay controls.
+    ///
+    /// Set every frame by the `LookupCurvePlugin` from the
+    /// [CurveSampleHistogram](crate::diagnostics::CurveSampleHistogram) resource.
+    #[cfg(feature = "diagnostics")]
+    pub fn set_sample_heat(&mut self, heat: Option<HistogramSnapshot>) {
+        self.sample_heat = heat;
+    }
+
+    /// Returns `true` once after the "Clear" button of the heat overlay was clicked
+    #[cfg(feature = "diagnostics")]
+    pub fn take_clear_sample_heat(&mut self) -> bool {
+        std::mem::take(&mut self.clear_sample_heat)
+    }
+
+    fn smoothness_ui(&self, ui: &mut Ui, curve: &LookupCurve, actions: &mut Vec<EditorAction>) {
+        ui.horizontal(|ui| {
+            let mut show = self.show_smoothness;
+            if ui
+                .checkbox(&mut show, "Smoothness")
+                .on_hover_text("Mark knots where the slope of the curve jumps")
+                .changed()
+            {
+                actions.push(EditorAction::ShowSmoothness(show));
+            }
+            if !self.show_smoothness {
+                return;
+            }
+            let kinks = curve.c1_discontinuities(self.smoothness_tolerance).len();
+            if kinks > 0 {
+                ui.colored_label(Color32::from_rgb(255, 165, 0), format!("{kinks} kinks"));
+                if ui.button("Smooth all").clicked() {
+                    actions.push(EditorAction::SmoothAllKnots {
+                        slope_tolerance: self.smoothness_tolerance,
+                        policy: SmoothPolicy::Average,
+                    });
+                }
+            }
+        });
+    }
+
+    fn extrapolation_ui(&self, ui: &mut Ui, curve: &LookupCurve, actions: &mut Vec<EditorAction>) {
+        let (before, after) = (curve.extrapolate_before, curve.extrapolate_after);
+        ui.horizontal(|ui| {
+            ui.label("Extrapolate")
+                .on_hover_text("How the curve continues before the first and after the last knot");
+            for (label, current) in [("before", before), ("after", after)] {
+                egui::ComboBox::from_id_source(ui.id().with(label))
+                    .selected_text(format!("{current:?} {label}"))
+                    .show_ui(ui, |ui| {
+                        for option in Extrapolation::ALL {
+                            if ui
+                                .selectable_label(current == option, format!("{option:?}"))
+                                .clicked()
+                            {
+                                let (before, after) = match label {
+                                    "before" => (option, after),
+                                    _ => (before, option),
+                                };
+                                actions.push(EditorAction::SetExtrapolation { before, after });
+                            }
+                        }
+                    });
+            }
+        });
+    }
+
+    fn reference_ui(
+        &self,
+        ui: &mut Ui,
+        curve: &LookupCurve,
+        y_unit: &UnitDisplay,
+        actions: &mut Vec<EditorAction>,
+    ) {
+        let Some(reference) = &self.reference_fn else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.colored_label(REFERENCE_COLOR, "Reference");
+            let (min_x, max_x) = if self.fit_error_in_view {
+                self.view_x_range()
+            } else {
+                self.fit_domain(curve)
+            };
+            match curve.fit_error(reference, min_x, max_x, FIT_ERROR_SAMPLES) {
+                Some(error) => ui.label(format!(
+                    "max error {}, RMS {}",
+                    y_unit.format(error.max),
+                    y_unit.format(error.rms)
+                )),
+                None => ui.label("no error"),
+            };
+            let mut in_view = self.fit_error_in_view;
+            if ui
+                .checkbox(&mut in_view, "In view")
+                .on_hover_text("Compare only the visible part, instead of the whole domain")
+                .changed()
+            {
+                actions.push(EditorAction::FitErrorInView(in_view));
+            }
+            if ui
+                .button("Fit to reference")
+                .on_hover_text("Replace the knots by knots fitted to the reference over the domain")
+                .clicked()
+            {
+                actions.push(EditorAction::FitToReference {
+                    tolerance: self.reference_fit_tolerance,
+                });
+            }
+        });
+    }
+
+    /// Draws [Self::reference_fn] over the visible range
+    pub(crate) fn paint_reference(
+        &self,
+        painter: &impl CanvasPainter,
+        to_screen: &emath::RectTransform,
+    ) {
+        let Some(reference) = &self.reference_fn else {
+            return;
+        };
+        let (min_x, max_x) = self.view_x_range();
+        // Half a pixel on the y axis
+        let tolerance = 0.5 * self.scale.y / self.editor_size.y.max(1.0);
+        for run in sample_adaptive(reference.as_ref(), min_x, max_x, tolerance) {
+            let points: Vec<Pos2> = run
+                .into_iter()
+                .map(|p| to_screen.transform_pos(self.curve_to_canvas(p)))
+                .collect();
+            painter.draw(DrawCall::DashedLine {
+                points,
+                stroke: Stroke::new(1.0, REFERENCE_COLOR),
+                dash_length: 6.0,
+                gap_length: 4.0,
+            });
+        }
+    }
+
+    /// Draws the segments of the curve, and the extrapolation before the first and after the last knot as dashed
+    /// lines to the edges of the view
+    pub(crate) fn paint_curve(
+        &self,
+        painter: &impl CanvasPainter,
+        to_screen: &emath::RectTransform,
+        curve: &LookupCurve,
+    ) {
+        let stroke = Stroke::new(2.0, Color32::GREEN);
+        let to_screen_pos = |p: Vec2| to_screen.transform_pos(self.curve_to_canvas(p));
+        let (view_start, view_end) = self.view_x_range();
+        // Half a pixel on the y axis
+        let tolerance = 0.5 * self.scale.y / self.editor_size.y.max(1.0);
+
+        for pair in curve.knots().windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if b.position.x < view_start || a.position.x > view_end {
+                continue;
+            }
+            let visible = a.position.x >= view_start && b.position.x <= view_end;
+            let eased = matches!(a.interpolation, KnotInterpolation::Power { .. });
+            if eased || a.interpolation == KnotInterpolation::Cubic && !visible {
+                // Only the part in the view, as a bezier much wider than the view is flattened too coarsely when
+                // zoomed in. Eased segments have no bezier to draw.
+                let (from_x, to_x) = (a.position.x.max(view_start), b.position.x.min(view_end));
+                for run in sample_adaptive(&|x| curve.lookup(x), from_x, to_x, tolerance) {
+                    painter.draw(DrawCall::Line {
+                        points: run.into_iter().map(to_screen_pos).collect(),
+                        stroke,
+                    });
+                }
+                continue;
+            }
+            painter.draw(match a.interpolation {
+                KnotInterpolation::Constant => DrawCall::Line {
+                    points: [
+                        a.position,
+                        Vec2::new(b.position.x, a.position.y),
+                        b.position,
+                    ]
+                    .map(to_screen_pos)
+                    .to_vec(),
+                    stroke,
+                },
+                KnotInterpolation::ConstantNext => DrawCall::Line {
+                    points: [
+                        a.position,
+                        Vec2::new(a.position.x, b.position.y),
+                        b.position,
+                    ]
+                    .map(to_screen_pos)
+                    .to_vec(),
+                    stroke,
+                },
+                KnotInterpolation::Linear | KnotInterpolation::Power { .. } => {
+                    DrawCall::LineSegment {
+                        points: [a.position, b.position].map(to_screen_pos),
+                        stroke,
                     }
-                    .into(),
+                }
+                KnotInterpolation::Cubic => DrawCall::CubicBezier {
+                    points: a.compute_bezier_to(b).map(to_screen_pos),
+                    stroke,
+                },
+            });
+        }
+
+        let (Some(first), Some(last)) = (curve.knots().first(), curve.knots().last()) else {
+            return;
+        };
+        let extrapolation = Stroke::new(1.0, Color32::GREEN.gamma_multiply(0.5));
+        for (from_x, to_x, mode) in [
+            (view_start, first.position.x, curve.extrapolate_before),
+            (last.position.x, view_end, curve.extrapolate_after),
+        ] {
+            if from_x >= to_x {
+                continue;
+            }
+            let runs = match mode {
+                Extrapolation::Clamp | Extrapolation::Linear => vec![[from_x, to_x]
+                    .map(|x| Vec2::new(x, curve.lookup(x)))
+                    .to_vec()],
+                Extrapolation::Repeat | Extrapolation::PingPong => {
+                    sample_adaptive(&|x| curve.lookup(x), from_x, to_x, tolerance)
+                }
+            };
+            for run in runs {
+                painter.draw(DrawCall::DashedLine {
+                    points: run.into_iter().map(to_screen_pos).collect(),
+                    stroke: extrapolation,
+                    dash_length: 6.0,
+                    gap_length: 4.0,
                 });
+            }
+        }
+    }
 
-                let text_canvas_pos = Pos2::new(5., self.curve_to_canvas(line_from).y);
-                if text_canvas_pos.y < self.editor_size.y - 30. {
-                    painter.text(
-                        to_screen.transform_pos(text_canvas_pos),
-                        egui::Align2::LEFT_CENTER,
-                        format!("{:.1}", line_from.y),
-                        egui::FontId::default(),
-                        Color32::WHITE,
+    /// Range of x in the view
+    pub(crate) fn view_x_range(&self) -> (f32, f32) {
+        (self.offset.x, self.offset.x + self.scale.x)
+    }
+
+    /// Range of x the curve is fitted to the reference over, the domain of the curve or the view if it has no width
+    pub(crate) fn fit_domain(&self, curve: &LookupCurve) -> (f32, f32) {
+        match (curve.knots().first(), curve.knots().last()) {
+            (Some(first), Some(last)) if first.position.x < last.position.x => {
+                (first.position.x, last.position.x)
+            }
+            _ => self.view_x_range(),
+        }
+    }
+
+    #[cfg(feature = "diagnostics")]
+    fn sample_heat_ui(&self, ui: &mut Ui, actions: &mut Vec<EditorAction>) {
+        let Some(heat) = &self.sample_heat else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            let mut show = self.show_sample_heat;
+            if ui
+                .checkbox(&mut show, "Sample heat")
+                .on_hover_text(format!("{} samples recorded", heat.total()))
+                .changed()
+            {
+                actions.push(EditorAction::ShowSampleHeat(show));
+            }
+            if ui.button("Clear").clicked() {
+                actions.push(EditorAction::ClearSampleHeat);
+            }
+            if heat.domain_reset {
+                ui.colored_label(
+                    Color32::YELLOW,
+                    "Samples were discarded, as the domain of the curve grew",
+                );
+            }
+        });
+    }
+
+    /// Tints the plot by the number of samples in each bucket, and the parts before and after the domain
+    /// by the number of extrapolated samples
+    #[cfg(feature = "diagnostics")]
+    fn paint_sample_heat(
+        &self,
+        painter: &impl CanvasPainter,
+        to_screen: &emath::RectTransform,
+        rect: Rect,
+        heat: &HistogramSnapshot,
+    ) {
+        let max = heat.max();
+        if max == 0 {
+            return;
+        }
+        let to_screen_x = |x: f32| {
+            to_screen
+                .transform_pos(self.curve_to_canvas(Vec2::new(x, 0.0)))
+                .x
+        };
+        let alpha = |count: u64| (count as f32 / max as f32 * 100.0) as u8;
+        let fill = |left: f32, right: f32, color: Color32| {
+            let (left, right) = (left.max(rect.left()), right.min(rect.right()));
+            if left < right {
+                painter.draw(DrawCall::Rect {
+                    rect: Rect::from_x_y_ranges(left..=right, rect.y_range()),
+                    rounding: 0.0,
+                    fill: color,
+                    stroke: Stroke::NONE,
+                });
+            }
+        };
+
+        for (i, count) in heat.buckets.iter().enumerate() {
+            if *count > 0 {
+                let (start, end) = heat.bucket_range(i);
+                fill(
+                    to_screen_x(start),
+                    to_screen_x(end),
+                    Color32::from_rgba_unmultiplied(255, 120, 0, alpha(*count)),
+                );
+            }
+        }
+
+        let extrapolated = |count: u64| Color32::from_rgba_unmultiplied(150, 60, 255, alpha(count));
+        let (start, end) = heat.domain;
+        if heat.below > 0 {
+            fill(rect.left(), to_screen_x(start), extrapolated(heat.below));
+        }
+        if heat.above > 0 {
+            fill(to_screen_x(end), rect.right(), extrapolated(heat.above));
+        }
+    }
+
+    /// Returns the recent samples, oldest first. Only recorded when [Self::sample_trail_length] is above 0.
+    ///
+    /// The trail is cleared when the curve is modified.
+    pub fn sample_trail(&self) -> &[TrailPoint] {
+        self.sample_trail.points()
+    }
+
+    /// Clears the recent samples, for example when the sampled value comes from a new source
+    pub fn clear_sample_trail(&mut self) {
+        self.sample_trail.clear();
+    }
+
+    /// Paints the recent samples, returns whether the trail is still fading out
+    fn paint_sample_trail(
+        &self,
+        painter: &impl CanvasPainter,
+        to_screen: &emath::RectTransform,
+        now: f64,
+    ) -> bool {
+        let points = self.sample_trail.points();
+        paint_preview_points(
+            painter,
+            points.iter().map(|point| {
+                let alpha = SampleTrail::alpha(point, now, self.sample_trail_fade);
+                (
+                    to_screen.transform_pos(self.curve_to_canvas(point.position)),
+                    Color32::from_rgba_unmultiplied(255, 0, 0, (alpha * 160.0) as u8),
+                )
+            }),
+        );
+
+        points
+            .last()
+            .is_some_and(|point| SampleTrail::alpha(point, now, self.sample_trail_fade) > 0.0)
+    }
+
+    /// Creates a knot at `position` following [Self::round_new_positions_to] and [Self::id_strategy]
+    pub(crate) fn new_knot(
+        &self,
+        curve: &LookupCurve,
+        position: Vec2,
+        interpolation: KnotInterpolation,
+    ) -> Knot {
+        let position = match self.round_new_positions_to {
+            Some(step) => quantize_position(position, step),
+            None => position,
+        };
+        Knot {
+            position,
+            interpolation,
+            id: self.id_strategy.assign(position, curve.knots()),
+            ..Default::default()
+        }
+    }
+
+    /// Snaps the positions of the knots with `ids` to guides, or rounds them following
+    /// [Self::round_new_positions_to]. Returns `true` if any moved.
+    pub(crate) fn round_knot_positions(&mut self, curve: &mut LookupCurve, ids: &[usize]) -> bool {
+        if self.round_new_positions_to.is_none() && !self.snaps_to_guides() {
+            return false;
+        }
+        let moves: Vec<_> = curve
+            .knots()
+            .iter()
+            .filter(|knot| ids.contains(&knot.id))
+            .map(|knot| EditorAction::MoveKnot {
+                id: knot.id,
+                to: self.snap_position(knot.position, true, false),
+            })
+            .collect();
+        self.apply_actions(curve, moves)
+    }
+
+    fn snaps_to_guides(&self) -> bool {
+        self.snap_to_guides && !self.guides.is_empty()
+    }
+
+    /// Snaps `position` to guides within [GUIDE_SNAP_DISTANCE] if [Self::snap_to_guides] is set, and otherwise
+    /// rounds it to [Self::grid_snap_step] if `grid` is set, or following [Self::round_new_positions_to] if `round`
+    /// is set
+    pub(super) fn snap_position(&self, position: Vec2, round: bool, grid: bool) -> Vec2 {
+        let no_guides = Guides::default();
+        let guides = if self.snap_to_guides {
+            &self.guides
+        } else {
+            &no_guides
+        };
+        let tolerance = self
+            .canvas_to_curve_vec(emath::Vec2::splat(GUIDE_SNAP_DISTANCE))
+            .abs();
+        let step = if grid {
+            Some(self.grid_snap_step)
+        } else {
+            self.round_new_positions_to
+                .filter(|_| round)
+                .map(Vec2::splat)
+        };
+        guides.snap(position, tolerance, step)
+    }
+
+    /// Returns the segment of the curve under `canvas_pos`, if not too close to a knot.
+    fn grab_segment(&self, curve: &LookupCurve, canvas_pos: Pos2) -> Option<SegmentGrab> {
+        let knots = curve.knots();
+        let (first, last) = (knots.first()?, knots.last()?);
+        let position = self.canvas_to_curve(canvas_pos);
+        if position.x <= first.position.x || position.x >= last.position.x {
+            return None;
+        }
+        if knots
+            .iter()
+            .any(|knot| self.curve_to_canvas(knot.position).distance(canvas_pos) <= KNOT_RADIUS)
+        {
+            return None;
+        }
+
+        let on_curve = Vec2::new(position.x, curve.lookup(position.x));
+        if (self.curve_to_canvas(on_curve).y - canvas_pos.y).abs() > CURVE_GRAB_DISTANCE {
+            return None;
+        }
+
+        let i = knots.search_knots(position.x);
+        let (left, right) = (&knots[i], &knots[i + 1]);
+        let scope = self.edit_scope.as_ref();
+        if !in_scope(scope, left.position.x) || !in_scope(scope, right.position.x) {
+            return None;
+        }
+        Some(SegmentGrab {
+            left_id: left.id,
+            right_id: right.id,
+            t: (position.x - left.position.x) / (right.position.x - left.position.x),
+            position: on_curve,
+        })
+    }
+
+    /// Displays the selected [FooterStats]. Stats are only recomputed when the curve has changed.
+    fn footer_ui(&mut self, ui: &mut Ui, curve: &LookupCurve) {
+        self.footer_stats_cache.update(curve);
+        let Some(stats) = self.footer_stats_cache.stats() else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            for (label, value) in footer_entries(stats, self.footer_stats) {
+                let response = ui
+                    .add(egui::Label::new(format!("{label}: {value}")).sense(Sense::click()))
+                    .on_hover_text("Click to copy");
+                if response.clicked() {
+                    ui.ctx().copy_text(value);
+                }
+                ui.separator();
+            }
+        });
+    }
+
+    /// Returns `true` if the curve has been modified since it was last saved, or first shown in the editor
+    pub fn is_dirty(&self, curve: &LookupCurve) -> bool {
+        self.saved_generation
+            .is_some_and(|generation| generation != curve.generation())
+    }
+
+    /// Generation of the curve when it was last saved or first shown, see [Self::is_dirty]
+    #[cfg_attr(not(feature = "editor_bevy"), allow(dead_code))]
+    pub(crate) fn saved_generation(&self) -> Option<u64> {
+        self.saved_generation
+    }
+
+    /// Marks the current state of the curve as saved, see [Self::is_dirty]
+    pub fn mark_saved(&mut self, curve: &LookupCurve) {
+        self.saved_generation = Some(curve.generation());
+    }
+
+    /// Shows an error message at the top of the editor for a few seconds
+    pub fn show_error<S: Into<String>>(&mut self, message: S) {
+        self.error = Some(EditorError {
+            message: message.into(),
+            shown_at: None,
+        });
+    }
+
+    fn error_ui(&mut self, ui: &mut Ui) {
+        let Some(error) = &mut self.error else {
+            return;
+        };
+        let now = ui.input(|input| input.time);
+        let shown_at = *error.shown_at.get_or_insert(now);
+        let response = ui
+            .colored_label(ui.visuals().error_fg_color, &error.message)
+            .interact(Sense::click())
+            .on_hover_text("Click to dismiss");
+        if response.clicked() || now - shown_at > ERROR_DURATION {
+            self.error = None;
+        } else {
+            // Repaint to hide the message in time, even without input
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_secs_f64(
+                    ERROR_DURATION - (now - shown_at),
+                ));
+        }
+    }
+
+    /// Saves the curve to `path`, logging the result
+    #[cfg(feature = "ron")]
+    pub(crate) fn save(
+        &self,
+        curve: &LookupCurve,
+        path: &str,
+    ) -> Result<(), crate::LookupCurveSaveError> {
+        let format = self
+            .save_format
+            .or_else(|| CurveFormat::from_path(path))
+            .unwrap_or_default();
+        if let Err(e) = curve.save_to_file_as(path, format) {
+            #[cfg(feature = "bevy_app")]
+            bevy_log::error!("Failed to save curve {}", e);
+            #[cfg(not(feature = "bevy_app"))]
+            println!("Failed to save curve {}", e);
+            Err(e)
+        } else {
+            #[cfg(feature = "bevy_app")]
+            bevy_log::info!("Curve saved successfully.");
+            #[cfg(not(feature = "bevy_app"))]
+            println!("Curve saved successfully.");
+            Ok(())
+        }
+    }
+
+    /// Rulers along the bottom and left edges of the plot that create guides when clicked, and the guides, which
+    /// can be dragged and have a context menu
+    fn guides_ui(
+        &self,
+        ui: &mut Ui,
+        painter: &impl CanvasPainter,
+        to_screen: &emath::RectTransform,
+        curve: &LookupCurve,
+        x_unit: &UnitDisplay,
+        y_unit: &ValueFormat,
+    ) -> Vec<EditorAction> {
+        let mut actions = Vec::new();
+        let x_unit = &ValueFormat::from(x_unit.clone());
+        let rect = *to_screen.to();
+        let to_canvas = to_screen.inverse();
+        let guides_id = ui.id().with("guides");
+
+        let rulers = [
+            (
+                GuideAxis::Vertical,
+                Rect::from_x_y_ranges(rect.x_range(), rect.bottom() - RULER_SIZE..=rect.bottom()),
+            ),
+            (
+                GuideAxis::Horizontal,
+                Rect::from_x_y_ranges(rect.left()..=rect.left() + RULER_SIZE, rect.y_range()),
+            ),
+        ];
+        for (axis, ruler) in rulers {
+            let response = ui
+                .interact(ruler, guides_id.with(axis), Sense::click())
+                .on_hover_text("Click to add a guide");
+            if response.hovered() {
+                painter.draw(DrawCall::Rect {
+                    rect: ruler,
+                    rounding: 0.0,
+                    fill: Color32::from_white_alpha(8),
+                    stroke: Stroke::NONE,
+                });
+            }
+            if let (true, Some(pointer)) = (response.clicked(), response.interact_pointer_pos()) {
+                let position = self.canvas_to_curve(to_canvas.transform_pos(pointer));
+                let value = match axis {
+                    GuideAxis::Horizontal => position.y,
+                    GuideAxis::Vertical => position.x,
+                };
+                actions.push(EditorAction::AddGuide { axis, value });
+            }
+        }
+
+        let in_knot_range = |x: f32| match (curve.knots().first(), curve.knots().last()) {
+            (Some(first), Some(last)) => (first.position.x..=last.position.x).contains(&x),
+            _ => false,
+        };
+        for guide in self.guides.iter() {
+            let (id, axis, value) = (guide.id, guide.axis, guide.value);
+            let (line_rect, unit) = match axis {
+                GuideAxis::Horizontal => {
+                    let y = to_screen
+                        .transform_pos(self.curve_to_canvas(Vec2::new(0.0, value)))
+                        .y;
+                    let y_range = y - GUIDE_SNAP_DISTANCE..=y + GUIDE_SNAP_DISTANCE;
+                    (Rect::from_x_y_ranges(rect.x_range(), y_range), y_unit)
+                }
+                GuideAxis::Vertical => {
+                    let x = to_screen
+                        .transform_pos(self.curve_to_canvas(Vec2::new(value, 0.0)))
+                        .x;
+                    let x_range = x - GUIDE_SNAP_DISTANCE..=x + GUIDE_SNAP_DISTANCE;
+                    (Rect::from_x_y_ranges(x_range, rect.y_range()), x_unit)
+                }
+            };
+            if !rect.intersects(line_rect) {
+                continue;
+            }
+
+            let cursor = match axis {
+                GuideAxis::Horizontal => egui::CursorIcon::ResizeVertical,
+                GuideAxis::Vertical => egui::CursorIcon::ResizeHorizontal,
+            };
+            let response = ui
+                .interact(line_rect, guides_id.with(id), Sense::click_and_drag())
+                .on_hover_cursor(cursor);
+            if response.dragged_by(egui::PointerButton::Primary) {
+                let delta = self.canvas_to_curve_vec(response.drag_delta());
+                let delta = match axis {
+                    GuideAxis::Horizontal => delta.y,
+                    GuideAxis::Vertical => delta.x,
+                };
+                actions.push(EditorAction::MoveGuide {
+                    id,
+                    value: value + delta,
+                });
+            }
+
+            response.context_menu(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(match axis {
+                        GuideAxis::Horizontal => "y:",
+                        GuideAxis::Vertical => "x:",
+                    });
+                    ui.add(
+                        egui::DragValue::from_get_set(|v| match v {
+                            Some(v) => {
+                                actions.push(EditorAction::MoveGuide {
+                                    id,
+                                    value: v as f32,
+                                });
+                                v
+                            }
+                            _ => value as f64,
+                        })
+                        .custom_formatter(|v, _| unit.format(v as f32))
+                        .custom_parser(|s| unit.parse(s).ok().map(f64::from))
+                        .speed(0.001),
                     );
+                });
+
+                match axis {
+                    GuideAxis::Horizontal => {
+                        let crossings = curve.crossings(value);
+                        if ui
+                            .add_enabled(
+                                !crossings.is_empty(),
+                                egui::Button::new("Add knots at crossings"),
+                            )
+                            .on_hover_text("Add knots where the curve crosses the guide")
+                            .clicked()
+                        {
+                            actions.extend(
+                                crossings
+                                    .into_iter()
+                                    .map(|x| EditorAction::InsertKnotAt { x }),
+                            );
+                            ui.close_menu();
+                        }
+                    }
+                    GuideAxis::Vertical => {
+                        if ui
+                            .add_enabled(
+                                in_knot_range(value),
+                                egui::Button::new("Add knot on curve"),
+                            )
+                            .on_hover_text(
+                                "Add a knot where the curve crosses the guide, keeping its shape",
+                            )
+                            .clicked()
+                        {
+                            actions.push(EditorAction::InsertKnotAt { x: value });
+                            ui.close_menu();
+                        }
+                    }
+                }
+                if ui.button("Delete guide").clicked() {
+                    actions.push(EditorAction::RemoveGuide { id });
+                    ui.close_menu();
                 }
+            });
+
+            let color = if response.hovered() || response.dragged() {
+                Color32::from_rgb(120, 200, 255)
+            } else {
+                Color32::from_rgb(60, 140, 200)
+            };
+            let stroke = Stroke::new(1.0, color);
+            let center = line_rect.center();
+            let points = match axis {
+                GuideAxis::Horizontal => [
+                    Pos2::new(rect.left(), center.y),
+                    Pos2::new(rect.right(), center.y),
+                ],
+                GuideAxis::Vertical => [
+                    Pos2::new(center.x, rect.top()),
+                    Pos2::new(center.x, rect.bottom()),
+                ],
+            };
+            painter.draw(DrawCall::LineSegment { points, stroke });
+        }
+        actions
+    }
+
+    pub(crate) fn paint_grid(
+        &mut self,
+        painter: &impl CanvasPainter,
+        to_screen: &emath::RectTransform,
+        x_unit: &UnitDisplay,
+        y_unit: &ValueFormat,
+        font_id: &egui::FontId,
+    ) {
+        let (step_x, step_y) = self.grid_steps(x_unit, &y_unit.unit);
+        let precision_x = step_precision(step_x * x_unit.scale.abs());
+        // Transformed values, like decibels, are not evenly spaced
+        let precision_y = if y_unit.transform.is_linear() {
+            step_precision(step_y * y_unit.unit.scale.abs())
+        } else {
+            y_unit.unit.precision
+        };
+        // Values of lines at a multiple of the step, as the lines accumulate rounding errors. Also avoids "-0.0".
+        let snap = |value: f32, step: f32| (value / step).round() * step + 0.0;
+
+        // vertical lines
+        for x in grid_lines(self.offset.x, self.scale.x, step_x) {
+            let line_from = Vec2::new(x, self.offset.y);
+            let line_to = Vec2::new(x, self.offset.y + self.scale.y);
+
+            painter.draw(DrawCall::LineSegment {
+                points: [
+                    to_screen.transform_pos(self.curve_to_canvas(line_from)),
+                    to_screen.transform_pos(self.curve_to_canvas(line_to)),
+                ],
+                stroke: Stroke::new(1.0, Color32::from_rgb(42, 42, 42)),
+            });
+
+            self.paint_tick_label(
+                painter,
+                to_screen.transform_pos(Pos2::new(
+                    self.curve_to_canvas(line_from).x,
+                    self.editor_size.y - 5.,
+                )),
+                egui::Align2::CENTER_BOTTOM,
+                format!(
+                    "{:.precision_x$}",
+                    x_unit.to_display(snap(line_from.x, step_x))
+                ),
+                font_id,
+            );
+        }
+
+        // horizontal lines
+        for y in grid_lines(self.offset.y, self.scale.y, step_y) {
+            let line_from = Vec2::new(self.offset.x, y);
+            let line_to = Vec2::new(self.offset.x + self.scale.x, y);
+
+            painter.draw(DrawCall::LineSegment {
+                points: [
+                    to_screen.transform_pos(self.curve_to_canvas(line_from)),
+                    to_screen.transform_pos(self.curve_to_canvas(line_to)),
+                ],
+                stroke: Stroke::new(1.0, Color32::from_rgb(42, 42, 42)),
+            });
+
+            let text_canvas_pos = Pos2::new(5., self.curve_to_canvas(line_from).y);
+            if text_canvas_pos.y < self.editor_size.y - 30. {
+                self.paint_tick_label(
+                    painter,
+                    to_screen.transform_pos(text_canvas_pos),
+                    egui::Align2::LEFT_CENTER,
+                    format!(
+                        "{:.precision_y$}",
+                        y_unit.to_display(snap(line_from.y, step_y))
+                    ),
+                    font_id,
+                );
             }
         }
+
+        // axis units
+        if !x_unit.unit.is_empty() {
+            painter.draw_text(
+                to_screen
+                    .transform_pos(Pos2::new(self.editor_size.x - 5., self.editor_size.y - 25.)),
+                egui::Align2::RIGHT_BOTTOM,
+                &x_unit.unit,
+                font_id.clone(),
+                Color32::GRAY,
+            );
+        }
+        if !y_unit.label().is_empty() {
+            painter.draw_text(
+                to_screen.transform_pos(Pos2::new(5., 5.)),
+                egui::Align2::LEFT_TOP,
+                y_unit.label(),
+                font_id.clone(),
+                Color32::GRAY,
+            );
+        }
+
+        self.tick_labels.retain_used();
+    }
+
+    /// Steps in curve space between grid lines along x and y, at least [Self::grid_spacing] apart on screen
+    pub(crate) fn grid_steps(&self, x_unit: &UnitDisplay, y_unit: &UnitDisplay) -> (f32, f32) {
+        let count = (self.editor_size / self.grid_spacing.max(Vec2::ONE))
+            .floor()
+            .max(Vec2::ONE);
+        (
+            display_step(self.scale.x, x_unit.scale, count.x as u32),
+            display_step(self.scale.y, y_unit.scale, count.y as u32),
+        )
+    }
+
+    /// Paints a tick label, reusing the cached layout if the label was shown before
+    fn paint_tick_label(
+        &mut self,
+        painter: &impl CanvasPainter,
+        pos: Pos2,
+        anchor: egui::Align2,
+        text: String,
+        font_id: &egui::FontId,
+    ) {
+        let galley = self.tick_labels.get_or_layout(text, |text| {
+            painter.layout_no_wrap(text, font_id.clone(), Color32::WHITE)
+        });
+        let rect = anchor.anchor_size(pos, galley.size());
+        painter.draw(DrawCall::Text {
+            pos: rect.min,
+            galley,
+            color: Color32::WHITE,
+        });
+    }
+}
+
+/// Shows the [LookupCurve::quantize_storage] rule of the curve, if any
+fn quantize_rule_ui(ui: &mut Ui, curve: &LookupCurve) {
+    let Some(rule) = curve.quantize_storage else {
+        return;
+    };
+    let steps: Vec<String> = [("x", rule.x_step), ("y", rule.y_step)]
+        .into_iter()
+        .filter_map(|(axis, step)| Some(format!("{axis} to {}", step_label(step?))))
+        .collect();
+    if steps.is_empty() {
+        return;
+    }
+    let tangents = if rule.tangents_too && rule.y_step.is_some() {
+        ", and tangents"
+    } else {
+        ""
+    };
+    ui.label(format!(
+        "Stored values rounded: {}{tangents}",
+        steps.join(", ")
+    ))
+    .on_hover_text("Dragged knots are rounded once released, see the rule of the curve");
+}
+
+/// `1/60` for reciprocals of integers, which are not exact as decimals, and the step itself for other steps
+fn step_label(step: f32) -> String {
+    let reciprocal = 1.0 / step;
+    if reciprocal > 1.0 && (reciprocal - reciprocal.round()).abs() <= reciprocal * 1e-6 {
+        let decimal = format!("{step}");
+        // Short decimals like 0.25 are shown as they are
+        if decimal.len() > 6 {
+            return format!("1/{}", reciprocal.round());
+        }
     }
+    format!("{step}")
+}
+
+/// Paints connected points, each line colored like the point it ends at
+pub(crate) fn paint_preview_points(
+    painter: &impl CanvasPainter,
+    points: impl Iterator<Item = (Pos2, Color32)>,
+) {
+    let mut prev: Option<Pos2> = None;
+    for (pos, color) in points {
+        if let Some(prev) = prev {
+            painter.draw(DrawCall::LineSegment {
+                points: [prev, pos],
+                stroke: Stroke::new(1.5, color),
+            });
+        }
+        painter.draw(DrawCall::CircleFilled {
+            center: pos,
+            radius: 2.0,
+            color,
+        });
+        prev = Some(pos);
+    }
+}
+
+/// Interpolation of a knot added at `x`, continuing the segment it is added to. Knots added before the first knot
+/// continue the first segment, and the first knot of an empty curve gets the default interpolation.
+pub(crate) fn new_knot_interpolation(curve: &LookupCurve, x: f32) -> KnotInterpolation {
+    let knots = curve.knots();
+    let left = knots.partition_point(|knot| knot.position.x <= x);
+    knots
+        .get(left.saturating_sub(1))
+        .map_or(Knot::default().interpolation, |knot| knot.interpolation)
+}
+
+/// Exponent offered for [KnotInterpolation::Power] segments that were not eased before
+const DEFAULT_EXPONENT: f32 = 2.0;
+
+/// Interpolations offered for knots with the interpolation `current`, keeping its exponent for
+/// [KnotInterpolation::Power]
+fn interpolation_options(current: Option<KnotInterpolation>) -> [KnotInterpolation; 5] {
+    let exponent = match current {
+        Some(KnotInterpolation::Power { exponent }) => exponent,
+        _ => DEFAULT_EXPONENT,
+    };
+    [
+        KnotInterpolation::Constant,
+        KnotInterpolation::ConstantNext,
+        KnotInterpolation::Linear,
+        KnotInterpolation::Power { exponent },
+        KnotInterpolation::Cubic,
+    ]
+}
+
+fn interpolation_label(interpolation: KnotInterpolation) -> &'static str {
+    match interpolation {
+        KnotInterpolation::Constant => "Constant",
+        KnotInterpolation::ConstantNext => "Constant next",
+        KnotInterpolation::Linear => "Linear",
+        KnotInterpolation::Power { .. } => "Power",
+        KnotInterpolation::Cubic => "Cubic",
+    }
+}
+
+/// Whether the interpolations are the same variant, whatever their parameters
+fn same_kind(a: KnotInterpolation, b: KnotInterpolation) -> bool {
+    std::mem::discriminant(&a) == std::mem::discriminant(&b)
+}
+
+/// Shows the exponent of [KnotInterpolation::Power] interpolations, returns the interpolation with a changed exponent
+fn exponent_ui(ui: &mut Ui, interpolation: KnotInterpolation) -> Option<KnotInterpolation> {
+    let KnotInterpolation::Power { mut exponent } = interpolation else {
+        return None;
+    };
+    ui.add(
+        egui::DragValue::new(&mut exponent)
+            .range(0.05..=20.0)
+            .speed(0.01)
+            .prefix("^"),
+    )
+    .on_hover_text("Exponent of the fraction of the segment, above 1 eases in, below 1 eases out")
+    .changed()
+    .then_some(KnotInterpolation::Power { exponent })
+}
+
+fn slope_unit(x_unit: &UnitDisplay, y_unit: &UnitDisplay) -> UnitDisplay {
+    let unit = match (x_unit.unit.is_empty(), y_unit.unit.is_empty()) {
+        (true, true) => String::new(),
+        (true, false) => y_unit.unit.clone(),
+        (false, _) => format!("{}/{}", y_unit.unit, x_unit.unit),
+    };
+    UnitDisplay {
+        unit,
+        scale: y_unit.scale / x_unit.scale,
+        precision: y_unit.precision,
+    }
+}
+
+/// Linear interpolation between two colors, in gamma space
+fn lerp_color(from: Color32, to: Color32, t: f32) -> Color32 {
+    let channel =
+        |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t.clamp(0.0, 1.0)).round() as u8;
+    Color32::from_rgb(
+        channel(from.r(), to.r()),
+        channel(from.g(), to.g()),
+        channel(from.b(), to.b()),
+    )
 }
 
 fn slope_weight_from_bezier(
@@ -0,0 +1,326 @@
+use std::path::{Path, PathBuf};
+
+use bevy_asset::{AssetServer, Assets};
+use bevy_ecs::prelude::{Commands, Component, Entity, EventReader, Query, Res, ResMut, Resource};
+use bevy_egui::EguiContexts;
+use bevy_window::{FileDragAndDrop, Window};
+
+use super::{LookupCurveEditor, LookupCurveEguiEditor};
+use crate::{CurveFormat, LookupCurve};
+
+/// Settings for opening curve files dropped onto a window
+#[derive(Resource, Clone, Debug)]
+pub struct CurveDropSettings {
+    /// Dropped files within this directory are loaded through the [AssetServer], other files are read directly.
+    ///
+    /// Defaults to the `assets` directory the default [AssetPlugin](bevy_asset::AssetPlugin) reads from.
+    pub asset_root: PathBuf,
+}
+
+impl Default for CurveDropSettings {
+    fn default() -> Self {
+        // Same base path as bevy_asset uses for the default file source
+        let base = std::env::var("BEVY_ASSET_ROOT")
+            .or_else(|_| std::env::var("CARGO_MANIFEST_DIR"))
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                std::env::current_exe()
+                    .ok()
+                    .and_then(|exe| exe.parent().map(Path::to_path_buf))
+                    .unwrap_or_default()
+            });
+        Self {
+            asset_root: base.join("assets"),
+        }
+    }
+}
+
+/// A dropped curve waiting for the user to confirm replacing the unsaved changes of an editor
+#[derive(Component)]
+pub(crate) struct PendingCurveDrop {
+    curve: LookupCurve,
+    path: String,
+}
+
+/// Where a dropped file is located
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DroppedPath {
+    /// Within the asset root, with the path relative to it
+    Asset(PathBuf),
+    /// Outside of the asset root
+    External(PathBuf),
+}
+
+/// What to do with dropped curve files
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DropAction {
+    /// Open each file in a new editor
+    SpawnEditors,
+    /// Replace the curve of the hovered editor
+    Replace,
+    /// Ask before replacing the curve of the hovered editor, as it has unsaved changes
+    ConfirmReplace,
+}
+
+/// Returns `true` if `path` has one of the [CURVE_EXTENSIONS], ignoring case
+pub(crate) fn is_curve_file(path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let name = name.to_string_lossy().to_lowercase();
+    // Files with the asset extension of any enabled format, which is preceded by a file stem
+    CurveFormat::all()
+        .iter()
+        .flat_map(|format| format.extensions())
+        .any(|extension| {
+            name.strip_suffix(extension)
+                .and_then(|stem| stem.strip_suffix('.'))
+                .is_some_and(|stem| !stem.is_empty())
+        })
+}
+
+/// Classifies `path` as inside or outside of `asset_root`. Both paths are expected to be absolute.
+pub(crate) fn classify_path(path: &Path, asset_root: &Path) -> DroppedPath {
+    match path.strip_prefix(asset_root) {
+        Ok(relative) if !relative.as_os_str().is_empty() => {
+            DroppedPath::Asset(relative.to_path_buf())
+        }
+        _ => DroppedPath::External(path.to_path_buf()),
+    }
+}
+
+/// Decides what to do with `file_count` dropped curve files.
+///
+/// `hovered_editor_dirty` is `None` if the files were not dropped onto an editor, otherwise whether
+/// it has unsaved changes. Dropping several files always opens new editors.
+pub(crate) fn drop_action(file_count: usize, hovered_editor_dirty: Option<bool>) -> DropAction {
+    match (file_count, hovered_editor_dirty) {
+        (1, Some(false)) => DropAction::Replace,
+        (1, Some(true)) => DropAction::ConfirmReplace,
+        _ => DropAction::SpawnEditors,
+    }
+}
+
+pub(crate) fn open_dropped_curve_files(
+    mut events: EventReader<FileDragAndDrop>,
+    windows: Query<&Window>,
+    settings: Res<CurveDropSettings>,
+    asset_server: Res<AssetServer>,
+    mut curves: ResMut<Assets<LookupCurve>>,
+    mut editors: Query<(Entity, &mut LookupCurveEditor)>,
+    mut commands: Commands,
+) {
+    let mut dropped = vec![];
+    let mut cursor = None;
+    for event in events.read() {
+        if let FileDragAndDrop::DroppedFile { window, path_buf } = event {
+            if is_curve_file(path_buf) {
+                dropped.push(path_buf.clone());
+                cursor = cursor.or_else(|| {
+                    windows
+                        .get(*window)
+                        .ok()
+                        .and_then(|window| window.cursor_position())
+                });
+            }
+        }
+    }
+    if dropped.is_empty() {
+        return;
+    }
+
+    let hovered_editor = cursor.and_then(|cursor| {
+        editors.iter().find_map(|(entity, editor)| {
+            editor
+                .egui_editor
+                .window_rect
+                .is_some_and(|rect| rect.contains(egui::pos2(cursor.x, cursor.y)))
+                .then_some(entity)
+        })
+    });
+    let hovered_dirty = hovered_editor.and_then(|entity| {
+        let (_, editor) = editors.get(entity).ok()?;
+        let curve = curves.get(&editor.curve_handle)?;
+        Some(editor.egui_editor.is_dirty(curve))
+    });
+
+    match drop_action(dropped.len(), hovered_dirty) {
+        DropAction::SpawnEditors => {
+            for path in dropped {
+                spawn_editor(&path, &settings, &asset_server, &mut curves, &mut commands);
+            }
+        }
+        action @ (DropAction::Replace | DropAction::ConfirmReplace) => {
+            let (Some(entity), Some(path)) = (hovered_editor, dropped.first()) else {
+                return;
+            };
+            let Ok((_, mut editor)) = editors.get_mut(entity) else {
+                return;
+            };
+            let path = path.to_string_lossy().to_string();
+            match LookupCurve::load_from_file(&path) {
+                Ok(curve) if action == DropAction::Replace => {
+                    replace_curve(&mut editor, &mut curves, curve, path);
+                }
+                Ok(curve) => {
+                    commands
+                        .entity(entity)
+                        .insert(PendingCurveDrop { curve, path });
+                }
+                Err(e) => editor
+                    .egui_editor
+                    .show_error(format!("Could not open {path}: {e}")),
+            }
+        }
+    }
+}
+
+/// Asks whether to discard unsaved changes, for editors with a [PendingCurveDrop]
+pub(crate) fn confirm_curve_drop_ui(
+    mut pending: Query<(Entity, &mut LookupCurveEditor, &PendingCurveDrop)>,
+    mut contexts: EguiContexts,
+    mut curves: ResMut<Assets<LookupCurve>>,
+    mut commands: Commands,
+) {
+    for (entity, mut editor, pending_drop) in &mut pending {
+        let mut decision = None;
+        egui::Window::new("Unsaved changes")
+            .id(egui::Id::new((entity, "confirm_curve_drop")))
+            .collapsible(false)
+            .resizable(false)
+            .show(contexts.ctx_mut(), |ui| {
+                ui.label(format!(
+                    "Discard unsaved changes to {} and open {}?",
+                    curves
+                        .get(&editor.curve_handle)
+                        .map_or("the curve", |curve| curve.name_or_default()),
+                    pending_drop.path
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Open").clicked() {
+                        decision = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        decision = Some(false);
+                    }
+                });
+            });
+
+        if let Some(open) = decision {
+            if open {
+                replace_curve(
+                    &mut editor,
+                    &mut curves,
+                    pending_drop.curve.clone(),
+                    pending_drop.path.clone(),
+                );
+            }
+            commands.entity(entity).remove::<PendingCurveDrop>();
+        }
+    }
+}
+
+/// Replaces the curve of `editor` with `curve`, using `path` as save path from now on
+fn replace_curve(
+    editor: &mut LookupCurveEditor,
+    curves: &mut Assets<LookupCurve>,
+    curve: LookupCurve,
+    path: String,
+) {
+    let Some(target) = curves.get_mut(&editor.curve_handle) else {
+        return;
+    };
+    *target = curve;
+    editor.egui_editor.fit_to_curve(target);
+    editor.egui_editor.mark_saved(target);
+    editor.egui_editor.save_format = CurveFormat::from_path(&path);
+    editor.egui_editor.ron_path = Some(path);
+}
+
+fn spawn_editor(
+    path: &Path,
+    settings: &CurveDropSettings,
+    asset_server: &AssetServer,
+    curves: &mut Assets<LookupCurve>,
+    commands: &mut Commands,
+) {
+    let path_string = path.to_string_lossy().to_string();
+    // Read the file even when loading through the asset server, to report errors right away
+    let curve = match LookupCurve::load_from_file(&path_string) {
+        Ok(curve) => curve,
+        Err(e) => {
+            bevy_log::error!("Could not open dropped curve {path_string}: {e}");
+            return;
+        }
+    };
+
+    let mut egui_editor = LookupCurveEguiEditor::with_save_path(path_string);
+    egui_editor.fit_to_curve(&curve);
+
+    let asset_root = settings
+        .asset_root
+        .canonicalize()
+        .unwrap_or_else(|_| settings.asset_root.clone());
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let handle = match classify_path(&absolute, &asset_root) {
+        DroppedPath::Asset(relative) => asset_server.load(relative),
+        DroppedPath::External(_) => curves.add(curve),
+    };
+
+    let mut editor = LookupCurveEditor::new(handle);
+    editor.egui_editor = egui_editor;
+    commands.spawn(editor);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_curve_extensions() {
+        assert!(is_curve_file(Path::new("/tmp/jump.curve.ron")));
+        assert!(is_curve_file(Path::new("C:/Curves/Jump.CURVE.RON")));
+        assert!(!is_curve_file(Path::new("/tmp/jump.ron")));
+        assert!(!is_curve_file(Path::new("/tmp/jump.curve.ron.bak")));
+        assert!(!is_curve_file(Path::new("/tmp/.curve.ron")));
+        assert!(!is_curve_file(Path::new("/")));
+        assert_eq!(
+            is_curve_file(Path::new("/tmp/jump.curve.yml")),
+            cfg!(feature = "yaml")
+        );
+        assert_eq!(
+            is_curve_file(Path::new("/tmp/jump.curve.toml")),
+            cfg!(feature = "toml")
+        );
+    }
+
+    #[test]
+    fn classifies_paths() {
+        let root = Path::new("/game/assets");
+        assert_eq!(
+            classify_path(Path::new("/game/assets/curves/jump.curve.ron"), root),
+            DroppedPath::Asset(PathBuf::from("curves/jump.curve.ron"))
+        );
+        assert_eq!(
+            classify_path(Path::new("/game/assets_old/jump.curve.ron"), root),
+            DroppedPath::External(PathBuf::from("/game/assets_old/jump.curve.ron"))
+        );
+        assert_eq!(
+            classify_path(Path::new("/home/jump.curve.ron"), root),
+            DroppedPath::External(PathBuf::from("/home/jump.curve.ron"))
+        );
+        assert_eq!(
+            classify_path(root, root),
+            DroppedPath::External(root.to_path_buf())
+        );
+    }
+
+    #[test]
+    fn drop_decisions() {
+        assert_eq!(drop_action(1, None), DropAction::SpawnEditors);
+        assert_eq!(drop_action(1, Some(false)), DropAction::Replace);
+        assert_eq!(drop_action(1, Some(true)), DropAction::ConfirmReplace);
+        assert_eq!(drop_action(3, Some(false)), DropAction::SpawnEditors);
+        assert_eq!(drop_action(3, None), DropAction::SpawnEditors);
+    }
+}
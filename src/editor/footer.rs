@@ -0,0 +1,123 @@
+use crate::{
+    stats::{CurveStats, Monotonicity},
+    LookupCurve,
+};
+
+bitflags::bitflags! {
+    /// Selects which [CurveStats] are shown in the footer of the editor
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct FooterStats: u8 {
+        const KNOT_COUNT = 1;
+        const DOMAIN = 1 << 1;
+        const RANGE = 1 << 2;
+        const MONOTONICITY = 1 << 3;
+        const TOTAL_VARIATION = 1 << 4;
+        const CORRECTIONS = 1 << 5;
+    }
+}
+
+impl Default for FooterStats {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Caches [CurveStats] until the [LookupCurve::generation] changes
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FooterStatsCache {
+    generation: Option<u64>,
+    stats: Option<CurveStats>,
+}
+
+impl FooterStatsCache {
+    /// Recomputes the stats if the curve has changed since the last update. Returns `true` if recomputed.
+    pub(crate) fn update(&mut self, curve: &LookupCurve) -> bool {
+        if self.generation == Some(curve.generation()) && self.stats.is_some() {
+            return false;
+        }
+        self.generation = Some(curve.generation());
+        self.stats = Some(CurveStats::compute(curve));
+        true
+    }
+
+    pub(crate) fn stats(&self) -> Option<&CurveStats> {
+        self.stats.as_ref()
+    }
+}
+
+/// Returns the footer entries as `(label, value)`, for the stats selected by `footer_stats`.
+pub(crate) fn footer_entries(
+    stats: &CurveStats,
+    footer_stats: FooterStats,
+) -> Vec<(&'static str, String)> {
+    let range_text = |range: Option<(f32, f32)>| match range {
+        Some((min, max)) => format!("{min:.3}..{max:.3}"),
+        None => "-".to_string(),
+    };
+
+    let mut entries = vec![];
+    if footer_stats.contains(FooterStats::KNOT_COUNT) {
+        entries.push(("Knots", stats.knot_count.to_string()));
+    }
+    if footer_stats.contains(FooterStats::DOMAIN) {
+        entries.push(("Domain", range_text(stats.domain)));
+    }
+    if footer_stats.contains(FooterStats::RANGE) {
+        entries.push(("Range", range_text(stats.range)));
+    }
+    if footer_stats.contains(FooterStats::MONOTONICITY) {
+        let monotonicity = match stats.monotonicity {
+            Monotonicity::Constant => "→",
+            Monotonicity::Increasing => "↑",
+            Monotonicity::Decreasing => "↓",
+            Monotonicity::Mixed => "mixed",
+        };
+        entries.push(("Monotonic", monotonicity.to_string()));
+    }
+    if footer_stats.contains(FooterStats::TOTAL_VARIATION) {
+        entries.push(("Variation", format!("{:.3}", stats.total_variation)));
+    }
+    if footer_stats.contains(FooterStats::CORRECTIONS) {
+        entries.push(("Corrections", stats.corrections.to_string()));
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy_math::Vec2;
+
+    #[test]
+    fn stats_refresh_exactly_on_generation_change() {
+        let mut curve = LookupCurve::new(vec![Knot::default()]);
+        let mut cache = FooterStatsCache::default();
+
+        assert!(cache.update(&curve));
+        assert!(!cache.update(&curve));
+        assert_eq!(cache.stats().unwrap().knot_count, 1);
+
+        curve.add_knot(Knot {
+            position: Vec2::ONE,
+            ..Default::default()
+        });
+        assert!(cache.update(&curve));
+        assert!(!cache.update(&curve));
+        assert_eq!(cache.stats().unwrap().knot_count, 2);
+        assert_eq!(cache.stats().unwrap().range, Some((0.0, 1.0)));
+
+        let _ = curve.lookup(0.5);
+        assert!(!cache.update(&curve));
+    }
+
+    #[test]
+    fn entries_follow_flags() {
+        let stats = CurveStats::compute(&LookupCurve::new(vec![Knot::default()]));
+        assert_eq!(footer_entries(&stats, FooterStats::empty()).len(), 0);
+        let entries = footer_entries(&stats, FooterStats::KNOT_COUNT | FooterStats::RANGE);
+        assert_eq!(entries[0], ("Knots", "1".to_string()));
+        assert_eq!(entries[1].0, "Range");
+        assert_eq!(footer_entries(&stats, FooterStats::all()).len(), 6);
+    }
+}
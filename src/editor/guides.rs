@@ -0,0 +1,211 @@
+use bevy_math::Vec2;
+
+use crate::quantize;
+
+/// Direction of a [Guide]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GuideAxis {
+    /// A line of constant y, created from the ruler left of the plot
+    Horizontal,
+    /// A line of constant x, created from the ruler below the plot
+    Vertical,
+}
+
+impl GuideAxis {
+    /// The coordinate of `position` that is constant along guides of this axis
+    fn coordinate(self, position: Vec2) -> f32 {
+        match self {
+            GuideAxis::Horizontal => position.y,
+            GuideAxis::Vertical => position.x,
+        }
+    }
+}
+
+/// A guide line of a [LookupCurveEguiEditor](super::LookupCurveEguiEditor), which dragged knots snap to
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Guide {
+    /// Id of the guide, unique within its [Guides]
+    pub id: usize,
+    pub axis: GuideAxis,
+    /// y of horizontal guides, x of vertical guides
+    pub value: f32,
+}
+
+/// Guide lines of an editor, kept with the editor and not with the curve
+#[derive(Clone, Debug, Default)]
+pub struct Guides {
+    guides: Vec<Guide>,
+    next_id: usize,
+}
+
+impl Guides {
+    /// Adds a guide and returns its id
+    pub fn add(&mut self, axis: GuideAxis, value: f32) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.guides.push(Guide { id, axis, value });
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Guide> {
+        self.guides.iter().find(|guide| guide.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Guide> {
+        self.guides.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.guides.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guides.is_empty()
+    }
+
+    /// Moves the guide with `id` to `value`. Returns `false` if there is no such guide.
+    pub fn move_to(&mut self, id: usize, value: f32) -> bool {
+        match self.guides.iter_mut().find(|guide| guide.id == id) {
+            Some(guide) => {
+                guide.value = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the guide with `id`, returning it
+    pub fn remove(&mut self, id: usize) -> Option<Guide> {
+        let i = self.guides.iter().position(|guide| guide.id == id)?;
+        Some(self.guides.remove(i))
+    }
+
+    /// Returns the guide closest to `position`, with its distance measured in multiples of `unit` along its axis.
+    ///
+    /// On ties, the guide added first is returned.
+    pub fn closest(&self, position: Vec2, unit: Vec2) -> Option<(&Guide, f32)> {
+        self.guides
+            .iter()
+            .map(|guide| {
+                let distance = (guide.axis.coordinate(position) - guide.value).abs()
+                    / guide.axis.coordinate(unit).abs();
+                (guide, distance)
+            })
+            .filter(|(_, distance)| !distance.is_nan())
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// Returns the guide within `tolerance` of `position`, the closest one if there are several
+    pub fn hit_test(&self, position: Vec2, tolerance: Vec2) -> Option<&Guide> {
+        self.closest(position, tolerance)
+            .filter(|(_, distance)| *distance <= 1.0)
+            .map(|(guide, _)| guide)
+    }
+
+    /// Returns the value of the guide along `axis` nearest to `value`
+    pub fn nearest(&self, axis: GuideAxis, value: f32) -> Option<f32> {
+        self.guides
+            .iter()
+            .filter(|guide| guide.axis == axis)
+            .map(|guide| guide.value)
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+    }
+
+    /// Snaps `position` for placing a knot, separately for x and y.
+    ///
+    /// A guide within `tolerance` takes priority, then rounding to multiples of the step of the axis in `grid_step`,
+    /// and otherwise the coordinate is kept.
+    pub fn snap(&self, position: Vec2, tolerance: Vec2, grid_step: Option<Vec2>) -> Vec2 {
+        let snap_axis = |axis: GuideAxis| {
+            let value = axis.coordinate(position);
+            match self.nearest(axis, value) {
+                Some(guide) if (guide - value).abs() <= axis.coordinate(tolerance).abs() => guide,
+                _ => grid_step.map_or(value, |step| quantize(value, axis.coordinate(step))),
+            }
+        };
+        Vec2::new(
+            snap_axis(GuideAxis::Vertical),
+            snap_axis(GuideAxis::Horizontal),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_stable() {
+        let mut guides = Guides::default();
+        let a = guides.add(GuideAxis::Horizontal, 1.0);
+        let b = guides.add(GuideAxis::Vertical, 2.0);
+        assert_ne!(a, b);
+        assert_eq!(guides.remove(a).map(|guide| guide.value), Some(1.0));
+        assert!(guides.remove(a).is_none());
+        // Ids of removed guides are not reused
+        let c = guides.add(GuideAxis::Horizontal, 3.0);
+        assert_ne!(c, a);
+
+        assert!(guides.move_to(b, 5.0));
+        assert!(!guides.move_to(a, 5.0));
+        assert_eq!(guides.get(b).unwrap().value, 5.0);
+        assert_eq!(guides.len(), 2);
+    }
+
+    #[test]
+    fn hit_test_measures_along_axis() {
+        let mut guides = Guides::default();
+        let horizontal = guides.add(GuideAxis::Horizontal, 1.0);
+        let vertical = guides.add(GuideAxis::Vertical, 10.0);
+        let tolerance = Vec2::new(1.0, 0.1);
+
+        // Far from the guides along their lines does not matter
+        let hit = guides.hit_test(Vec2::new(-100.0, 1.05), tolerance);
+        assert_eq!(hit.map(|guide| guide.id), Some(horizontal));
+        let hit = guides.hit_test(Vec2::new(10.5, 100.0), tolerance);
+        assert_eq!(hit.map(|guide| guide.id), Some(vertical));
+        assert!(guides.hit_test(Vec2::new(5.0, 1.5), tolerance).is_none());
+
+        // Closest relative to the tolerance of each axis
+        let hit = guides.hit_test(Vec2::new(10.8, 1.05), tolerance);
+        assert_eq!(hit.map(|guide| guide.id), Some(horizontal));
+        assert!(Guides::default().hit_test(Vec2::ZERO, tolerance).is_none());
+    }
+
+    #[test]
+    fn guides_snap_before_grid() {
+        let mut guides = Guides::default();
+        guides.add(GuideAxis::Horizontal, 0.33);
+        guides.add(GuideAxis::Vertical, 2.0);
+        guides.add(GuideAxis::Vertical, 2.2);
+        let tolerance = Vec2::splat(0.1);
+
+        assert_eq!(
+            guides.snap(Vec2::new(2.14, 0.3), tolerance, Some(Vec2::splat(0.5))),
+            Vec2::new(2.2, 0.33)
+        );
+        // Out of reach of the guides, falls back to the grid
+        assert_eq!(
+            guides.snap(Vec2::new(2.6, 0.7), tolerance, Some(Vec2::splat(0.5))),
+            Vec2::new(2.5, 0.5)
+        );
+        assert_eq!(
+            guides.snap(Vec2::new(2.6, 0.7), tolerance, None),
+            Vec2::new(2.6, 0.7)
+        );
+        // Snapping is per axis
+        assert_eq!(
+            guides.snap(Vec2::new(1.95, 0.7), tolerance, None),
+            Vec2::new(2.0, 0.7)
+        );
+        assert_eq!(
+            Guides::default().snap(Vec2::new(1.95, 0.7), tolerance, Some(Vec2::splat(0.5))),
+            Vec2::new(2.0, 0.5)
+        );
+        // Each axis has its own grid step
+        assert_eq!(
+            guides.snap(Vec2::new(2.6, 0.7), tolerance, Some(Vec2::new(1.0, 0.25))),
+            Vec2::new(3.0, 0.75)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::VecDeque;
+
+use crate::LookupCurve;
+
+/// Default of [EditHistory::limit]
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Undo and redo history of the edits of a curve, see [LookupCurveEguiEditor::history](super::LookupCurveEguiEditor::history)
+///
+/// Each step keeps the whole curve as it was before the edit, so that every [EditorAction](super::EditorAction) can
+/// be undone the same way. While the pointer is held, like during a drag, all edits are coalesced into one step.
+#[derive(Clone, Debug)]
+pub struct EditHistory {
+    /// Maximum number of undo steps, the oldest steps are dropped beyond it
+    pub limit: usize,
+    undo: VecDeque<LookupCurve>,
+    redo: Vec<LookupCurve>,
+    /// Edits are coalesced into one step while set
+    coalescing: bool,
+    /// Whether the last step was recorded while coalescing, so that further edits belong to it
+    coalesced: bool,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl EditHistory {
+    /// An empty history keeping at most `limit` undo steps
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            coalescing: false,
+            coalesced: false,
+        }
+    }
+
+    /// Number of steps that can be undone
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Number of undone steps that can be redone
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Removes all steps
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.coalesced = false;
+    }
+
+    /// Restores `curve` to the state before the last step. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self, curve: &mut LookupCurve) -> bool {
+        let Some(before) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(restore(curve, before));
+        self.coalesced = false;
+        true
+    }
+
+    /// Restores `curve` to the state after the last undone step. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self, curve: &mut LookupCurve) -> bool {
+        let Some(after) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push_back(restore(curve, after));
+        self.coalesced = false;
+        true
+    }
+
+    /// Adds the steps of `later`, recorded after the steps of this history. Replaces the redo steps if `later` has
+    /// any steps.
+    #[cfg_attr(not(feature = "editor_bevy"), allow(dead_code))]
+    pub(crate) fn append(&mut self, later: EditHistory) {
+        if later.undo.is_empty() && later.redo.is_empty() {
+            return;
+        }
+        self.undo.extend(later.undo);
+        self.redo = later.redo;
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Starts coalescing edits into one step, or stops it
+    pub(crate) fn set_coalescing(&mut self, coalescing: bool) {
+        self.coalescing = coalescing;
+        if !coalescing {
+            self.coalesced = false;
+        }
+    }
+
+    /// Whether the next edit needs the state of the curve before it, or belongs to the last step
+    pub(crate) fn needs_snapshot(&self) -> bool {
+        self.limit > 0 && !(self.coalescing && self.coalesced)
+    }
+
+    /// Records an edit, with the state of the curve before it if [Self::needs_snapshot]. Clears the redo steps.
+    pub(crate) fn record(&mut self, before: Option<LookupCurve>) {
+        self.redo.clear();
+        if let Some(before) = before {
+            self.undo.push_back(before);
+            self.coalesced = self.coalescing;
+        }
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+/// Replaces `curve` with `state` and returns the replaced curve. The generation keeps counting up from the replaced
+/// curve, so that data derived from the replaced curve is not mistaken for data of `state`.
+fn restore(curve: &mut LookupCurve, state: LookupCurve) -> LookupCurve {
+    let replaced = std::mem::replace(curve, state);
+    curve.generation = replaced.generation;
+    curve.bump_generation();
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+
+    use super::*;
+    use crate::Knot;
+
+    fn edit(history: &mut EditHistory, curve: &mut LookupCurve, x: f32) {
+        let before = history.needs_snapshot().then(|| curve.clone());
+        curve.add_knot(Knot {
+            position: Vec2::new(x, 0.0),
+            ..Default::default()
+        });
+        history.record(before);
+    }
+
+    fn xs(curve: &LookupCurve) -> Vec<f32> {
+        curve.knots().iter().map(|knot| knot.position.x).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_steps() {
+        let mut history = EditHistory::new(3);
+        let mut curve = LookupCurve::default();
+        for x in 0..5 {
+            edit(&mut history, &mut curve, x as f32);
+        }
+        assert_eq!(history.undo_len(), 3);
+
+        let generation = curve.generation();
+        assert!(history.undo(&mut curve));
+        assert_eq!(xs(&curve), [0.0, 1.0, 2.0, 3.0]);
+        assert!(curve.generation() > generation);
+        assert!(history.undo(&mut curve));
+        assert!(history.redo(&mut curve));
+        assert_eq!(xs(&curve), [0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(history.redo_len(), 1);
+
+        // A new edit after undoing drops the undone steps
+        edit(&mut history, &mut curve, 9.0);
+        assert_eq!(history.redo_len(), 0);
+        assert!(!history.redo(&mut curve));
+        for _ in 0..3 {
+            assert!(history.undo(&mut curve));
+        }
+        assert!(!history.undo(&mut curve));
+        assert_eq!(xs(&curve), [0.0, 1.0]);
+    }
+
+    #[test]
+    fn coalesced_edits_are_one_step() {
+        let mut history = EditHistory::default();
+        let mut curve = LookupCurve::default();
+        edit(&mut history, &mut curve, 0.0);
+        history.set_coalescing(true);
+        for x in 1..10 {
+            edit(&mut history, &mut curve, x as f32);
+        }
+        history.set_coalescing(false);
+        edit(&mut history, &mut curve, 10.0);
+        assert_eq!(history.undo_len(), 3);
+
+        history.undo(&mut curve);
+        history.undo(&mut curve);
+        assert_eq!(xs(&curve), [0.0]);
+
+        let mut disabled = EditHistory::new(0);
+        edit(&mut disabled, &mut curve, 1.0);
+        assert!(!disabled.undo(&mut curve));
+    }
+}
@@ -1,7 +1,61 @@
 mod editor_egui;
 pub use editor_egui::*;
 
+mod action;
+pub use action::{ActionError, ActionOutcome, EditorAction};
+
+mod clipboard;
+pub use clipboard::{KnotClipboard, PastePlacement};
+
+#[cfg_attr(not(feature = "editor_bevy"), allow(dead_code))]
+mod compare;
+
+mod display_transform;
+pub use display_transform::{DisplayTransform, DECIBEL_FLOOR, SEMITONE_FLOOR};
+
+mod draw;
+
+mod edit_response;
+pub use edit_response::CurveEditResponse;
+
+mod edit_scope;
+
+mod footer;
+pub use footer::FooterStats;
+
+mod guides;
+pub use guides::{Guide, GuideAxis, Guides};
+
+mod history;
+pub use history::{EditHistory, DEFAULT_HISTORY_LIMIT};
+
+mod multi_edit;
+pub use multi_edit::{KnotEdit, RelativeValue};
+
+#[cfg_attr(not(feature = "editor_bevy"), allow(dead_code))]
+mod mutation_policy;
+pub use mutation_policy::MutationPolicy;
+
+mod render_cache;
+
+mod sample_trail;
+pub use sample_trail::TrailPoint;
+
+mod segment_drag;
+
+#[cfg(feature = "editor_bevy")]
+mod curve_scrub;
 #[cfg(feature = "editor_bevy")]
 mod editor_bevy;
 #[cfg(feature = "editor_bevy")]
+mod file_drop;
+#[cfg(feature = "editor_bevy")]
+pub use curve_scrub::{CurveScrub, CurveScrubChanged};
+#[cfg(feature = "editor_bevy")]
 pub use editor_bevy::*;
+#[cfg(feature = "editor_bevy")]
+pub use file_drop::CurveDropSettings;
+#[cfg(all(feature = "editor_bevy", feature = "bevy_reflect"))]
+mod target;
+#[cfg(all(feature = "editor_bevy", feature = "bevy_reflect"))]
+pub use target::{LookupCurveEditorTarget, TargetError};
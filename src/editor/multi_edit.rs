@@ -0,0 +1,165 @@
+use crate::{Knot, KnotInterpolation, TangentMode, TangentSide, UnitDisplay, UnitParseError};
+
+/// Value entered in a numeric field when editing several knots, applied to the value of each knot
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RelativeValue {
+    /// A plain number, replacing the value
+    Set(f32),
+    /// `+=` or `-=` followed by a number
+    Add(f32),
+    /// `*=` followed by a number
+    Multiply(f32),
+}
+
+impl RelativeValue {
+    /// Parses a plain number, or a number prefixed by `+=`, `-=` or `*=`.
+    ///
+    /// Numbers to set or add are parsed with `unit`, the factor of `*=` is unitless.
+    pub fn parse(text: &str, unit: &UnitDisplay) -> Result<Self, UnitParseError> {
+        let text = text.trim();
+        if let Some(number) = text.strip_prefix("+=") {
+            Ok(RelativeValue::Add(unit.parse(number)?))
+        } else if let Some(number) = text.strip_prefix("-=") {
+            Ok(RelativeValue::Add(-unit.parse(number)?))
+        } else if let Some(number) = text.strip_prefix("*=") {
+            Ok(RelativeValue::Multiply(
+                UnitDisplay::default().parse(number)?,
+            ))
+        } else {
+            Ok(RelativeValue::Set(unit.parse(text)?))
+        }
+    }
+
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            RelativeValue::Set(set) => set,
+            RelativeValue::Add(delta) => value + delta,
+            RelativeValue::Multiply(factor) => value * factor,
+        }
+    }
+}
+
+/// A change applied to each knot by [EditorAction::EditKnots](super::EditorAction::EditKnots)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KnotEdit {
+    Interpolation(KnotInterpolation),
+    /// Sets the mode of both tangents
+    TangentMode(TangentMode),
+    Y(RelativeValue),
+}
+
+impl KnotEdit {
+    pub(crate) fn apply(self, knot: Knot) -> Knot {
+        match self {
+            KnotEdit::Interpolation(interpolation) => Knot {
+                interpolation,
+                ..knot
+            },
+            KnotEdit::TangentMode(mode) => knot
+                .with_tangent_mode(TangentSide::Left, mode)
+                .with_tangent_mode(TangentSide::Right, mode),
+            KnotEdit::Y(value) => {
+                let mut knot = knot;
+                knot.position.y = value.apply(knot.position.y);
+                knot
+            }
+        }
+    }
+}
+
+/// A property of several knots
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Shared<T> {
+    /// There are no knots
+    None,
+    /// All knots have this value
+    Same(T),
+    /// The knots have different values
+    Mixed,
+}
+
+impl<T: PartialEq> Shared<T> {
+    pub(crate) fn of(values: impl IntoIterator<Item = T>) -> Self {
+        let mut values = values.into_iter();
+        let Some(first) = values.next() else {
+            return Shared::None;
+        };
+        if values.all(|value| value == first) {
+            Shared::Same(first)
+        } else {
+            Shared::Mixed
+        }
+    }
+
+    /// Text shown for the property, "mixed" if the values differ
+    pub(crate) fn label(&self, format: impl FnOnce(&T) -> String) -> String {
+        match self {
+            Shared::None => String::new(),
+            Shared::Same(value) => format(value),
+            Shared::Mixed => "mixed".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_relative_values() {
+        let plain = UnitDisplay::default();
+        assert_eq!(
+            RelativeValue::parse("0.5", &plain),
+            Ok(RelativeValue::Set(0.5))
+        );
+        assert_eq!(
+            RelativeValue::parse(" += 0.1", &plain),
+            Ok(RelativeValue::Add(0.1))
+        );
+        assert_eq!(
+            RelativeValue::parse("-=2", &plain),
+            Ok(RelativeValue::Add(-2.0))
+        );
+        assert_eq!(
+            RelativeValue::parse("-2", &plain),
+            Ok(RelativeValue::Set(-2.0))
+        );
+        assert_eq!(
+            RelativeValue::parse("*=2", &plain),
+            Ok(RelativeValue::Multiply(2.0))
+        );
+        assert!(RelativeValue::parse("+=", &plain).is_err());
+        assert!(RelativeValue::parse("/=2", &plain).is_err());
+        assert!(RelativeValue::parse("*=inf", &plain).is_err());
+
+        // Units apply to set and added values, not to factors
+        let ms = UnitDisplay::new("ms").with_scale(1000.0);
+        assert_eq!(
+            RelativeValue::parse("+=100 ms", &ms),
+            Ok(RelativeValue::Add(0.1))
+        );
+        assert_eq!(
+            RelativeValue::parse("*=3", &ms),
+            Ok(RelativeValue::Multiply(3.0))
+        );
+
+        assert_eq!(RelativeValue::Add(-1.5).apply(1.0), -0.5);
+        assert_eq!(RelativeValue::Multiply(2.0).apply(1.5), 3.0);
+        assert_eq!(RelativeValue::Set(4.0).apply(1.5), 4.0);
+    }
+
+    #[test]
+    fn mixed_values() {
+        assert_eq!(Shared::<f32>::of([]), Shared::None);
+        assert_eq!(Shared::of([1.0, 1.0]), Shared::Same(1.0));
+        assert_eq!(Shared::of([1.0, 1.0, 2.0]), Shared::Mixed);
+        assert_eq!(
+            Shared::of([KnotInterpolation::Cubic, KnotInterpolation::Linear]).label(|_| "".into()),
+            "mixed"
+        );
+        assert_eq!(
+            Shared::of([TangentMode::Free]).label(|m| format!("{m:?}")),
+            "Free"
+        );
+    }
+}
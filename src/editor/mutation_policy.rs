@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use super::LookupCurveEguiEditor;
+use crate::LookupCurve;
+
+/// When an editor writes its edits to the edited curve asset, see
+/// [LookupCurveEditor::mutation_policy](super::LookupCurveEditor::mutation_policy).
+///
+/// Edits are always shown in the editor right away. Every write modifies the asset, and sends an asset modified event
+/// and a [LookupCurveEdited](super::LookupCurveEdited) event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum MutationPolicy {
+    /// Write in every frame the curve was changed in
+    EveryFrame,
+    /// Write once the user releases the pointer, so a drag is written once when it completes
+    OnRelease,
+    /// Write at most once per interval while the user interacts with the editor, and once the pointer is released
+    Throttled(Duration),
+}
+
+impl Default for MutationPolicy {
+    fn default() -> Self {
+        MutationPolicy::Throttled(Duration::from_millis(100))
+    }
+}
+
+/// Copy of the edited curve the editor shows and edits, written to the asset according to a [MutationPolicy]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PendingCurve {
+    working: Option<LookupCurve>,
+    /// Generation of the asset when it was copied or last written
+    synced_generation: Option<u64>,
+    last_write: Option<Duration>,
+    detached: bool,
+}
+
+impl PendingCurve {
+    /// The copy to show and edit. Replaced by a new copy of `asset` if the asset was changed outside of the editor,
+    /// dropping edits that were not written yet.
+    pub(crate) fn working(&mut self, asset: &LookupCurve) -> &mut LookupCurve {
+        if !self.detached && self.synced_generation != Some(asset.generation()) {
+            self.synced_generation = Some(asset.generation());
+            self.working = None;
+        }
+        self.working.get_or_insert_with(|| asset.clone())
+    }
+
+    /// While detached the asset shows another curve, see [SavedCompare](super::compare::SavedCompare). The copy is
+    /// kept when the asset changes, and edits are not written until [Self::write_now].
+    pub(crate) fn set_detached(&mut self, detached: bool) {
+        self.detached = detached;
+    }
+
+    /// Returns the copy to write to the asset now, whether or not it has edits. The caller must write it.
+    pub(crate) fn write_now(&mut self, now: Duration) -> Option<&LookupCurve> {
+        let working = self.working.as_ref()?;
+        self.synced_generation = Some(working.generation());
+        self.last_write = Some(now);
+        Some(working)
+    }
+
+    /// Returns the copy if it has edits that should be written to the asset now. The caller must write it, so that
+    /// the asset has the generation of the copy.
+    ///
+    /// `interacting` is whether the user is still interacting with the editor, `now` the time since startup.
+    pub(crate) fn take_write(
+        &mut self,
+        policy: MutationPolicy,
+        interacting: bool,
+        now: Duration,
+    ) -> Option<&LookupCurve> {
+        let working = self.working.as_ref()?;
+        if self.detached || self.synced_generation == Some(working.generation()) {
+            return None;
+        }
+        let due = match policy {
+            MutationPolicy::EveryFrame => true,
+            MutationPolicy::OnRelease => !interacting,
+            MutationPolicy::Throttled(interval) => {
+                !interacting
+                    || self
+                        .last_write
+                        .is_none_or(|last| now.saturating_sub(last) >= interval)
+            }
+        };
+        if !due {
+            return None;
+        }
+        self.synced_generation = Some(working.generation());
+        self.last_write = Some(now);
+        Some(working)
+    }
+}
+
+impl LookupCurveEguiEditor {
+    /// Whether a pointer button pressed within the editor window is still held down, like during a drag
+    pub fn is_interacting(&self, ctx: &egui::Context) -> bool {
+        let Some(rect) = self.window_rect else {
+            return false;
+        };
+        ctx.input(|input| {
+            input.pointer.any_down()
+                && input
+                    .pointer
+                    .press_origin()
+                    .is_some_and(|origin| rect.contains(origin))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy_math::Vec2;
+
+    const FRAME: Duration = Duration::from_millis(10);
+
+    /// Drags the second knot for 60 frames, then releases it. Returns the number of writes and the final positions.
+    fn drag(policy: MutationPolicy) -> (usize, Vec<Vec2>) {
+        let mut asset = LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::new(1.0, 1.0),
+                ..Default::default()
+            },
+        ]);
+        let mut pending = PendingCurve::default();
+        let mut writes = 0;
+        for frame in 0..=60 {
+            let interacting = frame < 60;
+            let working = pending.working(&asset);
+            if interacting {
+                let mut knot = working.knots()[1];
+                knot.position.y += 0.01;
+                working.modify_knot(1, knot);
+            }
+            if let Some(curve) = pending.take_write(policy, interacting, FRAME * frame) {
+                asset = curve.clone();
+                writes += 1;
+            }
+        }
+        (
+            writes,
+            asset.knots().iter().map(|knot| knot.position).collect(),
+        )
+    }
+
+    #[test]
+    fn drags_are_written_according_to_policy() {
+        let (every_frame, expected) = drag(MutationPolicy::EveryFrame);
+        assert_eq!(every_frame, 60);
+        assert!((expected[1].y - 1.6).abs() < 1e-4);
+
+        let (on_release, curve) = drag(MutationPolicy::OnRelease);
+        assert_eq!(on_release, 1);
+        assert_eq!(curve, expected);
+
+        // At 0, 100, ..., 500 ms while dragging, and once more on release
+        let (throttled, curve) = drag(MutationPolicy::Throttled(FRAME * 10));
+        assert_eq!(throttled, 7);
+        assert_eq!(curve, expected);
+    }
+
+    #[test]
+    fn external_changes_replace_the_copy() {
+        let mut asset = LookupCurve::new(vec![Knot::default()]);
+        let mut pending = PendingCurve::default();
+        pending.working(&asset).add_knot(Knot {
+            position: Vec2::ONE,
+            ..Default::default()
+        });
+        assert!(pending
+            .take_write(MutationPolicy::OnRelease, true, Duration::ZERO)
+            .is_none());
+
+        asset.delete_knot(0);
+        assert!(pending.working(&asset).knots().is_empty());
+        assert!(pending
+            .take_write(MutationPolicy::EveryFrame, false, Duration::ZERO)
+            .is_none());
+    }
+
+    #[test]
+    fn detached_copies_are_kept_until_written() {
+        let mut asset = LookupCurve::new(vec![Knot::default()]);
+        let mut pending = PendingCurve::default();
+        pending.working(&asset);
+        pending.set_detached(true);
+        pending.working(&asset).add_knot(Knot {
+            position: Vec2::ONE,
+            ..Default::default()
+        });
+        assert!(pending
+            .take_write(MutationPolicy::EveryFrame, false, Duration::ZERO)
+            .is_none());
+
+        // The asset shows another curve meanwhile
+        asset = LookupCurve::default();
+        assert_eq!(pending.working(&asset).knots().len(), 2);
+
+        pending.set_detached(false);
+        asset = pending.write_now(Duration::ZERO).unwrap().clone();
+        assert_eq!(pending.working(&asset).knots().len(), 2);
+        assert!(pending
+            .take_write(MutationPolicy::EveryFrame, false, Duration::ZERO)
+            .is_none());
+    }
+}
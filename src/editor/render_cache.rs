@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use egui::{Context, FontId, Galley, Id, Style, TextStyle};
+
+/// Max number of editors rebuilding their caches after a style change within a single frame
+pub(crate) const MAX_REBUILDS_PER_FRAME: usize = 1;
+
+/// Font used for tick labels
+pub(crate) fn label_font(style: &Style) -> FontId {
+    TextStyle::Body.resolve(style)
+}
+
+/// Hash of the style settings that affect the cached rendering of the editor.
+///
+/// Only changes when the scale or the label font changes, not for unrelated settings like spacing or colors.
+pub(crate) fn style_key(style: &Style, pixels_per_point: f32) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    pixels_per_point.to_bits().hash(&mut hasher);
+    label_font(style).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Limits the number of cache rebuilds per frame, shared by all editors of an egui context
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RebuildBudget {
+    frame: u64,
+    used: usize,
+}
+
+impl RebuildBudget {
+    /// Returns `true` if a rebuild is allowed in `frame`, counting it towards the budget
+    pub(crate) fn try_acquire(&mut self, frame: u64, max_per_frame: usize) -> bool {
+        if self.frame != frame {
+            self.frame = frame;
+            self.used = 0;
+        }
+        if self.used >= max_per_frame {
+            return false;
+        }
+        self.used += 1;
+        true
+    }
+
+    /// [RebuildBudget::try_acquire] for the current frame of `ctx`, using the budget stored in its memory
+    pub(crate) fn acquire_in(ctx: &Context) -> bool {
+        let frame = ctx.frame_nr();
+        ctx.data_mut(|data| {
+            data.get_temp_mut_or_default::<RebuildBudget>(Id::new(
+                "lookup_curve_editor_rebuild_budget",
+            ))
+            .try_acquire(frame, MAX_REBUILDS_PER_FRAME)
+        })
+    }
+}
+
+/// Laid out tick labels, kept until the style changes or the label is no longer shown
+#[derive(Clone, Debug)]
+pub(crate) struct TickLabelCache<G = Arc<Galley>> {
+    /// [style_key] the labels were laid out with
+    style_key: Option<u64>,
+    labels: HashMap<String, G>,
+    /// Labels shown since the last [TickLabelCache::retain_used]
+    used: HashSet<String>,
+}
+
+impl<G> Default for TickLabelCache<G> {
+    fn default() -> Self {
+        Self {
+            style_key: None,
+            labels: HashMap::new(),
+            used: HashSet::new(),
+        }
+    }
+}
+
+impl<G: Clone> TickLabelCache<G> {
+    /// Clears the cache if `style_key` has changed.
+    ///
+    /// An empty cache is reset right away, otherwise only if `acquire` grants a rebuild, and the stale labels
+    /// are kept until then. Returns `true` if the cache is still stale.
+    pub(crate) fn sync_style(&mut self, style_key: u64, acquire: impl FnOnce() -> bool) -> bool {
+        match self.style_key {
+            Some(key) if key == style_key => false,
+            Some(_) if !self.labels.is_empty() && !acquire() => true,
+            _ => {
+                self.style_key = Some(style_key);
+                self.labels.clear();
+                false
+            }
+        }
+    }
+
+    /// Returns the cached label for `text`, laying it out if missing
+    pub(crate) fn get_or_layout(&mut self, text: String, layout: impl FnOnce(String) -> G) -> G {
+        let label = match self.labels.get(&text) {
+            Some(label) => label.clone(),
+            None => {
+                let label = layout(text.clone());
+                self.labels.insert(text.clone(), label.clone());
+                label
+            }
+        };
+        self.used.insert(text);
+        label
+    }
+
+    /// Removes labels that were not shown since the last call
+    pub(crate) fn retain_used(&mut self) {
+        let used = std::mem::take(&mut self.used);
+        self.labels.retain(|text, _| used.contains(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style_key_tracks_relevant_fields() {
+        let style = Style::default();
+        let key = style_key(&style, 1.0);
+        assert_eq!(style_key(&style.clone(), 1.0), key);
+        assert_ne!(style_key(&style, 1.5), key);
+
+        let mut unrelated = style.clone();
+        unrelated.spacing.item_spacing *= 2.0;
+        unrelated.visuals.dark_mode = !unrelated.visuals.dark_mode;
+        unrelated
+            .text_styles
+            .insert(TextStyle::Small, FontId::proportional(30.0));
+        assert_eq!(style_key(&unrelated, 1.0), key);
+
+        let mut larger_font = style.clone();
+        larger_font
+            .text_styles
+            .insert(TextStyle::Body, FontId::proportional(30.0));
+        assert_ne!(style_key(&larger_font, 1.0), key);
+
+        let mut monospace = style;
+        monospace.text_styles.insert(
+            TextStyle::Body,
+            FontId::monospace(label_font(&Style::default()).size),
+        );
+        assert_ne!(style_key(&monospace, 1.0), key);
+    }
+
+    #[test]
+    fn budget_limits_rebuilds_per_frame() {
+        let mut budget = RebuildBudget::default();
+        assert!(budget.try_acquire(1, 2));
+        assert!(budget.try_acquire(1, 2));
+        assert!(!budget.try_acquire(1, 2));
+        assert!(budget.try_acquire(2, 2));
+        assert!(!budget.try_acquire(2, 0));
+    }
+
+    #[test]
+    fn staggered_rebuilds_eventually_refresh_all_caches() {
+        const EDITORS: usize = 10;
+        const MAX_PER_FRAME: usize = 3;
+        let mut budget = RebuildBudget::default();
+        let mut caches: Vec<TickLabelCache<String>> = (0..EDITORS)
+            .map(|_| {
+                let mut cache = TickLabelCache::default();
+                cache.sync_style(1, || unreachable!("empty caches are reset right away"));
+                cache.get_or_layout("0.5".to_string(), |text| format!("old {text}"));
+                cache
+            })
+            .collect();
+
+        let mut frames = 0;
+        loop {
+            frames += 1;
+            let mut rebuilds = 0;
+            let mut stale = 0;
+            for cache in &mut caches {
+                let still_stale = cache.sync_style(2, || {
+                    let granted = budget.try_acquire(frames, MAX_PER_FRAME);
+                    rebuilds += granted as usize;
+                    granted
+                });
+                stale += still_stale as usize;
+                let label = cache.get_or_layout("0.5".to_string(), |text| format!("new {text}"));
+                assert_eq!(label.starts_with("old"), still_stale);
+            }
+            assert!(rebuilds <= MAX_PER_FRAME);
+            if stale == 0 {
+                break;
+            }
+            assert!(frames < 100);
+        }
+        assert_eq!(frames, EDITORS.div_ceil(MAX_PER_FRAME) as u64);
+    }
+
+    #[test]
+    fn unchanged_labels_are_not_laid_out_again() {
+        let mut cache = TickLabelCache::default();
+        cache.sync_style(1, || true);
+        let mut layouts = 0;
+        let mut layout = |text: String| {
+            layouts += 1;
+            text
+        };
+        for frame in 0..3 {
+            for tick in frame..frame + 4 {
+                cache.get_or_layout(format!("{tick}.0"), &mut layout);
+            }
+            cache.retain_used();
+        }
+        // 4 labels in the first frame, then one new label per frame
+        assert_eq!(layouts, 6);
+        assert_eq!(cache.labels.len(), 4);
+        assert!(!cache.labels.contains_key("0.0"));
+    }
+}
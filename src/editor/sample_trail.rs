@@ -0,0 +1,103 @@
+use bevy_math::Vec2;
+
+/// A recorded sample, see [LookupCurveEguiEditor::sample_trail](super::LookupCurveEguiEditor::sample_trail)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TrailPoint {
+    /// Position of the sample in curve space
+    pub position: Vec2,
+    /// Time of the sample in seconds, as reported by egui
+    pub time: f64,
+}
+
+/// Ring buffer of the most recent samples, oldest first
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SampleTrail {
+    points: Vec<TrailPoint>,
+    /// [LookupCurve::generation](crate::LookupCurve::generation) of the curve the points were sampled from
+    generation: Option<u64>,
+}
+
+impl SampleTrail {
+    pub(crate) fn points(&self) -> &[TrailPoint] {
+        &self.points
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Clears the trail if the curve has changed since the points were recorded
+    pub(crate) fn sync_generation(&mut self, generation: u64) {
+        if self.generation != Some(generation) {
+            self.generation = Some(generation);
+            self.clear();
+        }
+    }
+
+    /// Records a sample, unless it is the same as the latest one. Keeps at most `length` points.
+    pub(crate) fn push(&mut self, position: Vec2, time: f64, length: usize) {
+        if self.points.last().map(|p| p.position) == Some(position) {
+            return;
+        }
+        self.points.push(TrailPoint { position, time });
+        if self.points.len() > length {
+            let excess = self.points.len() - length;
+            self.points.drain(..excess);
+        }
+    }
+
+    /// Opacity of a point given its age, fading linearly from 1 to 0 over `fade_duration` seconds
+    pub(crate) fn alpha(point: &TrailPoint, now: f64, fade_duration: f32) -> f32 {
+        if fade_duration <= 0.0 {
+            return 1.0;
+        }
+        let age = (now - point.time).max(0.0) as f32;
+        (1.0 - age / fade_duration).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_latest_points() {
+        let mut trail = SampleTrail::default();
+        for i in 0..5 {
+            trail.push(Vec2::new(i as f32, 0.0), i as f64, 3);
+        }
+        let xs: Vec<f32> = trail.points().iter().map(|p| p.position.x).collect();
+        assert_eq!(xs, vec![2.0, 3.0, 4.0]);
+
+        trail.push(Vec2::new(4.0, 0.0), 10.0, 3);
+        assert_eq!(trail.points().len(), 3);
+        assert_eq!(trail.points()[2].time, 4.0);
+
+        trail.push(Vec2::ZERO, 11.0, 0);
+        assert!(trail.points().is_empty());
+    }
+
+    #[test]
+    fn fades_with_age() {
+        let point = TrailPoint {
+            position: Vec2::ZERO,
+            time: 1.0,
+        };
+        assert_eq!(SampleTrail::alpha(&point, 1.0, 2.0), 1.0);
+        assert_eq!(SampleTrail::alpha(&point, 2.0, 2.0), 0.5);
+        assert_eq!(SampleTrail::alpha(&point, 5.0, 2.0), 0.0);
+        assert_eq!(SampleTrail::alpha(&point, 0.0, 2.0), 1.0);
+        assert_eq!(SampleTrail::alpha(&point, 5.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn clears_when_curve_changes() {
+        let mut trail = SampleTrail::default();
+        trail.sync_generation(1);
+        trail.push(Vec2::ZERO, 0.0, 8);
+        trail.sync_generation(1);
+        assert_eq!(trail.points().len(), 1);
+        trail.sync_generation(2);
+        assert!(trail.points().is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use bevy_math::Vec2;
+
+/// Pointer movement (in points) needed before a press on the curve turns into a segment drag instead of a click
+pub(crate) const SEGMENT_DRAG_THRESHOLD: f32 = 4.0;
+
+/// State of grabbing the curve between two knots
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) enum SegmentDrag {
+    #[default]
+    Idle,
+    /// Pressed on the curve, but not yet moved far enough to count as a drag
+    Pending {
+        grab: SegmentGrab,
+        moved: Vec2,
+    },
+    Dragging {
+        grab: SegmentGrab,
+    },
+}
+
+/// Where a segment was grabbed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct SegmentGrab {
+    /// Id of the knot to the left of the grabbed segment
+    pub left_id: usize,
+    /// Id of the knot to the right of the grabbed segment
+    pub right_id: usize,
+    /// Grab position within the segment, 0 at the left knot and 1 at the right knot
+    pub t: f32,
+    /// Grab position in curve space
+    pub position: Vec2,
+}
+
+/// Result of updating a [SegmentDrag]
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SegmentDragEvent {
+    None,
+    /// Released without moving past the threshold
+    Click(SegmentGrab),
+    /// Moved while dragging, with the pointer delta in screen space since the last event
+    Drag(SegmentGrab, Vec2),
+}
+
+impl SegmentDrag {
+    pub(crate) fn start(grab: SegmentGrab) -> Self {
+        SegmentDrag::Pending {
+            grab,
+            moved: Vec2::ZERO,
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        !matches!(self, SegmentDrag::Idle)
+    }
+
+    /// Advances the state with the pointer movement since last update
+    pub(crate) fn update(&mut self, delta: Vec2, released: bool) -> SegmentDragEvent {
+        match self {
+            SegmentDrag::Idle => SegmentDragEvent::None,
+            SegmentDrag::Pending { grab, moved } => {
+                let grab = *grab;
+                let moved = *moved + delta;
+                if moved.length() > SEGMENT_DRAG_THRESHOLD {
+                    // Include the movement so far, so that the curve does not lag behind the pointer
+                    *self = if released {
+                        SegmentDrag::Idle
+                    } else {
+                        SegmentDrag::Dragging { grab }
+                    };
+                    SegmentDragEvent::Drag(grab, moved)
+                } else if released {
+                    *self = SegmentDrag::Idle;
+                    SegmentDragEvent::Click(grab)
+                } else {
+                    *self = SegmentDrag::Pending { grab, moved };
+                    SegmentDragEvent::None
+                }
+            }
+            SegmentDrag::Dragging { grab } => {
+                let grab = *grab;
+                if released {
+                    *self = SegmentDrag::Idle;
+                }
+                if delta == Vec2::ZERO {
+                    SegmentDragEvent::None
+                } else {
+                    SegmentDragEvent::Drag(grab, delta)
+                }
+            }
+        }
+    }
+}
+
+/// Splits a vertical drag of a segment `dy` into deltas for the left and right knot of the segment.
+///
+/// Without `falloff` both knots move equally. With `falloff` the knot nearer to the grab position `t` moves more,
+/// weighted so that the grabbed point of a linear segment follows the pointer exactly.
+pub(crate) fn segment_drag_deltas(dy: f32, t: f32, falloff: bool) -> (f32, f32) {
+    if !falloff {
+        return (dy, dy);
+    }
+    let t = t.clamp(0.0, 1.0);
+    let (w_left, w_right) = (1.0 - t, t);
+    let norm = w_left * w_left + w_right * w_right;
+    (dy * w_left / norm, dy * w_right / norm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grab() -> SegmentGrab {
+        SegmentGrab {
+            left_id: 1,
+            right_id: 2,
+            t: 0.25,
+            position: Vec2::ZERO,
+        }
+    }
+
+    #[test]
+    fn equal_deltas_without_falloff() {
+        assert_eq!(segment_drag_deltas(2.0, 0.1, false), (2.0, 2.0));
+    }
+
+    #[test]
+    fn falloff_moves_nearer_knot_more() {
+        let (left, right) = segment_drag_deltas(1.0, 0.25, true);
+        assert!(left > right);
+        // The grabbed point of a linear segment follows the pointer
+        assert!(((1.0 - 0.25) * left + 0.25 * right - 1.0).abs() < 1e-6);
+
+        assert_eq!(segment_drag_deltas(1.0, 0.0, true), (1.0, 0.0));
+        assert_eq!(segment_drag_deltas(1.0, 1.0, true), (0.0, 1.0));
+        let (left, right) = segment_drag_deltas(1.0, 0.5, true);
+        assert_eq!(left, right);
+        assert_eq!(left, 1.0);
+    }
+
+    #[test]
+    fn small_movement_is_click() {
+        let mut drag = SegmentDrag::start(grab());
+        assert_eq!(
+            drag.update(Vec2::new(1.0, 1.0), false),
+            SegmentDragEvent::None
+        );
+        assert_eq!(
+            drag.update(Vec2::new(0.0, 1.0), true),
+            SegmentDragEvent::Click(grab())
+        );
+        assert!(!drag.is_active());
+    }
+
+    #[test]
+    fn movement_past_threshold_drags() {
+        let mut drag = SegmentDrag::start(grab());
+        assert_eq!(
+            drag.update(Vec2::new(0.0, 3.0), false),
+            SegmentDragEvent::None
+        );
+        assert_eq!(
+            drag.update(Vec2::new(0.0, 3.0), false),
+            SegmentDragEvent::Drag(grab(), Vec2::new(0.0, 6.0))
+        );
+        assert_eq!(
+            drag.update(Vec2::new(0.0, -1.0), false),
+            SegmentDragEvent::Drag(grab(), Vec2::new(0.0, -1.0))
+        );
+        assert_eq!(drag.update(Vec2::ZERO, false), SegmentDragEvent::None);
+        assert_eq!(drag.update(Vec2::ZERO, true), SegmentDragEvent::None);
+        assert!(!drag.is_active());
+    }
+}
@@ -0,0 +1,469 @@
+use std::any::TypeId;
+use std::fmt;
+
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::prelude::{AppTypeRegistry, Component, Entity, Mut, ReflectComponent, Resource};
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::world::World;
+use bevy_reflect::{GetPath, ParsedPath, Reflect};
+
+use super::LookupCurveEditor;
+use crate::LookupCurve;
+
+/// The curve a [LookupCurveEditor] edits, when it is not only an asset.
+///
+/// Spawn editors for targets with
+/// [LookupCurveCommandsExt::spawn_curve_editor_for](super::LookupCurveCommandsExt::spawn_curve_editor_for). Editors
+/// of component and resource targets edit a copy of the curve in an asset of their own, which is synced with the
+/// target every frame. Their title is marked as in-memory, as they can not be saved to a file.
+///
+/// Components and resources are found through reflection, so their type must be registered with
+/// `App::register_type`, reflecting `Component` or `Resource`.
+#[derive(Component, Clone, Debug)]
+pub enum LookupCurveEditorTarget {
+    /// A curve asset, the same as a [LookupCurveEditor] without a target
+    Asset(Handle<LookupCurve>),
+    /// A curve at `path` within a component of `entity`, see [LookupCurveEditorTarget::component]
+    Component {
+        entity: Entity,
+        type_id: TypeId,
+        type_name: &'static str,
+        path: ParsedPath,
+    },
+    /// A curve at `path` within a resource, see [LookupCurveEditorTarget::resource]
+    Resource {
+        type_id: TypeId,
+        type_name: &'static str,
+        path: ParsedPath,
+    },
+}
+
+/// Why a [LookupCurveEditorTarget] could not be resolved to a curve
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TargetError {
+    /// The path could not be parsed, or does not lead to a field
+    InvalidPath(String),
+    /// The curve asset does not exist, or is not loaded yet
+    MissingAsset,
+    /// The entity does not exist, for example because it was despawned
+    MissingEntity(Entity),
+    /// The entity has no component, or the world no resource, of this type
+    Missing(&'static str),
+    /// The type is not registered as reflected component or resource
+    NotRegistered(&'static str),
+    /// The field at this path is not a [LookupCurve]
+    NotACurve(String),
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetError::InvalidPath(e) => write!(f, "Invalid path to curve: {e}"),
+            TargetError::MissingAsset => write!(f, "Curve asset is not loaded"),
+            TargetError::MissingEntity(entity) => write!(f, "Entity {entity} does not exist"),
+            TargetError::Missing(type_name) => write!(f, "No {type_name} to edit the curve of"),
+            TargetError::NotRegistered(type_name) => {
+                write!(f, "{type_name} is not registered for reflection")
+            }
+            TargetError::NotACurve(path) => write!(f, "Field {path} is not a lookup curve"),
+        }
+    }
+}
+
+impl std::error::Error for TargetError {}
+
+impl LookupCurveEditorTarget {
+    /// Targets the curve at `path` within the `C` component of `entity`, like `"recoil"` or `"curves[2]"`
+    pub fn component<C: Component + Reflect>(
+        entity: Entity,
+        path: &str,
+    ) -> Result<Self, TargetError> {
+        Ok(LookupCurveEditorTarget::Component {
+            entity,
+            type_id: TypeId::of::<C>(),
+            type_name: std::any::type_name::<C>(),
+            path: parse_path(path)?,
+        })
+    }
+
+    /// Targets the curve at `path` within the `R` resource
+    pub fn resource<R: Resource + Reflect>(path: &str) -> Result<Self, TargetError> {
+        Ok(LookupCurveEditorTarget::Resource {
+            type_id: TypeId::of::<R>(),
+            type_name: std::any::type_name::<R>(),
+            path: parse_path(path)?,
+        })
+    }
+
+    /// Whether the target is a curve asset. Only assets can be saved to files, other targets are kept in memory.
+    pub fn is_asset(&self) -> bool {
+        matches!(self, LookupCurveEditorTarget::Asset(_))
+    }
+
+    /// Finds the targeted curve in `world`
+    pub fn resolve<'w>(&self, world: &'w World) -> Result<&'w LookupCurve, TargetError> {
+        let (reflected, path) = match self {
+            LookupCurveEditorTarget::Asset(handle) => {
+                return world
+                    .resource::<Assets<LookupCurve>>()
+                    .get(handle)
+                    .ok_or(TargetError::MissingAsset);
+            }
+            LookupCurveEditorTarget::Component {
+                entity,
+                type_id,
+                type_name,
+                path,
+            } => {
+                let registry = world.resource::<AppTypeRegistry>().read();
+                let reflect_component = registry
+                    .get_type_data::<ReflectComponent>(*type_id)
+                    .ok_or(TargetError::NotRegistered(type_name))?;
+                let entity_ref = world
+                    .get_entity(*entity)
+                    .ok_or(TargetError::MissingEntity(*entity))?;
+                let reflected = reflect_component
+                    .reflect(entity_ref)
+                    .ok_or(TargetError::Missing(type_name))?;
+                (reflected, path)
+            }
+            LookupCurveEditorTarget::Resource {
+                type_id,
+                type_name,
+                path,
+            } => {
+                let registry = world.resource::<AppTypeRegistry>().read();
+                let reflect_resource = registry
+                    .get_type_data::<ReflectResource>(*type_id)
+                    .ok_or(TargetError::NotRegistered(type_name))?;
+                let reflected = reflect_resource
+                    .reflect(world)
+                    .ok_or(TargetError::Missing(type_name))?;
+                (reflected, path)
+            }
+        };
+        curve_at(reflected, path)
+    }
+
+    /// Finds the targeted curve in `world` for modifying it. The component or resource is only marked as changed
+    /// when the curve is modified.
+    pub fn resolve_mut<'w>(
+        &self,
+        world: &'w mut World,
+    ) -> Result<Mut<'w, LookupCurve>, TargetError> {
+        // Check the whole path first, so that the mutable access below can not fail
+        self.resolve(world)?;
+        let (reflected, path) = match self {
+            LookupCurveEditorTarget::Asset(handle) => {
+                let assets = world.resource_mut::<Assets<LookupCurve>>();
+                return Ok(assets.map_unchanged(|assets| assets.get_mut(handle).unwrap()));
+            }
+            LookupCurveEditorTarget::Component {
+                entity,
+                type_id,
+                path,
+                ..
+            } => {
+                let registry = world.resource::<AppTypeRegistry>().clone();
+                let registry = registry.read();
+                let reflect_component = registry.get_type_data::<ReflectComponent>(*type_id);
+                let entity_mut = world.entity_mut(*entity);
+                (reflect_component.unwrap().reflect_mut(entity_mut), path)
+            }
+            LookupCurveEditorTarget::Resource { type_id, path, .. } => {
+                let registry = world.resource::<AppTypeRegistry>().clone();
+                let registry = registry.read();
+                let reflect_resource = registry.get_type_data::<ReflectResource>(*type_id);
+                (reflect_resource.unwrap().reflect_mut(world), path)
+            }
+        };
+        Ok(reflected.unwrap().map_unchanged(|reflected| {
+            reflected
+                .reflect_path_mut(path)
+                .ok()
+                .and_then(|field| field.downcast_mut::<LookupCurve>())
+                .unwrap()
+        }))
+    }
+}
+
+fn parse_path(path: &str) -> Result<ParsedPath, TargetError> {
+    ParsedPath::parse(path).map_err(|e| TargetError::InvalidPath(e.to_string()))
+}
+
+fn curve_at<'w>(
+    reflected: &'w dyn Reflect,
+    path: &ParsedPath,
+) -> Result<&'w LookupCurve, TargetError> {
+    reflected
+        .reflect_path(path)
+        .map_err(|e| TargetError::InvalidPath(e.to_string()))?
+        .downcast_ref::<LookupCurve>()
+        .ok_or_else(|| TargetError::NotACurve(path.to_string()))
+}
+
+/// State of syncing the asset of an editor with its component or resource target
+#[derive(Component, Clone, Debug, Default)]
+pub(crate) struct TargetSync {
+    /// Generation of the curve when it was last copied to or from the target
+    synced_generation: Option<u64>,
+    /// Why the target could not be synced in the last frame
+    error: Option<TargetError>,
+}
+
+/// Copies edits written to the asset of editors with a component or resource target to the target, or changes of
+/// the target to the asset. Editors whose target is gone keep their curve and show the error once.
+pub(crate) fn sync_editor_targets(world: &mut World) {
+    let mut editors = world.query::<(
+        Entity,
+        &LookupCurveEditor,
+        &LookupCurveEditorTarget,
+        Option<&TargetSync>,
+    )>();
+    let targets: Vec<_> = editors
+        .iter(world)
+        .filter(|(_, _, target, _)| !target.is_asset())
+        .map(|(entity, editor, target, sync)| {
+            (
+                entity,
+                editor.curve_handle.clone(),
+                target.clone(),
+                sync.cloned().unwrap_or_default(),
+            )
+        })
+        .collect();
+
+    for (entity, handle, target, mut sync) in targets {
+        let error = sync_target(world, &handle, &target, &mut sync).err();
+        if let Some(error) = error
+            .as_ref()
+            .filter(|&error| sync.error.as_ref() != Some(error))
+        {
+            if let Some(mut editor) = world.get_mut::<LookupCurveEditor>(entity) {
+                editor.egui_editor.show_error(error.to_string());
+            }
+        }
+        sync.error = error;
+        world.entity_mut(entity).insert(sync);
+    }
+}
+
+fn sync_target(
+    world: &mut World,
+    handle: &Handle<LookupCurve>,
+    target: &LookupCurveEditorTarget,
+    sync: &mut TargetSync,
+) -> Result<(), TargetError> {
+    let Some(edited) = world.resource::<Assets<LookupCurve>>().get(handle) else {
+        return Ok(());
+    };
+    // Edits the editor wrote to its asset
+    if sync
+        .synced_generation
+        .is_some_and(|generation| generation != edited.generation())
+    {
+        let edited = edited.clone();
+        let mut curve = target.resolve_mut(world)?;
+        let name = curve.name.take();
+        *curve = edited;
+        curve.name = name;
+        sync.synced_generation = Some(curve.generation());
+        return Ok(());
+    }
+
+    let curve = target.resolve(world)?;
+    if sync.synced_generation != Some(curve.generation()) {
+        let mut copy = curve.clone();
+        copy.name = Some(format!("{} (in-memory)", curve.name_or_default()));
+        sync.synced_generation = Some(copy.generation());
+        world
+            .resource_mut::<Assets<LookupCurve>>()
+            .insert(handle, copy);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::LookupCurveCommandsExt;
+    use crate::Knot;
+    use bevy::prelude::{App, AssetApp, AssetPlugin, Commands, MinimalPlugins, Update};
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_math::Vec2;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Weapon {
+        recoil: LookupCurve,
+        damage: f32,
+    }
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct Tuning {
+        curves: Vec<LookupCurve>,
+    }
+
+    fn curve(y: f32) -> LookupCurve {
+        LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::new(1.0, y),
+                ..Default::default()
+            },
+        ])
+        .with_name("recoil")
+    }
+
+    fn test_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<LookupCurve>()
+            .register_type::<Weapon>()
+            .register_type::<Tuning>()
+            .insert_resource(Tuning {
+                curves: vec![curve(1.0), curve(2.0)],
+            })
+            .add_systems(Update, sync_editor_targets);
+        let weapon = app
+            .world_mut()
+            .spawn(Weapon {
+                recoil: curve(1.0),
+                damage: 1.0,
+            })
+            .id();
+        (app, weapon)
+    }
+
+    fn last_y(curve: &LookupCurve) -> f32 {
+        curve.knots().last().unwrap().position.y
+    }
+
+    #[test]
+    fn targets_resolve_through_reflection() {
+        let (mut app, weapon) = test_app();
+        let world = app.world_mut();
+        let target = LookupCurveEditorTarget::component::<Weapon>(weapon, "recoil").unwrap();
+        assert_eq!(last_y(target.resolve(world).unwrap()), 1.0);
+        let target = LookupCurveEditorTarget::resource::<Tuning>("curves[1]").unwrap();
+        assert_eq!(last_y(target.resolve(world).unwrap()), 2.0);
+
+        let mut curve = target.resolve_mut(world).unwrap();
+        let mut knot = curve.knots()[1];
+        knot.position.y = 3.0;
+        curve.modify_knot(1, knot);
+        assert_eq!(last_y(&world.resource::<Tuning>().curves[1]), 3.0);
+    }
+
+    #[test]
+    fn unresolvable_targets_are_errors() {
+        let (mut app, weapon) = test_app();
+        let world = app.world_mut();
+        let resolve = |target: Result<LookupCurveEditorTarget, TargetError>| {
+            target.and_then(|target| target.resolve(world).map(|_| ()))
+        };
+        assert!(matches!(
+            resolve(LookupCurveEditorTarget::component::<Weapon>(weapon, "[")),
+            Err(TargetError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            resolve(LookupCurveEditorTarget::component::<Weapon>(
+                weapon, "spread"
+            )),
+            Err(TargetError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            resolve(LookupCurveEditorTarget::component::<Weapon>(
+                weapon, "damage"
+            )),
+            Err(TargetError::NotACurve(_))
+        ));
+        assert!(matches!(
+            resolve(LookupCurveEditorTarget::resource::<Tuning>("curves[5]")),
+            Err(TargetError::InvalidPath(_))
+        ));
+        let unregistered = LookupCurveEditorTarget::Component {
+            entity: weapon,
+            type_id: TypeId::of::<LookupCurve>(),
+            type_name: "LookupCurve",
+            path: ParsedPath::parse("name").unwrap(),
+        };
+        assert_eq!(
+            unregistered.resolve(world).err(),
+            Some(TargetError::NotRegistered("LookupCurve"))
+        );
+
+        let empty = world.spawn_empty().id();
+        let target = LookupCurveEditorTarget::component::<Weapon>(empty, "recoil").unwrap();
+        assert!(matches!(
+            target.resolve(world),
+            Err(TargetError::Missing(_))
+        ));
+        world.despawn(weapon);
+        let target = LookupCurveEditorTarget::component::<Weapon>(weapon, "recoil").unwrap();
+        assert_eq!(
+            target.resolve(world).err(),
+            Some(TargetError::MissingEntity(weapon))
+        );
+    }
+
+    #[test]
+    fn editors_of_components_edit_the_component() {
+        let (mut app, weapon) = test_app();
+        let target = LookupCurveEditorTarget::component::<Weapon>(weapon, "recoil").unwrap();
+        app.world_mut()
+            .run_system_once(move |mut commands: Commands| {
+                commands.spawn_curve_editor_for(target.clone());
+            });
+        app.update();
+
+        let mut editors = app.world_mut().query::<(Entity, &LookupCurveEditor)>();
+        let (editor, handle) = {
+            let (entity, editor) = editors.single(app.world());
+            (entity, editor.curve_handle.clone())
+        };
+        let asset = app
+            .world()
+            .resource::<Assets<LookupCurve>>()
+            .get(&handle)
+            .unwrap();
+        assert_eq!(last_y(asset), 1.0);
+        assert_eq!(asset.name.as_deref(), Some("recoil (in-memory)"));
+
+        // Edits written to the asset by the editor are copied to the component, which keeps its name
+        let mut assets = app.world_mut().resource_mut::<Assets<LookupCurve>>();
+        let asset = assets.get_mut(&handle).unwrap();
+        let mut knot = asset.knots()[1];
+        knot.position.y = 5.0;
+        asset.modify_knot(1, knot);
+        app.update();
+        let recoil = &app.world().get::<Weapon>(weapon).unwrap().recoil;
+        assert_eq!(last_y(recoil), 5.0);
+        assert_eq!(recoil.name.as_deref(), Some("recoil"));
+
+        // Changes of the component are copied to the asset
+        app.world_mut().get_mut::<Weapon>(weapon).unwrap().recoil = curve(7.0);
+        app.update();
+        let asset = app
+            .world()
+            .resource::<Assets<LookupCurve>>()
+            .get(&handle)
+            .unwrap();
+        assert_eq!(last_y(asset), 7.0);
+
+        // Once the entity is gone, the editor keeps its curve and reports the error
+        app.world_mut().despawn(weapon);
+        app.update();
+        app.update();
+        let sync = app.world().get::<TargetSync>(editor).unwrap();
+        assert_eq!(sync.error, Some(TargetError::MissingEntity(weapon)));
+        let asset = app
+            .world()
+            .resource::<Assets<LookupCurve>>()
+            .get(&handle)
+            .unwrap();
+        assert_eq!(last_y(asset), 7.0);
+    }
+}
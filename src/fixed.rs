@@ -0,0 +1,668 @@
+use smallvec::SmallVec;
+
+use crate::{Extrapolation, KnotInterpolation, LookupCurve, INLINE_KNOTS};
+
+/// Signed fixed-point number with `FRAC` fractional bits, stored in an `i64`. The default is Q32.32.
+///
+/// All arithmetic saturates at [Fixed::MIN] and [Fixed::MAX] instead of overflowing.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed<const FRAC: u32 = 32>(i64);
+
+impl<const FRAC: u32> Fixed<FRAC> {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << FRAC);
+    pub const MIN: Self = Self(i64::MIN);
+    pub const MAX: Self = Self(i64::MAX);
+
+    /// Fails to compile for unsupported numbers of fractional bits
+    const VALID_FRAC: () = assert!(
+        matches!(FRAC, 1..=62),
+        "Fixed needs between 1 and 62 fractional bits"
+    );
+
+    /// Creates a fixed-point number from its raw bits, where `1 << FRAC` is one
+    #[inline]
+    pub const fn from_bits(bits: i64) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits, where `1 << FRAC` is one
+    #[inline]
+    pub const fn to_bits(self) -> i64 {
+        self.0
+    }
+
+    /// Converts from `f64`, rounding to the nearest representable value. Saturates when out of range, and NaN becomes zero.
+    pub fn from_f64(value: f64) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_FRAC;
+        // `as` saturates, and turns NaN into 0
+        Self((value * (1u64 << FRAC) as f64).round() as i64)
+    }
+
+    /// Converts from `f32`, see [Fixed::from_f64]
+    pub fn from_f32(value: f32) -> Self {
+        Self::from_f64(value as f64)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / (1u64 << FRAC) as f64
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    #[inline]
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    #[inline]
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Multiplies, rounding towards negative infinity
+    #[inline]
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        Self::saturate((self.0 as i128 * rhs.0 as i128) >> FRAC)
+    }
+
+    /// Divides, rounding towards zero. Division by zero saturates towards the sign of `self`.
+    #[inline]
+    pub fn saturating_div(self, rhs: Self) -> Self {
+        if rhs.0 == 0 {
+            return if self.0 >= 0 { Self::MAX } else { Self::MIN };
+        }
+        Self::saturate(((self.0 as i128) << FRAC) / rhs.0 as i128)
+    }
+
+    /// `self * numerator / denominator` without intermediate rounding. `denominator` must not be zero.
+    #[inline]
+    fn mul_div(self, numerator: Self, denominator: Self) -> Self {
+        Self::saturate(self.0 as i128 * numerator.0 as i128 / denominator.0 as i128)
+    }
+
+    #[inline]
+    fn saturate(bits: i128) -> Self {
+        Self(bits.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+    }
+}
+
+/// A [LookupCurve] converted to fixed-point, for deterministic evaluation using integer arithmetic only.
+/// Created with [LookupCurve::to_fixed_curve].
+///
+/// Knot positions and segment coefficients are converted once, and [FixedLookupCurve::sample] never touches
+/// floating point, so the results are bit-identical on all platforms and build profiles.
+///
+/// Accuracy compared to [LookupCurve::lookup]:
+/// - Linear segments are interpolated exactly, apart from the final rounding.
+/// - [KnotInterpolation::Power] segments raise the fraction of the segment to the exponent with an integer base 2
+///   logarithm and exponential, one step per fractional bit each. The exponent is stored with 32 fractional bits.
+/// - Cubic segments are evaluated with Horner's method, and weighted cubic segments solve for the curve
+///   parameter with a fixed number of bisection steps (one per fractional bit) instead of Newton-Raphson.
+///   [LookupCurve::max_iters] and [LookupCurve::max_error] are not used.
+/// - With the default 32 fractional bits, results are within `1e-6 * max(|y|, 1)` of an exact (`f64`) evaluation
+///   of the curve for values within ±1000, which is typically more accurate than the `f32` lookup. Values are
+///   limited to ±2^(63 - FRAC), and saturate beyond that.
+/// - Fewer fractional bits give more range but less precision, the error grows roughly as `2^-FRAC` times the
+///   magnitude of the values.
+#[derive(Clone, Debug)]
+pub struct FixedLookupCurve<const FRAC: u32 = 32> {
+    xs: SmallVec<[Fixed<FRAC>; INLINE_KNOTS]>,
+    ys: SmallVec<[Fixed<FRAC>; INLINE_KNOTS]>,
+    /// Segment between each pair of knots
+    segments: SmallVec<[FixedSegment<FRAC>; INLINE_KNOTS - 1]>,
+    before: Extrapolation,
+    after: Extrapolation,
+    /// Slopes of [Extrapolation::Linear]
+    slope_before: Fixed<FRAC>,
+    slope_after: Fixed<FRAC>,
+}
+
+#[derive(Clone, Debug)]
+enum FixedSegment<const FRAC: u32> {
+    /// y of the left knot
+    Constant,
+    /// y of the right knot, the segment is only sampled after the left knot
+    ConstantNext,
+    /// Straight line between the knots
+    Linear,
+    /// Straight line eased with the fraction of the segment raised to `exponent`, which has
+    /// [EXPONENT_FRAC] fractional bits
+    Power { exponent: i64 },
+    /// Unweighted cubic, polynomial coefficients in `s = (x - x0) / (x1 - x0)`
+    Polynomial { coeff: [Fixed<FRAC>; 4] },
+    /// Weighted cubic, polynomial coefficients for x and y in the bezier parameter `t`
+    Bezier {
+        x: [Fixed<FRAC>; 4],
+        y: [Fixed<FRAC>; 4],
+    },
+}
+
+impl LookupCurve {
+    /// Converts the curve to Q32.32 fixed-point, see [FixedLookupCurve]
+    pub fn to_fixed_curve(&self) -> FixedLookupCurve {
+        self.to_fixed_curve_with_bits()
+    }
+
+    /// Converts the curve to fixed-point with `FRAC` fractional bits, see [FixedLookupCurve]
+    pub fn to_fixed_curve_with_bits<const FRAC: u32>(&self) -> FixedLookupCurve<FRAC> {
+        let fixed = |v: f64| Fixed::<FRAC>::from_f64(v);
+        let coefficients = |[a, b, c, d]: [f64; 4]| [fixed(a), fixed(b), fixed(c), fixed(d)];
+
+        let segments = self
+            .knots
+            .windows(2)
+            .map(|pair| {
+                let (knot_a, knot_b) = (&pair[0], &pair[1]);
+                match knot_a.interpolation {
+                    KnotInterpolation::Constant => FixedSegment::Constant,
+                    KnotInterpolation::ConstantNext => FixedSegment::ConstantNext,
+                    KnotInterpolation::Linear => FixedSegment::Linear,
+                    KnotInterpolation::Power { exponent } => FixedSegment::Power {
+                        exponent: (exponent as f64 * (1u64 << EXPONENT_FRAC) as f64).round() as i64,
+                    },
+                    KnotInterpolation::Cubic
+                        if knot_a.right_tangent.weight.is_some()
+                            || knot_b.left_tangent.weight.is_some() =>
+                    {
+                        let [p0, p1, p2, p3] = knot_a.compute_bezier_to(knot_b);
+                        let bezier = |p0: f32, p1: f32, p2: f32, p3: f32| {
+                            let [p0, p1, p2, p3] = [p0, p1, p2, p3].map(f64::from);
+                            coefficients([
+                                p0,
+                                3.0 * (p1 - p0),
+                                3.0 * (p0 - 2.0 * p1 + p2),
+                                -p0 + 3.0 * p1 - 3.0 * p2 + p3,
+                            ])
+                        };
+                        FixedSegment::Bezier {
+                            x: bezier(p0.x, p1.x, p2.x, p3.x),
+                            y: bezier(p0.y, p1.y, p2.y, p3.y),
+                        }
+                    }
+                    KnotInterpolation::Cubic => {
+                        // Hermite basis expanded to a polynomial, see unweighted_cubic_interp
+                        let dx = knot_b.position.x as f64 - knot_a.position.x as f64;
+                        let (y0, y1) = (knot_a.position.y as f64, knot_b.position.y as f64);
+                        let m0 = knot_a.right_tangent.slope as f64 * dx;
+                        let m1 = knot_b.left_tangent.slope as f64 * dx;
+                        FixedSegment::Polynomial {
+                            coeff: coefficients([
+                                y0,
+                                m0,
+                                -3.0 * y0 - 2.0 * m0 + 3.0 * y1 - m1,
+                                2.0 * y0 + m0 - 2.0 * y1 + m1,
+                            ]),
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        let (slope_before, slope_after) = self.boundary_slopes();
+        FixedLookupCurve {
+            xs: self
+                .knots
+                .iter()
+                .map(|k| Fixed::from_f32(k.position.x))
+                .collect(),
+            ys: self
+                .knots
+                .iter()
+                .map(|k| Fixed::from_f32(k.position.y))
+                .collect(),
+            segments,
+            before: self.extrapolate_before,
+            after: self.extrapolate_after,
+            slope_before: Fixed::from_f32(slope_before),
+            slope_after: Fixed::from_f32(slope_after),
+        }
+    }
+}
+
+impl<const FRAC: u32> FixedLookupCurve<FRAC> {
+    /// Find y for given x on the curve. Like [LookupCurve::lookup], values outside of the knot range are
+    /// extrapolated with the settings of the curve.
+    pub fn sample(&self, x: Fixed<FRAC>) -> Fixed<FRAC> {
+        let (Some(first_x), Some(last_x)) = (self.xs.first(), self.xs.last()) else {
+            return Fixed::ZERO;
+        };
+        let x = match self.extrapolate(x) {
+            Ok(x) => x,
+            Err(y) => return y,
+        };
+        if self.xs.len() == 1 || x <= *first_x {
+            return self.ys[0];
+        }
+        if x >= *last_x {
+            return self.ys[self.ys.len() - 1];
+        }
+
+        let i = self.xs.partition_point(|knot_x| *knot_x < x) - 1;
+        let (x0, x1) = (self.xs[i], self.xs[i + 1]);
+        let (y0, y1) = (self.ys[i], self.ys[i + 1]);
+        match &self.segments[i] {
+            FixedSegment::Constant => y0,
+            FixedSegment::ConstantNext => y1,
+            FixedSegment::Linear => y0.saturating_add(
+                y1.saturating_sub(y0)
+                    .mul_div(x.saturating_sub(x0), x1.saturating_sub(x0)),
+            ),
+            FixedSegment::Power { exponent } => {
+                let s = x.saturating_sub(x0).saturating_div(x1.saturating_sub(x0));
+                y0.saturating_add(
+                    y1.saturating_sub(y0)
+                        .saturating_mul(pow_fraction(s, *exponent)),
+                )
+            }
+            FixedSegment::Polynomial { coeff } => {
+                let s = x.saturating_sub(x0).saturating_div(x1.saturating_sub(x0));
+                horner(coeff, s)
+            }
+            FixedSegment::Bezier {
+                x: coeff_x,
+                y: coeff_y,
+            } => horner(coeff_y, bisect_t(coeff_x, x)),
+        }
+    }
+
+    /// Maps `x` outside of the knot range into it for [Extrapolation::Repeat] and [Extrapolation::PingPong], or
+    /// returns the extrapolated y as the error. Wraps with integer remainders, so that repeating is exact.
+    fn extrapolate(&self, x: Fixed<FRAC>) -> Result<Fixed<FRAC>, Fixed<FRAC>> {
+        let (first, last) = (0, self.xs.len() - 1);
+        let (mode, boundary, slope) = if x < self.xs[first] {
+            (self.before, first, self.slope_before)
+        } else if x > self.xs[last] {
+            (self.after, last, self.slope_after)
+        } else {
+            return Ok(x);
+        };
+        let start = self.xs[first].to_bits() as i128;
+        let width = self.xs[last].to_bits() as i128 - start;
+        let offset = x.to_bits() as i128 - start;
+        let y = self.ys[boundary];
+        let phase = match mode {
+            Extrapolation::Clamp => return Err(y),
+            Extrapolation::Linear => {
+                let dx = x.saturating_sub(self.xs[boundary]);
+                return Err(y.saturating_add(slope.saturating_mul(dx)));
+            }
+            _ if width <= 0 => return Err(y),
+            Extrapolation::Repeat => offset.rem_euclid(width),
+            Extrapolation::PingPong => {
+                let phase = offset.rem_euclid(2 * width);
+                if phase > width {
+                    2 * width - phase
+                } else {
+                    phase
+                }
+            }
+        };
+        Ok(Fixed::from_bits((start + phase) as i64))
+    }
+
+    /// Number of knots in the curve
+    pub fn knot_count(&self) -> usize {
+        self.xs.len()
+    }
+}
+
+/// Evaluates the polynomial `c[0] + c[1] * t + c[2] * t^2 + c[3] * t^3`
+#[inline]
+fn horner<const FRAC: u32>(c: &[Fixed<FRAC>; 4], t: Fixed<FRAC>) -> Fixed<FRAC> {
+    c[3].saturating_mul(t)
+        .saturating_add(c[2])
+        .saturating_mul(t)
+        .saturating_add(c[1])
+        .saturating_mul(t)
+        .saturating_add(c[0])
+}
+
+/// Fractional bits of the exponent of [FixedSegment::Power]
+const EXPONENT_FRAC: u32 = 32;
+
+/// `2^(2^-k)` with 62 fractional bits at index `k`, computed with integer square roots
+const EXP2_ROOTS: [u64; 63] = {
+    let mut roots = [0; 63];
+    roots[0] = 1 << 63;
+    let mut k = 1;
+    while k < roots.len() {
+        roots[k] = ((roots[k - 1] as u128) << 62).isqrt() as u64;
+        k += 1;
+    }
+    roots
+};
+
+/// `s^exponent` for `s` in `0..=1` and a positive `exponent` with [EXPONENT_FRAC] fractional bits, as
+/// `2^(exponent * log2(s))`
+fn pow_fraction<const FRAC: u32>(s: Fixed<FRAC>, exponent: i64) -> Fixed<FRAC> {
+    let one = 1u128 << FRAC;
+    let bits = s.to_bits().clamp(0, 1 << FRAC) as u128;
+    if bits == 0 {
+        return Fixed::ZERO;
+    }
+
+    // log2(s) with FRAC fractional bits: the integer part from the leading bit, then one fractional bit per
+    // squaring of the mantissa
+    let msb = 127 - bits.leading_zeros();
+    let mut mantissa = bits << (FRAC - msb);
+    let mut log2 = (msb as i128 - FRAC as i128) << FRAC;
+    for k in 1..=FRAC {
+        mantissa = (mantissa * mantissa) >> FRAC;
+        if mantissa >= 2 * one {
+            mantissa >>= 1;
+            log2 |= 1 << (FRAC - k);
+        }
+    }
+
+    // Products too small for i128 are far below the precision of the result
+    let Some(power) = (exponent as i128).checked_mul(log2) else {
+        return Fixed::ZERO;
+    };
+    let power = power >> EXPONENT_FRAC;
+    // 2^power, the integer part as a shift and the fractional part as a product of roots of 2
+    let shift = -(power >> FRAC);
+    if shift > FRAC as i128 + 1 {
+        return Fixed::ZERO;
+    }
+    let fraction = power & (one as i128 - 1);
+    let mut result = one;
+    for k in 1..=FRAC {
+        if fraction & (1 << (FRAC - k)) != 0 {
+            let root = if FRAC == 62 {
+                EXP2_ROOTS[k as usize] as u128
+            } else {
+                let dropped = 62 - FRAC;
+                (EXP2_ROOTS[k as usize] as u128 + (1 << (dropped - 1))) >> dropped
+            };
+            result = (result * root) >> FRAC;
+        }
+    }
+    Fixed::from_bits((result >> shift) as i64)
+}
+
+/// Finds `t` in `0..=1` where the polynomial `coeff_x` reaches `x`, assuming it is increasing.
+///
+/// Always runs one step per fractional bit, so that the result does not depend on convergence checks.
+#[inline]
+fn bisect_t<const FRAC: u32>(coeff_x: &[Fixed<FRAC>; 4], x: Fixed<FRAC>) -> Fixed<FRAC> {
+    let (mut low, mut high) = (Fixed::<FRAC>::ZERO, Fixed::<FRAC>::ONE);
+    for _ in 0..=FRAC {
+        let mid = Fixed::from_bits(low.to_bits() + (high.to_bits() - low.to_bits()) / 2);
+        if horner(coeff_x, mid) < x {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Fixed::from_bits(low.to_bits() + (high.to_bits() - low.to_bits()) / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, Tangent};
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    fn with_tangents(knot: Knot, slope: f32, weight: Option<f32>) -> Knot {
+        let tangent = Tangent {
+            slope,
+            weight,
+            ..Default::default()
+        };
+        Knot {
+            left_tangent: tangent,
+            right_tangent: tangent,
+            ..knot
+        }
+    }
+
+    fn fixtures() -> Vec<LookupCurve> {
+        vec![
+            LookupCurve::new(vec![
+                knot(0.0, 0.0, KnotInterpolation::Linear),
+                knot(0.3, 0.7, KnotInterpolation::Constant),
+                knot(0.6, -0.2, KnotInterpolation::Linear),
+                knot(1.0, 1.0, KnotInterpolation::Linear),
+            ]),
+            LookupCurve::new(vec![
+                with_tangents(knot(0.0, 0.0, KnotInterpolation::Cubic), 2.0, None),
+                with_tangents(knot(0.5, 0.8, KnotInterpolation::Cubic), -1.0, None),
+                with_tangents(knot(1.0, 0.1, KnotInterpolation::Cubic), 0.5, None),
+            ]),
+            LookupCurve::new(vec![
+                with_tangents(knot(0.0, 0.0, KnotInterpolation::Cubic), 1.5, Some(0.6)),
+                with_tangents(knot(0.4, 1.0, KnotInterpolation::Cubic), 0.0, Some(0.2)),
+                with_tangents(knot(1.0, 0.5, KnotInterpolation::Cubic), -2.0, None),
+            ]),
+            LookupCurve::new(vec![
+                with_tangents(knot(-50.0, -800.0, KnotInterpolation::Cubic), 10.0, None),
+                with_tangents(knot(10.0, 400.0, KnotInterpolation::Cubic), 30.0, Some(0.5)),
+                with_tangents(knot(100.0, 900.0, KnotInterpolation::Linear), 0.0, None),
+            ]),
+            LookupCurve::new(vec![
+                knot(0.0, 0.0, KnotInterpolation::Power { exponent: 2.5 }),
+                knot(0.25, 1.0, KnotInterpolation::ConstantNext),
+                knot(0.5, -0.5, KnotInterpolation::Power { exponent: 0.3 }),
+                knot(0.75, 0.5, KnotInterpolation::Power { exponent: 7.0 }),
+                knot(1.0, -1.0, KnotInterpolation::Linear),
+            ]),
+        ]
+    }
+
+    /// Exact evaluation of the curve in `f64`, mirroring the segment types of [FixedLookupCurve]
+    fn reference(curve: &LookupCurve, x: f64) -> f64 {
+        let knots = curve.knots();
+        let (first, last) = (knots[0], knots[knots.len() - 1]);
+        if x <= first.position.x as f64 {
+            return first.position.y as f64;
+        }
+        if x >= last.position.x as f64 {
+            return last.position.y as f64;
+        }
+        let i = knots.partition_point(|k| (k.position.x as f64) < x) - 1;
+        let (a, b) = (knots[i], knots[i + 1]);
+        let (x0, y0) = (a.position.x as f64, a.position.y as f64);
+        let (x1, y1) = (b.position.x as f64, b.position.y as f64);
+        match a.interpolation {
+            KnotInterpolation::Constant => y0,
+            KnotInterpolation::ConstantNext => y1,
+            KnotInterpolation::Linear => y0 + (y1 - y0) * (x - x0) / (x1 - x0),
+            KnotInterpolation::Power { exponent } => {
+                y0 + (y1 - y0) * ((x - x0) / (x1 - x0)).powf(exponent as f64)
+            }
+            KnotInterpolation::Cubic
+                if a.right_tangent.weight.is_some() || b.left_tangent.weight.is_some() =>
+            {
+                let p = a.compute_bezier_to(&b).map(|p| (p.x as f64, p.y as f64));
+                let bezier = |t: f64, f: fn((f64, f64)) -> f64| {
+                    let u = 1.0 - t;
+                    u * u * u * f(p[0])
+                        + 3.0 * u * u * t * f(p[1])
+                        + 3.0 * u * t * t * f(p[2])
+                        + t * t * t * f(p[3])
+                };
+                let (mut low, mut high) = (0.0, 1.0);
+                for _ in 0..100 {
+                    let mid = (low + high) / 2.0;
+                    if bezier(mid, |p| p.0) < x {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                bezier((low + high) / 2.0, |p| p.1)
+            }
+            KnotInterpolation::Cubic => {
+                let dx = x1 - x0;
+                let s = (x - x0) / dx;
+                let m0 = a.right_tangent.slope as f64 * dx;
+                let m1 = b.left_tangent.slope as f64 * dx;
+                let (s2, s3) = (s * s, s * s * s);
+                (2.0 * s3 - 3.0 * s2 + 1.0) * y0
+                    + (s3 - 2.0 * s2 + s) * m0
+                    + (-2.0 * s3 + 3.0 * s2) * y1
+                    + (s3 - s2) * m1
+            }
+        }
+    }
+
+    #[test]
+    fn matches_f64_reference() {
+        for curve in fixtures() {
+            let fixed_curve = curve.to_fixed_curve();
+            let (first, last) = (curve.knots()[0], curve.knots()[curve.knots().len() - 1]);
+            let (start, end) = (first.position.x as f64, last.position.x as f64);
+            for i in -10..=1010 {
+                let x = start + (end - start) * i as f64 / 1000.0;
+                let expected = reference(&curve, Fixed::<32>::from_f64(x).to_f64());
+                let actual = fixed_curve.sample(Fixed::from_f64(x)).to_f64();
+                assert!(
+                    (actual - expected).abs() < 1e-6 * expected.abs().max(1.0),
+                    "x = {x}: {actual} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn close_to_f32_lookup() {
+        // The f32 solver for weighted segments needs a normalized domain to converge reliably
+        for curve in fixtures()
+            .into_iter()
+            .take(3)
+            .chain(fixtures().into_iter().skip(4))
+        {
+            let fixed_curve = curve.to_fixed_curve();
+            for i in 0..=100 {
+                let x = curve.knots()[0].position.x
+                    + (curve.knots()[curve.knots().len() - 1].position.x
+                        - curve.knots()[0].position.x)
+                        * i as f32
+                        / 100.0;
+                let expected = curve.lookup(x);
+                let actual = fixed_curve.sample(Fixed::from_f32(x)).to_f32();
+                assert!((actual - expected).abs() <= 1e-3 * expected.abs().max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn deterministic_bits() {
+        // Dyadic values are exact in fixed-point, so these bits must be identical in every build profile
+        let linear = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 2.0, KnotInterpolation::Linear),
+        ])
+        .to_fixed_curve();
+        assert_eq!(linear.sample(Fixed::from_f64(0.25)).to_bits(), 1 << 31);
+
+        let smoothstep = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Cubic),
+            knot(1.0, 1.0, KnotInterpolation::Cubic),
+        ])
+        .to_fixed_curve();
+        assert_eq!(smoothstep.sample(Fixed::from_f64(0.5)).to_bits(), 1 << 31);
+        // 3 * 0.25^2 - 2 * 0.25^3 = 0.15625
+        assert_eq!(smoothstep.sample(Fixed::from_f64(0.25)).to_bits(), 5 << 27);
+
+        for curve in fixtures() {
+            let a = curve.to_fixed_curve();
+            let b = curve.clone().to_fixed_curve();
+            for i in 0..=100 {
+                let x = Fixed::from_f64(i as f64 / 100.0);
+                assert_eq!(a.sample(x).to_bits(), b.sample(x).to_bits());
+                assert_eq!(a.sample(x).to_bits(), a.sample(x).to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn extrapolates_like_the_curve() {
+        for before in Extrapolation::ALL {
+            for after in Extrapolation::ALL {
+                let curve = fixtures()[1].clone().with_extrapolation(before, after);
+                let fixed_curve = curve.to_fixed_curve();
+                for i in -300..=400 {
+                    let x = i as f32 / 100.0;
+                    let expected = curve.lookup(x);
+                    let actual = fixed_curve.sample(Fixed::from_f32(x)).to_f32();
+                    assert!(
+                        (actual - expected).abs() <= 1e-3 * expected.abs().max(1.0),
+                        "{before:?} {after:?} {x}: {actual} != {expected}"
+                    );
+                }
+            }
+        }
+
+        // Repeating is exact
+        let repeat = fixtures()[0]
+            .clone()
+            .with_extrapolation(Extrapolation::Repeat, Extrapolation::Repeat)
+            .to_fixed_curve();
+        // Within the knot range, but not on the first knot, which is also the end of the previous period
+        for i in 1..100 {
+            let x = Fixed::from_f64(i as f64 / 100.0);
+            for period in [-3, -1, 1, 5] {
+                let wrapped = x.saturating_add(Fixed::from_bits(period << 32));
+                assert_eq!(repeat.sample(wrapped).to_bits(), repeat.sample(x).to_bits());
+            }
+        }
+        assert_eq!(
+            LookupCurve::default()
+                .with_extrapolation(Extrapolation::Linear, Extrapolation::PingPong)
+                .to_fixed_curve()
+                .sample(Fixed::ONE),
+            Fixed::ZERO
+        );
+    }
+
+    #[test]
+    fn configurable_fractional_bits() {
+        let curve = &fixtures()[1];
+        let coarse = curve.to_fixed_curve_with_bits::<16>();
+        for i in 0..=100 {
+            let x = i as f64 / 100.0;
+            let expected = reference(curve, Fixed::<16>::from_f64(x).to_f64());
+            let actual = coarse.sample(Fixed::from_f64(x)).to_f64();
+            assert!((actual - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn saturates() {
+        assert_eq!(Fixed::<32>::MAX.saturating_add(Fixed::ONE), Fixed::MAX);
+        assert_eq!(Fixed::<32>::MIN.saturating_sub(Fixed::ONE), Fixed::MIN);
+        assert_eq!(
+            Fixed::<32>::from_f64(1e12).saturating_mul(Fixed::from_f64(1e12)),
+            Fixed::MAX
+        );
+        assert_eq!(Fixed::<32>::ONE.saturating_div(Fixed::ZERO), Fixed::MAX);
+        assert_eq!(Fixed::<32>::from_f64(f64::INFINITY), Fixed::MAX);
+        assert_eq!(Fixed::<32>::from_f64(f64::NAN), Fixed::ZERO);
+    }
+
+    #[test]
+    fn empty_and_single_knot() {
+        assert_eq!(
+            LookupCurve::default().to_fixed_curve().sample(Fixed::ONE),
+            Fixed::ZERO
+        );
+        let single = LookupCurve::new(vec![knot(0.5, 0.25, KnotInterpolation::Linear)]);
+        assert_eq!(
+            single.to_fixed_curve().sample(Fixed::ZERO),
+            Fixed::from_f64(0.25)
+        );
+    }
+}
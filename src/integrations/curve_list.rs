@@ -0,0 +1,159 @@
+use std::sync::{Arc, Mutex};
+
+use bevy_asset::{AssetId, Assets};
+use bevy_ecs::prelude::{Mut, World};
+
+use crate::editor::LookupCurveEguiEditor;
+use crate::registry::{CurveAssetRegistry, CurveSort, CurveSummary};
+use crate::LookupCurve;
+
+const THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(64.0, 32.0);
+
+/// Filter, sort and open editors of [curve_asset_list_ui], kept in egui memory
+#[derive(Clone, Default)]
+struct CurveListState {
+    filter: String,
+    sort: CurveSort,
+    descending: bool,
+    editors: Vec<(AssetId<LookupCurve>, Arc<Mutex<LookupCurveEguiEditor>>)>,
+}
+
+/// Lists all curve assets with a thumbnail, name, knot count and domain, using the summaries of
+/// [CurveAssetRegistry]. The list can be filtered by name and sorted by the properties in [CurveSort].
+///
+/// Clicking a row opens an editor window for the curve, or brings it to the front if it is already open. Editor
+/// windows are shown as part of the list, so they close when the list is no longer shown.
+///
+/// Needs the `LookupCurvePlugin`. Call this from a system with exclusive [World] access, like the windows of
+/// `bevy-inspector-egui`.
+pub fn curve_asset_list_ui(world: &mut World, ui: &mut egui::Ui) {
+    let id = ui.id().with("curve asset list");
+    if !world.contains_resource::<CurveAssetRegistry>() {
+        ui.label("no CurveAssetRegistry in world");
+        return;
+    }
+    if !world.contains_resource::<Assets<LookupCurve>>() {
+        ui.label("no Assets<LookupCurve> in world");
+        return;
+    }
+
+    world.resource_scope(|world, mut curves: Mut<Assets<LookupCurve>>| {
+        let registry = world.resource::<CurveAssetRegistry>();
+        let mut state = ui
+            .memory(|mem| mem.data.get_temp::<CurveListState>(id))
+            .unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut state.filter);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Sort by");
+            egui::ComboBox::from_id_source(id.with("sort"))
+                .selected_text(state.sort.label())
+                .show_ui(ui, |ui| {
+                    for sort in CurveSort::ALL {
+                        ui.selectable_value(&mut state.sort, sort, sort.label());
+                    }
+                });
+            ui.checkbox(&mut state.descending, "Descending");
+        });
+        ui.separator();
+
+        let rows = registry.rows(&state.filter, state.sort, state.descending);
+        if rows.is_empty() {
+            ui.label("No curves");
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (&asset_id, summary) in rows {
+                if curve_row_ui(ui, summary).clicked() {
+                    if state.editors.iter().any(|(open, _)| *open == asset_id) {
+                        let layer = egui::LayerId::new(egui::Order::Middle, id.with(asset_id));
+                        ui.ctx().move_to_top(layer);
+                    } else if let Some(curve) = curves.get(asset_id) {
+                        let editor = LookupCurveEguiEditor::fitted_to_curve(curve);
+                        state.editors.push((asset_id, Arc::new(Mutex::new(editor))));
+                    }
+                }
+            }
+        });
+
+        state.editors.retain(|(asset_id, editor)| {
+            let Some(mut curve) = curves.get(*asset_id).cloned() else {
+                return false;
+            };
+            let mut open = true;
+            let mut changed = false;
+            egui::Window::new(curve.name_or_default())
+                .id(id.with(*asset_id))
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    changed = editor.lock().unwrap().ui(ui, &mut curve, None).changed;
+                });
+            // Only changed curves are written back, so that unchanged assets send no modified events
+            if changed {
+                curves.insert(*asset_id, curve);
+            }
+            open
+        });
+
+        ui.memory_mut(|mem| mem.data.insert_temp(id, state));
+    });
+}
+
+fn curve_row_ui(ui: &mut egui::Ui, summary: &CurveSummary) -> egui::Response {
+    let row = ui.horizontal(|ui| {
+        thumbnail_ui(ui, summary);
+        ui.vertical(|ui| {
+            ui.strong(summary.display_name());
+            let domain = match summary.stats.domain {
+                Some((min, max)) => format!("x {min} to {max}"),
+                None => "empty".to_string(),
+            };
+            ui.label(format!("{} knots, {domain}", summary.stats.knot_count));
+        });
+    });
+    row.response
+        .interact(egui::Sense::click())
+        .on_hover_text("Open editor")
+}
+
+fn thumbnail_ui(ui: &mut egui::Ui, summary: &CurveSummary) {
+    let (rect, _) = ui.allocate_exact_size(THUMBNAIL_SIZE, egui::Sense::hover());
+    ui.painter()
+        .rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let (Some(first), Some(last)) = (summary.thumbnail.first(), summary.thumbnail.last()) else {
+        return;
+    };
+    if last.x <= first.x {
+        return;
+    }
+    let (min_y, max_y) = summary
+        .thumbnail
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| {
+            (min.min(p.y), max.max(p.y))
+        });
+    let rect = rect.shrink(2.0);
+    let points = summary
+        .thumbnail
+        .iter()
+        .map(|p| {
+            let tx = (p.x - first.x) / (last.x - first.x);
+            // Flat curves are drawn in the middle
+            let ty = if max_y > min_y {
+                (p.y - min_y) / (max_y - min_y)
+            } else {
+                0.5
+            };
+            egui::pos2(
+                rect.left() + tx * rect.width(),
+                rect.bottom() - ty * rect.height(),
+            )
+        })
+        .collect();
+    ui.painter().add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, egui::Color32::GREEN),
+    ));
+}
@@ -215,7 +215,7 @@ fn lookup_curve_miniature_with_edit(
             .id(id.with("window"))
             .open(&mut open)
             .show(ui.ctx(), |ui| {
-                changed = editor_state.lock().unwrap().ui(ui, curve, None);
+                changed = editor_state.lock().unwrap().ui(ui, curve, None).changed;
             });
 
         if !open {
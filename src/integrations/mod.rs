@@ -0,0 +1,29 @@
+//! Bevy components, resources and system params of the crate, gathered from the modules they belong to, and
+//! integrations with other crates like `bevy-inspector-egui`. Most need the `LookupCurvePlugin`.
+
+#[cfg(feature = "inspector-egui")]
+mod curve_list;
+#[cfg(feature = "inspector-egui")]
+pub use curve_list::curve_asset_list_ui;
+#[cfg(feature = "inspector-egui")]
+mod inspector;
+#[cfg(feature = "inspector-egui")]
+pub(crate) use inspector::InspectorPlugin;
+
+#[cfg(feature = "bevy_asset")]
+pub use crate::asset::LookupCurveAssetLoader;
+#[cfg(feature = "diagnostics")]
+pub use crate::diagnostics::CurveSampleHistogram;
+#[cfg(feature = "editor_bevy")]
+pub use crate::editor::{
+    CurveDropSettings, CurveScrub, Curves, LookupCurveCommandsExt, LookupCurveEdited,
+    LookupCurveEditor,
+};
+#[cfg(all(feature = "ipc", feature = "bevy_asset"))]
+pub use crate::ipc::{CurveIpcHost, CurveIpcHostPlugin};
+#[cfg(feature = "bevy_asset")]
+pub use crate::link::{CurveLink, CurveLinks};
+#[cfg(feature = "bevy_asset")]
+pub use crate::registry::CurveAssetRegistry;
+#[cfg(feature = "tween_bevy")]
+pub use crate::tween::LookupCurveTween;
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use super::{
+    ClientMessage, CurveDelta, HostMessage, IpcError, Peer, PEER_TIMEOUT, PROTOCOL_VERSION,
+};
+use crate::LookupCurve;
+
+/// Interval between attempts to reconnect to the host
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+/// Pause between checks for an answer while waiting for the host
+const ANSWER_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Something that happened on the channel, returned by [CurveIpcClient::poll]
+#[derive(Clone, Debug)]
+pub enum IpcEvent {
+    /// The curve exposed as `key` changed, through an edit in the game or a pushed [CurveDelta]
+    Changed {
+        key: String,
+        curve: Box<LookupCurve>,
+    },
+    /// The host rejected a pushed delta
+    Rejected { key: String, reason: String },
+    /// The connection to the host was lost, the client reconnects in later polls
+    Disconnected,
+    /// The client reconnected to the host. Curves might have changed while disconnected, fetch them again.
+    Reconnected,
+}
+
+/// Client side of the channel, used by an editor in another process than the game. Does not need Bevy.
+///
+/// Requests wait at most [PEER_TIMEOUT] for the host to answer. Call [Self::poll] regularly, for example every
+/// frame, to receive change notifications and keep the connection alive.
+#[derive(Debug)]
+pub struct CurveIpcClient {
+    address: SocketAddr,
+    peer: Option<Peer>,
+    keys: Vec<String>,
+    /// Events received while waiting for an answer, returned by the next [Self::poll]
+    events: VecDeque<IpcEvent>,
+    last_connect_attempt: Instant,
+}
+
+impl CurveIpcClient {
+    /// Connects to the host at `address`, see `CurveIpcHostPlugin`
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self, IpcError> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or(IpcError::Disconnected)?;
+        let mut client = Self {
+            address,
+            peer: None,
+            keys: Vec::new(),
+            events: VecDeque::new(),
+            last_connect_attempt: Instant::now(),
+        };
+        client.reconnect()?;
+        Ok(client)
+    }
+
+    /// Keys of the curves exposed by the host, as of the last connection
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.peer.is_some()
+    }
+
+    /// Connects again to the host, dropping the current connection
+    pub fn reconnect(&mut self) -> Result<(), IpcError> {
+        self.peer = None;
+        self.last_connect_attempt = Instant::now();
+        let stream = TcpStream::connect_timeout(&self.address, PEER_TIMEOUT)?;
+        self.peer = Some(Peer::new(stream)?);
+        let keys = self
+            .send(&ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+            })
+            .and_then(|()| {
+                self.wait_for(|message| match message {
+                    HostMessage::Hello { keys, .. } => Ok(Ok(keys)),
+                    HostMessage::Rejected { key, reason } => {
+                        Ok(Err(IpcError::Rejected { key, reason }))
+                    }
+                    message => Err(message),
+                })
+            });
+        match keys {
+            Ok(keys) => {
+                self.keys = keys;
+                Ok(())
+            }
+            Err(e) => {
+                self.peer = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the current curve exposed as `key`
+    pub fn fetch(&mut self, key: &str) -> Result<LookupCurve, IpcError> {
+        self.send(&ClientMessage::Fetch {
+            key: key.to_string(),
+        })?;
+        self.wait_for(|message| match message {
+            HostMessage::Curve { key: k, curve } if k == key => Ok(Ok(*curve)),
+            HostMessage::Rejected { key: k, reason } if k == key => {
+                Ok(Err(IpcError::Rejected { key: k, reason }))
+            }
+            message => Err(message),
+        })
+    }
+
+    /// Sends an edit of the curve exposed as `key`. The host applies it in its next update, and then sends the
+    /// changed curve as [IpcEvent::Changed], or [IpcEvent::Rejected] if the delta could not be applied.
+    pub fn push(&mut self, key: &str, delta: CurveDelta) -> Result<(), IpcError> {
+        self.send(&ClientMessage::Push {
+            key: key.to_string(),
+            delta,
+        })
+    }
+
+    /// Returns the events since the last poll without blocking, sends heartbeats, and reconnects if the connection
+    /// was lost
+    pub fn poll(&mut self) -> Vec<IpcEvent> {
+        if self.peer.is_none() {
+            if self.last_connect_attempt.elapsed() > RECONNECT_INTERVAL && self.reconnect().is_ok()
+            {
+                self.events.push_back(IpcEvent::Reconnected);
+            }
+            return self.events.drain(..).collect();
+        }
+        let alive = self.receive().and_then(|()| match self.peer.as_mut() {
+            Some(peer) => peer.keep_alive(&ClientMessage::Heartbeat),
+            None => Err(IpcError::Disconnected),
+        });
+        if alive.is_err() {
+            self.disconnect();
+        }
+        self.events.drain(..).collect()
+    }
+
+    fn send(&mut self, message: &ClientMessage) -> Result<(), IpcError> {
+        let peer = self.peer.as_mut().ok_or(IpcError::Disconnected)?;
+        let result = peer.send(message);
+        if matches!(&result, Err(e) if !matches!(e, IpcError::Encode(_))) {
+            self.disconnect();
+        }
+        result
+    }
+
+    /// Receives messages, turning them into events
+    fn receive(&mut self) -> Result<(), IpcError> {
+        let peer = self.peer.as_mut().ok_or(IpcError::Disconnected)?;
+        for message in peer.receive::<HostMessage>()? {
+            self.handle(message);
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, message: HostMessage) {
+        match message {
+            HostMessage::Curve { key, curve } => {
+                self.events.push_back(IpcEvent::Changed { key, curve })
+            }
+            HostMessage::Rejected { key, reason } => {
+                self.events.push_back(IpcEvent::Rejected { key, reason })
+            }
+            HostMessage::Hello { keys, .. } => self.keys = keys,
+            HostMessage::Heartbeat => {}
+        }
+    }
+
+    /// Waits for the answer to a request. `answer` returns what a message answers, or gives the message back if it
+    /// is not the answer. Those messages are kept as events.
+    fn wait_for<T>(
+        &mut self,
+        mut answer: impl FnMut(HostMessage) -> Result<Result<T, IpcError>, HostMessage>,
+    ) -> Result<T, IpcError> {
+        let deadline = Instant::now() + PEER_TIMEOUT;
+        while Instant::now() < deadline {
+            let peer = self.peer.as_mut().ok_or(IpcError::Disconnected)?;
+            let messages = match peer.receive::<HostMessage>() {
+                Ok(messages) => messages,
+                Err(e) => {
+                    self.disconnect();
+                    return Err(e);
+                }
+            };
+            let mut messages = messages.into_iter();
+            while let Some(message) = messages.next() {
+                match answer(message) {
+                    Ok(result) => {
+                        // Keep what arrived after the answer
+                        messages.for_each(|message| self.handle(message));
+                        return result;
+                    }
+                    Err(message) => self.handle(message),
+                }
+            }
+            if let Some(Err(e)) = self
+                .peer
+                .as_mut()
+                .map(|peer| peer.keep_alive(&ClientMessage::Heartbeat))
+            {
+                self.disconnect();
+                return Err(e);
+            }
+            std::thread::sleep(ANSWER_POLL_INTERVAL);
+        }
+        Err(IpcError::Timeout)
+    }
+
+    fn disconnect(&mut self) {
+        self.peer = None;
+        self.last_connect_attempt = Instant::now();
+        self.events.push_back(IpcEvent::Disconnected);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::prelude::{ResMut, Resource};
+use bevy_log::warn;
+
+use super::{CurveDelta, CurveStore, IpcError, IpcServer};
+use crate::LookupCurve;
+
+/// Port [CurveIpcHostPlugin] listens on by default
+pub const DEFAULT_IPC_PORT: u16 = 47_193;
+
+/// Lets editors in other processes fetch and edit curve assets exposed in [CurveIpcHost], see [CurveIpcClient].
+///
+/// Only listens on the loopback interface by default. There is no authentication, so do not listen on other
+/// interfaces on untrusted networks.
+///
+/// [CurveIpcClient]: super::CurveIpcClient
+pub struct CurveIpcHostPlugin {
+    pub address: SocketAddr,
+}
+
+impl Default for CurveIpcHostPlugin {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_IPC_PORT)),
+        }
+    }
+}
+
+impl Plugin for CurveIpcHostPlugin {
+    fn build(&self, app: &mut App) {
+        let server = IpcServer::bind(self.address)
+            .inspect_err(|e| {
+                warn!(
+                    "Could not listen for curve editors on {}: {e}",
+                    self.address
+                )
+            })
+            .ok();
+        app.insert_resource(CurveIpcHost {
+            server,
+            exposed: HashMap::new(),
+            notified: HashMap::new(),
+        })
+        .add_systems(PostUpdate, update_ipc_host);
+    }
+}
+
+/// Curve assets exposed to editors in other processes, see [CurveIpcHostPlugin]
+#[derive(Resource)]
+pub struct CurveIpcHost {
+    server: Option<IpcServer>,
+    exposed: HashMap<String, Handle<LookupCurve>>,
+    /// Generation of each exposed curve when clients were last notified
+    notified: HashMap<String, u64>,
+}
+
+impl CurveIpcHost {
+    /// Exposes the curve as `key`, replacing the curve exposed as `key` before. Clients connecting later see the
+    /// key in [CurveIpcClient::keys](super::CurveIpcClient::keys), connected clients can fetch it.
+    pub fn expose(&mut self, key: impl Into<String>, curve: Handle<LookupCurve>) {
+        let key = key.into();
+        self.notified.remove(&key);
+        self.exposed.insert(key, curve);
+    }
+
+    /// Stops exposing the curve exposed as `key`, returns its handle
+    pub fn unexpose(&mut self, key: &str) -> Option<Handle<LookupCurve>> {
+        self.notified.remove(key);
+        self.exposed.remove(key)
+    }
+
+    /// Address the host listens on, an error if it could not listen on the address of [CurveIpcHostPlugin]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match &self.server {
+            Some(server) => server.local_addr(),
+            None => Err(io::ErrorKind::NotConnected.into()),
+        }
+    }
+}
+
+/// Exposed curve assets, as seen by the server
+struct ExposedAssets<'a> {
+    exposed: &'a HashMap<String, Handle<LookupCurve>>,
+    curves: &'a mut Assets<LookupCurve>,
+}
+
+impl CurveStore for ExposedAssets<'_> {
+    fn keys(&self) -> Vec<String> {
+        self.exposed.keys().cloned().collect()
+    }
+
+    fn curve(&self, key: &str) -> Option<&LookupCurve> {
+        self.curves.get(self.exposed.get(key)?)
+    }
+
+    fn apply(&mut self, key: &str, delta: &CurveDelta) -> Result<(), IpcError> {
+        let curve = self
+            .exposed
+            .get(key)
+            .and_then(|handle| self.curves.get_mut(handle))
+            .ok_or_else(|| IpcError::UnknownKey(key.to_string()))?;
+        delta.apply(curve)
+    }
+}
+
+/// Answers requests of connected clients, applies pushed deltas, and notifies clients of changed curves
+pub fn update_ipc_host(mut host: ResMut<CurveIpcHost>, mut curves: ResMut<Assets<LookupCurve>>) {
+    let CurveIpcHost {
+        server: Some(server),
+        exposed,
+        notified,
+    } = &mut *host
+    else {
+        return;
+    };
+    server.update(&mut ExposedAssets {
+        exposed,
+        curves: &mut curves,
+    });
+    for (key, handle) in exposed.iter() {
+        let Some(curve) = curves.get(handle) else {
+            continue;
+        };
+        if notified.insert(key.clone(), curve.generation()) != Some(curve.generation()) {
+            server.notify(key, curve);
+        }
+    }
+}
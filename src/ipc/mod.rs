@@ -0,0 +1,408 @@
+//! Channel between a game and a curve editor running in another process.
+//!
+//! The game exposes curve assets by key with `CurveIpcHostPlugin` and [CurveIpcHost], and the editor connects with
+//! [CurveIpcClient], which does not need Bevy. The client fetches curves, pushes edits as [CurveDelta]s, and is
+//! notified whenever an exposed curve changes.
+//!
+//! Messages are sent over a local TCP socket, framed by their length as 4 little endian bytes followed by RON.
+//! Both sides send heartbeats, and drop connections to peers that stay silent for [PEER_TIMEOUT].
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Knot, LookupCurve};
+
+mod client;
+pub use client::{CurveIpcClient, IpcEvent};
+
+#[cfg_attr(not(feature = "bevy_asset"), allow(dead_code))]
+mod server;
+#[cfg_attr(not(feature = "bevy_asset"), allow(unused_imports))]
+pub(crate) use server::{CurveStore, IpcServer};
+
+#[cfg(feature = "bevy_asset")]
+mod host_bevy;
+#[cfg(feature = "bevy_asset")]
+pub use host_bevy::*;
+
+/// Version of the protocol, hosts reject clients with a different version
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Interval between heartbeats sent to a peer that is otherwise silent
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+/// Connections to peers that send nothing for this long are closed
+pub const PEER_TIMEOUT: Duration = Duration::from_secs(5);
+/// Largest accepted frame, larger frames close the connection
+const MAX_FRAME_LEN: usize = 16 << 20;
+
+/// An edit of a curve, pushed by [CurveIpcClient::push].
+///
+/// Knots are referred to by index, as knot ids are not shared between processes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum CurveDelta {
+    /// Replaces the knot at `index`, see [LookupCurve::modify_knot]
+    SetKnot { index: usize, knot: Knot },
+    /// See [LookupCurve::add_knot]
+    AddKnot(Knot),
+    /// See [LookupCurve::delete_knot]
+    DeleteKnot { index: usize },
+    /// Replaces the whole curve
+    Replace(Box<LookupCurve>),
+}
+
+impl CurveDelta {
+    /// Applies the delta to `curve`, leaving it unchanged on errors
+    pub fn apply(&self, curve: &mut LookupCurve) -> Result<(), IpcError> {
+        let check_index = |index: usize| {
+            if index < curve.knots().len() {
+                Ok(index)
+            } else {
+                Err(IpcError::KnotIndex(index))
+            }
+        };
+        match self {
+            CurveDelta::SetKnot { index, knot } => {
+                let index = check_index(*index)?;
+                if !knot.position.is_finite() {
+                    return Err(IpcError::NotFinite);
+                }
+                let id = curve.knots()[index].id;
+                curve.modify_knot(index, Knot { id, ..*knot });
+            }
+            CurveDelta::AddKnot(knot) => {
+                if !knot.position.is_finite() {
+                    return Err(IpcError::NotFinite);
+                }
+                curve.add_knot(*knot);
+            }
+            CurveDelta::DeleteKnot { index } => {
+                let index = check_index(*index)?;
+                curve.delete_knot(index);
+            }
+            CurveDelta::Replace(replacement) => *curve = (**replacement).clone(),
+        }
+        Ok(())
+    }
+}
+
+/// Error of the curve IPC channel
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum IpcError {
+    /// An [IO](std::io) Error, usually a closed connection
+    #[error("Curve IPC connection failed: {0}")]
+    Io(#[from] io::Error),
+    /// A message could not be encoded
+    #[error("Could not encode curve IPC message: {0}")]
+    Encode(#[from] ron::error::Error),
+    /// A received message could not be decoded
+    #[error("Could not decode curve IPC message: {0}")]
+    Decode(#[from] ron::error::SpannedError),
+    /// A received frame exceeds the size limit
+    #[error("Curve IPC frame of {0} bytes is too large")]
+    FrameTooLarge(usize),
+    /// The peer closed the connection or stopped sending heartbeats
+    #[error("Curve IPC peer disconnected")]
+    Disconnected,
+    /// The peer did not answer in time
+    #[error("Curve IPC peer did not answer in time")]
+    Timeout,
+    /// The host rejected a request
+    #[error("Curve IPC host rejected request for {key:?}: {reason}")]
+    Rejected { key: String, reason: String },
+    /// The host exposes no curve with this key
+    #[error("No curve exposed as {0:?}")]
+    UnknownKey(String),
+    /// A [CurveDelta] refers to a knot index the curve does not have
+    #[error("Curve has no knot at index {0}")]
+    KnotIndex(usize),
+    /// A [CurveDelta] has a knot position that is not finite
+    #[error("Knot position is not finite")]
+    NotFinite,
+}
+
+/// Message sent by [CurveIpcClient]
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum ClientMessage {
+    Hello { version: u32 },
+    Fetch { key: String },
+    Push { key: String, delta: CurveDelta },
+    Heartbeat,
+}
+
+/// Message sent by the host
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum HostMessage {
+    /// Answer to [ClientMessage::Hello], with the keys of the exposed curves
+    Hello {
+        version: u32,
+        keys: Vec<String>,
+    },
+    /// Answer to [ClientMessage::Fetch], and sent to every client when an exposed curve changes
+    Curve {
+        key: String,
+        curve: Box<LookupCurve>,
+    },
+    /// A request for `key` failed
+    Rejected {
+        key: String,
+        reason: String,
+    },
+    Heartbeat,
+}
+
+/// A nonblocking connection to a peer, framing messages and tracking heartbeats
+#[derive(Debug)]
+pub(crate) struct Peer {
+    stream: TcpStream,
+    /// Received bytes not yet forming a complete frame
+    incoming: Vec<u8>,
+    /// Frames not yet written, as the socket would block
+    outgoing: Vec<u8>,
+    last_received: Instant,
+    last_sent: Instant,
+}
+
+impl Peer {
+    pub(crate) fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        let now = Instant::now();
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            last_received: now,
+            last_sent: now,
+        })
+    }
+
+    /// Queues `message` and writes as much as the socket accepts
+    pub(crate) fn send(&mut self, message: &impl Serialize) -> Result<(), IpcError> {
+        let body = ron::ser::to_string(message)?;
+        self.outgoing
+            .extend_from_slice(&(body.len() as u32).to_le_bytes());
+        self.outgoing.extend_from_slice(body.as_bytes());
+        self.last_sent = Instant::now();
+        self.flush()
+    }
+
+    /// Writes queued frames until the socket would block
+    pub(crate) fn flush(&mut self) -> Result<(), IpcError> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(IpcError::Disconnected),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the messages received since the last call, without blocking
+    pub(crate) fn receive<T: DeserializeOwned>(&mut self) -> Result<Vec<T>, IpcError> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(IpcError::Disconnected),
+                Ok(n) => {
+                    self.incoming.extend_from_slice(&chunk[..n]);
+                    self.last_received = Instant::now();
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(len) = self.incoming.get(..4) {
+            let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+            if len > MAX_FRAME_LEN {
+                return Err(IpcError::FrameTooLarge(len));
+            }
+            if self.incoming.len() < 4 + len {
+                break;
+            }
+            let message = ron::de::from_bytes(&self.incoming[4..4 + len]);
+            self.incoming.drain(..4 + len);
+            messages.push(message?);
+        }
+        Ok(messages)
+    }
+
+    /// Sends a heartbeat if nothing was sent for [HEARTBEAT_INTERVAL], and returns an error if nothing was received
+    /// for [PEER_TIMEOUT]
+    pub(crate) fn keep_alive(&mut self, heartbeat: &impl Serialize) -> Result<(), IpcError> {
+        if self.last_received.elapsed() > PEER_TIMEOUT {
+            return Err(IpcError::Disconnected);
+        }
+        if self.last_sent.elapsed() > HEARTBEAT_INTERVAL {
+            self.send(heartbeat)?;
+        }
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KnotInterpolation;
+    use bevy_math::Vec2;
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+
+    fn knot(x: f32, y: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Linear,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn deltas_keep_knot_ids() {
+        let mut curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]);
+        let id = curve.knots()[1].id;
+        CurveDelta::SetKnot {
+            index: 1,
+            knot: knot(2.0, 0.5),
+        }
+        .apply(&mut curve)
+        .unwrap();
+        assert_eq!(curve.knots()[1].id, id);
+        assert_eq!(curve.knots()[1].position, Vec2::new(2.0, 0.5));
+
+        CurveDelta::AddKnot(knot(1.0, 3.0))
+            .apply(&mut curve)
+            .unwrap();
+        assert_eq!(curve.knots()[1].position, Vec2::new(1.0, 3.0));
+        CurveDelta::DeleteKnot { index: 0 }
+            .apply(&mut curve)
+            .unwrap();
+        assert_eq!(curve.knots().len(), 2);
+
+        assert!(matches!(
+            CurveDelta::DeleteKnot { index: 2 }.apply(&mut curve),
+            Err(IpcError::KnotIndex(2))
+        ));
+        assert!(matches!(
+            CurveDelta::AddKnot(knot(f32::NAN, 0.0)).apply(&mut curve),
+            Err(IpcError::NotFinite)
+        ));
+        assert_eq!(curve.knots().len(), 2);
+    }
+
+    #[test]
+    fn frames_arrive_in_pieces() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut sender = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut receiver = Peer::new(listener.accept().unwrap().0).unwrap();
+
+        let body = ron::ser::to_string(&ClientMessage::Fetch { key: "a".into() }).unwrap();
+        let mut frame = (body.len() as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(body.as_bytes());
+        let (first, second) = frame.split_at(3);
+
+        sender.write_all(first).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(receiver.receive::<ClientMessage>().unwrap().is_empty());
+        sender.write_all(second).unwrap();
+        sender.write_all(&frame).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let messages = receiver.receive::<ClientMessage>().unwrap();
+        assert!(matches!(
+            messages.as_slice(),
+            [ClientMessage::Fetch { key: a }, ClientMessage::Fetch { key: b }] if a == "a" && b == "a"
+        ));
+
+        sender
+            .write_all(&(MAX_FRAME_LEN as u32 + 1).to_le_bytes())
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(matches!(
+            receiver.receive::<ClientMessage>(),
+            Err(IpcError::FrameTooLarge(_))
+        ));
+    }
+
+    /// Curves of a host without Bevy
+    struct TestStore(HashMap<String, LookupCurve>);
+
+    impl CurveStore for TestStore {
+        fn keys(&self) -> Vec<String> {
+            self.0.keys().cloned().collect()
+        }
+
+        fn curve(&self, key: &str) -> Option<&LookupCurve> {
+            self.0.get(key)
+        }
+
+        fn apply(&mut self, key: &str, delta: &CurveDelta) -> Result<(), IpcError> {
+            let curve = self
+                .0
+                .get_mut(key)
+                .ok_or_else(|| IpcError::UnknownKey(key.to_string()))?;
+            delta.apply(curve)
+        }
+    }
+
+    #[test]
+    fn client_fetches_pushes_and_is_notified() {
+        let mut server = IpcServer::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        let mut store = TestStore(HashMap::from([(
+            "speed".to_string(),
+            LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]),
+        )]));
+
+        let client = std::thread::spawn(move || {
+            let mut client = CurveIpcClient::connect(address).unwrap();
+            assert_eq!(client.keys(), ["speed"]);
+            assert_eq!(client.fetch("speed").unwrap().knots().len(), 2);
+            assert!(matches!(
+                client.fetch("missing"),
+                Err(IpcError::Rejected { .. })
+            ));
+            client
+                .push("speed", CurveDelta::AddKnot(knot(2.0, 0.0)))
+                .unwrap();
+            // Notified of the pushed change, and of the change made by the host after it
+            let deadline = Instant::now() + PEER_TIMEOUT;
+            let mut knot_counts = Vec::new();
+            while knot_counts.last() != Some(&4) && Instant::now() < deadline {
+                for event in client.poll() {
+                    if let IpcEvent::Changed { key, curve } = event {
+                        assert_eq!(key, "speed");
+                        knot_counts.push(curve.knots().len());
+                    }
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            knot_counts
+        });
+
+        let mut notified = None;
+        let deadline = Instant::now() + PEER_TIMEOUT;
+        while !client.is_finished() && Instant::now() < deadline {
+            server.update(&mut store);
+            let curve = store.0.get_mut("speed").unwrap();
+            if notified != Some(curve.generation()) {
+                notified = Some(curve.generation());
+                server.notify("speed", curve);
+            } else if curve.knots().len() == 3 {
+                // A change made by the host, once the client was notified of the pushed change
+                curve.add_knot(knot(3.0, 1.0));
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let knot_counts = client.join().unwrap();
+        assert_eq!(knot_counts.last(), Some(&4));
+        assert!(knot_counts.contains(&3));
+    }
+}
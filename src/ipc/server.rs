@@ -0,0 +1,120 @@
+use std::io;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+
+use super::{ClientMessage, CurveDelta, HostMessage, IpcError, Peer, PROTOCOL_VERSION};
+use crate::LookupCurve;
+
+/// Curves exposed by a host, by key
+pub(crate) trait CurveStore {
+    fn keys(&self) -> Vec<String>;
+    fn curve(&self, key: &str) -> Option<&LookupCurve>;
+    fn apply(&mut self, key: &str, delta: &CurveDelta) -> Result<(), IpcError>;
+}
+
+/// Connected client, which can only send requests after the handshake
+#[derive(Debug)]
+struct Client {
+    peer: Peer,
+    handshake_done: bool,
+}
+
+/// Host side of the channel, accepting clients and answering their requests without blocking
+#[derive(Debug)]
+pub(crate) struct IpcServer {
+    listener: TcpListener,
+    clients: Vec<Client>,
+}
+
+impl IpcServer {
+    pub(crate) fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+        })
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts new clients and answers the requests received since the last update, applying pushed deltas to
+    /// `store`. Clients that disconnected or stopped sending heartbeats are dropped.
+    pub(crate) fn update(&mut self, store: &mut impl CurveStore) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if let Ok(peer) = Peer::new(stream) {
+                        self.clients.push(Client {
+                            peer,
+                            handshake_done: false,
+                        });
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                // Errors of connections that failed before they were accepted
+                Err(_) => break,
+            }
+        }
+
+        self.clients
+            .retain_mut(|client| Self::update_client(client, store).is_ok());
+    }
+
+    fn update_client(client: &mut Client, store: &mut impl CurveStore) -> Result<(), IpcError> {
+        for message in client.peer.receive::<ClientMessage>()? {
+            let reply = match message {
+                ClientMessage::Hello { version } if version != PROTOCOL_VERSION => {
+                    client.peer.send(&HostMessage::Rejected {
+                        key: String::new(),
+                        reason: format!("Host uses protocol version {PROTOCOL_VERSION}"),
+                    })?;
+                    return Err(IpcError::Disconnected);
+                }
+                ClientMessage::Hello { .. } => {
+                    client.handshake_done = true;
+                    Some(HostMessage::Hello {
+                        version: PROTOCOL_VERSION,
+                        keys: store.keys(),
+                    })
+                }
+                ClientMessage::Heartbeat => None,
+                _ if !client.handshake_done => return Err(IpcError::Disconnected),
+                ClientMessage::Fetch { key } => Some(match store.curve(&key) {
+                    Some(curve) => HostMessage::Curve {
+                        key,
+                        curve: Box::new(curve.clone()),
+                    },
+                    None => HostMessage::Rejected {
+                        reason: IpcError::UnknownKey(key.clone()).to_string(),
+                        key,
+                    },
+                }),
+                // Applied deltas are announced by [Self::notify], once the host sees the curve changed
+                ClientMessage::Push { key, delta } => match store.apply(&key, &delta) {
+                    Ok(()) => None,
+                    Err(e) => Some(HostMessage::Rejected {
+                        key,
+                        reason: e.to_string(),
+                    }),
+                },
+            };
+            if let Some(reply) = reply {
+                client.peer.send(&reply)?;
+            }
+        }
+        client.peer.keep_alive(&HostMessage::Heartbeat)
+    }
+
+    /// Sends the changed curve with `key` to every client
+    pub(crate) fn notify(&mut self, key: &str, curve: &LookupCurve) {
+        let message = HostMessage::Curve {
+            key: key.to_string(),
+            curve: Box::new(curve.clone()),
+        };
+        self.clients
+            .retain_mut(|client| !client.handshake_done || client.peer.send(&message).is_ok());
+    }
+}
@@ -0,0 +1,142 @@
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{AssetId, Assets, Handle};
+use bevy_ecs::prelude::{ResMut, Resource};
+use bevy_log::warn;
+
+use super::{link_order, LinkError, LinkRelation};
+use crate::{LookupCurve, NotMonotonic};
+
+pub(crate) struct LinkPlugin;
+
+impl Plugin for LinkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurveLinks>()
+            .add_systems(PostUpdate, update_linked_curves);
+    }
+}
+
+/// A curve asset generated from another curve asset, see [CurveLinks]
+#[derive(Clone, Debug)]
+pub struct CurveLink {
+    pub primary: Handle<LookupCurve>,
+    pub derived: Handle<LookupCurve>,
+    pub relation: LinkRelation,
+}
+
+/// Generations of the curves when a derived curve was last generated
+#[derive(Copy, Clone, Debug, Default)]
+struct LinkState {
+    primary_generation: Option<u64>,
+    derived_generation: Option<u64>,
+    error: Option<NotMonotonic>,
+}
+
+/// Links between curve assets, keeping derived curves in sync with their primary curve.
+///
+/// Whenever a primary curve changes, [update_linked_curves] regenerates the derived curve in [PostUpdate]. Changes
+/// made directly to a derived curve are overwritten, and editors of derived curves refuse edits and offer to
+/// detach the link instead.
+///
+/// Derived curves can be primary curves of other links. They are updated in the same frame as their primary.
+#[derive(Resource, Default)]
+pub struct CurveLinks {
+    /// Links in order of dependency, so that derived curves are updated after their primary
+    links: Vec<(CurveLink, LinkState)>,
+}
+
+impl CurveLinks {
+    /// Registers a link. Curves can only be derived from one curve, and not from themselves through other links.
+    pub fn link(&mut self, link: CurveLink) -> Result<(), LinkError> {
+        link.relation.validate()?;
+        let mut ids: Vec<(AssetId<LookupCurve>, AssetId<LookupCurve>)> = self
+            .links
+            .iter()
+            .map(|(link, _)| (link.primary.id(), link.derived.id()))
+            .collect();
+        ids.push((link.primary.id(), link.derived.id()));
+        let order = link_order(&ids)?;
+
+        let mut links: Vec<_> = std::mem::take(&mut self.links)
+            .into_iter()
+            .map(Some)
+            .chain([Some((link, LinkState::default()))])
+            .collect();
+        self.links = order.into_iter().filter_map(|i| links[i].take()).collect();
+        Ok(())
+    }
+
+    /// Removes the link deriving `derived`, leaving the curve as it is. Returns the link if there was one.
+    pub fn detach(&mut self, derived: impl Into<AssetId<LookupCurve>>) -> Option<CurveLink> {
+        let derived = derived.into();
+        let i = self
+            .links
+            .iter()
+            .position(|(link, _)| link.derived.id() == derived)?;
+        Some(self.links.remove(i).0)
+    }
+
+    /// Returns the link deriving `curve`, if it is a derived curve
+    pub fn derived_by(&self, curve: impl Into<AssetId<LookupCurve>>) -> Option<&CurveLink> {
+        let curve = curve.into();
+        self.links
+            .iter()
+            .map(|(link, _)| link)
+            .find(|link| link.derived.id() == curve)
+    }
+
+    /// Returns why `derived` could not be generated from the current primary curve, if it could not
+    pub fn error(&self, derived: impl Into<AssetId<LookupCurve>>) -> Option<NotMonotonic> {
+        let derived = derived.into();
+        self.links
+            .iter()
+            .find(|(link, _)| link.derived.id() == derived)
+            .and_then(|(_, state)| state.error)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CurveLink> {
+        self.links.iter().map(|(link, _)| link)
+    }
+}
+
+/// Regenerates derived curves whose primary curve changed, or which were changed directly, see [CurveLinks].
+///
+/// Links whose primary curve is not loaded are skipped. If a curve can not be derived, for example because the
+/// primary curve is not invertible, the derived curve is left as it is until the primary curve changes again.
+pub fn update_linked_curves(
+    mut links: ResMut<CurveLinks>,
+    mut curves: ResMut<Assets<LookupCurve>>,
+) {
+    for (link, state) in links.links.iter_mut() {
+        let Some(primary) = curves.get(&link.primary) else {
+            continue;
+        };
+        let derived_generation = curves.get(&link.derived).map(LookupCurve::generation);
+        if state.primary_generation == Some(primary.generation())
+            && (state.error.is_some() || state.derived_generation == derived_generation)
+        {
+            continue;
+        }
+        state.primary_generation = Some(primary.generation());
+
+        match link.relation.derive(primary) {
+            Ok(mut derived) => {
+                // Keep the name of the derived curve
+                if let Some(existing) = curves.get(&link.derived) {
+                    derived.name.clone_from(&existing.name);
+                }
+                state.derived_generation = Some(derived.generation());
+                state.error = None;
+                curves.insert(&link.derived, derived);
+            }
+            Err(e) => {
+                if state.error != Some(e) {
+                    warn!(
+                        "Could not derive curve ({}) of {:?}: {e}",
+                        link.relation, link.primary
+                    );
+                }
+                state.error = Some(e);
+            }
+        }
+    }
+}
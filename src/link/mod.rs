@@ -0,0 +1,174 @@
+//! Curves derived from other curves, kept in sync as the primary curve changes
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::{LookupCurve, NotMonotonic};
+
+#[cfg(feature = "bevy_asset")]
+mod link_bevy;
+#[cfg(feature = "bevy_asset")]
+pub use link_bevy::*;
+
+/// How a derived curve is generated from its primary curve
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LinkRelation {
+    /// The inverse of the primary curve, see [LookupCurve::inverted]
+    Inverse { tolerance: f32 },
+    /// The primary curve scaled on both axes, see [LookupCurve::scaled]
+    Scaled { x: f32, y: f32 },
+    /// The primary curve mirrored across the vertical line at `axis_x`, see [LookupCurve::mirrored]
+    Mirrored { axis_x: f32 },
+}
+
+impl LinkRelation {
+    /// Generates the derived curve from `primary`
+    pub fn derive(&self, primary: &LookupCurve) -> Result<LookupCurve, NotMonotonic> {
+        match *self {
+            LinkRelation::Inverse { tolerance } => primary.inverted(tolerance),
+            LinkRelation::Scaled { x, y } => Ok(primary.scaled(x, y)),
+            LinkRelation::Mirrored { axis_x } => Ok(primary.mirrored(axis_x)),
+        }
+    }
+
+    /// Checks the parameters of the relation
+    pub fn validate(&self) -> Result<(), LinkError> {
+        let valid = match *self {
+            LinkRelation::Inverse { tolerance } => tolerance > 0.0 && tolerance.is_finite(),
+            LinkRelation::Scaled { x, y } => x != 0.0 && x.is_finite() && y.is_finite(),
+            LinkRelation::Mirrored { axis_x } => axis_x.is_finite(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(LinkError::InvalidRelation(*self))
+        }
+    }
+}
+
+impl fmt::Display for LinkRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkRelation::Inverse { .. } => write!(f, "inverse"),
+            LinkRelation::Scaled { x, y } => write!(f, "scaled by ({x}, {y})"),
+            LinkRelation::Mirrored { axis_x } => write!(f, "mirrored at x = {axis_x}"),
+        }
+    }
+}
+
+/// Error returned when registering a link between curves
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LinkError {
+    /// The primary and derived curve are the same curve
+    SelfLink,
+    /// The link would make a curve derived from itself through other links
+    Cycle,
+    /// The derived curve is already derived from another curve
+    AlreadyDerived,
+    /// The parameters of the relation are invalid, like a zero scale
+    InvalidRelation(LinkRelation),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::SelfLink => write!(f, "A curve can not be derived from itself"),
+            LinkError::Cycle => write!(f, "Link would make a curve derived from itself"),
+            LinkError::AlreadyDerived => write!(f, "Curve is already derived from another curve"),
+            LinkError::InvalidRelation(relation) => {
+                write!(f, "Invalid parameters for relation {relation:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+/// Orders links given as `(primary, derived)` so that every link comes after the link deriving its primary.
+///
+/// Returns the indices of the links in that order, or an error if a curve is derived more than once, from itself, or
+/// from itself through other links.
+#[cfg_attr(not(feature = "bevy_asset"), allow(dead_code))]
+pub(crate) fn link_order<T: Copy + Eq + Hash>(links: &[(T, T)]) -> Result<Vec<usize>, LinkError> {
+    let mut deriving: HashMap<T, usize> = HashMap::new();
+    for (i, &(primary, derived)) in links.iter().enumerate() {
+        if primary == derived {
+            return Err(LinkError::SelfLink);
+        }
+        if deriving.insert(derived, i).is_some() {
+            return Err(LinkError::AlreadyDerived);
+        }
+    }
+
+    // Each curve is derived at most once, so following primaries from any link either ends or cycles
+    let mut order = Vec::with_capacity(links.len());
+    let mut placed = vec![false; links.len()];
+    for start in 0..links.len() {
+        let mut chain = Vec::new();
+        let mut link = Some(start);
+        while let Some(i) = link.filter(|&i| !placed[i]) {
+            if chain.contains(&i) {
+                return Err(LinkError::Cycle);
+            }
+            chain.push(i);
+            link = deriving.get(&links[i].0).copied();
+        }
+        for &i in chain.iter().rev() {
+            placed[i] = true;
+            order.push(i);
+        }
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, KnotInterpolation};
+    use bevy_math::Vec2;
+
+    #[test]
+    fn links_are_ordered_by_dependency() {
+        let order = link_order(&[(2, 3), (1, 2), (0, 1), (1, 4)]).unwrap();
+        assert_eq!(order, [2, 1, 0, 3]);
+        let position = |link| order.iter().position(|&i| i == link).unwrap();
+        assert!(position(2) < position(3));
+        assert!(link_order::<u32>(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_links_are_rejected() {
+        assert_eq!(link_order(&[(0, 1), (1, 1)]), Err(LinkError::SelfLink));
+        assert_eq!(
+            link_order(&[(0, 1), (2, 1)]),
+            Err(LinkError::AlreadyDerived)
+        );
+        assert_eq!(link_order(&[(0, 1), (1, 2), (2, 0)]), Err(LinkError::Cycle));
+        assert_eq!(
+            link_order(&[(3, 4), (0, 1), (1, 2), (2, 0)]),
+            Err(LinkError::Cycle)
+        );
+
+        assert!(LinkRelation::Scaled { x: 0.0, y: 1.0 }.validate().is_err());
+        assert!(LinkRelation::Inverse { tolerance: 0.0 }.validate().is_err());
+        assert!(LinkRelation::Mirrored { axis_x: 1.0 }.validate().is_ok());
+    }
+
+    #[test]
+    fn derived_inverse_is_within_tolerance() {
+        let knot = |x: f32, y: f32| Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        };
+        let primary = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 2.0), knot(2.0, 3.0)]);
+        let relation = LinkRelation::Inverse { tolerance: 1e-3 };
+        let derived = relation.derive(&primary).unwrap();
+        for i in 0..=20 {
+            let x = i as f32 / 10.0;
+            let back = derived.lookup(primary.lookup(x));
+            assert!((back - x).abs() < 1e-2, "{x}: {back}");
+        }
+    }
+}
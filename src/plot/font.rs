@@ -0,0 +1,129 @@
+use image::{Rgba, RgbaImage};
+
+/// Width of a glyph in font pixels
+const GLYPH_WIDTH: u32 = 3;
+/// Height of a glyph in font pixels
+const GLYPH_HEIGHT: u32 = 5;
+/// Size of a font pixel in image pixels
+pub(crate) const FONT_SCALE: u32 = 2;
+/// Horizontal distance between glyphs in image pixels
+const ADVANCE: u32 = (GLYPH_WIDTH + 1) * FONT_SCALE;
+/// Height of a line of text in image pixels
+pub(crate) const LINE_HEIGHT: u32 = (GLYPH_HEIGHT + 2) * FONT_SCALE;
+
+/// Returns the width of `text` in image pixels
+pub(crate) fn text_width(text: &str) -> u32 {
+    (text.chars().count() as u32 * ADVANCE).saturating_sub(FONT_SCALE)
+}
+
+/// Draws `text` with its top left corner at `(x, y)`. Pixels outside of the image are skipped.
+pub(crate) fn draw_text(image: &mut RgbaImage, x: i32, y: i32, text: &str, color: Rgba<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let glyph = glyph(c);
+        let glyph_x = x + (i as u32 * ADVANCE) as i32;
+        for row in 0..GLYPH_HEIGHT {
+            for column in 0..GLYPH_WIDTH {
+                let bit = (GLYPH_HEIGHT - 1 - row) * GLYPH_WIDTH + (GLYPH_WIDTH - 1 - column);
+                if glyph & (1 << bit) == 0 {
+                    continue;
+                }
+                for dy in 0..FONT_SCALE {
+                    for dx in 0..FONT_SCALE {
+                        let px = glyph_x + (column * FONT_SCALE + dx) as i32;
+                        let py = y + (row * FONT_SCALE + dy) as i32;
+                        if px >= 0
+                            && py >= 0
+                            && (px as u32) < image.width()
+                            && (py as u32) < image.height()
+                        {
+                            image.put_pixel(px as u32, py as u32, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 3x5 bitmap of `c`, one bit per pixel with the top left pixel in the highest bit.
+/// Characters without a glyph are drawn as `?`.
+fn glyph(c: char) -> u16 {
+    match c {
+        ' ' => 0,
+        '0' => 0b111_101_101_101_111,
+        '1' => 0b010_110_010_010_111,
+        '2' => 0b111_001_111_100_111,
+        '3' => 0b111_001_111_001_111,
+        '4' => 0b101_101_111_001_001,
+        '5' => 0b111_100_111_001_111,
+        '6' => 0b111_100_111_101_111,
+        '7' => 0b111_001_001_010_010,
+        '8' => 0b111_101_111_101_111,
+        '9' => 0b111_101_111_001_111,
+        'A' => 0b010_101_111_101_101,
+        'B' => 0b110_101_110_101_110,
+        'C' => 0b011_100_100_100_011,
+        'D' => 0b110_101_101_101_110,
+        'E' => 0b111_100_110_100_111,
+        'F' => 0b111_100_110_100_100,
+        'G' => 0b011_100_101_101_011,
+        'H' => 0b101_101_111_101_101,
+        'I' => 0b111_010_010_010_111,
+        'J' => 0b001_001_001_101_010,
+        'K' => 0b101_101_110_101_101,
+        'L' => 0b100_100_100_100_111,
+        'M' => 0b101_111_111_101_101,
+        'N' => 0b110_101_101_101_101,
+        'O' => 0b010_101_101_101_010,
+        'P' => 0b110_101_110_100_100,
+        'Q' => 0b010_101_101_110_011,
+        'R' => 0b110_101_110_101_101,
+        'S' => 0b011_100_010_001_110,
+        'T' => 0b111_010_010_010_010,
+        'U' => 0b101_101_101_101_111,
+        'V' => 0b101_101_101_101_010,
+        'W' => 0b101_101_111_111_101,
+        'X' => 0b101_101_010_101_101,
+        'Y' => 0b101_101_010_010_010,
+        'Z' => 0b111_001_010_100_111,
+        'a' => 0b000_011_101_101_011,
+        'b' => 0b100_110_101_101_110,
+        'c' => 0b000_011_100_100_011,
+        'd' => 0b001_011_101_101_011,
+        'e' => 0b000_010_111_100_011,
+        'f' => 0b001_010_111_010_010,
+        'g' => 0b011_101_011_001_110,
+        'h' => 0b100_110_101_101_101,
+        'i' => 0b010_000_010_010_010,
+        'j' => 0b001_000_001_101_010,
+        'k' => 0b100_101_110_110_101,
+        'l' => 0b110_010_010_010_111,
+        'm' => 0b000_111_111_111_101,
+        'n' => 0b000_110_101_101_101,
+        'o' => 0b000_010_101_101_010,
+        'p' => 0b000_110_101_110_100,
+        'q' => 0b000_011_101_011_001,
+        'r' => 0b000_011_100_100_100,
+        's' => 0b000_011_110_011_110,
+        't' => 0b010_111_010_010_011,
+        'u' => 0b000_101_101_101_011,
+        'v' => 0b000_101_101_111_010,
+        'w' => 0b000_101_111_111_111,
+        'x' => 0b000_101_010_010_101,
+        'y' => 0b000_101_011_001_110,
+        'z' => 0b000_111_011_110_111,
+        '.' => 0b000_000_000_000_010,
+        ',' => 0b000_000_000_010_100,
+        ':' => 0b000_010_000_010_000,
+        '-' => 0b000_000_111_000_000,
+        '+' => 0b000_010_111_010_000,
+        '=' => 0b000_111_000_111_000,
+        '_' => 0b000_000_000_000_111,
+        '/' => 0b001_001_010_100_100,
+        '%' => 0b101_001_010_100_101,
+        '*' => 0b000_101_010_101_000,
+        '(' => 0b001_010_010_010_001,
+        ')' => 0b100_010_010_010_100,
+        _ => 0b111_001_010_000_010,
+    }
+}
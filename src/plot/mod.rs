@@ -0,0 +1,654 @@
+use bevy_math::Vec2;
+use image::{Rgba, RgbaImage};
+
+use crate::stats::CurveStats;
+use crate::ticks::{grid_lines, nice_step, step_precision};
+use crate::{KnotInterpolation, LookupCurve, TangentSide, UnitDisplay};
+
+mod font;
+use font::{draw_text, text_width, FONT_SCALE, LINE_HEIGHT};
+
+/// Options for [render_curve_report]
+#[derive(Clone, Debug)]
+pub struct PlotOptions {
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the curve plot in pixels. The derivative and integral plots are half as high.
+    pub plot_height: u32,
+    /// Number of samples used for drawing each plot
+    pub samples: usize,
+    /// Draw the knots and tangent handles of the curve
+    pub knots: bool,
+    /// Add a plot of the derivative (slope) of the curve
+    pub derivative: bool,
+    /// Add a plot of the cumulative integral of the curve, starting from the first knot
+    pub integral: bool,
+    /// Add a box with the domain, range and knot count of the curve
+    pub stats: bool,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            plot_height: 400,
+            samples: 512,
+            knots: true,
+            derivative: true,
+            integral: true,
+            stats: true,
+        }
+    }
+}
+
+const BACKGROUND: Rgba<u8> = Rgba([20, 20, 20, 255]);
+const PLOT_BACKGROUND: Rgba<u8> = Rgba([30, 30, 30, 255]);
+const GRID: Rgba<u8> = Rgba([42, 42, 42, 255]);
+const TEXT: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const LABEL: Rgba<u8> = Rgba([160, 160, 160, 255]);
+const CURVE: Rgba<u8> = Rgba([0, 255, 0, 255]);
+const KNOT: Rgba<u8> = Rgba([144, 238, 144, 255]);
+const TANGENT: Rgba<u8> = Rgba([160, 160, 160, 255]);
+const DERIVATIVE: Rgba<u8> = Rgba([255, 170, 0, 255]);
+const INTEGRAL: Rgba<u8> = Rgba([80, 160, 255, 255]);
+
+/// Space left of the plots for the y axis labels
+const MARGIN_LEFT: u32 = 72;
+const MARGIN_RIGHT: u32 = 16;
+const MARGIN_BOTTOM: u32 = 8;
+/// Space above the first plot for the title
+const TITLE_HEIGHT: u32 = 28;
+/// Space below each plot for the x axis labels
+const AXIS_HEIGHT: u32 = 28;
+const STATS_PADDING: u32 = 6;
+const STATS_LINES: u32 = 3;
+/// Max number of ticks on an axis
+const MAX_TICKS: u32 = 8;
+
+/// Renders `curve` to an image, with the plots and stats selected by `options`.
+///
+/// Meant for documentation and reviews, the image is not pixel-identical to the editor.
+/// Units and display scales are taken from [LookupCurve::x_unit] and [LookupCurve::y_unit].
+/// Text is drawn with a small built-in font that only covers ASCII.
+pub fn render_curve_report(curve: &LookupCurve, options: PlotOptions) -> RgbaImage {
+    let layout = ReportLayout::new(&options);
+    let mut image = RgbaImage::from_pixel(layout.size.0, layout.size.1, BACKGROUND);
+
+    let x_unit = curve.x_unit.clone().unwrap_or_default();
+    let y_unit = curve.y_unit.clone().unwrap_or_default();
+    let stats = CurveStats::compute(curve);
+
+    draw_text(
+        &mut image,
+        MARGIN_LEFT as i32,
+        ((TITLE_HEIGHT - LINE_HEIGHT) / 2) as i32,
+        curve.name_or_default(),
+        TEXT,
+    );
+
+    let (x_min, x_max) = stats.domain.unwrap_or((0.0, 1.0));
+    let samples = options.samples.max(2);
+    let xs: Vec<f32> = (0..samples)
+        .map(|i| x_min + (x_max - x_min) * i as f32 / (samples - 1) as f32)
+        .collect();
+
+    let points: Vec<Vec2> = xs.iter().map(|&x| Vec2::new(x, curve.lookup(x))).collect();
+    let (y_min, y_max) = stats.range.unwrap_or((0.0, 1.0));
+    let transform = PlotTransform::new(
+        layout.curve,
+        Vec2::new(x_min, y_min),
+        Vec2::new(x_max, y_max),
+    )
+    .padded(0.05);
+    draw_plot(
+        &mut image, &transform, &points, CURVE, &x_unit, &y_unit, "y",
+    );
+    if options.knots {
+        draw_knots(&mut image, &transform, curve);
+    }
+
+    if let Some(rect) = layout.derivative {
+        let side = |i: usize| {
+            if i + 1 == xs.len() {
+                TangentSide::Left
+            } else {
+                TangentSide::Right
+            }
+        };
+        let points: Vec<Vec2> = xs
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| Vec2::new(x, curve.slope_at(x, side(i))))
+            .collect();
+        let unit = derived_unit(&x_unit, &y_unit, "/", y_unit.scale / x_unit.scale);
+        draw_plot(
+            &mut image,
+            &PlotTransform::fitted(rect, &points),
+            &points,
+            DERIVATIVE,
+            &x_unit,
+            &unit,
+            "dy/dx",
+        );
+    }
+
+    if let Some(rect) = layout.integral {
+        let points = cumulative_integral(&points);
+        let unit = derived_unit(&x_unit, &y_unit, "*", y_unit.scale * x_unit.scale);
+        draw_plot(
+            &mut image,
+            &PlotTransform::fitted(rect, &points),
+            &points,
+            INTEGRAL,
+            &x_unit,
+            &unit,
+            "integral",
+        );
+    }
+
+    if let Some(rect) = layout.stats {
+        fill_rect(&mut image, rect, PLOT_BACKGROUND);
+        let range_text = |range: Option<(f32, f32)>, unit: &UnitDisplay| match range {
+            Some((min, max)) => format!("{}..{}", unit.format(min), unit.format(max)),
+            None => "-".to_string(),
+        };
+        let lines = [
+            format!("Domain: {}", range_text(stats.domain, &x_unit)),
+            format!("Range: {}", range_text(stats.range, &y_unit)),
+            format!("Knots: {}", stats.knot_count),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                &mut image,
+                (rect.x + STATS_PADDING) as i32,
+                (rect.y + STATS_PADDING + i as u32 * LINE_HEIGHT) as i32,
+                line,
+                TEXT,
+            );
+        }
+    }
+
+    image
+}
+
+/// Rectangle in image pixels
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct PixelRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl PixelRect {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x as i32
+            && y >= self.y as i32
+            && x < (self.x + self.width) as i32
+            && y < (self.y + self.height) as i32
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+}
+
+/// Placement of the parts of a report, stacked vertically
+#[derive(Clone, Debug, PartialEq)]
+struct ReportLayout {
+    size: (u32, u32),
+    curve: PixelRect,
+    derivative: Option<PixelRect>,
+    integral: Option<PixelRect>,
+    stats: Option<PixelRect>,
+}
+
+impl ReportLayout {
+    fn new(options: &PlotOptions) -> Self {
+        let width = options.width.max(MARGIN_LEFT + MARGIN_RIGHT + 1);
+        let plot_width = width - MARGIN_LEFT - MARGIN_RIGHT;
+        let plot_height = options.plot_height.max(1);
+        let sub_plot_height = (plot_height / 2).max(1);
+
+        let mut y = TITLE_HEIGHT;
+        let mut next_rect = |height: u32, space_below: u32| {
+            let rect = PixelRect {
+                x: MARGIN_LEFT,
+                y,
+                width: plot_width,
+                height,
+            };
+            y += height + space_below;
+            rect
+        };
+
+        let curve = next_rect(plot_height, AXIS_HEIGHT);
+        let derivative = options
+            .derivative
+            .then(|| next_rect(sub_plot_height, AXIS_HEIGHT));
+        let integral = options
+            .integral
+            .then(|| next_rect(sub_plot_height, AXIS_HEIGHT));
+        let stats = options
+            .stats
+            .then(|| next_rect(STATS_LINES * LINE_HEIGHT + 2 * STATS_PADDING, 0));
+
+        let bottom = [Some(curve), derivative, integral, stats]
+            .into_iter()
+            .flatten()
+            .map(|rect| rect.bottom())
+            .max()
+            .unwrap_or(0);
+        let height = if stats.is_some() {
+            bottom + MARGIN_BOTTOM
+        } else {
+            bottom + AXIS_HEIGHT
+        };
+
+        Self {
+            size: (width, height),
+            curve,
+            derivative,
+            integral,
+            stats,
+        }
+    }
+}
+
+/// Maps values in a plot to pixels in its [PixelRect]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct PlotTransform {
+    rect: PixelRect,
+    min: Vec2,
+    max: Vec2,
+}
+
+impl PlotTransform {
+    /// Empty or inverted ranges are widened around their center, so that every value maps to a finite pixel.
+    fn new(rect: PixelRect, min: Vec2, max: Vec2) -> Self {
+        let widen = |min: f32, max: f32| {
+            if max > min {
+                (min, max)
+            } else {
+                let center = if min.is_finite() { min } else { 0.0 };
+                (center - 0.5, center + 0.5)
+            }
+        };
+        let (min_x, max_x) = widen(min.x, max.x);
+        let (min_y, max_y) = widen(min.y, max.y);
+        Self {
+            rect,
+            min: Vec2::new(min_x, min_y),
+            max: Vec2::new(max_x, max_y),
+        }
+    }
+
+    /// Transform showing all `points`
+    fn fitted(rect: PixelRect, points: &[Vec2]) -> Self {
+        let (min, max) = points
+            .iter()
+            .filter(|p| p.is_finite())
+            .fold((Vec2::INFINITY, Vec2::NEG_INFINITY), |(min, max), p| {
+                (min.min(*p), max.max(*p))
+            });
+        Self::new(rect, min, max).padded(0.05)
+    }
+
+    /// Returns the transform with the y range grown by `fraction` of its size on both sides
+    fn padded(self, fraction: f32) -> Self {
+        let padding = (self.max.y - self.min.y) * fraction;
+        Self {
+            min: Vec2::new(self.min.x, self.min.y - padding),
+            max: Vec2::new(self.max.x, self.max.y + padding),
+            ..self
+        }
+    }
+
+    /// Returns the pixel position of `value`. y grows upwards in the plot, and downwards in the image.
+    fn value_to_pixel(&self, value: Vec2) -> Vec2 {
+        let t = (value - self.min) / (self.max - self.min);
+        Vec2::new(
+            self.rect.x as f32 + t.x * (self.rect.width - 1) as f32,
+            self.rect.y as f32 + (1.0 - t.y) * (self.rect.height - 1) as f32,
+        )
+    }
+}
+
+/// Draws the background, grid, axis labels and the polyline through `points`
+fn draw_plot(
+    image: &mut RgbaImage,
+    transform: &PlotTransform,
+    points: &[Vec2],
+    color: Rgba<u8>,
+    x_unit: &UnitDisplay,
+    y_unit: &UnitDisplay,
+    label: &str,
+) {
+    let rect = transform.rect;
+    fill_rect(image, rect, PLOT_BACKGROUND);
+
+    for (x, text) in axis_ticks(transform.min.x, transform.max.x, x_unit) {
+        let pixel_x = transform.value_to_pixel(Vec2::new(x, transform.min.y)).x;
+        draw_line(
+            image,
+            Vec2::new(pixel_x, rect.y as f32),
+            Vec2::new(pixel_x, (rect.bottom() - 1) as f32),
+            GRID,
+            rect,
+        );
+        draw_text(
+            image,
+            pixel_x as i32 - text_width(&text) as i32 / 2,
+            (rect.bottom() + FONT_SCALE * 2) as i32,
+            &text,
+            TEXT,
+        );
+    }
+    for (y, text) in axis_ticks(transform.min.y, transform.max.y, y_unit) {
+        let pixel_y = transform.value_to_pixel(Vec2::new(transform.min.x, y)).y;
+        draw_line(
+            image,
+            Vec2::new(rect.x as f32, pixel_y),
+            Vec2::new((rect.x + rect.width - 1) as f32, pixel_y),
+            GRID,
+            rect,
+        );
+        draw_text(
+            image,
+            rect.x as i32 - text_width(&text) as i32 - 2 * FONT_SCALE as i32,
+            pixel_y as i32 - LINE_HEIGHT as i32 / 2 + FONT_SCALE as i32,
+            &text,
+            TEXT,
+        );
+    }
+
+    for pair in points.windows(2) {
+        if pair[0].is_finite() && pair[1].is_finite() {
+            draw_line(
+                image,
+                transform.value_to_pixel(pair[0]),
+                transform.value_to_pixel(pair[1]),
+                color,
+                rect,
+            );
+        }
+    }
+
+    let label = if y_unit.unit.is_empty() {
+        label.to_string()
+    } else {
+        format!("{label} ({})", y_unit.unit)
+    };
+    draw_text(
+        image,
+        (rect.x + 2 * FONT_SCALE) as i32,
+        (rect.y + 2 * FONT_SCALE) as i32,
+        &label,
+        LABEL,
+    );
+    if !x_unit.unit.is_empty() {
+        draw_text(
+            image,
+            (rect.x + rect.width) as i32 - text_width(&x_unit.unit) as i32 - 2 * FONT_SCALE as i32,
+            rect.bottom() as i32 - LINE_HEIGHT as i32,
+            &x_unit.unit,
+            LABEL,
+        );
+    }
+}
+
+/// Draws the knots, and the tangent handles of cubic segments
+fn draw_knots(image: &mut RgbaImage, transform: &PlotTransform, curve: &LookupCurve) {
+    let knots = curve.knots();
+    for (i, knot) in knots.iter().enumerate() {
+        let knot_pixel = transform.value_to_pixel(knot.position);
+        let mut handles = vec![];
+        if let Some(next) = curve.next_knot(i) {
+            if matches!(knot.interpolation, KnotInterpolation::Cubic) {
+                handles.push(knot.compute_bezier_to(next)[1]);
+            }
+        }
+        if let Some(prev) = curve.prev_knot(i) {
+            if matches!(prev.interpolation, KnotInterpolation::Cubic) {
+                handles.push(prev.compute_bezier_to(knot)[2]);
+            }
+        }
+        for handle in handles {
+            let handle_pixel = transform.value_to_pixel(handle);
+            draw_line(image, knot_pixel, handle_pixel, TANGENT, transform.rect);
+            draw_dot(image, handle_pixel, 2, TANGENT, transform.rect);
+        }
+        draw_dot(image, knot_pixel, 3, KNOT, transform.rect);
+    }
+}
+
+/// Tick positions between `min` and `max`, at nice steps of the displayed values, with their labels
+fn axis_ticks(min: f32, max: f32, unit: &UnitDisplay) -> Vec<(f32, String)> {
+    let (display_min, display_max) = {
+        let (a, b) = (unit.to_display(min), unit.to_display(max));
+        (a.min(b), a.max(b))
+    };
+    let step = nice_step(display_max - display_min, MAX_TICKS);
+    let precision = step_precision(step);
+    grid_lines(display_min, display_max - display_min, step)
+        .filter(|v| *v >= display_min && *v <= display_max)
+        .map(|v| {
+            // Avoid "-0.0"
+            let v = if v.abs() < step * 1e-3 { 0.0 } else { v };
+            (unit.from_display(v), format!("{v:.precision$}"))
+        })
+        .collect()
+}
+
+/// Unit of a value derived from x and y, such as the slope
+fn derived_unit(
+    x_unit: &UnitDisplay,
+    y_unit: &UnitDisplay,
+    operator: &str,
+    scale: f32,
+) -> UnitDisplay {
+    let unit = match (x_unit.unit.is_empty(), y_unit.unit.is_empty()) {
+        (true, true) => String::new(),
+        (true, false) => y_unit.unit.clone(),
+        (false, true) if operator == "*" => x_unit.unit.clone(),
+        (false, _) => format!("{}{operator}{}", y_unit.unit, x_unit.unit),
+    };
+    UnitDisplay {
+        unit,
+        scale,
+        precision: y_unit.precision,
+    }
+}
+
+/// Cumulative integral of the polyline through `points`, using the trapezoidal rule
+fn cumulative_integral(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sum = 0.0;
+    let mut prev: Option<Vec2> = None;
+    points
+        .iter()
+        .map(|p| {
+            if let Some(prev) = prev {
+                sum += (p.x - prev.x) * (p.y + prev.y) * 0.5;
+            }
+            prev = Some(*p);
+            Vec2::new(p.x, sum)
+        })
+        .collect()
+}
+
+fn fill_rect(image: &mut RgbaImage, rect: PixelRect, color: Rgba<u8>) {
+    for y in rect.y..rect.bottom().min(image.height()) {
+        for x in rect.x..(rect.x + rect.width).min(image.width()) {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+/// Draws a line from `a` to `b`, skipping pixels outside of `clip`
+fn draw_line(image: &mut RgbaImage, a: Vec2, b: Vec2, color: Rgba<u8>, clip: PixelRect) {
+    let steps = (b - a).abs().max_element().ceil().min(100_000.0) as i32;
+    for i in 0..=steps {
+        let t = if steps == 0 {
+            0.0
+        } else {
+            i as f32 / steps as f32
+        };
+        let p = a.lerp(b, t).round();
+        put_pixel_clipped(image, p.x as i32, p.y as i32, color, clip);
+    }
+}
+
+fn draw_dot(image: &mut RgbaImage, center: Vec2, radius: i32, color: Rgba<u8>, clip: PixelRect) {
+    let (cx, cy) = (center.x.round() as i32, center.y.round() as i32);
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dy * dy <= radius * radius {
+                put_pixel_clipped(image, cx + dx, cy + dy, color, clip);
+            }
+        }
+    }
+}
+
+fn put_pixel_clipped(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>, clip: PixelRect) {
+    if clip.contains(x, y) && (x as u32) < image.width() && (y as u32) < image.height() {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+
+    fn test_curve() -> LookupCurve {
+        LookupCurve::new(vec![
+            Knot {
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::new(0.5, 0.8),
+                interpolation: KnotInterpolation::Linear,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::ONE,
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn layout_stacks_selected_parts() {
+        let options = PlotOptions {
+            width: 400,
+            plot_height: 200,
+            ..Default::default()
+        };
+        let layout = ReportLayout::new(&options);
+        assert_eq!(
+            layout.curve,
+            PixelRect {
+                x: MARGIN_LEFT,
+                y: TITLE_HEIGHT,
+                width: 400 - MARGIN_LEFT - MARGIN_RIGHT,
+                height: 200,
+            }
+        );
+        let derivative = layout.derivative.unwrap();
+        let integral = layout.integral.unwrap();
+        let stats = layout.stats.unwrap();
+        assert_eq!(derivative.y, layout.curve.bottom() + AXIS_HEIGHT);
+        assert_eq!(derivative.height, 100);
+        assert_eq!(integral.y, derivative.bottom() + AXIS_HEIGHT);
+        assert_eq!(stats.y, integral.bottom() + AXIS_HEIGHT);
+        assert_eq!(layout.size, (400, stats.bottom() + MARGIN_BOTTOM));
+
+        let layout = ReportLayout::new(&PlotOptions {
+            derivative: false,
+            integral: false,
+            stats: false,
+            ..options
+        });
+        assert_eq!(layout.derivative, None);
+        assert_eq!(layout.stats, None);
+        assert_eq!(layout.size.1, layout.curve.bottom() + AXIS_HEIGHT);
+    }
+
+    #[test]
+    fn layout_handles_tiny_sizes() {
+        let layout = ReportLayout::new(&PlotOptions {
+            width: 0,
+            plot_height: 0,
+            ..Default::default()
+        });
+        assert_eq!(layout.curve.width, 1);
+        assert_eq!(layout.curve.height, 1);
+        assert_eq!(layout.derivative.unwrap().height, 1);
+    }
+
+    #[test]
+    fn values_map_to_pixels() {
+        let rect = PixelRect {
+            x: 10,
+            y: 20,
+            width: 101,
+            height: 51,
+        };
+        let transform = PlotTransform::new(rect, Vec2::ZERO, Vec2::new(1.0, 2.0));
+        assert_eq!(transform.value_to_pixel(Vec2::ZERO), Vec2::new(10.0, 70.0));
+        assert_eq!(
+            transform.value_to_pixel(Vec2::new(1.0, 2.0)),
+            Vec2::new(110.0, 20.0)
+        );
+        assert_eq!(
+            transform.value_to_pixel(Vec2::new(0.5, 1.0)),
+            Vec2::new(60.0, 45.0)
+        );
+
+        // Flat curves are centered
+        let transform = PlotTransform::fitted(rect, &[Vec2::new(0.0, 3.0), Vec2::new(1.0, 3.0)]);
+        assert!((transform.value_to_pixel(Vec2::new(0.0, 3.0)).y - 45.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn ticks_use_display_units() {
+        let ms = UnitDisplay::new("ms").with_scale(1000.0);
+        let ticks = axis_ticks(0.0, 0.5, &ms);
+        assert_eq!(ticks.first().unwrap().1, "0");
+        assert_eq!(ticks.last().unwrap(), &(0.5, "500".to_string()));
+        let ticks = axis_ticks(-1.0, 1.0, &UnitDisplay::default());
+        assert!(ticks.iter().any(|(v, text)| *v == 0.0 && text == "0.0"));
+    }
+
+    #[test]
+    fn integral_of_linear_curve() {
+        let points: Vec<Vec2> = (0..=10)
+            .map(|i| Vec2::new(i as f32 / 10.0, i as f32 / 10.0))
+            .collect();
+        let integral = cumulative_integral(&points);
+        assert_eq!(integral[0], Vec2::ZERO);
+        assert!((integral[10].y - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn renders_report() {
+        let options = PlotOptions {
+            width: 300,
+            plot_height: 120,
+            samples: 64,
+            ..Default::default()
+        };
+        let layout = ReportLayout::new(&options);
+        let image = render_curve_report(&test_curve(), options);
+        assert_eq!(image.dimensions(), layout.size);
+        assert!(image.pixels().any(|p| *p == CURVE));
+        assert!(image.pixels().any(|p| *p == DERIVATIVE));
+        assert!(image.pixels().any(|p| *p == INTEGRAL));
+        assert!(image.pixels().any(|p| *p == TEXT));
+
+        // Empty curves render without panicking
+        let image = render_curve_report(&LookupCurve::default(), PlotOptions::default());
+        assert!(image.width() > 0 && image.height() > 0);
+    }
+}
@@ -0,0 +1,31 @@
+//! Commonly used items, import them with `use bevy_lookup_curve::prelude::*;`
+
+pub use crate::sample::SampleCurve;
+pub use crate::{
+    Extrapolation, Knot, KnotInterpolation, LookupCache, LookupCurve, Tangent, TangentMode,
+    TangentSide,
+};
+
+#[cfg(any(
+    feature = "bevy_asset",
+    feature = "editor_bevy",
+    feature = "inspector-egui",
+    feature = "tween_bevy",
+    feature = "diagnostics"
+))]
+pub use crate::LookupCurvePlugin;
+
+#[cfg(feature = "editor_egui")]
+pub use crate::editor::LookupCurveEguiEditor;
+#[cfg(feature = "editor_bevy")]
+pub use crate::editor::{Curves, LookupCurveCommandsExt, LookupCurveEditor};
+
+#[cfg(all(feature = "tween_bevy", feature = "editor_bevy"))]
+pub use crate::tween::CurveSheetCommandsExt;
+#[cfg(feature = "tween_bevy")]
+pub use crate::tween::{CurveSheet, CurveSheetAppExt, CurveSheetCompleted, LookupCurveTween};
+pub use crate::tween::{CurveTween, TweenRepeat, Tweenable};
+
+pub use crate::world_edit::CurvePlane;
+#[cfg(feature = "world_edit")]
+pub use crate::world_edit::WorldCurveEditor;
@@ -0,0 +1,403 @@
+//! Processing of lookup curve assets, see [CurveProcessSettings].
+//!
+//! Processed curves are validated and stored in a canonical encoding, either as compact RON or as a binary encoding.
+//! The asset loader accepts both processed and unprocessed curves.
+use bevy_math::Vec2;
+
+use crate::{
+    serialize_curve_ron, CurveFormat, EditClock, Extrapolation, Knot, KnotInterpolation,
+    LookupCurve, LookupCurveLoadError, QuantizeRule, RonStyle, Tangent, UnitDisplay,
+};
+
+/// Bytes at the start of binary encoded curves. RON can not start with these bytes.
+const BINARY_MAGIC: &[u8; 4] = b"\0LCB";
+/// Version of the binary encoding, bumped on every change to [BinaryCurve]
+const BINARY_VERSION: u8 = 4;
+
+/// How a processed curve is encoded
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CurveEncoding {
+    /// RON without whitespace, with fields in a fixed order
+    #[default]
+    Ron,
+    /// Compact binary encoding using [postcard], faster to load than RON
+    Binary,
+}
+
+/// What processing does when a curve breaks a rule
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum RuleAction {
+    /// Keep the curve as is
+    #[default]
+    Ignore,
+    /// Fix the curve, see the rule for how
+    Repair,
+    /// Fail processing with a [CurveProcessError]
+    Fail,
+}
+
+/// Settings for processing lookup curve assets
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CurveProcessSettings {
+    /// Anchors the curve does not pass through, repaired with [LookupCurve::enforce_anchors]
+    pub anchors: RuleAction,
+    /// Segments with bezier control points that are not monotonic on the x axis, repaired with
+    /// [LookupCurve::enforce_monotonic_x]
+    pub monotonic_x: RuleAction,
+    /// Knots that are not finite, repaired by removing them
+    pub finite_knots: RuleAction,
+    /// Encoding of the processed curve
+    pub encoding: CurveEncoding,
+}
+
+/// Error while processing a lookup curve
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum CurveProcessError {
+    /// The source could not be loaded
+    #[error(transparent)]
+    Load(#[from] LookupCurveLoadError),
+    /// The curve does not pass through some of its anchors
+    #[error("Lookup curve does not pass through {0} of its anchors")]
+    AnchorViolations(usize),
+    /// Some segments are not monotonic on the x axis
+    #[error(
+        "Lookup curve has {0} segments with control points that are not monotonic on the x axis"
+    )]
+    NotMonotonicX(usize),
+    /// Some knots have positions or tangents that are not finite
+    #[error("Lookup curve has {0} knots that are not finite")]
+    NotFinite(usize),
+    /// An [IO](std::io) Error, while writing the processed curve
+    #[error("Could not write processed lookup curve: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not serialize lookup curve to RON: {0}")]
+    Ron(#[from] ron::error::Error),
+    /// A [postcard] Error
+    #[error("Could not encode lookup curve: {0}")]
+    Binary(#[from] postcard::Error),
+}
+
+impl LookupCurve {
+    /// Applies the rules of `settings` to the curve, repairing it or returning the first broken rule
+    pub fn apply_process_rules(
+        &mut self,
+        settings: &CurveProcessSettings,
+    ) -> Result<(), CurveProcessError> {
+        let not_finite = self.knots.iter().filter(|k| !k.is_finite()).count();
+        if not_finite > 0 {
+            match settings.finite_knots {
+                RuleAction::Ignore => {}
+                RuleAction::Repair => {
+                    self.knots.retain(|k| k.is_finite());
+                    self.refresh_auto_tangents();
+                    self.bump_generation();
+                    self.bump_order_epoch();
+                }
+                RuleAction::Fail => return Err(CurveProcessError::NotFinite(not_finite)),
+            }
+        }
+
+        let not_monotonic = self.non_monotonic_x_segments().len();
+        if not_monotonic > 0 {
+            match settings.monotonic_x {
+                RuleAction::Ignore => {}
+                RuleAction::Repair => {
+                    self.enforce_monotonic_x();
+                }
+                RuleAction::Fail => return Err(CurveProcessError::NotMonotonicX(not_monotonic)),
+            }
+        }
+
+        // Anchors last, as the other repairs might move the curve away from them
+        let violations = self.validate_anchors().len();
+        if violations > 0 {
+            match settings.anchors {
+                RuleAction::Ignore => {}
+                RuleAction::Repair => self.enforce_anchors(),
+                RuleAction::Fail => return Err(CurveProcessError::AnchorViolations(violations)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the curve as a processed asset, see [CurveEncoding]
+    pub fn encode_processed(&self, encoding: CurveEncoding) -> Result<Vec<u8>, CurveProcessError> {
+        match encoding {
+            CurveEncoding::Ron => Ok(serialize_curve_ron(self, RonStyle::Compact)?),
+            CurveEncoding::Binary => {
+                let mut bytes = BINARY_MAGIC.to_vec();
+                bytes.push(BINARY_VERSION);
+                Ok(postcard::to_extend(&BinaryCurve::from(self), bytes)?)
+            }
+        }
+    }
+
+    /// Loads a curve from a source asset, applies the rules of `settings` and encodes it.
+    pub fn process_bytes(
+        bytes: &[u8],
+        settings: &CurveProcessSettings,
+    ) -> Result<Vec<u8>, CurveProcessError> {
+        let mut curve = LookupCurve::from_asset_bytes(bytes, "")?;
+        curve.apply_process_rules(settings)?;
+        curve.encode_processed(settings.encoding)
+    }
+
+    /// Deserializes a lookup curve from RON, or from the binary encoding of processed curves.
+    /// `path` is only used for diagnostics.
+    pub(crate) fn from_asset_bytes(bytes: &[u8], path: &str) -> Result<Self, LookupCurveLoadError> {
+        let Some(encoded) = bytes.strip_prefix(BINARY_MAGIC) else {
+            return Ok(LookupCurve::from_text_bytes(bytes, path, CurveFormat::Ron)?);
+        };
+        match encoded.split_first() {
+            Some((&BINARY_VERSION, encoded)) => {
+                Ok(postcard::from_bytes::<BinaryCurve>(encoded)?.into())
+            }
+            _ => Err(LookupCurveLoadError::UnsupportedBinaryVersion),
+        }
+    }
+}
+
+/// Binary encoding of a [LookupCurve].
+///
+/// Separate from the RON representation, since [postcard] does not support the skipped fields of [LookupCurve]
+/// and [Knot]. Contains the same fields as are stored in RON.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BinaryCurve {
+    knots: Vec<BinaryKnot>,
+    name: Option<String>,
+    x_unit: Option<UnitDisplay>,
+    y_unit: Option<UnitDisplay>,
+    anchors: Vec<Vec2>,
+    strict_monotonic_x: bool,
+    extrapolate_before: Extrapolation,
+    extrapolate_after: Extrapolation,
+    track_edit_times: bool,
+    edit_clock: EditClock,
+    time_warp: bool,
+    quantize_storage: Option<QuantizeRule>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BinaryKnot {
+    position: Vec2,
+    interpolation: KnotInterpolation,
+    left_tangent: Tangent,
+    right_tangent: Tangent,
+    created_at: Option<u64>,
+    modified_at: Option<u64>,
+}
+
+impl From<&LookupCurve> for BinaryCurve {
+    fn from(curve: &LookupCurve) -> Self {
+        Self {
+            knots: curve
+                .knots
+                .iter()
+                .map(|knot| BinaryKnot {
+                    position: knot.position,
+                    interpolation: knot.interpolation,
+                    left_tangent: knot.left_tangent,
+                    right_tangent: knot.right_tangent,
+                    created_at: knot.created_at,
+                    modified_at: knot.modified_at,
+                })
+                .collect(),
+            name: curve.name.clone(),
+            x_unit: curve.x_unit.clone(),
+            y_unit: curve.y_unit.clone(),
+            anchors: curve.anchors.clone(),
+            strict_monotonic_x: curve.strict_monotonic_x,
+            extrapolate_before: curve.extrapolate_before,
+            extrapolate_after: curve.extrapolate_after,
+            track_edit_times: curve.track_edit_times,
+            edit_clock: curve.edit_clock,
+            time_warp: curve.time_warp,
+            quantize_storage: curve.quantize_storage,
+        }
+    }
+}
+
+impl From<BinaryCurve> for LookupCurve {
+    fn from(binary: BinaryCurve) -> Self {
+        // Knots are in order of x, as they were encoded from a curve
+        let knots: Vec<Knot> = binary
+            .knots
+            .into_iter()
+            .map(|knot| Knot {
+                position: knot.position,
+                interpolation: knot.interpolation,
+                left_tangent: knot.left_tangent,
+                right_tangent: knot.right_tangent,
+                created_at: knot.created_at,
+                modified_at: knot.modified_at,
+                ..Default::default()
+            })
+            .collect();
+        LookupCurve {
+            knots: knots.into(),
+            name: binary.name,
+            x_unit: binary.x_unit,
+            y_unit: binary.y_unit,
+            anchors: binary.anchors,
+            strict_monotonic_x: binary.strict_monotonic_x,
+            extrapolate_before: binary.extrapolate_before,
+            extrapolate_after: binary.extrapolate_after,
+            track_edit_times: binary.track_edit_times,
+            edit_clock: binary.edit_clock,
+            time_warp: binary.time_warp,
+            quantize_storage: binary.quantize_storage,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = include_bytes!("../assets/example.curve.ron");
+
+    /// Canonical RON of the curve, for comparing curves
+    fn canonical(curve: &LookupCurve) -> String {
+        ron::ser::to_string(curve).unwrap()
+    }
+
+    fn settings(encoding: CurveEncoding) -> CurveProcessSettings {
+        CurveProcessSettings {
+            encoding,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn processed_curves_round_trip() {
+        let source = LookupCurve::from_asset_bytes(FIXTURE, "").unwrap();
+        for encoding in [CurveEncoding::Ron, CurveEncoding::Binary] {
+            let processed = LookupCurve::process_bytes(FIXTURE, &settings(encoding)).unwrap();
+            let loaded = LookupCurve::from_asset_bytes(&processed, "").unwrap();
+            assert_eq!(canonical(&loaded), canonical(&source), "{encoding:?}");
+            assert_eq!(loaded.lookup(0.4), source.lookup(0.4));
+
+            // Processing a processed curve does not change it
+            let reprocessed = LookupCurve::process_bytes(&processed, &settings(encoding)).unwrap();
+            assert_eq!(reprocessed, processed);
+        }
+        let binary = LookupCurve::process_bytes(FIXTURE, &settings(CurveEncoding::Binary)).unwrap();
+        assert!(binary.len() < FIXTURE.len() / 4);
+    }
+
+    #[test]
+    fn all_fields_are_encoded() {
+        let mut curve = LookupCurve::new(vec![
+            Knot {
+                position: Vec2::new(0.0, 1.0),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::new(2.0, 3.0),
+                ..Default::default()
+            },
+        ])
+        .with_name("curve")
+        .with_x_unit(UnitDisplay::new("ms").with_scale(1000.0).with_precision(0))
+        .with_anchors(vec![Vec2::new(2.0, 3.0)])
+        .with_edit_times(EditClock::UnixTime)
+        .with_extrapolation(Extrapolation::Linear, Extrapolation::PingPong);
+        curve.strict_monotonic_x = true;
+        let knot = curve.knots()[0];
+        curve.modify_knot(
+            0,
+            knot.with_tangent_weight(crate::TangentSide::Right, Some(0.5)),
+        );
+
+        let binary = curve.encode_processed(CurveEncoding::Binary).unwrap();
+        let loaded = LookupCurve::from_asset_bytes(&binary, "").unwrap();
+        assert_eq!(canonical(&loaded), canonical(&curve));
+    }
+
+    #[test]
+    fn rules() {
+        let mut source = LookupCurve::from_asset_bytes(FIXTURE, "").unwrap();
+        source.anchors.push(Vec2::new(0.5, 10.0));
+        let knot = source.knots()[0];
+        source.modify_knot(
+            0,
+            knot.with_tangent_weight(crate::TangentSide::Right, Some(1.0)),
+        );
+        // Beyond the anchor, so that it does not affect the other rules
+        let mut not_finite = Knot {
+            position: Vec2::new(5.0, 0.0),
+            ..Default::default()
+        };
+        not_finite.left_tangent.slope = f32::NAN;
+        source.add_knot(not_finite);
+        let bytes = ron::ser::to_string(&source).unwrap().into_bytes();
+
+        // Ignored by default
+        let processed =
+            LookupCurve::process_bytes(&bytes, &CurveProcessSettings::default()).unwrap();
+        let ignored = LookupCurve::from_asset_bytes(&processed, "").unwrap();
+        assert_eq!(ignored.knots().len(), source.knots().len());
+
+        let fail = |rule: fn(&mut CurveProcessSettings)| {
+            let mut settings = CurveProcessSettings::default();
+            rule(&mut settings);
+            LookupCurve::process_bytes(&bytes, &settings).unwrap_err()
+        };
+        assert!(matches!(
+            fail(|s| s.finite_knots = RuleAction::Fail),
+            CurveProcessError::NotFinite(1)
+        ));
+        assert!(matches!(
+            fail(|s| s.monotonic_x = RuleAction::Fail),
+            CurveProcessError::NotMonotonicX(1)
+        ));
+        assert!(matches!(
+            fail(|s| s.anchors = RuleAction::Fail),
+            CurveProcessError::AnchorViolations(1)
+        ));
+
+        let repair = CurveProcessSettings {
+            anchors: RuleAction::Repair,
+            monotonic_x: RuleAction::Repair,
+            finite_knots: RuleAction::Repair,
+            encoding: CurveEncoding::Binary,
+        };
+        let processed = LookupCurve::process_bytes(&bytes, &repair).unwrap();
+        let repaired = LookupCurve::from_asset_bytes(&processed, "").unwrap();
+        assert!(repaired.knots().iter().all(Knot::is_finite));
+        assert!(repaired.is_monotonic_x());
+        assert!(repaired.validate_anchors().is_empty());
+    }
+
+    #[test]
+    fn unsupported_binary_version() {
+        let mut binary = LookupCurve::default()
+            .encode_processed(CurveEncoding::Binary)
+            .unwrap();
+        binary[BINARY_MAGIC.len()] = BINARY_VERSION + 1;
+        assert!(matches!(
+            LookupCurve::from_asset_bytes(&binary, ""),
+            Err(LookupCurveLoadError::UnsupportedBinaryVersion)
+        ));
+    }
+
+    #[test]
+    fn loader_and_parse_curve_ron_agree() {
+        let loaded = LookupCurve::from_asset_bytes(FIXTURE, "").unwrap();
+        let parsed = crate::parse_curve_ron(FIXTURE).unwrap();
+        assert_eq!(canonical(&loaded), canonical(&parsed));
+
+        let truncated = &FIXTURE[..FIXTURE.len() / 2];
+        let (Err(LookupCurveLoadError::Parse(loaded)), Err(parsed)) = (
+            LookupCurve::from_asset_bytes(truncated, ""),
+            crate::parse_curve_ron(truncated),
+        ) else {
+            panic!("expected parse errors");
+        };
+        assert_eq!(loaded.to_string(), parsed.to_string());
+    }
+}
@@ -0,0 +1,303 @@
+//! Summaries of known curves for listing, filtering and sorting them, kept up to date using
+//! [LookupCurve::generation]
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use bevy_math::Vec2;
+
+use crate::stats::CurveStats;
+use crate::{LookupCache, LookupCurve};
+
+#[cfg(feature = "bevy_asset")]
+mod registry_bevy;
+#[cfg(feature = "bevy_asset")]
+pub use registry_bevy::*;
+
+/// Number of samples in [CurveSummary::thumbnail]
+pub const THUMBNAIL_SAMPLES: usize = 32;
+
+/// Cached description of a curve, recomputed when the generation of the curve changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurveSummary {
+    pub name: Option<String>,
+    pub stats: CurveStats,
+    /// Points sampled evenly over the domain of the curve, empty if the curve has less than two knots
+    pub thumbnail: Vec<Vec2>,
+    /// Generation of the curve the summary was computed from
+    pub generation: u64,
+    /// Increases every time a summary in the registry is recomputed. Unlike generations of different curves,
+    /// revisions can be compared to find the most recently modified curve.
+    pub revision: u64,
+}
+
+impl CurveSummary {
+    fn compute(curve: &LookupCurve, revision: u64) -> Self {
+        let stats = CurveStats::compute(curve);
+        let thumbnail = match stats.domain {
+            Some((min_x, max_x)) if curve.knots().len() >= 2 => {
+                let mut cache = LookupCache::new();
+                (0..THUMBNAIL_SAMPLES)
+                    .map(|i| {
+                        let x = min_x + (max_x - min_x) * i as f32 / (THUMBNAIL_SAMPLES - 1) as f32;
+                        Vec2::new(x, curve.lookup_cached(x, &mut cache))
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        Self {
+            name: curve.name.clone(),
+            stats,
+            thumbnail,
+            generation: curve.generation(),
+            revision,
+        }
+    }
+
+    /// The name of the curve, or a placeholder for unnamed curves
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("Unnamed lookup curve")
+    }
+
+    /// Width of the domain, 0 if the curve has no knots
+    pub fn domain_width(&self) -> f32 {
+        self.stats.domain.map_or(0.0, |(min, max)| max - min)
+    }
+
+    /// Whether the name contains `filter`, ignoring case. An empty filter matches every curve.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim();
+        filter.is_empty()
+            || self
+                .display_name()
+                .to_lowercase()
+                .contains(&filter.to_lowercase())
+    }
+}
+
+/// Property to sort [CurveRegistry::rows] by
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum CurveSort {
+    #[default]
+    Name,
+    KnotCount,
+    DomainWidth,
+    /// Most recently modified first
+    LastModified,
+}
+
+impl CurveSort {
+    pub const ALL: [CurveSort; 4] = [
+        CurveSort::Name,
+        CurveSort::KnotCount,
+        CurveSort::DomainWidth,
+        CurveSort::LastModified,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CurveSort::Name => "Name",
+            CurveSort::KnotCount => "Knot count",
+            CurveSort::DomainWidth => "Domain width",
+            CurveSort::LastModified => "Last modified",
+        }
+    }
+
+    fn compare(&self, a: &CurveSummary, b: &CurveSummary) -> Ordering {
+        match self {
+            CurveSort::Name => a
+                .display_name()
+                .to_lowercase()
+                .cmp(&b.display_name().to_lowercase()),
+            CurveSort::KnotCount => a.stats.knot_count.cmp(&b.stats.knot_count),
+            CurveSort::DomainWidth => a.domain_width().total_cmp(&b.domain_width()),
+            CurveSort::LastModified => b.revision.cmp(&a.revision),
+        }
+    }
+}
+
+/// Summaries of known curves by key, only recomputed when a curve changed
+#[derive(Clone, Debug)]
+pub struct CurveRegistry<K> {
+    summaries: HashMap<K, CurveSummary>,
+    revision: u64,
+}
+
+impl<K> Default for CurveRegistry<K> {
+    fn default() -> Self {
+        Self {
+            summaries: HashMap::new(),
+            revision: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq> CurveRegistry<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the summary of the curve with `key`, if the generation of `curve` differs from the summarized one.
+    /// Returns `true` if the summary was recomputed.
+    ///
+    /// Changes to the public fields of the curve, like the name, do not change its generation and are only picked
+    /// up with the next change that does.
+    pub fn update(&mut self, key: K, curve: &LookupCurve) -> bool {
+        if self
+            .summaries
+            .get(&key)
+            .is_some_and(|summary| summary.generation == curve.generation())
+        {
+            return false;
+        }
+        self.revision += 1;
+        self.summaries
+            .insert(key, CurveSummary::compute(curve, self.revision));
+        true
+    }
+
+    /// Forgets the curve with `key`, returns its summary
+    pub fn remove(&mut self, key: &K) -> Option<CurveSummary> {
+        self.summaries.remove(key)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&CurveSummary> {
+        self.summaries.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.summaries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.summaries.is_empty()
+    }
+
+    /// Summaries matching `filter` (see [CurveSummary::matches]), sorted by `sort`. Ties keep the most recently
+    /// modified curve first, so the order is stable between frames.
+    pub fn rows(
+        &self,
+        filter: &str,
+        sort: CurveSort,
+        descending: bool,
+    ) -> Vec<(&K, &CurveSummary)> {
+        let mut rows: Vec<_> = self
+            .summaries
+            .iter()
+            .filter(|(_, summary)| summary.matches(filter))
+            .collect();
+        rows.sort_by(|(_, a), (_, b)| {
+            let order = if descending {
+                sort.compare(b, a)
+            } else {
+                sort.compare(a, b)
+            };
+            order.then_with(|| b.revision.cmp(&a.revision))
+        });
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, KnotInterpolation};
+
+    fn curve(name: &str, xs: &[f32]) -> LookupCurve {
+        LookupCurve::new(
+            xs.iter()
+                .map(|&x| Knot {
+                    position: Vec2::new(x, x * 2.0),
+                    interpolation: KnotInterpolation::Linear,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+        .with_name(name)
+    }
+
+    fn names<'a>(rows: &[(&u32, &'a CurveSummary)]) -> Vec<&'a str> {
+        rows.iter()
+            .map(|(_, summary)| summary.display_name())
+            .collect()
+    }
+
+    #[test]
+    fn summaries_are_only_recomputed_on_new_generations() {
+        let mut registry = CurveRegistry::new();
+        let mut speed = curve("speed", &[0.0, 1.0]);
+        assert!(registry.update(0, &speed));
+        assert!(!registry.update(0, &speed));
+        let revision = registry.get(&0).unwrap().revision;
+
+        speed.add_knot(Knot {
+            position: Vec2::new(3.0, 0.0),
+            ..Default::default()
+        });
+        assert!(registry.update(0, &speed));
+        let summary = registry.get(&0).unwrap();
+        assert!(summary.revision > revision);
+        assert_eq!(summary.stats.knot_count, 3);
+        assert_eq!(summary.stats.domain, Some((0.0, 3.0)));
+        assert_eq!(summary.thumbnail.len(), THUMBNAIL_SAMPLES);
+        assert_eq!(summary.thumbnail[0], Vec2::ZERO);
+        assert_eq!(summary.thumbnail[THUMBNAIL_SAMPLES - 1].x, 3.0);
+
+        assert!(registry.remove(&0).is_some());
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn empty_curves_have_no_thumbnail() {
+        let mut registry = CurveRegistry::new();
+        registry.update(0, &LookupCurve::new(vec![]));
+        registry.update(1, &curve("single", &[1.0]));
+        for key in [0, 1] {
+            assert!(registry.get(&key).unwrap().thumbnail.is_empty());
+        }
+        assert_eq!(registry.get(&0).unwrap().domain_width(), 0.0);
+    }
+
+    #[test]
+    fn rows_are_filtered_and_sorted() {
+        let mut registry = CurveRegistry::new();
+        registry.update(0, &curve("Enemy speed", &[0.0, 1.0, 2.0]));
+        registry.update(1, &curve("player Speed", &[0.0, 4.0]));
+        registry.update(2, &curve("Fade", &[0.0, 0.5, 1.0, 1.5]));
+
+        let rows = registry.rows("", CurveSort::Name, false);
+        assert_eq!(names(&rows), ["Enemy speed", "Fade", "player Speed"]);
+        let rows = registry.rows(" SPEED", CurveSort::Name, true);
+        assert_eq!(names(&rows), ["player Speed", "Enemy speed"]);
+        let rows = registry.rows("", CurveSort::KnotCount, true);
+        assert_eq!(names(&rows), ["Fade", "Enemy speed", "player Speed"]);
+        let rows = registry.rows("", CurveSort::DomainWidth, false);
+        assert_eq!(names(&rows), ["Fade", "Enemy speed", "player Speed"]);
+        assert!(registry.rows("slow", CurveSort::Name, false).is_empty());
+    }
+
+    #[test]
+    fn last_modified_lists_recent_changes_first() {
+        let mut registry = CurveRegistry::new();
+        let mut curves: Vec<_> = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| curve(name, &[0.0, 1.0]))
+            .collect();
+        for (key, curve) in curves.iter().enumerate() {
+            registry.update(key as u32, curve);
+        }
+        curves[0].delete_knot(0);
+        for (key, curve) in curves.iter().enumerate() {
+            registry.update(key as u32, curve);
+        }
+
+        let rows = registry.rows("", CurveSort::LastModified, false);
+        assert_eq!(names(&rows), ["a", "c", "b"]);
+        // Equal knot counts fall back to the most recent change
+        let rows = registry.rows("", CurveSort::KnotCount, false);
+        assert_eq!(names(&rows), ["a", "c", "b"]);
+    }
+}
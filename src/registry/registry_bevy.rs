@@ -0,0 +1,87 @@
+use std::ops::Deref;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{AssetEvent, AssetId, Assets};
+use bevy_ecs::prelude::{EventReader, Res, ResMut, Resource};
+
+use super::CurveRegistry;
+use crate::LookupCurve;
+
+pub(crate) struct RegistryPlugin;
+
+impl Plugin for RegistryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurveAssetRegistry>()
+            .add_systems(PostUpdate, update_curve_asset_registry);
+    }
+}
+
+/// Summaries of all loaded curve assets, updated by [update_curve_asset_registry] from asset events
+#[derive(Resource, Default, Debug)]
+pub struct CurveAssetRegistry(CurveRegistry<AssetId<LookupCurve>>);
+
+impl Deref for CurveAssetRegistry {
+    type Target = CurveRegistry<AssetId<LookupCurve>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Updates the summaries of added and modified curve assets, and forgets removed ones
+pub fn update_curve_asset_registry(
+    mut registry: ResMut<CurveAssetRegistry>,
+    mut events: EventReader<AssetEvent<LookupCurve>>,
+    curves: Res<Assets<LookupCurve>>,
+) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                if let Some(curve) = curves.get(id) {
+                    registry.0.update(id, curve);
+                }
+            }
+            AssetEvent::Removed { id } => {
+                registry.0.remove(&id);
+            }
+            AssetEvent::Unused { .. } | AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+    use bevy::prelude::{AssetApp, AssetPlugin, MinimalPlugins};
+
+    #[test]
+    fn registry_follows_asset_events() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<LookupCurve>()
+            .add_plugins(RegistryPlugin);
+
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<LookupCurve>>()
+            .add(LookupCurve::default().with_name("speed"));
+        // Asset events are sent in `Last`, and read in `PostUpdate` of the next frame
+        app.update();
+        app.update();
+        let registry = app.world().resource::<CurveAssetRegistry>();
+        assert_eq!(registry.get(&handle.id()).unwrap().display_name(), "speed");
+
+        let mut curves = app.world_mut().resource_mut::<Assets<LookupCurve>>();
+        curves.get_mut(&handle).unwrap().add_knot(Knot::default());
+        app.update();
+        app.update();
+        let registry = app.world().resource::<CurveAssetRegistry>();
+        assert_eq!(registry.get(&handle.id()).unwrap().stats.knot_count, 1);
+
+        drop(handle);
+        app.update();
+        app.update();
+        assert!(app.world().resource::<CurveAssetRegistry>().is_empty());
+    }
+}
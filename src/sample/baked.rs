@@ -0,0 +1,256 @@
+use crate::{Extrapolator, LookupCurve, Outside};
+
+/// Points checked between each pair of samples when measuring the error of a [BakedLookupCurve]
+const ERROR_CHECKS: usize = 3;
+
+/// A [LookupCurve] sampled at evenly spaced x over its knot range, interpolated linearly between the samples.
+/// Created with [LookupCurve::bake].
+///
+/// Sampling takes the same time for every curve, but is only as accurate as the resolution allows, see
+/// [LookupCurve::bake_error]. Jumps of the curve, like at constant segments, are smoothed over one sample step.
+#[derive(Clone, Debug)]
+pub struct BakedLookupCurve {
+    pub(super) min_x: f32,
+    pub(super) max_x: f32,
+    ys: Vec<f32>,
+    extrapolator: Extrapolator,
+    /// [LookupCurve::generation] of the curve the samples were taken from
+    generation: u64,
+}
+
+impl BakedLookupCurve {
+    /// Find y for given x. Like [LookupCurve::lookup], values outside of the knot range are extrapolated with the
+    /// settings of the curve.
+    #[inline]
+    pub fn sample(&self, x: f32) -> f32 {
+        let x = match self.extrapolator.outside(x) {
+            Some(Outside::Value { y, .. }) => return y,
+            Some(Outside::Mapped { x, .. }) => x,
+            None => x,
+        };
+        let last = self.ys.len() - 1;
+        if last == 0 || x <= self.min_x || x.is_nan() {
+            return self.ys[0];
+        }
+        if x >= self.max_x {
+            return self.ys[last];
+        }
+        let t = (x - self.min_x) / (self.max_x - self.min_x) * last as f32;
+        let i = (t as usize).min(last - 1);
+        let s = t - i as f32;
+        self.ys[i] + (self.ys[i + 1] - self.ys[i]) * s
+    }
+
+    /// Brings the samples up to date with `curve`, which they were taken from at an earlier generation.
+    ///
+    /// Only the samples within the segments changed since then are taken again, see
+    /// [LookupCurve::edit_effect_since]. Edits that change the range of the knots take all samples again. Returns
+    /// the number of samples taken.
+    pub fn update(&mut self, curve: &LookupCurve) -> usize {
+        #[cfg(feature = "trace")]
+        let span = tracing::info_span!(
+            "lookup_curve_cache_update",
+            segments = curve.knots.len().saturating_sub(1),
+            rebuilt = tracing::field::Empty
+        )
+        .entered();
+
+        let rebuilt = self.update_samples(curve);
+
+        #[cfg(feature = "trace")]
+        span.record("rebuilt", rebuilt);
+
+        rebuilt
+    }
+
+    fn update_samples(&mut self, curve: &LookupCurve) -> usize {
+        let effect = curve.edit_effect_since(self.generation);
+        let knots = &curve.knots;
+        let range = (knots.first().zip(knots.last()))
+            .map(|(first, last)| (first.position.x, last.position.x));
+        if effect.reordered || range != Some((self.min_x, self.max_x)) {
+            *self = curve.bake(self.resolution());
+            return self.resolution();
+        }
+        let mut taken = 0;
+        if !effect.dirty_segments.is_empty() {
+            let start = knots[effect.dirty_segments.start].position.x;
+            let end = knots[effect.dirty_segments.end].position.x;
+            let steps = (self.ys.len() - 1) as f32;
+            let index = |x: f32| (x - self.min_x) / (self.max_x - self.min_x) * steps;
+            // One more sample on each side, in case of rounding
+            let from = (index(start).floor() as usize).saturating_sub(1);
+            let to = (index(end).ceil() as usize + 1).min(self.ys.len() - 1);
+            for i in from..=to {
+                let x = sample_x(self.min_x, self.max_x, i, self.ys.len());
+                if (start..=end).contains(&x) {
+                    self.ys[i] = curve.lookup(x);
+                    taken += 1;
+                }
+            }
+        }
+        self.extrapolator = curve.extrapolator();
+        self.generation = curve.generation;
+        taken
+    }
+
+    /// Number of samples
+    pub fn resolution(&self) -> usize {
+        self.ys.len()
+    }
+
+    /// Size of the samples in bytes
+    pub fn memory(&self) -> usize {
+        self.ys.len() * std::mem::size_of::<f32>()
+    }
+}
+
+impl LookupCurve {
+    /// Samples the curve at `resolution` evenly spaced x from the first to the last knot, at least 2, see
+    /// [BakedLookupCurve]
+    pub fn bake(&self, resolution: usize) -> BakedLookupCurve {
+        #[cfg(feature = "trace")]
+        let _span = tracing::info_span!(
+            "lookup_curve_cache_update",
+            segments = self.knots.len().saturating_sub(1),
+            resolution
+        )
+        .entered();
+        let (min_x, max_x) = match (self.knots.first(), self.knots.last()) {
+            (Some(first), Some(last)) => (first.position.x, last.position.x),
+            _ => (0.0, 0.0),
+        };
+        let resolution = resolution.max(2);
+        let ys = (0..resolution)
+            .map(|i| self.lookup(sample_x(min_x, max_x, i, resolution)))
+            .collect();
+        BakedLookupCurve {
+            min_x,
+            max_x,
+            ys,
+            extrapolator: self.extrapolator(),
+            generation: self.generation,
+        }
+    }
+
+    /// Largest difference in y between the curve and the curve baked with `resolution`, checked at a few points
+    /// between each pair of samples, and at the knots and the middle of each segment
+    pub fn bake_error(&self, resolution: usize) -> f32 {
+        let baked = self.bake(resolution);
+        let steps = baked.resolution() - 1;
+        let width = baked.max_x - baked.min_x;
+        let between_samples = (0..steps).flat_map(|i| {
+            (1..=ERROR_CHECKS).map(move |k| {
+                let t = i as f32 + k as f32 / (ERROR_CHECKS + 1) as f32;
+                baked.min_x + width * t / steps as f32
+            })
+        });
+        // Features narrower than a step can fall between the points above
+        let at_knots = self.knots.windows(2).flat_map(|pair| {
+            let (a, b) = (pair[0].position.x, pair[1].position.x);
+            [a, 0.5 * (a + b), b]
+        });
+        between_samples
+            .chain(at_knots)
+            .map(|x| (self.lookup(x) - baked.sample(x)).abs())
+            .fold(0.0, f32::max)
+    }
+}
+
+/// x of the sample `i` of `resolution` samples from `min_x` to `max_x`
+fn sample_x(min_x: f32, max_x: f32, i: usize, resolution: usize) -> f32 {
+    if i == resolution - 1 {
+        // Avoid rounding errors at the end, to arrive exactly at the last knot
+        max_x
+    } else {
+        min_x + (max_x - min_x) * i as f32 / (resolution - 1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, KnotInterpolation};
+    use bevy_math::Vec2;
+
+    #[test]
+    fn baked_samples_match_the_curve_at_the_samples() {
+        let curve = LookupCurve::new(vec![
+            Knot {
+                position: Vec2::new(-1.0, 2.0),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::new(3.0, -1.0),
+                ..Default::default()
+            },
+        ]);
+        let baked = curve.bake(9);
+        assert_eq!(baked.resolution(), 9);
+        assert_eq!(baked.memory(), 36);
+        for i in 0..9 {
+            let x = -1.0 + i as f32 * 0.5;
+            assert!((baked.sample(x) - curve.lookup(x)).abs() < 1e-6);
+        }
+        assert_eq!(baked.sample(-5.0), 2.0);
+        assert_eq!(baked.sample(5.0), -1.0);
+
+        assert_eq!(LookupCurve::default().bake(4).sample(1.0), 0.0);
+        let single = LookupCurve::new(vec![Knot {
+            position: Vec2::new(1.0, 3.0),
+            ..Default::default()
+        }]);
+        assert_eq!(single.bake(4).sample(7.0), 3.0);
+        assert_eq!(single.bake_error(4), 0.0);
+    }
+
+    #[test]
+    fn updates_take_the_samples_of_changed_segments() {
+        let knots = (0..20)
+            .map(|i| Knot {
+                position: Vec2::new(i as f32, (i * i % 7) as f32),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            })
+            .collect();
+        let mut curve = LookupCurve::new(knots);
+        // 10 samples per segment
+        let mut baked = curve.bake(191);
+        assert_eq!(baked.update(&curve), 0);
+
+        let knot = curve.knots()[8];
+        curve.modify_knot(
+            8,
+            Knot {
+                position: knot.position + Vec2::Y,
+                ..knot
+            },
+        );
+        // Both segments, with the samples on the knots around them
+        assert_eq!(baked.update(&curve), 21);
+        assert_eq!(baked.ys, curve.bake(191).ys);
+
+        // Knots passing each other, and a changed range of the knots
+        let knot = curve.knots()[3];
+        curve.modify_knot(
+            3,
+            Knot {
+                position: knot.position + Vec2::X * 1.5,
+                ..knot
+            },
+        );
+        assert_eq!(baked.update(&curve), 191);
+        assert_eq!(baked.ys, curve.bake(191).ys);
+        let knot = curve.knots()[19];
+        curve.modify_knot(
+            19,
+            Knot {
+                position: knot.position + Vec2::X,
+                ..knot
+            },
+        );
+        assert_eq!(baked.update(&curve), 191);
+        assert_eq!(baked.ys, curve.bake(191).ys);
+    }
+}
@@ -0,0 +1,156 @@
+use super::precomputed::Segment;
+use super::KnotSearch;
+use crate::{LookupCurve, Outside};
+
+impl LookupCurve {
+    /// Fills `out` with y at `start_x`, `start_x + dx_per_sample`, `start_x + 2 * dx_per_sample` and so on, without
+    /// allocating. Made for sequential sampling, like filling a buffer of an audio parameter.
+    ///
+    /// The segment of a sample is only searched when x leaves the segment of the previous sample, its coefficients
+    /// are reused until then. Weighted cubic segments start the solve of a sample from the t of the previous sample.
+    /// The results match [LookupCurve::lookup] of each x within the tolerance of the solve, see
+    /// [LookupCurve::max_error].
+    ///
+    /// x is computed from the index of each sample, so rounding errors do not add up over long buffers. Like
+    /// [LookupCurve::lookup], samples outside of the knot range are extrapolated with the settings of the curve. A
+    /// `dx_per_sample` of 0 fills `out` with y at `start_x`, and a negative `dx_per_sample` samples in decreasing x.
+    pub fn fill_buffer(&self, start_x: f32, dx_per_sample: f32, out: &mut [f32]) {
+        #[cfg(feature = "trace")]
+        let _span = tracing::info_span!("lookup_curve_fill_buffer", samples = out.len()).entered();
+        let knots = self.knots();
+        if knots.len() < 2 || dx_per_sample == 0.0 {
+            out.fill(self.lookup(start_x));
+            return;
+        }
+        let (first, last) = (knots[0].position, knots[knots.len() - 1].position);
+        let extrapolator = self.extrapolator();
+
+        // Index of the knot starting the segment of the previous sample, and the segment
+        let mut active: Option<(usize, Segment)> = None;
+        let mut t = 0.0;
+        let mut previous_x = None;
+        for (n, y) in out.iter_mut().enumerate() {
+            let x = start_x + dx_per_sample * n as f32;
+            let x = match extrapolator.outside(x) {
+                Some(Outside::Value { y: value, .. }) => {
+                    *y = value;
+                    continue;
+                }
+                Some(Outside::Mapped { x, .. }) => x,
+                None => x,
+            };
+            // Repeating jumps back over the knot range, where the t of the previous sample is a bad start
+            let jumped = previous_x
+                .replace(x)
+                .is_some_and(|previous: f32| (x - previous).abs() > 2.0 * dx_per_sample.abs());
+            if x <= first.x || x.is_nan() {
+                *y = first.y;
+                continue;
+            }
+            if x >= last.x {
+                *y = last.y;
+                continue;
+            }
+            // Segments include their end, like in KnotSearch::search_knots
+            let in_active = !jumped
+                && active
+                    .as_ref()
+                    .is_some_and(|&(i, _)| knots[i].position.x < x && x <= knots[i + 1].position.x);
+            let (_, segment) = if in_active {
+                active.as_ref().unwrap()
+            } else {
+                let i = knots.search_knots(x);
+                let (a, b) = (&knots[i], &knots[i + 1]);
+                // Exact for segments that are linear in t
+                t = (x - a.position.x) / (b.position.x - a.position.x);
+                &*active.insert((i, Segment::new(a, b)))
+            };
+            *y = segment.y_from(x, &mut t, self.max_error, self.max_iters);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Knot, KnotInterpolation, LookupCurve, TangentSide};
+    use bevy_math::Vec2;
+
+    fn curve() -> LookupCurve {
+        let knot = |x: f32, y: f32, interpolation| {
+            Knot {
+                position: Vec2::new(x, y),
+                interpolation,
+                ..Default::default()
+            }
+            .with_tangent_slope(TangentSide::Left, 2.0)
+            .with_tangent_slope(TangentSide::Right, -1.0)
+        };
+        LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Constant),
+            knot(0.1, 1.0, KnotInterpolation::Linear),
+            knot(0.3, -0.5, KnotInterpolation::Cubic),
+            knot(0.5, 0.5, KnotInterpolation::Cubic)
+                .with_tangent_weight(TangentSide::Right, Some(0.9)),
+            knot(0.8, 2.0, KnotInterpolation::Cubic)
+                .with_tangent_weight(TangentSide::Left, Some(0.1)),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+        ])
+    }
+
+    /// Fills a buffer and compares it to looking up each sample
+    fn assert_matches_lookup(curve: &LookupCurve, start_x: f32, dx: f32, len: usize) {
+        let mut out = vec![f32::NAN; len];
+        curve.fill_buffer(start_x, dx, &mut out);
+        for (n, y) in out.into_iter().enumerate() {
+            let x = start_x + dx * n as f32;
+            let expected = curve.lookup(x);
+            // Both solves stop within max_error of x, which is a larger error in y where the curve is steep
+            let slope = [TangentSide::Left, TangentSide::Right]
+                .map(|side| curve.slope_at(x, side).abs())
+                .into_iter()
+                .fold(0.0, f32::max);
+            let tolerance = 1e-5 + 2.0 * curve.max_error * slope;
+            assert!(
+                (y - expected).abs() <= tolerance,
+                "sample {n} at {x}: {y} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn buffers_spanning_segments_match_lookup() {
+        let curve = curve();
+        // All segments, with samples exactly on knots
+        assert_matches_lookup(&curve, 0.0, 0.05, 21);
+        assert_matches_lookup(&curve, 0.0, 1.0 / 512.0, 512);
+        // Several short buffers in a row, like audio blocks
+        for block in 0..8 {
+            assert_matches_lookup(&curve, block as f32 * 0.125, 0.125 / 64.0, 64);
+        }
+    }
+
+    #[test]
+    fn buffers_starting_outside_of_the_knot_range() {
+        let curve = curve();
+        assert_matches_lookup(&curve, -0.5, 0.01, 200);
+        let mut out = [0.0; 4];
+        curve.fill_buffer(2.0, 0.5, &mut out);
+        assert_eq!(out, [1.0; 4]);
+        curve.fill_buffer(-2.0, 0.5, &mut out);
+        assert_eq!(out, [0.0; 4]);
+    }
+
+    #[test]
+    fn zero_and_negative_steps() {
+        let curve = curve();
+        let mut out = [f32::NAN; 8];
+        curve.fill_buffer(0.6, 0.0, &mut out);
+        assert_eq!(out, [curve.lookup(0.6); 8]);
+        // Backwards through all segments
+        assert_matches_lookup(&curve, 1.2, -0.01, 140);
+
+        LookupCurve::default().fill_buffer(0.5, 0.1, &mut out);
+        assert_eq!(out, [0.0; 8]);
+        curve.fill_buffer(0.5, 0.1, &mut []);
+    }
+}
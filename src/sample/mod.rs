@@ -0,0 +1,685 @@
+//! Sampling a [LookupCurve], and finding the knots around a sample
+
+use bevy_math::Vec2;
+
+mod baked;
+pub use baked::BakedLookupCurve;
+
+mod knot_search;
+pub use knot_search::KnotSearch;
+
+mod fill;
+
+mod precomputed;
+pub use precomputed::PrecomputedLookupCurve;
+
+mod predict;
+pub use predict::{CurvePredictor, PredictedSample};
+
+mod sample_curve;
+pub(crate) use sample_curve::normalized_x;
+pub use sample_curve::{MappedCurve, SampleCurve};
+
+mod recommend;
+pub use recommend::{
+    CurveSampler, SamplingCosts, SamplingParams, SamplingRecommendation, SamplingStrategy,
+    MAX_BAKE_RESOLUTION,
+};
+
+use crate::{Knot, KnotInterpolation, LookupCurve, Outside, TangentSide};
+
+/// Cache to speed up coherent lookups, see [LookupCurve::lookup_cached]
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct LookupCache {
+    last_knot_index: Option<usize>,
+    /// The [LookupCurve::generation] the cache was last used with
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    generation: Option<u64>,
+}
+
+impl LookupCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LookupCurve {
+    /// Find y for given x on the curve
+    ///
+    /// Outside of the knot range the curve continues as set by [LookupCurve::extrapolate_before] and
+    /// [LookupCurve::extrapolate_after]. Empty curves are 0 everywhere. NaN x gives the y of the first knot, see
+    /// [LookupCurve::try_lookup] for detecting it. Knots with the same x are a step to the later knot.
+    ///
+    /// This is deliberately not instrumented with a tracing span (even with the `trace` feature),
+    /// as it is expected to be called in hot loops where the span overhead would dominate.
+    #[inline]
+    pub fn lookup(&self, x: f32) -> f32 {
+        self.lookup_internal(x, None)
+    }
+
+    /// Find y for given x on the curve, like [LookupCurve::lookup]. Returns `None` if x is NaN, or if y is NaN,
+    /// which only happens for curves with knots that are not finite.
+    #[inline]
+    pub fn try_lookup(&self, x: f32) -> Option<f32> {
+        if x.is_nan() {
+            return None;
+        }
+        let y = self.lookup(x);
+        (!y.is_nan()).then_some(y)
+    }
+
+    /// Find y for a normalized `t`, where 0 maps to the first knot and 1 to the last knot of the curve.
+    ///
+    /// Useful when using the curve as easing, without caring about its domain.
+    #[inline]
+    pub fn sample_normalized(&self, t: f32) -> f32 {
+        match self.normalized_x(t) {
+            Some(x) => self.lookup(x),
+            None => 0.0,
+        }
+    }
+
+    /// The x that [Self::sample_normalized] samples for `t`, `None` for curves without knots
+    #[inline]
+    pub fn normalized_x(&self, t: f32) -> Option<f32> {
+        Some(normalized_x(self.domain()?, t))
+    }
+
+    /// Find y for given x on the curve, with a LookupCache. Can speed up coherent lookups, but might slow down random lookups.
+    #[inline]
+    pub fn lookup_cached(&self, x: f32, cache: &mut LookupCache) -> f32 {
+        self.lookup_internal(x, Some(cache))
+    }
+
+    /// Returns the slope (dy/dx) of the curve at `x`.
+    ///
+    /// `side` decides which segment is used when `x` is exactly on a knot, [TangentSide::Left] uses the segment
+    /// ending at `x` and [TangentSide::Right] the segment starting at `x`. Outside of the knot range the slope
+    /// follows [LookupCurve::extrapolate_before] and [LookupCurve::extrapolate_after].
+    pub fn slope_at(&self, x: f32, side: TangentSide) -> f32 {
+        if self.knots.len() < 2 {
+            return 0.0;
+        }
+        match self.extrapolator().outside(x) {
+            Some(Outside::Value { slope, .. }) => return slope,
+            Some(Outside::Mapped { x, mirrored: false }) => return self.slope_at(x, side),
+            Some(Outside::Mapped { x, mirrored: true }) => {
+                let side = match side {
+                    TangentSide::Left => TangentSide::Right,
+                    TangentSide::Right => TangentSide::Left,
+                };
+                return -self.slope_at(x, side);
+            }
+            None => {}
+        }
+        let end = match side {
+            TangentSide::Left => self.knots.partition_point(|k| k.position.x < x),
+            TangentSide::Right => self.knots.partition_point(|k| k.position.x <= x),
+        };
+        if end == 0 || end >= self.knots.len() {
+            // On the first or last knot, looking outside of the knot range
+            let (first, last) = (
+                self.knots[0].position.x,
+                self.knots[self.knots.len() - 1].position.x,
+            );
+            return if end == 0 && x == first {
+                self.slope_past_boundary(false, side)
+            } else if end > 0 && x == last {
+                self.slope_past_boundary(true, side)
+            } else {
+                0.0
+            };
+        }
+        let (knot_a, knot_b) = (&self.knots[end - 1], &self.knots[end]);
+        let dx = knot_b.position.x - knot_a.position.x;
+        if dx == 0.0 {
+            return 0.0;
+        }
+
+        match knot_a.interpolation {
+            KnotInterpolation::Constant | KnotInterpolation::ConstantNext => 0.0,
+            KnotInterpolation::Linear => (knot_b.position.y - knot_a.position.y) / dx,
+            KnotInterpolation::Power { .. } => {
+                let dy = knot_b.position.y - knot_a.position.y;
+                if dy == 0.0 {
+                    return 0.0;
+                }
+                let s = (x - knot_a.position.x) / dx;
+                knot_a.interpolation.fraction_slope(s).unwrap_or(0.0) * dy / dx
+            }
+            KnotInterpolation::Cubic => {
+                if knot_a.right_tangent.weight.is_some() || knot_b.left_tangent.weight.is_some() {
+                    let segment =
+                        CubicSegment::from_bezier_points(knot_a.compute_bezier_to(knot_b));
+                    let velocity =
+                        segment.velocity(segment.find_t_given_x(x, self.max_error, self.max_iters));
+                    if velocity.x == 0.0 {
+                        0.0
+                    } else {
+                        velocity.y / velocity.x
+                    }
+                } else {
+                    unweighted_cubic_slope(knot_a, knot_b, x)
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn lookup_internal(&self, x: f32, cache: Option<&mut LookupCache>) -> f32 {
+        if self.knots.is_empty() {
+            return 0.0;
+        }
+        let (first, last) = (
+            self.knots[0].position,
+            self.knots[self.knots.len() - 1].position,
+        );
+        // Only extrapolate outside of the knot range, to keep lookups within it fast
+        let x = if x < first.x || x > last.x {
+            match self.extrapolator().outside(x) {
+                Some(Outside::Value { y, .. }) => return y,
+                Some(Outside::Mapped { x, .. }) => x,
+                None => x,
+            }
+        } else {
+            x
+        };
+        // Mapped x can end up on the first or last knot
+        if self.knots.len() == 1 || x <= first.x || x.is_nan() {
+            return first.y;
+        }
+        if x >= last.x {
+            return last.y;
+        }
+
+        // Find left knot
+        let i = if let Some(cache) = cache {
+            if cache.generation != Some(self.generation) {
+                // The curve has changed since the cache was used, the index stays valid if no knots moved past
+                // each other
+                if cache
+                    .generation
+                    .is_none_or(|generation| self.edit_effect_since(generation).reordered)
+                {
+                    cache.last_knot_index = None;
+                }
+                cache.generation = Some(self.generation);
+            }
+            self.knots
+                .search_knots_with_cache(x, &mut cache.last_knot_index)
+        } else {
+            self.knots.search_knots(x)
+        };
+        let (knot_a, knot_b) = (&self.knots[i], &self.knots[i + 1]);
+
+        // Interpolate
+        match knot_a.interpolation {
+            KnotInterpolation::Constant => knot_a.position.y,
+            // Knots with the same x are a step, the search never ends up between them
+            _ if knot_b.position.x == knot_a.position.x => knot_b.position.y,
+            KnotInterpolation::ConstantNext if x == knot_a.position.x => knot_a.position.y,
+            KnotInterpolation::ConstantNext => knot_b.position.y,
+            KnotInterpolation::Linear => {
+                let s = (x - knot_a.position.x) / (knot_b.position.x - knot_a.position.x);
+                knot_a.position.lerp(knot_b.position, s).y
+            }
+            KnotInterpolation::Power { exponent } => {
+                let s = (x - knot_a.position.x) / (knot_b.position.x - knot_a.position.x);
+                knot_a.position.y + (knot_b.position.y - knot_a.position.y) * s.powf(exponent)
+            }
+            KnotInterpolation::Cubic => {
+                if knot_a.right_tangent.weight.is_some() || knot_b.left_tangent.weight.is_some() {
+                    weighted_cubic_interp(knot_a, knot_b, x, self.max_error, self.max_iters)
+                } else {
+                    unweighted_cubic_interp(knot_a, knot_b, x)
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn unweighted_cubic_interp(knot_a: &Knot, knot_b: &Knot, x: f32) -> f32 {
+    let s = (x - knot_a.position.x) / (knot_b.position.x - knot_a.position.x);
+    let [c0, c1, c2, c3] = hermite_coefficients(knot_a, knot_b);
+    c0 + s * (c1 + s * (c2 + s * c3))
+}
+
+/// Polynomial coefficients of an unweighted cubic segment, with x normalized to `0..=1` over the segment
+#[inline]
+pub(crate) fn hermite_coefficients(knot_a: &Knot, knot_b: &Knot) -> [f32; 4] {
+    let dx = knot_b.position.x - knot_a.position.x;
+    let (y0, y1) = (knot_a.position.y, knot_b.position.y);
+    let m0 = knot_a.right_tangent.slope * dx;
+    let m1 = knot_b.left_tangent.slope * dx;
+    [
+        y0,
+        m0,
+        3. * (y1 - y0) - 2. * m0 - m1,
+        2. * (y0 - y1) + m0 + m1,
+    ]
+}
+
+/// Derivative of [unweighted_cubic_interp] with respect to x
+fn unweighted_cubic_slope(knot_a: &Knot, knot_b: &Knot, x: f32) -> f32 {
+    let dx = knot_b.position.x - knot_a.position.x;
+    let x = (x - knot_a.position.x) / dx;
+    let m0 = knot_a.right_tangent.slope * dx;
+    let m1 = knot_b.left_tangent.slope * dx;
+
+    let x2 = x * x;
+
+    let a = 6. * x2 - 6. * x;
+    let b = 3. * x2 - 4. * x + 1.;
+    let c = 3. * x2 - 2. * x;
+    let d = -6. * x2 + 6. * x;
+
+    (a * knot_a.position.y + b * m0 + c * m1 + d * knot_b.position.y) / dx
+}
+
+#[inline]
+fn weighted_cubic_interp(
+    knot_a: &Knot,
+    knot_b: &Knot,
+    x: f32,
+    max_error: f32,
+    max_iters: u8,
+) -> f32 {
+    CubicSegment::from_bezier_points(knot_a.compute_bezier_to(knot_b))
+        .find_y_given_x(x, max_error, max_iters)
+}
+
+/// Mostly a copy of code from https://github.com/bevyengine/bevy/blob/main/crates/bevy_math/src/cubic_splines.rs
+///
+/// Copied because the cubic_splines module does not exactly fit the API we need:
+/// 1. Allow constructing a single CubicSegment from bezier points (without allocating a CubicCurve, and without restricting c0 and c1 to 0 and 1)
+/// 2. find_y_given_x needs to be accessible
+/// 3. max_iters and max_error should be configurable
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct CubicSegment {
+    pub(crate) coeff: [Vec2; 4],
+}
+
+impl CubicSegment {
+    /// Instantaneous position of a point at parametric value `t`.
+    #[inline]
+    pub(crate) fn position(&self, t: f32) -> Vec2 {
+        let [a, b, c, d] = self.coeff;
+        a + b * t + c * t.powi(2) + d * t.powi(3)
+    }
+
+    /// Instantaneous velocity of a point at parametric value `t`.
+    #[inline]
+    pub(crate) fn velocity(&self, t: f32) -> Vec2 {
+        let [_, b, c, d] = self.coeff;
+        b + c * 2.0 * t + d * 3.0 * t.powi(2)
+    }
+
+    #[inline]
+    fn find_y_given_x(&self, x: f32, max_error: f32, max_iters: u8) -> f32 {
+        self.position(self.find_t_given_x(x, max_error, max_iters))
+            .y
+    }
+
+    /// Finds the parametric value `t` in `0..=1` at which the segment reaches `x`.
+    ///
+    /// `x` is clamped to the x range of the segment first. Otherwise x just outside of the segment, as can happen
+    /// from rounding at knots, lets the solve wander off and extrapolate far from the neighboring segment.
+    #[inline]
+    pub(crate) fn find_t_given_x(&self, x: f32, max_error: f32, max_iters: u8) -> f32 {
+        let x = self.clamp_x(x);
+        // x doubles as the first guess, like in bevy
+        self.find_t_given_x_from(x, x, max_error, max_iters)
+    }
+
+    /// [CubicSegment::find_t_given_x], starting the solve at `t_guess`. A close guess, like the t of a nearby x,
+    /// needs fewer iterations.
+    ///
+    /// t is kept in `0..=1`. Where a Newton step is not possible, like at a vertical tangent with dx/dt near 0, or
+    /// would leave the bracket of t around the solution found so far, the bracket is bisected instead.
+    #[inline]
+    pub(crate) fn find_t_given_x_from(
+        &self,
+        x: f32,
+        t_guess: f32,
+        max_error: f32,
+        max_iters: u8,
+    ) -> f32 {
+        let x = self.clamp_x(x);
+        let increasing = self.position(1.0).x >= self.position(0.0).x;
+        // Bracket of t around the solution, narrowed by every guess
+        let (mut low, mut high) = (0.0, 1.0);
+        let mut t_guess = if t_guess.is_nan() {
+            0.5
+        } else {
+            t_guess.clamp(0.0, 1.0)
+        };
+        let mut t_result = t_guess;
+        for _ in 0..max_iters {
+            let pos_guess = self.position(t_guess);
+            t_result = t_guess;
+            let error = pos_guess.x - x;
+            if error.abs() <= max_error {
+                return t_result;
+            }
+            if (error > 0.0) == increasing {
+                high = t_guess;
+            } else {
+                low = t_guess;
+            }
+            // Using Newton's method, use the tangent line to estimate a better guess value.
+            let slope = self.velocity(t_guess).x; // dx/dt
+            let next = (t_guess - error / slope).clamp(0.0, 1.0);
+            t_guess = if slope.abs() > f32::EPSILON && next >= low && next <= high {
+                next
+            } else {
+                0.5 * (low + high)
+            };
+        }
+
+        #[cfg(feature = "trace")]
+        tracing::debug!(
+            x,
+            max_iters,
+            max_error,
+            "weighted cubic solve did not converge"
+        );
+
+        t_result
+    }
+
+    #[inline]
+    fn clamp_x(&self, x: f32) -> f32 {
+        let (start, end) = (self.position(0.0).x, self.position(1.0).x);
+        x.clamp(start.min(end), start.max(end))
+    }
+
+    #[inline]
+    pub(crate) fn from_bezier_points(control_points: [Vec2; 4]) -> CubicSegment {
+        let char_matrix = [
+            [1., 0., 0., 0.],
+            [-3., 3., 0., 0.],
+            [3., -6., 3., 0.],
+            [-1., 3., -3., 1.],
+        ];
+
+        Self::coefficients(control_points, 1.0, char_matrix)
+    }
+
+    #[inline]
+    fn coefficients(p: [Vec2; 4], multiplier: f32, char_matrix: [[f32; 4]; 4]) -> CubicSegment {
+        let [c0, c1, c2, c3] = char_matrix;
+        // These are the polynomial coefficients, computed by multiplying the characteristic
+        // matrix by the point matrix.
+        let mut coeff = [
+            p[0] * c0[0] + p[1] * c0[1] + p[2] * c0[2] + p[3] * c0[3],
+            p[0] * c1[0] + p[1] * c1[1] + p[2] * c1[2] + p[3] * c1[3],
+            p[0] * c2[0] + p[1] * c2[1] + p[2] * c2[2] + p[3] * c2[3],
+            p[0] * c3[0] + p[1] * c3[1] + p[2] * c3[2] + p[3] * c3[3],
+        ];
+        coeff.iter_mut().for_each(|c| *c *= multiplier);
+        CubicSegment { coeff }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_is_invalidated_by_generation() {
+        let mut curve = LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::new(0.5, 0.2),
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::ONE,
+                ..Default::default()
+            },
+        ]);
+        let mut cache = LookupCache::new();
+        curve.lookup_cached(0.9, &mut cache);
+        assert_eq!(cache.last_knot_index, Some(1));
+
+        curve.delete_knot(2);
+        curve.delete_knot(1);
+        curve.add_knot(Knot {
+            position: Vec2::new(1.0, 1.0),
+            ..Default::default()
+        });
+        curve.lookup_cached(0.1, &mut cache);
+        assert_eq!(cache.last_knot_index, Some(0));
+        assert_eq!(cache.generation, Some(curve.generation()));
+    }
+
+    /// Two weighted cubic segments far from x = 0, where the solve starts far from the solution
+    fn weighted_curve() -> LookupCurve {
+        let knot = |x: f32, y: f32| {
+            Knot {
+                position: Vec2::new(x, y),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            }
+            .with_tangent_slope(TangentSide::Left, -4.0)
+            .with_tangent_slope(TangentSide::Right, 4.0)
+            .with_tangent_weight(TangentSide::Left, Some(0.9))
+            .with_tangent_weight(TangentSide::Right, Some(0.05))
+        };
+        LookupCurve::new(vec![knot(100.0, 0.0), knot(100.5, 2.0), knot(103.0, -1.0)])
+    }
+
+    #[test]
+    fn segments_are_clamped_to_their_x_range() {
+        let curve = weighted_curve();
+        let knots = curve.knots();
+        for pair in knots.windows(2) {
+            let segment = CubicSegment::from_bezier_points(pair[0].compute_bezier_to(&pair[1]));
+            let (start, end) = (pair[0].position, pair[1].position);
+            for (x, expected) in [
+                (start.x.next_down(), start.y),
+                (start.x - 1.0, start.y),
+                (end.x.next_up(), end.y),
+                (end.x + 1.0, end.y),
+            ] {
+                let y = segment.find_y_given_x(x, curve.max_error, curve.max_iters);
+                assert!((y - expected).abs() < 1e-3, "{x}: {y} != {expected}");
+                let t = segment.find_t_given_x(x, curve.max_error, curve.max_iters);
+                assert!((0.0..=1.0).contains(&t), "{x}: {t}");
+            }
+        }
+    }
+
+    #[test]
+    fn no_spikes_at_knots() {
+        let curve = weighted_curve();
+        let mut cache = LookupCache::new();
+        for knot in curve.knots() {
+            let x = knot.position.x;
+            for x in [
+                x.next_down().next_down(),
+                x.next_down(),
+                x,
+                x.next_up(),
+                x.next_up().next_up(),
+            ] {
+                for y in [curve.lookup(x), curve.lookup_cached(x, &mut cache)] {
+                    assert!(
+                        (y - knot.position.y).abs() < 1e-3,
+                        "{x}: {y} != {}",
+                        knot.position.y
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn vertical_tangents_are_solved() {
+        // dx/dt is 0 at both ends
+        let segment = CubicSegment::from_bezier_points([
+            Vec2::ZERO,
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::ONE,
+        ]);
+        for i in 0..=100 {
+            let x = i as f32 / 100.0;
+            for t_guess in [0.0, 0.5, 1.0, -3.0, f32::NAN] {
+                let t = segment.find_t_given_x_from(x, t_guess, 1e-6, 40);
+                assert!((0.0..=1.0).contains(&t), "{x} from {t_guess}: {t}");
+                let error = (segment.position(t).x - x).abs();
+                assert!(error <= 1e-5, "{x} from {t_guess}: off by {error}");
+            }
+        }
+
+        let knot = |x: f32, y: f32| {
+            Knot {
+                position: Vec2::new(x, y),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            }
+            .with_tangent_slope(TangentSide::Left, 1e7)
+            .with_tangent_slope(TangentSide::Right, 1e7)
+            .with_tangent_weight(TangentSide::Left, Some(1.0))
+            .with_tangent_weight(TangentSide::Right, Some(1.0))
+        };
+        let curve = LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0), knot(3.0, 2.0)]);
+        for i in 0..=300 {
+            let y = curve.lookup(i as f32 / 100.0);
+            assert!(y.is_finite(), "{i}: {y}");
+        }
+    }
+
+    #[test]
+    fn knots_with_the_same_x_are_a_step() {
+        let knot = |x: f32, y: f32, interpolation| Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        };
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 1.0, KnotInterpolation::Linear),
+            knot(1.0, 3.0, KnotInterpolation::Cubic),
+            knot(1.0, 4.0, KnotInterpolation::Cubic),
+            knot(2.0, 0.0, KnotInterpolation::Linear),
+        ]);
+        assert_eq!(curve.lookup(1.0), 1.0);
+        assert!((curve.lookup(1.0f32.next_up()) - 4.0).abs() < 1e-4);
+
+        let precomputed = curve.precompute();
+        let mut cache = LookupCache::new();
+        let mut filled = [0.0; 201];
+        curve.fill_buffer(0.0, 0.01, &mut filled);
+        for (i, filled) in filled.into_iter().enumerate() {
+            let x = 0.01 * i as f32;
+            let y = curve.lookup(x);
+            assert!(y.is_finite(), "{x}: {y}");
+            assert_eq!(curve.lookup_cached(x, &mut cache), y, "{x}");
+            assert_eq!(precomputed.lookup(x), y, "{x}");
+            assert_eq!(filled, y, "{x}");
+            for side in [TangentSide::Left, TangentSide::Right] {
+                assert!(curve.slope_at(x, side).is_finite(), "{x}");
+            }
+        }
+    }
+
+    #[test]
+    fn constant_next_steps_right_after_the_knot() {
+        let knot = |x: f32, y: f32, interpolation| Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        };
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::ConstantNext),
+            knot(1.0, 1.0, KnotInterpolation::ConstantNext),
+            knot(2.0, 3.0, KnotInterpolation::Constant),
+            knot(3.0, 2.0, KnotInterpolation::Linear),
+        ]);
+        let precomputed = curve.precompute();
+        let mut cache = LookupCache::new();
+        for (x, y) in [
+            (0.0, 0.0),
+            (0.0f32.next_up(), 1.0),
+            (0.5, 1.0),
+            // At the knot the curve has the y of the knot
+            (1.0, 1.0),
+            (1.0f32.next_up(), 3.0),
+            (2.0, 3.0),
+            (2.5, 3.0),
+            (3.0, 2.0),
+        ] {
+            assert_eq!(curve.lookup(x), y, "{x}");
+            assert_eq!(curve.lookup_cached(x, &mut cache), y, "{x}");
+            assert_eq!(precomputed.lookup(x), y, "{x}");
+        }
+        assert_eq!(curve.slope_at(0.5, TangentSide::Left), 0.0);
+    }
+
+    #[test]
+    fn power_segments_raise_the_fraction() {
+        let curve = LookupCurve::new(vec![
+            Knot {
+                position: Vec2::new(1.0, 1.0),
+                interpolation: KnotInterpolation::Power { exponent: 3.0 },
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::new(3.0, -1.0),
+                ..Default::default()
+            },
+        ]);
+        let precomputed = curve.precompute();
+        for i in 0..=100 {
+            let x = 1.0 + 2.0 * i as f32 / 100.0;
+            let s = (x - 1.0) / 2.0;
+            let expected = 1.0 - 2.0 * s * s * s;
+            assert!((curve.lookup(x) - expected).abs() < 1e-6, "{x}");
+            assert!((precomputed.lookup(x) - expected).abs() < 1e-6, "{x}");
+            let slope = -3.0 * s * s;
+            assert!(
+                (curve.slope_at(x, TangentSide::Left) - slope).abs() < 1e-5,
+                "{x}"
+            );
+        }
+    }
+
+    #[test]
+    fn nan_and_infinite_x() {
+        for before in crate::Extrapolation::ALL {
+            for after in crate::Extrapolation::ALL {
+                let curve = weighted_curve().with_extrapolation(before, after);
+                assert_eq!(curve.lookup(f32::NAN), 0.0);
+                assert_eq!(curve.lookup_cached(f32::NAN, &mut LookupCache::new()), 0.0);
+                assert_eq!(curve.try_lookup(f32::NAN), None);
+                assert_eq!(curve.try_lookup(100.5), Some(2.0));
+                assert_eq!(curve.precompute().lookup(f32::NAN), 0.0);
+                assert_eq!(curve.bake(64).sample(f32::NAN), 0.0);
+                let mut filled = [1.0; 4];
+                curve.fill_buffer(f32::NAN, 0.1, &mut filled);
+                assert_eq!(filled, [0.0; 4]);
+                for x in [f32::INFINITY, f32::NEG_INFINITY, f32::MAX, f32::MIN] {
+                    let y = curve.lookup(x);
+                    assert!(!y.is_nan(), "{before:?} {after:?} {x}");
+                    assert_eq!(curve.try_lookup(x), Some(y));
+                    assert!(!curve.slope_at(x, TangentSide::Left).is_nan());
+                }
+            }
+        }
+
+        let broken = LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::new(1.0, f32::NAN),
+                ..Default::default()
+            },
+        ]);
+        assert_eq!(broken.try_lookup(0.5), None);
+    }
+}
@@ -0,0 +1,487 @@
+use bevy_math::Vec2;
+
+use super::{hermite_coefficients, CubicSegment};
+use crate::{Extrapolator, Knot, KnotInterpolation, LookupCurve, Outside};
+
+/// How y is found in one segment of a curve, with the coefficients computed ahead
+#[derive(Clone, Debug)]
+pub(super) enum Segment {
+    Constant {
+        y: f32,
+    },
+    Linear {
+        x0: f32,
+        dx: f32,
+        y0: f32,
+        dy: f32,
+    },
+    /// Linear segment eased with [KnotInterpolation::Power]
+    Power {
+        x0: f32,
+        dx: f32,
+        y0: f32,
+        dy: f32,
+        exponent: f32,
+    },
+    /// Polynomial in x normalized to `0..=1` over the segment
+    Hermite {
+        x0: f32,
+        dx: f32,
+        coeff: [f32; 4],
+    },
+    /// Weighted cubic segment, solved for t like in the curve
+    Bezier(CubicSegment),
+}
+
+impl Segment {
+    /// The segment from `a` to `b`
+    pub(super) fn new(a: &Knot, b: &Knot) -> Self {
+        let dx = b.position.x - a.position.x;
+        match a.interpolation {
+            KnotInterpolation::Constant => Segment::Constant { y: a.position.y },
+            // Knots with the same x are a step
+            _ if dx == 0.0 => Segment::Constant { y: b.position.y },
+            // Only sampled after the knot
+            KnotInterpolation::ConstantNext => Segment::Constant { y: b.position.y },
+            KnotInterpolation::Linear => Segment::Linear {
+                x0: a.position.x,
+                dx,
+                y0: a.position.y,
+                dy: b.position.y - a.position.y,
+            },
+            KnotInterpolation::Power { exponent } => Segment::Power {
+                x0: a.position.x,
+                dx,
+                y0: a.position.y,
+                dy: b.position.y - a.position.y,
+                exponent,
+            },
+            KnotInterpolation::Cubic
+                if a.right_tangent.weight.is_some() || b.left_tangent.weight.is_some() =>
+            {
+                Segment::Bezier(CubicSegment::from_bezier_points(a.compute_bezier_to(b)))
+            }
+            KnotInterpolation::Cubic => Segment::Hermite {
+                x0: a.position.x,
+                dx,
+                coeff: hermite_coefficients(a, b),
+            },
+        }
+    }
+
+    /// y at `x` within the segment, like [LookupCurve::lookup]
+    #[inline]
+    pub(super) fn y(&self, x: f32, max_error: f32, max_iters: u8) -> f32 {
+        match self {
+            Segment::Constant { y } => *y,
+            Segment::Linear { x0, dx, y0, dy } => y0 + dy * ((x - x0) / dx),
+            Segment::Power {
+                x0,
+                dx,
+                y0,
+                dy,
+                exponent,
+            } => y0 + dy * ((x - x0) / dx).powf(*exponent),
+            Segment::Hermite { x0, dx, coeff } => {
+                let s = (x - x0) / dx;
+                let [c0, c1, c2, c3] = *coeff;
+                c0 + s * (c1 + s * (c2 + s * c3))
+            }
+            Segment::Bezier(segment) => segment.find_y_given_x(x, max_error, max_iters),
+        }
+    }
+
+    /// y at `x` within the segment, and its first, second and third derivative with respect to x.
+    ///
+    /// Derivatives of weighted segments are 0 where the segment is vertical.
+    pub(super) fn derivatives(&self, x: f32, max_error: f32, max_iters: u8) -> [f32; 4] {
+        match self {
+            Segment::Constant { y } => [*y, 0.0, 0.0, 0.0],
+            Segment::Linear { dx, dy, .. } => [self.y(x, max_error, max_iters), dy / dx, 0.0, 0.0],
+            Segment::Power {
+                x0,
+                dx,
+                y0,
+                dy,
+                exponent: e,
+            } => {
+                let s = (x - x0) / dx;
+                [
+                    y0 + dy * s.powf(*e),
+                    dy * power_term(*e, e - 1.0, s) / dx,
+                    dy * power_term(e * (e - 1.0), e - 2.0, s) / (dx * dx),
+                    dy * power_term(e * (e - 1.0) * (e - 2.0), e - 3.0, s) / (dx * dx * dx),
+                ]
+            }
+            Segment::Hermite { x0, dx, coeff } => {
+                let s = (x - x0) / dx;
+                let [c0, c1, c2, c3] = *coeff;
+                [
+                    c0 + s * (c1 + s * (c2 + s * c3)),
+                    (c1 + s * (2.0 * c2 + s * 3.0 * c3)) / dx,
+                    (2.0 * c2 + s * 6.0 * c3) / (dx * dx),
+                    6.0 * c3 / (dx * dx * dx),
+                ]
+            }
+            Segment::Bezier(segment) => {
+                let t = segment.find_t_given_x(x, max_error, max_iters);
+                let [_, b, c, d] = segment.coeff;
+                let p1 = b + c * 2.0 * t + d * 3.0 * t * t;
+                let p2 = c * 2.0 + d * 6.0 * t;
+                let p3 = d * 6.0;
+                let y = segment.position(t).y;
+                if p1.x == 0.0 {
+                    return [y, 0.0, 0.0, 0.0];
+                }
+                // Chain rule with dt/dx = 1 / x'(t)
+                let n = p2.y * p1.x - p1.y * p2.x;
+                let dn = p3.y * p1.x - p1.y * p3.x;
+                [
+                    y,
+                    p1.y / p1.x,
+                    n / p1.x.powi(3),
+                    (dn * p1.x - 3.0 * n * p2.x) / p1.x.powi(5),
+                ]
+            }
+        }
+    }
+
+    /// Upper bounds of the absolute first, second and third derivative with respect to x, between `x_min` and
+    /// `x_max` within the segment. Infinite for weighted segments that are vertical in the range.
+    ///
+    /// For weighted segments the bounds are built from bounds of the derivatives with respect to t over the t range,
+    /// so they are larger than the actual maxima.
+    pub(super) fn derivative_bounds(
+        &self,
+        x_min: f32,
+        x_max: f32,
+        max_error: f32,
+        max_iters: u8,
+    ) -> [f32; 3] {
+        match self {
+            Segment::Constant { .. } => [0.0; 3],
+            Segment::Linear { dx, dy, .. } => [(dy / dx).abs(), 0.0, 0.0],
+            Segment::Power {
+                x0,
+                dx,
+                dy,
+                exponent: e,
+                ..
+            } => {
+                // Each derivative is a power of s, largest at one end of the range
+                let (s0, s1) = ((x_min - x0) / dx, (x_max - x0) / dx);
+                let bound = |factor: f32, power: f32, order: i32| {
+                    let at = |s: f32| power_term(factor, power, s.clamp(0.0, 1.0)).abs();
+                    (dy / dx.powi(order)).abs() * at(s0).max(at(s1))
+                };
+                [
+                    bound(*e, e - 1.0, 1),
+                    bound(e * (e - 1.0), e - 2.0, 2),
+                    bound(e * (e - 1.0) * (e - 2.0), e - 3.0, 3),
+                ]
+            }
+            Segment::Hermite { x0, dx, coeff } => {
+                let (s0, s1) = ((x_min - x0) / dx, (x_max - x0) / dx);
+                let range = (s0.min(s1), s0.max(s1));
+                let [_, c1, c2, c3] = *coeff;
+                [
+                    max_abs_quadratic([c1, 2.0 * c2, 3.0 * c3], range) / dx.abs(),
+                    max_abs_quadratic([2.0 * c2, 6.0 * c3, 0.0], range) / (dx * dx),
+                    (6.0 * c3 / (dx * dx * dx)).abs(),
+                ]
+            }
+            Segment::Bezier(segment) => {
+                let (t0, t1) = (
+                    segment.find_t_given_x(x_min, max_error, max_iters),
+                    segment.find_t_given_x(x_max, max_error, max_iters),
+                );
+                let range = (t0.min(t1), t0.max(t1));
+                let [_, b, c, d] = segment.coeff;
+                let derivative = |axis: fn(Vec2) -> f32| {
+                    let (b, c, d) = (axis(b), axis(c), axis(d));
+                    (
+                        max_abs_quadratic([b, 2.0 * c, 3.0 * d], range),
+                        max_abs_quadratic([2.0 * c, 6.0 * d, 0.0], range),
+                        (6.0 * d).abs(),
+                    )
+                };
+                let (x1, x2, x3) = derivative(|p| p.x);
+                let (y1, y2, y3) = derivative(|p| p.y);
+                let min_x1 = min_abs_quadratic([b.x, 2.0 * c.x, 3.0 * d.x], range);
+                if min_x1 == 0.0 {
+                    return [f32::INFINITY; 3];
+                }
+                // Bounds of the terms in Segment::derivatives
+                [
+                    y1 / min_x1,
+                    (y2 * x1 + y1 * x2) / min_x1.powi(3),
+                    ((y3 * x1 + y1 * x3) * x1 + 3.0 * (y2 * x1 + y1 * x2) * x2) / min_x1.powi(5),
+                ]
+            }
+        }
+    }
+
+    /// Like [Segment::y], but solving weighted segments starting from `t`, which is set to the t of `x`
+    #[inline]
+    pub(super) fn y_from(&self, x: f32, t: &mut f32, max_error: f32, max_iters: u8) -> f32 {
+        if let Segment::Bezier(segment) = self {
+            *t = segment.find_t_given_x_from(x, *t, max_error, max_iters);
+            segment.position(*t).y
+        } else {
+            self.y(x, max_error, max_iters)
+        }
+    }
+}
+
+/// `factor * s^power`, 0 for a factor of 0 even where the power is infinite
+fn power_term(factor: f32, power: f32, s: f32) -> f32 {
+    if factor == 0.0 {
+        0.0
+    } else {
+        factor * s.powf(power)
+    }
+}
+
+/// Largest absolute value of `c[0] + c[1] * t + c[2] * t^2` for t in `range`
+fn max_abs_quadratic(c: [f32; 3], (t0, t1): (f32, f32)) -> f32 {
+    let p = |t: f32| c[0] + t * (c[1] + t * c[2]);
+    let mut max = p(t0).abs().max(p(t1).abs());
+    if let Some(vertex) = quadratic_vertex(c).filter(|t| (t0..=t1).contains(t)) {
+        max = max.max(p(vertex).abs());
+    }
+    max
+}
+
+/// Smallest absolute value of `c[0] + c[1] * t + c[2] * t^2` for t in `range`, 0 if it changes sign
+fn min_abs_quadratic(c: [f32; 3], (t0, t1): (f32, f32)) -> f32 {
+    let p = |t: f32| c[0] + t * (c[1] + t * c[2]);
+    let (a, b) = (p(t0), p(t1));
+    if a.signum() != b.signum() || a == 0.0 || b == 0.0 {
+        return 0.0;
+    }
+    let mut min = a.abs().min(b.abs());
+    if let Some(vertex) = quadratic_vertex(c).filter(|t| (t0..=t1).contains(t)) {
+        let v = p(vertex);
+        if v.signum() != a.signum() || v == 0.0 {
+            return 0.0;
+        }
+        min = min.min(v.abs());
+    }
+    min
+}
+
+fn quadratic_vertex(c: [f32; 3]) -> Option<f32> {
+    (c[2] != 0.0).then(|| -c[1] / (2.0 * c[2]))
+}
+
+/// A [LookupCurve] with the coefficients of each segment computed ahead, created with [LookupCurve::precompute].
+///
+/// Looking up y is a binary search for the segment and the evaluation of its polynomial, and gives the same result
+/// as [LookupCurve::lookup]. Unlike a [BakedLookupCurve](super::BakedLookupCurve) it is exact, but the time of a
+/// lookup grows with the number of knots, and weighted cubic segments are still solved iteratively.
+///
+/// It does not follow changes of the curve, bring it up to date with [PrecomputedLookupCurve::update] when the
+/// [LookupCurve::generation] changes.
+#[derive(Clone, Debug)]
+pub struct PrecomputedLookupCurve {
+    /// x of the knots, searched for the segment
+    pub(super) xs: Vec<f32>,
+    /// The segment starting at each knot but the last
+    pub(super) segments: Vec<Segment>,
+    pub(super) first_y: f32,
+    pub(super) last_y: f32,
+    pub(super) max_error: f32,
+    pub(super) max_iters: u8,
+    pub(super) extrapolator: Extrapolator,
+    /// [LookupCurve::generation] of the curve the segments were computed from
+    pub(super) generation: u64,
+}
+
+impl PrecomputedLookupCurve {
+    /// Find y for given x. Like [LookupCurve::lookup], values outside of the knot range are extrapolated with the
+    /// settings of the curve.
+    #[inline]
+    pub fn lookup(&self, x: f32) -> f32 {
+        let x = match self.extrapolator.outside(x) {
+            Some(Outside::Value { y, .. }) => return y,
+            Some(Outside::Mapped { x, .. }) => x,
+            None => x,
+        };
+        // Same order of checks as the curve, so that the same segment is used for equal x
+        if self.xs.len() <= 1 || x <= self.xs[0] || x.is_nan() {
+            return self.first_y;
+        }
+        if x >= self.xs[self.xs.len() - 1] {
+            return self.last_y;
+        }
+        // Like KnotSearch::search_knots
+        let i = self.xs.partition_point(|&knot_x| knot_x < x) - 1;
+        self.segments[i].y(x, self.max_error, self.max_iters)
+    }
+
+    /// Brings the segments up to date with `curve`, which they were computed from at an earlier generation.
+    ///
+    /// Only the segments changed since then are computed again, see [LookupCurve::edit_effect_since]. Returns the
+    /// number of computed segments.
+    pub fn update(&mut self, curve: &LookupCurve) -> usize {
+        #[cfg(feature = "trace")]
+        let span = tracing::info_span!(
+            "lookup_curve_cache_update",
+            segments = curve.knots.len().saturating_sub(1),
+            rebuilt = tracing::field::Empty
+        )
+        .entered();
+
+        let rebuilt = self.update_segments(curve);
+
+        #[cfg(feature = "trace")]
+        span.record("rebuilt", rebuilt);
+
+        rebuilt
+    }
+
+    fn update_segments(&mut self, curve: &LookupCurve) -> usize {
+        let effect = curve.edit_effect_since(self.generation);
+        if effect.reordered || self.xs.len() != curve.knots.len() {
+            *self = curve.precompute();
+            return self.segments.len();
+        }
+        let knots = &curve.knots;
+        for i in effect.dirty_segments.clone() {
+            self.segments[i] = Segment::new(&knots[i], &knots[i + 1]);
+            self.xs[i] = knots[i].position.x;
+            self.xs[i + 1] = knots[i + 1].position.x;
+        }
+        self.first_y = knots.first().map_or(0.0, |knot| knot.position.y);
+        self.last_y = knots.last().map_or(0.0, |knot| knot.position.y);
+        self.max_error = curve.max_error;
+        self.max_iters = curve.max_iters;
+        self.extrapolator = curve.extrapolator();
+        self.generation = curve.generation;
+        effect.dirty_segments.len()
+    }
+
+    /// Size of the precomputed segments in bytes
+    pub fn memory(&self) -> usize {
+        self.xs.len() * std::mem::size_of::<f32>()
+            + self.segments.len() * std::mem::size_of::<Segment>()
+    }
+}
+
+impl LookupCurve {
+    /// Computes the coefficients of each segment ahead, for curves sampled many times between edits, see
+    /// [PrecomputedLookupCurve]
+    pub fn precompute(&self) -> PrecomputedLookupCurve {
+        #[cfg(feature = "trace")]
+        let _span = tracing::info_span!(
+            "lookup_curve_cache_update",
+            segments = self.knots.len().saturating_sub(1)
+        )
+        .entered();
+        let segments = self
+            .knots
+            .windows(2)
+            .map(|pair| Segment::new(&pair[0], &pair[1]))
+            .collect();
+        PrecomputedLookupCurve {
+            xs: self.knots.iter().map(|knot| knot.position.x).collect(),
+            segments,
+            first_y: self.knots.first().map_or(0.0, |knot| knot.position.y),
+            last_y: self.knots.last().map_or(0.0, |knot| knot.position.y),
+            max_error: self.max_error,
+            max_iters: self.max_iters,
+            extrapolator: self.extrapolator(),
+            generation: self.generation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TangentSide;
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+        .with_tangent_slope(TangentSide::Left, -1.5)
+        .with_tangent_slope(TangentSide::Right, 2.0)
+    }
+
+    #[test]
+    fn precomputed_matches_the_curve() {
+        let weighted = knot(2.0, 1.0, KnotInterpolation::Cubic)
+            .with_tangent_weight(TangentSide::Right, Some(0.8));
+        let curve = LookupCurve::new(vec![
+            knot(-1.0, 0.5, KnotInterpolation::Constant),
+            knot(0.0, 2.0, KnotInterpolation::Linear),
+            knot(0.5, -1.0, KnotInterpolation::Cubic),
+            weighted,
+            knot(3.0, 0.0, KnotInterpolation::Cubic),
+            // Same x as the previous knot, a jump
+            knot(3.0, 4.0, KnotInterpolation::Linear),
+            knot(4.5, 3.0, KnotInterpolation::Linear),
+        ]);
+        let precomputed = curve.precompute();
+        let mut xs: Vec<f32> = (-200..=600).map(|i| i as f32 / 100.0).collect();
+        for knot in curve.knots() {
+            let x = knot.position.x;
+            xs.extend([x.next_down(), x, x.next_up()]);
+        }
+        for x in xs {
+            let (expected, y) = (curve.lookup(x), precomputed.lookup(x));
+            assert!((y - expected).abs() < 1e-6, "{x}: {y} != {expected}");
+        }
+        assert_eq!(precomputed.lookup(-5.0), 0.5);
+        assert_eq!(precomputed.lookup(10.0), 3.0);
+
+        assert_eq!(LookupCurve::default().precompute().lookup(1.0), 0.0);
+        let single = LookupCurve::new(vec![knot(1.0, 3.0, KnotInterpolation::Cubic)]);
+        assert_eq!(single.precompute().lookup(7.0), 3.0);
+    }
+
+    #[test]
+    fn updates_compute_changed_segments() {
+        let knots = (0..20)
+            .map(|i| knot(i as f32, (i * i % 7) as f32, KnotInterpolation::Cubic))
+            .collect();
+        let mut curve = LookupCurve::new(knots);
+        let mut precomputed = curve.precompute();
+        assert_eq!(precomputed.update(&curve), 0);
+
+        let moved = |curve: &LookupCurve, i: usize, by: Vec2| Knot {
+            position: curve.knots()[i].position + by,
+            ..curve.knots()[i]
+        };
+        curve.modify_knot(8, moved(&curve, 8, Vec2::new(0.1, 1.0)));
+        assert_eq!(precomputed.update(&curve), 2);
+        let weighted = curve.knots()[12].with_tangent_weight(TangentSide::Right, Some(0.5));
+        curve.modify_knot(12, weighted);
+        let linear = Knot {
+            interpolation: KnotInterpolation::Linear,
+            ..curve.knots()[14]
+        };
+        curve.modify_knot(14, linear);
+        // Both edits at once
+        assert_eq!(precomputed.update(&curve), 4);
+        let expected = curve.precompute();
+        for x in (0..400).map(|i| i as f32 / 20.0) {
+            assert_eq!(precomputed.lookup(x), expected.lookup(x), "{x}");
+        }
+
+        curve.modify_knot(3, moved(&curve, 3, Vec2::X * 1.5));
+        assert_eq!(precomputed.update(&curve), 19);
+        curve.modify_knot(0, moved(&curve, 0, -Vec2::X));
+        assert_eq!(precomputed.update(&curve), 19);
+        curve.delete_knot(5);
+        assert_eq!(precomputed.update(&curve), 18);
+        let expected = curve.precompute();
+        for x in (-20..400).map(|i| i as f32 / 20.0) {
+            assert_eq!(precomputed.lookup(x), expected.lookup(x), "{x}");
+        }
+    }
+}
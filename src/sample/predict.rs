@@ -0,0 +1,297 @@
+use super::precomputed::Segment;
+use super::PrecomputedLookupCurve;
+use crate::{LookupCurve, Outside};
+
+/// A sample of a curve at a predicted x with its derivatives, returned by [CurvePredictor::predict]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PredictedSample {
+    /// x the sample was taken at, `current_x + lead`
+    pub x: f32,
+    /// y at [Self::x], like [LookupCurve::lookup]
+    pub value: f32,
+    /// First derivative dy/dx at [Self::x]
+    pub slope: f32,
+    /// Second derivative d²y/dx² at [Self::x], the curvature used by [Self::corrected]
+    pub second_order_correction: f32,
+}
+
+impl PredictedSample {
+    /// Corrects the prediction for the actual x, with the Taylor expansion
+    /// `value + slope * dx + 0.5 * second_order_correction * dx²` where `dx = actual_x - x`.
+    ///
+    /// The error is at most [CurvePredictor::correction_error_bound] of [Self::x] with a `max_dx` of at least `dx`.
+    #[inline]
+    pub fn corrected(&self, actual_x: f32) -> f32 {
+        let dx = actual_x - self.x;
+        self.value + dx * (self.slope + 0.5 * self.second_order_correction * dx)
+    }
+}
+
+/// Predicts samples of a curve ahead of time, for networked clients that sample at a predicted future x and correct
+/// the sample when the authoritative x arrives.
+///
+/// Holds a snapshot of the curve with the coefficients of each segment computed ahead, like a
+/// [PrecomputedLookupCurve]. Derivatives are computed exactly from the segment polynomials, not with finite
+/// differences. It does not follow changes of the curve, create it again when the [LookupCurve::generation] changes.
+#[derive(Clone, Debug)]
+pub struct CurvePredictor {
+    curve: PrecomputedLookupCurve,
+}
+
+impl CurvePredictor {
+    /// Takes a snapshot of `curve`
+    pub fn new(curve: &LookupCurve) -> Self {
+        Self {
+            curve: curve.precompute(),
+        }
+    }
+
+    /// Samples the curve at `current_x + lead`, with the first and second derivative for correcting the sample
+    /// with [PredictedSample::corrected].
+    ///
+    /// Exactly on a knot the derivatives of the segment ending at the knot are used. Outside of the knot range the
+    /// sample is extrapolated like [LookupCurve::lookup], where [Extrapolation::Clamp](crate::Extrapolation::Clamp)
+    /// has derivatives of 0.
+    pub fn predict(&self, current_x: f32, lead: f32) -> PredictedSample {
+        let x = current_x + lead;
+        let curve = &self.curve;
+        let (sample_x, mirrored) = match curve.extrapolator.outside(x) {
+            Some(Outside::Value { y, slope }) => {
+                return PredictedSample {
+                    x,
+                    value: y,
+                    slope,
+                    second_order_correction: 0.0,
+                }
+            }
+            Some(Outside::Mapped { x, mirrored }) => (x, mirrored),
+            None => (x, false),
+        };
+        let [value, slope, curvature, _] = match self.segment_at(sample_x) {
+            Some(i) => curve.segments[i].derivatives(sample_x, curve.max_error, curve.max_iters),
+            None => [curve.lookup(sample_x), 0.0, 0.0, 0.0],
+        };
+        PredictedSample {
+            x,
+            value,
+            slope: if mirrored { -slope } else { slope },
+            second_order_correction: curvature,
+        }
+    }
+
+    /// Upper bound of the error of [PredictedSample::corrected] for a sample predicted at `x`, corrected to an
+    /// actual x at most `max_dx` away.
+    ///
+    /// The bound is the remainder of the Taylor expansion, the largest third derivative of the segment within
+    /// `max_dx` of `x` times `max_dx³ / 6`. For weighted cubic segments it includes the tolerance of the solve
+    /// for t, see [LookupCurve::max_error]. Derivatives are not continuous at knots, so the bound is infinite if
+    /// the range reaches another segment, and the sample should be taken again instead of being corrected.
+    pub fn correction_error_bound(&self, x: f32, max_dx: f32) -> f32 {
+        let max_dx = max_dx.abs();
+        let curve = &self.curve;
+        // Repeated and mirrored ranges must stay within a segment of the knot range, like any other range
+        let x = match curve.extrapolator.outside(x) {
+            Some(Outside::Mapped { x, .. }) => x,
+            _ => x,
+        };
+        let (x_min, x_max) = (x - max_dx, x + max_dx);
+        let Some(i) = self.segment_at(x) else {
+            // Constant or linear before and after the knots
+            let (first, last) = match (curve.xs.first(), curve.xs.last()) {
+                (Some(&first), Some(&last)) if curve.xs.len() > 1 => (first, last),
+                _ => return 0.0,
+            };
+            return if x_max <= first || x_min >= last {
+                0.0
+            } else {
+                f32::INFINITY
+            };
+        };
+        if x_min < curve.xs[i] || x_max > curve.xs[i + 1] {
+            return f32::INFINITY;
+        }
+
+        let segment = &curve.segments[i];
+        let [slope, curvature, third] =
+            segment.derivative_bounds(x_min, x_max, curve.max_error, curve.max_iters);
+        let taylor = third / 6.0 * max_dx.powi(3);
+        if let Segment::Bezier(_) = segment {
+            // The sample and the derivatives are taken at a t that misses x by up to max_error, like the sample at
+            // the actual x that the correction is compared to
+            let solve = slope + max_dx * (curvature + 0.5 * max_dx * third);
+            taylor + 2.0 * curve.max_error * solve
+        } else {
+            taylor
+        }
+    }
+
+    /// Index of the segment containing `x`, including its end like [KnotSearch](super::KnotSearch), or `None`
+    /// outside of the knot range
+    fn segment_at(&self, x: f32) -> Option<usize> {
+        let xs = &self.curve.xs;
+        if xs.len() < 2 || x <= xs[0] || x >= xs[xs.len() - 1] {
+            return None;
+        }
+        Some(xs.partition_point(|&knot_x| knot_x < x) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Extrapolation, Knot, KnotInterpolation, TangentSide};
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32, left: f32, right: f32) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Cubic,
+            ..Default::default()
+        }
+        .with_tangent_slope(TangentSide::Left, left)
+        .with_tangent_slope(TangentSide::Right, right)
+    }
+
+    /// Mostly weighted segments, with an unweighted and a linear one
+    fn curves() -> Vec<LookupCurve> {
+        vec![
+            LookupCurve::new(vec![
+                knot(0.0, 0.0, 0.0, 3.0).with_tangent_weight(TangentSide::Right, Some(0.6)),
+                knot(1.0, 1.0, -1.0, -1.0)
+                    .with_tangent_weight(TangentSide::Left, Some(0.2))
+                    .with_tangent_weight(TangentSide::Right, Some(0.45)),
+                knot(2.5, -0.5, 4.0, 0.5),
+                Knot {
+                    interpolation: KnotInterpolation::Linear,
+                    ..knot(3.0, 0.2, 0.0, 0.0)
+                },
+                knot(4.0, 2.0, 0.0, 0.0).with_tangent_weight(TangentSide::Right, Some(0.9)),
+                knot(5.0, 0.0, -8.0, 0.0).with_tangent_weight(TangentSide::Left, Some(0.05)),
+            ]),
+            LookupCurve::new(vec![
+                knot(-2.0, 10.0, 0.0, -20.0).with_tangent_weight(TangentSide::Right, Some(1.0)),
+                knot(-1.0, -5.0, 12.0, 12.0)
+                    .with_tangent_weight(TangentSide::Left, Some(0.8))
+                    .with_tangent_weight(TangentSide::Right, Some(0.1)),
+                knot(0.5, 3.0, 0.0, 0.0).with_tangent_weight(TangentSide::Left, Some(0.7)),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn derivatives_match_finite_differences() {
+        for curve in curves() {
+            let predictor = CurvePredictor::new(&curve);
+            let xs = curve.knots().windows(2).flat_map(|pair| {
+                let (a, b) = (pair[0].position.x, pair[1].position.x);
+                (1..10).map(move |i| a + (b - a) * i as f32 / 10.0)
+            });
+            for x in xs {
+                let sample = predictor.predict(x - 0.25, 0.25);
+                assert!((sample.x - x).abs() < 1e-6);
+                let x = sample.x;
+                assert!((sample.value - curve.lookup(x)).abs() < 1e-5);
+                assert!((sample.slope - curve.slope_at(x, TangentSide::Left)).abs() < 1e-3);
+                let h = 1e-3;
+                let slope_difference = (curve.slope_at(x + h, TangentSide::Left)
+                    - curve.slope_at(x - h, TangentSide::Left))
+                    / (2.0 * h);
+                let scale = 1.0 + sample.second_order_correction.abs();
+                assert!(
+                    (sample.second_order_correction - slope_difference).abs() < 0.05 * scale,
+                    "{x}: {} != {slope_difference}",
+                    sample.second_order_correction
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn corrections_stay_within_the_bound() {
+        for curve in curves() {
+            let predictor = CurvePredictor::new(&curve);
+            let (first, last) = (
+                curve.knots()[0].position.x,
+                curve.knots()[curve.knots().len() - 1].position.x,
+            );
+            let mut finite = 0;
+            for i in -20..=220 {
+                let x = first + (last - first) * i as f32 / 200.0;
+                let sample = predictor.predict(x, 0.0);
+                for max_dx in [0.001, 0.01, 0.05, 0.2] {
+                    let bound = predictor.correction_error_bound(x, max_dx);
+                    if bound.is_infinite() {
+                        continue;
+                    }
+                    finite += 1;
+                    for j in -4..=4 {
+                        let actual_x = x + max_dx * j as f32 / 4.0;
+                        let error = (sample.corrected(actual_x) - curve.lookup(actual_x)).abs();
+                        // Rounding of f32 math, which the bound does not include
+                        let rounding = 1e-5 * (1.0 + sample.value.abs());
+                        assert!(
+                            error <= bound + rounding,
+                            "{x} + {max_dx}: {error} > {bound}"
+                        );
+                    }
+                }
+            }
+            assert!(finite > 500, "{finite}");
+        }
+    }
+
+    #[test]
+    fn bound_is_infinite_across_knots_and_zero_outside() {
+        let curve = &curves()[0];
+        let predictor = CurvePredictor::new(curve);
+        assert_eq!(predictor.correction_error_bound(0.95, 0.1), f32::INFINITY);
+        assert_eq!(predictor.correction_error_bound(1.0, 0.01), f32::INFINITY);
+        assert!(predictor.correction_error_bound(0.5, 0.1).is_finite());
+        assert!(predictor.correction_error_bound(0.5, 0.01) < 1e-3);
+        // Linear segment
+        assert_eq!(predictor.correction_error_bound(3.5, 0.2), 0.0);
+        // Constant before and after the knots
+        assert_eq!(predictor.correction_error_bound(-1.0, 0.5), 0.0);
+        assert_eq!(predictor.correction_error_bound(6.0, 1.0), 0.0);
+        assert_eq!(predictor.correction_error_bound(-0.1, 0.5), f32::INFINITY);
+
+        let outside = predictor.predict(10.0, 2.0);
+        assert_eq!(
+            (
+                outside.value,
+                outside.slope,
+                outside.second_order_correction
+            ),
+            (0.0, 0.0, 0.0)
+        );
+        let empty = CurvePredictor::new(&LookupCurve::default());
+        assert_eq!(empty.predict(1.0, 1.0).value, 0.0);
+        assert_eq!(empty.correction_error_bound(1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn extrapolated_samples_follow_the_curve() {
+        let curve = curves()[0]
+            .clone()
+            .with_extrapolation(Extrapolation::Linear, Extrapolation::PingPong);
+        let predictor = CurvePredictor::new(&curve);
+
+        // Linear before the first knot, with the tangent of the first knot
+        let sample = predictor.predict(-2.0, -1.0);
+        assert_eq!((sample.value, sample.slope), (-9.0, 3.0));
+        assert_eq!(sample.corrected(-4.0), curve.lookup(-4.0));
+        assert_eq!(predictor.correction_error_bound(-3.0, 1.0), 0.0);
+        assert_eq!(predictor.correction_error_bound(-0.5, 1.0), f32::INFINITY);
+
+        // Mirrored after the last knot
+        for x in [5.5, 6.7, 9.2] {
+            let sample = predictor.predict(x, 0.0);
+            assert!((sample.value - curve.lookup(x)).abs() < 1e-5);
+            assert!((sample.slope - curve.slope_at(x, TangentSide::Left)).abs() < 1e-3);
+            assert!((sample.slope + curve.slope_at(10.0 - x, TangentSide::Left)).abs() < 1e-3);
+        }
+        assert!(predictor.correction_error_bound(5.5, 0.1).is_finite());
+        // Crosses the mirror at the last knot
+        assert_eq!(predictor.correction_error_bound(5.05, 0.1), f32::INFINITY);
+    }
+}
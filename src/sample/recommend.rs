@@ -0,0 +1,444 @@
+use std::hint::black_box;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use bevy_math::Vec2;
+
+use super::BakedLookupCurve;
+use crate::{Knot, KnotInterpolation, LookupCache, LookupCurve, Tangent};
+
+/// Highest resolution [LookupCurve::recommend_sampling] bakes with. Curves that need more, like curves with jumps,
+/// are sampled analytically.
+pub const MAX_BAKE_RESOLUTION: usize = 1 << 16;
+/// Number of frames a bake has to pay off in, its cost is spread over them
+const BAKE_PAYOFF_FRAMES: f32 = 60.0;
+/// Lookups timed per measurement in [SamplingCosts::measure]
+const CALIBRATION_LOOKUPS: usize = 4096;
+
+/// Nanoseconds spent by the parts of a lookup, used by [LookupCurve::recommend_sampling]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SamplingCosts {
+    /// Evaluating a constant segment
+    pub constant: f32,
+    /// Evaluating a linear segment
+    pub linear: f32,
+    /// Evaluating an unweighted cubic segment
+    pub cubic: f32,
+    /// Evaluating a weighted cubic segment, which solves for the curve parameter
+    pub weighted_cubic: f32,
+    /// Each halving of the binary search for the segment of a lookup
+    pub search_step: f32,
+    /// Finding the segment with a [LookupCache], for coherent lookups
+    pub cached_search: f32,
+    /// Sampling a [BakedLookupCurve]
+    pub baked: f32,
+}
+
+impl SamplingCosts {
+    /// Costs measured with a short benchmark the first time this is called, then reused for the rest of the process
+    pub fn calibrated() -> &'static SamplingCosts {
+        static COSTS: OnceLock<SamplingCosts> = OnceLock::new();
+        COSTS.get_or_init(SamplingCosts::measure)
+    }
+
+    /// Times lookups on small curves of each kind of segment. Takes around a millisecond in release builds.
+    pub fn measure() -> SamplingCosts {
+        let segment = |interpolation, weight| {
+            let knot = |x: f32, y: f32| Knot {
+                position: Vec2::new(x, y),
+                interpolation,
+                right_tangent: Tangent {
+                    weight,
+                    ..Default::default()
+                },
+                left_tangent: Tangent {
+                    weight,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)])
+        };
+        let time = |lookup: &mut dyn FnMut(f32) -> f32| {
+            let start = Instant::now();
+            for i in 0..CALIBRATION_LOOKUPS {
+                black_box(lookup(black_box(i as f32 / CALIBRATION_LOOKUPS as f32)));
+            }
+            start.elapsed().as_nanos() as f32 / CALIBRATION_LOOKUPS as f32
+        };
+        let per_segment = |curve: LookupCurve| time(&mut |x| curve.lookup(x));
+
+        let linear = per_segment(segment(KnotInterpolation::Linear, None));
+        // The search over 64 knots takes 6 steps, one more than over 2 knots
+        let many = LookupCurve::new(
+            (0..64)
+                .map(|i| Knot {
+                    position: Vec2::new(i as f32 / 63.0, 0.0),
+                    interpolation: KnotInterpolation::Linear,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        let searched = time(&mut |x| many.lookup(x));
+        let mut cache = LookupCache::new();
+        let cached = time(&mut |x| many.lookup_cached(x, &mut cache));
+        let baked = many.bake(256);
+
+        SamplingCosts {
+            constant: per_segment(segment(KnotInterpolation::Constant, None)),
+            linear,
+            cubic: per_segment(segment(KnotInterpolation::Cubic, None)),
+            weighted_cubic: per_segment(segment(KnotInterpolation::Cubic, Some(0.5))),
+            search_step: ((searched - linear) / 5.0).max(0.0),
+            cached_search: (cached - linear).max(0.0),
+            baked: time(&mut |x| baked.sample(x)),
+        }
+    }
+}
+
+/// How to sample a curve, see [LookupCurve::recommend_sampling]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SamplingStrategy {
+    /// [LookupCurve::lookup]
+    Analytic,
+    /// [LookupCurve::lookup_cached], faster than [SamplingStrategy::Analytic] for curves with many knots when
+    /// consecutive lookups are close to each other
+    CachedAnalytic,
+    /// [LookupCurve::bake] with `resolution`, the lowest that meets the required accuracy
+    Baked { resolution: usize },
+}
+
+/// Result of [LookupCurve::recommend_sampling]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SamplingRecommendation {
+    pub strategy: SamplingStrategy,
+    /// Estimated nanoseconds spent sampling per frame. For [SamplingStrategy::Baked] this includes the cost of the
+    /// bake, spread over a second at 60 frames per second.
+    pub frame_cost: f32,
+    /// Bytes used besides the curve
+    pub memory: usize,
+}
+
+/// Expected use of a curve, see [LookupCurve::auto_sampler]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SamplingParams {
+    pub expected_queries_per_frame: u32,
+    /// Largest difference in y to [LookupCurve::lookup] that is acceptable
+    pub accuracy_required: f32,
+}
+
+/// Samples a curve with the strategy recommended by [LookupCurve::recommend_sampling], see
+/// [LookupCurve::auto_sampler]
+#[derive(Clone, Debug)]
+pub enum CurveSampler<'a> {
+    Analytic(&'a LookupCurve),
+    CachedAnalytic(&'a LookupCurve, LookupCache),
+    Baked(BakedLookupCurve),
+}
+
+impl CurveSampler<'_> {
+    /// Find y for given x on the curve
+    #[inline]
+    pub fn sample(&mut self, x: f32) -> f32 {
+        match self {
+            CurveSampler::Analytic(curve) => curve.lookup(x),
+            CurveSampler::CachedAnalytic(curve, cache) => curve.lookup_cached(x, cache),
+            CurveSampler::Baked(baked) => baked.sample(x),
+        }
+    }
+
+    pub fn strategy(&self) -> SamplingStrategy {
+        match self {
+            CurveSampler::Analytic(_) => SamplingStrategy::Analytic,
+            CurveSampler::CachedAnalytic(..) => SamplingStrategy::CachedAnalytic,
+            CurveSampler::Baked(baked) => SamplingStrategy::Baked {
+                resolution: baked.resolution(),
+            },
+        }
+    }
+}
+
+impl LookupCurve {
+    /// Recommends how to sample the curve `expected_queries_per_frame` times per frame, with at most
+    /// `accuracy_required` difference in y to [LookupCurve::lookup].
+    ///
+    /// Estimates the cost of a lookup from the kinds and widths of the segments of the curve and the number of knots,
+    /// using [SamplingCosts::calibrated]. Finding the resolution to bake with samples the curve many times, so call
+    /// this once when the curve is loaded or changed, not every frame.
+    pub fn recommend_sampling(
+        &self,
+        expected_queries_per_frame: u32,
+        accuracy_required: f32,
+    ) -> SamplingRecommendation {
+        self.recommend_sampling_with_costs(
+            expected_queries_per_frame,
+            accuracy_required,
+            SamplingCosts::calibrated(),
+        )
+    }
+
+    /// [LookupCurve::recommend_sampling] with the given costs instead of measured ones
+    pub fn recommend_sampling_with_costs(
+        &self,
+        expected_queries_per_frame: u32,
+        accuracy_required: f32,
+        costs: &SamplingCosts,
+    ) -> SamplingRecommendation {
+        let queries = expected_queries_per_frame as f32;
+        let segment = self.mean_segment_cost(costs);
+        let search_steps = (self.knots.len().max(1) as f32).log2().ceil();
+        let lookup = segment + search_steps * costs.search_step;
+        let analytic = SamplingRecommendation {
+            strategy: SamplingStrategy::Analytic,
+            frame_cost: queries * lookup,
+            memory: 0,
+        };
+        let cached = SamplingRecommendation {
+            strategy: SamplingStrategy::CachedAnalytic,
+            frame_cost: queries * (segment + costs.cached_search),
+            memory: std::mem::size_of::<LookupCache>(),
+        };
+        let baked = self
+            .min_bake_resolution(accuracy_required)
+            .map(|resolution| SamplingRecommendation {
+                strategy: SamplingStrategy::Baked { resolution },
+                frame_cost: queries * costs.baked + resolution as f32 * lookup / BAKE_PAYOFF_FRAMES,
+                memory: resolution * std::mem::size_of::<f32>(),
+            });
+
+        // Ties go to the earlier strategy, which needs less memory
+        [Some(analytic), Some(cached), baked]
+            .into_iter()
+            .flatten()
+            .reduce(|best, next| {
+                if next.frame_cost < best.frame_cost {
+                    next
+                } else {
+                    best
+                }
+            })
+            .unwrap()
+    }
+
+    /// Samples the curve with the strategy of [LookupCurve::recommend_sampling], baking the curve if recommended
+    pub fn auto_sampler(&self, params: SamplingParams) -> CurveSampler<'_> {
+        let recommendation =
+            self.recommend_sampling(params.expected_queries_per_frame, params.accuracy_required);
+        self.sampler(recommendation.strategy)
+    }
+
+    /// Samples the curve with `strategy`
+    pub fn sampler(&self, strategy: SamplingStrategy) -> CurveSampler<'_> {
+        match strategy {
+            SamplingStrategy::Analytic => CurveSampler::Analytic(self),
+            SamplingStrategy::CachedAnalytic => {
+                CurveSampler::CachedAnalytic(self, LookupCache::new())
+            }
+            SamplingStrategy::Baked { resolution } => CurveSampler::Baked(self.bake(resolution)),
+        }
+    }
+
+    /// Lowest resolution for [LookupCurve::bake] with a [LookupCurve::bake_error] of at most `accuracy`, up to
+    /// [MAX_BAKE_RESOLUTION]. Doubles the resolution until the error is small enough, then bisects.
+    pub fn min_bake_resolution(&self, accuracy: f32) -> Option<usize> {
+        if accuracy.is_nan() || accuracy < 0.0 {
+            return None;
+        }
+        let meets = |resolution: usize| self.bake_error(resolution) <= accuracy;
+        let mut high = 2;
+        while !meets(high) {
+            if high >= MAX_BAKE_RESOLUTION {
+                return None;
+            }
+            high = (high * 2).min(MAX_BAKE_RESOLUTION);
+        }
+        let mut low = high / 2;
+        // Resolutions up to low are too coarse, high is fine
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if meets(mid) {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Some(high)
+    }
+
+    /// Cost of evaluating a segment, averaged over the domain of the curve
+    fn mean_segment_cost(&self, costs: &SamplingCosts) -> f32 {
+        let cost = |knot_a: &Knot, knot_b: &Knot| match knot_a.interpolation {
+            KnotInterpolation::Constant | KnotInterpolation::ConstantNext => costs.constant,
+            KnotInterpolation::Linear => costs.linear,
+            // A power takes about as long as a cubic polynomial
+            KnotInterpolation::Power { .. } => costs.cubic,
+            KnotInterpolation::Cubic
+                if knot_a.right_tangent.weight.is_some()
+                    || knot_b.left_tangent.weight.is_some() =>
+            {
+                costs.weighted_cubic
+            }
+            KnotInterpolation::Cubic => costs.cubic,
+        };
+        let (width, weighted) = self
+            .knots
+            .windows(2)
+            .map(|pair| {
+                let width = pair[1].position.x - pair[0].position.x;
+                (width, width * cost(&pair[0], &pair[1]))
+            })
+            .fold((0.0, 0.0), |(a, b), (width, weighted)| {
+                (a + width, b + weighted)
+            });
+        if width > 0.0 {
+            weighted / width
+        } else {
+            costs.constant
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TangentMode;
+
+    const COSTS: SamplingCosts = SamplingCosts {
+        constant: 2.0,
+        linear: 4.0,
+        cubic: 8.0,
+        weighted_cubic: 60.0,
+        search_step: 3.0,
+        cached_search: 6.0,
+        baked: 3.0,
+    };
+
+    /// y = x² over 0..=1, as a single unweighted cubic segment
+    fn parabola() -> LookupCurve {
+        let tangent = |slope| Tangent {
+            slope,
+            mode: TangentMode::Free,
+            weight: None,
+        };
+        LookupCurve::new(vec![
+            Knot {
+                position: Vec2::ZERO,
+                interpolation: KnotInterpolation::Cubic,
+                right_tangent: tangent(0.0),
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::ONE,
+                left_tangent: tangent(2.0),
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn bake_resolution_meets_the_interpolation_error_bound() {
+        // Interpolating x² linearly with step h is off by at most h²/4, at the middle of each step
+        let curve = parabola();
+        for (accuracy, steps) in [(1.01e-4, 50), (1.01e-2, 5), (6.26e-2, 2)] {
+            let resolution = curve.min_bake_resolution(accuracy).unwrap();
+            assert_eq!(resolution, steps + 1, "accuracy {accuracy}");
+            let h = 1.0 / steps as f32;
+            assert!((curve.bake_error(resolution) - h * h / 4.0).abs() < 1e-6);
+            assert!(curve.bake_error(resolution - 1) > accuracy);
+        }
+
+        // Linear curves are exact at the lowest resolution
+        let linear = LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::new(2.0, 1.0),
+                ..Default::default()
+            },
+        ]);
+        assert_eq!(linear.min_bake_resolution(0.0), Some(2));
+    }
+
+    #[test]
+    fn jumps_can_not_be_baked() {
+        let curve = LookupCurve::new(vec![
+            Knot {
+                interpolation: KnotInterpolation::Constant,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::new(0.5, 1.0),
+                interpolation: KnotInterpolation::Constant,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::ONE,
+                ..Default::default()
+            },
+        ]);
+        assert_eq!(curve.min_bake_resolution(0.1), None);
+        let recommendation = curve.recommend_sampling_with_costs(1_000_000, 0.1, &COSTS);
+        assert_eq!(recommendation.strategy, SamplingStrategy::Analytic);
+    }
+
+    #[test]
+    fn recommendation_follows_queries_and_accuracy() {
+        let curve = parabola();
+        let strategy = |queries, accuracy| {
+            curve
+                .recommend_sampling_with_costs(queries, accuracy, &COSTS)
+                .strategy
+        };
+        // Lookups take 8ns for the segment and 3ns for one search step. A bake of 51 samples costs 51 lookups,
+        // spread over 60 frames.
+        assert_eq!(strategy(1, 1.01e-4), SamplingStrategy::Analytic);
+        assert_eq!(
+            strategy(10_000, 1.01e-4),
+            SamplingStrategy::Baked { resolution: 51 }
+        );
+        // Tighter accuracy needs larger bakes, which take more queries to pay off
+        assert_eq!(
+            strategy(10, 1.01e-4),
+            SamplingStrategy::Baked { resolution: 51 }
+        );
+        assert_eq!(strategy(10, 1e-6), SamplingStrategy::Analytic);
+
+        let recommendation = curve.recommend_sampling_with_costs(10_000, 1.01e-4, &COSTS);
+        assert_eq!(recommendation.memory, 51 * 4);
+        assert!((recommendation.frame_cost - (30_000.0 + 51.0 * 11.0 / 60.0)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn cached_lookups_are_recommended_for_many_knots() {
+        let curve = LookupCurve::new(
+            (0..1024)
+                .map(|i| Knot {
+                    position: Vec2::new(i as f32, (i % 2) as f32),
+                    interpolation: KnotInterpolation::Linear,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        // Binary search takes 10 steps of 3ns, the cache 6ns, and the zigzag can not be baked smaller than the knots
+        let recommendation = curve.recommend_sampling_with_costs(1, 1e-3, &COSTS);
+        assert_eq!(recommendation.strategy, SamplingStrategy::CachedAnalytic);
+        assert_eq!(recommendation.frame_cost, 10.0);
+    }
+
+    #[test]
+    fn auto_sampler_is_within_the_accuracy() {
+        let curve = parabola();
+        let params = SamplingParams {
+            expected_queries_per_frame: 100_000,
+            accuracy_required: 1e-3,
+        };
+        let mut sampler = curve.auto_sampler(params);
+        for i in 0..=100 {
+            let x = i as f32 / 100.0;
+            assert!((sampler.sample(x) - curve.lookup(x)).abs() <= 1e-3);
+        }
+
+        let costs = SamplingCosts::calibrated();
+        assert!(costs.linear.is_finite() && costs.linear >= 0.0);
+        assert!(std::ptr::eq(costs, SamplingCosts::calibrated()));
+    }
+}
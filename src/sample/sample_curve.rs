@@ -0,0 +1,244 @@
+use super::{BakedLookupCurve, PrecomputedLookupCurve};
+use crate::stats::CurveStats;
+use crate::LookupCurve;
+
+/// Curves mapping x to y, implemented by [LookupCurve] and the curves made from it for faster sampling, so that
+/// code can sample any of them.
+///
+/// Use [SampleCurve::map] for outputs other than `f32`, like a uniform scale or a blend between two colors.
+pub trait SampleCurve {
+    /// y at `x`. Outside of the [domain](Self::domain) y is extrapolated with the settings of the curve.
+    fn sample(&self, x: f32) -> f32;
+
+    /// Range of x spanned by the knots, `None` for curves without knots
+    fn domain(&self) -> Option<(f32, f32)>;
+
+    /// y at `x` clamped to the domain, so that the curve is not extrapolated
+    fn sample_clamped(&self, x: f32) -> f32 {
+        match self.domain() {
+            Some((min, max)) => self.sample(x.clamp(min, max)),
+            None => self.sample(x),
+        }
+    }
+
+    /// y at a normalized `t`, where 0 maps to the start and 1 to the end of the domain, see
+    /// [LookupCurve::sample_normalized]
+    fn sample_normalized(&self, t: f32) -> f32 {
+        match self.domain() {
+            Some(domain) => self.sample(normalized_x(domain, t)),
+            None => 0.0,
+        }
+    }
+
+    /// The curve with `f` applied to its output. Map a reference to keep the curve.
+    fn map<T, F: Fn(f32) -> T>(self, f: F) -> MappedCurve<Self, F>
+    where
+        Self: Sized,
+    {
+        MappedCurve { curve: self, f }
+    }
+}
+
+impl<C: SampleCurve + ?Sized> SampleCurve for &C {
+    #[inline]
+    fn sample(&self, x: f32) -> f32 {
+        (**self).sample(x)
+    }
+
+    fn domain(&self) -> Option<(f32, f32)> {
+        (**self).domain()
+    }
+}
+
+/// x within `domain` for a normalized `t`
+#[inline]
+pub(crate) fn normalized_x((min, max): (f32, f32), t: f32) -> f32 {
+    if t >= 1.0 {
+        // Avoid rounding errors at the end, to arrive exactly at the last knot
+        max + (t - 1.0) * (max - min)
+    } else {
+        min + t * (max - min)
+    }
+}
+
+impl SampleCurve for LookupCurve {
+    #[inline]
+    fn sample(&self, x: f32) -> f32 {
+        self.lookup(x)
+    }
+
+    fn domain(&self) -> Option<(f32, f32)> {
+        LookupCurve::domain(self)
+    }
+}
+
+impl SampleCurve for PrecomputedLookupCurve {
+    #[inline]
+    fn sample(&self, x: f32) -> f32 {
+        self.lookup(x)
+    }
+
+    fn domain(&self) -> Option<(f32, f32)> {
+        Some((*self.xs.first()?, *self.xs.last()?))
+    }
+}
+
+impl SampleCurve for BakedLookupCurve {
+    #[inline]
+    fn sample(&self, x: f32) -> f32 {
+        BakedLookupCurve::sample(self, x)
+    }
+
+    /// The range the samples were taken over, `(0, 0)` for curves baked without knots
+    fn domain(&self) -> Option<(f32, f32)> {
+        Some((self.min_x, self.max_x))
+    }
+}
+
+impl LookupCurve {
+    /// Range of x from the first to the last knot, `None` for curves without knots
+    pub fn domain(&self) -> Option<(f32, f32)> {
+        let (first, last) = (self.knots.first()?, self.knots.last()?);
+        Some((first.position.x, last.position.x))
+    }
+
+    /// Smallest and largest y of the curve within its [domain](Self::domain), including the extrema of cubic
+    /// segments. `None` for curves without knots.
+    ///
+    /// This analyzes every segment, see [CurveStats].
+    pub fn value_range(&self) -> Option<(f32, f32)> {
+        CurveStats::compute(self).range
+    }
+}
+
+/// A [SampleCurve] with a function applied to its output, created with [SampleCurve::map]
+#[derive(Clone, Copy, Debug)]
+pub struct MappedCurve<C, F> {
+    curve: C,
+    f: F,
+}
+
+impl<C: SampleCurve, T, F: Fn(f32) -> T> MappedCurve<C, F> {
+    /// The mapped output at `x`, see [SampleCurve::sample]
+    #[inline]
+    pub fn sample(&self, x: f32) -> T {
+        (self.f)(self.curve.sample(x))
+    }
+
+    /// The mapped output at `x` clamped to the domain, see [SampleCurve::sample_clamped]
+    pub fn sample_clamped(&self, x: f32) -> T {
+        (self.f)(self.curve.sample_clamped(x))
+    }
+
+    /// The mapped output at a normalized `t`, see [SampleCurve::sample_normalized]
+    pub fn sample_normalized(&self, t: f32) -> T {
+        (self.f)(self.curve.sample_normalized(t))
+    }
+
+    /// See [SampleCurve::domain]
+    pub fn domain(&self) -> Option<(f32, f32)> {
+        self.curve.domain()
+    }
+
+    /// The curve with `g` applied to the output of this curve
+    pub fn map<U>(self, g: impl Fn(T) -> U) -> MappedCurve<C, impl Fn(f32) -> U> {
+        let f = self.f;
+        MappedCurve {
+            curve: self.curve,
+            f: move |y| g(f(y)),
+        }
+    }
+
+    /// The curve that was mapped
+    pub fn inner(&self) -> &C {
+        &self.curve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::{Vec2, Vec3};
+
+    use super::*;
+    use crate::tween::Tweenable;
+    use crate::{Extrapolation, Knot, KnotInterpolation};
+
+    fn curve() -> LookupCurve {
+        let mut curve = LookupCurve::new(vec![
+            Knot {
+                position: Vec2::new(1.0, 0.0),
+                interpolation: KnotInterpolation::Linear,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::new(3.0, 1.0),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            },
+            Knot {
+                position: Vec2::new(5.0, 0.5),
+                ..Default::default()
+            },
+        ]);
+        curve.extrapolate_before = Extrapolation::Linear;
+        curve
+    }
+
+    /// Samples any curve, to check that the variants of a curve agree
+    fn samples(curve: impl SampleCurve) -> Vec<f32> {
+        [-1.0, 1.0, 2.0, 3.5, 5.0, 6.0]
+            .into_iter()
+            .map(|x| curve.sample(x))
+            .collect()
+    }
+
+    #[test]
+    fn variants_sample_alike() {
+        let curve = curve();
+        let expected = samples(&curve);
+        assert_eq!(samples(curve.precompute()), expected);
+        let baked = samples(curve.bake(4096));
+        for (baked, expected) in baked.iter().zip(&expected) {
+            assert!((baked - expected).abs() < 1e-3, "{baked} != {expected}");
+        }
+
+        assert_eq!(curve.domain(), Some((1.0, 5.0)));
+        assert_eq!(curve.precompute().domain(), Some((1.0, 5.0)));
+        assert_eq!(curve.bake(16).domain(), Some((1.0, 5.0)));
+        assert_eq!(LookupCurve::default().domain(), None);
+        assert_eq!(LookupCurve::default().value_range(), None);
+    }
+
+    #[test]
+    fn clamps_and_normalizes() {
+        let curve = curve();
+        assert_eq!(curve.sample(-1.0), -1.0);
+        assert_eq!(curve.sample_clamped(-1.0), 0.0);
+        assert_eq!(curve.sample_clamped(6.0), 0.5);
+        let precomputed = curve.precompute();
+        assert_eq!(
+            SampleCurve::sample_normalized(&precomputed, 0.25),
+            curve.sample_normalized(0.25)
+        );
+        assert_eq!(SampleCurve::sample_normalized(&precomputed, 1.0), 0.5);
+
+        assert_eq!(curve.value_range(), Some((0.0, 1.0)));
+    }
+
+    #[test]
+    fn maps_to_other_outputs() {
+        let curve = curve();
+        let scale = (&curve).map(Vec3::splat);
+        assert_eq!(scale.sample(3.0), Vec3::ONE);
+        assert_eq!(scale.domain(), Some((1.0, 5.0)));
+
+        let from = Vec3::new(1.0, 0.0, 0.0);
+        let to = Vec3::new(0.0, 0.0, 1.0);
+        let blend = curve
+            .bake(64)
+            .map(|t| from.tween(&to, t))
+            .map(|color| color.z);
+        assert_eq!(blend.sample_normalized(0.0), 0.0);
+        assert_eq!(blend.sample_clamped(9.0), 0.5);
+    }
+}
@@ -0,0 +1,210 @@
+use bevy_math::Vec2;
+
+/// Playback state for scrubbing through the domain of a curve, for example against a [RecordedTrack].
+///
+/// The position only changes through [ScrubPlayback::seek] and [ScrubPlayback::advance], which report
+/// whether it changed, so that previews can be driven deterministically from the changes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct ScrubPlayback {
+    x: f32,
+    pub playing: bool,
+    /// x units per second of playback, negative values play backwards
+    pub speed: f32,
+    /// Range of x that playback wraps around in, as `(start, end)`. Without a range playback continues indefinitely.
+    pub loop_range: Option<(f32, f32)>,
+}
+
+impl Default for ScrubPlayback {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            playing: false,
+            speed: 1.0,
+            loop_range: None,
+        }
+    }
+}
+
+impl ScrubPlayback {
+    /// The current position
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    /// Moves to `x`, regardless of the loop range. Returns `true` if the position changed.
+    pub fn seek(&mut self, x: f32) -> bool {
+        let changed = self.x != x;
+        self.x = x;
+        changed
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn toggle(&mut self) {
+        self.playing = !self.playing;
+    }
+
+    /// Advances playback by `seconds` if playing, wrapping around within the loop range.
+    /// Returns `true` if the position changed.
+    pub fn advance(&mut self, seconds: f32) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let mut x = self.x + self.speed * seconds;
+        if let Some((start, end)) = self.loop_range {
+            let width = end - start;
+            x = if width > 0.0 {
+                start + (x - start).rem_euclid(width)
+            } else {
+                start
+            };
+        }
+        self.seek(x)
+    }
+}
+
+/// Recorded values of a gameplay session, shown as a read-only track while scrubbing
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct RecordedTrack {
+    /// Recorded `(x, value)` points, sorted by x
+    points: Vec<Vec2>,
+}
+
+impl RecordedTrack {
+    /// Constructs a track from recorded `(x, value)` points, in any order. Points that are not finite, like NaN values
+    /// of a failed measurement, are dropped.
+    pub fn new(mut points: Vec<Vec2>) -> Self {
+        points.retain(|point| point.is_finite());
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+        Self { points }
+    }
+
+    /// The recorded points, sorted by x
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// x of the first and last point, `None` if the track is empty
+    pub fn domain(&self) -> Option<(f32, f32)> {
+        Some((self.points.first()?.x, self.points.last()?.x))
+    }
+
+    /// Returns the x of the recorded point nearest to `x`, `None` if the track is empty
+    pub fn snap(&self, x: f32) -> Option<f32> {
+        let i = self.points.partition_point(|point| point.x < x);
+        let after = self.points.get(i).map(|point| point.x);
+        let before = i.checked_sub(1).map(|i| self.points[i].x);
+        match (before, after) {
+            (Some(before), Some(after)) if x - before <= after - x => Some(before),
+            (_, Some(after)) => Some(after),
+            (before, None) => before,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Advances `playback` by `frames` of `dt` seconds, returning the positions after each change
+    fn run(playback: &mut ScrubPlayback, frames: usize, dt: f32) -> Vec<f32> {
+        let mut positions = Vec::new();
+        for _ in 0..frames {
+            if playback.advance(dt) {
+                positions.push(playback.x());
+            }
+        }
+        positions
+    }
+
+    fn assert_positions(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn paused_playback_does_not_move() {
+        let mut playback = ScrubPlayback::default();
+        assert!(run(&mut playback, 10, 0.1).is_empty());
+        assert!(playback.seek(2.0));
+        assert!(!playback.seek(2.0));
+        assert_eq!(playback.x(), 2.0);
+    }
+
+    #[test]
+    fn wraps_at_loop_boundary_and_follows_speed_changes() {
+        let mut playback = ScrubPlayback {
+            speed: 2.0,
+            loop_range: Some((1.0, 2.0)),
+            ..Default::default()
+        };
+        playback.seek(1.5);
+        playback.play();
+        assert_positions(&run(&mut playback, 3, 0.1), &[1.7, 1.9, 1.1]);
+
+        playback.speed = -5.0;
+        assert_positions(&run(&mut playback, 2, 0.1), &[1.6, 1.1]);
+        assert_positions(&run(&mut playback, 1, 0.1), &[1.6]);
+
+        playback.pause();
+        assert!(run(&mut playback, 5, 0.1).is_empty());
+        playback.toggle();
+        playback.speed = 0.0;
+        assert!(run(&mut playback, 5, 0.1).is_empty());
+    }
+
+    #[test]
+    fn seeking_outside_loop_range_wraps_on_next_advance() {
+        let mut playback = ScrubPlayback {
+            playing: true,
+            loop_range: Some((0.0, 1.0)),
+            ..Default::default()
+        };
+        playback.seek(3.25);
+        assert_positions(&run(&mut playback, 1, 0.25), &[0.5]);
+
+        playback.loop_range = Some((2.0, 2.0));
+        assert_positions(&run(&mut playback, 2, 0.25), &[2.0]);
+    }
+
+    #[test]
+    fn snaps_to_nearest_recorded_sample() {
+        let track = RecordedTrack::new(vec![
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 2.0),
+        ]);
+        assert_eq!(track.domain(), Some((0.0, 2.0)));
+        assert_eq!(track.snap(-1.0), Some(0.0));
+        assert_eq!(track.snap(0.4), Some(0.0));
+        assert_eq!(track.snap(0.5), Some(0.0));
+        assert_eq!(track.snap(0.6), Some(1.0));
+        assert_eq!(track.snap(1.0), Some(1.0));
+        assert_eq!(track.snap(5.0), Some(2.0));
+        assert_eq!(RecordedTrack::default().snap(1.0), None);
+    }
+
+    #[test]
+    fn drops_points_that_are_not_finite() {
+        let track = RecordedTrack::new(vec![
+            Vec2::new(1.0, 0.5),
+            Vec2::new(f32::NAN, 1.0),
+            Vec2::new(0.0, f32::NAN),
+            Vec2::new(f32::INFINITY, 2.0),
+            Vec2::new(0.5, 3.0),
+        ]);
+        assert_eq!(track.points(), [Vec2::new(0.5, 3.0), Vec2::new(1.0, 0.5)]);
+        assert_eq!(track.domain(), Some((0.5, 1.0)));
+        assert_eq!(RecordedTrack::new(vec![Vec2::NAN]).domain(), None);
+    }
+}
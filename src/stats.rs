@@ -0,0 +1,214 @@
+use crate::{CubicSegment, KnotInterpolation, LookupCurve};
+
+/// Direction of a [LookupCurve] over its whole domain
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub enum Monotonicity {
+    /// y never changes
+    Constant,
+    /// y never decreases
+    Increasing,
+    /// y never increases
+    Decreasing,
+    /// y both increases and decreases
+    Mixed,
+}
+
+/// Statistics of a [LookupCurve], computed by analyzing each segment
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct CurveStats {
+    pub knot_count: usize,
+    /// x of the first and last knot, `None` if the curve has no knots
+    pub domain: Option<(f32, f32)>,
+    /// Exact min and max y of the curve, including cubic overshoot between knots. `None` if the curve has no knots
+    pub range: Option<(f32, f32)>,
+    pub monotonicity: Monotonicity,
+    /// Sum of all absolute changes in y along the curve
+    pub total_variation: f32,
+    /// Number of cubic segments where the tangent weights makes the segment stall or fold back on the x axis.
+    ///
+    /// Lookups on these segments rely on the solver correcting its guess, and might not exactly match the drawn curve.
+    pub corrections: usize,
+}
+
+impl CurveStats {
+    /// Computes the stats for `curve`. This analyzes every segment, so consider caching the result
+    /// using [LookupCurve::generation].
+    pub fn compute(curve: &LookupCurve) -> Self {
+        let knots = curve.knots();
+        let (Some(first), Some(last)) = (knots.first(), knots.last()) else {
+            return Self {
+                knot_count: 0,
+                domain: None,
+                range: None,
+                monotonicity: Monotonicity::Constant,
+                total_variation: 0.0,
+                corrections: 0,
+            };
+        };
+
+        // All y values where the curve changes direction or jumps, in order of x
+        let mut critical_ys = vec![first.position.y];
+        let mut corrections = 0;
+        for pair in knots.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if matches!(a.interpolation, KnotInterpolation::Cubic) {
+                let segment = CubicSegment::from_bezier_points(a.compute_bezier_to(b));
+                critical_ys.extend(
+                    segment
+                        .extrema_y()
+                        .into_iter()
+                        .flatten()
+                        .map(|t| segment.position(t).y),
+                );
+                if segment.folds_back_x() {
+                    corrections += 1;
+                }
+            }
+            critical_ys.push(b.position.y);
+        }
+
+        let (min, max) = critical_ys
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &y| {
+                (min.min(y), max.max(y))
+            });
+
+        let mut increases = false;
+        let mut decreases = false;
+        let mut total_variation = 0.0;
+        for pair in critical_ys.windows(2) {
+            let dy = pair[1] - pair[0];
+            increases |= dy > 0.0;
+            decreases |= dy < 0.0;
+            total_variation += dy.abs();
+        }
+
+        Self {
+            knot_count: knots.len(),
+            domain: Some((first.position.x, last.position.x)),
+            range: Some((min, max)),
+            monotonicity: match (increases, decreases) {
+                (false, false) => Monotonicity::Constant,
+                (true, false) => Monotonicity::Increasing,
+                (false, true) => Monotonicity::Decreasing,
+                (true, true) => Monotonicity::Mixed,
+            },
+            total_variation,
+            corrections,
+        }
+    }
+}
+
+impl CubicSegment {
+    /// Parametric values in `(0, 1)` where the derivative of y is zero, in increasing order.
+    pub(crate) fn extrema_y(&self) -> [Option<f32>; 2] {
+        let [_, b, c, d] = self.coeff;
+        quadratic_roots_in_unit(3.0 * d.y, 2.0 * c.y, b.y)
+    }
+
+    /// Returns `true` if x stops increasing anywhere within the segment
+    fn folds_back_x(&self) -> bool {
+        let velocity_x = |t: f32| self.velocity(t).x;
+        let [_, _, c, d] = self.coeff;
+        // Vertex of the quadratic derivative
+        let vertex = if d.x != 0.0 { -c.x / (3.0 * d.x) } else { 0.0 };
+        velocity_x(0.0) < 0.0
+            || velocity_x(1.0) < 0.0
+            || (vertex > 0.0 && vertex < 1.0 && velocity_x(vertex) <= 0.0)
+    }
+}
+
+/// Roots of `a * t^2 + b * t + c` strictly within `(0, 1)`, in increasing order.
+fn quadratic_roots_in_unit(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
+    let in_unit = |t: f32| (t > 0.0 && t < 1.0).then_some(t);
+
+    if a.abs() < f32::EPSILON {
+        if b.abs() < f32::EPSILON {
+            return [None, None];
+        }
+        return [in_unit(-c / b), None];
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return [None, None];
+    }
+    let sqrt = discriminant.sqrt();
+    let (t0, t1) = ((-b - sqrt) / (2.0 * a), (-b + sqrt) / (2.0 * a));
+    let (t0, t1) = (t0.min(t1), t0.max(t1));
+    [in_unit(t0), in_unit(t1).filter(|&t| t != t0)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, Tangent};
+    use bevy_math::Vec2;
+
+    fn knot(x: f32, y: f32, interpolation: KnotInterpolation) -> Knot {
+        Knot {
+            position: Vec2::new(x, y),
+            interpolation,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn empty_curve() {
+        let stats = CurveStats::compute(&LookupCurve::default());
+        assert_eq!(stats.knot_count, 0);
+        assert_eq!(stats.domain, None);
+        assert_eq!(stats.range, None);
+        assert_eq!(stats.monotonicity, Monotonicity::Constant);
+    }
+
+    #[test]
+    fn linear_and_constant_segments() {
+        let curve = LookupCurve::new(vec![
+            knot(0.0, 0.0, KnotInterpolation::Linear),
+            knot(1.0, 2.0, KnotInterpolation::Constant),
+            knot(2.0, 3.0, KnotInterpolation::Linear),
+        ]);
+        let stats = CurveStats::compute(&curve);
+        assert_eq!(stats.knot_count, 3);
+        assert_eq!(stats.domain, Some((0.0, 2.0)));
+        assert_eq!(stats.range, Some((0.0, 3.0)));
+        assert_eq!(stats.monotonicity, Monotonicity::Increasing);
+        assert_eq!(stats.total_variation, 3.0);
+        assert_eq!(stats.corrections, 0);
+    }
+
+    #[test]
+    fn cubic_overshoot_is_included() {
+        let mut start = knot(0.0, 0.0, KnotInterpolation::Cubic);
+        start.right_tangent.slope = 4.0;
+        let curve = LookupCurve::new(vec![start, knot(1.0, 1.0, KnotInterpolation::Cubic)]);
+        let stats = CurveStats::compute(&curve);
+        let (min, max) = stats.range.unwrap();
+        assert_eq!(min, 0.0);
+        assert!(max > 1.0);
+        assert_eq!(stats.monotonicity, Monotonicity::Mixed);
+        assert!((stats.total_variation - (max + (max - 1.0))).abs() < 1e-5);
+    }
+
+    #[test]
+    fn detects_folding_segments() {
+        let weighted = Tangent {
+            weight: Some(1.0),
+            ..Default::default()
+        };
+        let curve = LookupCurve::new(vec![
+            Knot {
+                right_tangent: weighted,
+                ..knot(0.0, 0.0, KnotInterpolation::Cubic)
+            },
+            Knot {
+                left_tangent: weighted,
+                ..knot(1.0, 1.0, KnotInterpolation::Cubic)
+            },
+        ]);
+        assert_eq!(CurveStats::compute(&curve).corrections, 1);
+    }
+}
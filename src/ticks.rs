@@ -0,0 +1,88 @@
+/// Values of grid lines that are multiples of `step`, starting at or just after `start` and covering `extent`.
+///
+/// Used for the grid of the editor, and the axes of plots. Yields nothing if `step` is not positive.
+pub(crate) fn grid_lines(start: f32, extent: f32, step: f32) -> impl Iterator<Item = f32> {
+    let (offset, count) = if step > 0.0 && step.is_finite() && extent.is_finite() {
+        (start % step, (extent / step).ceil() as i32 + 1)
+    } else {
+        (0.0, 0)
+    };
+    (0..count).map(move |i| start + (i as f32) * step - offset)
+}
+
+/// Returns a "nice" step (1, 2 or 5 times a power of ten) giving at most `max_count` steps over `extent`
+pub(crate) fn nice_step(extent: f32, max_count: u32) -> f32 {
+    let extent = extent.abs();
+    if extent == 0.0 || !extent.is_finite() || max_count == 0 {
+        return 1.0;
+    }
+    // Allow for rounding errors, so that an extent of 1 in 10 steps gives 0.1 and not 0.2
+    let rough = extent as f64 / max_count as f64 * (1.0 - 1e-5);
+    let exponent = rough.log10().floor() as i32;
+    (exponent - 1..=exponent + 1)
+        .flat_map(|e| [1.0, 2.0, 5.0].map(|m| m * 10f64.powi(e)))
+        .find(|step| *step >= rough)
+        .unwrap_or(10f64.powi(exponent + 1)) as f32
+}
+
+/// [nice_step] of displayed values, for `extent` of stored values and a display scale of `unit_scale`, converted back
+/// to stored values. The steps are nice numbers in the units the values are shown in, like 200 for milliseconds.
+#[cfg_attr(not(feature = "editor_egui"), allow(dead_code))]
+pub(crate) fn display_step(extent: f32, unit_scale: f32, max_count: u32) -> f32 {
+    let unit_scale = unit_scale.abs();
+    if unit_scale == 0.0 || !unit_scale.is_finite() {
+        return nice_step(extent, max_count);
+    }
+    nice_step(extent * unit_scale, max_count) / unit_scale
+}
+
+/// Number of decimals needed to tell apart values that are `step` apart
+pub(crate) fn step_precision(step: f32) -> usize {
+    if step <= 0.0 || !step.is_finite() {
+        return 0;
+    }
+    // Allow for rounding errors, 0.01 is stored as slightly less than 0.01
+    (-(step.log10() + 1e-4).floor()).max(0.0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_lines_cover_extent() {
+        let lines: Vec<f32> = grid_lines(0.25, 1.0, 0.5).collect();
+        assert_eq!(lines, vec![0.0, 0.5, 1.0]);
+        let lines: Vec<f32> = grid_lines(-1.0, 2.0, 1.0).collect();
+        assert_eq!(lines, vec![-1.0, 0.0, 1.0]);
+        assert_eq!(grid_lines(0.0, 1.0, 0.0).count(), 0);
+        assert_eq!(grid_lines(0.0, f32::INFINITY, 1.0).count(), 0);
+    }
+
+    #[test]
+    fn nice_steps() {
+        assert_eq!(nice_step(1.0, 10), 0.1);
+        assert_eq!(nice_step(1.0, 4), 0.5);
+        assert_eq!(nice_step(30.0, 8), 5.0);
+        assert_eq!(nice_step(-30.0, 8), 5.0);
+        assert_eq!(nice_step(1500.0, 5), 500.0);
+        assert_eq!(nice_step(0.0, 5), 1.0);
+    }
+
+    #[test]
+    fn display_steps_are_nice_in_display_units() {
+        // Seconds shown as milliseconds
+        assert_eq!(display_step(1.0, 1000.0, 5), 0.2);
+        assert_eq!(display_step(1000.0, 1.0, 5), 200.0);
+        assert_eq!(display_step(1.0, 0.0, 10), 0.1);
+    }
+
+    #[test]
+    fn precision_follows_step() {
+        assert_eq!(step_precision(5.0), 0);
+        assert_eq!(step_precision(0.5), 1);
+        assert_eq!(step_precision(0.02), 2);
+        assert_eq!(step_precision(0.01), 2);
+        assert_eq!(step_precision(0.0), 0);
+    }
+}
@@ -0,0 +1,396 @@
+use std::time::Duration;
+
+use bevy_math::{Quat, Vec2, Vec3};
+
+use crate::LookupCurve;
+
+#[cfg(feature = "tween_bevy")]
+mod sheet;
+#[cfg(feature = "tween_bevy")]
+pub use sheet::*;
+#[cfg(feature = "tween_bevy")]
+mod tween_bevy;
+#[cfg(feature = "tween_bevy")]
+pub use tween_bevy::*;
+
+/// Values that can be interpolated by a [CurveTween]
+pub trait Tweenable: Clone {
+    /// Interpolates from `self` to `to` by `t`.
+    ///
+    /// `t` is not clamped, values outside of `0..=1` extrapolate past `self` or `to`.
+    fn tween(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    #[inline]
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        self * (1.0 - t) + to * t
+    }
+}
+
+impl Tweenable for Vec2 {
+    #[inline]
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        *self * (1.0 - t) + *to * t
+    }
+}
+
+impl Tweenable for Vec3 {
+    #[inline]
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        *self * (1.0 - t) + *to * t
+    }
+}
+
+impl Tweenable for Quat {
+    #[inline]
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        self.slerp(*to, t)
+    }
+}
+
+/// What a tween does when it reaches its end
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TweenRepeat {
+    /// Stop at the end
+    #[default]
+    Once,
+    /// Start over from the beginning
+    Loop,
+    /// Change direction and go back towards the beginning, and so on
+    PingPong,
+}
+
+/// Keeps track of time and direction for a tween
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TweenTimer {
+    duration: Duration,
+    elapsed: Duration,
+    reversed: bool,
+    repeat: TweenRepeat,
+}
+
+impl TweenTimer {
+    pub fn new(duration: Duration, repeat: TweenRepeat) -> Self {
+        Self {
+            duration,
+            elapsed: Duration::ZERO,
+            reversed: false,
+            repeat,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn repeat(&self) -> TweenRepeat {
+        self.repeat
+    }
+
+    /// Returns `true` if the timer is currently running backwards
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// Advances the timer by `dt` in the current direction
+    pub fn tick(&mut self, dt: Duration) {
+        if self.duration.is_zero() {
+            self.elapsed = if self.reversed {
+                Duration::ZERO
+            } else {
+                self.duration
+            };
+            return;
+        }
+
+        match self.repeat {
+            TweenRepeat::Once => {
+                self.elapsed = if self.reversed {
+                    self.elapsed.saturating_sub(dt)
+                } else {
+                    (self.elapsed + dt).min(self.duration)
+                };
+            }
+            TweenRepeat::Loop => {
+                let duration = self.duration.as_nanos() as i128;
+                let elapsed = self.elapsed.as_nanos() as i128;
+                let dt = dt.as_nanos() as i128;
+                let elapsed = if self.reversed {
+                    elapsed - dt
+                } else {
+                    elapsed + dt
+                };
+                self.elapsed = Duration::from_nanos(elapsed.rem_euclid(duration) as u64);
+            }
+            TweenRepeat::PingPong => {
+                // Unfold the back-and-forth motion into a phase over two durations
+                let duration = self.duration.as_nanos() as i128;
+                let elapsed = self.elapsed.as_nanos() as i128;
+                let phase = if self.reversed {
+                    2 * duration - elapsed
+                } else {
+                    elapsed
+                };
+                let phase = (phase + dt.as_nanos() as i128).rem_euclid(2 * duration);
+                if phase <= duration {
+                    self.elapsed = Duration::from_nanos(phase as u64);
+                    self.reversed = false;
+                } else {
+                    self.elapsed = Duration::from_nanos((2 * duration - phase) as u64);
+                    self.reversed = true;
+                }
+            }
+        }
+    }
+
+    /// Advances the timer like [Self::tick], and returns how many times it arrived at its end, or at its beginning
+    /// while running backwards. [TweenRepeat::PingPong] timers count arrivals at both ends.
+    ///
+    /// Repeating timers with a zero duration never arrive.
+    pub fn tick_arrivals(&mut self, dt: Duration) -> u32 {
+        let duration = self.duration.as_nanos() as i128;
+        let elapsed = self.elapsed.as_nanos() as i128;
+        let dt = dt.as_nanos() as i128;
+        let arrivals = match self.repeat {
+            TweenRepeat::Once => {
+                let finished = self.finished();
+                self.tick(Duration::from_nanos(dt as u64));
+                return (!finished && self.finished()) as u32;
+            }
+            _ if duration == 0 => 0,
+            TweenRepeat::Loop if self.reversed => {
+                // Running backwards the beginning of the loop is at the end of the duration
+                let elapsed = if elapsed == 0 { duration } else { elapsed };
+                let left = elapsed - dt;
+                if left > 0 {
+                    0
+                } else {
+                    -left / duration + 1
+                }
+            }
+            TweenRepeat::Loop => (elapsed + dt) / duration,
+            TweenRepeat::PingPong => {
+                let phase = if self.reversed {
+                    2 * duration - elapsed
+                } else {
+                    elapsed
+                };
+                (phase + dt) / duration - phase / duration
+            }
+        };
+        self.tick(Duration::from_nanos(dt as u64));
+        arrivals.min(u32::MAX as i128) as u32
+    }
+
+    /// Normalized progress of the timer, between 0 and 1
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            return if self.elapsed.is_zero() && self.reversed {
+                0.0
+            } else {
+                1.0
+            };
+        }
+        (self.elapsed.as_secs_f64() / self.duration.as_secs_f64()) as f32
+    }
+
+    /// Returns `true` if a [TweenRepeat::Once] timer has reached its end (or its beginning if reversed)
+    pub fn finished(&self) -> bool {
+        match self.repeat {
+            TweenRepeat::Once if self.reversed => self.elapsed.is_zero(),
+            TweenRepeat::Once => self.elapsed >= self.duration,
+            _ => false,
+        }
+    }
+
+    /// Rewinds the timer to the beginning and makes it run forwards
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.reversed = false;
+    }
+
+    /// Changes the direction of the timer, keeping the elapsed time
+    pub fn reverse(&mut self) {
+        self.reversed = !self.reversed;
+    }
+}
+
+/// Tweens a value from `from` to `to` over a duration, using a [LookupCurve] as easing.
+///
+/// The curve is sampled with [LookupCurve::sample_normalized], so its domain does not need to be `0..=1`.
+/// The output of the curve is used as blend factor without clamping, so a curve going above 1
+/// will overshoot past `to`, and below 0 will undershoot past `from`.
+#[derive(Clone, Debug)]
+pub struct CurveTween<T: Tweenable> {
+    pub from: T,
+    pub to: T,
+    pub curve: LookupCurve,
+    pub timer: TweenTimer,
+}
+
+impl<T: Tweenable> CurveTween<T> {
+    pub fn new(from: T, to: T, duration: Duration, curve: LookupCurve) -> Self {
+        Self {
+            from,
+            to,
+            curve,
+            timer: TweenTimer::new(duration, TweenRepeat::Once),
+        }
+    }
+
+    /// Consumes the tween and returns it with the repeat mode set
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.timer.repeat = repeat;
+        self
+    }
+
+    /// Advances the tween by `dt` and returns the new value
+    pub fn tick(&mut self, dt: Duration) -> T {
+        self.timer.tick(dt);
+        self.value()
+    }
+
+    /// Returns the value at the current time without advancing the tween
+    pub fn value(&self) -> T {
+        self.from.tween(
+            &self.to,
+            self.curve.sample_normalized(self.timer.progress()),
+        )
+    }
+
+    /// See [TweenTimer::finished]
+    pub fn finished(&self) -> bool {
+        self.timer.finished()
+    }
+
+    /// See [TweenTimer::reset]
+    pub fn reset(&mut self) {
+        self.timer.reset();
+    }
+
+    /// See [TweenTimer::reverse]
+    pub fn reverse(&mut self) {
+        self.timer.reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Knot, KnotInterpolation};
+
+    fn linear_curve(knots: &[(f32, f32)]) -> LookupCurve {
+        LookupCurve::new(
+            knots
+                .iter()
+                .map(|&(x, y)| Knot {
+                    position: Vec2::new(x, y),
+                    interpolation: KnotInterpolation::Linear,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn overshoots_past_to() {
+        let curve = linear_curve(&[(0.0, 0.0), (0.5, 1.5), (1.0, 1.0)]);
+        let mut tween = CurveTween::new(10.0, 20.0, ms(1000), curve);
+        assert_eq!(tween.tick(ms(500)), 25.0);
+    }
+
+    #[test]
+    fn arrives_exactly_at_end() {
+        // Domain not in 0..1 to make sure it is normalized
+        let curve = linear_curve(&[(-3.0, 0.0), (7.0, 1.0)]);
+        let mut tween = CurveTween::new(0.1, 0.7, ms(300), curve);
+        for _ in 0..7 {
+            tween.tick(ms(50));
+        }
+        assert!(tween.finished());
+        assert_eq!(tween.value(), 0.7);
+        assert_eq!(tween.tick(ms(50)), 0.7);
+    }
+
+    #[test]
+    fn reverses_mid_flight() {
+        let curve = linear_curve(&[(0.0, 0.0), (1.0, 1.0)]);
+        let mut tween = CurveTween::new(0.0, 100.0, ms(1000), curve);
+        tween.tick(ms(600));
+        tween.reverse();
+        assert!((tween.tick(ms(200)) - 40.0).abs() < 1e-4);
+        assert!(!tween.finished());
+        assert_eq!(tween.tick(ms(1000)), 0.0);
+        assert!(tween.finished());
+    }
+
+    #[test]
+    fn loops() {
+        let curve = linear_curve(&[(0.0, 0.0), (1.0, 1.0)]);
+        let mut tween = CurveTween::new(0.0, 100.0, ms(1000), curve).with_repeat(TweenRepeat::Loop);
+        assert!((tween.tick(ms(1250)) - 25.0).abs() < 1e-4);
+        assert!(!tween.finished());
+    }
+
+    #[test]
+    fn ping_pongs() {
+        let curve = linear_curve(&[(0.0, 0.0), (1.0, 1.0)]);
+        let mut tween =
+            CurveTween::new(0.0, 100.0, ms(1000), curve).with_repeat(TweenRepeat::PingPong);
+        assert!((tween.tick(ms(1250)) - 75.0).abs() < 1e-4);
+        assert!(tween.timer.is_reversed());
+        assert!((tween.tick(ms(1000)) - 25.0).abs() < 1e-4);
+        assert!(!tween.timer.is_reversed());
+        assert!(!tween.finished());
+    }
+
+    #[test]
+    fn counts_arrivals() {
+        let mut timer = TweenTimer::new(ms(100), TweenRepeat::Once);
+        assert_eq!(timer.tick_arrivals(ms(60)), 0);
+        assert_eq!(timer.tick_arrivals(ms(60)), 1);
+        assert_eq!(timer.tick_arrivals(ms(60)), 0);
+
+        let mut timer = TweenTimer::new(ms(100), TweenRepeat::Loop);
+        assert_eq!(timer.tick_arrivals(ms(100)), 1);
+        assert_eq!(timer.tick_arrivals(ms(250)), 2);
+        assert_eq!(timer.elapsed(), ms(50));
+        timer.reverse();
+        assert_eq!(timer.tick_arrivals(ms(50)), 1);
+        // Still at the beginning, which is not another arrival
+        assert_eq!(timer.tick_arrivals(ms(10)), 0);
+
+        let mut timer = TweenTimer::new(ms(100), TweenRepeat::PingPong);
+        assert_eq!(timer.tick_arrivals(ms(150)), 1);
+        assert!(timer.is_reversed());
+        assert_eq!(timer.tick_arrivals(ms(50)), 1);
+        assert_eq!(timer.tick_arrivals(ms(300)), 3);
+
+        let mut timer = TweenTimer::new(Duration::ZERO, TweenRepeat::Loop);
+        assert_eq!(timer.tick_arrivals(ms(10)), 0);
+    }
+
+    #[test]
+    fn tweens_vectors_and_quats() {
+        let curve = linear_curve(&[(0.0, 0.0), (1.0, 1.0)]);
+        let mut tween = CurveTween::new(Vec3::ZERO, Vec3::X, ms(100), curve.clone());
+        assert_eq!(tween.tick(ms(100)), Vec3::X);
+
+        let to = Quat::from_rotation_z(1.0);
+        let mut tween = CurveTween::new(Quat::IDENTITY, to, ms(100), curve);
+        assert!(tween.tick(ms(50)).angle_between(Quat::from_rotation_z(0.5)) < 1e-4);
+    }
+}
@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy_app::{App, Update};
+use bevy_asset::{Assets, Handle};
+#[cfg(feature = "editor_bevy")]
+use bevy_ecs::prelude::Commands;
+use bevy_ecs::prelude::{
+    Component, Entity, Event, EventWriter, IntoSystemConfigs, Query, Res, Resource,
+};
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_reflect::Reflect;
+use bevy_time::Time;
+
+use super::{TweenRepeat, TweenTimer};
+#[cfg(feature = "editor_bevy")]
+use crate::editor::LookupCurveEditor;
+use crate::LookupCurve;
+
+/// A named curve of a [CurveSheet]
+#[derive(Clone, Debug, Reflect)]
+pub struct CurveSheetEntry {
+    pub name: String,
+    pub curve: Handle<LookupCurve>,
+}
+
+/// Component with several named curves sampled along one shared timeline, like the scale, alpha and hue of a sprite
+///
+/// The timeline is advanced by [advance_curve_sheets], which samples every curve at the progress of the timeline
+/// with [LookupCurve::sample_normalized]. Read the values with [CurveSheet::value], or write them to components of
+/// the entity with closures registered by [CurveSheetAppExt::add_curve_sheet_applier]. A [CurveSheetCompleted] event
+/// is sent whenever the timeline arrives at its end.
+///
+/// The timeline and repeat mode are serializable with the `serialize` feature. Sheets are stored in scenes through
+/// reflection, as curve handles have no serde representation.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct CurveSheet {
+    pub entries: Vec<CurveSheetEntry>,
+    pub timeline: TweenTimer,
+    /// Stops advancing the timeline, the curves are still sampled
+    pub paused: bool,
+    /// Values sampled during the last update, in the order of `entries`. `None` for curves that are not loaded.
+    #[reflect(ignore)]
+    values: Vec<Option<f32>>,
+}
+
+impl CurveSheet {
+    /// Constructs a sheet without curves, playing its timeline of `duration` once
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            entries: Vec::new(),
+            timeline: TweenTimer::new(duration, TweenRepeat::Once),
+            paused: false,
+            values: Vec::new(),
+        }
+    }
+
+    /// Consumes the sheet and returns it with the curve added as entry `name`
+    pub fn with_curve(mut self, name: impl Into<String>, curve: Handle<LookupCurve>) -> Self {
+        self.entries.push(CurveSheetEntry {
+            name: name.into(),
+            curve,
+        });
+        self
+    }
+
+    /// Consumes the sheet and returns it with the repeat mode of the timeline set
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.timeline.repeat = repeat;
+        self
+    }
+
+    /// The curve of the entry `name`
+    pub fn curve(&self, name: &str) -> Option<&Handle<LookupCurve>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| &entry.curve)
+    }
+
+    /// The value of the entry `name` sampled during the last update, `None` before its curve is loaded
+    pub fn value(&self, name: &str) -> Option<f32> {
+        let index = self.entries.iter().position(|entry| entry.name == name)?;
+        self.values.get(index).copied().flatten()
+    }
+
+    /// The names and values of all entries with a value, see [Self::value]
+    pub fn values(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.entries
+            .iter()
+            .zip(&self.values)
+            .filter_map(|(entry, value)| Some((entry.name.as_str(), (*value)?)))
+    }
+
+    /// Rewinds the timeline to the beginning, see [TweenTimer::reset]
+    pub fn restart(&mut self) {
+        self.timeline.reset();
+    }
+}
+
+/// Sent by [advance_curve_sheets] whenever the timeline of a [CurveSheet] arrives at its end, see
+/// [TweenTimer::tick_arrivals]. Sheets played once send it once, repeating sheets at the end of every repetition.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CurveSheetCompleted {
+    pub sheet: Entity,
+}
+
+/// Advances the timelines of all [CurveSheet]s that are not paused, and samples their curves
+pub fn advance_curve_sheets(
+    time: Res<Time>,
+    curves: Res<Assets<LookupCurve>>,
+    mut sheets: Query<(Entity, &mut CurveSheet)>,
+    mut completed: EventWriter<CurveSheetCompleted>,
+) {
+    for (entity, mut sheet) in &mut sheets {
+        let CurveSheet {
+            entries,
+            timeline,
+            paused,
+            values,
+        } = &mut *sheet;
+        if !*paused {
+            for _ in 0..timeline.tick_arrivals(time.delta()) {
+                completed.send(CurveSheetCompleted { sheet: entity });
+            }
+        }
+        let t = timeline.progress();
+        values.clear();
+        values.extend(
+            entries
+                .iter()
+                .map(|entry| Some(curves.get(&entry.curve)?.sample_normalized(t))),
+        );
+    }
+}
+
+type Applier<C> = Box<dyn Fn(&mut C, f32) + Send + Sync>;
+
+/// Closures writing the values of [CurveSheet] entries to components of type `C`, see
+/// [CurveSheetAppExt::add_curve_sheet_applier]
+#[derive(Resource)]
+pub struct CurveSheetAppliers<C: Component> {
+    appliers: HashMap<String, Vec<Applier<C>>>,
+}
+
+impl<C: Component> Default for CurveSheetAppliers<C> {
+    fn default() -> Self {
+        Self {
+            appliers: HashMap::new(),
+        }
+    }
+}
+
+impl<C: Component> CurveSheetAppliers<C> {
+    /// Adds `apply` for the entries named `name`, next to the closures already added for it
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        apply: impl Fn(&mut C, f32) + Send + Sync + 'static,
+    ) {
+        self.appliers
+            .entry(name.into())
+            .or_default()
+            .push(Box::new(apply));
+    }
+}
+
+/// Calls the [CurveSheetAppliers] of `C` with the values of every [CurveSheet] on an entity with a `C`
+pub fn apply_curve_sheets<C: Component>(
+    appliers: Res<CurveSheetAppliers<C>>,
+    mut sheets: Query<(&CurveSheet, &mut C)>,
+) {
+    for (sheet, mut component) in &mut sheets {
+        for (name, value) in sheet.values() {
+            for apply in appliers.appliers.get(name).into_iter().flatten() {
+                apply(&mut component, value);
+            }
+        }
+    }
+}
+
+/// Extension trait for registering how the values of [CurveSheet]s are applied
+pub trait CurveSheetAppExt {
+    /// Calls `apply` with the component `C` and the value of the entry `name` of every [CurveSheet] on an entity with
+    /// a `C`, right after [advance_curve_sheets]. Adds [apply_curve_sheets] for `C` the first time.
+    fn add_curve_sheet_applier<C: Component>(
+        &mut self,
+        name: impl Into<String>,
+        apply: impl Fn(&mut C, f32) + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl CurveSheetAppExt for App {
+    fn add_curve_sheet_applier<C: Component>(
+        &mut self,
+        name: impl Into<String>,
+        apply: impl Fn(&mut C, f32) + Send + Sync + 'static,
+    ) -> &mut Self {
+        if !self.world().contains_resource::<CurveSheetAppliers<C>>() {
+            self.init_resource::<CurveSheetAppliers<C>>()
+                .add_systems(Update, apply_curve_sheets::<C>.after(advance_curve_sheets));
+        }
+        self.world_mut()
+            .resource_mut::<CurveSheetAppliers<C>>()
+            .add(name, apply);
+        self
+    }
+}
+
+/// Shows the timeline of a [CurveSheet] as the sample of a [LookupCurveEditor] for one of its curves, see
+/// [CurveSheetCommandsExt::spawn_curve_sheet_editors]
+#[cfg(feature = "editor_bevy")]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub struct CurveSheetPlayhead {
+    /// Entity of the sheet
+    pub sheet: Entity,
+    /// Index of the curve in the entries of the sheet
+    pub entry: usize,
+}
+
+/// Extension trait for opening editors for the curves of a [CurveSheet]
+#[cfg(feature = "editor_bevy")]
+pub trait CurveSheetCommandsExt {
+    /// Spawns a [LookupCurveEditor] for every curve of `sheet`, the sheet of `sheet_entity`. The editors show the
+    /// timeline of the running sheet as their sample, see [CurveSheetPlayhead]. Returns the editor entities in the
+    /// order of the entries.
+    fn spawn_curve_sheet_editors(
+        &mut self,
+        sheet_entity: Entity,
+        sheet: &CurveSheet,
+    ) -> Vec<Entity>;
+}
+
+#[cfg(feature = "editor_bevy")]
+impl CurveSheetCommandsExt for Commands<'_, '_> {
+    fn spawn_curve_sheet_editors(
+        &mut self,
+        sheet_entity: Entity,
+        sheet: &CurveSheet,
+    ) -> Vec<Entity> {
+        sheet
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(entry, CurveSheetEntry { curve, .. })| {
+                let playhead = CurveSheetPlayhead {
+                    sheet: sheet_entity,
+                    entry,
+                };
+                self.spawn((LookupCurveEditor::new(curve.clone()), playhead))
+                    .id()
+            })
+            .collect()
+    }
+}
+
+/// Sets the sample of editors with a [CurveSheetPlayhead] to the x of their curve at the progress of the timeline
+#[cfg(feature = "editor_bevy")]
+pub fn sync_curve_sheet_playheads(
+    sheets: Query<&CurveSheet>,
+    curves: Res<Assets<LookupCurve>>,
+    mut editors: Query<(&CurveSheetPlayhead, &mut LookupCurveEditor)>,
+) {
+    for (playhead, mut editor) in &mut editors {
+        let sample = sheets.get(playhead.sheet).ok().and_then(|sheet| {
+            let entry = sheet.entries.get(playhead.entry)?;
+            curves
+                .get(&entry.curve)?
+                .normalized_x(sheet.timeline.progress())
+        });
+        if editor.sample != sample {
+            editor.sample = sample;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{AssetApp, AssetPlugin, Events, MinimalPlugins, Transform};
+    use bevy::time::TimeUpdateStrategy;
+    use bevy_math::{Vec2, Vec3};
+
+    use super::*;
+    use crate::tween::TweenPlugin;
+    use crate::{Knot, KnotInterpolation};
+
+    fn linear(knots: &[(f32, f32)]) -> LookupCurve {
+        LookupCurve::new(
+            knots
+                .iter()
+                .map(|&(x, y)| Knot {
+                    position: Vec2::new(x, y),
+                    interpolation: KnotInterpolation::Linear,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    /// An app advancing time by 100 ms per update
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<LookupCurve>()
+            .add_plugins(TweenPlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )));
+        // Starts the clock, so that sheets spawned afterwards advance by exactly 100 ms per update
+        app.update();
+        app
+    }
+
+    fn add_curve(app: &mut App, knots: &[(f32, f32)]) -> Handle<LookupCurve> {
+        app.world_mut()
+            .resource_mut::<Assets<LookupCurve>>()
+            .add(linear(knots))
+    }
+
+    /// Sheets of the events sent since the last call
+    fn completed(app: &mut App) -> Vec<Entity> {
+        app.world_mut()
+            .resource_mut::<Events<CurveSheetCompleted>>()
+            .drain()
+            .map(|event| event.sheet)
+            .collect()
+    }
+
+    #[test]
+    fn samples_every_curve_along_the_timeline() {
+        let mut app = test_app();
+        // The domain of the curves does not matter, they are sampled normalized
+        let scale = add_curve(&mut app, &[(0.0, 1.0), (1.0, 2.0)]);
+        let alpha = add_curve(&mut app, &[(-5.0, 1.0), (5.0, 0.0)]);
+        let entity = app
+            .world_mut()
+            .spawn(
+                CurveSheet::new(Duration::from_secs(1))
+                    .with_curve("scale", scale)
+                    .with_curve("alpha", alpha)
+                    .with_curve("missing", Handle::default()),
+            )
+            .id();
+
+        app.update();
+        app.update();
+        let sheet = app.world().get::<CurveSheet>(entity).unwrap();
+        assert!((sheet.value("scale").unwrap() - 1.2).abs() < 1e-5);
+        assert!((sheet.value("alpha").unwrap() - 0.8).abs() < 1e-5);
+        assert_eq!(sheet.value("missing"), None);
+        assert_eq!(sheet.values().count(), 2);
+
+        app.world_mut()
+            .get_mut::<CurveSheet>(entity)
+            .unwrap()
+            .paused = true;
+        app.update();
+        let sheet = app.world().get::<CurveSheet>(entity).unwrap();
+        assert!((sheet.value("scale").unwrap() - 1.2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn completes_once_or_every_loop() {
+        let mut app = test_app();
+        let curve = add_curve(&mut app, &[(0.0, 0.0), (1.0, 1.0)]);
+        let once = app
+            .world_mut()
+            .spawn(CurveSheet::new(Duration::from_millis(250)).with_curve("x", curve.clone()))
+            .id();
+        let looping = app
+            .world_mut()
+            .spawn(
+                CurveSheet::new(Duration::from_millis(250))
+                    .with_curve("x", curve)
+                    .with_repeat(TweenRepeat::Loop),
+            )
+            .id();
+
+        let mut sheets = Vec::new();
+        for _ in 0..10 {
+            app.update();
+            sheets.extend(completed(&mut app));
+        }
+        // One second of a 250 ms timeline
+        assert_eq!(sheets.iter().filter(|&&sheet| sheet == once).count(), 1);
+        assert_eq!(sheets.iter().filter(|&&sheet| sheet == looping).count(), 4);
+        let sheet = app.world().get::<CurveSheet>(once).unwrap();
+        assert_eq!(sheet.value("x"), Some(1.0));
+
+        app.world_mut()
+            .get_mut::<CurveSheet>(once)
+            .unwrap()
+            .restart();
+        app.update();
+        app.update();
+        app.update();
+        let sheets = completed(&mut app);
+        assert_eq!(sheets.iter().filter(|&&sheet| sheet == once).count(), 1);
+    }
+
+    #[test]
+    fn applies_values_with_registered_closures() {
+        let mut app = test_app();
+        app.add_curve_sheet_applier("scale", |transform: &mut Transform, scale| {
+            transform.scale = Vec3::splat(scale);
+        })
+        .add_curve_sheet_applier("height", |transform: &mut Transform, y| {
+            transform.translation.y = y;
+        })
+        .add_curve_sheet_applier("height", |transform: &mut Transform, y| {
+            transform.translation.z = -y;
+        });
+        let scale = add_curve(&mut app, &[(0.0, 1.0), (1.0, 3.0)]);
+        let height = add_curve(&mut app, &[(0.0, 0.0), (1.0, 10.0)]);
+        let sheet = CurveSheet::new(Duration::from_millis(500))
+            .with_curve("scale", scale)
+            .with_curve("height", height.clone());
+        let entity = app
+            .world_mut()
+            .spawn((sheet.clone(), Transform::default()))
+            .id();
+        // Entries without an applier and entities without the component are left alone
+        let unapplied = app
+            .world_mut()
+            .spawn(CurveSheet::new(Duration::from_millis(500)).with_curve("other", height))
+            .id();
+
+        app.update();
+        app.update();
+        let transform = app.world().get::<Transform>(entity).unwrap();
+        assert!((transform.scale - Vec3::splat(1.8)).length() < 1e-5);
+        assert!((transform.translation - Vec3::new(0.0, 4.0, -4.0)).length() < 1e-5);
+        assert!(app.world().get::<Transform>(unapplied).is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::{Assets, Handle};
+#[cfg(feature = "editor_bevy")]
+use bevy_ecs::prelude::IntoSystemConfigs;
+use bevy_ecs::prelude::{Component, Query, Res};
+use bevy_math::{Quat, Vec2, Vec3};
+use bevy_time::Time;
+
+#[cfg(feature = "editor_bevy")]
+use super::sync_curve_sheet_playheads;
+use super::{
+    advance_curve_sheets, CurveSheet, CurveSheetCompleted, TweenRepeat, TweenTimer, Tweenable,
+};
+use crate::LookupCurve;
+
+pub(crate) struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                update_lookup_curve_tweens::<f32>,
+                update_lookup_curve_tweens::<Vec2>,
+                update_lookup_curve_tweens::<Vec3>,
+                update_lookup_curve_tweens::<Quat>,
+                advance_curve_sheets,
+            ),
+        )
+        .add_event::<CurveSheetCompleted>()
+        .register_type::<CurveSheet>();
+        #[cfg(feature = "editor_bevy")]
+        app.add_systems(
+            Update,
+            sync_curve_sheet_playheads.after(advance_curve_sheets),
+        );
+    }
+}
+
+/// Component for tweening a value using a [LookupCurve] asset as easing, see [super::CurveTween]
+///
+/// The tween is advanced by [update_lookup_curve_tweens], and the result can be read using [LookupCurveTween::value].
+/// Systems for `f32`, `Vec2`, `Vec3` and `Quat` are added by the `LookupCurvePlugin`.
+#[derive(Component)]
+pub struct LookupCurveTween<T: Tweenable + Send + Sync + 'static> {
+    pub from: T,
+    pub to: T,
+    pub curve_handle: Handle<LookupCurve>,
+    pub timer: TweenTimer,
+    value: T,
+}
+
+impl<T: Tweenable + Send + Sync + 'static> LookupCurveTween<T> {
+    pub fn new(
+        from: T,
+        to: T,
+        duration: std::time::Duration,
+        curve_handle: Handle<LookupCurve>,
+    ) -> Self {
+        Self {
+            value: from.clone(),
+            from,
+            to,
+            curve_handle,
+            timer: TweenTimer::new(duration, TweenRepeat::Once),
+        }
+    }
+
+    /// Consumes the tween and returns it with the repeat mode set
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.timer.repeat = repeat;
+        self
+    }
+
+    /// The value computed during the last update
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+}
+
+/// Advances all [LookupCurveTween]s of type `T`. Tweens whose curve is not loaded yet are not advanced.
+pub fn update_lookup_curve_tweens<T: Tweenable + Send + Sync + 'static>(
+    time: Res<Time>,
+    curves: Res<Assets<LookupCurve>>,
+    mut tweens: Query<&mut LookupCurveTween<T>>,
+) {
+    for mut tween in &mut tweens {
+        let Some(curve) = curves.get(&tween.curve_handle) else {
+            continue;
+        };
+        tween.timer.tick(time.delta());
+        let value = tween
+            .from
+            .tween(&tween.to, curve.sample_normalized(tween.timer.progress()));
+        tween.value = value;
+    }
+}
@@ -0,0 +1,237 @@
+//! Editing curves that describe spatial profiles, like terrain cross-sections or road banking over distance, in the
+//! world instead of in a plot. See [CurvePlane], and `WorldCurveEditor` with the `world_edit` feature.
+
+use bevy_math::{Vec2, Vec3};
+
+use crate::LookupCurve;
+
+#[cfg(feature = "world_edit")]
+mod world_edit_bevy;
+#[cfg(feature = "world_edit")]
+pub use world_edit_bevy::*;
+
+/// Rays closer to parallel to the plane than this, as the cosine of the angle to its normal, do not hit it
+const PARALLEL_EPSILON: f32 = 1e-6;
+
+/// Maps curve space onto a plane in world space.
+///
+/// The point `(x, y)` of a curve is at `origin + x * x_axis + y * y_axis` in the world. The axes are scaled
+/// directions, their length is the world distance of one unit on the curve. They do not need to be orthogonal, but
+/// a plane with parallel axes is degenerate and can not be picked.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct CurvePlane {
+    pub origin: Vec3,
+    pub x_axis: Vec3,
+    pub y_axis: Vec3,
+}
+
+impl Default for CurvePlane {
+    /// The world XY plane, one unit per unit
+    fn default() -> Self {
+        Self {
+            origin: Vec3::ZERO,
+            x_axis: Vec3::X,
+            y_axis: Vec3::Y,
+        }
+    }
+}
+
+impl CurvePlane {
+    /// Constructs a plane from the directions of the axes and the world length of one unit along them
+    pub fn new(
+        origin: Vec3,
+        x_direction: Vec3,
+        x_scale: f32,
+        y_direction: Vec3,
+        y_scale: f32,
+    ) -> Self {
+        Self {
+            origin,
+            x_axis: x_direction.normalize_or_zero() * x_scale,
+            y_axis: y_direction.normalize_or_zero() * y_scale,
+        }
+    }
+
+    /// World position of a point in curve space
+    pub fn to_world(&self, point: Vec2) -> Vec3 {
+        self.origin + self.x_axis * point.x + self.y_axis * point.y
+    }
+
+    /// Unit normal of the plane, `None` if the plane is degenerate
+    pub fn normal(&self) -> Option<Vec3> {
+        self.x_axis.cross(self.y_axis).try_normalize()
+    }
+
+    /// Curve space position of the point on the plane closest to `world`, `None` if the plane is degenerate
+    pub fn project(&self, world: Vec3) -> Option<Vec2> {
+        let offset = world - self.origin;
+        // Solves the normal equations of the axes, which may be skewed
+        let (xx, xy, yy) = (
+            self.x_axis.dot(self.x_axis),
+            self.x_axis.dot(self.y_axis),
+            self.y_axis.dot(self.y_axis),
+        );
+        let det = xx * yy - xy * xy;
+        if det <= f32::EPSILON * xx * yy {
+            return None;
+        }
+        let (ox, oy) = (offset.dot(self.x_axis), offset.dot(self.y_axis));
+        Some(Vec2::new(yy * ox - xy * oy, xx * oy - xy * ox) / det)
+    }
+
+    /// Curve space position where a ray hits the plane.
+    ///
+    /// Returns `None` if the ray points away from the plane, is parallel to it, or the plane is degenerate. Rays
+    /// at a grazing angle hit the plane far away, so a dragged knot moves a lot for a small movement of the pointer.
+    pub fn ray_hit(&self, ray_origin: Vec3, ray_direction: Vec3) -> Option<Vec2> {
+        let normal = self.normal()?;
+        let direction = ray_direction.try_normalize()?;
+        let cos = direction.dot(normal);
+        if cos.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let distance = (self.origin - ray_origin).dot(normal) / cos;
+        if distance < 0.0 {
+            return None;
+        }
+        self.project(ray_origin + direction * distance)
+    }
+
+    /// World distance between two points in curve space
+    pub fn world_distance(&self, a: Vec2, b: Vec2) -> f32 {
+        let delta = b - a;
+        (self.x_axis * delta.x + self.y_axis * delta.y).length()
+    }
+
+    /// Index of the knot of `curve` closest to `point` in the world, if it is within the world distance `radius`
+    pub fn nearest_knot(&self, curve: &LookupCurve, point: Vec2, radius: f32) -> Option<usize> {
+        curve
+            .knots()
+            .iter()
+            .map(|knot| self.world_distance(knot.position, point))
+            .enumerate()
+            .filter(|&(_, distance)| distance <= radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Knot;
+
+    fn assert_near(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-4, "{a:?} != {b:?}");
+    }
+
+    /// Planes with scaled, rotated and skewed axes
+    fn planes() -> [CurvePlane; 4] {
+        [
+            CurvePlane::default(),
+            CurvePlane::new(Vec3::new(1.0, 2.0, 3.0), Vec3::X, 10.0, Vec3::Y, 0.5),
+            // A road banking profile along z, with y up
+            CurvePlane::new(Vec3::new(-4.0, 0.0, 2.0), Vec3::NEG_Z, 2.0, Vec3::Y, 3.0),
+            CurvePlane {
+                origin: Vec3::new(0.0, -1.0, 5.0),
+                x_axis: Vec3::new(1.0, 0.0, 1.0),
+                y_axis: Vec3::new(0.3, 2.0, -0.4),
+            },
+        ]
+    }
+
+    #[test]
+    fn projecting_reverses_to_world() {
+        for plane in planes() {
+            for point in [Vec2::ZERO, Vec2::new(0.3, -2.0), Vec2::new(-7.5, 4.25)] {
+                assert_near(plane.project(plane.to_world(point)).unwrap(), point);
+                // Points off the plane project along its normal
+                let off = plane.to_world(point) + plane.normal().unwrap() * 3.0;
+                assert_near(plane.project(off).unwrap(), point);
+            }
+        }
+    }
+
+    #[test]
+    fn rays_hit_where_they_cross_the_plane() {
+        // Looking straight at the XY plane
+        let plane = CurvePlane::default();
+        let hit = plane.ray_hit(Vec3::new(0.5, 0.25, 10.0), Vec3::NEG_Z);
+        assert_near(hit.unwrap(), Vec2::new(0.5, 0.25));
+        // From behind
+        let hit = plane.ray_hit(Vec3::new(0.5, 0.25, -10.0), Vec3::Z * 4.0);
+        assert_near(hit.unwrap(), Vec2::new(0.5, 0.25));
+    }
+
+    #[test]
+    fn oblique_rays_hit_where_they_cross_the_plane() {
+        let cameras = [
+            Vec3::new(3.0, 5.0, 7.0),
+            Vec3::new(-20.0, 1.0, 0.5),
+            Vec3::new(0.1, 30.0, -2.0),
+        ];
+        for plane in planes() {
+            let normal = plane.normal().unwrap();
+            for camera in cameras {
+                for point in [Vec2::ZERO, Vec2::new(1.5, -0.5), Vec2::new(-3.0, 2.0)] {
+                    let target = plane.to_world(point);
+                    let direction = target - camera;
+                    if direction.normalize().dot(normal).abs() < 0.05 {
+                        // Too close to parallel to be accurate
+                        continue;
+                    }
+                    assert_near(plane.ray_hit(camera, direction).unwrap(), point);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_and_receding_rays_miss() {
+        let plane = CurvePlane::default();
+        // In the plane and above it
+        assert_eq!(plane.ray_hit(Vec3::ZERO, Vec3::X), None);
+        assert_eq!(plane.ray_hit(Vec3::Z, Vec3::new(1.0, 1.0, 0.0)), None);
+        // Pointing away from the plane
+        assert_eq!(plane.ray_hit(Vec3::Z, Vec3::Z), None);
+        // Without a direction
+        assert_eq!(plane.ray_hit(Vec3::Z, Vec3::ZERO), None);
+
+        let degenerate = CurvePlane {
+            origin: Vec3::ZERO,
+            x_axis: Vec3::X,
+            y_axis: Vec3::X * 2.0,
+        };
+        assert_eq!(degenerate.normal(), None);
+        assert_eq!(degenerate.ray_hit(Vec3::Z, Vec3::NEG_Z), None);
+        assert_eq!(degenerate.project(Vec3::ONE), None);
+    }
+
+    #[test]
+    fn knots_are_picked_by_world_distance() {
+        let curve = LookupCurve::new(vec![
+            Knot::default(),
+            Knot {
+                position: Vec2::ONE,
+                ..Default::default()
+            },
+        ]);
+        // One unit of x is 10 world units, one unit of y is 0.1
+        let plane = CurvePlane::new(Vec3::ZERO, Vec3::X, 10.0, Vec3::Y, 0.1);
+        assert!((plane.world_distance(Vec2::ZERO, Vec2::new(0.3, 40.0)) - 5.0).abs() < 1e-5);
+        assert_eq!(plane.nearest_knot(&curve, Vec2::new(0.3, 0.0), 1.0), None);
+        assert_eq!(
+            plane.nearest_knot(&curve, Vec2::new(0.3, 0.0), 5.0),
+            Some(0)
+        );
+        assert_eq!(
+            plane.nearest_knot(&curve, Vec2::new(0.9, -5.0), 2.0),
+            Some(1)
+        );
+        assert_eq!(
+            plane.nearest_knot(&LookupCurve::default(), Vec2::ZERO, 1.0),
+            None
+        );
+    }
+}
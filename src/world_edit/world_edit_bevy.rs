@@ -0,0 +1,268 @@
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::{Assets, Handle};
+use bevy_color::Color;
+use bevy_ecs::prelude::{
+    resource_exists, Component, Entity, EventWriter, IntoSystemConfigs, Query, Res, ResMut, With,
+};
+use bevy_egui::{EguiContexts, EguiUserTextures};
+use bevy_gizmos::gizmos::Gizmos;
+use bevy_input::{keyboard::KeyCode, mouse::MouseButton, ButtonInput};
+use bevy_log::warn;
+use bevy_math::{Quat, Vec2, Vec3};
+use bevy_render::camera::Camera;
+use bevy_transform::components::GlobalTransform;
+use bevy_window::{PrimaryWindow, Window};
+
+use super::CurvePlane;
+use crate::editor::{EditorAction, LookupCurveEdited, LookupCurveEguiEditor};
+use crate::{KnotInterpolation, LookupCurve};
+
+pub(crate) struct WorldEditPlugin;
+
+impl Plugin for WorldEditPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (world_curve_editor_input, draw_world_curve_editors)
+                .chain()
+                .run_if(resource_exists::<EguiUserTextures>),
+        );
+    }
+}
+
+/// Component for editing a curve asset in the world, drawn with gizmos on a [CurvePlane].
+///
+/// Hovering a knot with the pointer highlights it, dragging it with [Self::drag_button] moves it on the plane.
+/// [Self::add_key] adds a knot at the pointer and [Self::delete_key] deletes the hovered knot. Tangents can not be
+/// edited in the world, use a [LookupCurveEditor](crate::editor::LookupCurveEditor) for them.
+///
+/// Edits are applied with [Self::action_editor] as [EditorAction]s, so that they follow its edit scope and knot
+/// rounding, and are written to the asset right away. Each write sends a [LookupCurveEdited].
+///
+/// Needs the gizmos of bevy, which are part of its `DefaultPlugins`.
+#[derive(Component)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+pub struct WorldCurveEditor {
+    pub curve_handle: Handle<LookupCurve>,
+    /// Where the curve is in the world
+    pub plane: CurvePlane,
+    /// Applies the edits, it is not shown
+    pub action_editor: LookupCurveEguiEditor,
+    /// Camera the pointer picks through, `None` for the first active camera
+    pub camera: Option<Entity>,
+    /// World radius of the knots, within which they are picked
+    pub knot_radius: f32,
+    /// Number of lines each segment of the curve is drawn with
+    pub resolution: usize,
+    pub curve_color: Color,
+    pub knot_color: Color,
+    /// Color of the hovered and the dragged knot
+    pub hover_color: Color,
+    pub drag_button: MouseButton,
+    /// `None` to disable adding knots
+    pub add_key: Option<KeyCode>,
+    /// `None` to disable deleting knots
+    pub delete_key: Option<KeyCode>,
+    /// Id of the hovered knot
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    hovered: Option<usize>,
+    /// Id of the dragged knot
+    #[cfg_attr(feature = "bevy_reflect", reflect(ignore))]
+    dragged: Option<usize>,
+}
+
+impl WorldCurveEditor {
+    /// Constructs a [WorldCurveEditor] for the curve asset on `plane`
+    pub fn new(curve_handle: Handle<LookupCurve>, plane: CurvePlane) -> Self {
+        Self {
+            curve_handle,
+            plane,
+            action_editor: LookupCurveEguiEditor::default(),
+            camera: None,
+            knot_radius: 0.1,
+            resolution: 16,
+            curve_color: Color::srgb(0.9, 0.9, 0.9),
+            knot_color: Color::srgb(0.3, 0.6, 1.0),
+            hover_color: Color::srgb(1.0, 0.8, 0.2),
+            drag_button: MouseButton::Left,
+            add_key: Some(KeyCode::KeyN),
+            delete_key: Some(KeyCode::Delete),
+            hovered: None,
+            dragged: None,
+        }
+    }
+
+    /// Sets [Self::knot_radius]
+    pub fn with_knot_radius(mut self, radius: f32) -> Self {
+        self.knot_radius = radius;
+        self
+    }
+
+    /// Sets [Self::camera]
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Id of the knot under the pointer, or of the dragged knot while dragging
+    pub fn hovered_knot(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    /// Id of the dragged knot
+    pub fn dragged_knot(&self) -> Option<usize> {
+        self.dragged
+    }
+}
+
+/// Ray through the pointer in the primary window, from `camera` or the first active camera
+fn pointer_ray(
+    cameras: &Query<(Entity, &Camera, &GlobalTransform)>,
+    camera: Option<Entity>,
+    cursor: Vec2,
+) -> Option<(Vec3, Vec3)> {
+    cameras
+        .iter()
+        .filter(|(entity, c, _)| camera.map_or(c.is_active, |camera| camera == *entity))
+        .find_map(|(_, c, transform)| c.viewport_to_world(transform, cursor))
+        .map(|ray| (ray.origin, *ray.direction))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn world_curve_editor_input(
+    mut editors: Query<(Entity, &mut WorldCurveEditor)>,
+    mut curves: ResMut<Assets<LookupCurve>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut contexts: EguiContexts,
+    mut edited: EventWriter<LookupCurveEdited>,
+) {
+    // Pointer input over egui windows, like a 2D editor, is not for the world
+    let over_egui = contexts
+        .try_ctx_mut()
+        .is_some_and(|ctx| ctx.wants_pointer_input() || ctx.is_pointer_over_area());
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .filter(|_| !over_egui);
+
+    for (entity, mut editor) in &mut editors {
+        let editor = &mut *editor;
+        let Some(curve) = curves.get(&editor.curve_handle) else {
+            continue;
+        };
+        let hit = cursor
+            .and_then(|cursor| pointer_ray(&cameras, editor.camera, cursor))
+            .and_then(|(origin, direction)| editor.plane.ray_hit(origin, direction));
+
+        if !mouse.pressed(editor.drag_button) {
+            editor.dragged = None;
+        }
+        editor.hovered = editor.dragged.or_else(|| {
+            let i = editor.plane.nearest_knot(curve, hit?, editor.knot_radius)?;
+            Some(curve.knots()[i].id)
+        });
+
+        let mut actions = Vec::new();
+        if let Some(hit) = hit {
+            if let Some(id) = editor.dragged {
+                // Only moved when the pointer moved, to not modify the asset every frame
+                if curve
+                    .knots()
+                    .iter()
+                    .any(|knot| knot.id == id && knot.position != hit)
+                {
+                    actions.push(EditorAction::MoveKnot { id, to: hit });
+                }
+            } else if mouse.just_pressed(editor.drag_button) {
+                editor.dragged = editor.hovered;
+            }
+            if editor.add_key.is_some_and(|key| keys.just_pressed(key)) {
+                let i = curve
+                    .knots()
+                    .partition_point(|knot| knot.position.x <= hit.x);
+                let interpolation = i.checked_sub(1).map_or(KnotInterpolation::Linear, |i| {
+                    curve.knots()[i].interpolation
+                });
+                actions.push(EditorAction::AddKnot {
+                    position: hit,
+                    interpolation,
+                });
+            }
+        }
+        if let Some(id) = editor.hovered {
+            if editor.delete_key.is_some_and(|key| keys.just_pressed(key)) {
+                actions.push(EditorAction::DeleteKnot { id });
+                editor.hovered = None;
+                editor.dragged = None;
+            }
+        }
+
+        if actions.is_empty() {
+            continue;
+        }
+        let Some(curve) = curves.get_mut(&editor.curve_handle) else {
+            continue;
+        };
+        let mut changed = false;
+        for action in actions {
+            match editor.action_editor.apply_action(action, curve) {
+                Ok(outcome) => changed |= outcome.curve_changed(),
+                Err(e) => {
+                    warn!("Could not edit curve in the world: {e}");
+                    editor.dragged = None;
+                }
+            }
+        }
+        if changed {
+            edited.send(LookupCurveEdited {
+                editor: entity,
+                curve: editor.curve_handle.id(),
+            });
+        }
+    }
+}
+
+fn draw_world_curve_editors(
+    editors: Query<&WorldCurveEditor>,
+    curves: Res<Assets<LookupCurve>>,
+    mut gizmos: Gizmos,
+) {
+    for editor in &editors {
+        let Some(curve) = curves.get(&editor.curve_handle) else {
+            continue;
+        };
+        let plane = editor.plane;
+        let knots = curve.knots();
+
+        let steps = editor.resolution.max(1);
+        let xs = knots
+            .windows(2)
+            .flat_map(|pair| {
+                let (a, b) = (pair[0].position.x, pair[1].position.x);
+                (0..steps).map(move |i| a + (b - a) * i as f32 / steps as f32)
+            })
+            .chain(knots.last().map(|knot| knot.position.x));
+        gizmos.linestrip(
+            xs.map(|x| plane.to_world(Vec2::new(x, curve.lookup(x)))),
+            editor.curve_color,
+        );
+
+        for knot in knots {
+            let color = if editor.hovered == Some(knot.id) {
+                editor.hover_color
+            } else {
+                editor.knot_color
+            };
+            gizmos.sphere(
+                plane.to_world(knot.position),
+                Quat::IDENTITY,
+                editor.knot_radius,
+                color,
+            );
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Checks that small curves are created, cloned and sampled without allocating.
+//!
+//! In its own test binary, as it replaces the global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use bevy_lookup_curve::{Knot, KnotInterpolation, LookupCache, LookupCurve, Tangent};
+use bevy_math::Vec2;
+
+/// Counts allocations made by the current thread, so that tests running in parallel do not interfere
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the result of `f` and the number of allocations it made
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+fn bezier_knots() -> [Knot; 3] {
+    let weighted = Tangent {
+        slope: 1.0,
+        weight: Some(0.5),
+        ..Default::default()
+    };
+    [
+        Knot {
+            position: Vec2::new(0.0, 0.0),
+            interpolation: KnotInterpolation::Cubic,
+            right_tangent: weighted,
+            ..Default::default()
+        },
+        Knot {
+            position: Vec2::new(0.5, 1.0),
+            interpolation: KnotInterpolation::Cubic,
+            left_tangent: weighted,
+            right_tangent: weighted,
+            ..Default::default()
+        },
+        Knot {
+            position: Vec2::new(1.0, 0.0),
+            interpolation: KnotInterpolation::Cubic,
+            left_tangent: weighted,
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn small_curve_does_not_allocate() {
+    let knots = bezier_knots();
+    let (curve, allocations) = count_allocations(|| LookupCurve::from(knots));
+    assert_eq!(allocations, 0, "construction");
+    assert_eq!(curve.knots().len(), 3);
+
+    let (clone, allocations) = count_allocations(|| curve.clone());
+    assert_eq!(allocations, 0, "clone");
+
+    let (sum, allocations) = count_allocations(|| {
+        let mut cache = LookupCache::new();
+        (0..=100)
+            .map(|i| i as f32 / 100.0)
+            .map(|x| clone.lookup(x) + curve.lookup_cached(x, &mut cache))
+            .sum::<f32>()
+    });
+    assert_eq!(allocations, 0, "sampling");
+    assert!(sum > 0.0);
+}
+
+#[test]
+fn unsorted_small_curve_does_not_allocate() {
+    let [a, b, c] = bezier_knots();
+    let (curve, allocations) = count_allocations(|| LookupCurve::from([c, a, b]));
+    assert_eq!(allocations, 0);
+    assert_eq!(curve.knots()[0].position.x, 0.0);
+    assert_eq!(curve.knots()[2].position.x, 1.0);
+}
+
+#[test]
+fn large_curve_reuses_vec() {
+    let knots: Vec<Knot> = (0..100)
+        .map(|i| Knot {
+            position: Vec2::new(i as f32, 0.0),
+            ..Default::default()
+        })
+        .collect();
+    let (curve, allocations) = count_allocations(|| LookupCurve::new(knots));
+    assert_eq!(allocations, 0);
+    assert_eq!(curve.knots().len(), 100);
+}
@@ -0,0 +1,77 @@
+//! Loads curve assets from disk in a headless app, through the asset server.
+#![cfg(feature = "bevy_asset")]
+
+use std::time::{Duration, Instant};
+
+use bevy::asset::{AssetApp, AssetPlugin, AssetServer, Assets, LoadState};
+use bevy::prelude::{App, MinimalPlugins};
+use bevy_lookup_curve::asset::LookupCurveAssetLoader;
+use bevy_lookup_curve::{parse_curve_ron, LookupCurve};
+
+const FIXTURE: &[u8] = include_bytes!("../assets/example.curve.ron");
+
+/// Updates the app until `done` returns `true`, panicking after a few seconds
+fn update_until(app: &mut App, mut done: impl FnMut(&mut App) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done(app) {
+        assert!(Instant::now() < deadline, "timed out");
+        app.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn loads_and_samples_curve_assets() {
+    let dir = std::env::temp_dir().join(format!("bevy_lookup_curve_assets_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("speed.curve.ron"), FIXTURE).unwrap();
+    std::fs::write(dir.join("speed.lookupcurve.ron"), FIXTURE).unwrap();
+    let out_of_range =
+        String::from_utf8(FIXTURE.to_vec())
+            .unwrap()
+            .replacen("(1.0, 1.0)", "(inf, 1.0)", 1);
+    std::fs::write(dir.join("broken.curve.ron"), out_of_range).unwrap();
+
+    // Same registration as LookupCurvePlugin, without the editor that needs a window
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: dir.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+    ))
+    .init_asset::<LookupCurve>()
+    .register_asset_loader(LookupCurveAssetLoader);
+
+    let server = app.world().resource::<AssetServer>().clone();
+    let handles = [
+        server.load::<LookupCurve>("speed.curve.ron"),
+        server.load::<LookupCurve>("speed.lookupcurve.ron"),
+    ];
+    let broken = server.load::<LookupCurve>("broken.curve.ron");
+    update_until(&mut app, |app| {
+        let curves = app.world().resource::<Assets<LookupCurve>>();
+        handles.iter().all(|handle| curves.contains(handle))
+            && matches!(server.load_state(&broken), LoadState::Failed(_))
+    });
+
+    let expected = parse_curve_ron(FIXTURE).unwrap();
+    let curves = app.world().resource::<Assets<LookupCurve>>();
+    for handle in &handles {
+        let curve = curves.get(handle).unwrap();
+        for i in 0..=100 {
+            let x = i as f32 / 100.0;
+            assert_eq!(curve.lookup(x), expected.lookup(x));
+        }
+    }
+
+    let LoadState::Failed(error) = server.load_state(&broken) else {
+        unreachable!();
+    };
+    let message = error.to_string();
+    assert!(message.contains("broken.curve.ron"), "{message}");
+    assert!(message.contains("knot 2"), "{message}");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,92 @@
+//! An editor process talking to a headless game app over the curve IPC channel, with the client in a thread.
+#![cfg(all(feature = "ipc", feature = "bevy_asset"))]
+
+use std::net::SocketAddr;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use bevy::asset::{AssetApp, AssetPlugin, Assets};
+use bevy::prelude::{App, MinimalPlugins};
+use bevy_lookup_curve::ipc::{
+    CurveDelta, CurveIpcClient, CurveIpcHost, CurveIpcHostPlugin, IpcEvent,
+};
+use bevy_lookup_curve::{Knot, KnotInterpolation, LookupCurve};
+use bevy_math::Vec2;
+
+fn knot(x: f32, y: f32) -> Knot {
+    Knot {
+        position: Vec2::new(x, y),
+        interpolation: KnotInterpolation::Linear,
+        ..Default::default()
+    }
+}
+
+/// Updates the app until `done` returns `true`, panicking after a few seconds
+fn update_until(app: &mut App, mut done: impl FnMut(&mut App) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done(app) {
+        assert!(Instant::now() < deadline, "timed out");
+        app.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn pushed_deltas_and_host_changes_reach_the_other_side() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<LookupCurve>()
+        .add_plugins(CurveIpcHostPlugin {
+            address: SocketAddr::from(([127, 0, 0, 1], 0)),
+        });
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<LookupCurve>>()
+        .add(LookupCurve::new(vec![knot(0.0, 0.0), knot(1.0, 1.0)]));
+    let mut host = app.world_mut().resource_mut::<CurveIpcHost>();
+    host.expose("speed", handle.clone());
+    let address = host.local_addr().unwrap();
+
+    let (knot_counts, received) = mpsc::channel();
+    let client = std::thread::spawn(move || {
+        let mut client = CurveIpcClient::connect(address).unwrap();
+        assert_eq!(client.keys(), ["speed"]);
+        assert_eq!(client.fetch("speed").unwrap().knots().len(), 2);
+        client
+            .push("speed", CurveDelta::AddKnot(knot(2.0, 0.5)))
+            .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            for event in client.poll() {
+                if let IpcEvent::Changed { curve, .. } = event {
+                    if knot_counts.send(curve.knots().len()).is_err() {
+                        return;
+                    }
+                }
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    });
+
+    // The pushed delta is applied to the asset
+    update_until(&mut app, |app| {
+        let curves = app.world().resource::<Assets<LookupCurve>>();
+        curves.get(&handle).unwrap().knots().len() == 3
+    });
+    let curves = app.world().resource::<Assets<LookupCurve>>();
+    assert_eq!(
+        curves.get(&handle).unwrap().knots()[2].position,
+        Vec2::new(2.0, 0.5)
+    );
+
+    // A change made in the game is sent to the client
+    app.world_mut()
+        .resource_mut::<Assets<LookupCurve>>()
+        .get_mut(&handle)
+        .unwrap()
+        .add_knot(knot(3.0, 1.0));
+    update_until(&mut app, |_| received.try_iter().any(|count| count == 4));
+
+    drop(received);
+    client.join().unwrap();
+}
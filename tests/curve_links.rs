@@ -0,0 +1,128 @@
+//! Keeps derived curve assets in sync with their primary curve in a headless app.
+#![cfg(feature = "bevy_asset")]
+
+use bevy::asset::{AssetApp, AssetPlugin, Assets, Handle};
+use bevy::prelude::{App, MinimalPlugins, PostUpdate};
+use bevy_lookup_curve::link::{
+    update_linked_curves, CurveLink, CurveLinks, LinkError, LinkRelation,
+};
+use bevy_lookup_curve::{Knot, KnotInterpolation, LookupCurve};
+use bevy_math::Vec2;
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<LookupCurve>()
+        .init_resource::<CurveLinks>()
+        .add_systems(PostUpdate, update_linked_curves);
+    app
+}
+
+fn curve(knots: &[(f32, f32)]) -> LookupCurve {
+    LookupCurve::new(
+        knots
+            .iter()
+            .map(|&(x, y)| Knot {
+                position: Vec2::new(x, y),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn add_curve(app: &mut App, knots: &[(f32, f32)]) -> Handle<LookupCurve> {
+    app.world_mut()
+        .resource_mut::<Assets<LookupCurve>>()
+        .add(curve(knots))
+}
+
+fn link(
+    app: &mut App,
+    primary: &Handle<LookupCurve>,
+    derived: &Handle<LookupCurve>,
+    relation: LinkRelation,
+) -> Result<(), LinkError> {
+    app.world_mut()
+        .resource_mut::<CurveLinks>()
+        .link(CurveLink {
+            primary: primary.clone(),
+            derived: derived.clone(),
+            relation,
+        })
+}
+
+#[test]
+fn derived_curves_follow_their_primary() {
+    let mut app = app();
+    let primary = add_curve(&mut app, &[(0.0, 0.0), (1.0, 2.0)]);
+    let inverse = add_curve(&mut app, &[]);
+    let scaled = add_curve(&mut app, &[]);
+    let tolerance = 1e-3;
+    link(
+        &mut app,
+        &primary,
+        &inverse,
+        LinkRelation::Inverse { tolerance },
+    )
+    .unwrap();
+    // Derived from a derived curve
+    link(
+        &mut app,
+        &inverse,
+        &scaled,
+        LinkRelation::Scaled { x: 2.0, y: 1.0 },
+    )
+    .unwrap();
+
+    for knots in [[(0.0, 0.0), (1.0, 2.0)], [(0.0, 1.0), (2.0, 3.0)]] {
+        app.world_mut()
+            .resource_mut::<Assets<LookupCurve>>()
+            .insert(&primary, curve(&knots));
+        app.update();
+
+        let curves = app.world().resource::<Assets<LookupCurve>>();
+        let (primary, inverse, scaled) = (
+            curves.get(&primary).unwrap(),
+            curves.get(&inverse).unwrap(),
+            curves.get(&scaled).unwrap(),
+        );
+        for i in 0..=10 {
+            let x = knots[0].0 + (knots[1].0 - knots[0].0) * i as f32 / 10.0;
+            let y = primary.lookup(x);
+            assert!((inverse.lookup(y) - x).abs() < 1e-2, "{x}");
+            assert!((scaled.lookup(y * 2.0) - x).abs() < 1e-2, "{x}");
+        }
+    }
+}
+
+#[test]
+fn invalid_links_are_rejected() {
+    let mut app = app();
+    let a = add_curve(&mut app, &[(0.0, 0.0), (1.0, 1.0)]);
+    let b = add_curve(&mut app, &[]);
+    let c = add_curve(&mut app, &[]);
+    let mirrored = LinkRelation::Mirrored { axis_x: 0.0 };
+
+    assert_eq!(link(&mut app, &a, &a, mirrored), Err(LinkError::SelfLink));
+    link(&mut app, &a, &b, mirrored).unwrap();
+    link(&mut app, &b, &c, mirrored).unwrap();
+    assert_eq!(link(&mut app, &c, &a, mirrored), Err(LinkError::Cycle));
+    assert_eq!(
+        link(&mut app, &c, &b, mirrored),
+        Err(LinkError::AlreadyDerived)
+    );
+
+    let detached = app.world_mut().resource_mut::<CurveLinks>().detach(&b);
+    assert!(detached.is_some());
+    assert!(app
+        .world()
+        .resource::<CurveLinks>()
+        .derived_by(&b)
+        .is_none());
+    assert!(app
+        .world()
+        .resource::<CurveLinks>()
+        .derived_by(&c)
+        .is_some());
+}
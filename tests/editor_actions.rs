@@ -0,0 +1,195 @@
+//! Scripts editing sessions through [EditorAction]s, without a window or pointer input.
+#![cfg(feature = "editor_egui")]
+
+use bevy_lookup_curve::editor::{
+    ActionError, ActionOutcome, CurveEditResponse, EditorAction, LookupCurveEguiEditor,
+};
+use bevy_lookup_curve::{KnotInterpolation, LookupCurve, TangentMode, TangentSide};
+use bevy_math::Vec2;
+
+/// Shows the editor for one frame, returns `true` if the curve was changed
+fn run_frame(editor: &mut LookupCurveEguiEditor, curve: &mut LookupCurve) -> bool {
+    let mut ctx = egui::Context::default();
+    let mut changed = false;
+    let _ = ctx.clone().run(egui::RawInput::default(), |_| {
+        changed = editor.ui_window(&mut ctx, "editor", curve, None);
+    });
+    changed
+}
+
+/// Shows the editor for one frame in a panel, like an editor embedded in a layout of the application
+fn run_embedded(editor: &mut LookupCurveEguiEditor, curve: &mut LookupCurve) -> CurveEditResponse {
+    let ctx = egui::Context::default();
+    let mut response = None;
+    let _ = ctx.run(egui::RawInput::default(), |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            response = Some(editor.ui(ui, curve, None));
+        });
+    });
+    response.unwrap()
+}
+
+fn add_knot(editor: &mut LookupCurveEguiEditor, curve: &mut LookupCurve, x: f32, y: f32) -> usize {
+    let outcome = editor.apply_action(
+        EditorAction::AddKnot {
+            position: Vec2::new(x, y),
+            interpolation: KnotInterpolation::Linear,
+        },
+        curve,
+    );
+    match outcome {
+        Ok(ActionOutcome::KnotAdded(id)) => id,
+        other => panic!("expected a knot to be added, got {other:?}"),
+    }
+}
+
+#[test]
+fn scripted_session() {
+    let mut editor = LookupCurveEguiEditor::default();
+    editor.round_new_positions_to = Some(0.25);
+    let mut curve = LookupCurve::default();
+
+    let a = add_knot(&mut editor, &mut curve, 0.0, 0.0);
+    let b = add_knot(&mut editor, &mut curve, 0.49, 0.26);
+    let c = add_knot(&mut editor, &mut curve, 1.0, 1.0);
+    assert_eq!(curve.knots()[1].position, Vec2::new(0.5, 0.25));
+
+    editor.pending_actions = vec![
+        EditorAction::MoveKnot {
+            id: b,
+            to: Vec2::new(0.5, 0.5),
+        },
+        EditorAction::SetInterpolation {
+            id: b,
+            interpolation: KnotInterpolation::Cubic,
+        },
+        EditorAction::SetTangentMode {
+            id: b,
+            side: TangentSide::Right,
+            mode: TangentMode::Free,
+        },
+        EditorAction::SetTangentSlope {
+            id: b,
+            side: TangentSide::Right,
+            slope: 2.0,
+        },
+        EditorAction::SetTangentWeight {
+            id: b,
+            side: TangentSide::Right,
+            weight: Some(0.5),
+        },
+        // Fails, but does not stop the following actions
+        EditorAction::DeleteKnot { id: usize::MAX },
+        EditorAction::DeleteKnot { id: a },
+        EditorAction::FitToCurve,
+        EditorAction::SetEditScope(Some(0.4..=0.6)),
+    ];
+    assert!(run_frame(&mut editor, &mut curve));
+    assert!(editor.pending_actions.is_empty());
+
+    let knots = curve.knots();
+    assert_eq!(knots.len(), 2);
+    assert_eq!((knots[0].id, knots[1].id), (b, c));
+    let knot = knots[0];
+    assert_eq!(knot.position, Vec2::new(0.5, 0.5));
+    assert!(matches!(knot.interpolation, KnotInterpolation::Cubic));
+    assert_eq!(knot.right_tangent.slope, 2.0);
+    assert_eq!(knot.right_tangent.weight, Some(0.5));
+    // Only the right tangent is free, so setting the slope did not align the left tangent
+    assert_eq!(knot.left_tangent.slope, 0.0);
+
+    assert_eq!(editor.edit_scope, Some(0.4..=0.6));
+    // Fitted to the knots from (0.5, 0.5) to (1, 1)
+    assert!((editor.offset - Vec2::new(0.4, 0.4)).length() < 1e-6);
+    assert!((editor.scale - Vec2::new(0.7, 0.7)).length() < 1e-6);
+    assert!(editor.is_dirty(&curve));
+
+    // The last knot is now outside of the edit scope
+    assert!(matches!(
+        editor.apply_action(EditorAction::DeleteKnot { id: c }, &mut curve),
+        Err(ActionError::OutOfScope(id)) if id == c
+    ));
+
+    // Nothing queued, nothing changes
+    assert!(!run_frame(&mut editor, &mut curve));
+}
+
+#[test]
+fn embedded_editor_reports_changes() {
+    let mut editor = LookupCurveEguiEditor::default();
+    let mut curve = LookupCurve::default();
+    let first = run_embedded(&mut editor, &mut curve);
+    assert!(!first.changed && !first.dirty && !first.saved);
+    assert!(first.response.rect.width() > 0.0);
+
+    let id = add_knot(&mut editor, &mut curve, 0.0, 1.0);
+    editor.pending_actions = vec![EditorAction::MoveKnot {
+        id,
+        to: Vec2::new(0.5, 2.0),
+    }];
+    let moved = run_embedded(&mut editor, &mut curve);
+    assert!(moved.changed && moved.dirty && !moved.saved);
+
+    // Unchanged frames stay dirty until the caller saves
+    let unchanged = run_embedded(&mut editor, &mut curve);
+    assert!(!unchanged.changed && unchanged.dirty);
+    editor.mark_saved(&curve);
+    assert!(!run_embedded(&mut editor, &mut curve).dirty);
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn scripted_save() {
+    let path = std::env::temp_dir().join(format!(
+        "bevy_lookup_curve_scripted_save_{}.curve.ron",
+        std::process::id()
+    ));
+    let mut editor = LookupCurveEguiEditor::default();
+    let mut curve = LookupCurve::default();
+    assert!(matches!(
+        editor.apply_action(EditorAction::Save, &mut curve),
+        Err(ActionError::NoSavePath)
+    ));
+
+    editor.ron_path = Some(path.to_str().unwrap().to_string());
+    let id = add_knot(&mut editor, &mut curve, 0.0, 1.0);
+    editor.pending_actions = vec![
+        EditorAction::MoveKnot {
+            id,
+            to: Vec2::new(0.25, 2.0),
+        },
+        EditorAction::Save,
+    ];
+    let response = run_embedded(&mut editor, &mut curve);
+    assert!(response.changed && response.saved && !response.dirty);
+    assert!(!editor.is_dirty(&curve));
+
+    let saved = LookupCurve::load_from_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(saved.knots().len(), 1);
+    assert_eq!(saved.knots()[0].position, Vec2::new(0.25, 2.0));
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn saving_an_unchanged_curve_reports_saved() {
+    let path = std::env::temp_dir().join(format!(
+        "bevy_lookup_curve_unchanged_save_{}.curve.ron",
+        std::process::id()
+    ));
+    let mut editor = LookupCurveEguiEditor::with_save_path(path.to_str().unwrap().to_string());
+    let mut curve = LookupCurve::default();
+    add_knot(&mut editor, &mut curve, 0.0, 1.0);
+    let first = run_embedded(&mut editor, &mut curve);
+    assert!(!first.saved && !first.dirty);
+
+    // Saving again without edits writes the file, while the generation stays the same
+    for _ in 0..2 {
+        editor.pending_actions = vec![EditorAction::Save];
+        let response = run_embedded(&mut editor, &mut curve);
+        assert!(!response.changed && response.saved && !response.dirty);
+        assert!(!run_embedded(&mut editor, &mut curve).saved);
+    }
+    assert!(path.exists());
+    std::fs::remove_file(&path).unwrap();
+}
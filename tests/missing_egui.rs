@@ -0,0 +1,102 @@
+//! Apps with lookup curve editors and without the `EguiPlugin` they need.
+#![cfg(feature = "editor_bevy")]
+
+use std::sync::{Arc, Mutex};
+
+use bevy::asset::{AssetApp, AssetPlugin, Assets};
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::{App, MinimalPlugins, Plugin, Update};
+use bevy::render::render_resource::Shader;
+use bevy::utils::tracing::{Event, Level, Subscriber};
+use bevy_egui::{EguiPlugin, EguiUserTextures};
+use bevy_lookup_curve::editor::LookupCurveEditor;
+use bevy_lookup_curve::{LookupCurve, LookupCurvePlugin};
+use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+/// Counts the warnings that mention `EguiPlugin`
+#[derive(Clone, Default)]
+struct WarningCounter(Arc<Mutex<usize>>);
+
+impl<S: Subscriber> Layer<S> for WarningCounter {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        struct Message(bool);
+        impl bevy::utils::tracing::field::Visit for Message {
+            fn record_debug(
+                &mut self,
+                _: &bevy::utils::tracing::field::Field,
+                value: &dyn std::fmt::Debug,
+            ) {
+                self.0 |= format!("{value:?}").contains("EguiPlugin");
+            }
+        }
+        let mut message = Message(false);
+        event.record(&mut message);
+        if *event.metadata().level() == Level::WARN && message.0 {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+}
+
+fn app(plugin: impl Plugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        // Loaded by the render part of EguiPlugin
+        .init_asset::<Shader>()
+        .add_plugins(plugin);
+    // Logs are collected on this thread
+    app.edit_schedule(Update, |schedule| {
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    });
+    app
+}
+
+#[test]
+fn editors_are_inert_without_egui() {
+    let counter = WarningCounter::default();
+    let _guard = tracing_subscriber::registry()
+        .with(counter.clone())
+        .set_default();
+
+    let mut app = app(LookupCurvePlugin.add_egui(false));
+    app.finish();
+    app.cleanup();
+    assert!(app.is_plugin_added::<LookupCurvePlugin>());
+    assert!(!app.is_plugin_added::<EguiPlugin>());
+    app.update();
+    assert_eq!(*counter.0.lock().unwrap(), 0, "no editor yet");
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<LookupCurve>>()
+        .add(LookupCurve::default());
+    for _ in 0..2 {
+        app.world_mut()
+            .spawn(LookupCurveEditor::new(handle.clone()));
+        for _ in 0..3 {
+            app.update();
+        }
+    }
+    assert_eq!(*counter.0.lock().unwrap(), 1);
+}
+
+#[test]
+fn adds_egui_by_default() {
+    let app = app(LookupCurvePlugin);
+    assert!(app.is_plugin_added::<EguiPlugin>());
+    // The editor systems run when the resources of EguiPlugin exist
+    assert!(app.world().contains_resource::<EguiUserTextures>());
+}
+
+#[test]
+#[should_panic(expected = "EguiPlugin")]
+fn requiring_egui_fails_on_startup() {
+    let mut app = app(LookupCurvePlugin.add_egui(false).require_egui(true));
+    app.finish();
+}
+
+#[test]
+#[should_panic(expected = "EguiPlugin")]
+fn requiring_egui_does_not_add_it() {
+    let mut app = app(LookupCurvePlugin.require_egui(true));
+    app.finish();
+}
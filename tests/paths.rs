@@ -0,0 +1,47 @@
+//! Items are reachable through the prelude, their named modules and the paths used before the modules existed.
+
+#[test]
+fn prelude() {
+    use bevy_lookup_curve::prelude::*;
+    use bevy_math::Vec2;
+
+    let curve = LookupCurve::new(vec![
+        Knot::default(),
+        Knot {
+            position: Vec2::ONE,
+            interpolation: KnotInterpolation::Linear,
+            ..Default::default()
+        }
+        .with_tangent_mode(TangentSide::Left, TangentMode::Free),
+    ]);
+    assert_eq!(curve.lookup_cached(0.5, &mut LookupCache::new()), 0.5);
+}
+
+#[test]
+fn named_modules_and_old_paths_are_the_same_items() {
+    use bevy_lookup_curve::{curve, sample};
+
+    let curve: curve::LookupCurve = bevy_lookup_curve::LookupCurve::from([
+        curve::Knot::default(),
+        bevy_lookup_curve::Knot {
+            position: bevy_math::Vec2::ONE,
+            ..Default::default()
+        },
+    ]);
+    let _: bevy_lookup_curve::LookupCache = sample::LookupCache::new();
+    let _: bevy_lookup_curve::UnitDisplay = curve::UnitDisplay::default();
+
+    #[allow(deprecated)]
+    fn search_old_path(knots: &[bevy_lookup_curve::Knot], x: f32) -> usize {
+        use bevy_lookup_curve::knot_search::KnotSearch;
+        knots.search_knots(x)
+    }
+    fn search(knots: &[bevy_lookup_curve::Knot], x: f32) -> usize {
+        use bevy_lookup_curve::sample::KnotSearch;
+        knots.search_knots(x)
+    }
+    assert_eq!(
+        search_old_path(curve.knots(), 0.5),
+        search(curve.knots(), 0.5)
+    );
+}
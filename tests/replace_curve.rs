@@ -0,0 +1,106 @@
+//! Replaces the contents of a curve asset while an editor is open on it, in a headless app.
+#![cfg(feature = "editor_bevy")]
+
+use bevy::asset::{AssetApp, AssetEvent, AssetPlugin, Assets, Handle};
+use bevy::ecs::event::{Events, ManualEventReader};
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::{App, Entity, MinimalPlugins, ResMut};
+use bevy_lookup_curve::editor::LookupCurveEditor;
+use bevy_lookup_curve::{Knot, KnotInterpolation, LookupCurve, PreserveOptions};
+use bevy_math::Vec2;
+
+fn curve(knots: &[(f32, f32)]) -> LookupCurve {
+    LookupCurve::new(
+        knots
+            .iter()
+            .map(|&(x, y)| Knot {
+                position: Vec2::new(x, y),
+                interpolation: KnotInterpolation::Cubic,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[test]
+fn replace_keeps_the_editor_selection() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<LookupCurve>();
+    let handle: Handle<LookupCurve> = app
+        .world_mut()
+        .resource_mut::<Assets<LookupCurve>>()
+        .add(curve(&[(0.0, 0.0), (0.5, 1.0), (1.0, 0.0)]).with_name("jump"));
+    let selected = app
+        .world()
+        .resource::<Assets<LookupCurve>>()
+        .get(&handle)
+        .unwrap()
+        .knots()[1]
+        .id;
+    let mut editor = LookupCurveEditor::new(handle.clone());
+    editor.egui_editor.selected_knots = vec![selected];
+    let entity: Entity = app.world_mut().spawn(editor).id();
+    app.update();
+
+    let mut reader = ManualEventReader::<AssetEvent<LookupCurve>>::default();
+    let modified = |app: &App, reader: &mut ManualEventReader<AssetEvent<LookupCurve>>| {
+        let events = app.world().resource::<Events<AssetEvent<LookupCurve>>>();
+        reader
+            .read(events)
+            .filter(|event| event.is_modified(&handle))
+            .count()
+    };
+    modified(&app, &mut reader);
+    let generation = app
+        .world()
+        .resource::<Assets<LookupCurve>>()
+        .get(&handle)
+        .unwrap()
+        .generation();
+
+    let preset = curve(&[(0.0, 0.2), (0.45, 1.4), (0.8, 0.6), (1.0, 0.0)]).with_name("preset");
+    let target = handle.clone();
+    app.world_mut()
+        .run_system_once(move |mut curves: ResMut<Assets<LookupCurve>>| {
+            let preserve = PreserveOptions {
+                metadata: true,
+                match_ids: true,
+                ..Default::default()
+            };
+            curves
+                .get_mut(&target)
+                .unwrap()
+                .replace_with(&preset, preserve);
+        });
+    app.update();
+
+    assert_eq!(modified(&app, &mut reader), 1);
+    let asset = app
+        .world()
+        .resource::<Assets<LookupCurve>>()
+        .get(&handle)
+        .unwrap()
+        .clone();
+    assert_eq!(asset.generation(), generation + 1);
+    assert_eq!(asset.name.as_deref(), Some("jump"));
+    assert_eq!(asset.knots().len(), 4);
+    let i = asset
+        .knot_index_of(selected)
+        .expect("the selected knot is kept");
+    assert_eq!(asset.knots()[i].position, Vec2::new(0.45, 1.4));
+
+    // The editor shows the replaced curve with the knot still selected
+    let mut editor = app
+        .world_mut()
+        .get_mut::<LookupCurveEditor>(entity)
+        .unwrap();
+    let mut shown = asset;
+    let mut ctx = egui::Context::default();
+    let _ = ctx.clone().run(egui::RawInput::default(), |_| {
+        editor
+            .egui_editor
+            .ui_window(&mut ctx, "editor", &mut shown, None);
+    });
+    assert_eq!(editor.egui_editor.selected_knots, [selected]);
+}